@@ -0,0 +1,55 @@
+use std::io::Read;
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::server::DebuggableServer;
+
+const CLIENTS: usize = 4;
+const DEBUGGABLES: usize = 100;
+
+fn change_all(debuggables: &mut [Debuggable<u64>], counter: u64) {
+    debuggables.iter_mut().for_each(|debuggable| {
+        *debuggable.borrow_mut() = counter;
+        drop(debuggable.borrow());
+    });
+}
+
+fn notify_changed_debuggables(criterion: &mut Criterion) {
+    let server = ScopedServer::new();
+    let mut clients = (0..CLIENTS)
+        .map(|_| {
+            let client = TcpStream::connect(server.addr()).unwrap();
+            client.set_nonblocking(true).unwrap();
+            client
+        })
+        .collect::<Vec<_>>();
+    let mut debuggables = (0..DEBUGGABLES)
+        .map(|index| DebuggableBuilder::new(format!("counter_{index}"), 0_u64).scoped(&server).build())
+        .collect::<Vec<_>>();
+    thread::sleep(Duration::from_millis(50));
+    let mut drain = vec![0_u8; 64 * 1024];
+    let mut drain_clients = |clients: &mut Vec<TcpStream>| clients.iter_mut().for_each(|client| {
+        while client.read(&mut drain).map(|read| read > 0).unwrap_or(false) {}
+    });
+    let mut counter = 0_u64;
+    criterion.bench_function("notify 100 changed debuggables one by one", |bencher| bencher.iter(|| {
+        counter += 1;
+        change_all(&mut debuggables, counter);
+        drain_clients(&mut clients);
+    }));
+    criterion.bench_function("notify 100 changed debuggables in a batch", |bencher| bencher.iter(|| {
+        counter += 1;
+        let batch = DebuggableServer::begin_batch(&server.handle());
+        change_all(&mut debuggables, counter);
+        drop(batch);
+        drain_clients(&mut clients);
+    }));
+}
+
+criterion_group!(benches, notify_changed_debuggables);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Read;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const CLIENTS: usize = 8;
+const VALUE_SIZE: usize = 64 * 1024;
+
+fn broadcast_large_value(criterion: &mut Criterion) {
+    let server = ScopedServer::new();
+    let mut clients = (0..CLIENTS)
+        .map(|_| {
+            let client = TcpStream::connect(server.addr()).unwrap();
+            client.set_nonblocking(true).unwrap();
+            client
+        })
+        .collect::<Vec<_>>();
+    let mut debuggable = DebuggableBuilder::new("large", String::new()).scoped(&server).build();
+    thread::sleep(Duration::from_millis(50));
+    let mut drain = vec![0_u8; VALUE_SIZE * 2];
+    let mut drain_clients = |clients: &mut Vec<TcpStream>| clients.iter_mut().for_each(|client| {
+        while client.read(&mut drain).map(|read| read > 0).unwrap_or(false) {}
+    });
+    let mut counter = 0_u8;
+    let mut allocations_per_update = Vec::new();
+    criterion.bench_function("broadcast 64 KB value to 8 clients", |bencher| bencher.iter(|| {
+        counter = counter.wrapping_add(1);
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        *debuggable.borrow_mut() = char::from(b'a' + counter % 26).to_string().repeat(VALUE_SIZE);
+        drop(debuggable.borrow());
+        allocations_per_update.push(ALLOCATIONS.load(Ordering::Relaxed) - before);
+        drain_clients(&mut clients);
+    }));
+    let average = allocations_per_update.iter().sum::<usize>() as f64 / allocations_per_update.len().max(1) as f64;
+    println!("Average allocations per broadcast: {average:.1}");
+}
+
+criterion_group!(benches, broadcast_large_value);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde::{Deserialize, Serialize};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+
+#[derive(Debug, Default, Clone, PartialEq, Hash, Serialize, Deserialize)]
+struct FiftyFields {
+    field_0: u64,
+    field_1: u64,
+    field_2: u64,
+    field_3: u64,
+    field_4: u64,
+    field_5: u64,
+    field_6: u64,
+    field_7: u64,
+    field_8: u64,
+    field_9: u64,
+    field_10: u64,
+    field_11: u64,
+    field_12: u64,
+    field_13: u64,
+    field_14: u64,
+    field_15: u64,
+    field_16: u64,
+    field_17: u64,
+    field_18: u64,
+    field_19: u64,
+    field_20: u64,
+    field_21: u64,
+    field_22: u64,
+    field_23: u64,
+    field_24: u64,
+    field_25: u64,
+    field_26: u64,
+    field_27: u64,
+    field_28: u64,
+    field_29: u64,
+    field_30: u64,
+    field_31: u64,
+    field_32: u64,
+    field_33: u64,
+    field_34: u64,
+    field_35: u64,
+    field_36: u64,
+    field_37: u64,
+    field_38: u64,
+    field_39: u64,
+    field_40: u64,
+    field_41: u64,
+    field_42: u64,
+    field_43: u64,
+    field_44: u64,
+    field_45: u64,
+    field_46: u64,
+    field_47: u64,
+    field_48: u64,
+    field_49: u64,
+}
+
+fn deref_unchanged(criterion: &mut Criterion) {
+    let server = ScopedServer::new();
+    let by_json = DebuggableBuilder::new("by_json", FiftyFields::default()).scoped(&server).build();
+    let by_eq = DebuggableBuilder::new("by_eq", FiftyFields::default()).scoped(&server).detect_changes_by_eq().build();
+    let by_hash = DebuggableBuilder::new("by_hash", FiftyFields::default()).scoped(&server).detect_changes_by_hash().build();
+    criterion.bench_function("deref unchanged 50 field struct by json", |bencher| bencher.iter(|| by_json.field_0));
+    criterion.bench_function("deref unchanged 50 field struct by eq", |bencher| bencher.iter(|| by_eq.field_0));
+    criterion.bench_function("deref unchanged 50 field struct by hash", |bencher| bencher.iter(|| by_hash.field_0));
+}
+
+criterion_group!(benches, deref_unchanged);
+criterion_main!(benches);
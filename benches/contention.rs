@@ -0,0 +1,41 @@
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+
+const THREADS: usize = 8;
+const DEREFS_PER_THREAD: u64 = 1_000;
+
+fn deref_distinct_debuggables(criterion: &mut Criterion) {
+    let server = ScopedServer::new();
+    criterion.bench_function("8 threads deref-ing distinct debuggables", |bencher| bencher.iter_custom(|iterations| {
+        let mut total = Duration::ZERO;
+        for _ in 0..iterations {
+            let barrier = Arc::new(Barrier::new(THREADS));
+            let workers = (0..THREADS).map(|thread_index| {
+                let handle = server.handle();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    let mut debuggable = DebuggableBuilder::new(format!("value_{thread_index}"), 0_u64)
+                        .server(Some(handle))
+                        .build();
+                    barrier.wait();
+                    let start = Instant::now();
+                    for value in 0..DEREFS_PER_THREAD {
+                        *debuggable.borrow_mut() = value;
+                    }
+                    start.elapsed()
+                })
+            }).collect::<Vec<_>>();
+            total += workers.into_iter().map(|worker| worker.join().unwrap()).max().unwrap_or_default();
+        }
+        total
+    }));
+}
+
+criterion_group!(benches, deref_distinct_debuggables);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+
+fn deref_hot_counter(criterion: &mut Criterion) {
+    let server = ScopedServer::new();
+    let plain = Box::new(0_u64);
+    let every_access = DebuggableBuilder::new("every_access", 0_u64).scoped(&server).build();
+    let every_1024th = DebuggableBuilder::new("every_1024th", 0_u64).scoped(&server).sample_every(1024).build();
+    let every_10ms = DebuggableBuilder::new("every_10ms", 0_u64).scoped(&server).sample_interval(Duration::from_millis(10)).build();
+    criterion.bench_function("deref plain pointer", |bencher| bencher.iter(|| **black_box(&plain)));
+    criterion.bench_function("deref counter every access", |bencher| bencher.iter(|| **black_box(&every_access)));
+    criterion.bench_function("deref counter every 1024th access", |bencher| bencher.iter(|| **black_box(&every_1024th)));
+    criterion.bench_function("deref counter every 10ms", |bencher| bencher.iter(|| **black_box(&every_10ms)));
+}
+
+criterion_group!(benches, deref_hot_counter);
+criterion_main!(benches);
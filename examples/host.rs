@@ -0,0 +1,55 @@
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use debug_monitor::client::MessageFraming;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::default_server;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Player {
+    name: String,
+    health: u32,
+    position: (f32, f32),
+}
+
+fn main() {
+    let server = default_server::default_server();
+    let (address, framing) = {
+        let server = server.read().unwrap();
+        (server.local_addr().expect("The default server has no address"), MessageFraming::of_server(&server))
+    };
+    let connection_file = std::env::temp_dir().join("debug_monitor_example.json");
+    let connection = serde_json::json!({ "address": address.to_string(), "endmark": framing.endmark, "escape": framing.escape });
+    std::fs::write(&connection_file, connection.to_string()).expect("Could not write the connection file");
+    println!("Serving debuggables on {address}, run `cargo run --example monitor` in another terminal");
+
+    let mut speed = DebuggableBuilder::new("speed", 1.0_f32)
+        .order(-1)
+        .on_remote_update(|speed| println!("Remote edit: speed = {speed}"))
+        .build();
+    let mut paused = DebuggableBuilder::new("paused", false)
+        .order(-1)
+        .on_remote_update(|paused| println!("Remote edit: paused = {paused}"))
+        .build();
+    let mut player = DebuggableBuilder::new("player", Player { name: "Ferris".to_string(), health: 100, position: (0.0, 0.0) })
+        .on_remote_update(|player| println!("Remote edit: player = {player:?}"))
+        .build();
+    let mut frame = DebuggableBuilder::new("frame", 0_u64).build();
+
+    loop {
+        *frame.borrow_mut() += 1;
+        if !*paused.borrow() {
+            let speed = *speed.borrow();
+            player.update(|player| {
+                player.position.0 += speed * 0.1;
+                player.position.1 = (player.position.0).sin();
+            });
+        }
+        // Reading a value is what applies the edits sent by monitors
+        let _ = speed.borrow_mut();
+        let _ = paused.borrow_mut();
+        thread::sleep(Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::BufRead;
+use std::net::SocketAddr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use debug_monitor::client::{DebuggableClient, MessageFraming};
+
+fn main() {
+    let connection_file = std::env::temp_dir().join("debug_monitor_example.json");
+    let connection = std::fs::read_to_string(&connection_file)
+        .expect("Could not read the connection file, start `cargo run --example host` first");
+    let connection: serde_json::Value = serde_json::from_str(&connection).expect("Invalid connection file");
+    let address: SocketAddr = connection["address"].as_str().unwrap().parse().unwrap();
+    let framing = MessageFraming::new(connection["endmark"].as_str().unwrap(), connection["escape"].as_str().unwrap());
+
+    let mut client = DebuggableClient::connect(address, framing).expect("Could not connect to the host");
+    println!("Connected to {address}, type `name=json` to edit a value, e.g. `speed=2.5` or `paused=true`");
+
+    let (edits, incoming_edits) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+            if edits.send(line).is_err() { break; }
+        }
+    });
+
+    loop {
+        let messages = client.poll().expect("Lost the connection to the host");
+        if !messages.is_empty() {
+            print_debuggables(&client);
+        }
+        while let Ok(edit) = incoming_edits.try_recv() {
+            let Some((name, value_json)) = edit.split_once('=') else {
+                println!("Expected `name=json`");
+                continue;
+            };
+            let id = client.sorted_debuggables().into_iter()
+                .find(|(_, debuggable)| debuggable.name == name.trim())
+                .map(|(id, _)| id);
+            match id {
+                None => println!("No debuggable named {}", name.trim()),
+                Some(id) => client.send_update(id, value_json.trim()).expect("Could not send the edit"),
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn print_debuggables(client: &DebuggableClient) {
+    println!("----");
+    client.sorted_debuggables().into_iter().for_each(|(_, debuggable)| {
+        println!("{:>10} = {}", debuggable.name, debuggable.value_in_json);
+    });
+}
@@ -0,0 +1,7 @@
+/// Prints the schema of the wire protocol, for generating the message types of clients written in
+/// other languages.
+///
+/// `cargo run --example print_schema --features schema > protocol_schema.json`
+fn main() {
+    println!("{}", debug_monitor::schema::protocol_schema());
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use debug_monitor::client::{DebuggableClient, MessageFraming};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+
+const DEBUGGABLE_COUNT: usize = 16;
+const RECONNECT_EVERY: Duration = Duration::from_secs(3);
+const REPORT_EVERY: Duration = Duration::from_secs(1);
+
+/// Runs a server with fake clients editing its values and printing its memory_report, so growth
+/// over a long run can be told apart from the application's own.
+///
+/// `cargo run --example soak -- [clients] [seconds]`, 8 clients for 60 seconds by default.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let client_count = args.next().map(|clients| clients.parse().expect("clients must be a number")).unwrap_or(8);
+    let duration = Duration::from_secs(args.next().map(|seconds| seconds.parse().expect("seconds must be a number")).unwrap_or(60));
+
+    let server = ScopedServer::new();
+    let counters = (0..DEBUGGABLE_COUNT)
+        .map(|index| DebuggableBuilder::new(format!("counter_{index}"), 0_u64).scoped(&server).build())
+        .collect::<Vec<_>>();
+    let mut log = DebuggableBuilder::new("log", String::new()).text_diff().scoped(&server).build();
+    let ids = (0..DEBUGGABLE_COUNT)
+        .map(|index| server.handle().read().unwrap().debuggable_id_of(&format!("counter_{index}")).unwrap())
+        .collect::<Vec<_>>();
+    println!("Soaking {} with {client_count} clients for {duration:?}", server.addr());
+
+    let framing = MessageFraming::of_server(&server.handle().read().unwrap());
+    let running = Arc::new(AtomicBool::new(true));
+    let client_threads = (0..client_count).map(|client_index| {
+        let (running, ids, addr, framing) = (running.clone(), ids.clone(), server.addr(), framing.clone());
+        thread::spawn(move || {
+            let mut sent = 0_u64;
+            while running.load(Ordering::Relaxed) {
+                // Reconnecting now and then leaves behind whatever the server fails to forget
+                let Ok(mut client) = DebuggableClient::connect(addr, framing.clone()) else {
+                    thread::sleep(Duration::from_millis(100));
+                    continue;
+                };
+                let connected_at = Instant::now();
+                while running.load(Ordering::Relaxed) && connected_at.elapsed() < RECONNECT_EVERY {
+                    let id = ids[(sent as usize + client_index) % ids.len()];
+                    if client.send_update(id, sent).is_err() || client.poll().is_err() { break; }
+                    sent += 1;
+                    thread::sleep(Duration::from_millis(2));
+                }
+            }
+        })
+    }).collect::<Vec<_>>();
+
+    let started_at = Instant::now();
+    let mut last_report = Instant::now();
+    let mut frame = 0_u64;
+    while started_at.elapsed() < duration {
+        // Reading the values is what applies the updates clients sent
+        counters.iter().for_each(|counter| { let _ = **counter; });
+        frame += 1;
+        if frame % 100 == 0 {
+            log.push_str(&format!("frame {frame}\n"));
+            if log.len() > 16 * 1024 {
+                log.clear();
+            }
+        }
+        if last_report.elapsed() >= REPORT_EVERY {
+            print_report(&server, started_at);
+            last_report = Instant::now();
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    running.store(false, Ordering::Relaxed);
+    client_threads.into_iter().for_each(|client_thread| { let _ = client_thread.join(); });
+    counters.iter().for_each(|counter| { let _ = **counter; });
+    thread::sleep(REPORT_EVERY);
+    println!("Once every client disconnected:");
+    print_report(&server, started_at);
+}
+
+fn print_report(server: &ScopedServer, started_at: Instant) {
+    let report = server.handle().read().unwrap().memory_report();
+    println!(
+        "[{:>5.1}s] values {} B, pending {} updates / {} B, outgoing {} B, {} clients with {} bookkeeping entries",
+        started_at.elapsed().as_secs_f32(),
+        report.last_value_bytes + report.initial_value_bytes,
+        report.pending_updates(),
+        report.pending_bytes(),
+        report.outgoing_queue_bytes,
+        report.clients.connected,
+        report.clients.entries(),
+    );
+}
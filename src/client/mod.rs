@@ -0,0 +1,978 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::mem;
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::client::reconnect::{OfflinePolicy, ReconnectPolicy};
+use crate::protocol;
+use crate::protocol::framing::{self, Endmark};
+use crate::serializable::framing::FramingInfo;
+use crate::serializable::input_limits::InputLimits;
+use crate::serializable::text_patch::{apply_hunks, diff_lines};
+use crate::serializable::{ClientUnitMessage, CompositeKind, GroupedUpdate, JSONDeSerializable, PROTOCOL_VERSION, ServerMessage};
+use crate::server::{DebuggableServer, IncomingTransform, OutgoingTransform};
+
+pub mod reconnect;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageFraming {
+    pub endmark: String,
+    pub escape: String,
+}
+
+impl From<FramingInfo> for MessageFraming {
+    fn from(framing: FramingInfo) -> Self {
+        Self { endmark: framing.endmark, escape: framing.escape }
+    }
+}
+
+/// Framing of servers built without DebuggableServerBuilder::message_endmark.
+impl Default for MessageFraming {
+    fn default() -> Self {
+        Self::new(framing::DEFAULT_ENDMARK, framing::DEFAULT_ESCAPE)
+    }
+}
+
+impl MessageFraming {
+    pub fn new<EndmarkString: ToString, Escape: ToString>(endmark: EndmarkString, escape: Escape) -> Self {
+        Self { endmark: endmark.to_string(), escape: escape.to_string() }
+    }
+
+    pub fn of_server(server: &DebuggableServer) -> Self {
+        Self::from(server.framing_info())
+    }
+
+    pub fn frame(&self, message: &str) -> String {
+        protocol::frame(message, &self.endmark, &self.escape)
+    }
+
+    pub fn unescape(&self, frame: &str) -> String {
+        protocol::unescape(frame, &self.endmark, &self.escape)
+    }
+
+    /// Takes the complete frames out of the received text, see protocol::framing::decode_frames.
+    pub fn decode_frames(&self, received: &mut String) -> Vec<String> {
+        framing::decode_frames(received, &Endmark::new_unchecked(&self.endmark, &self.escape))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteDebuggable {
+    pub name: String,
+    pub value_in_json: String,
+    pub nullable: bool,
+    pub order: i32,
+    pub revision: u64,
+    /// Set while the server only sends a summary of the value, value_in_json then holds the last
+    /// whole value received, if any. See DebuggableClient::request_full_value.
+    pub summary: Option<ValueSummary>,
+    /// Unix time in milliseconds the value last changed at, known only from servers and clients
+    /// speaking CHANGE_INFO_PROTOCOL_VERSION, and only for values received through Notify.
+    pub changed_at_ms: Option<u64>,
+    /// Client whose update set the value, None when the host set it or it isn't known.
+    pub last_author: Option<usize>,
+    /// Never reused by the server unlike the id, so state kept per debuggable can be keyed by it.
+    /// None until an Added or Notify carrying it arrives, or from servers predating it.
+    pub uid: Option<u64>,
+    /// Whether the server only sends the value in answer to request_value, see
+    /// DebuggableBuilder::on_demand.
+    pub on_demand: bool,
+    /// Whether a client's update set the value rather than the host, as last told by Notify.
+    pub overridden: bool,
+}
+
+/// Debuggables the server groups into a single widget, members holds their ids by slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteComposite {
+    pub kind: CompositeKind,
+    pub members: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueSummary {
+    pub byte_len: usize,
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CasOutcome {
+    Applied { revision: u64 },
+    Conflict { current_revision: u64, current_value: String },
+    TimedOut,
+}
+
+/// How the server answered an update sent through DebuggableClient::send_tracked_update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    Accepted,
+    Rejected,
+    /// No answer arrived in time, usually because the debuggable didn't sync.
+    TimedOut,
+}
+
+pub const DEFAULT_UPDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+struct PendingUpdate {
+    debuggable_id: usize,
+    sent_at: Instant,
+    value_json: String,
+    // Value of the mirror before the update was applied optimistically, restored on rejection
+    previous_json: Option<String>,
+}
+
+pub struct DebuggableClient {
+    stream: TcpStream,
+    framing: MessageFraming,
+    received: String,
+    client_id: Option<usize>,
+    protocol_version: u32,
+    debuggables: BTreeMap<usize, RemoteDebuggable>,
+    composites: BTreeMap<String, RemoteComposite>,
+    server_info: Option<ServerInfo>,
+    outgoing_transform: Option<OutgoingTransform>,
+    incoming_transform: Option<IncomingTransform>,
+    address: Option<SocketAddr>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    offline_policy: OfflinePolicy,
+    connection: ConnectionState,
+    replay: Option<BTreeMap<usize, RemoteDebuggable>>,
+    offline_queue: VecDeque<(ClientUnitMessage, Vec<Option<String>>)>,
+    events: Vec<ClientEvent>,
+    next_request_id: u64,
+    next_txn_id: u64,
+    pending_updates: HashMap<u64, PendingUpdate>,
+    update_timeout: Duration,
+    update_outcomes: Vec<(u64, UpdateOutcome)>,
+    rpc_results: HashMap<u64, Result<String, String>>,
+    // Chunks received so far of values requested whole
+    partial_values: HashMap<usize, String>,
+    // Text patches that didn't apply to the mirror, whose values are requested whole after polling
+    stale_text_values: BTreeSet<usize>,
+    panel: Option<String>,
+    optimistic: bool,
+    session_key: Option<String>,
+    // Blobs stored in this client's session, as last sent back by the server or stored locally
+    blobs: BTreeMap<String, String>,
+}
+
+/// Changes in the connection of a client with a reconnect policy, and in its view of the server's
+/// debuggables once it reconnects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientEvent {
+    Disconnected,
+    Reconnecting { attempt: u32 },
+    Connected,
+    /// A debuggable known before disconnecting no longer exists.
+    Removed { id: usize, name: String },
+    /// A debuggable known before disconnecting exists under a new id.
+    Remapped { name: String, old_id: usize, new_id: usize },
+    /// An optimistically applied update was rejected or overwritten by someone else's value, the
+    /// debuggable holds the server's value again.
+    Reverted { id: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectionState {
+    Connected,
+    Disconnected { attempt: u32, next_attempt: Instant },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    pub crate_version: String,
+    pub protocol_version: u32,
+    pub capabilities: Vec<String>,
+    pub debuggable_count: usize,
+    pub framing: Option<FramingInfo>,
+}
+
+impl ServerInfo {
+    /// Whether the server listed the given identifier from serializable::capabilities.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|listed| listed == capability)
+    }
+}
+
+impl Debug for DebuggableClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebuggableClient")
+            .field("stream", &self.stream)
+            .field("framing", &self.framing)
+            .field("received", &self.received)
+            .field("client_id", &self.client_id)
+            .field("protocol_version", &self.protocol_version)
+            .field("debuggables", &self.debuggables)
+            .field("composites", &self.composites)
+            .field("server_info", &self.server_info)
+            .field("has_outgoing_transform", &self.outgoing_transform.is_some())
+            .field("has_incoming_transform", &self.incoming_transform.is_some())
+            .field("address", &self.address)
+            .field("reconnect_policy", &self.reconnect_policy)
+            .field("offline_policy", &self.offline_policy)
+            .field("connection", &self.connection)
+            .field("is_replaying", &self.replay.is_some())
+            .field("offline_queue", &self.offline_queue)
+            .field("events", &self.events)
+            .field("next_request_id", &self.next_request_id)
+            .field("next_txn_id", &self.next_txn_id)
+            .field("pending_updates", &self.pending_updates)
+            .field("update_timeout", &self.update_timeout)
+            .field("update_outcomes", &self.update_outcomes)
+            .field("rpc_results", &self.rpc_results)
+            .field("partial_values", &self.partial_values.iter().map(|(id, partial_value)| (id, partial_value.len())).collect::<Vec<_>>())
+            .field("stale_text_values", &self.stale_text_values)
+            .field("panel", &self.panel)
+            .field("optimistic", &self.optimistic)
+            .field("session_key", &self.session_key)
+            .field("blobs", &self.blobs.iter().map(|(key, data)| (key, data.len())).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl DebuggableClient {
+    pub fn connect(address: SocketAddr, framing: MessageFraming) -> io::Result<Self> {
+        Self::from_stream(TcpStream::connect(address)?, framing)
+    }
+
+    /// Connects to a server built with DebuggableServerBuilder::tls, whose certificate has to chain
+    /// up to the given root and name the IP address connected to.
+    #[cfg(feature = "tls")]
+    pub fn connect_tls(address: SocketAddr, framing: MessageFraming, root_cert_pem: &str) -> io::Result<Self> {
+        Self::from_stream(crate::server::tls::connect_bridged(address, root_cert_pem)?, framing)
+    }
+
+    /// Connects to a server serving the named pipe, see DebuggableServerBuilder::named_pipe. Fails
+    /// with ErrorKind::Unsupported outside of Windows.
+    #[cfg(feature = "windows-pipes")]
+    pub fn connect_named_pipe(name: &str, framing: MessageFraming) -> io::Result<Self> {
+        Self::from_stream(crate::server::named_pipe::connect_bridged(name)?, framing)
+    }
+
+    pub fn from_stream(stream: TcpStream, framing: MessageFraming) -> io::Result<Self> {
+        Self::from_stream_with_transforms(stream, framing, None, None)
+    }
+
+    /// Like from_stream, with the counterparts of the server's transforms already in place for the
+    /// Hello sent on connecting.
+    pub fn from_stream_with_transforms(stream: TcpStream, framing: MessageFraming, outgoing_transform: Option<OutgoingTransform>,
+                                       incoming_transform: Option<IncomingTransform>) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        let address = stream.peer_addr().ok();
+        let mut client = Self {
+            stream,
+            framing,
+            received: String::new(),
+            client_id: None,
+            protocol_version: 1,
+            debuggables: BTreeMap::new(),
+            composites: BTreeMap::new(),
+            server_info: None,
+            outgoing_transform,
+            incoming_transform,
+            address,
+            reconnect_policy: None,
+            offline_policy: OfflinePolicy::FailFast,
+            connection: ConnectionState::Connected,
+            replay: None,
+            offline_queue: VecDeque::new(),
+            events: Vec::new(),
+            next_request_id: 0,
+            next_txn_id: 0,
+            pending_updates: HashMap::new(),
+            update_timeout: DEFAULT_UPDATE_TIMEOUT,
+            update_outcomes: Vec::new(),
+            rpc_results: HashMap::new(),
+            partial_values: HashMap::new(),
+            stale_text_values: BTreeSet::new(),
+            panel: None,
+            optimistic: false,
+            session_key: None,
+            blobs: BTreeMap::new(),
+        };
+        client.write_message(&client.hello())?;
+        Ok(client)
+    }
+
+    fn hello(&self) -> ClientUnitMessage {
+        ClientUnitMessage::Hello { protocol_version: PROTOCOL_VERSION, supports_deflate: cfg!(feature = "compression"), panel: self.panel.clone(), supports_text_patches: true, session_key: self.session_key.clone() }
+    }
+
+    /// Says hello again under the session key, which the server restores the panel, subscriptions
+    /// and blobs of if it remembers it. The key is also given on every reconnection.
+    pub fn resume_session<SessionKey: ToString>(&mut self, session_key: SessionKey) -> io::Result<()> {
+        self.session_key = Some(session_key.to_string());
+        let hello = self.hello();
+        self.send(&hello)
+    }
+
+    pub fn session_key(&self) -> Option<&str> {
+        self.session_key.as_deref()
+    }
+
+    /// Asks to only be sent values of the named debuggables, or of all of them when None.
+    pub fn subscribe(&mut self, names: Option<Vec<String>>) -> io::Result<()> {
+        self.send(&ClientUnitMessage::Subscribe { names })
+    }
+
+    /// Stores the data in this client's session, sent back by the server when the session resumes.
+    pub fn store_blob<Key: ToString, Data: ToString>(&mut self, key: Key, data: Data) -> io::Result<()> {
+        let (key, data) = (key.to_string(), data.to_string());
+        self.send(&ClientUnitMessage::StoreBlob { key: key.clone(), data: data.clone() })?;
+        self.blobs.insert(key, data);
+        Ok(())
+    }
+
+    pub fn blob(&self, key: &str) -> Option<&str> {
+        self.blobs.get(key).map(String::as_str)
+    }
+
+    /// Panel updates sent by this client are attributed to, the server learns it on the next
+    /// handshake and through every update sent from now on.
+    pub fn set_panel(&mut self, panel: Option<String>) {
+        self.panel = panel;
+    }
+
+    /// Reconnects with the given policy when the connection drops instead of failing on poll.
+    pub fn set_reconnect_policy(&mut self, reconnect_policy: Option<ReconnectPolicy>) {
+        self.reconnect_policy = reconnect_policy;
+    }
+
+    pub fn set_offline_policy(&mut self, offline_policy: OfflinePolicy) {
+        self.offline_policy = offline_policy;
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection == ConnectionState::Connected
+    }
+
+    pub fn take_events(&mut self) -> Vec<ClientEvent> {
+        mem::take(&mut self.events)
+    }
+
+    pub fn client_id(&self) -> Option<usize> {
+        self.client_id
+    }
+
+    pub fn protocol_version(&self) -> u32 {
+        self.protocol_version
+    }
+
+    /// Counterpart of the server's incoming transform, applied to every message this client sends
+    /// from now on.
+    pub fn set_outgoing_transform(&mut self, outgoing_transform: Option<OutgoingTransform>) {
+        self.outgoing_transform = outgoing_transform;
+    }
+
+    /// Counterpart of the server's outgoing transform, messages for which it returns None are
+    /// ignored.
+    pub fn set_incoming_transform(&mut self, incoming_transform: Option<IncomingTransform>) {
+        self.incoming_transform = incoming_transform;
+    }
+
+    /// What the server announced about itself when this client connected.
+    pub fn server_info(&self) -> Option<&ServerInfo> {
+        self.server_info.as_ref()
+    }
+
+    pub fn debuggables(&self) -> &BTreeMap<usize, RemoteDebuggable> {
+        &self.debuggables
+    }
+
+    pub fn sorted_debuggables(&self) -> Vec<(usize, &RemoteDebuggable)> {
+        let mut debuggables = self.debuggables.iter().map(|(id, debuggable)| (*id, debuggable)).collect::<Vec<_>>();
+        debuggables.sort_by(|(_, this), (_, other)| this.order.cmp(&other.order).then_with(|| this.name.cmp(&other.name)));
+        debuggables
+    }
+
+    pub fn debuggable(&self, debuggable_id: usize) -> Option<&RemoteDebuggable> {
+        self.debuggables.get(&debuggable_id)
+    }
+
+    pub fn composites(&self) -> &BTreeMap<String, RemoteComposite> {
+        &self.composites
+    }
+
+    pub fn composite(&self, name: &str) -> Option<&RemoteComposite> {
+        self.composites.get(name)
+    }
+
+    pub fn send(&mut self, message: &ClientUnitMessage) -> io::Result<()> {
+        let is_replaying_to_queue = self.replay.is_some() && self.offline_policy != OfflinePolicy::FailFast;
+        if !self.is_connected() || is_replaying_to_queue {
+            return self.send_offline(message);
+        }
+        match self.write_message(message) {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                self.lose_connection(error)?;
+                self.send_offline(message)
+            }
+        }
+    }
+
+    fn send_offline(&mut self, message: &ClientUnitMessage) -> io::Result<()> {
+        let OfflinePolicy::Queue { max_queued } = self.offline_policy else {
+            return Err(io::Error::new(ErrorKind::NotConnected, "Not connected to the debuggable server"));
+        };
+        if self.offline_queue.len() >= max_queued {
+            return Err(io::Error::new(ErrorKind::WouldBlock, "Too many messages are waiting for the debuggable server"));
+        }
+        let known_debuggables = self.replay.as_ref().unwrap_or(&self.debuggables);
+        let target_names = message.edited_ids().into_iter()
+            .map(|id| known_debuggables.get(&id).map(|debuggable| debuggable.name.clone()))
+            .collect();
+        self.offline_queue.push_back((message.clone(), target_names));
+        Ok(())
+    }
+
+    fn write_message(&mut self, message: &ClientUnitMessage) -> io::Result<()> {
+        let message = message.to_json()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "Could not serialize message"))?;
+        let message = match self.outgoing_transform.as_ref() {
+            None => message,
+            Some(outgoing_transform) => outgoing_transform(&message),
+        };
+        let frame = self.framing.frame(&message);
+        self.stream.set_nonblocking(false)?;
+        let written = self.stream.write_all(frame.as_bytes());
+        self.stream.set_nonblocking(true)?;
+        written
+    }
+
+    pub fn send_update<ValueJson: ToString>(&mut self, debuggable_id: usize, value_json: ValueJson) -> io::Result<()> {
+        self.send(&ClientUnitMessage::UpdateValue { id: debuggable_id, new_value: value_json.to_string(), request_id: None, panel: self.panel.clone() })
+    }
+
+    /// Sends an update attributed to the given panel rather than this client's own.
+    pub fn send_panel_update<Panel: ToString, ValueJson: ToString>(&mut self, panel: Panel, debuggable_id: usize, value_json: ValueJson) -> io::Result<()> {
+        self.send(&ClientUnitMessage::UpdateValue { id: debuggable_id, new_value: value_json.to_string(), request_id: None, panel: Some(panel.to_string()) })
+    }
+
+    /// Asks the server to move a numeric debuggable towards the value over the duration.
+    pub fn send_animation<ValueJson: ToString>(&mut self, debuggable_id: usize, target_json: ValueJson, duration: Duration) -> io::Result<()> {
+        let duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        self.send(&ClientUnitMessage::AnimateValue { id: debuggable_id, target_json: target_json.to_string(), duration_ms })
+    }
+
+    /// Asks for the values of the debuggables whose name starts with the prefix.
+    pub fn request_group_snapshot<Prefix: ToString>(&mut self, prefix: Prefix) -> io::Result<()> {
+        self.send(&ClientUnitMessage::GroupSnapshotRequest { prefix: prefix.to_string() })
+    }
+
+    /// Asks to restore the debuggables whose name starts with the prefix to their initial values,
+    /// the outcome arrives as a GroupResult.
+    pub fn reset_group<Prefix: ToString>(&mut self, prefix: Prefix) -> io::Result<()> {
+        self.send(&ClientUnitMessage::GroupReset { prefix: prefix.to_string() })
+    }
+
+    /// Sends an update the server acknowledges once the debuggable processes it, returns the id its
+    /// outcome is reported under in take_update_outcomes.
+    /// When optimistic, the value is applied to the local mirror right away.
+    pub fn send_tracked_update<ValueJson: ToString>(&mut self, debuggable_id: usize, value_json: ValueJson) -> io::Result<u64> {
+        let request_id = self.next_request_id;
+        let value_json = value_json.to_string();
+        self.send(&ClientUnitMessage::UpdateValue { id: debuggable_id, new_value: value_json.clone(), request_id: Some(request_id), panel: self.panel.clone() })?;
+        self.next_request_id += 1;
+        let previous_json = match self.debuggables.get_mut(&debuggable_id) {
+            Some(debuggable) if self.optimistic => Some(mem::replace(&mut debuggable.value_in_json, value_json.clone())),
+            _ => None,
+        };
+        self.pending_updates.insert(request_id, PendingUpdate { debuggable_id, sent_at: Instant::now(), value_json, previous_json });
+        Ok(request_id)
+    }
+
+    /// Applies tracked updates to the local mirror before the server accepts them, rolling them
+    /// back with a ClientEvent::Reverted if they're rejected or someone else's value arrives first.
+    pub fn set_optimistic(&mut self, optimistic: bool) {
+        self.optimistic = optimistic;
+    }
+
+    /// How long tracked updates wait for their acknowledgment before timing out.
+    pub fn set_update_timeout(&mut self, update_timeout: Duration) {
+        self.update_timeout = update_timeout;
+    }
+
+    pub fn is_update_pending(&self, request_id: u64) -> bool {
+        self.pending_updates.contains_key(&request_id)
+    }
+
+    /// Whether a tracked update of the given debuggable is still waiting for its acknowledgment.
+    pub fn has_pending_update(&self, debuggable_id: usize) -> bool {
+        self.pending_updates.values().any(|pending_update| pending_update.debuggable_id == debuggable_id)
+    }
+
+    /// Outcomes of tracked updates answered or timed out since the last call.
+    pub fn take_update_outcomes(&mut self) -> Vec<(u64, UpdateOutcome)> {
+        self.expire_pending_updates();
+        mem::take(&mut self.update_outcomes)
+    }
+
+    fn expire_pending_updates(&mut self) {
+        let update_timeout = self.update_timeout;
+        let timed_out = self.pending_updates.iter()
+            .filter(|(_, pending_update)| pending_update.sent_at.elapsed() > update_timeout)
+            .map(|(request_id, _)| *request_id)
+            .collect::<Vec<_>>();
+        for request_id in timed_out {
+            self.pending_updates.remove(&request_id);
+            self.update_outcomes.push((request_id, UpdateOutcome::TimedOut));
+        }
+    }
+
+    /// Calls the RPC endpoint with the given id, returns the id its result is kept under once it
+    /// arrives, see take_rpc_result.
+    pub fn call_rpc<RequestJson: ToString>(&mut self, endpoint_id: usize, request_json: RequestJson) -> io::Result<u64> {
+        let call_id = self.next_request_id;
+        self.send(&ClientUnitMessage::RpcCall { id: endpoint_id, call_id, request_json: request_json.to_string() })?;
+        self.next_request_id += 1;
+        Ok(call_id)
+    }
+
+    /// The response of the call, or why the server couldn't give one, if it arrived already.
+    pub fn take_rpc_result(&mut self, call_id: u64) -> Option<Result<String, String>> {
+        self.rpc_results.remove(&call_id)
+    }
+
+    pub fn has_rpc_result(&self, call_id: u64) -> bool {
+        self.rpc_results.contains_key(&call_id)
+    }
+
+    /// Asks for the current value of the debuggable. On demand debuggables answer once their owner
+    /// computes it, requests made meanwhile are answered together.
+    pub fn request_value(&mut self, debuggable_id: usize) -> io::Result<()> {
+        self.send(&ClientUnitMessage::RequestValue { id: debuggable_id, full: false })
+    }
+
+    /// Asks for the whole value of a summarized debuggable, which arrives in chunks and replaces
+    /// its value_in_json once complete. Until then the server refuses edits of it from this client.
+    pub fn request_full_value(&mut self, debuggable_id: usize) -> io::Result<()> {
+        self.send(&ClientUnitMessage::RequestValue { id: debuggable_id, full: true })
+    }
+
+    /// Sends updates of several debuggables to be released together once all of them sync.
+    pub fn send_update_group(&mut self, updates: &[(usize, String)]) -> io::Result<()> {
+        let updates = updates.iter()
+            .map(|(id, new_value)| GroupedUpdate { id: *id, new_value: new_value.clone() })
+            .collect();
+        self.send(&ClientUnitMessage::UpdateGroup { updates })
+    }
+
+    /// Sends the new text of a text debuggable as the lines that changed from its value in this
+    /// client's mirror, returning false without sending anything if the mirror doesn't hold a text.
+    /// The server answers with the whole value if the mirror is behind it.
+    pub fn send_text_update(&mut self, debuggable_id: usize, new_text: &str) -> io::Result<bool> {
+        let Some(debuggable) = self.debuggable(debuggable_id) else { return Ok(false); };
+        let Some(base_text) = String::from_json(&debuggable.value_in_json) else { return Ok(false); };
+        let base_revision = debuggable.revision;
+        self.send(&ClientUnitMessage::UpdateTextPatch { id: debuggable_id, base_revision, hunks: diff_lines(&base_text, new_text) })?;
+        Ok(true)
+    }
+
+    /// Opens an edit transaction, returning the id to stage values in, commit and abort it with.
+    pub fn begin_edit(&mut self) -> io::Result<u64> {
+        let txn_id = self.next_txn_id;
+        self.next_txn_id += 1;
+        self.send(&ClientUnitMessage::BeginEdit { txn_id })?;
+        Ok(txn_id)
+    }
+
+    /// Stages the value in the transaction, nobody sees it until the transaction is committed.
+    pub fn stage_value<ValueJson: ToString>(&mut self, txn_id: u64, debuggable_id: usize, value_json: ValueJson) -> io::Result<()> {
+        self.send(&ClientUnitMessage::StageValue { txn_id, id: debuggable_id, new_value: value_json.to_string() })
+    }
+
+    pub fn commit_edit(&mut self, txn_id: u64) -> io::Result<()> {
+        self.send(&ClientUnitMessage::CommitEdit { txn_id })
+    }
+
+    pub fn abort_edit(&mut self, txn_id: u64) -> io::Result<()> {
+        self.send(&ClientUnitMessage::AbortEdit { txn_id })
+    }
+
+    pub fn send_renotify(&mut self) -> io::Result<()> {
+        self.send(&ClientUnitMessage::Renotify)
+    }
+
+    /// Sends a compare-and-swap update against the last revision this client saw and waits for the
+    /// server's verdict. Messages received while waiting are applied as usual.
+    pub fn try_set_cas<ValueJson: ToString>(&mut self, debuggable_id: usize, value_json: ValueJson, timeout: Duration) -> io::Result<CasOutcome> {
+        let expected_revision = self.debuggable(debuggable_id).map(|debuggable| debuggable.revision).unwrap_or_default();
+        self.send(&ClientUnitMessage::UpdateValueCas { id: debuggable_id, expected_revision, new_value: value_json.to_string() })?;
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            for message in self.poll()? {
+                match message {
+                    ServerMessage::CasAccepted { id, revision } if id == debuggable_id =>
+                        return Ok(CasOutcome::Applied { revision }),
+                    ServerMessage::Conflict { id, current_revision, current_value } if id == debuggable_id =>
+                        return Ok(CasOutcome::Conflict { current_revision, current_value }),
+                    _ => {}
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        Ok(CasOutcome::TimedOut)
+    }
+
+    /// Reads whatever the server sent without blocking, applies it to the known debuggables and
+    /// returns the received messages.
+    pub fn poll(&mut self) -> io::Result<Vec<ServerMessage>> {
+        if !self.is_connected() {
+            self.try_reconnect();
+            if !self.is_connected() { return Ok(Vec::new()); }
+        }
+        let mut buffer = [0_u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.lose_connection(io::Error::new(ErrorKind::ConnectionAborted, "The server closed the connection"))?;
+                    return Ok(Vec::new());
+                }
+                Ok(read) => self.received.push_str(&String::from_utf8_lossy(&buffer[..read])),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.lose_connection(error)?;
+                    return Ok(Vec::new());
+                }
+            }
+        }
+        let mut messages = Vec::new();
+        for frame in self.framing.decode_frames(&mut self.received) {
+            let frame = match self.incoming_transform.as_ref().map(|incoming_transform| incoming_transform(&frame)) {
+                None => frame,
+                Some(Some(frame)) => frame,
+                Some(None) => {
+                    log::warn!("Ignoring message from debuggable server rejected by the incoming transform");
+                    continue;
+                }
+            };
+            match InputLimits::default().parse::<ServerMessage>(&frame) {
+                Err(rejection) => log::warn!("Ignoring message from debuggable server: {rejection}"),
+                Ok(message) => {
+                    self.apply(&message);
+                    // The server replays its debuggables on accept, before answering Hello
+                    if matches!(message, ServerMessage::Welcome { .. }) {
+                        self.finish_replay();
+                    }
+                    messages.push(message);
+                }
+            }
+        }
+        while let Some(debuggable_id) = self.stale_text_values.pop_first() {
+            self.send(&ClientUnitMessage::RequestValue { id: debuggable_id, full: false })?;
+        }
+        Ok(messages)
+    }
+
+    /// Fails with the given error unless this client reconnects.
+    fn lose_connection(&mut self, error: io::Error) -> io::Result<()> {
+        if self.reconnect_policy.is_none() || self.address.is_none() { return Err(error); }
+        let _ = self.stream.shutdown(Shutdown::Both);
+        self.connection = ConnectionState::Disconnected { attempt: 0, next_attempt: Instant::now() };
+        self.events.push(ClientEvent::Disconnected);
+        Ok(())
+    }
+
+    fn try_reconnect(&mut self) {
+        let ConnectionState::Disconnected { attempt, next_attempt } = self.connection else { return; };
+        if Instant::now() < next_attempt { return; }
+        let (Some(reconnect_policy), Some(address)) = (self.reconnect_policy, self.address) else { return; };
+        let attempt = attempt + 1;
+        self.events.push(ClientEvent::Reconnecting { attempt });
+        let connect_timeout = reconnect_policy.max_delay.max(Duration::from_millis(1));
+        let stream = TcpStream::connect_timeout(&address, connect_timeout)
+            .and_then(|stream| stream.set_nonblocking(true).map(|_| stream));
+        let Ok(stream) = stream else {
+            self.connection = ConnectionState::Disconnected { attempt, next_attempt: Instant::now() + reconnect_policy.delay_of(attempt) };
+            return;
+        };
+        self.stream = stream;
+        self.received.clear();
+        self.connection = ConnectionState::Connected;
+        if self.replay.is_none() {
+            self.replay = Some(mem::take(&mut self.debuggables));
+        } else {
+            self.debuggables.clear();
+        }
+        self.composites.clear();
+        self.events.push(ClientEvent::Connected);
+        let handshake = self.write_message(&self.hello())
+            .and_then(|_| self.write_message(&ClientUnitMessage::Renotify));
+        if let Err(error) = handshake {
+            let _ = self.lose_connection(error);
+        }
+    }
+
+    /// Compares the debuggables known before disconnecting with the replayed ones and sends the
+    /// messages queued meanwhile, remapping updates to the debuggable with the same name.
+    fn finish_replay(&mut self) {
+        let Some(previous_debuggables) = self.replay.take() else { return; };
+        let ids_by_name = self.debuggables.iter()
+            .map(|(id, debuggable)| (debuggable.name.clone(), *id))
+            .collect::<HashMap<_, _>>();
+        for (old_id, debuggable) in previous_debuggables {
+            match ids_by_name.get(&debuggable.name) {
+                None => self.events.push(ClientEvent::Removed { id: old_id, name: debuggable.name }),
+                Some(new_id) if *new_id != old_id => self.events.push(ClientEvent::Remapped { name: debuggable.name, old_id, new_id: *new_id }),
+                Some(_) => {}
+            }
+        }
+        while let Some((message, target_names)) = self.offline_queue.pop_front() {
+            let Some(message) = remapped_by_name(message, &target_names, &ids_by_name) else { continue; };
+            if let Err(error) = self.write_message(&message) {
+                self.offline_queue.push_front((message, Vec::new()));
+                let _ = self.lose_connection(error);
+                return;
+            }
+        }
+    }
+
+    fn apply(&mut self, message: &ServerMessage) {
+        match message {
+            ServerMessage::ServerInfo { crate_version, protocol_version, capabilities, debuggable_count, framing } => {
+                self.server_info = Some(ServerInfo {
+                    crate_version: crate_version.clone(),
+                    protocol_version: *protocol_version,
+                    capabilities: capabilities.clone(),
+                    debuggable_count: *debuggable_count,
+                    framing: framing.clone(),
+                });
+            }
+            ServerMessage::GiveClientId { client_id } => self.client_id = Some(*client_id),
+            ServerMessage::Welcome { client_id, protocol_version } => {
+                self.client_id = Some(*client_id);
+                self.protocol_version = *protocol_version;
+            }
+            ServerMessage::Notify { id, name, value_in_json, revision, changed_at_ms, author, uid, overridden } => {
+                if !self.set_value(*id, name, value_in_json.clone(), *revision) { return; }
+                if let Some(debuggable) = self.debuggables.get_mut(id) {
+                    debuggable.changed_at_ms = *changed_at_ms;
+                    debuggable.last_author = *author;
+                    debuggable.uid = uid.or(debuggable.uid);
+                    debuggable.overridden = *overridden;
+                }
+            }
+            ServerMessage::NotifyMany { notifies } => notifies.iter().for_each(|notify| {
+                if !self.set_value(notify.id, &notify.name, notify.value_in_json.clone(), notify.revision) { return; }
+                if let Some(debuggable) = self.debuggables.get_mut(&notify.id) {
+                    debuggable.overridden = notify.overridden;
+                }
+            }),
+            #[cfg(feature = "compression")]
+            ServerMessage::NotifyEncoded { id, name, encoding, value_in_json, revision } => {
+                match crate::server::compression::decompress(encoding, value_in_json) {
+                    None => log::warn!("Could not decode value of debuggable {name} with encoding {encoding}"),
+                    Some(value_in_json) => { self.set_value(*id, name, value_in_json, *revision); }
+                }
+            }
+            ServerMessage::NotifyTextPatch { id, base_revision, revision, hunks } => {
+                let patched = self.debuggables.get(id)
+                    .filter(|debuggable| debuggable.revision == *base_revision && debuggable.summary.is_none())
+                    .and_then(|debuggable| Some((debuggable.name.clone(), String::from_json(&debuggable.value_in_json)?)))
+                    .and_then(|(name, text)| Some((name, apply_hunks(&text, hunks)?.to_json()?)));
+                match patched {
+                    None => { self.stale_text_values.insert(*id); }
+                    Some((name, value_in_json)) => { self.set_value(*id, &name, value_in_json, *revision); }
+                }
+            }
+            ServerMessage::NotifyIndex { id, index, element_json, revision } => {
+                if let Some(debuggable) = self.debuggables.get_mut(id) {
+                    debuggable.revision = *revision;
+                    match replace_array_element(&debuggable.value_in_json, *index, element_json) {
+                        None => log::warn!("Could not apply update of index {index} to debuggable {}", debuggable.name),
+                        Some(value_in_json) => debuggable.value_in_json = value_in_json,
+                    }
+                }
+            }
+            ServerMessage::Metadata { id, nullable, order, on_demand } => {
+                if let Some(debuggable) = self.debuggables.get_mut(id) {
+                    debuggable.nullable = *nullable;
+                    debuggable.order = *order;
+                    debuggable.on_demand = *on_demand;
+                }
+            }
+            ServerMessage::Added { id, name, uid, .. } => {
+                let debuggable = self.debuggables.entry(*id)
+                    .or_insert_with(|| RemoteDebuggable { name: name.clone(), value_in_json: String::new(), nullable: false, order: 0, revision: 0, summary: None, changed_at_ms: None, last_author: None, uid: None, on_demand: false, overridden: false });
+                debuggable.name = name.clone();
+                debuggable.uid = *uid;
+            }
+            ServerMessage::Remove { id, .. } => { self.debuggables.remove(id); }
+            ServerMessage::UpdateAck { request_id, accepted, .. } => {
+                if let Some(pending_update) = self.pending_updates.remove(request_id) {
+                    let outcome = if *accepted { UpdateOutcome::Accepted } else { UpdateOutcome::Rejected };
+                    self.update_outcomes.push((*request_id, outcome));
+                    if !*accepted {
+                        self.roll_back(*request_id, pending_update);
+                    }
+                }
+            }
+            ServerMessage::RpcResult { call_id, response_json, error } => {
+                let result = match (response_json, error) {
+                    (Some(response_json), None) => Ok(response_json.clone()),
+                    (_, error) => Err(error.clone().unwrap_or_else(|| "The server gave no response".to_string())),
+                };
+                self.rpc_results.insert(*call_id, result);
+            }
+            ServerMessage::NotifySummary { id, name, byte_len, preview } => {
+                let debuggable = self.debuggables.entry(*id)
+                    .or_insert_with(|| RemoteDebuggable { name: name.clone(), value_in_json: String::new(), nullable: false, order: 0, revision: 0, summary: None, changed_at_ms: None, last_author: None, uid: None, on_demand: false, overridden: false });
+                debuggable.name = name.clone();
+                debuggable.summary = Some(ValueSummary { byte_len: *byte_len, preview: preview.clone() });
+            }
+            ServerMessage::NotifyUnset { id } => {
+                if let Some(debuggable) = self.debuggables.get_mut(id) {
+                    debuggable.value_in_json.clear();
+                    debuggable.summary = None;
+                }
+            }
+            ServerMessage::ValueChunk { id, revision, chunk_index, chunk_count, json_chunk } => {
+                let partial_value = self.partial_values.entry(*id).or_default();
+                if *chunk_index == 0 {
+                    partial_value.clear();
+                }
+                partial_value.push_str(json_chunk);
+                if chunk_index + 1 < *chunk_count { return; }
+                let value_in_json = self.partial_values.remove(id).unwrap_or_default();
+                if let Some(debuggable) = self.debuggables.get_mut(id) {
+                    debuggable.value_in_json = value_in_json;
+                    debuggable.revision = *revision;
+                    debuggable.summary = None;
+                }
+            }
+            ServerMessage::Composite { name, kind, members } => {
+                self.composites.insert(name.clone(), RemoteComposite { kind: *kind, members: members.clone() });
+            }
+            ServerMessage::CompositeDissolved { name } => { self.composites.remove(name); }
+            ServerMessage::Blob { key, data } => { self.blobs.insert(key.clone(), data.clone()); }
+            ServerMessage::RemoveAll => {
+                self.debuggables.clear();
+                self.composites.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Restores the value the mirror had before a rejected optimistic update. If later updates of
+    /// the same debuggable are pending, the latest of them inherits it instead.
+    fn roll_back(&mut self, request_id: u64, rejected: PendingUpdate) {
+        let Some(previous_json) = rejected.previous_json else { return; };
+        let later_update = self.pending_updates.iter_mut()
+            .filter(|(later_request_id, pending_update)| **later_request_id > request_id && pending_update.debuggable_id == rejected.debuggable_id)
+            .min_by_key(|(later_request_id, _)| **later_request_id);
+        if let Some((_, later_update)) = later_update {
+            if later_update.previous_json.as_ref() == Some(&rejected.value_json) {
+                later_update.previous_json = Some(previous_json);
+            }
+            return;
+        }
+        if let Some(debuggable) = self.debuggables.get_mut(&rejected.debuggable_id) {
+            debuggable.value_in_json = previous_json;
+            self.events.push(ClientEvent::Reverted { id: rejected.debuggable_id });
+        }
+    }
+
+    /// Whether a notified value should replace the mirror. Echoes of values this client sent before
+    /// its latest pending update are skipped so they don't undo it, other values win over pending
+    /// optimistic updates, which are then reported as reverted.
+    fn accepts_notified(&mut self, debuggable_id: usize, value_in_json: &str) -> bool {
+        let latest_update = self.pending_updates.iter()
+            .filter(|(_, pending_update)| pending_update.debuggable_id == debuggable_id)
+            .max_by_key(|(request_id, _)| **request_id)
+            .map(|(_, pending_update)| pending_update.value_json.as_str());
+        let Some(latest_update) = latest_update else { return true; };
+        if latest_update == value_in_json { return true; }
+        let is_stale_echo = self.pending_updates.values()
+            .any(|pending_update| pending_update.debuggable_id == debuggable_id && pending_update.value_json == value_in_json);
+        if is_stale_echo { return false; }
+        let mut was_optimistic = false;
+        self.pending_updates.values_mut()
+            .filter(|pending_update| pending_update.debuggable_id == debuggable_id)
+            .for_each(|pending_update| was_optimistic |= pending_update.previous_json.take().is_some());
+        if was_optimistic {
+            self.events.push(ClientEvent::Reverted { id: debuggable_id });
+        }
+        true
+    }
+
+    /// Returns false if the value was ignored, as it's superseded by a pending optimistic update.
+    fn set_value(&mut self, debuggable_id: usize, name: &str, value_in_json: String, revision: u64) -> bool {
+        if !self.accepts_notified(debuggable_id, &value_in_json) { return false; }
+        let debuggable = self.debuggables.entry(debuggable_id)
+            .or_insert_with(|| RemoteDebuggable { name: name.to_string(), value_in_json: String::new(), nullable: false, order: 0, revision: 0, summary: None, changed_at_ms: None, last_author: None, uid: None, on_demand: false, overridden: false });
+        debuggable.name = name.to_string();
+        debuggable.value_in_json = value_in_json;
+        debuggable.revision = revision;
+        debuggable.summary = None;
+        debuggable.changed_at_ms = None;
+        debuggable.last_author = None;
+        true
+    }
+}
+
+fn target_ids_mut(message: &mut ClientUnitMessage) -> Vec<&mut usize> {
+    match message {
+        ClientUnitMessage::UpdateValue { id, .. }
+        | ClientUnitMessage::UpdateIndex { id, .. }
+        | ClientUnitMessage::UpdateValueCas { id, .. }
+        | ClientUnitMessage::UpdateTextPatch { id, .. }
+        | ClientUnitMessage::StageValue { id, .. }
+        | ClientUnitMessage::AnimateValue { id, .. } => vec![id],
+        ClientUnitMessage::UpdateGroup { updates } => updates.iter_mut().map(|update| &mut update.id).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Points the message at the debuggables now holding the names it targeted when it was queued,
+/// dropping it if any of them no longer exists.
+fn remapped_by_name(mut message: ClientUnitMessage, target_names: &[Option<String>], ids_by_name: &HashMap<String, usize>) -> Option<ClientUnitMessage> {
+    for (target_id, target_name) in target_ids_mut(&mut message).into_iter().zip(target_names) {
+        let Some(target_name) = target_name else { continue; };
+        match ids_by_name.get(target_name) {
+            None => {
+                log::warn!("Dropping update queued for debuggable {target_name}, which no longer exists");
+                return None;
+            }
+            Some(new_id) => *target_id = *new_id,
+        }
+    }
+    Some(message)
+}
+
+fn replace_array_element(array_json: &str, index: usize, element_json: &str) -> Option<String> {
+    let array_start = array_json.find('[')?;
+    let (mut depth, mut in_string, mut is_escaped) = (0_usize, false, false);
+    let (mut element, mut element_start) = (0_usize, array_start + 1);
+    for (position, character) in array_json.char_indices().skip_while(|(position, _)| *position <= array_start) {
+        if in_string {
+            match character {
+                _ if is_escaped => is_escaped = false,
+                '\\' => is_escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match character {
+            '"' => in_string = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' if depth > 0 => depth -= 1,
+            ',' | ']' if depth == 0 => {
+                if element == index {
+                    return Some(format!("{}{}{}", &array_json[..element_start], element_json, &array_json[position..]));
+                }
+                if character == ']' { return None; }
+                element += 1;
+                element_start = position + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
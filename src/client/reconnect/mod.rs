@@ -0,0 +1,51 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How a DebuggableClient reconnects after losing its connection, each attempt waits twice as
+/// long as the previous one up to max_delay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction between 0 and 1 of each delay that is randomly taken off, so clients that lost the
+    /// same server don't all reconnect at once.
+    pub jitter: f64,
+    /// Gives up after this many failed attempts, None retries forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self { base_delay: Duration::from_millis(100), max_delay: Duration::from_secs(10), jitter: 0.2, max_attempts: None }
+    }
+}
+
+impl ReconnectPolicy {
+    pub(crate) fn delay_of(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        delay.mul_f64(1.0 - jitter)
+    }
+
+    pub(crate) fn gives_up_after(&self, attempt: u32) -> bool {
+        self.max_attempts.is_some_and(|max_attempts| attempt >= max_attempts)
+    }
+}
+
+/// What happens to messages sent while a reconnecting client is disconnected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfflinePolicy {
+    /// Sending fails with ErrorKind::NotConnected.
+    FailFast,
+    /// Messages are sent once reconnected, updates being remapped to the debuggable with the same
+    /// name. Sending fails once max_queued messages are waiting.
+    Queue { max_queued: usize },
+}
+
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(0);
+    (hasher.finish() >> 11) as f64 / (1_u64 << 53) as f64
+}
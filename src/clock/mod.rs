@@ -0,0 +1,20 @@
+use std::fmt::Debug;
+use std::time::{Instant, SystemTime};
+
+pub trait Clock: Debug + Send + Sync {
+    fn now_instant(&self) -> Instant;
+    fn now_system(&self) -> SystemTime;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_system(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// How a debuggable decides whether its value changed locally since it was last synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeDetection {
+    /// Serializes the value on every sync and compares the JSON with the last one sent.
+    ByJson,
+    /// Compares the value with a copy taken on the last sync, serializing only when they differ.
+    ByEq,
+    /// Compares the hash of the value with the one taken on the last sync.
+    ByHash,
+}
+
+pub(crate) enum ChangeDetector<Value> {
+    ByJson,
+    ByEq {
+        shadow: Option<Value>,
+        equals: fn(&Value, &Value) -> bool,
+        clone: fn(&Value) -> Value,
+    },
+    ByHash {
+        last_hash: Option<u64>,
+        hash: fn(&Value) -> u64,
+    },
+}
+
+impl<Value> ChangeDetector<Value> {
+    pub(crate) fn by_eq() -> Self where Value: PartialEq + Clone {
+        Self::ByEq { shadow: None, equals: Value::eq, clone: Value::clone }
+    }
+
+    pub(crate) fn by_hash() -> Self where Value: Hash {
+        Self::ByHash { last_hash: None, hash: hash_of::<Value> }
+    }
+
+    pub(crate) fn kind(&self) -> ChangeDetection {
+        match self {
+            ChangeDetector::ByJson => ChangeDetection::ByJson,
+            ChangeDetector::ByEq { .. } => ChangeDetection::ByEq,
+            ChangeDetector::ByHash { .. } => ChangeDetection::ByHash,
+        }
+    }
+
+    /// Whether the value is known to be the one remembered on the last sync, ByJson never knows.
+    pub(crate) fn is_unchanged(&self, value: &Value) -> bool {
+        match self {
+            ChangeDetector::ByJson => false,
+            ChangeDetector::ByEq { shadow, equals, .. } => shadow.as_ref().is_some_and(|shadow| equals(shadow, value)),
+            ChangeDetector::ByHash { last_hash, hash } => *last_hash == Some(hash(value)),
+        }
+    }
+
+    pub(crate) fn remember(&mut self, value: &Value) {
+        match self {
+            ChangeDetector::ByJson => {}
+            ChangeDetector::ByEq { shadow, clone, .. } => *shadow = Some(clone(value)),
+            ChangeDetector::ByHash { last_hash, hash } => *last_hash = Some(hash(value)),
+        }
+    }
+}
+
+fn hash_of<Value: Hash>(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,175 @@
+use std::any::{Any, type_name};
+use std::cell::{Cell, OnceCell, RefCell, UnsafeCell};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, RwLock};
+
+use crate::debuggable::change_detection::ChangeDetector;
+use crate::debuggable::value_codec::ValueCodec;
+use crate::debuggable::{Debuggable, DebuggableOptions, ServerRegistration};
+use crate::default_server;
+use crate::serializable::JSONDeSerializable;
+use crate::server::DebuggableServer;
+
+pub struct DebuggableGroupBuilder {
+    server: Arc<RwLock<DebuggableServer>>,
+    entries: Vec<GroupEntry>,
+}
+
+pub struct GroupEntryBuilder<Value> {
+    name: String,
+    initial_value: Value,
+    options: DebuggableOptions,
+}
+
+struct GroupEntry {
+    name: String,
+    options: DebuggableOptions,
+    value: Box<dyn Any>,
+    value_type: &'static str,
+    to_json: Box<dyn Fn(&dyn Any) -> Option<String>>,
+    from_json: fn(&str) -> Option<Box<dyn Any>>,
+}
+
+pub struct DebuggableGroup {
+    members: HashMap<String, GroupMember>,
+}
+
+struct GroupMember {
+    registration: ServerRegistration,
+    options: DebuggableOptions,
+    value: Box<dyn Any>,
+    value_type: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupTakeError {
+    NotFound { name: String },
+    TypeMismatch { name: String, expected: &'static str, found: &'static str },
+}
+
+impl Display for GroupTakeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GroupTakeError::NotFound { name } => write!(f, "No debuggable named '{name}' in this group"),
+            GroupTakeError::TypeMismatch { name, expected, found } =>
+                write!(f, "Debuggable '{name}' holds a value of type {found}, but {expected} was requested"),
+        }
+    }
+}
+
+impl std::error::Error for GroupTakeError {}
+
+impl GroupEntryBuilder<()> {
+    pub fn initial<Value: JSONDeSerializable + 'static>(self, initial_value: Value) -> GroupEntryBuilder<Value> {
+        GroupEntryBuilder { name: self.name, initial_value, options: self.options }
+    }
+}
+
+impl<Value> GroupEntryBuilder<Value> {
+    pub fn name<Name: ToString>(mut self, name: Name) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn keep(mut self) -> Self {
+        self.options.is_keep = true;
+        self
+    }
+
+    pub fn order(mut self, order: i32) -> Self {
+        self.options.order = order;
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.options.hidden = hidden;
+        self
+    }
+}
+
+impl DebuggableGroupBuilder {
+    pub fn new(server: Arc<RwLock<DebuggableServer>>) -> Self {
+        Self { server, entries: Vec::new() }
+    }
+
+    pub fn on_default_server() -> Self {
+        Self::new(default_server::default_server())
+    }
+
+    pub fn add<Name: ToString, Value: JSONDeSerializable + 'static>(self, name: Name, initial_value: Value) -> Self {
+        self.add_with(|entry| entry.name(name).initial(initial_value))
+    }
+
+    pub fn add_with<Value: JSONDeSerializable + 'static, Configure>(mut self, configure: Configure) -> Self
+        where Configure: FnOnce(GroupEntryBuilder<()>) -> GroupEntryBuilder<Value> {
+        let entry = configure(GroupEntryBuilder { name: String::new(), initial_value: (), options: Default::default() });
+        let mut options = entry.options;
+        options.nullable = Value::from_json("null").is_some();
+        self.entries.push(GroupEntry {
+            name: entry.name,
+            options,
+            value: Box::new(entry.initial_value),
+            value_type: type_name::<Value>(),
+            to_json: Box::new(|value| value.downcast_ref::<Value>().and_then(Value::to_json)),
+            from_json: |json| Value::from_json(json).map(|value| Box::new(value) as Box<dyn Any>),
+        });
+        self
+    }
+
+    pub fn build(self) -> DebuggableGroup {
+        let server_guard = self.server.write().unwrap();
+        let mut registered_ids = Vec::with_capacity(self.entries.len());
+        let mut members = HashMap::with_capacity(self.entries.len());
+        for mut entry in self.entries {
+            let (id, registration) = ServerRegistration::init_on_locked(&server_guard, &entry.name, &entry.options);
+            if entry.options.is_keep {
+                let kept_value = server_guard.last_value_of(id).and_then(|json| (entry.from_json)(&json));
+                if let Some(kept_value) = kept_value {
+                    entry.value = kept_value;
+                }
+            }
+            server_guard.set_last_value(id, (entry.to_json)(&*entry.value));
+            registered_ids.push(id);
+            members.insert(entry.name, GroupMember {
+                registration: ServerRegistration::of_registered(self.server.clone(), &server_guard, id, registration),
+                options: entry.options,
+                value: entry.value,
+                value_type: entry.value_type,
+            });
+        }
+        server_guard.broadcast_notify_many(&registered_ids);
+        drop(server_guard);
+        DebuggableGroup { members }
+    }
+}
+
+impl DebuggableGroup {
+    pub fn names(&self) -> impl Iterator<Item=&str> {
+        self.members.keys().map(String::as_str)
+    }
+
+    pub fn take<Value: JSONDeSerializable + 'static>(&mut self, name: &str) -> Result<Debuggable<Value>, GroupTakeError> {
+        let member = self.members.get(name).ok_or_else(|| GroupTakeError::NotFound { name: name.to_string() })?;
+        if !member.value.is::<Value>() {
+            return Err(GroupTakeError::TypeMismatch { name: name.to_string(), expected: type_name::<Value>(), found: member.value_type });
+        }
+        let member = self.members.remove(name).unwrap();
+        let value = *member.value.downcast::<Value>().unwrap();
+        Ok(Debuggable {
+            value: UnsafeCell::new(value),
+            name: name.to_string(),
+            options: member.options,
+            active_borrows: Cell::new(0),
+            registrations: OnceCell::from(vec![member.registration]),
+            lazy_servers: RefCell::new(None),
+            on_remote_update: RefCell::new(None),
+            change_generation: Cell::new(0),
+            change_detector: RefCell::new(ChangeDetector::ByJson),
+            codec: ValueCodec::Default,
+            is_unserializable: Cell::new(false),
+            provider: RefCell::new(None),
+            awaiting_value: Cell::new(false),
+        })
+    }
+}
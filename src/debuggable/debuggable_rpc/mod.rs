@@ -0,0 +1,113 @@
+use std::any::{type_name, Any};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::default_server;
+use crate::scoped_server::ScopedServer;
+use crate::serializable::{AddedOrigin, JSONDeSerializable};
+use crate::server::{DebuggableServer, Who};
+use crate::snapshot;
+
+type RpcHandler<Request, Response> = Box<dyn FnMut(Request) -> Response>;
+
+/// Endpoint clients call through RpcCall, answered only to the caller with the handler's response.
+/// Calls are served when the host calls serve, those waiting longer than the server's RPC timeout
+/// are answered with an error instead.
+pub struct DebuggableRpc<Request: JSONDeSerializable, Response: JSONDeSerializable> {
+    name: String,
+    server: Arc<RwLock<DebuggableServer>>,
+    // Follows the endpoint when compaction moves it
+    id: Arc<AtomicUsize>,
+    registration: u64,
+    handler: RpcHandler<Request, Response>,
+    types: PhantomData<fn(Request) -> Response>,
+}
+
+impl<Request: JSONDeSerializable, Response: JSONDeSerializable> DebuggableRpc<Request, Response> {
+    pub fn new<Name: ToString, Handler: FnMut(Request) -> Response + 'static>(name: Name, handler: Handler) -> Self {
+        Self::on_server(default_server::default_server(), name, handler)
+    }
+
+    pub fn scoped<Name: ToString, Handler: FnMut(Request) -> Response + 'static>(scoped_server: &ScopedServer, name: Name, handler: Handler) -> Self {
+        Self::on_server(scoped_server.handle(), name, handler)
+    }
+
+    pub fn on_server<Name: ToString, Handler: FnMut(Request) -> Response + 'static>(server: Arc<RwLock<DebuggableServer>>, name: Name, handler: Handler) -> Self {
+        let name = name.to_string();
+        let (id, registration) = {
+            let locked_server = server.read().unwrap();
+            let (id, _) = locked_server.init_debuggable(name.clone(), false);
+            locked_server.init_rpc_endpoint(id);
+            locked_server.broadcast_added(id, AddedOrigin::HostCode);
+            let signature = format!("{} -> {}", type_name::<Request>(), type_name::<Response>());
+            locked_server.notify_new_value(id, Some(format!("{{\"rpc\":{}}}", snapshot::quoted(&signature))), Who::All);
+            (locked_server.id_cell_of(id).unwrap(), locked_server.registration_of(id).unwrap())
+        };
+        Self { name, server, id, registration, handler: Box::new(handler), types: PhantomData }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> usize {
+        self.id.load(Ordering::Relaxed)
+    }
+
+    /// Runs the handler for every call received so far, returning how many were answered. Requests
+    /// that can't be deserialized and handlers that panic are answered with an error.
+    pub fn serve(&mut self) -> usize {
+        let pending_calls = {
+            let server = self.server.read().unwrap();
+            if !server.is_registration_alive(self.id(), self.registration) { return 0; }
+            server.poll_clients();
+            // Endpoints have no value clients could update
+            if server.pending_updates_of(self.id()) > 0 {
+                server.discard_pending_of(self.id());
+            }
+            server.take_rpc_calls(self.id())
+        };
+        let served = pending_calls.len();
+        for pending_call in pending_calls {
+            let result = self.handle(&pending_call.request_json);
+            DebuggableServer::answer_rpc_call(&self.server.read().unwrap(), &pending_call.author, pending_call.call_id, result);
+        }
+        served
+    }
+
+    fn handle(&mut self, request_json: &str) -> Result<String, String> {
+        let request = Request::from_json_detailed(request_json).map_err(|reason| format!("Malformed request: {reason}"))?;
+        let handler = &mut self.handler;
+        let response = panic::catch_unwind(AssertUnwindSafe(|| handler(request)))
+            .map_err(|panic| format!("The handler panicked: {}", panic_message(&*panic)))?;
+        response.to_json().ok_or_else(|| "The response could not be serialized".to_string())
+    }
+}
+
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic.downcast_ref::<&str>().copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+}
+
+impl<Request: JSONDeSerializable, Response: JSONDeSerializable> Drop for DebuggableRpc<Request, Response> {
+    fn drop(&mut self) {
+        let Ok(server) = self.server.read() else { return; };
+        if server.is_shut_down() { return; }
+        server.remove_debuggable(self.id(), self.registration);
+    }
+}
+
+impl<Request: JSONDeSerializable, Response: JSONDeSerializable> Debug for DebuggableRpc<Request, Response> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebuggableRpc")
+            .field("name", &self.name)
+            .field("id", &self.id())
+            .field("registration", &self.registration)
+            .finish()
+    }
+}
@@ -0,0 +1,89 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock};
+
+use crate::debuggable::Debuggable;
+use crate::serializable::JSONDeSerializable;
+use crate::server::{DebuggableServer, Who};
+
+pub struct DebuggableVec<T> where T: JSONDeSerializable, Vec<T>: JSONDeSerializable {
+    debuggable: Debuggable<Vec<T>>,
+}
+
+impl<T> DebuggableVec<T> where T: JSONDeSerializable, Vec<T>: JSONDeSerializable {
+    pub fn new<Name: ToString>(name: Name, initial_value: Vec<T>) -> Self {
+        Self { debuggable: Debuggable::new(name, initial_value) }
+    }
+
+    pub fn new_server<Name: ToString>(server: Arc<RwLock<DebuggableServer>>, name: Name, initial_value: Vec<T>, is_keep: bool) -> Self {
+        Self { debuggable: Debuggable::new_server(server, name, initial_value, is_keep) }
+    }
+
+    pub fn set(&mut self, index: usize, value: T) -> Option<T> {
+        self.apply_index_updates();
+        let element_json = value.to_json();
+        let values = self.debuggable.value.get_mut();
+        let old_value = std::mem::replace(values.get_mut(index)?, value);
+        if element_json.is_none() { return Some(old_value); }
+        let full_value = self.debuggable.codec.to_json(values);
+        self.debuggable.live_registrations().for_each(|registration| {
+            registration.server().read().unwrap()
+                .notify_index(registration.id(), index, element_json.clone().unwrap(), full_value.clone(), Who::All);
+        });
+        Some(old_value)
+    }
+
+    pub fn into_inner(self) -> Debuggable<Vec<T>> {
+        self.debuggable
+    }
+
+    fn apply_index_updates(&self) {
+        if self.debuggable.active_borrows.get() > 0 { return; }
+        self.debuggable.ensure_registered();
+        let registrations = self.debuggable.live_registrations().collect::<Vec<_>>();
+        for (source_index, registration) in registrations.iter().enumerate() {
+            let index_updates = {
+                let server_handle = registration.server();
+                let server = server_handle.read().unwrap();
+                server.poll_clients();
+                server.take_incoming_index_updates_of(registration.id())
+            };
+            for (client, index, element_json) in index_updates {
+                let values = unsafe { &mut *self.debuggable.value.get() };
+                let element = T::from_json(&element_json);
+                if index >= values.len() || element.is_none() {
+                    registration.server().read().unwrap().renotify_clients(registration.id(), &[client]);
+                    continue;
+                }
+                values[index] = element.unwrap();
+                self.debuggable.record_remote_update();
+                let full_value = self.debuggable.codec.to_json(values);
+                registrations.iter().enumerate().for_each(|(target_index, target)| {
+                    let who = if target_index == source_index { Who::AllBut(client) } else { Who::All };
+                    target.server.read().unwrap().notify_index(target.id(), index, element_json.clone(), full_value.clone(), who);
+                });
+            }
+        }
+    }
+}
+
+impl<T> From<Debuggable<Vec<T>>> for DebuggableVec<T> where T: JSONDeSerializable, Vec<T>: JSONDeSerializable {
+    fn from(debuggable: Debuggable<Vec<T>>) -> Self {
+        Self { debuggable }
+    }
+}
+
+impl<T> Deref for DebuggableVec<T> where T: JSONDeSerializable, Vec<T>: JSONDeSerializable {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.apply_index_updates();
+        &*self.debuggable
+    }
+}
+
+impl<T> DerefMut for DebuggableVec<T> where T: JSONDeSerializable, Vec<T>: JSONDeSerializable {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.apply_index_updates();
+        &mut *self.debuggable
+    }
+}
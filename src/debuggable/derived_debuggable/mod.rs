@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::mem;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::debuggable::debuggable_rpc::panic_message;
+use crate::default_server;
+use crate::scoped_server::ScopedServer;
+use crate::serializable::{AddedOrigin, JSONDeSerializable};
+use crate::server::{DebuggableServer, Who};
+
+type Computation = Box<dyn FnMut() -> Option<String>>;
+
+thread_local! {
+    // Computations are kept on the thread that registered them, as they usually read state that
+    // can't leave it
+    static DERIVED_ON_THIS_THREAD: RefCell<Vec<DerivedEntry>> = RefCell::new(Vec::new());
+}
+
+struct DerivedEntry {
+    // Address of the server, which lives behind an Arc for as long as it's registered there
+    server_key: usize,
+    name: String,
+    id: Arc<AtomicUsize>,
+    registration: u64,
+    compute: Computation,
+}
+
+impl DerivedEntry {
+    fn id(&self) -> usize {
+        self.id.load(Ordering::Relaxed)
+    }
+
+    /// Publishes the computed value, clients are only notified when its JSON changed. A panicking
+    /// computation unsets the value.
+    fn refresh(&mut self, server: &DebuggableServer) {
+        let compute = &mut self.compute;
+        match panic::catch_unwind(AssertUnwindSafe(|| compute())) {
+            Ok(Some(value_json)) => server.notify_new_value(self.id(), Some(value_json), Who::All),
+            Ok(None) => log::warn!("Value of derived debuggable {} could not be serialized", self.name),
+            Err(panic) => {
+                log::error!("Computing derived debuggable {} panicked: {}", self.name, panic_message(&*panic));
+                server.unset_value(self.id());
+            }
+        }
+    }
+}
+
+fn key_of(server: &DebuggableServer) -> usize {
+    server as *const DebuggableServer as usize
+}
+
+/// Refreshes the derived debuggables of the server registered on the calling thread. Refreshes
+/// triggered while computing one of them find nothing to do.
+pub(crate) fn refresh_on_this_thread(server: &DebuggableServer) -> usize {
+    let server_key = key_of(server);
+    let Some(mut entries) = DERIVED_ON_THIS_THREAD.with(|derived| {
+        let mut derived = derived.try_borrow_mut().ok()?;
+        let (entries, others) = mem::take(&mut *derived).into_iter().partition::<Vec<_>, _>(|entry| entry.server_key == server_key);
+        *derived = others;
+        Some(entries)
+    }) else { return 0; };
+    entries.retain(|entry| server.is_registration_alive(entry.id(), entry.registration));
+    entries.iter_mut().for_each(|entry| entry.refresh(server));
+    let refreshed = entries.len();
+    // Handles dropped while computing unregistered themselves already
+    entries.retain(|entry| server.is_registration_alive(entry.id(), entry.registration));
+    DERIVED_ON_THIS_THREAD.with(|derived| derived.borrow_mut().extend(entries));
+    refreshed
+}
+
+/// Read-only debuggable whose value is computed by the host, such as a view over other
+/// debuggables. It's recomputed each time DebuggableServer::refresh_derived runs, which polling
+/// and read_all_clients do at most once per derived refresh interval.
+///
+/// The computation runs on the thread that registered it, and only refreshes made from that
+/// thread run it. As it may run whenever a debuggable of the server syncs, it must not borrow
+/// state the host could be mutating at that time. If it panics, the panic is logged and clients
+/// are told the value is unset until a later refresh succeeds.
+pub struct DerivedDebuggable<Value: JSONDeSerializable> {
+    name: String,
+    server: Arc<RwLock<DebuggableServer>>,
+    // Follows the debuggable when compaction moves it
+    id: Arc<AtomicUsize>,
+    registration: u64,
+    types: PhantomData<fn() -> Value>,
+}
+
+impl<Value: JSONDeSerializable> DerivedDebuggable<Value> {
+    pub fn new<Name: ToString, Compute: FnMut() -> Value + 'static>(name: Name, compute: Compute) -> Self {
+        Self::on_server(default_server::default_server(), name, compute)
+    }
+
+    pub fn scoped<Name: ToString, Compute: FnMut() -> Value + 'static>(scoped_server: &ScopedServer, name: Name, compute: Compute) -> Self {
+        Self::on_server(scoped_server.handle(), name, compute)
+    }
+
+    pub fn on_server<Name: ToString, Compute: FnMut() -> Value + 'static>(server: Arc<RwLock<DebuggableServer>>, name: Name, mut compute: Compute) -> Self {
+        let name = name.to_string();
+        let mut entry = {
+            let locked_server = server.read().unwrap();
+            let (id, _) = locked_server.init_debuggable(name.clone(), false);
+            locked_server.set_read_only(id);
+            locked_server.broadcast_added(id, AddedOrigin::HostCode);
+            DerivedEntry {
+                server_key: key_of(&locked_server),
+                name: name.clone(),
+                id: locked_server.id_cell_of(id).unwrap(),
+                registration: locked_server.registration_of(id).unwrap(),
+                compute: Box::new(move || compute().to_json()),
+            }
+        };
+        entry.refresh(&server.read().unwrap());
+        let (id, registration) = (entry.id.clone(), entry.registration);
+        DERIVED_ON_THIS_THREAD.with(|derived| derived.borrow_mut().push(entry));
+        Self { name, server, id, registration, types: PhantomData }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> usize {
+        self.id.load(Ordering::Relaxed)
+    }
+}
+
+impl<Value: JSONDeSerializable> Drop for DerivedDebuggable<Value> {
+    fn drop(&mut self) {
+        let _ = DERIVED_ON_THIS_THREAD.try_with(|derived| {
+            if let Ok(mut derived) = derived.try_borrow_mut() {
+                derived.retain(|entry| !Arc::ptr_eq(&entry.id, &self.id));
+            }
+        });
+        let Ok(server) = self.server.read() else { return; };
+        if server.is_shut_down() { return; }
+        server.remove_debuggable(self.id(), self.registration);
+    }
+}
+
+impl<Value: JSONDeSerializable> Debug for DerivedDebuggable<Value> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DerivedDebuggable")
+            .field("name", &self.name)
+            .field("id", &self.id())
+            .field("registration", &self.registration)
+            .finish()
+    }
+}
@@ -0,0 +1,194 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use crate::debuggable::value_codec::ValueCodec;
+use crate::serializable::JSONDeSerializable;
+
+/// How a debuggable serializes NaN and infinite floats, which JSON has no representation for, see
+/// DebuggableBuilder::float_policy.
+///
+/// For f32 and f64 values the policy applies to the value itself. For any other value, like
+/// structs containing floats, it applies to the whole JSON text the value serializes to: non-finite
+/// tokens the backend writes bare (NaN, inf, Infinity) are handled, and on decoding StringEncode
+/// turns the encoded strings back into tokens for the backend to parse. Round-tripping struct
+/// fields therefore requires StringEncode and a backend that reads those tokens; backends writing
+/// non-finite floats as null, like serde_json, lose them before the policy sees them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatPolicy {
+    /// Treats non-finite values as a serialization failure, reported to on_serialize_error.
+    RejectNonFinite,
+    /// Encodes them as the strings "NaN", "Infinity" and "-Infinity", decoding them back.
+    StringEncode,
+    /// Sends them as 0, and takes the encoded strings from clients as 0 too.
+    ClampToZero,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NonFinite {
+    NaN,
+    Infinity,
+    NegativeInfinity,
+}
+
+impl NonFinite {
+    fn of(float: f64) -> Option<NonFinite> {
+        if float.is_nan() { return Some(NonFinite::NaN); }
+        if float.is_infinite() { return Some(if float > 0.0 { NonFinite::Infinity } else { NonFinite::NegativeInfinity }); }
+        None
+    }
+
+    fn from_token(token: &str) -> Option<NonFinite> {
+        match token {
+            "NaN" | "nan" | "-NaN" => Some(NonFinite::NaN),
+            "inf" | "+inf" | "Infinity" | "+Infinity" => Some(NonFinite::Infinity),
+            "-inf" | "-Infinity" => Some(NonFinite::NegativeInfinity),
+            _ => None,
+        }
+    }
+
+    fn from_encoded(string_literal: &str) -> Option<NonFinite> {
+        match string_literal {
+            "\"NaN\"" => Some(NonFinite::NaN),
+            "\"Infinity\"" => Some(NonFinite::Infinity),
+            "\"-Infinity\"" => Some(NonFinite::NegativeInfinity),
+            _ => None,
+        }
+    }
+
+    fn encoded(self) -> &'static str {
+        match self {
+            NonFinite::NaN => "\"NaN\"",
+            NonFinite::Infinity => "\"Infinity\"",
+            NonFinite::NegativeInfinity => "\"-Infinity\"",
+        }
+    }
+
+    // Tokens Rust's float parsing reads back
+    fn token(self) -> &'static str {
+        match self {
+            NonFinite::NaN => "NaN",
+            NonFinite::Infinity => "inf",
+            NonFinite::NegativeInfinity => "-inf",
+        }
+    }
+
+    fn value(self) -> f64 {
+        match self {
+            NonFinite::NaN => f64::NAN,
+            NonFinite::Infinity => f64::INFINITY,
+            NonFinite::NegativeInfinity => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Wraps the codec so the value goes through the policy on both directions.
+pub(crate) fn wrap_codec<Value: JSONDeSerializable + 'static>(codec: ValueCodec<Value>, policy: FloatPolicy) -> ValueCodec<Value> {
+    let codec = Arc::new(codec);
+    let decoding_codec = codec.clone();
+    ValueCodec::Custom {
+        to_json: Box::new(move |value| encode(value, policy, |value| codec.to_json(value))),
+        from_json: Box::new(move |json| decode(json, policy, |json| decoding_codec.from_json_detailed(json).ok())),
+    }
+}
+
+fn encode<Value: 'static>(value: &Value, policy: FloatPolicy, to_json: impl Fn(&Value) -> Option<String>) -> Option<String> {
+    let Some(float) = float_of(value) else {
+        let json = to_json(value)?;
+        return match policy {
+            FloatPolicy::RejectNonFinite => {
+                let (_, non_finite_count) = rewrite_values(&json, |token| bare_non_finite(token).map(|_| ""));
+                (non_finite_count == 0).then_some(json)
+            }
+            FloatPolicy::StringEncode => Some(rewrite_values(&json, |token| bare_non_finite(token).map(NonFinite::encoded)).0),
+            FloatPolicy::ClampToZero => Some(rewrite_values(&json, |token| bare_non_finite(token).map(|_| "0.0")).0),
+        };
+    };
+    match (NonFinite::of(float), policy) {
+        (None, _) => to_json(value),
+        (Some(_), FloatPolicy::RejectNonFinite) => None,
+        (Some(non_finite), FloatPolicy::StringEncode) => Some(non_finite.encoded().to_string()),
+        (Some(_), FloatPolicy::ClampToZero) => Some("0.0".to_string()),
+    }
+}
+
+fn decode<Value: 'static>(json: &str, policy: FloatPolicy, from_json: impl Fn(&str) -> Option<Value>) -> Option<Value> {
+    if is_float::<Value>() {
+        return match (NonFinite::from_encoded(json.trim()), policy) {
+            (Some(non_finite), FloatPolicy::StringEncode) => float_as::<Value>(non_finite.value()),
+            (Some(_), FloatPolicy::ClampToZero) => float_as::<Value>(0.0),
+            (Some(_), FloatPolicy::RejectNonFinite) => None,
+            (None, _) => from_json(json).filter(|value| float_of(value).is_some_and(f64::is_finite)),
+        };
+    }
+    match policy {
+        FloatPolicy::RejectNonFinite => from_json(json),
+        FloatPolicy::StringEncode => from_json(&rewrite_values(json, |token| NonFinite::from_encoded(token).map(NonFinite::token)).0),
+        FloatPolicy::ClampToZero => from_json(&rewrite_values(json, |token| NonFinite::from_encoded(token).map(|_| "0.0")).0),
+    }
+}
+
+fn bare_non_finite(token: &str) -> Option<NonFinite> {
+    if token.starts_with('"') { return None; }
+    NonFinite::from_token(token)
+}
+
+fn is_float<Value: 'static>() -> bool {
+    TypeId::of::<Value>() == TypeId::of::<f32>() || TypeId::of::<Value>() == TypeId::of::<f64>()
+}
+
+fn float_of<Value: 'static>(value: &Value) -> Option<f64> {
+    let value = value as &dyn Any;
+    value.downcast_ref::<f64>().copied().or_else(|| value.downcast_ref::<f32>().map(|float| *float as f64))
+}
+
+fn float_as<Value: 'static>(float: f64) -> Option<Value> {
+    let boxed: Box<dyn Any> = if TypeId::of::<Value>() == TypeId::of::<f32>() { Box::new(float as f32) } else { Box::new(float) };
+    boxed.downcast::<Value>().ok().map(|value| *value)
+}
+
+/// Replaces the values of the JSON text the closure gives a replacement for, object keys are left
+/// alone. Values are passed as written, strings with their quotes. Returns the rewritten text and
+/// how many values were replaced.
+fn rewrite_values(json: &str, replacement_of: impl Fn(&str) -> Option<&'static str>) -> (String, usize) {
+    let mut rewritten = String::with_capacity(json.len());
+    let mut replaced = 0;
+    let mut rest = json;
+    while let Some(character) = rest.chars().next() {
+        let token_len = match character {
+            '"' => string_literal_len(rest),
+            ',' | ':' | '[' | ']' | '{' | '}' => 0,
+            whitespace if whitespace.is_whitespace() => 0,
+            _ => rest.find(|character: char| matches!(character, ',' | ':' | '[' | ']' | '{' | '}' | '"') || character.is_whitespace()).unwrap_or(rest.len()),
+        };
+        if token_len == 0 {
+            rewritten.push(character);
+            rest = &rest[character.len_utf8()..];
+            continue;
+        }
+        let (token, after) = rest.split_at(token_len);
+        let is_key = after.trim_start().starts_with(':');
+        match replacement_of(token).filter(|_| !is_key) {
+            Some(replacement) => {
+                rewritten.push_str(replacement);
+                replaced += 1;
+            }
+            None => rewritten.push_str(token),
+        }
+        rest = after;
+    }
+    (rewritten, replaced)
+}
+
+// Length of the string literal the text starts with, the whole text if it's unterminated
+fn string_literal_len(text: &str) -> usize {
+    let mut escaped = false;
+    for (index, character) in text.char_indices().skip(1) {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return index + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
@@ -1,33 +1,168 @@
-use std::cell::UnsafeCell;
-use std::collections::HashSet;
-use std::fmt::Debug;
+use std::any::type_name;
+use std::cell::{Cell, OnceCell, RefCell, UnsafeCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
 
+use crate::serializable::closure_codec::ClosureCodec;
 use crate::serializable::JSONDeSerializable;
-use crate::serializable::ServerMessage;
-use crate::server::{DebuggableServer, Who};
+use crate::serializable::{AddedOrigin, CompositeKind, ServerMessage};
+use crate::server::{Author, DebuggableServer, Redactor, Who};
+use crate::server::declarations::DeclaredOptions;
+use crate::server::callbacks;
+use crate::server::defaults::DebuggableDefaults;
+use crate::server::pending_updates::PendingUpdate;
 use simple_tcp::server::Server;
 use crate::default_server;
+use crate::error::DebugMonitorError;
+use crate::scoped_server::ScopedServer;
+use crate::debuggable::change_detection::{ChangeDetection, ChangeDetector};
+use crate::debuggable::float_policy::FloatPolicy;
+use crate::debuggable::sampling::{Sampler, Sampling};
+use crate::debuggable::shared_debuggable::SharedDebuggable;
+use crate::debuggable::value_codec::{UnserializableBuilder, Unserializable, ValueCodec};
+
+pub mod change_detection;
+pub mod debuggable_vec;
+pub mod debuggable_group;
+pub mod debuggable_rpc;
+pub mod derived_debuggable;
+pub mod float_policy;
+pub mod plain_debuggable;
+pub mod shared_debuggable;
+pub mod value_codec;
+mod sampling;
 
 pub struct Debuggable<Value> where Value: JSONDeSerializable {
     value: UnsafeCell<Value>,
-    id: usize,
-    server: Arc<RwLock<DebuggableServer>>,
+    name: String,
+    options: DebuggableOptions,
+    active_borrows: Cell<usize>,
+    registrations: OnceCell<Vec<ServerRegistration>>,
+    lazy_servers: RefCell<Option<LazyServers>>,
+    on_remote_update: RefCell<Option<RemoteUpdateHandler<Value>>>,
+    change_generation: Cell<u64>,
+    change_detector: RefCell<ChangeDetector<Value>>,
+    codec: ValueCodec<Value>,
+    // Whether the value couldn't be serialized last time, errors are reported when it starts failing
+    is_unserializable: Cell<bool>,
+    provider: RefCell<Option<ValueProvider<Value>>>,
+    // Whether poll_requests reported requests, which its next call fulfills with the value then
+    awaiting_value: Cell<bool>,
+    sampler: Sampler,
+}
+
+#[derive(Default)]
+struct LazyServers {
+    server: Option<Arc<RwLock<DebuggableServer>>>,
+    mirror_servers: Vec<Arc<RwLock<DebuggableServer>>>,
 }
 
+pub type RemoteUpdateHandler<Value> = Box<dyn FnMut(&Value)>;
+
+pub type ValueProvider<Value> = Box<dyn FnMut() -> Value>;
+
+pub type Migration = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+pub type SerializeErrorHandler = Arc<dyn Fn(&DebugMonitorError) + Send + Sync>;
+
+struct ServerRegistration {
+    // Replaced when the server is adopted by another one
+    server: RefCell<Arc<RwLock<DebuggableServer>>>,
+    // Shared with the server, which updates it when compacting
+    id: RefCell<Arc<AtomicUsize>>,
+    registration: Cell<u64>,
+    // Checked before locking the server, which might be unusable once shut down
+    server_shut_down: RefCell<Arc<AtomicBool>>,
+}
+
+pub struct DebuggableRef<'debuggable, Value: JSONDeSerializable> {
+    debuggable: &'debuggable Debuggable<Value>,
+}
+
+pub struct DebuggableRefMut<'debuggable, Value: JSONDeSerializable> {
+    debuggable: &'debuggable mut Debuggable<Value>,
+}
+
+/// Builds a debuggable, registering it on the default server unless told otherwise.
+///
+/// ```
+/// use debug_monitor::debuggable::DebuggableBuilder;
+/// use debug_monitor::scoped_server::ScopedServer;
+///
+/// let server = ScopedServer::new();
+/// let mut speed = DebuggableBuilder::new("speed", 1.5_f32).scoped(&server).build();
+/// *speed = 3.0;
+/// assert_eq!(*speed, 3.0);
+/// assert_eq!(server.handle().read().unwrap().value_of("speed").as_deref(), Some("3.0"));
+/// ```
 pub struct DebuggableBuilder<Value: JSONDeSerializable> {
     initial_value: Value,
     name: String,
     server: Option<Arc<RwLock<DebuggableServer>>>,
+    mirror_servers: Vec<Arc<RwLock<DebuggableServer>>>,
+    options: DebuggableOptions,
+    on_remote_update: Option<RemoteUpdateHandler<Value>>,
+    provider: Option<ValueProvider<Value>>,
+    change_detector: ChangeDetector<Value>,
+    codec: ValueCodec<Value>,
+    lazy: bool,
+    sampling: Sampling,
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct DebuggableOptions {
     is_keep: bool,
+    redactor: Option<Redactor>,
+    ttl: Option<Duration>,
+    hidden: bool,
+    nullable: bool,
+    order: i32,
+    migration: Option<Migration>,
+    interpolable: bool,
+    text_diff: bool,
+    numeric_bounds: Option<(f64, f64)>,
+    on_serialize_error: Option<SerializeErrorHandler>,
+    composite_member: Option<(String, CompositeKind, usize)>,
+    on_demand: bool,
+    read_only: bool,
 }
 
 
+impl DebuggableOptions {
+    /// Options declared on the server fill in the ones this debuggable leaves at their default.
+    fn adopting(&self, declared: &DeclaredOptions) -> DebuggableOptions {
+        let mut options = self.clone();
+        options.is_keep |= declared.keep;
+        options.hidden |= declared.hidden;
+        if options.order == 0 {
+            options.order = declared.order;
+        }
+        options
+    }
+
+    /// Defaults of the server fill in the options this debuggable leaves at their default.
+    fn adopting_defaults(&self, defaults: &DebuggableDefaults) -> DebuggableOptions {
+        let mut options = self.clone();
+        options.is_keep |= defaults.keep;
+        options.hidden |= defaults.hidden;
+        options.read_only |= defaults.read_only;
+        if options.order == 0 {
+            options.order = defaults.order;
+        }
+        options.ttl = options.ttl.or(defaults.ttl);
+        options
+    }
+}
+
 impl<Value: JSONDeSerializable> DebuggableBuilder<Value> {
     pub fn new<Name: ToString>(name: Name, initial_value: Value) -> Self {
-        Self { initial_value, name: name.to_string(), server: None, is_keep: false }
+        Self { initial_value, name: name.to_string(), server: None, mirror_servers: Vec::new(), options: Default::default(), on_remote_update: None, provider: None, change_detector: ChangeDetector::ByJson, codec: ValueCodec::Default, lazy: false, sampling: Sampling::EveryAccess }
     }
 
     pub fn server(mut self, server: Option<Arc<RwLock<DebuggableServer>>>) -> DebuggableBuilder<Value> {
@@ -35,94 +170,847 @@ impl<Value: JSONDeSerializable> DebuggableBuilder<Value> {
         self
     }
 
+    pub fn scoped(mut self, scoped_server: &ScopedServer) -> DebuggableBuilder<Value> {
+        self.server = Some(scoped_server.handle());
+        self
+    }
+
+    pub fn also_on(mut self, server: Arc<RwLock<DebuggableServer>>) -> DebuggableBuilder<Value> {
+        self.mirror_servers.push(server);
+        self
+    }
+
+    /// Keeps the last value on the server once dropped, so a debuggable built later under the same
+    /// name starts from it.
+    ///
+    /// ```
+    /// use debug_monitor::debuggable::DebuggableBuilder;
+    /// use debug_monitor::scoped_server::ScopedServer;
+    ///
+    /// let server = ScopedServer::new();
+    /// {
+    ///     let mut volume = DebuggableBuilder::new("volume", 10).scoped(&server).keep().build();
+    ///     *volume = 4;
+    ///     assert_eq!(*volume, 4);
+    /// }
+    /// let volume = DebuggableBuilder::new("volume", 10).scoped(&server).keep().build();
+    /// assert_eq!(*volume, 4);
+    /// ```
     pub fn keep(mut self) -> DebuggableBuilder<Value> {
-        self.is_keep = true;
+        self.options.is_keep = true;
         self
     }
 
     pub fn dont_keep(mut self) -> DebuggableBuilder<Value> {
-        self.is_keep = false;
+        self.options.is_keep = false;
         self
     }
 
     pub fn set_is_keep(mut self, is_keep: bool) -> DebuggableBuilder<Value> {
-        self.is_keep = is_keep;
+        self.options.is_keep = is_keep;
         self
     }
 
-    pub fn build(mut self) -> Debuggable<Value> {
-        let server = self.server.unwrap_or_else(|| default_server::default_server());
-        Debuggable::new_server(server, self.name, self.initial_value, self.is_keep)
+    /// Rewrites the serialized value right before it is sent to clients, change detection keeps
+    /// using the unredacted value. Anything recording outgoing values stores the redacted form.
+    pub fn redact<Redact: Fn(&str) -> String + Send + Sync + 'static>(mut self, redact: Redact) -> DebuggableBuilder<Value> {
+        self.options.redactor = Some(Redactor::new(redact));
+        self
+    }
+
+    /// Removes this debuggable from the server when its owner doesn't sync it within the given
+    /// duration, the owner registers it again under a new id on its next sync.
+    pub fn ttl(mut self, ttl: Duration) -> DebuggableBuilder<Value> {
+        self.options.ttl = Some(ttl);
+        self
+    }
+
+    pub fn order(mut self, order: i32) -> DebuggableBuilder<Value> {
+        self.options.order = order;
+        self
+    }
+
+    /// Rewrites a kept value that no longer deserializes, for example after the type of the value
+    /// changed, returning None falls back to the initial value.
+    pub fn migrate<Migrate: Fn(&str) -> Option<String> + Send + Sync + 'static>(mut self, migrate: Migrate) -> DebuggableBuilder<Value> {
+        self.options.migration = Some(Arc::new(migrate));
+        self
+    }
+
+    /// Keeps the fractions of the intermediate values of animations requested by clients, even
+    /// when both ends serialize as whole numbers, as floats might.
+    pub fn interpolable(mut self) -> DebuggableBuilder<Value> {
+        self.options.interpolable = true;
+        self
+    }
+
+    /// Has the server clamp numbers clients send into the inclusive range before the debuggable
+    /// syncs, see DebuggableServer::set_numeric_bounds.
+    pub fn numeric_bounds(mut self, min: f64, max: f64) -> DebuggableBuilder<Value> {
+        self.options.numeric_bounds = Some((min, max));
+        self
+    }
+
+    /// Takes the slot of the composite, which clients are told about once every slot is taken
+    /// and which dissolves when any member is dropped. See DebuggableServer::declare_composite.
+    pub fn composite_member<Name: ToString>(mut self, composite: Name, kind: CompositeKind, slot: usize) -> DebuggableBuilder<Value> {
+        self.options.composite_member = Some((composite.to_string(), kind, slot));
+        self
+    }
+
+    /// Sends the value to clients only when they request it, for values too expensive to keep up
+    /// to date. Clients see the debuggable flagged in its Metadata, without a value, and request
+    /// it through DebuggableClient::request_value; the host computes it once Debuggable::poll_requests
+    /// reports requests, and its next call sends the value to every client that requested it.
+    pub fn on_demand(mut self) -> DebuggableBuilder<Value> {
+        self.options.on_demand = true;
+        self
+    }
+
+    /// Makes the debuggable on demand, computing the value through the provider on the first sync
+    /// after clients request it and sending it to them right away.
+    ///
+    /// ```
+    /// use debug_monitor::debuggable::DebuggableBuilder;
+    /// use debug_monitor::scoped_server::ScopedServer;
+    ///
+    /// let server = ScopedServer::new();
+    /// let statistics = DebuggableBuilder::new("statistics", 0_usize)
+    ///     .scoped(&server)
+    ///     .provide_on_demand(|| 42)
+    ///     .build();
+    /// assert_eq!(*statistics, 0);
+    /// ```
+    pub fn provide_on_demand<Provide: FnMut() -> Value + 'static>(mut self, provider: Provide) -> DebuggableBuilder<Value> {
+        self.options.on_demand = true;
+        self.provider = Some(Box::new(provider));
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> DebuggableBuilder<Value> {
+        self.options.hidden = hidden;
+        self
+    }
+
+    /// Calls the handler with the new value whenever a client's update is adopted on sync.
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use debug_monitor::debuggable::DebuggableBuilder;
+    /// use debug_monitor::scoped_server::ScopedServer;
+    ///
+    /// let server = ScopedServer::new();
+    /// let seen = Rc::new(Cell::new(0));
+    /// let seen_by_handler = seen.clone();
+    /// let lives = DebuggableBuilder::new("lives", 3)
+    ///     .scoped(&server)
+    ///     .on_remote_update(move |lives| seen_by_handler.set(*lives))
+    ///     .build();
+    /// let handle = server.handle();
+    /// let id = handle.read().unwrap().debuggable_id_of("lives").unwrap();
+    /// assert!(handle.read().unwrap().queue_update(id, 0, "9".to_string()));
+    /// assert_eq!(*lives, 9);
+    /// assert_eq!(seen.get(), 9);
+    /// assert_eq!(lives.change_generation(), 1);
+    /// ```
+    pub fn on_remote_update<OnRemoteUpdate: FnMut(&Value) + 'static>(mut self, on_remote_update: OnRemoteUpdate) -> DebuggableBuilder<Value> {
+        self.on_remote_update = Some(Box::new(on_remote_update));
+        self
+    }
+
+    /// Compares the value against a copy of it before serializing it on each sync.
+    pub fn detect_changes_by_eq(mut self) -> DebuggableBuilder<Value> where Value: PartialEq + Clone {
+        self.change_detector = ChangeDetector::by_eq();
+        self
+    }
+
+    /// Compares the hash of the value before serializing it on each sync.
+    pub fn detect_changes_by_hash(mut self) -> DebuggableBuilder<Value> where Value: Hash {
+        self.change_detector = ChangeDetector::by_hash();
+        self
+    }
+
+    pub fn detect_changes_by_json(mut self) -> DebuggableBuilder<Value> {
+        self.change_detector = ChangeDetector::ByJson;
+        self
+    }
+
+    /// Serializes the value through the given closures instead of its JSONDeSerializable impl,
+    /// updates the closures can't deserialize are rejected.
+    pub fn with_serializer<ToJsonFn, FromJsonFn>(mut self, to_json: ToJsonFn, from_json: FromJsonFn) -> DebuggableBuilder<Value>
+        where ToJsonFn: Fn(&Value) -> Option<String> + Send + Sync + 'static,
+              FromJsonFn: Fn(&str) -> Option<Value> + Send + Sync + 'static {
+        self.codec = ValueCodec::Custom { to_json: Box::new(to_json), from_json: Box::new(from_json) };
+        self
+    }
+
+    /// Serializes NaN and infinite floats through the policy instead of failing or sending null,
+    /// see FloatPolicy. Wraps the serializer set so far, so it goes after with_serializer.
+    pub fn float_policy(mut self, policy: FloatPolicy) -> DebuggableBuilder<Value> where Value: 'static {
+        self.codec = float_policy::wrap_codec(mem::replace(&mut self.codec, ValueCodec::Default), policy);
+        self
+    }
+
+    /// Called when the value stops being serializable, whether at registration or on a later sync.
+    /// Clients see the value unset until it can be serialized again.
+    pub fn on_serialize_error<OnSerializeError: Fn(&DebugMonitorError) + Send + Sync + 'static>(mut self, on_serialize_error: OnSerializeError) -> DebuggableBuilder<Value> {
+        self.options.on_serialize_error = Some(Arc::new(on_serialize_error));
+        self
+    }
+
+    /// Defers resolving the server and registering until the value is first accessed, so that
+    /// debuggables created before the application configures its default server still use it.
+    pub fn lazy(mut self) -> DebuggableBuilder<Value> {
+        self.lazy = true;
+        self
+    }
+
+    /// Syncs only on every nth access through Deref and DerefMut instead of on all of them, for
+    /// values accessed too often to check for changes each time, like per-packet counters. Remote
+    /// updates wait for the next access that syncs, set, update, borrow and sync always sync.
+    pub fn sample_every(mut self, nth: u32) -> DebuggableBuilder<Value> {
+        self.sampling = Sampling::EveryNth(nth.max(1));
+        self
+    }
+
+    /// Syncs on access through Deref and DerefMut at most once per interval of the server's clock,
+    /// like sample_every does every nth access.
+    pub fn sample_interval(mut self, interval: Duration) -> DebuggableBuilder<Value> {
+        self.sampling = Sampling::Interval(interval);
+        self
+    }
+
+    pub fn build(self) -> Debuggable<Value> {
+        // Registering inside a callback would take locks the thread already holds
+        let mut debuggable = if self.lazy || callbacks::is_running_callback() {
+            let lazy_servers = LazyServers { server: self.server, mirror_servers: self.mirror_servers };
+            Debuggable::new_lazy(lazy_servers, self.name, self.initial_value, self.options, self.codec)
+        } else {
+            let server = self.server.unwrap_or_else(|| default_server::default_server());
+            let mut servers = vec![server];
+            servers.extend(self.mirror_servers);
+            Debuggable::new_with_options(servers, self.name, self.initial_value, self.options, self.codec)
+        };
+        *debuggable.on_remote_update.borrow_mut() = self.on_remote_update;
+        *debuggable.provider.borrow_mut() = self.provider;
+        *debuggable.change_detector.borrow_mut() = self.change_detector;
+        debuggable.sampler = Sampler::new(self.sampling);
+        debuggable
+    }
+
+    /// Builds a debuggable that can be cloned and used from several threads, remote update
+    /// handlers and on demand providers aren't supported on it.
+    pub fn build_shared(self) -> SharedDebuggable<Value> where Value: Send {
+        SharedDebuggable::new(self.build())
+    }
+}
+
+impl DebuggableBuilder<String> {
+    /// Sends changes of the text to clients supporting it as the lines that changed, whenever
+    /// that's smaller than the whole text. Meant for large texts edited a few lines at a time, like
+    /// shader sources or scripts.
+    pub fn text_diff(mut self) -> DebuggableBuilder<String> {
+        self.options.text_diff = true;
+        self
+    }
+}
+
+impl<T: 'static> DebuggableBuilder<ClosureCodec<T>> {
+    /// Starts building a debuggable serialized by the given functions, which are kept by the
+    /// debuggable itself, so it can go to any server and others of the same type may use their own.
+    pub fn with_codec<Name: ToString>(name: Name, initial_value: T, to_json: fn(&T) -> Option<String>, from_json: fn(&str) -> Option<T>) -> DebuggableBuilder<ClosureCodec<T>> {
+        DebuggableBuilder::new(name, ClosureCodec(initial_value))
+            .with_serializer(move |value: &ClosureCodec<T>| to_json(&value.0), move |json: &str| from_json(json).map(ClosureCodec))
+    }
+}
+
+impl<T: 'static> DebuggableBuilder<Unserializable<T>> {
+    /// Starts building a debuggable of a type that doesn't implement JSONDeSerializable, it can
+    /// only be built once a serializer is given.
+    pub fn new_unserializable<Name: ToString>(name: Name, initial_value: T) -> UnserializableBuilder<T> {
+        UnserializableBuilder::new(name.to_string(), initial_value)
     }
 }
 
 
 impl<Value: JSONDeSerializable> Debuggable<Value> {
     pub fn new<Name: ToString>(name: Name, initial_value: Value) -> Self {
-        Self::new_server(default_server::default_server(), name, initial_value, false)
+        DebuggableBuilder::new(name, initial_value).build()
     }
 
     pub fn new_server<Name: ToString>(server: Arc<RwLock<DebuggableServer>>, name: Name, initial_value: Value, is_keep: bool) -> Self {
-        let name = name.to_string();
-        let (id, existed) = server.write().unwrap().init_debuggable(name, is_keep);
-        let initial_value = if is_keep {
-            server.read().unwrap().last_value_of(id).map(|json| Value::from_json(&json)).flatten().unwrap_or(initial_value)
+        let builder = DebuggableBuilder::new(name, initial_value).server(Some(server));
+        if is_keep { builder.keep() } else { builder }.build()
+    }
+
+    pub(crate) fn new_with_options<Name: ToString>(servers: Vec<Arc<RwLock<DebuggableServer>>>, name: Name, initial_value: Value, options: DebuggableOptions, codec: ValueCodec<Value>) -> Self {
+        let (name, mut options) = Self::applying_defaults_of(servers.first(), name.to_string(), options);
+        options.nullable = codec.from_json_detailed("null").is_ok();
+        let registrations = Self::register_on(servers, &name, &options);
+        let initial_value = if options.is_keep {
+            Self::kept_value_of(&registrations, &codec, &name, &options).unwrap_or(initial_value)
         } else {
             initial_value
         };
-        server.write().unwrap().notify_new_value(id, initial_value.to_json(), Who::All);
-        Self { value: UnsafeCell::new(initial_value), id, server }
+        let initial_json = codec.to_json(&initial_value);
+        if initial_json.is_none() {
+            Self::report_serialize_error(&name, &options);
+        }
+        Self::replay_on(&registrations, initial_json.clone());
+        Self {
+            value: UnsafeCell::new(initial_value),
+            name,
+            options,
+            active_borrows: Cell::new(0),
+            registrations: OnceCell::from(registrations),
+            lazy_servers: RefCell::new(None),
+            on_remote_update: RefCell::new(None),
+            change_generation: Cell::new(0),
+            change_detector: RefCell::new(ChangeDetector::ByJson),
+            codec,
+            is_unserializable: Cell::new(initial_json.is_none()),
+            provider: RefCell::new(None),
+            awaiting_value: Cell::new(false),
+            sampler: Sampler::new(Sampling::EveryAccess),
+        }
+    }
+
+    fn new_lazy<Name: ToString>(lazy_servers: LazyServers, name: Name, initial_value: Value, options: DebuggableOptions, codec: ValueCodec<Value>) -> Self {
+        let (name, mut options) = Self::applying_defaults_of(lazy_servers.server.as_ref(), name.to_string(), options);
+        options.nullable = codec.from_json_detailed("null").is_ok();
+        Self {
+            value: UnsafeCell::new(initial_value),
+            name,
+            options,
+            active_borrows: Cell::new(0),
+            registrations: OnceCell::new(),
+            lazy_servers: RefCell::new(Some(lazy_servers)),
+            on_remote_update: RefCell::new(None),
+            change_generation: Cell::new(0),
+            change_detector: RefCell::new(ChangeDetector::ByJson),
+            codec,
+            is_unserializable: Cell::new(false),
+            provider: RefCell::new(None),
+            awaiting_value: Cell::new(false),
+            sampler: Sampler::new(Sampling::EveryAccess),
+        }
+    }
+
+    /// Prefixes the name and fills in the options by the defaults of the server the debuggable is
+    /// built on, taken once so registering again later gives the same name. Lazy debuggables left
+    /// to the default server have no server to take them from.
+    fn applying_defaults_of(server: Option<&Arc<RwLock<DebuggableServer>>>, name: String, options: DebuggableOptions) -> (String, DebuggableOptions) {
+        // Inside callbacks the thread may already hold the server, whose defaults are then skipped
+        let defaults = server.and_then(|server| match callbacks::is_running_callback() {
+            true => server.try_read().ok().map(|server| server.defaults_on_this_thread()),
+            false => Some(server.read().unwrap().defaults_on_this_thread()),
+        });
+        let Some(defaults) = defaults else { return (name, options); };
+        (format!("{}{name}", defaults.prefix), options.adopting_defaults(&defaults))
+    }
+
+    fn register_on(servers: Vec<Arc<RwLock<DebuggableServer>>>, name: &str, options: &DebuggableOptions) -> Vec<ServerRegistration> {
+        servers.into_iter()
+            .map(|server| ServerRegistration::register(server, name, options))
+            .collect()
+    }
+
+    fn kept_value_of(registrations: &[ServerRegistration], codec: &ValueCodec<Value>, name: &str, options: &DebuggableOptions) -> Option<Value> {
+        registrations.iter()
+            .filter_map(|registration| registration.server().read().unwrap().last_value_of(registration.id()))
+            .filter_map(|json| Self::kept_value_from(&json, codec, name, options))
+            .next()
+    }
+
+    /// The kept value is replayed right after being adopted, so a migrated one replaces it.
+    fn kept_value_from(json: &str, codec: &ValueCodec<Value>, name: &str, options: &DebuggableOptions) -> Option<Value> {
+        let reason = match codec.from_json_detailed(json) {
+            Ok(kept_value) => return Some(kept_value),
+            Err(reason) => reason,
+        };
+        let migrated_json = options.migration.as_ref().and_then(|migration| migration(json))?;
+        match codec.from_json_detailed(&migrated_json) {
+            Ok(kept_value) => {
+                log::info!("Migrated kept value of debuggable {name} from {json} to {migrated_json} ({reason})");
+                Some(kept_value)
+            }
+            Err(migrated_reason) => {
+                log::warn!("Discarding kept value of debuggable {name}, its migration {migrated_json} was rejected: {migrated_reason}");
+                None
+            }
+        }
+    }
+
+    fn replay_on(registrations: &[ServerRegistration], json: Option<String>) {
+        registrations.iter().for_each(|registration| {
+            registration.server().read().unwrap().notify_new_value(registration.id(), json.clone(), Who::All);
+        });
+    }
+
+    /// Registrations of this debuggable, registering it first if it was built lazily.
+    fn registrations(&self) -> &[ServerRegistration] {
+        self.registrations.get_or_init(|| {
+            let lazy_servers = self.lazy_servers.borrow_mut().take().unwrap_or_default();
+            let server = lazy_servers.server.unwrap_or_else(default_server::default_server);
+            let mut servers = vec![server];
+            servers.extend(lazy_servers.mirror_servers);
+            let registrations = Self::register_on(servers, &self.name, &self.options);
+            if self.options.is_keep && self.active_borrows.get() == 0 {
+                if let Some(kept_value) = Self::kept_value_of(&registrations, &self.codec, &self.name, &self.options) {
+                    unsafe { *self.value.get() = kept_value; }
+                }
+            }
+            Self::replay_on(&registrations, self.current_json());
+            registrations
+        })
+    }
+
+    /// Number of remote updates accepted so far, local writes don't count.
+    pub fn change_generation(&self) -> u64 {
+        self.change_generation.get()
+    }
+
+    pub fn changed_since(&self, last_seen: u64) -> bool {
+        self.change_generation.get() != last_seen
+    }
+
+    fn record_remote_update(&self) {
+        self.change_generation.set(self.change_generation.get() + 1);
+        self.live_registrations().for_each(|registration| {
+            registration.server().read().unwrap().record_remote_update(registration.id());
+        });
+    }
+
+    /// Id the server this debuggable was first registered on never gives to another debuggable,
+    /// unlike the id clients address updates to. None until it's registered.
+    pub fn uid(&self) -> Option<u64> {
+        self.registrations.get()?.first().map(|registration| registration.registration.get())
+    }
+
+    pub fn change_detection(&self) -> ChangeDetection {
+        self.change_detector.borrow().kind()
+    }
+
+    pub fn is_registered(&self) -> bool {
+        self.registrations.get().is_some()
+    }
+
+    /// Whether any server this debuggable is registered on wasn't shut down, once none is left the
+    /// debuggable keeps its value locally without syncing it.
+    pub fn is_server_alive(&self) -> bool {
+        match self.registrations.get() {
+            None => true,
+            Some(registrations) => registrations.iter().any(ServerRegistration::is_server_alive),
+        }
+    }
+
+    /// Registrations on servers that weren't shut down.
+    fn live_registrations(&self) -> impl Iterator<Item=&ServerRegistration> {
+        self.registrations().iter().filter(|registration| registration.is_server_alive())
+    }
+
+    pub fn on_remote_update<OnRemoteUpdate: FnMut(&Value) + 'static>(&mut self, on_remote_update: OnRemoteUpdate) {
+        *self.on_remote_update.borrow_mut() = Some(Box::new(on_remote_update));
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.options.hidden = hidden;
+        self.registrations.get().into_iter().flatten().filter(|registration| registration.is_server_alive()).for_each(|registration| {
+            registration.server().read().unwrap().set_hidden(registration.id(), hidden);
+        });
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.options.hidden
+    }
+
+    /// Whether the current value was set by a monitor rather than by host code, cleared once host
+    /// code writes it or DebuggableServer::clear_override restores it.
+    pub fn is_overridden(&self) -> bool {
+        self.live_registrations().any(|registration| registration.server().read().unwrap().is_overridden(registration.id()))
+    }
+
+    pub fn pending_updates(&self) -> usize {
+        self.registrations.get().into_iter().flatten()
+            .filter(|registration| registration.is_server_alive())
+            .map(|registration| registration.server().read().unwrap().pending_updates_of(registration.id()))
+            .sum()
+    }
+
+    /// Syncs and returns how many clients requested the value of this on demand debuggable since
+    /// it was last sent to them. Once the host computes the value, the next call sends it to them,
+    /// along with any client requesting it meanwhile.
+    pub fn poll_requests(&mut self) -> usize {
+        self.process_changes();
+        if self.awaiting_value.replace(false) {
+            self.fulfill_requests();
+        }
+        let requests = self.value_requests();
+        self.awaiting_value.set(requests > 0);
+        requests
+    }
+
+    fn value_requests(&self) -> usize {
+        self.live_registrations()
+            .map(|registration| registration.server().read().unwrap().value_requesters_of(registration.id()))
+            .sum()
+    }
+
+    fn fulfill_requests(&self) {
+        self.live_registrations().for_each(|registration| {
+            registration.server().read().unwrap().fulfill_requests(registration.id());
+        });
+    }
+
+    /// Computes the value through the provider when clients requested it, returning whether it did.
+    fn provide_requested(&self) -> bool {
+        if !self.options.on_demand || self.provider.borrow().is_none() || self.value_requests() == 0 { return false; }
+        let provided = (self.provider.borrow_mut().as_mut().unwrap())();
+        unsafe { *self.value.get() = provided; }
+        true
+    }
+
+    pub fn discard_pending(&mut self) -> usize {
+        self.ensure_registered();
+        let current_json = self.codec.to_json(self.value.get_mut());
+        self.live_registrations().map(|registration| {
+            let server_handle = registration.server();
+            let server = server_handle.read().unwrap();
+            server.notify_new_value(registration.id(), current_json.clone(), Who::All);
+            server.discard_pending_of(registration.id())
+        }).sum()
+    }
+
+    /// Moves this debuggable to another server: it's removed from the ones it's registered on,
+    /// mirrors included, and registered on the new one under a fresh id with its current value.
+    /// Updates clients sent to the old servers and that weren't synced yet are dropped.
+    pub fn migrate_to(&mut self, new_server: Arc<RwLock<DebuggableServer>>) {
+        let Some(registrations) = self.registrations.take() else {
+            *self.lazy_servers.get_mut() = Some(LazyServers { server: Some(new_server), mirror_servers: Vec::new() });
+            return;
+        };
+        registrations.iter().for_each(ServerRegistration::release_moved);
+        drop(registrations);
+        let registrations = vec![ServerRegistration::register(new_server, &self.name, &self.options)];
+        Self::replay_on(&registrations, self.current_json());
+        self.registrations = OnceCell::from(registrations);
+        self.change_detector.get_mut().remember(self.value.get_mut());
+    }
+
+    /// Moves the registrations on servers adopted by another one, see
+    /// DebuggableServer::adopt_all_from. Returns whether any was moved.
+    fn follow_adoptions(&self) -> bool {
+        let mut moved = false;
+        for registration in self.live_registrations() {
+            let adopter = registration.server().read().unwrap().adopter();
+            if let Some(adopter) = adopter {
+                registration.move_to(adopter, &self.name, &self.options);
+                moved = true;
+            }
+        }
+        moved
+    }
+
+    /// Serializes the value, reporting when it stops being serializable.
+    fn current_json(&self) -> Option<String> {
+        let current_json = self.codec.to_json(unsafe { &*self.value.get() });
+        let was_unserializable = self.is_unserializable.replace(current_json.is_none());
+        if current_json.is_none() && !was_unserializable {
+            Self::report_serialize_error(&self.name, &self.options);
+        }
+        current_json
+    }
+
+    fn report_serialize_error(name: &str, options: &DebuggableOptions) {
+        let error = DebugMonitorError::Serialize { name: name.to_string(), type_name: type_name::<Value>() };
+        log::warn!("{error}, clients see it unset meanwhile");
+        if let Some(on_serialize_error) = options.on_serialize_error.as_ref() {
+            on_serialize_error(&error);
+        }
+    }
+
+    /// Returns whether any registration had to be made again.
+    fn ensure_registered(&self) -> bool {
+        self.live_registrations()
+            .map(|registration| registration.ensure_registered(&self.name, &self.options))
+            .fold(false, |registered_again, registered| registered_again | registered)
+    }
+
+    /// Syncs without serializing when the value didn't change locally and no client sent updates.
+    fn is_synced_without_changes(&self, registered_again: bool) -> bool {
+        if registered_again || !self.change_detector.borrow().is_unchanged(unsafe { &*self.value.get() }) {
+            return false;
+        }
+        let mut has_incoming = false;
+        for registration in self.live_registrations() {
+            let server_handle = registration.server();
+            let server = server_handle.read().unwrap();
+            server.poll_clients();
+            has_incoming |= server.touch_debuggable(registration.id());
+        }
+        !has_incoming
+    }
+
+    pub fn set(&mut self, value: Value) {
+        *self.borrow_mut() = value;
+    }
+
+    pub fn update<Update: FnOnce(&mut Value)>(&mut self, update: Update) {
+        update(&mut *self.borrow_mut());
+    }
+
+    pub fn borrow(&self) -> DebuggableRef<'_, Value> {
+        self.process_changes();
+        self.active_borrows.set(self.active_borrows.get() + 1);
+        DebuggableRef { debuggable: self }
+    }
+
+    pub fn borrow_mut(&mut self) -> DebuggableRefMut<'_, Value> {
+        self.process_changes();
+        DebuggableRefMut { debuggable: self }
+    }
+
+    /// Sends local changes to clients and applies their updates, even when accesses are sampled.
+    pub fn sync(&self) {
+        self.process_changes();
+    }
+
+    /// Syncs on the accesses the sampling picks, the rest only count themselves.
+    #[inline]
+    fn process_sampled_changes(&self) {
+        if !self.sampler.should_sample() { return; }
+        self.process_changes();
+        if self.sampler.needs_clock() && self.registrations.get().is_some() {
+            if let Some(registration) = self.live_registrations().next() {
+                self.sampler.use_clock(registration.server().read().unwrap().clock());
+            }
+        }
     }
 
     fn process_changes(&self) {
-        self.server.read().unwrap().accept_incoming_not_blocking();
-        self.server.read().unwrap().read_all_clients();
-        let current_json = unsafe { (*self.value.get()).to_json() };
-        let has_changed = !self.server.read().unwrap().last_value_of_equals(self.id, &current_json);
-        let incoming_jsons = self.server.write().unwrap().take_incoming_jsons_of(self.id);
-        let mut wrong_clients: HashSet<usize> = HashSet::new();
-        let new_value = incoming_jsons.into_iter().rev().map(|(client, new_json)| {
-            let json_is_different = current_json.is_none() || new_json.ne(current_json.as_ref().unwrap());
-            if !json_is_different { return None; }
-            let new_value = Value::from_json(&new_json);
+        if self.active_borrows.get() > 0 || callbacks::is_running_callback() || !self.is_server_alive() { return; }
+        let provided = self.provide_requested();
+        self.sync_changes();
+        if provided {
+            self.fulfill_requests();
+        }
+    }
+
+    fn sync_changes(&self) {
+        let registered_again = self.follow_adoptions() | self.ensure_registered();
+        if self.is_synced_without_changes(registered_again) { return; }
+        let current_json = self.current_json();
+        let mut new_value: Option<(usize, Author, Value)> = None;
+        let registrations = self.live_registrations().collect::<Vec<_>>();
+        let mut pending_per_server = Vec::with_capacity(registrations.len());
+        for (server_index, registration) in registrations.iter().enumerate() {
+            let pending_sync = {
+                let server_handle = registration.server();
+                let server = server_handle.read().unwrap();
+                server.poll_clients();
+                server.sync_debuggable(registration.id(), &current_json)
+            };
+            // Candidates are deserialized once the server is released
+            let mut wrong_clients: HashMap<Author, String> = HashMap::new();
+            let mut acks = Vec::new();
             if new_value.is_none() {
-                wrong_clients.insert(client);
-                return None;
+                new_value = self.select_incoming(pending_sync.incoming_jsons, &current_json, &mut wrong_clients, &mut acks)
+                    .map(|(author, value)| (server_index, author, value));
+            } else {
+                Self::reject_superseded(pending_sync.incoming_jsons, &mut acks);
             }
-            let new_value = new_value.unwrap();
-            if new_value.to_json().is_none() {
-                wrong_clients.insert(client);
+            pending_per_server.push((pending_sync.has_changed, wrong_clients, acks));
+        }
+        let new_json = new_value.as_ref().map(|(_, _, new_value)| self.codec.to_json(new_value));
+        for ((server_index, registration), (has_changed, wrong_clients, acks)) in registrations.iter().enumerate().zip(pending_per_server) {
+            wrong_clients.iter().for_each(|(author, reason)| {
+                log::warn!("Rejected update of debuggable {} from client {} (panel {:?}): {reason}", self.name, author.client, author.panel);
+                DebuggableServer::reject_update(&registration.server().read().unwrap(), registration.id(), author, reason, type_name::<Value>());
+            });
+            let wrong_clients = wrong_clients.into_keys().map(|author| author.client).collect::<HashSet<_>>();
+            if let Some((_, author, _)) = new_value.as_ref().filter(|(winner_server, _, _)| *winner_server == server_index) {
+                registration.server().read().unwrap().accept_update(registration.id(), author);
+            }
+            let who_to_notify = match new_value.as_ref() {
+                Some((winner_server, author, _)) if *winner_server == server_index => Some(Who::AllBut(author.client)),
+                Some(_) => Some(Who::All),
+                None if has_changed => Some(Who::All),
+                None if !wrong_clients.is_empty() => Some(Who::WrongClients(wrong_clients)),
+                None => None,
+            };
+            if who_to_notify.is_some() {
+                let json = if new_json.is_none() { current_json.clone() } else { new_json.clone().unwrap() };
+                registration.server().read().unwrap().notify_new_value(registration.id(), json, who_to_notify.unwrap());
+            }
+            acks.into_iter().for_each(|(author, request_id, accepted)| {
+                registration.server().read().unwrap().acknowledge_update(&author, request_id, accepted);
+            });
+        }
+        if new_value.is_none() {
+            self.change_detector.borrow_mut().remember(unsafe { &*self.value.get() });
+            return;
+        }
+        let (_, _, new_value) = new_value.unwrap();
+        unsafe { *self.value.get() = new_value; }
+        self.change_detector.borrow_mut().remember(unsafe { &*self.value.get() });
+        self.record_remote_update();
+        if let Some(on_remote_update) = self.on_remote_update.borrow_mut().as_mut() {
+            on_remote_update(unsafe { &*self.value.get() });
+        }
+    }
+
+    /// Only the latest update is considered, the earlier ones are acknowledged as not accepted.
+    fn select_incoming(&self, mut incoming_jsons: Vec<PendingUpdate>, current_json: &Option<String>, wrong_clients: &mut HashMap<Author, String>, acks: &mut Vec<(Author, u64, bool)>) -> Option<(Author, Value)> {
+        let PendingUpdate { author, request_id, json: new_json, .. } = incoming_jsons.pop()?;
+        Self::reject_superseded(incoming_jsons, acks);
+        let json_is_different = current_json.is_none() || new_json.ne(current_json.as_ref().unwrap());
+        let selected = if json_is_different { self.deserialize_incoming(&author, &new_json, wrong_clients) } else { None };
+        if let Some(request_id) = request_id {
+            acks.push((author.clone(), request_id, selected.is_some() || !json_is_different));
+        }
+        selected.map(|new_value| (author, new_value))
+    }
+
+    fn deserialize_incoming(&self, author: &Author, new_json: &str, wrong_clients: &mut HashMap<Author, String>) -> Option<Value> {
+        let new_value = match self.codec.from_json_detailed(new_json) {
+            Ok(new_value) => new_value,
+            Err(reason) => {
+                wrong_clients.insert(author.clone(), reason);
                 return None;
             }
-            Some((client, new_value))
-        })
-            .next().unwrap_or(None);
-
-        let who_to_notify = if new_value.is_some() {
-            Some(Who::AllBut(new_value.as_ref().unwrap().0))
-        } else if has_changed {
-            Some(Who::All)
-        } else if !has_changed && !wrong_clients.is_empty() {
-            Some(Who::WrongClients(wrong_clients))
-        } else {
-            None
         };
-        if who_to_notify.is_some() {
-            let json = if new_value.is_none() { current_json } else { new_value.as_ref().unwrap().1.to_json() };
-            self.server.write().unwrap().notify_new_value(self.id, json, who_to_notify.unwrap());
+        if self.codec.to_json(&new_value).is_none() {
+            wrong_clients.insert(author.clone(), "The value could not be serialized back".to_string());
+            return None;
         }
-        if new_value.is_none() { return; }
-        let (_, new_value) = new_value.unwrap();
-        unsafe { *self.value.get() = new_value; }
+        Some(new_value)
+    }
+
+    fn reject_superseded(incoming_jsons: Vec<PendingUpdate>, acks: &mut Vec<(Author, u64, bool)>) {
+        incoming_jsons.into_iter()
+            .filter_map(|update| Some((update.author, update.request_id?)))
+            .for_each(|(author, request_id)| acks.push((author, request_id, false)));
+    }
+}
+
+impl ServerRegistration {
+    fn register(server: Arc<RwLock<DebuggableServer>>, name: &str, options: &DebuggableOptions) -> Self {
+        let (id, registration) = Self::init_on(&server, name, options);
+        let locked_server = server.clone();
+        let locked_server = locked_server.read().unwrap();
+        Self::of_registered(server, &locked_server, id, registration)
+    }
+
+    fn of_registered(server: Arc<RwLock<DebuggableServer>>, locked_server: &DebuggableServer, id: usize, registration: u64) -> Self {
+        Self {
+            server: RefCell::new(server),
+            id: RefCell::new(locked_server.id_cell_of(id).unwrap()),
+            registration: Cell::new(registration),
+            server_shut_down: RefCell::new(locked_server.shut_down_flag()),
+        }
+    }
+
+    fn server(&self) -> Arc<RwLock<DebuggableServer>> {
+        self.server.borrow().clone()
+    }
+
+    fn is_server_alive(&self) -> bool {
+        !self.server_shut_down.borrow().load(Ordering::Relaxed)
+    }
+
+    /// Removes the debuggable from its server even if its value is kept there, as it's now served
+    /// by another one.
+    fn release_moved(&self) {
+        if !self.is_server_alive() { return; }
+        let server = self.server();
+        let Ok(server) = server.read() else { return; };
+        server.release_moved(self.id(), self.registration.get());
+    }
+
+    /// Moves the registration to the server that adopted its own, under a fresh id.
+    fn move_to(&self, new_server: Arc<RwLock<DebuggableServer>>, name: &str, options: &DebuggableOptions) {
+        self.release_moved();
+        let (id, registration) = Self::init_on(&new_server, name, options);
+        {
+            let locked_server = new_server.read().unwrap();
+            *self.id.borrow_mut() = locked_server.id_cell_of(id).unwrap();
+            *self.server_shut_down.borrow_mut() = locked_server.shut_down_flag();
+        }
+        self.registration.set(registration);
+        *self.server.borrow_mut() = new_server;
+    }
+
+    fn init_on(server: &Arc<RwLock<DebuggableServer>>, name: &str, options: &DebuggableOptions) -> (usize, u64) {
+        Self::init_on_locked(&server.write().unwrap(), name, options)
+    }
+
+    fn init_on_locked(server: &DebuggableServer, name: &str, options: &DebuggableOptions) -> (usize, u64) {
+        let declared_options;
+        let options = match server.declared_options_of(name) {
+            None => options,
+            Some(declared) => {
+                declared_options = options.adopting(&declared);
+                &declared_options
+            }
+        };
+        let (id, existed) = server.init_debuggable(name.to_string(), options.is_keep);
+        server.set_redactor(id, options.redactor.clone());
+        server.set_ttl(id, options.ttl);
+        server.init_hidden(id, options.hidden);
+        server.set_nullable(id, options.nullable);
+        server.init_order(id, options.order);
+        server.set_interpolable(id, options.interpolable);
+        server.set_text_diff(id, options.text_diff);
+        server.set_on_demand(id, options.on_demand);
+        if options.read_only {
+            server.set_read_only(id);
+        }
+        if let Some((min, max)) = options.numeric_bounds {
+            server.set_numeric_bounds(id, min, max);
+        }
+        server.broadcast_added(id, if existed { AddedOrigin::Replay } else { AddedOrigin::HostCode });
+        server.broadcast_metadata(id);
+        if let Some((composite, kind, slot)) = options.composite_member.as_ref() {
+            server.join_composite(composite, *kind, *slot, id);
+        }
+        (id, server.registration_of(id).unwrap())
+    }
+
+    fn ensure_registered(&self, name: &str, options: &DebuggableOptions) -> bool {
+        let server = self.server();
+        if server.read().unwrap().is_registration_alive(self.id(), self.registration.get()) { return false; }
+        let (id, registration) = Self::init_on(&server, name, options);
+        *self.id.borrow_mut() = server.read().unwrap().id_cell_of(id).unwrap();
+        self.registration.set(registration);
+        true
+    }
+
+    fn id(&self) -> usize {
+        self.id.borrow().load(Ordering::Relaxed)
+    }
+}
+
+impl<T: 'static> Debuggable<ClosureCodec<T>> {
+    pub fn with_codec<Name: ToString>(name: Name, initial_value: T, to_json: fn(&T) -> Option<String>, from_json: fn(&str) -> Option<T>) -> Self {
+        DebuggableBuilder::with_codec(name, initial_value, to_json, from_json).build()
+    }
+}
+
+impl<T> Debuggable<Option<T>> where Option<T>: JSONDeSerializable {
+    pub fn clear(&mut self) -> Option<T> {
+        self.deref_mut().take()
     }
 }
 
+// References returned by deref stay valid only until the next deref, which may apply a remote
+// update over the value, use borrow to hold a reference across accesses.
 impl<Value: JSONDeSerializable> Deref for Debuggable<Value> {
     type Target = Value;
 
     fn deref(&self) -> &Self::Target {
         unsafe {
-            self.process_changes();
+            self.process_sampled_changes();
             &*self.value.get()
         }
     }
@@ -130,20 +1018,82 @@ impl<Value: JSONDeSerializable> Deref for Debuggable<Value> {
 
 impl<Value: JSONDeSerializable> DerefMut for Debuggable<Value> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.process_changes();
+        self.process_sampled_changes();
         self.value.get_mut()
     }
 }
 
-impl<Value: JSONDeSerializable> Drop for Debuggable<Value> {
+impl<'debuggable, Value: JSONDeSerializable> Deref for DebuggableRef<'debuggable, Value> {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.debuggable.value.get() }
+    }
+}
+
+impl<'debuggable, Value: JSONDeSerializable> Drop for DebuggableRef<'debuggable, Value> {
     fn drop(&mut self) {
-        self.server.read().unwrap().remove_debuggable(self.id);
+        self.debuggable.active_borrows.set(self.debuggable.active_borrows.get() - 1);
     }
 }
 
+impl<'debuggable, Value: JSONDeSerializable> Deref for DebuggableRefMut<'debuggable, Value> {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.debuggable.value.get() }
+    }
+}
+
+impl<'debuggable, Value: JSONDeSerializable> DerefMut for DebuggableRefMut<'debuggable, Value> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.debuggable.value.get_mut()
+    }
+}
+
+impl Drop for ServerRegistration {
+    fn drop(&mut self) {
+        if !self.is_server_alive() { return; }
+        let server = self.server();
+        let Ok(server) = server.read() else { return; };
+        server.remove_debuggable(self.id(), self.registration.get());
+    }
+}
+
+/// Formats the value as of the last sync, use Debuggable::debug_synced to sync first.
 impl<Value> Debug for Debuggable<Value> where Value: Debug + JSONDeSerializable {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        let _ = self.deref();
-        unsafe { f.write_str(&*format!("{:?}", *self.value.get())) }
+        unsafe { Debug::fmt(&*self.value.get(), f) }
+    }
+}
+
+impl<Value> Display for Debuggable<Value> where Value: Display + JSONDeSerializable {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        unsafe { Display::fmt(&*self.value.get(), f) }
+    }
+}
+
+/// Serializes the value as of the last sync.
+#[cfg(feature = "use_serde")]
+impl<Value> serde::Serialize for Debuggable<Value> where Value: serde::Serialize + JSONDeSerializable {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        unsafe { (*self.value.get()).serialize(serializer) }
+    }
+}
+
+struct DebugSynced<'debuggable, Value: JSONDeSerializable> {
+    debuggable: &'debuggable Debuggable<Value>,
+}
+
+impl<'debuggable, Value> Debug for DebugSynced<'debuggable, Value> where Value: Debug + JSONDeSerializable {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        Debug::fmt(self.debuggable.deref(), f)
+    }
+}
+
+impl<Value> Debuggable<Value> where Value: Debug + JSONDeSerializable {
+    /// Formats the value after syncing it, unlike the Debug implementation.
+    pub fn debug_synced(&self) -> impl Debug + '_ {
+        DebugSynced { debuggable: self }
     }
 }
\ No newline at end of file
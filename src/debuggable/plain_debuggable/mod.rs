@@ -0,0 +1,53 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// Stand-in for a Debuggable that never talks to a server, used when instrumentation is stripped.
+/// It mirrors the parts of the Debuggable API that user code reads and writes values through.
+#[derive(Default, Clone, PartialEq)]
+pub struct PlainDebuggable<Value>(Value);
+
+impl<Value> PlainDebuggable<Value> {
+    pub fn new(initial_value: Value) -> Self {
+        Self(initial_value)
+    }
+
+    pub fn set(&mut self, value: Value) {
+        self.0 = value;
+    }
+
+    pub fn update<Update: FnOnce(&mut Value)>(&mut self, update: Update) {
+        update(&mut self.0)
+    }
+
+    pub fn borrow(&self) -> &Value {
+        &self.0
+    }
+
+    pub fn borrow_mut(&mut self) -> &mut Value {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl<Value> Deref for PlainDebuggable<Value> {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<Value> DerefMut for PlainDebuggable<Value> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<Value: Debug> Debug for PlainDebuggable<Value> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
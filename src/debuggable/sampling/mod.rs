@@ -0,0 +1,63 @@
+use std::cell::{Cell, OnceCell};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::clock::Clock;
+
+/// How often accessing a debuggable through Deref and DerefMut syncs it, see
+/// DebuggableBuilder::sample_every and DebuggableBuilder::sample_interval.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sampling {
+    #[default]
+    EveryAccess,
+    EveryNth(u32),
+    Interval(Duration),
+}
+
+/// Decides which accesses sync, kept in the debuggable itself so skipped ones take no locks.
+pub(crate) struct Sampler {
+    sampling: Sampling,
+    accesses: Cell<u32>,
+    last_sample: Cell<Option<Instant>>,
+    // Clock of the server, known once the debuggable is registered
+    clock: OnceCell<Arc<dyn Clock>>,
+}
+
+impl Sampler {
+    pub(crate) fn new(sampling: Sampling) -> Self {
+        Self { sampling, accesses: Cell::new(0), last_sample: Cell::new(None), clock: OnceCell::new() }
+    }
+
+    /// Whether this access syncs, counting it. The first access always does.
+    #[inline]
+    pub(crate) fn should_sample(&self) -> bool {
+        match self.sampling {
+            Sampling::EveryAccess => true,
+            Sampling::EveryNth(nth) => {
+                let accesses = self.accesses.get();
+                self.accesses.set(if accesses + 1 >= nth { 0 } else { accesses + 1 });
+                accesses == 0
+            }
+            Sampling::Interval(interval) => {
+                // Intervals are measured on the server's clock, every access syncs until it's known
+                let Some(clock) = self.clock.get() else { return true; };
+                let now = clock.now_instant();
+                match self.last_sample.get() {
+                    Some(last_sample) if now.saturating_duration_since(last_sample) < interval => false,
+                    _ => {
+                        self.last_sample.set(Some(now));
+                        true
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn needs_clock(&self) -> bool {
+        matches!(self.sampling, Sampling::Interval(_)) && self.clock.get().is_none()
+    }
+
+    pub(crate) fn use_clock(&self, clock: Arc<dyn Clock>) {
+        let _ = self.clock.set(clock);
+    }
+}
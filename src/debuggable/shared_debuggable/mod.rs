@@ -0,0 +1,67 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+use crate::debuggable::Debuggable;
+use crate::serializable::JSONDeSerializable;
+
+/// Handle to a debuggable owned by several parts of an application, possibly on different
+/// threads. Every clone shares the same registration, which is removed when the last one drops.
+pub struct SharedDebuggable<Value: JSONDeSerializable> {
+    debuggable: Arc<Mutex<SendDebuggable<Value>>>,
+}
+
+struct SendDebuggable<Value: JSONDeSerializable>(Debuggable<Value>);
+
+// A Debuggable is only kept from being Send by its remote update handler and its on demand
+// provider, closures that may capture values bound to their thread, which SharedDebuggable::new
+// takes out of it. The mutex then ensures a single thread accesses it at a time.
+unsafe impl<Value: JSONDeSerializable + Send> Send for SendDebuggable<Value> {}
+
+impl<Value: JSONDeSerializable + Send> SharedDebuggable<Value> {
+    pub(crate) fn new(mut debuggable: Debuggable<Value>) -> Self {
+        if debuggable.on_remote_update.get_mut().take().is_some() {
+            log::warn!("Debuggable {} is shared between threads, its remote update handler is ignored", debuggable.name);
+        }
+        if debuggable.provider.get_mut().take().is_some() {
+            log::warn!("Debuggable {} is shared between threads, its on demand provider is ignored", debuggable.name);
+        }
+        Self { debuggable: Arc::new(Mutex::new(SendDebuggable(debuggable))) }
+    }
+
+    pub fn get_cloned(&self) -> Value where Value: Clone {
+        (*self.debuggable.lock().unwrap().0).clone()
+    }
+
+    pub fn with<Read: FnOnce(&Value) -> Output, Output>(&self, read: Read) -> Output {
+        read(&*self.debuggable.lock().unwrap().0)
+    }
+
+    pub fn set(&self, value: Value) {
+        self.debuggable.lock().unwrap().0.set(value);
+    }
+
+    pub fn update<Update: FnOnce(&mut Value)>(&self, update: Update) {
+        self.debuggable.lock().unwrap().0.update(update);
+    }
+
+    /// Sends local changes to clients and applies their updates.
+    pub fn sync(&self) {
+        self.debuggable.lock().unwrap().0.sync();
+    }
+
+    pub fn handles(&self) -> usize {
+        Arc::strong_count(&self.debuggable)
+    }
+}
+
+impl<Value: JSONDeSerializable> Clone for SharedDebuggable<Value> {
+    fn clone(&self) -> Self {
+        Self { debuggable: self.debuggable.clone() }
+    }
+}
+
+impl<Value: JSONDeSerializable + Send + Debug> Debug for SharedDebuggable<Value> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.with(|value| value.fmt(f))
+    }
+}
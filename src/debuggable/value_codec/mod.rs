@@ -0,0 +1,88 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use crate::debuggable::DebuggableBuilder;
+use crate::serializable::JSONDeSerializable;
+
+pub type ToJson<Value> = Box<dyn Fn(&Value) -> Option<String> + Send + Sync>;
+pub type FromJson<Value> = Box<dyn Fn(&str) -> Option<Value> + Send + Sync>;
+
+/// How a debuggable serializes its value, either through JSONDeSerializable or through closures
+/// given to DebuggableBuilder::with_serializer.
+pub(crate) enum ValueCodec<Value> {
+    Default,
+    Custom {
+        to_json: ToJson<Value>,
+        from_json: FromJson<Value>,
+    },
+}
+
+impl<Value: JSONDeSerializable> ValueCodec<Value> {
+    pub(crate) fn to_json(&self, value: &Value) -> Option<String> {
+        match self {
+            ValueCodec::Default => value.to_json(),
+            ValueCodec::Custom { to_json, .. } => to_json(value),
+        }
+    }
+
+    pub(crate) fn from_json_detailed(&self, json: &str) -> Result<Value, String> {
+        match self {
+            ValueCodec::Default => Value::from_json_detailed(json),
+            ValueCodec::Custom { from_json, .. } => from_json(json)
+                .ok_or_else(|| format!("The custom deserializer rejected {json}")),
+        }
+    }
+}
+
+/// Wraps a value that has no JSON representation of its own, debuggables of it are built through
+/// DebuggableBuilder::new_unserializable, which requires giving one.
+#[derive(Default, Clone, PartialEq)]
+pub struct Unserializable<T>(pub T);
+
+impl<T> JSONDeSerializable for Unserializable<T> {
+    fn to_json(&self) -> Option<String> {
+        None
+    }
+
+    fn from_json(_json: &str) -> Option<Self> {
+        None
+    }
+}
+
+impl<T> Deref for Unserializable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Unserializable<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Debug> Debug for Unserializable<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub struct UnserializableBuilder<T> {
+    name: String,
+    initial_value: T,
+}
+
+impl<T: 'static> UnserializableBuilder<T> {
+    pub(crate) fn new(name: String, initial_value: T) -> Self {
+        Self { name, initial_value }
+    }
+
+    pub fn with_serializer<ToJsonFn, FromJsonFn>(self, to_json: ToJsonFn, from_json: FromJsonFn) -> DebuggableBuilder<Unserializable<T>>
+        where ToJsonFn: Fn(&T) -> Option<String> + Send + Sync + 'static,
+              FromJsonFn: Fn(&str) -> Option<T> + Send + Sync + 'static {
+        DebuggableBuilder::new(self.name, Unserializable(self.initial_value))
+            .with_serializer(move |value: &Unserializable<T>| to_json(&value.0), move |json: &str| from_json(json).map(Unserializable))
+    }
+}
@@ -1,17 +1,34 @@
 use std::mem::MaybeUninit;
-use std::net::TcpListener;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
 use std::sync::{Arc, Once, RwLock};
 
-use crate::server::debuggable_server_builder::DebuggableServerBuilder;
+use crate::server::callbacks::CallbackScope;
+use crate::server::debuggable_server_builder::{DebuggableServerBuilder, DEFAULT_FALLBACK_PORTS};
 use crate::server::DebuggableServer;
 
 static mut DEFAULT_SERVER: MaybeUninit<Arc<RwLock<DebuggableServer>>> = MaybeUninit::uninit();
 static DEFAULT_SERVER_ONCE: Once = Once::new();
-static mut DEFAULT_SERVER_INITIALIZER: fn() -> DebuggableServerBuilder = || DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:5050").unwrap());
+// Binds 127.0.0.1:5050, or the next free port of DEFAULT_FALLBACK_PORTS if another application has it
+static mut DEFAULT_SERVER_INITIALIZER: fn() -> DebuggableServerBuilder =
+    || DebuggableServerBuilder::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_FALLBACK_PORTS.start)));
 
+/// Server debuggables register on unless given another, built on first use from the initializer.
+///
+/// ```
+/// use std::net::TcpListener;
+/// use debug_monitor::debuggable::Debuggable;
+/// use debug_monitor::default_server::{default_server, set_default_server_initializer};
+/// use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+///
+/// set_default_server_initializer(|| DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()));
+/// let score = Debuggable::new("score", 0);
+/// assert_eq!(*score, 0);
+/// assert!(default_server().read().unwrap().debuggable_id_of("score").is_some());
+/// ```
 pub fn default_server() -> Arc<RwLock<DebuggableServer>> {
     unsafe {
         DEFAULT_SERVER_ONCE.call_once(|| {
+            let _callback = CallbackScope::enter();
             let server_builder = DEFAULT_SERVER_INITIALIZER();
             DEFAULT_SERVER.write(Arc::new(RwLock::new(server_builder.build())));
         });
@@ -25,4 +42,69 @@ pub fn set_default_server_initializer(initializer: fn() -> DebuggableServerBuild
 
 pub fn is_default_server_initialized() -> bool {
     DEFAULT_SERVER_ONCE.is_completed()
-}
\ No newline at end of file
+}
+
+/// Shuts the default server down: its clients are sent RemoveAll and disconnected. default_server
+/// keeps returning it afterwards, and debuggables on it keep their values locally. When it wasn't
+/// used yet, it's replaced by a server that's shut down from the start.
+///
+/// ```
+/// use std::net::TcpListener;
+/// use debug_monitor::debuggable::Debuggable;
+/// use debug_monitor::default_server::{default_server, set_default_server_initializer};
+/// use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+///
+/// set_default_server_initializer(|| DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()));
+/// let mut score = Debuggable::new("score", 0);
+/// debug_monitor::shutdown_default_server();
+/// *score = 3;
+/// assert_eq!(*score, 3);
+/// assert!(default_server().read().unwrap().is_shut_down());
+/// ```
+pub fn shutdown_default_server() {
+    let server = initialized_or_inert();
+    let Ok(server) = server.read() else { return; };
+    server.shutdown();
+}
+
+/// Shuts the default server down when the process exits normally, see shutdown_default_server.
+/// Installing it more than once has no further effect.
+#[cfg(feature = "exit-hook")]
+pub fn install_exit_hook() {
+    static EXIT_HOOK_ONCE: Once = Once::new();
+    EXIT_HOOK_ONCE.call_once(|| {
+        if unsafe { libc::atexit(shutdown_at_exit) } != 0 {
+            log::warn!("Could not install the exit hook shutting down the default server");
+        }
+    });
+}
+
+#[cfg(feature = "exit-hook")]
+extern "C" fn shutdown_at_exit() {
+    // Unwinding out of an exit handler aborts, and a server held by another thread at exit is left as is
+    let _ = std::panic::catch_unwind(|| {
+        if !is_default_server_initialized() { return; }
+        let server = default_server();
+        let Ok(server) = server.try_read() else { return; };
+        server.shutdown();
+    });
+}
+
+fn initialized_or_inert() -> Arc<RwLock<DebuggableServer>> {
+    unsafe {
+        DEFAULT_SERVER_ONCE.call_once(|| {
+            let server = inert_server();
+            server.shutdown();
+            DEFAULT_SERVER.write(Arc::new(RwLock::new(server)));
+        });
+        DEFAULT_SERVER.assume_init_ref().clone()
+    }
+}
+
+/// Listens on an ephemeral loopback port only because every server has a listener.
+fn inert_server() -> DebuggableServer {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap());
+    #[cfg(feature = "discovery")]
+    let builder = builder.announces(false);
+    builder.build()
+}
@@ -0,0 +1,114 @@
+use std::{fs, io, process};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::protocol::framing::{self, Endmark};
+use crate::serializable::{ClientUnitMessage, JSONDeSerializable};
+use crate::server::{DebuggableServer, OutgoingTransform};
+
+pub struct DirClient {
+    dir: PathBuf,
+    client_id: usize,
+    session: u64,
+    next_transaction: u64,
+    endmark: Endmark,
+    outgoing_transform: Option<OutgoingTransform>,
+}
+
+impl DirClient {
+    pub fn new<Dir: AsRef<Path>>(dir: Dir, client_id: usize) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let counter_path = Self::counter_path_of(&dir, client_id);
+        let next_transaction = match fs::read_to_string(&counter_path) {
+            Ok(counter) => counter.trim().parse::<u64>()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid transaction counter in {counter_path:?}: {error}")))?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => 0,
+            Err(error) => return Err(error),
+        };
+        let endmark = Endmark::new_unchecked(framing::DEFAULT_ENDMARK, framing::DEFAULT_ESCAPE);
+        Ok(Self { dir, client_id, session: Self::new_session(), next_transaction, endmark, outgoing_transform: None })
+    }
+
+    pub fn for_server(server: &DebuggableServer, client_id: usize) -> io::Result<Self> {
+        let read_dir = server.read_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "The server has no read dir"))?;
+        let (endmark, endmark_escape) = {
+            let server = server.read();
+            let end_mark = server.message_endmark();
+            (end_mark.string().to_string(), end_mark.escape().to_string())
+        };
+        Ok(Self::new(read_dir, client_id)?.endmark(endmark, endmark_escape))
+    }
+
+    /// Escapes messages for the endmark a server was given through
+    /// DebuggableServerBuilder::message_endmark, rather than for framing::DEFAULT_ENDMARK.
+    pub fn endmark<EndmarkString: ToString, Escape: ToString>(mut self, endmark: EndmarkString, endmark_escape: Escape) -> Self {
+        self.endmark = Endmark::new_unchecked(endmark, endmark_escape);
+        self
+    }
+
+    /// Counterpart of the server's incoming transform, applied to every message before writing it.
+    pub fn outgoing_transform(mut self, outgoing_transform: OutgoingTransform) -> Self {
+        self.outgoing_transform = Some(outgoing_transform);
+        self
+    }
+
+    pub fn client_id(&self) -> usize {
+        self.client_id
+    }
+
+    /// Random id of this instance, telling the server its transactions apart from those written
+    /// by earlier runs of the same client.
+    pub fn session(&self) -> u64 {
+        self.session
+    }
+
+    pub fn next_transaction(&self) -> u64 {
+        self.next_transaction
+    }
+
+    pub fn send_update<ValueJson: ToString>(&mut self, debuggable_id: usize, value_json: ValueJson) -> io::Result<()> {
+        self.send(ClientUnitMessage::UpdateValue { id: debuggable_id, new_value: value_json.to_string(), request_id: None, panel: None })
+    }
+
+    pub fn send_renotify(&mut self) -> io::Result<()> {
+        self.send(ClientUnitMessage::Renotify)
+    }
+
+    fn send(&mut self, message: ClientUnitMessage) -> io::Result<()> {
+        let message = message.to_json()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Could not serialize message"))?;
+        let message = match self.outgoing_transform.as_ref() {
+            None => message,
+            Some(outgoing_transform) => outgoing_transform(&message),
+        };
+        let message = framing::escape(&message, &self.endmark).into_owned();
+        let transaction = self.next_transaction;
+        // The counter is persisted before the transaction is written, so a crash in between skips a
+        // number instead of reusing it
+        self.next_transaction += 1;
+        Self::write_atomically(&Self::counter_path_of(&self.dir, self.client_id), &self.next_transaction.to_string())?;
+        let transaction_path = self.dir.join(format!("client-{}-session-{}-transaction-{}", self.client_id, self.session, transaction));
+        Self::write_atomically(&transaction_path, &message)
+    }
+
+    fn new_session() -> u64 {
+        let mut hasher = RandomState::new().build_hasher();
+        process::id().hash(&mut hasher);
+        SystemTime::now().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+        let file_name = path.file_name().and_then(|file_name| file_name.to_str()).unwrap_or_default();
+        let temporary_path = path.with_file_name(format!(".{file_name}.tmp"));
+        fs::write(&temporary_path, contents)?;
+        fs::rename(&temporary_path, path)
+    }
+
+    fn counter_path_of(dir: &Path, client_id: usize) -> PathBuf {
+        dir.join(format!(".dir-client-{client_id}-counter"))
+    }
+}
@@ -0,0 +1,111 @@
+//! Servers periodically announce themselves with a UDP beacon on DISCOVERY_PORT, so monitors can
+//! find every instrumented application running around them through scan.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Socket, Type};
+
+pub const DISCOVERY_PORT: u16 = 5049;
+
+/// Servers never send more than one beacon per interval.
+pub const BEACON_INTERVAL: Duration = Duration::from_secs(1);
+
+const MAX_BEACON_LEN: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredServer {
+    /// Name of the process hosting the server.
+    pub name: String,
+    pub addr: SocketAddr,
+    pub debuggables: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Beacon {
+    socket: UdpSocket,
+    target: SocketAddr,
+    last_sent: Option<Instant>,
+    /// Address clients should connect to when it isn't the server's own, as happens behind TLS.
+    pub(crate) announced_addr: Option<SocketAddr>,
+}
+
+impl Beacon {
+    /// Beacons of servers listening on loopback stay on it, others are broadcast.
+    pub(crate) fn default_target(server_addr: Option<SocketAddr>) -> SocketAddr {
+        let is_loopback = server_addr.is_some_and(|server_addr| server_addr.ip().is_loopback());
+        let ip = if is_loopback { Ipv4Addr::LOCALHOST } else { Ipv4Addr::BROADCAST };
+        SocketAddr::new(IpAddr::V4(ip), DISCOVERY_PORT)
+    }
+
+    pub(crate) fn bind(target: SocketAddr) -> io::Result<Self> {
+        let local_ip = match target.ip() {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind(SocketAddr::new(local_ip, 0))?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, target, last_sent: None, announced_addr: None })
+    }
+
+    pub(crate) fn is_due(&self, now: Instant) -> bool {
+        self.last_sent.map_or(true, |last_sent| now.saturating_duration_since(last_sent) >= BEACON_INTERVAL)
+    }
+
+    pub(crate) fn send(&mut self, now: Instant, announced: &DiscoveredServer) {
+        self.last_sent = Some(now);
+        let Ok(beacon) = serde_json::to_string(announced) else { return; };
+        if let Err(error) = self.socket.send_to(beacon.as_bytes(), self.target) {
+            log::debug!("Could not send discovery beacon to {}: {}", self.target, error);
+        }
+    }
+}
+
+pub(crate) fn process_name() -> String {
+    std::env::current_exe().ok()
+        .and_then(|executable| executable.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Listens for beacons during the given time, returning each server found once. Servers listening
+/// on every interface are reported with the address their beacon came from.
+pub fn scan(timeout: Duration) -> Vec<DiscoveredServer> {
+    scan_port(DISCOVERY_PORT, timeout).unwrap_or_else(|error| {
+        log::warn!("Could not listen for discovery beacons: {}", error);
+        Vec::new()
+    })
+}
+
+pub fn scan_port(port: u16, timeout: Duration) -> io::Result<Vec<DiscoveredServer>> {
+    // Shared so several monitors on one machine can scan at once
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None)?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port).into())?;
+    let socket: UdpSocket = socket.into();
+    let give_up_at = Instant::now() + timeout;
+    let mut found = HashMap::new();
+    let mut buffer = [0; MAX_BEACON_LEN];
+    loop {
+        let remaining = give_up_at.saturating_duration_since(Instant::now());
+        if remaining.is_zero() { break; }
+        socket.set_read_timeout(Some(remaining))?;
+        let (len, sender) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => continue,
+            Err(error) => return Err(error),
+        };
+        let Ok(mut server) = serde_json::from_slice::<DiscoveredServer>(&buffer[..len]) else { continue; };
+        if server.addr.ip().is_unspecified() {
+            server.addr.set_ip(sender.ip());
+        }
+        found.insert(server.addr, server);
+    }
+    let mut found = found.into_values().collect::<Vec<_>>();
+    found.sort_by_key(|server| server.addr);
+    Ok(found)
+}
@@ -0,0 +1,19 @@
+use std::fmt::{Display, Formatter};
+
+/// Problems the host can observe without them interrupting it, such as through
+/// DebuggableBuilder::on_serialize_error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugMonitorError {
+    /// The value of a debuggable could not be serialized, clients see it unset until it can.
+    Serialize { name: String, type_name: &'static str },
+}
+
+impl Display for DebugMonitorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DebugMonitorError::Serialize { name, type_name } => write!(f, "Value of debuggable {name} ({type_name}) could not be serialized"),
+        }
+    }
+}
+
+impl std::error::Error for DebugMonitorError {}
@@ -1,6 +1,36 @@
+#[cfg(feature = "server")]
 pub mod server;
+#[cfg(feature = "server")]
 pub mod debuggable;
 pub mod serializable;
+pub mod protocol;
+#[cfg(feature = "server")]
 pub mod default_server;
+#[cfg(feature = "server")]
+pub mod scoped_server;
+#[cfg(feature = "server")]
+pub mod dir_client;
+#[cfg(feature = "server")]
+pub mod client;
+pub mod clock;
+#[cfg(feature = "server")]
+pub mod testing;
+pub mod snapshot;
+pub mod error;
+#[cfg(feature = "server")]
+mod macros;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "discovery")]
+pub mod discovery;
+#[cfg(feature = "schema")]
+pub mod schema;
 
-pub use simple_tcp;
\ No newline at end of file
+#[cfg(feature = "server")]
+pub use simple_tcp;
+#[cfg(feature = "server")]
+pub use default_server::shutdown_default_server;
+#[cfg(feature = "server")]
+pub use server::crash_dump::{install_panic_hook, DumpSelection};
+#[cfg(feature = "exit-hook")]
+pub use default_server::install_exit_hook;
\ No newline at end of file
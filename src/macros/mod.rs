@@ -0,0 +1,30 @@
+#[cfg(not(any(feature = "strip", all(feature = "strip_in_release", not(debug_assertions)))))]
+#[macro_export]
+macro_rules! debuggable {
+    ($name:expr, $initial_value:expr $(,)?) => {
+        $crate::debuggable::DebuggableBuilder::new($name, $initial_value).build()
+    };
+}
+
+// The name is never expanded, so it doesn't end up in the binary
+#[cfg(any(feature = "strip", all(feature = "strip_in_release", not(debug_assertions))))]
+#[macro_export]
+macro_rules! debuggable {
+    ($name:expr, $initial_value:expr $(,)?) => {
+        $crate::debuggable::plain_debuggable::PlainDebuggable::new($initial_value)
+    };
+}
+
+#[cfg(not(any(feature = "strip", all(feature = "strip_in_release", not(debug_assertions)))))]
+#[macro_export]
+macro_rules! debuggable_scope {
+    ($($instrumentation:tt)*) => {
+        { $($instrumentation)* }
+    };
+}
+
+#[cfg(any(feature = "strip", all(feature = "strip_in_release", not(debug_assertions))))]
+#[macro_export]
+macro_rules! debuggable_scope {
+    ($($instrumentation:tt)*) => {};
+}
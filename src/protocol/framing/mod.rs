@@ -0,0 +1,119 @@
+//! How messages are delimited and escaped on the wire, as done by servers, clients and the read
+//! directory alike, so clients written in other languages can port these few functions as they are.
+//!
+//! Every message is followed by the endmark, and endmarks within the message are replaced by the
+//! escape. A frame is unescaped by replacing every escape in it back by the endmark, so messages
+//! must not contain the escape itself, which would be unescaped into an endmark as well.
+//!
+//! ```
+//! use debug_monitor::protocol::framing::{decode_frames, encode_frame, Endmark};
+//!
+//! let endmark = Endmark::new("<end>", "<escaped>").unwrap();
+//! let mut received = format!("{}{}", encode_frame("a<end>b", &endmark), encode_frame("c", &endmark));
+//! received.push_str("partial");
+//! assert_eq!(decode_frames(&mut received, &endmark), vec!["a<end>b".to_string(), "c".to_string()]);
+//! assert_eq!(received, "partial");
+//! ```
+
+use std::borrow::Cow;
+use std::mem;
+
+use crate::serializable::framing::validate_endmark;
+
+/// Endmark of servers not given another through DebuggableServerBuilder::message_endmark. Neither
+/// it nor its escape, DEFAULT_ESCAPE, are ever written raw by a JSON serializer.
+pub const DEFAULT_ENDMARK: &str = "\u{1e}";
+pub const DEFAULT_ESCAPE: &str = "\u{1b}";
+
+/// The endmark terminating messages and the escape replacing it within them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endmark {
+    endmark: String,
+    escape: String,
+}
+
+impl Endmark {
+    /// Fails as serializable::framing::validate_endmark does.
+    pub fn new<EndmarkString: ToString, Escape: ToString>(endmark: EndmarkString, escape: Escape) -> Result<Self, String> {
+        let endmark = Self::new_unchecked(endmark, escape);
+        validate_endmark(&endmark.endmark, &endmark.escape)?;
+        Ok(endmark)
+    }
+
+    /// For endmarks already validated, like those of running servers.
+    pub(crate) fn new_unchecked<EndmarkString: ToString, Escape: ToString>(endmark: EndmarkString, escape: Escape) -> Self {
+        Self { endmark: endmark.to_string(), escape: escape.to_string() }
+    }
+
+    pub fn endmark(&self) -> &str {
+        &self.endmark
+    }
+
+    pub fn escape(&self) -> &str {
+        &self.escape
+    }
+}
+
+/// Replaces every endmark in the payload by the escape, borrowing it when there's none.
+pub fn escape<'payload>(payload: &'payload str, endmark: &Endmark) -> Cow<'payload, str> {
+    if payload.contains(&*endmark.endmark) {
+        Cow::Owned(payload.replace(&*endmark.endmark, &endmark.escape))
+    } else {
+        Cow::Borrowed(payload)
+    }
+}
+
+/// Replaces every escape in the escaped payload back by the endmark.
+pub fn unescape(escaped: &str, endmark: &Endmark) -> String {
+    escaped.replace(&*endmark.escape, &endmark.endmark)
+}
+
+/// Same as unescape, but as unescaping never makes the contents longer when the escape is at least
+/// as long as the endmark, the bytes are compacted in their own buffer rather than copied into a
+/// new String.
+pub fn unescape_in_place(contents: &mut String, endmark: &Endmark) {
+    let (endmark, escape) = (&*endmark.endmark, &*endmark.escape);
+    if escape.is_empty() || !contents.contains(escape) { return; }
+    if escape.len() < endmark.len() {
+        *contents = contents.replace(escape, endmark);
+        return;
+    }
+    let mut bytes = mem::take(contents).into_bytes();
+    let (mut read, mut written) = (0, 0);
+    while read < bytes.len() {
+        if bytes[read..].starts_with(escape.as_bytes()) {
+            bytes[written..written + endmark.len()].copy_from_slice(endmark.as_bytes());
+            read += escape.len();
+            written += endmark.len();
+        } else {
+            bytes[written] = bytes[read];
+            read += 1;
+            written += 1;
+        }
+    }
+    bytes.truncate(written);
+    // Whole UTF-8 sequences are only ever replaced by other whole sequences
+    *contents = String::from_utf8(bytes).unwrap();
+}
+
+/// Escapes the payload and terminates it with the endmark.
+pub fn encode_frame(payload: &str, endmark: &Endmark) -> String {
+    let escaped = escape(payload, endmark);
+    let mut frame = String::with_capacity(escaped.len() + endmark.endmark.len());
+    frame.push_str(&escaped);
+    frame.push_str(&endmark.endmark);
+    frame
+}
+
+/// Takes every complete frame out of the buffer and returns their payloads unescaped, leaving any
+/// partial trailing frame in it until the rest is received.
+pub fn decode_frames(buffer: &mut String, endmark: &Endmark) -> Vec<String> {
+    let mut payloads = Vec::new();
+    let mut consumed = 0;
+    while let Some(frame_end) = buffer[consumed..].find(&*endmark.endmark) {
+        payloads.push(unescape(&buffer[consumed..consumed + frame_end], endmark));
+        consumed += frame_end + endmark.endmark.len();
+    }
+    buffer.drain(..consumed);
+    payloads
+}
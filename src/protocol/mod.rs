@@ -0,0 +1,22 @@
+//! Framing shared by servers and clients: messages are terminated by an endmark, and endmarks
+//! inside them are replaced by an escape. Free of any transport, so it builds without the server
+//! feature.
+
+use crate::protocol::framing::Endmark;
+
+pub mod framing;
+
+/// Escapes every endmark in the message and terminates it with one, see framing::encode_frame.
+pub fn frame(message: &str, endmark: &str, escape: &str) -> String {
+    framing::encode_frame(message, &Endmark::new_unchecked(endmark, escape))
+}
+
+/// Turns the escapes of a received frame, without its terminating endmark, back into endmarks.
+pub fn unescape(frame: &str, endmark: &str, escape: &str) -> String {
+    framing::unescape(frame, &Endmark::new_unchecked(endmark, escape))
+}
+
+/// Replaces every escaped endmark by the endmark itself, see framing::unescape_in_place.
+pub fn unescape_in_place(contents: &mut String, endmark: &str, escape: &str) {
+    framing::unescape_in_place(contents, &Endmark::new_unchecked(endmark, escape))
+}
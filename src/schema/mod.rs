@@ -0,0 +1,94 @@
+//! Machine-readable description of the wire protocol, so clients written in other languages can
+//! generate their message types instead of keeping them in sync by hand.
+//!
+//! Enums are externally tagged: a variant with fields is an object whose only key is the variant
+//! name, holding an object of its fields, and a variant without fields is its name as a string.
+//! Field types are "integer", "number", "boolean", "string", "array<T>", "optional<T>" or the
+//! name of another type the schema lists.
+
+use crate::serializable::framing::{Framing, FramingInfo};
+use crate::serializable::text_patch::TextHunk;
+use crate::serializable::{AddedOrigin, ClientUnitMessage, CompositeKind, GroupedUpdate, NotifyEntry, RemoveReason, ServerMessage, BASE_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+pub(crate) enum TypeSchema {
+    Enum { name: &'static str, variants: &'static [VariantSchema] },
+    Struct { name: &'static str, fields: &'static [FieldSchema] },
+}
+
+pub(crate) struct VariantSchema {
+    pub(crate) name: &'static str,
+    pub(crate) fields: &'static [FieldSchema],
+}
+
+pub(crate) struct FieldSchema {
+    pub(crate) name: &'static str,
+    pub(crate) rust_type: &'static str,
+}
+
+// Messages first, then the types their fields refer to
+const PROTOCOL_TYPES: [TypeSchema; 10] = [
+    ServerMessage::TYPE_SCHEMA,
+    ClientUnitMessage::TYPE_SCHEMA,
+    NotifyEntry::TYPE_SCHEMA,
+    AddedOrigin::TYPE_SCHEMA,
+    RemoveReason::TYPE_SCHEMA,
+    CompositeKind::TYPE_SCHEMA,
+    GroupedUpdate::TYPE_SCHEMA,
+    FramingInfo::TYPE_SCHEMA,
+    Framing::TYPE_SCHEMA,
+    TextHunk::TYPE_SCHEMA,
+];
+
+/// The schema as JSON, holding the protocol versions, which type servers and clients send their
+/// messages as, and the variants and fields of every type involved.
+///
+/// ```
+/// let schema = debug_monitor::schema::protocol_schema();
+/// assert!(schema.contains("\"server_messages\":\"ServerMessage\""));
+/// ```
+pub fn protocol_schema() -> String {
+    let types = PROTOCOL_TYPES.iter().map(type_json).collect::<Vec<_>>().join(",");
+    format!("{{\"protocol_version\":{PROTOCOL_VERSION},\"base_protocol_version\":{BASE_PROTOCOL_VERSION},\"enum_encoding\":\"externally_tagged\",\
+             \"server_messages\":\"ServerMessage\",\"client_messages\":\"ClientUnitMessage\",\"types\":[{types}]}}")
+}
+
+fn type_json(type_schema: &TypeSchema) -> String {
+    match type_schema {
+        TypeSchema::Enum { name, variants } => {
+            let variants = variants.iter()
+                .map(|variant| format!("{{\"name\":\"{}\",\"fields\":{}}}", variant.name, fields_json(variant.fields)))
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{{\"name\":\"{name}\",\"kind\":\"enum\",\"variants\":[{variants}]}}")
+        }
+        TypeSchema::Struct { name, fields } => format!("{{\"name\":\"{name}\",\"kind\":\"struct\",\"fields\":{}}}", fields_json(fields)),
+    }
+}
+
+fn fields_json(fields: &[FieldSchema]) -> String {
+    let fields = fields.iter()
+        .map(|field| format!("{{\"name\":\"{}\",\"type\":\"{}\"}}", field.name, wire_type_of(field.rust_type)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{fields}]")
+}
+
+/// Type of the field as the schema names it, from the Rust type it's declared with.
+fn wire_type_of(rust_type: &str) -> String {
+    let rust_type = rust_type.chars().filter(|character| !character.is_whitespace()).collect::<String>();
+    let generic_of = |wrapper: &str| rust_type.strip_prefix(wrapper).and_then(|rest| rest.strip_prefix('<')).and_then(|rest| rest.strip_suffix('>')).map(str::to_string);
+    if let Some(inner) = generic_of("Option") {
+        return format!("optional<{}>", wire_type_of(&inner));
+    }
+    if let Some(inner) = generic_of("Vec") {
+        return format!("array<{}>", wire_type_of(&inner));
+    }
+    match rust_type.as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "integer".to_string(),
+        "f32" | "f64" => "number".to_string(),
+        "bool" => "boolean".to_string(),
+        "String" => "string".to_string(),
+        // Paths like framing::FramingInfo are listed under their own name
+        path => path.rsplit("::").next().unwrap_or(path).to_string(),
+    }
+}
@@ -0,0 +1,66 @@
+use std::mem;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, RwLock};
+
+use socket2::{Domain, Socket, Type};
+
+use crate::server::debuggable_server_builder::DebuggableServerBuilder;
+use crate::server::DebuggableServer;
+
+/// Server listening on an ephemeral loopback port, shut down when dropped, which also closes its
+/// listener so the port is free again even if debuggables registered on it are still alive.
+///
+/// ```
+/// use debug_monitor::debuggable::DebuggableBuilder;
+/// use debug_monitor::scoped_server::ScopedServer;
+///
+/// let server = ScopedServer::new();
+/// assert!(server.addr().ip().is_loopback());
+/// let debuggable = DebuggableBuilder::new("name", "Ferris".to_string()).scoped(&server).build();
+/// drop(server);
+/// assert!(!debuggable.is_server_alive());
+/// assert_eq!(debuggable.as_str(), "Ferris");
+/// ```
+pub struct ScopedServer {
+    server: Arc<RwLock<DebuggableServer>>,
+    addr: SocketAddr,
+}
+
+impl ScopedServer {
+    pub fn new() -> ScopedServer {
+        Self::from_builder(DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()))
+    }
+
+    pub fn from_builder(builder: DebuggableServerBuilder) -> ScopedServer {
+        let server = builder.build();
+        let addr = server.local_addr().unwrap();
+        Self { server: Arc::new(RwLock::new(server)), addr }
+    }
+
+    pub fn handle(&self) -> Arc<RwLock<DebuggableServer>> {
+        self.server.clone()
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for ScopedServer {
+    fn drop(&mut self) {
+        let Ok(mut server) = self.server.write() else { return; };
+        server.shutdown();
+        // Debuggables outliving the scope still hold the handle, so the server behind it is swapped
+        // for a shut down one without a bound socket, dropping the listener along with the server
+        if let Some(released) = released_server() {
+            drop(mem::replace(&mut *server, released));
+        }
+    }
+}
+
+fn released_server() -> Option<DebuggableServer> {
+    let unbound = Socket::new(Domain::IPV4, Type::STREAM, None).ok()?;
+    let released = DebuggableServer::new(TcpListener::from(unbound));
+    released.shutdown();
+    Some(released)
+}
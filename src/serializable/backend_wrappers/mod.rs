@@ -0,0 +1,66 @@
+#[cfg(any(feature = "use_serde", feature = "use_nanoserde"))]
+use std::ops::{Deref, DerefMut};
+
+#[cfg(any(feature = "use_serde", feature = "use_nanoserde"))]
+use crate::serializable::JSONDeSerializable;
+
+#[cfg(feature = "use_serde")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SerdeJson<T>(pub T);
+
+#[cfg(feature = "use_serde")]
+impl<T> JSONDeSerializable for SerdeJson<T> where T: serde::Serialize + serde::de::DeserializeOwned {
+    fn to_json(&self) -> Option<String> {
+        serde_json::to_string(&self.0).ok()
+    }
+
+    fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok().map(Self)
+    }
+
+    fn from_json_detailed(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map(Self).map_err(|error| error.to_string())
+    }
+}
+
+#[cfg(feature = "use_nanoserde")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NanoJson<T>(pub T);
+
+#[cfg(feature = "use_nanoserde")]
+impl<T> JSONDeSerializable for NanoJson<T> where T: nanoserde::SerJson + nanoserde::DeJson {
+    fn to_json(&self) -> Option<String> {
+        Some(self.0.serialize_json())
+    }
+
+    fn from_json(json: &str) -> Option<Self> {
+        T::deserialize_json(json).ok().map(Self)
+    }
+
+    fn from_json_detailed(json: &str) -> Result<Self, String> {
+        T::deserialize_json(json).map(Self).map_err(|error| format!("{error:?}"))
+    }
+}
+
+macro_rules! impl_wrapper_deref {
+    ($wrapper:ident, $feature:literal) => {
+        #[cfg(feature = $feature)]
+        impl<T> Deref for $wrapper<T> {
+            type Target = T;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl<T> DerefMut for $wrapper<T> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+    };
+}
+
+impl_wrapper_deref!(SerdeJson, "use_serde");
+impl_wrapper_deref!(NanoJson, "use_nanoserde");
@@ -0,0 +1,39 @@
+//! Identifiers a server lists in ServerMessage::ServerInfo, these are part of the protocol and
+//! never change meaning once published.
+
+/// Several values can arrive in a single NotifyMany message.
+pub const NOTIFY_MANY: &str = "notify_many";
+/// Large values arrive as NotifyEncoded messages to clients that announced deflate support.
+pub const DEFLATE: &str = "deflate";
+/// Updates can be sent as UpdateValueCas and are answered with CasAccepted or Conflict.
+pub const CAS: &str = "cas";
+/// Single elements of lists can be updated through UpdateIndex and arrive as NotifyIndex.
+pub const INDEX_UPDATES: &str = "index_updates";
+/// New debuggables are announced with Added and removals carry their reason.
+pub const ADDED: &str = "added";
+/// Updates sent with a request id are answered with UpdateAck once their debuggable syncs.
+pub const UPDATE_ACKS: &str = "update_acks";
+/// Updates of several debuggables can be sent together as an UpdateGroup.
+pub const UPDATE_GROUPS: &str = "update_groups";
+/// Numeric debuggables can be moved towards a value through AnimateValue.
+pub const ANIMATIONS: &str = "animations";
+/// Debuggables sharing a name prefix can be requested and reset together.
+pub const GROUP_OPERATIONS: &str = "group_operations";
+/// RPC endpoints registered by the host answer RpcCall messages with an RpcResult.
+pub const RPC: &str = "rpc";
+/// Values above a size arrive as NotifySummary, RequestValue fetches them whole as ValueChunk.
+pub const VALUE_SUMMARIES: &str = "value_summaries";
+/// Related debuggables are grouped through Composite messages.
+pub const COMPOSITES: &str = "composites";
+/// Text debuggables can change through NotifyTextPatch and UpdateTextPatch, to clients announcing
+/// support for them in Hello.
+pub const TEXT_PATCHES: &str = "text_patches";
+/// Values of on demand debuggables, flagged in their Metadata, are only sent in answer to
+/// RequestValue once the host computes them.
+pub const ON_DEMAND: &str = "on_demand";
+/// Custom messages are dispatched to handlers registered by the host.
+pub const CUSTOM_MESSAGES: &str = "custom_messages";
+/// A JSON-RPC listener is available next to the regular one.
+pub const JSONRPC: &str = "jsonrpc";
+/// Updates can also be written as files to the server's read directory.
+pub const READ_DIR: &str = "read_dir";
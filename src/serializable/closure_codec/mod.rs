@@ -0,0 +1,44 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::serializable::JSONDeSerializable;
+
+/// Wraps a value whose JSON representation is given by plain functions instead of a serde backend.
+/// The functions are kept by each debuggable built through DebuggableBuilder::with_codec, so
+/// debuggables of the same wrapped type may each use their own. Outside of one it has no JSON
+/// representation.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ClosureCodec<T: 'static>(pub T);
+
+impl<T: 'static> ClosureCodec<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: 'static> JSONDeSerializable for ClosureCodec<T> {
+    fn to_json(&self) -> Option<String> {
+        None
+    }
+
+    fn from_json(_json: &str) -> Option<Self> {
+        None
+    }
+}
+
+impl<T: 'static> Deref for ClosureCodec<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: 'static> DerefMut for ClosureCodec<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -0,0 +1,43 @@
+//! How messages are delimited on a connection, announced in ServerMessage::ServerInfo so clients
+//! written in other languages can configure themselves.
+
+#[cfg(feature = "use_nanoserde")]
+use nanoserde::{DeJson, SerJson};
+#[cfg(feature = "use_serde")]
+use serde::{Deserialize, Serialize};
+
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Framing {
+        /// Every message is followed by the endmark, occurrences of it within a message are replaced
+        /// by the escape.
+        Endmark,
+        /// Every message is preceded by its length, as done by listeners using Codec::Cbor.
+        LengthPrefixed,
+    }
+}
+
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct FramingInfo {
+        pub endmark: String,
+        pub escape: String,
+        pub mode: Framing,
+    }
+}
+
+/// Fails when either is empty or one is a prefix of the other, as then escaped messages couldn't
+/// be told apart from their end.
+pub fn validate_endmark(endmark: &str, escape: &str) -> Result<(), String> {
+    if endmark.is_empty() || escape.is_empty() {
+        return Err("Neither the endmark nor its escape can be empty".to_string());
+    }
+    if endmark.starts_with(escape) || escape.starts_with(endmark) {
+        return Err(format!("The endmark {endmark:?} and its escape {escape:?} can't be a prefix of one another"));
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::fmt::{Display, Formatter};
+
+use crate::serializable::JSONDeSerializable;
+
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputLimits {
+    pub max_message_len: usize,
+    pub max_nesting_depth: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self { max_message_len: DEFAULT_MAX_MESSAGE_LEN, max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputRejection {
+    TooLong { len: usize, max_len: usize },
+    TooDeep { max_depth: usize },
+    Unparseable,
+}
+
+impl Display for InputRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputRejection::TooLong { len, max_len } => write!(f, "Message of {len} bytes exceeds the limit of {max_len} bytes"),
+            InputRejection::TooDeep { max_depth } => write!(f, "Message nests deeper than {max_depth} levels"),
+            InputRejection::Unparseable => f.write_str("Could not parse message"),
+        }
+    }
+}
+
+impl std::error::Error for InputRejection {}
+
+impl InputLimits {
+    /// Cheap byte scan run before handing a message to the JSON parser.
+    pub fn check(&self, json: &str) -> Result<(), InputRejection> {
+        if json.len() > self.max_message_len {
+            return Err(InputRejection::TooLong { len: json.len(), max_len: self.max_message_len });
+        }
+        let (mut depth, mut in_string, mut is_escaped) = (0_usize, false, false);
+        for byte in json.bytes() {
+            if in_string {
+                match byte {
+                    _ if is_escaped => is_escaped = false,
+                    b'\\' => is_escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'[' | b'{' => {
+                    depth += 1;
+                    if depth > self.max_nesting_depth {
+                        return Err(InputRejection::TooDeep { max_depth: self.max_nesting_depth });
+                    }
+                }
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse<Message: JSONDeSerializable>(&self, json: &str) -> Result<Message, InputRejection> {
+        self.check(json)?;
+        Message::from_json(json).ok_or(InputRejection::Unparseable)
+    }
+}
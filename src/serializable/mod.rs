@@ -3,9 +3,34 @@ use nanoserde::{DeJson, SerJson};
 #[cfg(feature = "use_serde")]
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+#[macro_use]
+mod protocol_type;
+pub mod closure_codec;
+#[cfg(not(any(feature = "use_serde", feature = "use_nanoserde")))]
+pub mod primitives;
+pub mod backend_wrappers;
+pub mod input_limits;
+pub mod capabilities;
+pub mod framing;
+pub mod text_patch;
+
+pub const BASE_PROTOCOL_VERSION: u32 = 1;
+pub const PROTOCOL_VERSION: u32 = 5;
+/// First protocol version whose Notify messages tell when and by whom the value last changed.
+pub const CHANGE_INFO_PROTOCOL_VERSION: u32 = 5;
+/// First protocol version whose clients apply NotifyIndex, older ones are sent the whole value.
+pub const NOTIFY_INDEX_PROTOCOL_VERSION: u32 = 2;
+/// First protocol version whose clients apply NotifyMany, older ones are sent a Notify per value.
+pub const NOTIFY_MANY_PROTOCOL_VERSION: u32 = 2;
+
 pub trait JSONDeSerializable: Sized {
     fn to_json(&self) -> Option<String>;
     fn from_json(json: &str) -> Option<Self>;
+
+    /// Like from_json, but explaining why the json was rejected.
+    fn from_json_detailed(json: &str) -> Result<Self, String> {
+        Self::from_json(json).ok_or_else(|| format!("Could not deserialize {json} as {}", std::any::type_name::<Self>()))
+    }
 }
 
 #[cfg(feature = "use_serde")]
@@ -17,9 +42,15 @@ impl<T> JSONDeSerializable for T where T: Serialize + DeserializeOwned {
     fn from_json(json: &str) -> Option<Self> {
         serde_json::from_str(json).ok()
     }
+
+    fn from_json_detailed(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|error| error.to_string())
+    }
 }
 
-#[cfg(feature = "use_nanoserde")]
+// When both backends are enabled serde provides the blanket implementation, nanoserde types can
+// still be used through the NanoJson wrapper.
+#[cfg(all(feature = "use_nanoserde", not(feature = "use_serde")))]
 impl<T> JSONDeSerializable for T where T: nanoserde::SerJson + nanoserde::DeJson {
     fn to_json(&self) -> Option<String> {
         Some(self.serialize_json())
@@ -28,31 +59,451 @@ impl<T> JSONDeSerializable for T where T: nanoserde::SerJson + nanoserde::DeJson
     fn from_json(json: &str) -> Option<Self> {
         Self::deserialize_json(json).ok()
     }
+
+    fn from_json_detailed(json: &str) -> Result<Self, String> {
+        Self::deserialize_json(json).map_err(|error| format!("{error:?}"))
+    }
 }
 
-#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
-pub enum ServerMessage {
-    GiveClientId {
-        client_id: usize
-    },
-    Notify {
-        id: usize,
-        name: String,
-        value_in_json: String,
-    },
-    Remove {
-        id: usize
-    },
-    RemoveAll,
-}
-
-#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
-pub enum ClientUnitMessage {
-    UpdateValue {
-        id: usize,
-        new_value: String,
-    },
-    Renotify,
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct NotifyEntry {
+        pub id: usize,
+        pub name: String,
+        pub value_in_json: String,
+        #[cfg_attr(feature = "use_serde", serde(default))]
+        #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+        pub revision: u64,
+        /// Whether a client's update set the value rather than the host, see Notify.
+        #[cfg_attr(feature = "use_serde", serde(default))]
+        #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+        pub overridden: bool,
+    }
+}
+
+protocol_type! {
+    /// Why a debuggable was announced through ServerMessage::Added.
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum AddedOrigin {
+        /// Registered by the host application.
+        HostCode,
+        /// Created on behalf of a client.
+        ClientCreated,
+        /// Already existed, either because its value was kept or because it's being sent again to a
+        /// client that just connected or asked to be renotified.
+        Replay,
+    }
+}
+
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum RemoveReason {
+        /// Its owner was dropped.
+        #[default]
+        Dropped,
+        /// Its owner didn't sync it within its time to live.
+        Expired,
+        /// The server is shutting down.
+        Shutdown,
+        /// The host removed it through DebuggableServer::kick_debuggable.
+        Kicked,
+        /// It was hidden, it's announced again once it's shown.
+        Hidden,
+        /// Its owner moved it to another server.
+        Moved,
+    }
+}
+
+protocol_type! {
+    /// How the members of a composite are meant to be shown together, each kind has a fixed number of
+    /// slots in the order listed.
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum CompositeKind {
+        /// x, y.
+        Vec2,
+        /// x, y, z.
+        Vec3,
+        /// Red, green, blue, alpha.
+        ColorRgba,
+        /// Min, max.
+        MinMaxRange,
+    }
+}
+
+impl CompositeKind {
+    pub fn slot_count(&self) -> usize {
+        match self {
+            CompositeKind::Vec2 | CompositeKind::MinMaxRange => 2,
+            CompositeKind::Vec3 => 3,
+            CompositeKind::ColorRgba => 4,
+        }
+    }
+}
+
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ServerMessage {
+        GiveClientId {
+            client_id: usize
+        },
+        Notify {
+            id: usize,
+            name: String,
+            value_in_json: String,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            revision: u64,
+            /// Unix time in milliseconds the value last changed at, only sent to clients speaking
+            /// CHANGE_INFO_PROTOCOL_VERSION or later.
+            #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "Option::is_none"))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            changed_at_ms: Option<u64>,
+            /// Client whose update set the value, None when the host changed it.
+            #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "Option::is_none"))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            author: Option<usize>,
+            /// Never reused by the server unlike the id, missing from servers predating it.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            uid: Option<u64>,
+            /// Whether the value was set by a client's update rather than by the host, until the
+            /// host writes it again or the override is cleared.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            overridden: bool,
+        },
+        NotifyEncoded {
+            id: usize,
+            name: String,
+            encoding: String,
+            value_in_json: String,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            revision: u64,
+        },
+        Remove {
+            id: usize,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            reason: RemoveReason,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            uid: Option<u64>,
+        },
+        RemoveAll,
+        Custom {
+            topic: String,
+            payload: String,
+        },
+        Welcome {
+            client_id: usize,
+            protocol_version: u32,
+        },
+        NotifyIndex {
+            id: usize,
+            index: usize,
+            element_json: String,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            revision: u64,
+        },
+        Metadata {
+            id: usize,
+            nullable: bool,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            order: i32,
+            /// The value is only sent in answer to RequestValue, once the host computes it.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            on_demand: bool,
+        },
+        NotifyMany {
+            notifies: Vec<NotifyEntry>,
+        },
+        Conflict {
+            id: usize,
+            current_revision: u64,
+            current_value: String,
+        },
+        CasAccepted {
+            id: usize,
+            revision: u64,
+        },
+        Error {
+            message: String,
+            /// Panel of the update this answers, if it was sent by one.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            panel: Option<String>,
+        },
+        Added {
+            id: usize,
+            name: String,
+            origin: AddedOrigin,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            uid: Option<u64>,
+        },
+        ServerInfo {
+            crate_version: String,
+            protocol_version: u32,
+            capabilities: Vec<String>,
+            debuggable_count: usize,
+            /// How the server delimits messages, missing from servers predating it.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            framing: Option<framing::FramingInfo>,
+        },
+        /// Answers an UpdateValue carrying a request id. Updates superseded by a later one before their
+        /// debuggable synced, or discarded by the host, aren't accepted.
+        UpdateAck {
+            request_id: u64,
+            accepted: bool,
+            /// Panel of the update this answers, if it was sent by one.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            panel: Option<String>,
+        },
+        /// Answers a GroupReset with the ids of the debuggables reset and those that couldn't be.
+        GroupResult {
+            prefix: String,
+            applied: Vec<usize>,
+            denied: Vec<usize>,
+        },
+        /// Answers an RpcCall to the client that made it, with either the response or why there's none.
+        RpcResult {
+            call_id: u64,
+            response_json: Option<String>,
+            error: Option<String>,
+        },
+        /// Sent instead of Notify when the value is larger than the server's max_value_bytes, with
+        /// the first characters of the value as its preview.
+        NotifySummary {
+            id: usize,
+            name: String,
+            byte_len: usize,
+            preview: String,
+        },
+        /// The debuggable has no value anymore, as when the host failed to compute it.
+        NotifyUnset {
+            id: usize,
+        },
+        /// Piece of a value requested through RequestValue with full set, the value is the
+        /// concatenation of the chunk_count chunks in order of chunk_index.
+        ValueChunk {
+            id: usize,
+            revision: u64,
+            chunk_index: usize,
+            chunk_count: usize,
+            json_chunk: String,
+        },
+        /// The debuggables are shown as one widget of the kind, members holds their ids by slot.
+        Composite {
+            name: String,
+            kind: CompositeKind,
+            members: Vec<usize>,
+        },
+        /// The composite no longer exists, as one of its members was removed.
+        CompositeDissolved {
+            name: String,
+        },
+        /// Sent instead of Notify for text debuggables diffed line by line, the hunks turn the value
+        /// the client holds at base_revision into the one at revision.
+        NotifyTextPatch {
+            id: usize,
+            base_revision: u64,
+            revision: u64,
+            hunks: Vec<text_patch::TextHunk>,
+        },
+        /// Data the client stored through StoreBlob, sent back when it resumes its session.
+        Blob {
+            key: String,
+            data: String,
+        },
+    }
+}
+
+impl ServerMessage {
+    pub fn min_protocol_version(&self) -> u32 {
+        match self {
+            ServerMessage::GiveClientId { .. }
+            | ServerMessage::Notify { .. }
+            | ServerMessage::Remove { .. }
+            | ServerMessage::RemoveAll
+            // Existed before versions were negotiated, so clients that never said hello still get it
+            | ServerMessage::Custom { .. }
+            // Replaces Notify for large values, so it's sent whenever Notify would be
+            | ServerMessage::NotifySummary { .. }
+            // Sent before clients can announce their version, older clients skip it as unparseable
+            | ServerMessage::ServerInfo { .. } => BASE_PROTOCOL_VERSION,
+            ServerMessage::Added { .. } => 3,
+            ServerMessage::Composite { .. } | ServerMessage::CompositeDissolved { .. } => 4,
+            _ => 2,
+        }
+    }
+}
+
+impl ClientUnitMessage {
+    /// Debuggables whose value the message changes.
+    pub fn edited_ids(&self) -> Vec<usize> {
+        match self {
+            ClientUnitMessage::UpdateValue { id, .. }
+            | ClientUnitMessage::UpdateIndex { id, .. }
+            | ClientUnitMessage::UpdateValueCas { id, .. }
+            | ClientUnitMessage::UpdateTextPatch { id, .. }
+            | ClientUnitMessage::StageValue { id, .. }
+            | ClientUnitMessage::AnimateValue { id, .. } => vec![*id],
+            ClientUnitMessage::UpdateGroup { updates } => updates.iter().map(|update| update.id).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct GroupedUpdate {
+        pub id: usize,
+        pub new_value: String,
+    }
+}
+
+protocol_type! {
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ClientUnitMessage {
+        UpdateValue {
+            id: usize,
+            new_value: String,
+            /// When given, the server answers with UpdateAck once the debuggable processes the update.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            request_id: Option<u64>,
+            /// Panel of a monitor multiplexing several of them over one connection, defaults to the one
+            /// given in Hello.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            panel: Option<String>,
+        },
+        UpdateIndex {
+            id: usize,
+            index: usize,
+            element_json: String,
+        },
+        Renotify,
+        Hello {
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            protocol_version: u32,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            supports_deflate: bool,
+            /// Panel updates sent over this connection are attributed to when they don't name one.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            panel: Option<String>,
+            /// Whether the client applies NotifyTextPatch, otherwise text debuggables are sent whole.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            supports_text_patches: bool,
+            /// Stable identity of the monitor, the server restores its panel, subscriptions and blobs
+            /// when it says hello again with the same key.
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            session_key: Option<String>,
+        },
+        Custom {
+            topic: String,
+            payload: String,
+        },
+        UpdateValueCas {
+            id: usize,
+            expected_revision: u64,
+            new_value: String,
+        },
+        /// Updates released to their debuggables together, once all of them synced.
+        UpdateGroup {
+            updates: Vec<GroupedUpdate>,
+        },
+        /// Moves a numeric debuggable towards the target over the duration, clients are notified of the
+        /// intermediate values. Later updates of the debuggable cancel the animation.
+        AnimateValue {
+            id: usize,
+            target_json: String,
+            duration_ms: u64,
+        },
+        /// Answered with a NotifyMany of the debuggables whose name starts with the prefix.
+        GroupSnapshotRequest {
+            prefix: String,
+        },
+        /// Restores the debuggables whose name starts with the prefix to the value they were
+        /// registered with, answered with GroupResult.
+        GroupReset {
+            prefix: String,
+        },
+        /// Asks the RPC endpoint with the given id to handle the request, answered with an RpcResult
+        /// carrying the same call id once the host serves it.
+        RpcCall {
+            id: usize,
+            call_id: u64,
+            request_json: String,
+        },
+        /// Answered with the Notify or NotifySummary of the debuggable, or with its whole value as
+        /// ValueChunk messages when full is set.
+        RequestValue {
+            id: usize,
+            #[cfg_attr(feature = "use_serde", serde(default))]
+            #[cfg_attr(feature = "use_nanoserde", nserde(default))]
+            full: bool,
+        },
+        /// Updates a text debuggable by the hunks made against its value at base_revision, answered
+        /// with its whole value if that isn't its current revision.
+        UpdateTextPatch {
+            id: usize,
+            base_revision: u64,
+            hunks: Vec<text_patch::TextHunk>,
+        },
+        /// Names of the debuggables the client wants values of, or all of them when None, as by
+        /// default. Added and Metadata are still sent for every debuggable.
+        Subscribe {
+            names: Option<Vec<String>>,
+        },
+        /// Stores the data under the key in the client's session, like the layout of its panel, to be
+        /// sent back as Blob when it resumes the session.
+        StoreBlob {
+            key: String,
+            data: String,
+        },
+        /// Opens an edit transaction, values staged in it aren't applied nor seen by anyone else
+        /// until it's committed.
+        BeginEdit {
+            txn_id: u64,
+        },
+        StageValue {
+            txn_id: u64,
+            id: usize,
+            new_value: String,
+        },
+        /// Applies every value staged in the transaction together, as an UpdateGroup would.
+        CommitEdit {
+            txn_id: u64,
+        },
+        /// Discards every value staged in the transaction.
+        AbortEdit {
+            txn_id: u64,
+        },
+    }
 }
@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use crate::serializable::JSONDeSerializable;
+
+macro_rules! impl_number_json {
+    ($($number:ty),*) => {
+        $(
+        impl JSONDeSerializable for $number {
+            fn to_json(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+
+            fn from_json(json: &str) -> Option<Self> {
+                <$number>::from_str(json.trim()).ok()
+            }
+        }
+        )*
+    };
+}
+
+macro_rules! impl_float_json {
+    ($($float:ty),*) => {
+        $(
+        impl JSONDeSerializable for $float {
+            fn to_json(&self) -> Option<String> {
+                if !self.is_finite() { return None; }
+                Some(format!("{:?}", self))
+            }
+
+            fn from_json(json: &str) -> Option<Self> {
+                <$float>::from_str(json.trim()).ok().filter(|float| float.is_finite())
+            }
+        }
+        )*
+    };
+}
+
+impl_number_json!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_float_json!(f32, f64);
+
+impl JSONDeSerializable for bool {
+    fn to_json(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+
+    fn from_json(json: &str) -> Option<Self> {
+        match json.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl JSONDeSerializable for String {
+    fn to_json(&self) -> Option<String> {
+        Some(escape_json_string(self))
+    }
+
+    fn from_json(json: &str) -> Option<Self> {
+        unescape_json_string(json.trim())
+    }
+}
+
+pub fn escape_json_string(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len() + 2);
+    escaped.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{08}' => escaped.push_str("\\b"),
+            '\u{0C}' => escaped.push_str("\\f"),
+            control if (control as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", control as u32)),
+            other => escaped.push(other),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+pub fn unescape_json_string(json: &str) -> Option<String> {
+    let inner = json.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut characters = inner.chars();
+    while let Some(character) = characters.next() {
+        if character == '"' { return None; }
+        if character != '\\' {
+            unescaped.push(character);
+            continue;
+        }
+        match characters.next()? {
+            '"' => unescaped.push('"'),
+            '\\' => unescaped.push('\\'),
+            '/' => unescaped.push('/'),
+            'b' => unescaped.push('\u{08}'),
+            'f' => unescaped.push('\u{0C}'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            't' => unescaped.push('\t'),
+            'u' => {
+                let code = characters.by_ref().take(4).collect::<String>();
+                let mut code = u32::from_str_radix(&code, 16).ok()?;
+                if (0xD800..0xDC00).contains(&code) {
+                    if characters.next()? != '\\' || characters.next()? != 'u' { return None; }
+                    let low = characters.by_ref().take(4).collect::<String>();
+                    let low = u32::from_str_radix(&low, 16).ok()?;
+                    code = 0x10000 + ((code - 0xD800) << 10) + (low.checked_sub(0xDC00)?);
+                }
+                unescaped.push(char::from_u32(code)?);
+            }
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+impl<T: JSONDeSerializable> JSONDeSerializable for Option<T> {
+    fn to_json(&self) -> Option<String> {
+        match self {
+            None => Some("null".to_string()),
+            Some(value) => value.to_json(),
+        }
+    }
+
+    fn from_json(json: &str) -> Option<Self> {
+        if json.trim() == "null" { return Some(None); }
+        T::from_json(json).map(Some)
+    }
+}
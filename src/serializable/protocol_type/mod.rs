@@ -0,0 +1,64 @@
+//! Declares the types sent over the wire together with a description of their shape, which the
+//! schema module turns into the protocol schema, so it can't drift from the types themselves.
+
+/// Defines the enum or struct as written and, with the schema feature, a TYPE_SCHEMA constant
+/// listing its variants and fields.
+macro_rules! protocol_type {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident $({
+                    $( $(#[$field_meta:meta])* $field:ident : $field_type:ty ),* $(,)?
+                })?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant $({
+                    $( $(#[$field_meta])* $field : $field_type ),*
+                })?
+            ),*
+        }
+
+        #[cfg(feature = "schema")]
+        impl $name {
+            pub(crate) const TYPE_SCHEMA: crate::schema::TypeSchema = crate::schema::TypeSchema::Enum {
+                name: stringify!($name),
+                variants: &[$(
+                    crate::schema::VariantSchema {
+                        name: stringify!($variant),
+                        fields: &[$($(
+                            crate::schema::FieldSchema { name: stringify!($field), rust_type: stringify!($field_type) }
+                        ),*)?],
+                    }
+                ),*],
+            };
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $( $(#[$field_meta:meta])* $field_vis:vis $field:ident : $field_type:ty ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $( $(#[$field_meta])* $field_vis $field : $field_type ),*
+        }
+
+        #[cfg(feature = "schema")]
+        impl $name {
+            pub(crate) const TYPE_SCHEMA: crate::schema::TypeSchema = crate::schema::TypeSchema::Struct {
+                name: stringify!($name),
+                fields: &[$(
+                    crate::schema::FieldSchema { name: stringify!($field), rust_type: stringify!($field_type) }
+                ),*],
+            };
+        }
+    };
+}
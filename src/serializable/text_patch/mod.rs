@@ -0,0 +1,105 @@
+//! Line-based patches sent instead of whole text values, see DebuggableBuilder::text_diff.
+
+#[cfg(feature = "use_nanoserde")]
+use nanoserde::{DeJson, SerJson};
+#[cfg(feature = "use_serde")]
+use serde::{Deserialize, Serialize};
+
+/// Past this many cells the lines that changed are sent as a single hunk rather than diffed, which
+/// keeps diffing texts that changed entirely from taking quadratic memory.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+protocol_type! {
+    /// Replaces removed lines of the base text, starting at its line start, by the inserted lines.
+    #[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TextHunk {
+        pub start: usize,
+        pub removed: usize,
+        pub inserted: Vec<String>,
+    }
+}
+
+/// Hunks turning the old text into the new one, in order of the lines they start at. Lines are
+/// separated by '\n' only, so applying them restores the new text byte for byte.
+///
+/// ```
+/// use debug_monitor::serializable::text_patch::{apply_hunks, diff_lines};
+///
+/// let old = "uniform float a;\nvoid main() {\n}";
+/// let new = "uniform float a;\nuniform float b;\nvoid main() {\n}";
+/// let hunks = diff_lines(old, new);
+/// assert_eq!(hunks.len(), 1);
+/// assert_eq!(apply_hunks(old, &hunks).as_deref(), Some(new));
+/// ```
+pub fn diff_lines(old: &str, new: &str) -> Vec<TextHunk> {
+    let old_lines = old.split('\n').collect::<Vec<_>>();
+    let new_lines = new.split('\n').collect::<Vec<_>>();
+    let prefix = old_lines.iter().zip(&new_lines).take_while(|(old, new)| old == new).count();
+    let suffix = old_lines[prefix..].iter().rev().zip(new_lines[prefix..].iter().rev()).take_while(|(old, new)| old == new).count();
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+    if old_middle.is_empty() && new_middle.is_empty() { return Vec::new(); }
+    if (old_middle.len() + 1).saturating_mul(new_middle.len() + 1) > MAX_DIFF_CELLS {
+        return vec![TextHunk { start: prefix, removed: old_middle.len(), inserted: new_middle.iter().map(|line| line.to_string()).collect() }];
+    }
+    let common_after = common_lines_after(old_middle, new_middle);
+    let width = new_middle.len() + 1;
+    let mut hunks = Vec::new();
+    let mut current: Option<TextHunk> = None;
+    let (mut old_index, mut new_index) = (0, 0);
+    while old_index < old_middle.len() || new_index < new_middle.len() {
+        if old_index < old_middle.len() && new_index < new_middle.len() && old_middle[old_index] == new_middle[new_index] {
+            hunks.extend(current.take());
+            old_index += 1;
+            new_index += 1;
+            continue;
+        }
+        let hunk = current.get_or_insert_with(|| TextHunk { start: prefix + old_index, removed: 0, inserted: Vec::new() });
+        let inserts = new_index < new_middle.len()
+            && (old_index == old_middle.len() || common_after[old_index * width + new_index + 1] >= common_after[(old_index + 1) * width + new_index]);
+        if inserts {
+            hunk.inserted.push(new_middle[new_index].to_string());
+            new_index += 1;
+        } else {
+            hunk.removed += 1;
+            old_index += 1;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+/// Length of the longest common subsequence of old[i..] and new[j..], at i * (new.len() + 1) + j.
+fn common_lines_after(old: &[&str], new: &[&str]) -> Vec<u32> {
+    let width = new.len() + 1;
+    let mut common_after = vec![0_u32; (old.len() + 1) * width];
+    for old_index in (0..old.len()).rev() {
+        for new_index in (0..new.len()).rev() {
+            common_after[old_index * width + new_index] = if old[old_index] == new[new_index] {
+                common_after[(old_index + 1) * width + new_index + 1] + 1
+            } else {
+                common_after[(old_index + 1) * width + new_index].max(common_after[old_index * width + new_index + 1])
+            };
+        }
+    }
+    common_after
+}
+
+/// Applies hunks made by diff_lines, None if they don't fit the base text, as when they were made
+/// against another one.
+pub fn apply_hunks(base: &str, hunks: &[TextHunk]) -> Option<String> {
+    let base_lines = base.split('\n').collect::<Vec<_>>();
+    let mut patched_lines = Vec::with_capacity(base_lines.len());
+    let mut next_line = 0;
+    for hunk in hunks {
+        let end = hunk.start.checked_add(hunk.removed)?;
+        if hunk.start < next_line || end > base_lines.len() { return None; }
+        patched_lines.extend(base_lines[next_line..hunk.start].iter().copied());
+        patched_lines.extend(hunk.inserted.iter().map(String::as_str));
+        next_line = end;
+    }
+    patched_lines.extend(base_lines[next_line..].iter().copied());
+    Some(patched_lines.join("\n"))
+}
@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+/// Client id the steps of animations are queued under, so every client is notified of them,
+/// including the one that requested the animation.
+pub const ANIMATION_CLIENT_ID: usize = usize::MAX - 1;
+
+pub const DEFAULT_ANIMATION_STEP: Duration = Duration::from_millis(50);
+
+/// Transition of a numeric debuggable towards a target requested by a client, sampled each time
+/// its owner syncs it.
+#[derive(Debug, Clone)]
+pub(crate) struct Animation {
+    start: f64,
+    target: f64,
+    target_json: String,
+    started_at: Instant,
+    duration: Duration,
+    last_step: Option<Instant>,
+    whole_numbers: bool,
+}
+
+impl Animation {
+    /// Fails unless both the current value and the target are JSON numbers. When both are whole
+    /// numbers intermediate values are rounded, unless the debuggable keeps fractions.
+    pub(crate) fn new(start_json: Option<&str>, target_json: String, duration: Duration, now: Instant, keeps_fractions: bool) -> Result<Self, String> {
+        let start_json = start_json.ok_or_else(|| "The debuggable has no value to animate from".to_string())?;
+        let start = number_of(start_json).ok_or_else(|| format!("Its value {start_json} is not a number"))?;
+        let target = number_of(&target_json).ok_or_else(|| format!("The target {target_json} is not a number"))?;
+        let whole_numbers = !keeps_fractions && is_whole(start_json) && is_whole(&target_json);
+        Ok(Self { start, target, target_json, started_at: now, duration, last_step: None, whole_numbers })
+    }
+
+    /// Value reached by now if at least min_step passed since the last one, along with whether
+    /// the animation ended, in which case the value is the target itself.
+    pub(crate) fn step(&mut self, now: Instant, min_step: Duration) -> Option<(String, bool)> {
+        let elapsed = now.saturating_duration_since(self.started_at);
+        if elapsed >= self.duration {
+            return Some((self.target_json.clone(), true));
+        }
+        if self.last_step.is_some_and(|last_step| now.saturating_duration_since(last_step) < min_step) {
+            return None;
+        }
+        self.last_step = Some(now);
+        let progress = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        let value = self.start + (self.target - self.start) * progress;
+        let json = if self.whole_numbers { format!("{}", value.round() as i64) } else { format!("{value}") };
+        Some((json, false))
+    }
+}
+
+pub(crate) fn number_of(json: &str) -> Option<f64> {
+    json.trim().parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+fn is_whole(json: &str) -> bool {
+    let digits = json.trim().strip_prefix('-').unwrap_or(json.trim());
+    !digits.is_empty() && digits.chars().all(|character| character.is_ascii_digit())
+}
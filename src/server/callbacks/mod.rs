@@ -0,0 +1,43 @@
+//! Host code the server calls back into while the thread polling it holds its locks.
+//!
+//! Custom message handlers (DebuggableServer::on_custom), client disconnect handlers and the
+//! initializer and after_build of the default server run inside a callback scope. Debuggables
+//! created there, on any server, are built lazily and register on their first access after the
+//! callback returns; accessing them, or any other debuggable, inside the callback uses the local
+//! value without syncing with the server. Both would otherwise take locks the thread already
+//! holds.
+//!
+//! Other code the server runs while locked, like serializers, redactors, migrations, transforms
+//! and on_serialize_error handlers, must not create or access debuggables of that server.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+thread_local! {
+    // Number of nested callback scopes alive on this thread
+    static RUNNING_CALLBACKS: Cell<usize> = Cell::new(0);
+}
+
+/// Marks the thread as running a callback of the host until dropped, unwinding included.
+pub(crate) struct CallbackScope {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl CallbackScope {
+    pub(crate) fn enter() -> Self {
+        RUNNING_CALLBACKS.with(|running| running.set(running.get() + 1));
+        Self { _not_send: PhantomData }
+    }
+}
+
+impl Drop for CallbackScope {
+    fn drop(&mut self) {
+        RUNNING_CALLBACKS.with(|running| running.set(running.get() - 1));
+    }
+}
+
+/// Whether this thread is running a callback the server made, where debuggables defer
+/// registering and syncing.
+pub fn is_running_callback() -> bool {
+    RUNNING_CALLBACKS.with(|running| running.get() > 0)
+}
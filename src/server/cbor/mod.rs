@@ -0,0 +1,91 @@
+//! Length-prefixed CBOR framing for listeners marked with Codec::Cbor. Each frame is the length of
+//! its payload as a big-endian u32 followed by the CBOR encoding of a protocol message, using the
+//! same serde representation as the JSON protocol.
+
+use std::io;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::thread;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::client::MessageFraming;
+use crate::serializable::input_limits::DEFAULT_MAX_MESSAGE_LEN;
+use crate::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+
+pub fn encode_frame<Message: Serialize>(message: &Message) -> io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    ciborium::ser::into_writer(message, &mut payload).map_err(|error| io::Error::new(ErrorKind::InvalidData, error.to_string()))?;
+    let payload_len = u32::try_from(payload.len()).map_err(|_| io::Error::new(ErrorKind::InvalidData, "CBOR frame too long"))?;
+    let mut frame = payload_len.to_be_bytes().to_vec();
+    frame.extend(payload);
+    Ok(frame)
+}
+
+/// Reads the next frame, None once the stream ends between frames. Frames that don't decode as
+/// the expected message are reported as InvalidData, the stream can still be read afterwards.
+pub fn read_frame<Message: DeserializeOwned, Reader: Read>(reader: &mut Reader) -> io::Result<Option<Message>> {
+    let mut payload_len = [0_u8; 4];
+    match reader.read_exact(&mut payload_len) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let payload_len = u32::from_be_bytes(payload_len) as usize;
+    if payload_len > DEFAULT_MAX_MESSAGE_LEN {
+        return Err(io::Error::new(ErrorKind::InvalidData, format!("CBOR frame of {payload_len} bytes is too long")));
+    }
+    let mut payload = vec![0_u8; payload_len];
+    reader.read_exact(&mut payload)?;
+    ciborium::de::from_reader(&*payload)
+        .map(Some)
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error.to_string()))
+}
+
+/// Translates between a CBOR client and the server's own JSON listener.
+pub(crate) fn forward_client(client_stream: TcpStream, inner_stream: TcpStream, framing: MessageFraming) -> io::Result<()> {
+    let (mut client_reader, mut inner_writer) = (client_stream.try_clone()?, inner_stream.try_clone()?);
+    let upstream_framing = framing.clone();
+    let upstream = thread::spawn(move || {
+        loop {
+            let message = match read_frame::<ClientUnitMessage, _>(&mut client_reader) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(error) if error.kind() == ErrorKind::InvalidData => {
+                    log::warn!("Ignoring CBOR frame from client: {error}");
+                    continue;
+                }
+                Err(_) => break,
+            };
+            let Some(message) = message.to_json() else { continue; };
+            if inner_writer.write_all(upstream_framing.frame(&message).as_bytes()).is_err() { break; }
+        }
+        let _ = inner_writer.shutdown(Shutdown::Write);
+    });
+    let (mut inner_reader, mut client_writer) = (inner_stream, client_stream);
+    let mut received = String::new();
+    let mut buffer = [0_u8; 16 * 1024];
+    loop {
+        let read = match inner_reader.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        received.push_str(&String::from_utf8_lossy(&buffer[..read]));
+        let mut is_closed = false;
+        for message in framing.decode_frames(&mut received) {
+            let Some(message) = ServerMessage::from_json(&message) else {
+                log::warn!("Could not translate server message to CBOR: {message}");
+                continue;
+            };
+            if encode_frame(&message).and_then(|frame| client_writer.write_all(&frame)).is_err() {
+                is_closed = true;
+                break;
+            }
+        }
+        if is_closed { break; }
+    }
+    let _ = client_writer.shutdown(Shutdown::Both);
+    let _ = upstream.join();
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::serializable::{CompositeKind, ServerMessage};
+
+/// Debuggables grouped under a name so clients show them as a single widget. A composite is only
+/// announced once each of its slots has a member, and dissolves as soon as one of them is removed.
+#[derive(Debug, Default)]
+pub(crate) struct Composites {
+    by_name: BTreeMap<String, Composite>,
+}
+
+#[derive(Debug)]
+struct Composite {
+    kind: CompositeKind,
+    members: Vec<Option<usize>>,
+}
+
+impl Composite {
+    fn new(kind: CompositeKind) -> Self {
+        Self { kind, members: vec![None; kind.slot_count()] }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.members.iter().all(Option::is_some)
+    }
+
+    fn message(&self, name: &str) -> Option<ServerMessage> {
+        if !self.is_complete() { return None; }
+        Some(ServerMessage::Composite { name: name.to_string(), kind: self.kind, members: self.members.iter().flatten().copied().collect() })
+    }
+}
+
+impl Composites {
+    /// Replaces the composite under the name.
+    pub(crate) fn declare(&mut self, name: &str, kind: CompositeKind, member_ids: Vec<usize>) {
+        let members = member_ids.into_iter().map(Some).collect();
+        self.by_name.insert(name.to_string(), Composite { kind, members });
+    }
+
+    /// Puts the debuggable in the slot of the composite, creating it if it's the first member to
+    /// join. Returns false if the composite is of another kind or hasn't the slot.
+    pub(crate) fn join(&mut self, name: &str, kind: CompositeKind, slot: usize, debuggable_id: usize) -> bool {
+        if slot >= kind.slot_count() { return false; }
+        let composite = self.by_name.entry(name.to_string()).or_insert_with(|| Composite::new(kind));
+        if composite.kind != kind { return false; }
+        composite.members[slot] = Some(debuggable_id);
+        true
+    }
+
+    /// Clears the slots the debuggable was in, returning the names of the composites it dissolved.
+    /// Composites left without members are forgotten.
+    pub(crate) fn forget_debuggable(&mut self, debuggable_id: usize) -> Vec<String> {
+        let mut dissolved = Vec::new();
+        for (name, composite) in self.by_name.iter_mut() {
+            if !composite.members.contains(&Some(debuggable_id)) { continue; }
+            if composite.is_complete() {
+                dissolved.push(name.clone());
+            }
+            composite.members.iter_mut()
+                .filter(|member| **member == Some(debuggable_id))
+                .for_each(|member| *member = None);
+        }
+        self.by_name.retain(|_, composite| composite.members.iter().any(Option::is_some));
+        dissolved
+    }
+
+    /// Forgets the composite, returning whether it had been announced.
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        self.by_name.remove(name).map_or(false, |composite| composite.is_complete())
+    }
+
+    pub(crate) fn message_of(&self, name: &str) -> Option<ServerMessage> {
+        self.by_name.get(name)?.message(name)
+    }
+
+    /// Messages announcing every complete composite, in order of name.
+    pub(crate) fn messages(&self) -> Vec<ServerMessage> {
+        self.by_name.iter().filter_map(|(name, composite)| composite.message(name)).collect()
+    }
+
+    pub(crate) fn remap_ids(&mut self, remapped_ids: &HashMap<usize, usize>) {
+        self.by_name.values_mut()
+            .flat_map(|composite| composite.members.iter_mut().flatten())
+            .for_each(|id| *id = remapped_ids.get(id).copied().unwrap_or(*id));
+    }
+}
@@ -0,0 +1,16 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+pub const DEFLATE_ENCODING: &str = "deflate+base64";
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 4 * 1024;
+
+pub fn compress(json: &str) -> String {
+    STANDARD.encode(miniz_oxide::deflate::compress_to_vec(json.as_bytes(), 6))
+}
+
+pub fn decompress(encoding: &str, encoded: &str) -> Option<String> {
+    if encoding != DEFLATE_ENCODING { return None; }
+    let compressed = STANDARD.decode(encoded).ok()?;
+    let json = miniz_oxide::inflate::decompress_to_vec(&compressed).ok()?;
+    String::from_utf8(json).ok()
+}
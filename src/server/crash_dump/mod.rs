@@ -0,0 +1,180 @@
+//! Last values of selected debuggables appended to panic reports, see install_panic_hook.
+//!
+//! Values are copied as debuggables change into a store the panic hook reads without taking any
+//! lock, as the panicking thread might be holding a server's lock or have poisoned it.
+
+use std::cell::UnsafeCell;
+use std::collections::BTreeMap;
+use std::panic;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Once, OnceLock, PoisonError};
+use std::thread;
+
+/// Bytes of name=value pairs a dump holds at most, those that don't fit are only counted.
+pub const MAX_DUMP_BYTES: usize = 4 * 1024;
+// Times a dump retries reading while the values are being switched before giving up
+const READ_ATTEMPTS: usize = 64;
+
+/// Debuggables whose values are dumped into panic reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpSelection {
+    All,
+    Names(Vec<String>),
+    /// Debuggables whose name starts with the prefix.
+    Group(String),
+}
+
+impl DumpSelection {
+    pub fn selects(&self, name: &str) -> bool {
+        match self {
+            DumpSelection::All => true,
+            DumpSelection::Names(names) => names.iter().any(|selected| selected == name),
+            DumpSelection::Group(prefix) => name.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+// Keyed by name and entry, as debuggables of different servers may share a name
+type DumpedValues = BTreeMap<(String, u64), Arc<str>>;
+
+/// Two copies of the values: the writer updates the one readers aren't using, switches readers to
+/// it and then updates the other, so readers never wait.
+struct LeftRight {
+    copies: [UnsafeCell<DumpedValues>; 2],
+    // Times readers were switched, the active copy being its parity. Counting switches instead of
+    // keeping the active copy lets readers tell it was switched away and back while they looked
+    switches: AtomicUsize,
+    readers: [AtomicUsize; 2],
+    writer: Mutex<()>,
+}
+
+// Copies are only written while no reader is counted on them, see write
+unsafe impl Sync for LeftRight {}
+
+/// Uncounts the reader even if reading panics, or the writer would wait for it forever.
+struct ReadGuard<'counter>(&'counter AtomicUsize);
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl LeftRight {
+    const fn new() -> Self {
+        Self {
+            copies: [UnsafeCell::new(BTreeMap::new()), UnsafeCell::new(BTreeMap::new())],
+            switches: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            writer: Mutex::new(()),
+        }
+    }
+
+    fn write<Change: Fn(&mut DumpedValues)>(&self, change: Change) {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let switches = self.switches.load(Ordering::SeqCst);
+        let active = switches % 2;
+        let inactive = 1 - active;
+        self.wait_for_readers_of(inactive);
+        change(unsafe { &mut *self.copies[inactive].get() });
+        self.switches.store(switches.wrapping_add(1), Ordering::SeqCst);
+        self.wait_for_readers_of(active);
+        change(unsafe { &mut *self.copies[active].get() });
+    }
+
+    fn wait_for_readers_of(&self, copy: usize) {
+        while self.readers[copy].load(Ordering::SeqCst) > 0 {
+            thread::yield_now();
+        }
+    }
+
+    /// Reads the copy readers are switched to, None if the writer kept switching them meanwhile.
+    fn read<Read: FnOnce(&DumpedValues) -> Output, Output>(&self, read: Read) -> Option<Output> {
+        for _ in 0..READ_ATTEMPTS {
+            let switches = self.switches.load(Ordering::SeqCst);
+            let active = switches % 2;
+            self.readers[active].fetch_add(1, Ordering::SeqCst);
+            let _reader = ReadGuard(&self.readers[active]);
+            // A writer that switched copies before this reader was counted may be writing it
+            if self.switches.load(Ordering::SeqCst) == switches {
+                return Some(read(unsafe { &*self.copies[active].get() }));
+            }
+        }
+        None
+    }
+}
+
+static SELECTION: OnceLock<DumpSelection> = OnceLock::new();
+static VALUES: LeftRight = LeftRight::new();
+static NEXT_ENTRY: AtomicU64 = AtomicU64::new(0);
+
+/// Entry the value of a debuggable is kept under, unique across servers.
+pub(crate) fn new_entry() -> u64 {
+    NEXT_ENTRY.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Whether a panic hook selecting the debuggable is installed.
+pub(crate) fn is_dumped(name: &str) -> bool {
+    SELECTION.get().is_some_and(|selection| selection.selects(name))
+}
+
+/// Keeps the value of the debuggable for dumps, an unset value forgets it.
+pub(crate) fn record(entry: u64, name: &str, value_in_json: Option<Arc<str>>) {
+    match value_in_json {
+        None => forget(entry, name),
+        Some(value_in_json) => VALUES.write(|values| { values.insert((name.to_string(), entry), value_in_json.clone()); }),
+    }
+}
+
+pub(crate) fn forget(entry: u64, name: &str) {
+    if !is_dumped(name) { return; }
+    VALUES.write(|values| { values.remove(&(name.to_string(), entry)); });
+}
+
+/// Appends the last values of the selected debuggables to every panic report, after the report of
+/// the panic hook installed before. Values are kept from the moment it's installed, so debuggables
+/// set earlier are only dumped once they change. Installing it more than once has no further
+/// effect.
+pub fn install_panic_hook(selection: DumpSelection) {
+    install_panic_hook_with(selection, |dump| eprintln!("{dump}"));
+}
+
+/// Same as install_panic_hook, but hands the dump to the given reporter instead of printing it, so
+/// it can be attached to crash reports.
+pub fn install_panic_hook_with<Report: Fn(&str) + Send + Sync + 'static>(selection: DumpSelection, report: Report) {
+    static PANIC_HOOK_ONCE: Once = Once::new();
+    PANIC_HOOK_ONCE.call_once(|| {
+        let _ = SELECTION.set(selection);
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            previous_hook(panic_info);
+            if let Some(dump) = current_dump() {
+                report(&dump);
+            }
+        }));
+    });
+}
+
+/// Dump the panic hook would report right now, None if no hook was installed or the values kept
+/// changing while reading them.
+pub fn current_dump() -> Option<String> {
+    SELECTION.get()?;
+    VALUES.read(|values| {
+        let mut dump = String::from("debuggables:");
+        let mut left_out = 0;
+        for ((name, _), value_in_json) in values {
+            if left_out > 0 || dump.len() + name.len() + value_in_json.len() + 2 > MAX_DUMP_BYTES {
+                left_out += 1;
+                continue;
+            }
+            dump.push(' ');
+            dump.push_str(name);
+            dump.push('=');
+            dump.push_str(value_in_json);
+        }
+        if left_out > 0 {
+            dump.push_str(&format!(" ...and {left_out} more"));
+        }
+        dump
+    })
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use crate::server::DebuggableOnServer;
+
+/// Borrowed view of a debuggable as the server knows it, only valid while the server is locked.
+#[derive(Debug, Clone, Copy)]
+pub struct DebuggableInfo<'server> {
+    id: usize,
+    debuggable: &'server DebuggableOnServer,
+    is_kept: bool,
+}
+
+impl<'server> DebuggableInfo<'server> {
+    pub(crate) fn new(id: usize, debuggable: &'server DebuggableOnServer, is_kept: bool) -> Self {
+        Self { id, debuggable, is_kept }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn name(&self) -> &'server str {
+        &self.debuggable.name
+    }
+
+    pub fn last_value_json(&self) -> Option<&'server str> {
+        self.debuggable.last_value.as_deref()
+    }
+
+    pub fn pending_updates(&self) -> usize {
+        self.debuggable.incoming_jsons.len() + self.debuggable.incoming_index_updates.len()
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.debuggable.hidden
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        self.debuggable.nullable
+    }
+
+    pub fn is_kept(&self) -> bool {
+        self.is_kept
+    }
+
+    pub fn is_redacted(&self) -> bool {
+        self.debuggable.redactor.is_some()
+    }
+
+    pub fn order(&self) -> i32 {
+        self.debuggable.order
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.debuggable.revision
+    }
+
+    pub fn change_generation(&self) -> u64 {
+        self.debuggable.change_generation
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.debuggable.ttl
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.debuggable.read_only
+    }
+
+    pub fn to_owned_info(&self) -> OwnedDebuggableInfo {
+        OwnedDebuggableInfo {
+            id: self.id(),
+            name: self.name().to_string(),
+            last_value_json: self.last_value_json().map(str::to_string),
+            pending_updates: self.pending_updates(),
+            is_hidden: self.is_hidden(),
+            is_nullable: self.is_nullable(),
+            is_kept: self.is_kept(),
+            is_redacted: self.is_redacted(),
+            order: self.order(),
+            revision: self.revision(),
+            change_generation: self.change_generation(),
+            ttl: self.ttl(),
+            is_read_only: self.is_read_only(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedDebuggableInfo {
+    pub id: usize,
+    pub name: String,
+    pub last_value_json: Option<String>,
+    pub pending_updates: usize,
+    pub is_hidden: bool,
+    pub is_nullable: bool,
+    pub is_kept: bool,
+    pub is_redacted: bool,
+    pub order: i32,
+    pub revision: u64,
+    pub change_generation: u64,
+    pub ttl: Option<Duration>,
+    pub is_read_only: bool,
+}
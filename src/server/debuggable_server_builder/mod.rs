@@ -1,24 +1,400 @@
-use std::net::TcpListener;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+#[cfg(feature = "windows-pipes")]
+use std::net::{IpAddr, Ipv4Addr};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::server::DebuggableServer;
+use crate::clock::Clock;
+use crate::serializable::framing::validate_endmark;
+use crate::serializable::input_limits::InputLimits;
+use crate::server::{ClientDisconnectHandler, DebuggableServer, IncomingTransform, OutgoingTransform, DEFAULT_EXPLAIN_AFTER_REJECTIONS, DEFAULT_IGNORE_AFTER_REJECTIONS};
+use crate::server::ip_filter::IpRange;
+use crate::server::listeners::AdditionalListener;
+use crate::server::declarations::DeclaredOptions;
+use crate::server::outgoing::OverflowPolicy;
+use crate::server::pending_updates::DEFAULT_MAX_PENDING_UPDATES;
+use crate::server::sessions::{DEFAULT_MAX_SESSIONS, DEFAULT_MAX_SESSION_BLOB_BYTES};
+use crate::server::socket_options::{bind_listener_with_fallback, ClientSocketOptions};
+#[cfg(feature = "discovery")]
+use crate::discovery::Beacon;
+#[cfg(feature = "tls")]
+use crate::server::tls::TlsSettings;
+#[cfg(feature = "fault-injection")]
+use crate::server::fault_injection::FaultInjector;
+
+/// Ports bind falls back to, in order, when the one asked for is among them but already taken.
+pub const DEFAULT_FALLBACK_PORTS: Range<u16> = 5050..5060;
 
 pub struct DebuggableServerBuilder {
-    tcp_listener: TcpListener,
+    tcp_listener: Option<TcpListener>,
+    bind_address: Option<SocketAddr>,
+    reuse_addr: bool,
+    fallback_ports: Range<u16>,
+    client_socket_options: ClientSocketOptions,
+    max_outgoing_queue: Option<usize>,
+    overflow_policy: OverflowPolicy,
+    refresh_interval: Option<Duration>,
+    allowed_ips: Option<Vec<IpRange>>,
+    additional_listeners: Vec<AdditionalListener>,
+    compression_threshold: Option<usize>,
+    message_endmark: Option<(String, String)>,
+    #[cfg(feature = "tls")]
+    tls_pem: Option<(String, String)>,
+    #[cfg(feature = "jsonrpc")]
+    jsonrpc_address: Option<SocketAddr>,
+    #[cfg(feature = "discovery")]
+    announces: bool,
+    #[cfg(feature = "discovery")]
+    discovery_target: Option<SocketAddr>,
+    #[cfg(feature = "windows-pipes")]
+    named_pipes: Vec<String>,
+    #[cfg(feature = "fault-injection")]
+    fault_injector: Option<FaultInjector>,
+    on_client_disconnect: Option<ClientDisconnectHandler>,
+    clock: Option<Arc<dyn Clock>>,
+    input_limits: InputLimits,
+    max_strikes: Option<u32>,
+    outgoing_transform: Option<OutgoingTransform>,
+    incoming_transform: Option<IncomingTransform>,
+    resync_threshold: Option<u32>,
+    explain_after_rejections: Option<u32>,
+    ignore_after_rejections: Option<u32>,
+    max_pending_updates: Option<usize>,
+    max_sessions: usize,
+    max_session_blob_bytes: usize,
+    replays_values_on_hello: bool,
+    update_group_timeout: Option<Duration>,
+    edit_transaction_timeout: Option<Duration>,
+    rpc_timeout: Option<Duration>,
+    max_value_bytes: Option<usize>,
+    derived_refresh_interval: Option<Duration>,
+    animation_step: Option<Duration>,
+    sync_budget: Option<Duration>,
+    compaction_threshold: Option<usize>,
     read_dir: Option<String>,
     only_reads_from_dir: bool,
+    declarations: Vec<(String, DeclaredOptions)>,
     after_build: fn(&mut DebuggableServer)
 }
 
 impl DebuggableServerBuilder {
     pub fn new(tcp_listener: TcpListener) -> DebuggableServerBuilder {
+        Self::from_listener_source(Some(tcp_listener), None)
+    }
+
+    pub fn bind(address: SocketAddr) -> DebuggableServerBuilder {
+        Self::from_listener_source(None, Some(address))
+    }
+
+    /// Serves clients connecting to the named pipe, such as `\\.\pipe\debug_monitor_myapp`. The
+    /// server itself then only listens on an ephemeral loopback port, which pipe clients are
+    /// forwarded to. try_build fails with ErrorKind::Unsupported outside of Windows.
+    #[cfg(feature = "windows-pipes")]
+    pub fn named_pipe<Name: ToString>(name: Name) -> DebuggableServerBuilder {
+        let mut builder = Self::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+            .fallback_ports(0..0)
+            .loopback_only();
+        builder.named_pipes.push(name.to_string());
+        builder
+    }
+
+    fn from_listener_source(tcp_listener: Option<TcpListener>, bind_address: Option<SocketAddr>) -> DebuggableServerBuilder {
         Self {
             tcp_listener,
+            bind_address,
+            reuse_addr: false,
+            fallback_ports: DEFAULT_FALLBACK_PORTS,
+            client_socket_options: Default::default(),
+            max_outgoing_queue: None,
+            overflow_policy: OverflowPolicy::DropOldest,
+            refresh_interval: None,
+            allowed_ips: None,
+            additional_listeners: Vec::new(),
+            compression_threshold: None,
+            message_endmark: None,
+            #[cfg(feature = "tls")]
+            tls_pem: None,
+            #[cfg(feature = "jsonrpc")]
+            jsonrpc_address: None,
+            #[cfg(feature = "discovery")]
+            announces: true,
+            #[cfg(feature = "discovery")]
+            discovery_target: None,
+            #[cfg(feature = "windows-pipes")]
+            named_pipes: Vec::new(),
+            #[cfg(feature = "fault-injection")]
+            fault_injector: None,
+            on_client_disconnect: None,
+            clock: None,
+            input_limits: Default::default(),
+            max_strikes: None,
+            outgoing_transform: None,
+            incoming_transform: None,
+            resync_threshold: None,
+            explain_after_rejections: Some(DEFAULT_EXPLAIN_AFTER_REJECTIONS),
+            ignore_after_rejections: Some(DEFAULT_IGNORE_AFTER_REJECTIONS),
+            max_pending_updates: Some(DEFAULT_MAX_PENDING_UPDATES),
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            max_session_blob_bytes: DEFAULT_MAX_SESSION_BLOB_BYTES,
+            replays_values_on_hello: false,
+            update_group_timeout: None,
+            edit_transaction_timeout: None,
+            rpc_timeout: None,
+            max_value_bytes: None,
+            derived_refresh_interval: None,
+            animation_step: None,
+            sync_budget: None,
+            compaction_threshold: None,
             read_dir: None,
             only_reads_from_dir: false,
+            declarations: Vec::new(),
             after_build: |_|{},
         }
     }
 
+    pub fn reuse_addr(mut self, reuse_addr: bool) -> Self {
+        self.reuse_addr = reuse_addr;
+        self
+    }
+
+    /// Ports tried after the one given to bind when it's taken, as long as it's among them. An
+    /// empty range disables falling back.
+    pub fn fallback_ports(mut self, fallback_ports: Range<u16>) -> Self {
+        self.fallback_ports = fallback_ports;
+        self
+    }
+
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.client_socket_options.tcp_nodelay = Some(tcp_nodelay);
+        self
+    }
+
+    pub fn client_read_timeout(mut self, read_timeout: Option<Duration>) -> Self {
+        self.client_socket_options.read_timeout = read_timeout;
+        self
+    }
+
+    pub fn client_write_timeout(mut self, write_timeout: Option<Duration>) -> Self {
+        self.client_socket_options.write_timeout = write_timeout;
+        self
+    }
+
+    pub fn max_outgoing_queue(mut self, max_outgoing_queue: usize) -> Self {
+        self.max_outgoing_queue = Some(max_outgoing_queue);
+        self
+    }
+
+    pub fn overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    pub fn refresh_interval(mut self, refresh_interval: Option<Duration>) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    pub fn allow_ips(mut self, allowed_ips: Vec<IpRange>) -> Self {
+        self.allowed_ips = Some(allowed_ips);
+        self
+    }
+
+    pub fn loopback_only(self) -> Self {
+        self.allow_ips(IpRange::loopback())
+    }
+
+    /// Also accepts clients on the given listener, either a TcpListener or an AdditionalListener
+    /// with its own allow-list. These listeners don't use TLS.
+    pub fn additional_listener<Listener: Into<AdditionalListener>>(mut self, listener: Listener) -> Self {
+        self.additional_listeners.push(listener.into());
+        self
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn compression_threshold(mut self, compression_threshold: usize) -> Self {
+        self.compression_threshold = Some(compression_threshold);
+        self
+    }
+
+    /// Delimits messages with the given endmark rather than protocol::framing::DEFAULT_ENDMARK,
+    /// replacing its occurrences within them by the escape. try_build fails if either is empty or
+    /// one is a prefix of the other.
+    pub fn message_endmark(mut self, endmark: &str, escape: &str) -> Self {
+        self.message_endmark = Some((endmark.to_string(), escape.to_string()));
+        self
+    }
+
+    /// Serves clients through TLS, see DebuggableClient::connect_tls. TLS is terminated in front of
+    /// the server, whose own listener then moves to loopback and refuses connections that didn't
+    /// come through the terminator, while its allow-list applies to the peers the terminator accepts.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, cert_chain_pem: &str, private_key_pem: &str) -> Self {
+        self.tls_pem = Some((cert_chain_pem.to_string(), private_key_pem.to_string()));
+        self
+    }
+
+    #[cfg(feature = "jsonrpc")]
+    pub fn jsonrpc(mut self, address: SocketAddr) -> Self {
+        self.jsonrpc_address = Some(address);
+        self
+    }
+
+    /// Whether the server announces itself to discovery::scan, which it does by default.
+    #[cfg(feature = "discovery")]
+    pub fn announces(mut self, announces: bool) -> Self {
+        self.announces = announces;
+        self
+    }
+
+    /// Where beacons are sent instead of DISCOVERY_PORT on loopback, or broadcast when the server
+    /// isn't listening on loopback.
+    #[cfg(feature = "discovery")]
+    pub fn discovery_target(mut self, discovery_target: SocketAddr) -> Self {
+        self.discovery_target = Some(discovery_target);
+        self
+    }
+
+    /// Disturbs the server's traffic as configured in the injector, see FaultInjector.
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_injector(mut self, fault_injector: FaultInjector) -> Self {
+        self.fault_injector = Some(fault_injector);
+        self
+    }
+
+    /// Debuggables created by the handler register once it returns, see the callbacks module.
+    pub fn on_client_disconnect<OnDisconnect>(mut self, on_client_disconnect: OnDisconnect) -> Self
+        where OnDisconnect: FnMut(usize, Option<SocketAddr>) + Send + 'static {
+        self.on_client_disconnect = Some(Box::new(on_client_disconnect));
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn max_message_len(mut self, max_message_len: usize) -> Self {
+        self.input_limits.max_message_len = max_message_len;
+        self
+    }
+
+    pub fn max_nesting_depth(mut self, max_nesting_depth: usize) -> Self {
+        self.input_limits.max_nesting_depth = max_nesting_depth;
+        self
+    }
+
+    pub fn disconnect_after_strikes(mut self, max_strikes: u32) -> Self {
+        self.max_strikes = Some(max_strikes.max(1));
+        self
+    }
+
+    /// Rewrites every serialized server message right before it's framed and sent. Compression
+    /// happens first, so the transform sees NotifyEncoded messages with their value compressed.
+    /// The JSON-RPC listener isn't affected.
+    pub fn outgoing_transform(mut self, outgoing_transform: OutgoingTransform) -> Self {
+        self.outgoing_transform = Some(outgoing_transform);
+        self
+    }
+
+    /// Rewrites every raw client message, whether read from TCP or from the read dir, before it's
+    /// parsed. Messages for which it returns None are dropped and counted as rejected_by_transform.
+    pub fn incoming_transform(mut self, incoming_transform: IncomingTransform) -> Self {
+        self.incoming_transform = Some(incoming_transform);
+        self
+    }
+
+    /// Resyncs every client once a single client sent updates for unknown ids this many times.
+    pub fn resync_after_unknown_ids(mut self, resync_threshold: u32) -> Self {
+        self.resync_threshold = Some(resync_threshold.max(1));
+        self
+    }
+
+    /// Consecutive rejected updates of a debuggable from one client after which it's told what the
+    /// debuggable expects, and after which its updates of it are ignored until it sends Renotify or
+    /// Hello. None disables either step.
+    pub fn rejection_escalation(mut self, explain_after: Option<u32>, ignore_after: Option<u32>) -> Self {
+        self.explain_after_rejections = explain_after.map(|explain_after| explain_after.max(1));
+        self.ignore_after_rejections = ignore_after.map(|ignore_after| ignore_after.max(1));
+        self
+    }
+
+    /// Updates a debuggable keeps waiting for it to sync, past which the oldest are dropped. None
+    /// keeps every update.
+    pub fn max_pending_updates(mut self, max_pending_updates: Option<usize>) -> Self {
+        self.max_pending_updates = max_pending_updates;
+        self
+    }
+
+    /// Sessions of clients that gave a session key in Hello the server remembers, past which those
+    /// used the longest ago are forgotten.
+    pub fn max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    /// Bytes the blobs of a session may take together, blobs that don't fit are rejected.
+    pub fn max_session_blob_bytes(mut self, max_session_blob_bytes: usize) -> Self {
+        self.max_session_blob_bytes = max_session_blob_bytes;
+        self
+    }
+
+    /// Sends clients the values of debuggables once they say hello instead of once accepted, see
+    /// DebuggableServer::set_replay_values_on_hello.
+    pub fn replay_values_on_hello(mut self, replays_values_on_hello: bool) -> Self {
+        self.replays_values_on_hello = replays_values_on_hello;
+        self
+    }
+
+    pub fn update_group_timeout(mut self, update_group_timeout: Duration) -> Self {
+        self.update_group_timeout = Some(update_group_timeout);
+        self
+    }
+
+    /// How long edit transactions may go without activity before they're dropped.
+    pub fn edit_transaction_timeout(mut self, edit_transaction_timeout: Duration) -> Self {
+        self.edit_transaction_timeout = Some(edit_transaction_timeout);
+        self
+    }
+
+    /// How long calls to RPC endpoints wait for the host to serve them before timing out.
+    pub fn rpc_timeout(mut self, rpc_timeout: Duration) -> Self {
+        self.rpc_timeout = Some(rpc_timeout);
+        self
+    }
+
+    /// Size above which clients are sent a summary of a value instead of the value itself.
+    pub fn max_value_bytes(mut self, max_value_bytes: usize) -> Self {
+        self.max_value_bytes = Some(max_value_bytes);
+        self
+    }
+
+    /// Minimum time between refreshes of derived debuggables triggered by polling.
+    pub fn derived_refresh_interval(mut self, derived_refresh_interval: Duration) -> Self {
+        self.derived_refresh_interval = Some(derived_refresh_interval);
+        self
+    }
+
+    /// Minimum time between the intermediate values of animations requested by clients.
+    pub fn animation_step(mut self, animation_step: Duration) -> Self {
+        self.animation_step = Some(animation_step);
+        self
+    }
+
+    /// Limits the time each sync may spend polling clients and the read directory, the work left
+    /// is resumed on the next sync.
+    pub fn sync_budget(mut self, sync_budget: Duration) -> Self {
+        self.sync_budget = Some(sync_budget);
+        self
+    }
+
+    /// Compacts the server's storage once this many debuggables were removed since the last time.
+    pub fn compact_after_removals(mut self, compaction_threshold: usize) -> Self {
+        self.compaction_threshold = Some(compaction_threshold.max(1));
+        self
+    }
+
     pub fn only_reads_from_dir(mut self) -> Self {
         self.only_reads_from_dir = true;
         self
@@ -29,18 +405,119 @@ impl DebuggableServerBuilder {
         self
     }
 
+    /// Announces a debuggable to clients before the host registers it, see DebuggableServer::declare.
+    pub fn declare<Name: ToString>(mut self, name: Name, options: DeclaredOptions) -> Self {
+        self.declarations.push((name.to_string(), options));
+        self
+    }
+
+    /// Runs on the server before it's shared. On the default server's builder, debuggables created
+    /// here register once default_server returns, see the callbacks module.
     pub fn after_build(mut self, after_build: fn(&mut DebuggableServer)) -> Self {
         self.after_build = after_build;
         self
     }
 
-    pub fn build(self) -> DebuggableServer {
-        let mut server = DebuggableServer::new(self.tcp_listener);
-        server.set_read_dir(self.read_dir);
+    pub fn try_build(self) -> io::Result<DebuggableServer> {
+        if let Some((endmark, escape)) = &self.message_endmark {
+            validate_endmark(endmark, escape).map_err(|reason| io::Error::new(io::ErrorKind::InvalidInput, reason))?;
+        }
+        let tcp_listener = match self.tcp_listener {
+            Some(tcp_listener) => tcp_listener,
+            None => bind_listener_with_fallback(self.bind_address.unwrap(), self.reuse_addr, self.fallback_ports)?,
+        };
+        #[cfg(feature = "discovery")]
+        let public_addr = tcp_listener.local_addr()?;
+        let binds_loopback = tcp_listener.local_addr().map(|address| address.ip().is_loopback()).unwrap_or(false);
+        if !binds_loopback && self.allowed_ips.is_none() {
+            log::warn!("Debuggable server listening on {:?} accepts connections from any address, consider using allow_ips or loopback_only",
+                tcp_listener.local_addr());
+        }
+        #[cfg(feature = "tls")]
+        let (tcp_listener, tls_terminated) = match self.tls_pem {
+            None => (tcp_listener, None),
+            Some((cert_chain_pem, private_key_pem)) => {
+                // TLS is terminated in front of the server, which then only listens on loopback
+                let tls_settings = TlsSettings::from_pem(&cert_chain_pem, &private_key_pem)?;
+                (TcpListener::bind("127.0.0.1:0")?, Some((tcp_listener, tls_settings)))
+            }
+        };
+        let mut server = DebuggableServer::with_endmark(tcp_listener, self.message_endmark);
+        if let Some(clock) = self.clock {
+            server.set_clock(clock);
+        }
+        server.set_allowed_ips(self.allowed_ips);
+        #[cfg(feature = "tls")]
+        if let Some((public_listener, tls_settings)) = tls_terminated {
+            server.terminate_tls(public_listener, tls_settings)?;
+        }
+        for additional_listener in self.additional_listeners {
+            server.add_listener(additional_listener)?;
+        }
+        #[cfg(feature = "windows-pipes")]
+        for name in &self.named_pipes {
+            server.add_named_pipe(name)?;
+        }
+        #[cfg(feature = "compression")]
+        server.set_compression_threshold(Some(self.compression_threshold.unwrap_or(crate::server::compression::DEFAULT_COMPRESSION_THRESHOLD)));
+        #[cfg(not(feature = "compression"))]
+        server.set_compression_threshold(self.compression_threshold);
+        server.set_client_socket_options(self.client_socket_options);
+        if let Some(max_outgoing_queue) = self.max_outgoing_queue {
+            server.set_outgoing_queue(max_outgoing_queue, self.overflow_policy);
+        }
+        server.set_refresh_interval(self.refresh_interval);
+        server.set_on_client_disconnect(self.on_client_disconnect);
+        server.set_input_limits(self.input_limits);
+        server.set_max_strikes(self.max_strikes);
+        server.set_outgoing_transform(self.outgoing_transform);
+        server.set_incoming_transform(self.incoming_transform);
+        server.set_resync_threshold(self.resync_threshold);
+        server.set_rejection_escalation(self.explain_after_rejections, self.ignore_after_rejections);
+        server.set_max_pending_updates(self.max_pending_updates);
+        server.set_max_sessions(self.max_sessions);
+        server.set_max_session_blob_bytes(self.max_session_blob_bytes);
+        server.set_replay_values_on_hello(self.replays_values_on_hello);
+        server.set_compaction_threshold(self.compaction_threshold);
+        if let Some(update_group_timeout) = self.update_group_timeout {
+            server.set_update_group_timeout(update_group_timeout);
+        }
+        if let Some(edit_transaction_timeout) = self.edit_transaction_timeout {
+            server.set_edit_transaction_timeout(edit_transaction_timeout);
+        }
+        if let Some(rpc_timeout) = self.rpc_timeout {
+            server.set_rpc_timeout(rpc_timeout);
+        }
+        server.set_max_value_bytes(self.max_value_bytes);
+        if let Some(derived_refresh_interval) = self.derived_refresh_interval {
+            server.set_derived_refresh_interval(derived_refresh_interval);
+        }
+        server.set_sync_budget(self.sync_budget);
+        if let Some(animation_step) = self.animation_step {
+            server.set_animation_step(animation_step);
+        }
+        #[cfg(feature = "jsonrpc")]
+        server.set_jsonrpc_listener(self.jsonrpc_address)?;
+        #[cfg(feature = "discovery")]
+        if self.announces {
+            let discovery_target = self.discovery_target.unwrap_or_else(|| Beacon::default_target(Some(public_addr)));
+            server.set_discovery_beacon(Some(discovery_target))?;
+            server.set_announced_addr(public_addr);
+        }
+        server.set_read_dir(self.read_dir)?;
+        #[cfg(feature = "fault-injection")]
+        server.set_fault_injector(self.fault_injector);
         if self.only_reads_from_dir {
             server.set_only_reads_from_dir(true);
         }
+        for (name, options) in self.declarations {
+            server.declare(name, options);
+        }
         (self.after_build)(&mut server);
-        server
+        Ok(server)
+    }
+
+    pub fn build(self) -> DebuggableServer {
+        self.try_build().unwrap()
     }
 }
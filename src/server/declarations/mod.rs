@@ -0,0 +1,25 @@
+/// Options of a debuggable declared before its owner registers it, the owner's own options take
+/// precedence where it sets them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeclaredOptions {
+    pub order: i32,
+    pub hidden: bool,
+    pub keep: bool,
+}
+
+impl DeclaredOptions {
+    pub fn order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn keep(mut self) -> Self {
+        self.keep = true;
+        self
+    }
+}
@@ -0,0 +1,98 @@
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+thread_local! {
+    // Pushed by DefaultsGuard, innermost last, keyed by the address of the server and the guard
+    static SCOPED_DEFAULTS: RefCell<Vec<(usize, u64, DebuggableDefaults)>> = RefCell::new(Vec::new());
+    static NEXT_GUARD: Cell<u64> = Cell::new(0);
+}
+
+/// Name prefix and options debuggables registered on a server start from, see
+/// DebuggableServer::set_default_options and DebuggableServer::scoped_defaults. A builder's own
+/// options take precedence where it sets them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebuggableDefaults {
+    pub prefix: String,
+    pub order: i32,
+    pub hidden: bool,
+    pub keep: bool,
+    /// Refuses updates from clients, leaving the value to the host.
+    pub read_only: bool,
+    pub ttl: Option<Duration>,
+}
+
+impl DebuggableDefaults {
+    pub fn prefix<Prefix: ToString>(mut self, prefix: Prefix) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn keep(mut self) -> Self {
+        self.keep = true;
+        self
+    }
+
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Nests the given defaults inside these, whose prefix goes after this one and whose options
+    /// win where they set them.
+    pub(crate) fn nesting(&self, inner: &DebuggableDefaults) -> DebuggableDefaults {
+        DebuggableDefaults {
+            prefix: format!("{}{}", self.prefix, inner.prefix),
+            order: if inner.order == 0 { self.order } else { inner.order },
+            hidden: self.hidden || inner.hidden,
+            keep: self.keep || inner.keep,
+            read_only: self.read_only || inner.read_only,
+            ttl: inner.ttl.or(self.ttl),
+        }
+    }
+}
+
+/// Applies its defaults to the debuggables built on this thread for its server until dropped.
+#[must_use = "the defaults only apply while the guard is alive"]
+pub struct DefaultsGuard {
+    server_key: usize,
+    guard_id: u64,
+    // Tied to the thread whose registrations it affects
+    _not_send: PhantomData<*const ()>,
+}
+
+impl DefaultsGuard {
+    pub(crate) fn push(server_key: usize, defaults: DebuggableDefaults) -> Self {
+        let guard_id = NEXT_GUARD.with(|next_guard| next_guard.replace(next_guard.get() + 1));
+        SCOPED_DEFAULTS.with(|scoped| scoped.borrow_mut().push((server_key, guard_id, defaults)));
+        Self { server_key, guard_id, _not_send: PhantomData }
+    }
+}
+
+impl Drop for DefaultsGuard {
+    fn drop(&mut self) {
+        SCOPED_DEFAULTS.with(|scoped| scoped.borrow_mut().retain(|(server_key, guard_id, _)| (*server_key, *guard_id) != (self.server_key, self.guard_id)));
+    }
+}
+
+/// Defaults of the guards alive on this thread for the server, nested in the given ones.
+pub(crate) fn scoped_on_this_thread(server_key: usize, server_defaults: &DebuggableDefaults) -> DebuggableDefaults {
+    SCOPED_DEFAULTS.with(|scoped| scoped.borrow().iter()
+        .filter(|(guard_server, _, _)| *guard_server == server_key)
+        .fold(server_defaults.clone(), |outer, (_, _, inner)| outer.nesting(inner)))
+}
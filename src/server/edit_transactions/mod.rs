@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Time an edit transaction may go without being staged into before it's dropped, unless changed
+/// through DebuggableServer::set_edit_transaction_timeout.
+pub const DEFAULT_EDIT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Values clients staged in their open edit transactions, kept apart from the debuggables until
+/// committed so neither the host nor other clients see them.
+#[derive(Debug)]
+pub(crate) struct EditTransactions {
+    open: HashMap<(usize, u64), OpenTransaction>,
+    timeout: Duration,
+}
+
+#[derive(Debug)]
+struct OpenTransaction {
+    staged: Vec<(usize, String)>,
+    last_activity: Instant,
+}
+
+impl EditTransactions {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self { open: HashMap::new(), timeout }
+    }
+
+    pub(crate) fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Opens the transaction, failing if the client already has one open under the same id.
+    pub(crate) fn begin(&mut self, client: usize, txn_id: u64, now: Instant) -> Result<(), String> {
+        if self.open.contains_key(&(client, txn_id)) {
+            return Err(format!("Edit transaction {txn_id} is already open"));
+        }
+        self.open.insert((client, txn_id), OpenTransaction { staged: Vec::new(), last_activity: now });
+        Ok(())
+    }
+
+    /// Stages the value, replacing any staged before for the same debuggable.
+    pub(crate) fn stage(&mut self, client: usize, txn_id: u64, debuggable_id: usize, new_value: String, now: Instant) -> Result<(), String> {
+        let Some(transaction) = self.open.get_mut(&(client, txn_id)) else {
+            return Err(format!("Edit transaction {txn_id} isn't open"));
+        };
+        transaction.last_activity = now;
+        match transaction.staged.iter_mut().find(|(id, _)| *id == debuggable_id) {
+            None => transaction.staged.push((debuggable_id, new_value)),
+            Some((_, staged_value)) => *staged_value = new_value,
+        }
+        Ok(())
+    }
+
+    /// Closes the transaction, returning the values staged in it.
+    pub(crate) fn close(&mut self, client: usize, txn_id: u64) -> Result<Vec<(usize, String)>, String> {
+        self.open.remove(&(client, txn_id))
+            .map(|transaction| transaction.staged)
+            .ok_or_else(|| format!("Edit transaction {txn_id} isn't open"))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    /// Number of values staged and the bytes they take.
+    pub(crate) fn staged_size(&self) -> (usize, usize) {
+        self.open.values().flat_map(|transaction| &transaction.staged)
+            .fold((0, 0), |(values, bytes), (_, new_value)| (values + 1, bytes + new_value.len()))
+    }
+
+    /// Drops the transactions that went without activity for longer than the timeout, returning
+    /// the client and id of each.
+    pub(crate) fn take_expired(&mut self, now: Instant) -> Vec<(usize, u64)> {
+        let timeout = self.timeout;
+        let expired = self.open.iter()
+            .filter(|(_, transaction)| now.saturating_duration_since(transaction.last_activity) >= timeout)
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        expired.iter().for_each(|key| { self.open.remove(key); });
+        expired
+    }
+
+    pub(crate) fn forget_client(&mut self, client: usize) {
+        self.open.retain(|(owner, _), _| *owner != client);
+    }
+
+    pub(crate) fn remap_ids(&mut self, remapped_ids: &HashMap<usize, usize>) {
+        self.open.values_mut().flat_map(|transaction| &mut transaction.staged)
+            .for_each(|(id, _)| *id = remapped_ids.get(id).copied().unwrap_or(*id));
+    }
+
+    /// Drops the values staged for the debuggable, as they could never be applied.
+    pub(crate) fn forget_debuggable(&mut self, debuggable_id: usize) {
+        self.open.values_mut().for_each(|transaction| transaction.staged.retain(|(id, _)| *id != debuggable_id));
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
+
+/// Events each subscriber may have waiting before the oldest ones are dropped.
+pub const EVENT_QUEUE_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    ClientConnected { index: usize, addr: Option<SocketAddr> },
+    ClientDisconnected { index: usize },
+    /// The server disconnected the client for not keeping up with the messages sent to it.
+    ClientDropped { index: usize },
+    DebuggableAdded { id: usize, name: String },
+    DebuggableRemoved { id: usize },
+    ValueChanged { id: usize, origin: ChangeOrigin },
+    UpdateRejected { id: usize, client: usize },
+    /// The read directory could no longer be read after having been, emitted once per outage.
+    ReadDirUnavailable { path: String },
+    /// The read directory can be read again after being unavailable.
+    ReadDirRecovered { path: String },
+}
+
+/// Who caused a ServerEvent::ValueChanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOrigin {
+    /// The host wrote the value.
+    Host,
+    /// An update from the client with the given index won.
+    Client { index: usize },
+    /// A step of an animation requested by a client.
+    Animation,
+    /// A group reset restored the value the debuggable was registered with.
+    GroupReset,
+    /// A set sent through the JSON-RPC listener, see server::jsonrpc.
+    JsonRpc,
+}
+
+impl ChangeOrigin {
+    /// Index of the client whose update won, if a client's update caused the change.
+    pub fn client_index(&self) -> Option<usize> {
+        match self {
+            ChangeOrigin::Client { index } => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PendingEvents {
+    events: VecDeque<ServerEvent>,
+    is_closed: bool,
+}
+
+#[derive(Debug, Default)]
+struct Subscriber {
+    pending: Mutex<PendingEvents>,
+    has_events: Condvar,
+}
+
+impl Subscriber {
+    fn close(&self) {
+        self.pending.lock().unwrap().is_closed = true;
+        self.has_events.notify_one();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.pending.lock().unwrap().is_closed
+    }
+
+    /// Hands events to the receiver one at a time, those arriving meanwhile wait in the bounded
+    /// queue. Stops once the receiver is dropped, or the server is and no events are left.
+    fn forward(&self, sender: SyncSender<ServerEvent>) {
+        loop {
+            let event = {
+                let mut pending = self.pending.lock().unwrap();
+                while pending.events.is_empty() && !pending.is_closed {
+                    pending = self.has_events.wait(pending).unwrap();
+                }
+                // Events emitted before the server was dropped are still delivered
+                let Some(event) = pending.events.pop_front() else { return; };
+                event
+            };
+            if sender.send(event).is_err() {
+                self.pending.lock().unwrap().is_closed = true;
+                return;
+            }
+        }
+    }
+}
+
+/// Subscribers of a server's events. Sending never blocks: a subscriber that doesn't keep up
+/// loses its oldest events, and those whose receiver was dropped are pruned.
+#[derive(Debug, Default)]
+pub(crate) struct EventSubscribers {
+    subscribers: Mutex<Vec<Arc<Subscriber>>>,
+}
+
+impl EventSubscribers {
+    pub(crate) fn subscribe(&self) -> Receiver<ServerEvent> {
+        // Without buffering the forwarder only takes an event once the receiver asks for it, so
+        // the ones waiting stay in the queue that drops the oldest
+        let (sender, receiver) = sync_channel(0);
+        let subscriber = Arc::new(Subscriber::default());
+        let forwarded = subscriber.clone();
+        thread::spawn(move || forwarded.forward(sender));
+        self.subscribers.lock().unwrap().push(subscriber);
+        receiver
+    }
+
+    pub(crate) fn emit(&self, event: ServerEvent) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if subscribers.is_empty() { return; }
+        subscribers.retain(|subscriber| !subscriber.is_closed());
+        for subscriber in subscribers.iter() {
+            let mut pending = subscriber.pending.lock().unwrap();
+            if pending.events.len() >= EVENT_QUEUE_CAPACITY {
+                pending.events.pop_front();
+            }
+            pending.events.push_back(event.clone());
+            drop(pending);
+            subscriber.has_events.notify_one();
+        }
+    }
+}
+
+impl Drop for EventSubscribers {
+    fn drop(&mut self) {
+        self.subscribers.lock().unwrap().iter().for_each(|subscriber| subscriber.close());
+    }
+}
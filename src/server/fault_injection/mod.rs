@@ -0,0 +1,156 @@
+use std::fmt::Debug;
+#[cfg(feature = "fault-injection")]
+use std::sync::atomic::{AtomicU32, Ordering};
+#[cfg(feature = "fault-injection")]
+use std::sync::Mutex;
+
+/// Points of the server's traffic where faults can be injected, asked once for each message sent
+/// to a client, each message received, each accept cycle and each read of the read directory.
+pub(crate) trait Faults: Debug + Send + Sync {
+    /// Whether any fault can be injected at all, so the send path can skip asking per client.
+    fn injects_any(&self) -> bool;
+    fn drops_outgoing(&self) -> bool;
+    fn duplicates_incoming(&self) -> bool;
+    fn skips_accept(&self) -> bool;
+    fn defers_dir_read(&self) -> bool;
+}
+
+/// Leaves every message and cycle untouched, used unless a FaultInjector is set.
+#[derive(Debug, Default)]
+pub(crate) struct NoFaults;
+
+impl Faults for NoFaults {
+    fn injects_any(&self) -> bool {
+        false
+    }
+
+    fn drops_outgoing(&self) -> bool {
+        false
+    }
+
+    fn duplicates_incoming(&self) -> bool {
+        false
+    }
+
+    fn skips_accept(&self) -> bool {
+        false
+    }
+
+    fn defers_dir_read(&self) -> bool {
+        false
+    }
+}
+
+/// Disturbs the server's traffic the way a flaky link would, so hosts can check they cope with it.
+/// Faults are drawn from a generator seeded with the given seed, so a run that polls the server
+/// the same way injects the same faults. Injected faults are counted in ServerStats.
+///
+/// ```
+/// use std::net::TcpListener;
+/// use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+/// use debug_monitor::server::fault_injection::FaultInjector;
+///
+/// let injector = FaultInjector::new(42).drop_outgoing(20).duplicate_incoming(10);
+/// let server = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+///     .fault_injector(injector)
+///     .try_build()
+///     .unwrap();
+/// ```
+#[cfg(feature = "fault-injection")]
+#[derive(Debug)]
+pub struct FaultInjector {
+    random: Mutex<SplitMix64>,
+    drop_outgoing_percent: u8,
+    duplicate_incoming_percent: u8,
+    skip_accept_percent: u8,
+    dir_delay_polls: u32,
+    dir_polls_waited: AtomicU32,
+}
+
+#[cfg(feature = "fault-injection")]
+impl FaultInjector {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            random: Mutex::new(SplitMix64(seed)),
+            drop_outgoing_percent: 0,
+            duplicate_incoming_percent: 0,
+            skip_accept_percent: 0,
+            dir_delay_polls: 0,
+            dir_polls_waited: AtomicU32::new(0),
+        }
+    }
+
+    /// Drops the given percentage of the messages sent to each client.
+    pub fn drop_outgoing(mut self, percent: u8) -> Self {
+        self.drop_outgoing_percent = percent.min(100);
+        self
+    }
+
+    /// Processes the given percentage of the messages received from clients twice in a row.
+    pub fn duplicate_incoming(mut self, percent: u8) -> Self {
+        self.duplicate_incoming_percent = percent.min(100);
+        self
+    }
+
+    /// Skips the given percentage of the cycles accepting new clients, which then connect later.
+    pub fn skip_accepts(mut self, percent: u8) -> Self {
+        self.skip_accept_percent = percent.min(100);
+        self
+    }
+
+    /// Leaves the read directory unread for the given number of polls before each read.
+    pub fn delay_dir_transactions(mut self, polls: u32) -> Self {
+        self.dir_delay_polls = polls;
+        self
+    }
+
+    fn rolls(&self, percent: u8) -> bool {
+        percent > 0 && self.random.lock().unwrap().next() % 100 < percent as u64
+    }
+}
+
+#[cfg(feature = "fault-injection")]
+impl Faults for FaultInjector {
+    fn injects_any(&self) -> bool {
+        true
+    }
+
+    fn drops_outgoing(&self) -> bool {
+        self.rolls(self.drop_outgoing_percent)
+    }
+
+    fn duplicates_incoming(&self) -> bool {
+        self.rolls(self.duplicate_incoming_percent)
+    }
+
+    fn skips_accept(&self) -> bool {
+        self.rolls(self.skip_accept_percent)
+    }
+
+    fn defers_dir_read(&self) -> bool {
+        let waited = self.dir_polls_waited.load(Ordering::Relaxed);
+        if waited >= self.dir_delay_polls {
+            self.dir_polls_waited.store(0, Ordering::Relaxed);
+            return false;
+        }
+        self.dir_polls_waited.store(waited + 1, Ordering::Relaxed);
+        true
+    }
+}
+
+/// Small generator whose sequence only depends on its seed, as faults need to be reproducible
+/// rather than unpredictable.
+#[cfg(feature = "fault-injection")]
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+#[cfg(feature = "fault-injection")]
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = self.0;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+}
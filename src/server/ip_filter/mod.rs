@@ -0,0 +1,89 @@
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    pub fn new(network: IpAddr, prefix_len: u8) -> Option<IpRange> {
+        let max_prefix_len = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_prefix_len { return None; }
+        Some(Self { network, prefix_len })
+    }
+
+    pub fn single(address: IpAddr) -> IpRange {
+        let prefix_len = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        Self { network: address, prefix_len }
+    }
+
+    pub fn loopback() -> Vec<IpRange> {
+        vec![
+            Self { network: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 0)), prefix_len: 8 },
+            Self { network: IpAddr::V6(Ipv6Addr::LOCALHOST), prefix_len: 128 },
+        ]
+    }
+
+    pub fn contains(&self, address: IpAddr) -> bool {
+        let address = match address {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(address),
+            v4 => v4,
+        };
+        match (self.network, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                prefix_matches(u32::from(network) as u128, u32::from(address) as u128, self.prefix_len, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                prefix_matches(u128::from(network), u128::from(address), self.prefix_len, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, address: u128, prefix_len: u8, bits: u8) -> bool {
+    if prefix_len == 0 { return true; }
+    let shift = (bits - prefix_len) as u32;
+    (network >> shift) == (address >> shift)
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = match range.split_once('/') {
+            None => (range, None),
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+        };
+        let address = IpAddr::from_str(address.trim()).map_err(|error| format!("Invalid address in '{range}': {error}"))?;
+        match prefix_len {
+            None => Ok(Self::single(address)),
+            Some(prefix_len) => {
+                let prefix_len = u8::from_str(prefix_len.trim()).map_err(|error| format!("Invalid prefix in '{range}': {error}"))?;
+                Self::new(address, prefix_len).ok_or_else(|| format!("Prefix too long in '{range}'"))
+            }
+        }
+    }
+}
+
+impl Display for IpRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+pub(crate) fn is_allowed(allowed_ips: &Option<Vec<IpRange>>, address: IpAddr) -> bool {
+    match allowed_ips {
+        None => true,
+        Some(allowed_ips) => allowed_ips.iter().any(|range| range.contains(address)),
+    }
+}
@@ -0,0 +1,284 @@
+//! Newline-delimited JSON-RPC 2.0 on a secondary listener, for tooling that doesn't speak the
+//! native protocol.
+//!
+//! Methods are `list`, `get(name)`, `set(name, value)` and `subscribe(name)`. A `set` is answered
+//! once the debuggable syncs the value, with `true`, or with an error object if the debuggable
+//! rejected it. Subscribed connections are sent a `notify` notification, with the id, name and
+//! value of the debuggable as params, whenever the revision of the debuggable changes, so once
+//! per value the host or any client wrote.
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+
+use serde_json::{json, Value};
+
+use crate::server::DebuggableServer;
+use crate::server::pending_updates::UpdateOrigin;
+
+/// Client id updates set through JSON-RPC are queued under, see RESERVED_CLIENT_IDS.
+pub const JSONRPC_CLIENT_ID: usize = usize::MAX;
+
+/// Bytes a connection may have waiting to be written before it's dropped as too slow to read them.
+const MAX_UNSENT_BYTES: usize = 1024 * 1024;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const DEBUGGABLE_NOT_FOUND: i64 = -32001;
+const UPDATE_REJECTED: i64 = -32002;
+
+#[derive(Debug)]
+pub(crate) struct JsonRpcBridge {
+    listener: TcpListener,
+    connections: Vec<JsonRpcConnection>,
+    next_request_id: u64,
+}
+
+#[derive(Debug)]
+struct JsonRpcConnection {
+    stream: TcpStream,
+    received: String,
+    unsent: Vec<u8>,
+    // Last revision notified of each subscribed debuggable
+    subscriptions: HashMap<usize, Option<u64>>,
+    // JSON-RPC id of each set waiting for its acknowledgement, by the request id it was queued with
+    pending_sets: HashMap<u64, Value>,
+    is_closed: bool,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new<Message: ToString>(code: i64, message: Message) -> Self {
+        Self { code, message: message.to_string() }
+    }
+}
+
+impl JsonRpcBridge {
+    pub(crate) fn remap_ids(&mut self, remapped_ids: &HashMap<usize, usize>) {
+        self.connections.iter_mut().for_each(|connection| {
+            connection.subscriptions = mem::take(&mut connection.subscriptions).into_iter()
+                .map(|(id, last_sent)| (remapped_ids.get(&id).copied().unwrap_or(id), last_sent))
+                .collect();
+        });
+    }
+
+    pub(crate) fn bind(address: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, connections: Vec::new(), next_request_id: 0 })
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub(crate) fn poll(&mut self, server: &DebuggableServer) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_ok() {
+                        self.connections.push(JsonRpcConnection {
+                            stream,
+                            received: String::new(),
+                            unsent: Vec::new(),
+                            subscriptions: HashMap::new(),
+                            pending_sets: HashMap::new(),
+                            is_closed: false,
+                        });
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    log::warn!("Could not accept JSON-RPC connection: {error}");
+                    break;
+                }
+            }
+        }
+        let next_request_id = &mut self.next_request_id;
+        self.connections.iter_mut().for_each(|connection| connection.read_requests(server, next_request_id));
+        let acks = mem::take(&mut server.write().jsonrpc_acks);
+        self.connections.iter_mut().for_each(|connection| {
+            connection.answer_sets(&acks);
+            connection.push_subscriptions(server);
+            connection.flush();
+        });
+        self.connections.retain(|connection| !connection.is_closed);
+    }
+}
+
+impl JsonRpcConnection {
+    fn read_requests(&mut self, server: &DebuggableServer, next_request_id: &mut u64) {
+        let mut buffer = [0_u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.is_closed = true;
+                    break;
+                }
+                Ok(read) => self.received.push_str(&String::from_utf8_lossy(&buffer[..read])),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.is_closed = true;
+                    break;
+                }
+            }
+        }
+        while let Some(line_end) = self.received.find('\n') {
+            let line = self.received.drain(..=line_end).collect::<String>();
+            let line = line.trim();
+            if line.is_empty() { continue; }
+            if let Some(response) = self.respond_to(server, line, next_request_id) {
+                self.write_line(&response);
+            }
+        }
+    }
+
+    fn respond_to(&mut self, server: &DebuggableServer, line: &str, next_request_id: &mut u64) -> Option<Value> {
+        let request = match serde_json::from_str::<Value>(line) {
+            Ok(request) => request,
+            Err(error) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, error))),
+        };
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str);
+        let is_valid = request.get("jsonrpc").and_then(Value::as_str) == Some("2.0") && method.is_some();
+        if !is_valid {
+            return Some(error_response(id.unwrap_or(Value::Null), RpcError::new(INVALID_REQUEST, "Invalid request")));
+        }
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        if method == Some("set") {
+            return match self.set(server, &params, id.clone(), next_request_id) {
+                Ok(()) => None,
+                Err(error) => id.map(|id| error_response(id, error)),
+            };
+        }
+        let result = self.call(server, method.unwrap(), &params);
+        // Requests without id are notifications and never get a response
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn call(&mut self, server: &DebuggableServer, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "list" => Ok(Value::Array(server.visible_debuggables().into_iter()
+                .map(|(id, name, value)| json!({ "id": id, "name": name, "value": parse_value(&value) }))
+                .collect())),
+            "get" => {
+                let id = debuggable_id_of(server, params)?;
+                Ok(server.visible_value_of(id).map(|value| parse_value(&value)).unwrap_or(Value::Null))
+            }
+            "subscribe" => {
+                let id = debuggable_id_of(server, params)?;
+                self.subscriptions.insert(id, None);
+                Ok(Value::Bool(true))
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+        }
+    }
+
+    /// Queues the value through the same path as updates of the native protocol, answering once
+    /// the debuggable acknowledges it. Sets sent as notifications are queued without waiting.
+    fn set(&mut self, server: &DebuggableServer, params: &Value, rpc_id: Option<Value>, next_request_id: &mut u64) -> Result<(), RpcError> {
+        let id = debuggable_id_of(server, params)?;
+        let value = param(params, 1, "value").ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing value"))?;
+        if server.visible_name_of(id).is_none() {
+            return Err(RpcError::new(DEBUGGABLE_NOT_FOUND, "Debuggable not found"));
+        }
+        let request_id = rpc_id.map(|rpc_id| {
+            let request_id = *next_request_id;
+            *next_request_id += 1;
+            self.pending_sets.insert(request_id, rpc_id);
+            request_id
+        });
+        DebuggableServer::receive_update(server, JSONRPC_CLIENT_ID, id, value.to_string(), request_id, None, UpdateOrigin::Host);
+        Ok(())
+    }
+
+    fn answer_sets(&mut self, acks: &[(u64, bool)]) {
+        if self.pending_sets.is_empty() { return; }
+        for (request_id, accepted) in acks {
+            let Some(rpc_id) = self.pending_sets.remove(request_id) else { continue; };
+            let response = if *accepted {
+                json!({ "jsonrpc": "2.0", "id": rpc_id, "result": true })
+            } else {
+                error_response(rpc_id, RpcError::new(UPDATE_REJECTED, "Update rejected"))
+            };
+            self.write_line(&response);
+        }
+    }
+
+    fn push_subscriptions(&mut self, server: &DebuggableServer) {
+        let mut notifications = Vec::new();
+        self.subscriptions.retain(|id, last_sent| {
+            let Some(name) = server.visible_name_of(*id) else { return false; };
+            let revision = server.revision_of(*id);
+            if *last_sent != revision {
+                let value = server.visible_value_of(*id);
+                notifications.push(json!({
+                    "jsonrpc": "2.0",
+                    "method": "notify",
+                    "params": { "id": id, "name": name, "value": value.as_deref().map(parse_value).unwrap_or(Value::Null) }
+                }));
+                *last_sent = revision;
+            }
+            true
+        });
+        notifications.iter().for_each(|notification| self.write_line(notification));
+    }
+
+    fn write_line(&mut self, message: &Value) {
+        if self.is_closed { return; }
+        self.unsent.extend_from_slice(format!("{message}\n").as_bytes());
+        if self.unsent.len() > MAX_UNSENT_BYTES {
+            log::warn!("Dropping JSON-RPC connection {:?} as it isn't reading what it's sent", self.stream.peer_addr().ok());
+            self.is_closed = true;
+        }
+    }
+
+    /// Writes as much of what was queued as the socket takes without blocking, the rest is written
+    /// on later polls.
+    fn flush(&mut self) {
+        while !self.unsent.is_empty() && !self.is_closed {
+            match self.stream.write(&self.unsent) {
+                Ok(0) => self.is_closed = true,
+                Ok(written) => { self.unsent.drain(..written); }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.is_closed = true,
+            }
+        }
+    }
+}
+
+fn debuggable_id_of(server: &DebuggableServer, params: &Value) -> Result<usize, RpcError> {
+    let name = param(params, 0, "name").and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing debuggable name"))?;
+    server.debuggable_id_of(name)
+        .ok_or_else(|| RpcError::new(DEBUGGABLE_NOT_FOUND, format!("No debuggable named {name}")))
+}
+
+fn param<'params>(params: &'params Value, position: usize, name: &str) -> Option<&'params Value> {
+    match params {
+        Value::Array(params) => params.get(position),
+        Value::Object(params) => params.get(name),
+        _ => None,
+    }
+}
+
+fn parse_value(value_in_json: &str) -> Value {
+    serde_json::from_str(value_in_json).unwrap_or_else(|_| Value::String(value_in_json.to_string()))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.code, "message": error.message } })
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use socket2::{Domain, Socket, Type};
+
+use crate::client::MessageFraming;
+use crate::server::ip_filter;
+use crate::server::ip_filter::IpRange;
+
+/// Local addresses the server sees forwarded connections coming from, each with the peer the
+/// server's allow-list is applied to, None for peers their own listener already filtered.
+pub(crate) type ForwardedPeers = Arc<Mutex<HashMap<SocketAddr, Option<SocketAddr>>>>;
+
+/// How clients of a listener encode protocol messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    /// JSON messages separated by the server's endmark.
+    #[default]
+    Json,
+    /// Length-prefixed CBOR frames, see server::cbor.
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+/// A listener accepting clients next to the server's own, with its own allow-list and codec.
+#[derive(Debug)]
+pub struct AdditionalListener {
+    tcp_listener: TcpListener,
+    allowed_ips: Option<Vec<IpRange>>,
+    codec: Codec,
+}
+
+impl AdditionalListener {
+    pub fn new(tcp_listener: TcpListener) -> Self {
+        Self { tcp_listener, allowed_ips: None, codec: Codec::Json }
+    }
+
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    pub fn allow_ips(mut self, allowed_ips: Vec<IpRange>) -> Self {
+        self.allowed_ips = Some(allowed_ips);
+        self
+    }
+
+    pub fn loopback_only(self) -> Self {
+        self.allow_ips(IpRange::loopback())
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.tcp_listener.local_addr()
+    }
+}
+
+impl From<TcpListener> for AdditionalListener {
+    fn from(tcp_listener: TcpListener) -> Self {
+        Self::new(tcp_listener)
+    }
+}
+
+/// Forwards every client accepted by the listener to the server listening on the inner address,
+/// the same way TLS is terminated in front of it.
+pub(crate) fn spawn_forwarder(listener: AdditionalListener, inner_address: SocketAddr, forwarded_peers: ForwardedPeers, framing: MessageFraming) {
+    thread::spawn(move || {
+        for incoming in listener.tcp_listener.incoming() {
+            let Ok(client_stream) = incoming else { continue; };
+            let is_allowed = client_stream.peer_addr()
+                .map(|peer| ip_filter::is_allowed(&listener.allowed_ips, peer.ip()))
+                .unwrap_or(false);
+            if !is_allowed {
+                let _ = client_stream.shutdown(Shutdown::Both);
+                continue;
+            }
+            let forwarded_peers = forwarded_peers.clone();
+            let (codec, framing) = (listener.codec, framing.clone());
+            thread::spawn(move || {
+                let peer = client_stream.peer_addr().ok();
+                if let Err(error) = forward_client(client_stream, inner_address, &forwarded_peers, codec, framing) {
+                    log::warn!("Connection from {:?} to an additional listener dropped: {}", peer, error);
+                }
+            });
+        }
+    });
+}
+
+#[cfg_attr(not(feature = "cbor"), allow(unused_variables))]
+fn forward_client(client_stream: TcpStream, inner_address: SocketAddr, forwarded_peers: &ForwardedPeers, codec: Codec, framing: MessageFraming) -> io::Result<()> {
+    let inner_stream = connect_inner(inner_address, forwarded_peers, None)?;
+    #[cfg(feature = "cbor")]
+    if codec == Codec::Cbor {
+        return crate::server::cbor::forward_client(client_stream, inner_stream, framing);
+    }
+    let (mut client_reader, mut inner_writer) = (client_stream.try_clone()?, inner_stream.try_clone()?);
+    let upstream = thread::spawn(move || {
+        let _ = io::copy(&mut client_reader, &mut inner_writer);
+        let _ = inner_writer.shutdown(Shutdown::Write);
+    });
+    let (mut inner_reader, mut client_writer) = (inner_stream, client_stream);
+    let _ = io::copy(&mut inner_reader, &mut client_writer);
+    let _ = client_writer.shutdown(Shutdown::Both);
+    let _ = upstream.join();
+    Ok(())
+}
+
+/// Binds before connecting so the server knows the forwarded peer before accepting it.
+pub(crate) fn connect_inner(inner_address: SocketAddr, forwarded_peers: &ForwardedPeers, peer: Option<SocketAddr>) -> io::Result<TcpStream> {
+    let inner_address = match inner_address.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), inner_address.port()),
+        IpAddr::V6(ip) if ip.is_unspecified() => SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), inner_address.port()),
+        _ => inner_address,
+    };
+    let socket = Socket::new(Domain::for_address(inner_address), Type::STREAM, None)?;
+    socket.bind(&SocketAddr::new(inner_address.ip(), 0).into())?;
+    let local_addr = socket.local_addr()?.as_socket()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Forwarding socket has no address"))?;
+    forwarded_peers.lock().unwrap().insert(local_addr, peer);
+    match socket.connect(&inner_address.into()) {
+        Ok(()) => Ok(socket.into()),
+        Err(error) => {
+            forwarded_peers.lock().unwrap().remove(&local_addr);
+            Err(error)
+        }
+    }
+}
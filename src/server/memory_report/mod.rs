@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+/// What a server holds in memory as far as the crate can account for it, see
+/// DebuggableServer::memory_report. Sizes count the bytes of the JSON kept, not the overhead of the
+/// collections holding it, so they tell whether something grows rather than how much is allocated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub debuggables: usize,
+    /// Bytes of the last value of every debuggable.
+    pub last_value_bytes: usize,
+    /// Bytes of the values debuggables were registered with, kept for group resets.
+    pub initial_value_bytes: usize,
+    /// Updates received from clients that their debuggable didn't sync yet, only listing the
+    /// debuggables that have any.
+    pub pending: BTreeMap<usize, PendingSize>,
+    /// Updates held in update groups waiting for the rest of their group to sync.
+    pub held_group_updates: PendingSize,
+    /// Values staged in edit transactions clients didn't commit yet.
+    pub staged_edit_values: PendingSize,
+    /// Bytes of messages waiting to be written to clients by the outgoing queues.
+    pub outgoing_queue_bytes: usize,
+    pub clients: ClientBookkeeping,
+}
+
+impl MemoryReport {
+    pub fn pending_updates(&self) -> usize {
+        self.pending.values().map(|pending| pending.updates).sum()
+    }
+
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.values().map(|pending| pending.bytes).sum()
+    }
+
+    /// Every byte this report accounts for.
+    pub fn total_bytes(&self) -> usize {
+        self.last_value_bytes + self.initial_value_bytes + self.pending_bytes() + self.held_group_updates.bytes + self.staged_edit_values.bytes + self.outgoing_queue_bytes
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PendingSize {
+    pub updates: usize,
+    pub bytes: usize,
+}
+
+/// Entries the server keeps about clients, all of which should go once the clients disconnect.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientBookkeeping {
+    pub connected: usize,
+    pub protocol_versions: usize,
+    pub panels: usize,
+    pub slots: usize,
+    pub strikes: usize,
+    pub unknown_id_references: usize,
+    pub consecutive_rejections: usize,
+    pub deflate_clients: usize,
+    pub text_patch_clients: usize,
+    /// Clients allowed to edit summarized debuggables, summed over every debuggable.
+    pub full_value_fetchers: usize,
+    /// Clients waiting for on demand values, summed over every debuggable.
+    pub value_requesters: usize,
+}
+
+impl ClientBookkeeping {
+    /// Entries kept about clients, connections left out.
+    pub fn entries(&self) -> usize {
+        self.protocol_versions + self.panels + self.slots + self.strikes + self.unknown_id_references
+            + self.consecutive_rejections + self.deflate_clients + self.text_patch_clients + self.full_value_fetchers
+            + self.value_requesters
+    }
+}
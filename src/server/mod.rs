@@ -1,10 +1,14 @@
-use std::{fs, mem};
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{fs, io, mem};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
 use std::fs::metadata;
-use std::net::TcpListener;
-use std::ops::{Deref, DerefMut};
-use std::str::FromStr;
+use std::io::Write;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::ops::{Deref, DerefMut, RangeInclusive};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::mpsc::Receiver;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant, SystemTime};
 
 use fixed_index_vec::fixed_index_vec::FixedIndexVec;
 use simple_tcp::server::Server;
@@ -12,9 +16,134 @@ use simple_tcp::simple_server::{InnerSimpleServer, SimpleServer};
 use simple_tcp::simple_server::builder::SimpleServerBuilder;
 use simple_tcp::unchecked_read_write_lock::UncheckedRwLock;
 
-use crate::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+use crate::clock::{Clock, SystemClock};
+use crate::debuggable::derived_debuggable;
+use crate::protocol::framing::{self, unescape_in_place, Endmark};
+use crate::serializable::input_limits::{InputLimits, InputRejection};
+use crate::serializable::text_patch::{apply_hunks, diff_lines};
+use crate::snapshot;
+use crate::snapshot::{SnapshotDiff, SnapshotError};
+#[cfg(feature = "discovery")]
+use crate::discovery;
+#[cfg(feature = "discovery")]
+use crate::discovery::{Beacon, DiscoveredServer};
+use crate::serializable::framing::{Framing, FramingInfo};
+use crate::serializable::{capabilities, AddedOrigin, BASE_PROTOCOL_VERSION, RemoveReason, ClientUnitMessage, CompositeKind, JSONDeSerializable, NotifyEntry, CHANGE_INFO_PROTOCOL_VERSION, NOTIFY_INDEX_PROTOCOL_VERSION, NOTIFY_MANY_PROTOCOL_VERSION, PROTOCOL_VERSION, ServerMessage};
+use crate::server::debuggable_info::{DebuggableInfo, OwnedDebuggableInfo};
+use crate::server::ip_filter::IpRange;
+use crate::server::listeners::{AdditionalListener, ForwardedPeers};
+use crate::server::declarations::DeclaredOptions;
+use crate::server::callbacks::CallbackScope;
+use crate::server::defaults::{DebuggableDefaults, DefaultsGuard};
+use crate::server::overlay::{OverlayEntry, OverlayFeed};
+use crate::client::MessageFraming;
+use crate::server::outgoing::{OutgoingQueues, OverflowPolicy};
+use crate::server::socket_options::ClientSocketOptions;
+use crate::server::stats::{ServerStats, StatsCounters};
+use crate::server::memory_report::{ClientBookkeeping, MemoryReport, PendingSize};
+use crate::server::pending_updates::{push_bounded, PendingUpdate, PendingUpdateInfo, UpdateOrigin, DEFAULT_MAX_PENDING_UPDATES};
+use crate::server::sessions::Sessions;
+use crate::server::edit_transactions::{EditTransactions, DEFAULT_EDIT_TRANSACTION_TIMEOUT};
+use crate::server::update_groups::{UpdateGroups, ReleasedGroup, DEFAULT_UPDATE_GROUP_TIMEOUT};
+use crate::server::animations::{number_of, Animation, ANIMATION_CLIENT_ID, DEFAULT_ANIMATION_STEP};
+use crate::server::events::{ChangeOrigin, EventSubscribers, ServerEvent};
+use crate::server::notify_batch::NotifyBatch;
+use crate::server::composites::Composites;
+use crate::server::fault_injection::{Faults, NoFaults};
+#[cfg(feature = "fault-injection")]
+use crate::server::fault_injection::FaultInjector;
+#[cfg(feature = "tls")]
+use crate::server::tls::TlsSettings;
+
+/// A call to an RPC endpoint waiting for the host to serve it.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingRpcCall {
+    pub(crate) author: Author,
+    pub(crate) call_id: u64,
+    pub(crate) request_json: String,
+    received_at: Instant,
+}
+
+/// Client id group resets are queued under, so every client is notified of the restored values.
+pub const GROUP_RESET_CLIENT_ID: usize = usize::MAX - 2;
+
+/// Client ids the server queues updates under on behalf of something other than a connected
+/// client: GROUP_RESET_CLIENT_ID, animations::ANIMATION_CLIENT_ID and jsonrpc::JSONRPC_CLIENT_ID.
+/// Messages addressed to them aren't sent and rejections of their updates aren't counted.
+pub const RESERVED_CLIENT_IDS: RangeInclusive<usize> = GROUP_RESET_CLIENT_ID..=usize::MAX;
+
+/// Consecutive rejected updates of a debuggable from one client after which it's told what the
+/// debuggable expects.
+pub const DEFAULT_EXPLAIN_AFTER_REJECTIONS: u32 = 3;
+/// Consecutive rejected updates of a debuggable from one client after which its updates of that
+/// debuggable are ignored, until it sends Renotify or Hello.
+pub const DEFAULT_IGNORE_AFTER_REJECTIONS: u32 = 10;
+
+/// How long an RpcCall waits for the host to serve its endpoint before it's answered with an error.
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimum time between attempts to recreate a read directory that disappeared, see
+/// DebuggableServer::set_read_dir_create.
+pub const READ_DIR_RECREATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Minimum time between refreshes of derived debuggables triggered by polling.
+pub const DEFAULT_DERIVED_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest DebuggableServer::shutdown waits for messages already queued for clients to be written.
+pub const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Characters of a summarized value sent as the preview of its NotifySummary.
+pub const SUMMARY_PREVIEW_CHARS: usize = 256;
+/// Most bytes of a value sent in each ValueChunk, unless that would split a character.
+pub const VALUE_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Steps polling clients is made of, run in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PollStage {
+    Accept,
+    ReadSockets,
+    ReadDir,
+    Housekeeping,
+}
+
+impl PollStage {
+    pub(crate) const ALL: [PollStage; 4] = [PollStage::Accept, PollStage::ReadSockets, PollStage::ReadDir, PollStage::Housekeeping];
+}
 
 pub mod debuggable_server_builder;
+pub mod socket_options;
+pub mod outgoing;
+pub mod ip_filter;
+#[cfg(feature = "compression")]
+pub mod compression;
+pub mod stats;
+pub mod debuggable_info;
+pub mod update_groups;
+pub mod listeners;
+pub mod declarations;
+pub mod defaults;
+pub mod callbacks;
+pub mod overlay;
+pub mod animations;
+pub mod events;
+pub mod notify_batch;
+pub mod composites;
+pub mod fault_injection;
+pub mod memory_report;
+pub mod pending_updates;
+pub mod sessions;
+pub mod edit_transactions;
+pub mod crash_dump;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(feature = "jsonrpc")]
+pub mod jsonrpc;
+#[cfg(feature = "windows-pipes")]
+pub mod named_pipe;
+#[cfg(all(unix, feature = "capture-stdio"))]
+pub mod stdio_capture;
 
 #[derive(Debug)]
 pub struct DebuggableServer(SimpleServer<DebuggableServerData, ()>);
@@ -33,239 +162,3348 @@ impl DerefMut for DebuggableServer {
     }
 }
 
-#[derive(Debug)]
+pub type CustomMessageHandler = Box<dyn FnMut(usize, &str) + Send>;
+pub type ClientDisconnectHandler = Box<dyn FnMut(usize, Option<SocketAddr>) + Send>;
+/// Rewrites each serialized message right before it's framed and sent.
+pub type OutgoingTransform = Box<dyn Fn(&str) -> String + Send + Sync>;
+/// Rewrites each raw received message before it's parsed, returning None drops the message.
+pub type IncomingTransform = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientHandle {
+    pub index: usize,
+    pub generation: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ClientSlot {
+    generation: u64,
+    address: Option<SocketAddr>,
+}
+
 pub struct DebuggableServerData {
     debuggables: FixedIndexVec<DebuggableOnServer>,
     kept_debuggable_values: HashMap<String, usize>,
+    // Placeholders announced before their owner registers them
+    declarations: HashMap<String, (usize, DeclaredOptions)>,
+    default_options: DebuggableDefaults,
     only_reads_from_dir: bool,
     read_from_dir: Option<String>,
+    recreates_read_dir: bool,
+    read_dir_has_read: bool,
+    read_dir_unavailable: bool,
+    last_read_dir_recreation: Option<Instant>,
+    // Last transaction processed of each session of each client of the read dir
+    dir_sessions: HashMap<(usize, u64), u64>,
+    custom_handlers: HashMap<String, CustomMessageHandler>,
+    client_socket_options: ClientSocketOptions,
+    outgoing_queues: Option<OutgoingQueues>,
+    stats: Arc<StatsCounters>,
+    faults: Box<dyn Faults>,
+    events: EventSubscribers,
+    // Server the owners of this one's debuggables move them to on their next sync
+    adopted_by: Option<Arc<RwLock<DebuggableServer>>>,
+    is_paused: bool,
+    // Open NotifyBatches, notifications are held back while any is
+    batch_depth: usize,
+    batched_notifies: Vec<(usize, Who)>,
+    dirty_while_paused: HashSet<usize>,
+    refresh_interval: Option<Duration>,
+    last_refresh: Instant,
+    derived_refresh_interval: Duration,
+    last_derived_refresh: Option<Instant>,
+    allowed_ips: Option<Vec<IpRange>>,
+    compression_threshold: Option<usize>,
+    deflate_clients: HashSet<usize>,
+    text_patch_clients: HashSet<usize>,
+    next_registration: u64,
+    local_addr: Option<SocketAddr>,
+    additional_local_addrs: Vec<SocketAddr>,
+    forwarded_peers: ForwardedPeers,
+    // Set when TLS is terminated in front of the server, whose own listener then only takes the
+    // connections the terminator forwards
+    admits_only_forwarded: bool,
+    // Shared with the registrations of debuggables, which stop using the server once it's set
+    is_shut_down: Arc<AtomicBool>,
+    client_protocol_versions: HashMap<usize, u32>,
+    client_panels: HashMap<usize, String>,
+    sessions: Sessions,
+    // Whether clients are sent values once they say hello rather than once accepted
+    replays_values_on_hello: bool,
+    client_slots: HashMap<usize, ClientSlot>,
+    next_client_generation: u64,
+    on_client_disconnect: Option<ClientDisconnectHandler>,
+    clock: Arc<dyn Clock>,
+    notify_buffer: Mutex<Vec<u8>>,
+    input_limits: InputLimits,
+    max_strikes: Option<u32>,
+    client_strikes: HashMap<usize, u32>,
+    outgoing_transform: Option<OutgoingTransform>,
+    incoming_transform: Option<IncomingTransform>,
+    resync_threshold: Option<u32>,
+    explain_after_rejections: Option<u32>,
+    ignore_after_rejections: Option<u32>,
+    max_pending_updates: Option<usize>,
+    rpc_timeout: Duration,
+    // Values larger than this are broadcast as NotifySummary instead
+    max_value_bytes: Option<usize>,
+    // Keyed by client and debuggable, reset once an update of the client is accepted
+    consecutive_rejections: HashMap<(usize, usize), u32>,
+    unknown_id_references: HashMap<usize, u32>,
+    update_groups: UpdateGroups,
+    edit_transactions: EditTransactions,
+    composites: Composites,
+    compaction_threshold: Option<usize>,
+    removals_since_compaction: usize,
+    animation_step: Duration,
+    sync_budget: Option<Duration>,
+    // Stage of poll_clients the next call starts from, after a call ran out of budget
+    next_poll_stage: usize,
+    // Set by testing::StepServer, which runs each stage itself
+    polls_manually: bool,
+    #[cfg(feature = "jsonrpc")]
+    jsonrpc: Option<jsonrpc::JsonRpcBridge>,
+    // Acknowledgements addressed to jsonrpc::JSONRPC_CLIENT_ID, taken by the bridge as it polls
+    #[cfg(feature = "jsonrpc")]
+    jsonrpc_acks: Vec<(u64, bool)>,
+    #[cfg(feature = "discovery")]
+    beacon: Option<Beacon>,
+    #[cfg(all(unix, feature = "capture-stdio"))]
+    stdout_capture: Option<stdio_capture::StdoutCapture>,
+    #[cfg(feature = "windows-pipes")]
+    named_pipes: Vec<String>,
+}
+
+impl Debug for DebuggableServerData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug_struct = f.debug_struct("DebuggableServerData");
+        debug_struct
+            .field("debuggables", &self.debuggables)
+            .field("kept_debuggable_values", &self.kept_debuggable_values)
+            .field("declarations", &self.declarations)
+            .field("default_options", &self.default_options)
+            .field("only_reads_from_dir", &self.only_reads_from_dir)
+            .field("read_from_dir", &self.read_from_dir)
+            .field("recreates_read_dir", &self.recreates_read_dir)
+            .field("read_dir_has_read", &self.read_dir_has_read)
+            .field("read_dir_unavailable", &self.read_dir_unavailable)
+            .field("last_read_dir_recreation", &self.last_read_dir_recreation)
+            .field("dir_sessions", &self.dir_sessions)
+            .field("custom_topics", &self.custom_handlers.keys().collect::<Vec<_>>())
+            .field("client_socket_options", &self.client_socket_options)
+            .field("outgoing_queues", &self.outgoing_queues)
+            .field("stats", &self.stats)
+            .field("faults", &self.faults)
+            .field("events", &self.events)
+            .field("adopted_by", &self.adopted_by.is_some())
+            .field("is_paused", &self.is_paused)
+            .field("batch_depth", &self.batch_depth)
+            .field("batched_notifies", &self.batched_notifies.len())
+            .field("dirty_while_paused", &self.dirty_while_paused)
+            .field("refresh_interval", &self.refresh_interval)
+            .field("derived_refresh_interval", &self.derived_refresh_interval)
+            .field("last_derived_refresh", &self.last_derived_refresh)
+            .field("allowed_ips", &self.allowed_ips)
+            .field("compression_threshold", &self.compression_threshold)
+            .field("deflate_clients", &self.deflate_clients)
+            .field("text_patch_clients", &self.text_patch_clients)
+            .field("local_addr", &self.local_addr)
+            .field("additional_local_addrs", &self.additional_local_addrs)
+            .field("forwarded_peers", &self.forwarded_peers)
+            .field("admits_only_forwarded", &self.admits_only_forwarded)
+            .field("is_shut_down", &self.is_shut_down)
+            .field("client_protocol_versions", &self.client_protocol_versions)
+            .field("client_panels", &self.client_panels)
+            .field("sessions", &self.sessions)
+            .field("replays_values_on_hello", &self.replays_values_on_hello)
+            .field("client_slots", &self.client_slots)
+            .field("has_on_client_disconnect", &self.on_client_disconnect.is_some())
+            .field("clock", &self.clock)
+            .field("input_limits", &self.input_limits)
+            .field("max_strikes", &self.max_strikes)
+            .field("client_strikes", &self.client_strikes)
+            .field("has_outgoing_transform", &self.outgoing_transform.is_some())
+            .field("has_incoming_transform", &self.incoming_transform.is_some())
+            .field("resync_threshold", &self.resync_threshold)
+            .field("explain_after_rejections", &self.explain_after_rejections)
+            .field("ignore_after_rejections", &self.ignore_after_rejections)
+            .field("max_pending_updates", &self.max_pending_updates)
+            .field("rpc_timeout", &self.rpc_timeout)
+            .field("max_value_bytes", &self.max_value_bytes)
+            .field("consecutive_rejections", &self.consecutive_rejections)
+            .field("unknown_id_references", &self.unknown_id_references)
+            .field("update_groups", &self.update_groups)
+            .field("edit_transactions", &self.edit_transactions)
+            .field("composites", &self.composites)
+            .field("compaction_threshold", &self.compaction_threshold)
+            .field("removals_since_compaction", &self.removals_since_compaction)
+            .field("animation_step", &self.animation_step)
+            .field("sync_budget", &self.sync_budget)
+            .field("next_poll_stage", &self.next_poll_stage)
+            .field("polls_manually", &self.polls_manually);
+        #[cfg(feature = "jsonrpc")]
+        debug_struct.field("jsonrpc", &self.jsonrpc);
+        #[cfg(feature = "jsonrpc")]
+        debug_struct.field("jsonrpc_acks", &self.jsonrpc_acks);
+        #[cfg(feature = "discovery")]
+        debug_struct.field("beacon", &self.beacon);
+        #[cfg(all(unix, feature = "capture-stdio"))]
+        debug_struct.field("stdout_capture", &self.stdout_capture);
+        #[cfg(feature = "windows-pipes")]
+        debug_struct.field("named_pipes", &self.named_pipes);
+        debug_struct.finish()
+    }
+}
+
+impl DebuggableServerData {
+    /// Queues the update unless the debuggable is unknown, returning the updates evicted to stay
+    /// within max_pending_updates.
+    fn queue_update(&mut self, debuggable_id: usize, author: Author, request_id: Option<u64>, new_value: String, origin: UpdateOrigin) -> Option<Vec<PendingUpdate>> {
+        let received = self.clock.now_instant();
+        let max_pending_updates = self.max_pending_updates;
+        match self.debuggables.get_mut(debuggable_id) {
+            Some(debuggable) if !debuggable.hidden && !debuggable.removing => {
+                debuggable.animation = None;
+                let update = PendingUpdate { author, request_id, json: new_value, received, origin };
+                Some(push_bounded(&mut debuggable.incoming_jsons, update, max_pending_updates))
+            }
+            _ => None,
+        }
+    }
+
+    fn ignores_updates_of(&self, client_id: usize, debuggable_id: usize) -> bool {
+        let Some(ignore_after) = self.ignore_after_rejections else { return false; };
+        self.consecutive_rejections.get(&(client_id, debuggable_id)).is_some_and(|rejections| *rejections >= ignore_after)
+    }
+
+    fn forget_rejections_of(&mut self, client_id: usize) {
+        self.consecutive_rejections.retain(|(client, _), _| *client != client_id);
+    }
+
+    fn release_update_groups(&mut self, released_groups: Vec<ReleasedGroup>) {
+        for (client, updates) in released_groups {
+            for (debuggable_id, new_value) in updates {
+                self.queue_update(debuggable_id, self.author_of(client, None), None, new_value, UpdateOrigin::Group);
+            }
+        }
+    }
+
+    /// Attributes a message of the client to the given panel, or else to the one it said hello with.
+    fn author_of(&self, client_id: usize, panel: Option<String>) -> Author {
+        Author { client: client_id, panel: panel.or_else(|| self.client_panels.get(&client_id).cloned()) }
+    }
+
+    /// Clients among the given ones subscribed to the debuggable, see ClientUnitMessage::Subscribe.
+    fn subscribed_clients(&self, debuggable_id: usize, clients: &[usize]) -> Vec<usize> {
+        let Some(debuggable) = self.debuggables.get(debuggable_id).filter(|_| self.sessions.has_subscriptions()) else {
+            return clients.to_vec();
+        };
+        clients.iter().copied().filter(|client| self.sessions.is_subscribed(*client, &debuggable.name)).collect()
+    }
+
+    /// Visible debuggables whose name starts with the prefix.
+    fn group_ids_of(&self, prefix: &str) -> Vec<usize> {
+        self.debuggables.iter_index()
+            .filter(|(id, debuggable)| !debuggable.hidden && debuggable.name.starts_with(prefix) && !self.is_placeholder(*id))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Queues the initial value of every debuggable of the group as an update, returning the ids it
+    /// was queued for and those of debuggables that are read-only or never had a value.
+    fn reset_group(&mut self, prefix: &str) -> (Vec<usize>, Vec<usize>) {
+        let (mut applied, mut denied) = (Vec::new(), Vec::new());
+        for id in self.group_ids_of(prefix) {
+            let initial_value_json = self.debuggables.get(id)
+                .filter(|debuggable| !debuggable.read_only)
+                .and_then(|debuggable| debuggable.initial_value_json.clone());
+            match initial_value_json {
+                None => denied.push(id),
+                Some(initial_value_json) => {
+                    self.queue_update(id, Author::client(GROUP_RESET_CLIENT_ID), None, initial_value_json.to_string(), UpdateOrigin::Host);
+                    applied.push(id);
+                }
+            }
+        }
+        (applied, denied)
+    }
+
+    fn is_placeholder(&self, debuggable_id: usize) -> bool {
+        self.declarations.values().any(|(declared_id, _)| *declared_id == debuggable_id)
+    }
+
+    fn visible_debuggable(&self, debuggable_id: usize) -> Option<&DebuggableOnServer> {
+        self.debuggables.get(debuggable_id).filter(|debuggable| !debuggable.hidden && !debuggable.removing)
+    }
+
+    /// First debuggable the message edits that is being removed, if any.
+    fn removing_target_of(&self, message: &ClientUnitMessage) -> Option<usize> {
+        message.edited_ids().into_iter().find(|id| self.debuggables.get(*id).is_some_and(|debuggable| debuggable.removing))
+    }
+
+    /// Notify message of the debuggable, telling when and by whom it last changed if
+    /// with_change_info is set.
+    fn notify_message_of(&self, debuggable_id: usize, with_change_info: bool) -> Option<String> {
+        let debuggable = self.debuggables.get(debuggable_id)?;
+        ServerMessage::Notify {
+            id: debuggable_id,
+            name: debuggable.name.clone(),
+            value_in_json: debuggable.outgoing_value()?.to_string(),
+            revision: debuggable.revision,
+            changed_at_ms: debuggable.changed_at_ms().filter(|_| with_change_info),
+            author: debuggable.last_author.filter(|_| with_change_info),
+            uid: Some(debuggable.registration),
+            overridden: debuggable.overridden,
+        }.to_json()
+    }
+
+    /// Writes the Notify message of a debuggable into a reused buffer, borrowing its name and value
+    /// instead of building an owned ServerMessage
+    #[cfg(feature = "use_serde")]
+    fn write_notify_message_of(&self, debuggable_id: usize, with_change_info: bool, buffer: &mut Vec<u8>) -> bool {
+        #[derive(serde::Serialize)]
+        enum BorrowedNotify<'debuggable> {
+            Notify {
+                id: usize,
+                name: &'debuggable str,
+                value_in_json: &'debuggable str,
+                revision: u64,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                changed_at_ms: Option<u64>,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                author: Option<usize>,
+                uid: u64,
+                overridden: bool,
+            },
+        }
+        let Some(debuggable) = self.debuggables.get(debuggable_id) else { return false; };
+        // Values that couldn't be serialized are left unset
+        let Some(outgoing_value) = debuggable.outgoing_value() else { return false; };
+        let message = BorrowedNotify::Notify {
+            id: debuggable_id,
+            name: &debuggable.name,
+            value_in_json: &outgoing_value,
+            revision: debuggable.revision,
+            changed_at_ms: debuggable.changed_at_ms().filter(|_| with_change_info),
+            author: debuggable.last_author.filter(|_| with_change_info),
+            uid: debuggable.registration,
+            overridden: debuggable.overridden,
+        };
+        buffer.clear();
+        serde_json::to_writer(&mut *buffer, &message).is_ok()
+    }
+
+    #[cfg(not(feature = "use_serde"))]
+    fn write_notify_message_of(&self, debuggable_id: usize, with_change_info: bool, buffer: &mut Vec<u8>) -> bool {
+        let Some(message) = self.notify_message_of(debuggable_id, with_change_info) else { return false; };
+        buffer.clear();
+        buffer.extend_from_slice(message.as_bytes());
+        true
+    }
+
+    /// Left out for summarized debuggables, which are sent through send_notify_to instead.
+    fn notify_entry_of(&self, debuggable_id: usize) -> Option<NotifyEntry> {
+        let debuggable = self.debuggables.get(debuggable_id)?;
+        if debuggable.hidden || debuggable.on_demand || self.is_summarized(debuggable_id) { return None; }
+        Some(NotifyEntry {
+            id: debuggable_id,
+            name: debuggable.name.clone(),
+            value_in_json: debuggable.outgoing_value()?.to_string(),
+            revision: debuggable.revision,
+            overridden: debuggable.overridden,
+        })
+    }
+
+    fn is_summarized(&self, debuggable_id: usize) -> bool {
+        let Some(max_value_bytes) = self.max_value_bytes else { return false; };
+        self.debuggables.get(debuggable_id)
+            .and_then(DebuggableOnServer::outgoing_value)
+            .is_some_and(|outgoing_value| outgoing_value.len() > max_value_bytes)
+    }
+
+    /// NotifySummary sent instead of the Notify of a debuggable whose value exceeds max_value_bytes.
+    fn summary_message_of(&self, debuggable_id: usize) -> Option<ServerMessage> {
+        let max_value_bytes = self.max_value_bytes?;
+        let debuggable = self.debuggables.get(debuggable_id)?;
+        let outgoing_value = debuggable.outgoing_value()?;
+        if outgoing_value.len() <= max_value_bytes { return None; }
+        Some(ServerMessage::NotifySummary {
+            id: debuggable_id,
+            name: debuggable.name.clone(),
+            byte_len: outgoing_value.len(),
+            preview: outgoing_value.chars().take(SUMMARY_PREVIEW_CHARS).collect(),
+        })
+    }
+
+    /// Why edits of the client are refused, either the debuggable is read-only or it's summarized
+    /// and the client didn't request its full value.
+    fn edit_refusal_of(&self, client_id: usize, debuggable_id: usize) -> Option<String> {
+        let debuggable = self.debuggables.get(debuggable_id)?;
+        if debuggable.read_only {
+            return Some(format!("Debuggable {debuggable_id} is read-only"));
+        }
+        if self.is_summarized(debuggable_id) && !debuggable.full_value_fetched_by.contains(&client_id) {
+            return Some(format!("Debuggable {debuggable_id} is summarized, request its full value before editing it"));
+        }
+        None
+    }
+
+    fn metadata_message_of(&self, debuggable_id: usize) -> Option<ServerMessage> {
+        let debuggable = self.debuggables.get(debuggable_id)?;
+        if debuggable.hidden { return None; }
+        Some(ServerMessage::Metadata {
+            id: debuggable_id,
+            nullable: debuggable.nullable,
+            order: debuggable.order,
+            on_demand: debuggable.on_demand,
+        })
+    }
+
+    fn transform_outgoing(&self, message: String) -> String {
+        match self.outgoing_transform.as_ref() {
+            None => message,
+            Some(outgoing_transform) => outgoing_transform(&message),
+        }
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        let mut supported = vec![capabilities::NOTIFY_MANY, capabilities::CAS, capabilities::INDEX_UPDATES, capabilities::ADDED,
+                                 capabilities::UPDATE_ACKS, capabilities::UPDATE_GROUPS, capabilities::CUSTOM_MESSAGES, capabilities::ANIMATIONS,
+                                 capabilities::GROUP_OPERATIONS, capabilities::RPC, capabilities::COMPOSITES, capabilities::TEXT_PATCHES,
+                                 capabilities::ON_DEMAND];
+        if cfg!(feature = "compression") && self.compression_threshold.is_some() {
+            supported.push(capabilities::DEFLATE);
+        }
+        #[cfg(feature = "jsonrpc")]
+        if self.jsonrpc.is_some() {
+            supported.push(capabilities::JSONRPC);
+        }
+        if self.read_from_dir.is_some() {
+            supported.push(capabilities::READ_DIR);
+        }
+        if self.max_value_bytes.is_some() {
+            supported.push(capabilities::VALUE_SUMMARIES);
+        }
+        supported.into_iter().map(str::to_string).collect()
+    }
+
+    fn server_info_message(&self, framing: FramingInfo) -> ServerMessage {
+        ServerMessage::ServerInfo {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: self.capabilities(),
+            debuggable_count: self.debuggables.iter_index().filter(|(_, debuggable)| !debuggable.hidden).count(),
+            framing: Some(framing),
+        }
+    }
+
+    fn protocol_version_of(&self, client_index: usize) -> u32 {
+        self.client_protocol_versions.get(&client_index).copied().unwrap_or(BASE_PROTOCOL_VERSION)
+    }
+
+    #[cfg(feature = "compression")]
+    fn encoded_notify_message_of(&self, debuggable_id: usize) -> Option<String> {
+        let threshold = self.compression_threshold?;
+        let debuggable = self.debuggables.get(debuggable_id)?;
+        let outgoing_value = debuggable.outgoing_value()?;
+        if outgoing_value.len() <= threshold { return None; }
+        ServerMessage::NotifyEncoded {
+            id: debuggable_id,
+            name: debuggable.name.clone(),
+            encoding: compression::DEFLATE_ENCODING.to_string(),
+            value_in_json: compression::compress(&outgoing_value),
+            revision: debuggable.revision,
+        }.to_json()
+    }
 }
 
 impl DebuggableServer {
     pub fn new(tcp_listener: TcpListener) -> DebuggableServer {
-        let server = SimpleServerBuilder::new(tcp_listener,
+        Self::with_endmark(tcp_listener, None)
+    }
+
+    /// Builds the server delimiting messages with the given endmark and escape instead of those
+    /// of simple_tcp, these must have passed serializable::framing::validate_endmark.
+    pub(crate) fn with_endmark(tcp_listener: TcpListener, endmark: Option<(String, String)>) -> DebuggableServer {
+        let local_addr = tcp_listener.local_addr().ok();
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let builder = SimpleServerBuilder::new(tcp_listener,
                                               DebuggableServerData {
                                                   debuggables: FixedIndexVec::new(),
                                                   kept_debuggable_values: Default::default(),
+                                                  declarations: HashMap::new(),
+                                                  default_options: Default::default(),
                                                   only_reads_from_dir: false,
                                                   read_from_dir: None,
+                                                  recreates_read_dir: false,
+                                                  read_dir_has_read: false,
+                                                  read_dir_unavailable: false,
+                                                  last_read_dir_recreation: None,
+                                                  dir_sessions: HashMap::new(),
+                                                  custom_handlers: HashMap::new(),
+                                                  client_socket_options: Default::default(),
+                                                  outgoing_queues: None,
+                                                  stats: Default::default(),
+                                                  faults: Box::new(NoFaults),
+                                                  events: Default::default(),
+                                                  adopted_by: None,
+                                                  is_paused: false,
+                                                  batch_depth: 0,
+                                                  batched_notifies: Vec::new(),
+                                                  dirty_while_paused: HashSet::new(),
+                                                  refresh_interval: None,
+                                                  last_refresh: clock.now_instant(),
+                                                  derived_refresh_interval: DEFAULT_DERIVED_REFRESH_INTERVAL,
+                                                  last_derived_refresh: None,
+                                                  allowed_ips: None,
+                                                  compression_threshold: None,
+                                                  deflate_clients: HashSet::new(),
+                                                  text_patch_clients: HashSet::new(),
+                                                  next_registration: 0,
+                                                  local_addr,
+                                                  additional_local_addrs: Vec::new(),
+                                                  forwarded_peers: Default::default(),
+                                                  admits_only_forwarded: false,
+                                                  is_shut_down: Default::default(),
+                                                  client_protocol_versions: HashMap::new(),
+                                                  client_panels: HashMap::new(),
+                                                  sessions: Sessions::new(),
+                                                  replays_values_on_hello: false,
+                                                  client_slots: HashMap::new(),
+                                                  next_client_generation: 0,
+                                                  on_client_disconnect: None,
+                                                  clock,
+                                                  notify_buffer: Mutex::new(Vec::new()),
+                                                  input_limits: Default::default(),
+                                                  max_strikes: None,
+                                                  client_strikes: HashMap::new(),
+                                                  outgoing_transform: None,
+                                                  incoming_transform: None,
+                                                  resync_threshold: None,
+                                                  explain_after_rejections: Some(DEFAULT_EXPLAIN_AFTER_REJECTIONS),
+                                                  ignore_after_rejections: Some(DEFAULT_IGNORE_AFTER_REJECTIONS),
+                                                  max_pending_updates: Some(DEFAULT_MAX_PENDING_UPDATES),
+                                                  rpc_timeout: DEFAULT_RPC_TIMEOUT,
+                                                  max_value_bytes: None,
+                                                  consecutive_rejections: HashMap::new(),
+                                                  unknown_id_references: HashMap::new(),
+                                                  update_groups: UpdateGroups::new(DEFAULT_UPDATE_GROUP_TIMEOUT),
+                                                  edit_transactions: EditTransactions::new(DEFAULT_EDIT_TRANSACTION_TIMEOUT),
+                                                  composites: Default::default(),
+                                                  compaction_threshold: None,
+                                                  removals_since_compaction: 0,
+                                                  animation_step: DEFAULT_ANIMATION_STEP,
+                                                  sync_budget: None,
+                                                  next_poll_stage: 0,
+                                                  polls_manually: false,
+                                                  #[cfg(feature = "jsonrpc")]
+                                                  jsonrpc: None,
+                                                  #[cfg(feature = "jsonrpc")]
+                                                  jsonrpc_acks: Vec::new(),
+                                                  #[cfg(feature = "discovery")]
+                                                  beacon: None,
+                                                  #[cfg(all(unix, feature = "capture-stdio"))]
+                                                  stdout_capture: None,
+                                                  #[cfg(feature = "windows-pipes")]
+                                                  named_pipes: Vec::new(),
                                               }, |_, _, _| Some(()))
             .on_accept(|server, client_index| {
-                Self::init_client(server, client_index);
+                // A slot still being tracked means its previous client left without being noticed
+                Self::forget_client(server, client_index);
+                let Some(address) = Self::admit_client(server, client_index) else { return; };
+                Self::track_client_slot(server, client_index, address);
+                Self::apply_client_socket_options(server, client_index);
+                Self::register_outgoing_queue(server, client_index);
+                let sends_values = !server.read().replays_values_on_hello;
+                Self::init_client(server, client_index, sends_values);
             })
             .on_get_message(|server, client_id, message| {
-                Self::process_message_of(server, client_id, message)
+                Self::receive_message_of(server, client_id, message, UpdateOrigin::Socket)
             })
             .on_close(|server| {
-                let remove_all_debuggables_message = &*ServerMessage::RemoveAll.to_json().unwrap();
+                let remove_all_debuggables_message = &*server.read().transform_outgoing(ServerMessage::RemoveAll.to_json().unwrap());
                 (0..server.read().clients().len()).into_iter().for_each(|client_index| {
                     server.send_message_to_client(client_index, remove_all_debuggables_message);
                 })
-            })
-            .build();
-        Self { 0: server }
+            });
+        let (endmark, escape) = endmark.unwrap_or_else(|| (framing::DEFAULT_ENDMARK.to_string(), framing::DEFAULT_ESCAPE.to_string()));
+        Self { 0: builder.message_endmark(endmark, escape).build() }
     }
 
-    fn init_client(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) {
-        server.read().send_message_to_client(client_index, &*ServerMessage::GiveClientId { client_id: client_index }.to_json().unwrap());
-        for (debuggable_index, debuggable) in server.read().debuggables.iter_index() {
-            let notify_value_message = &*ServerMessage::Notify {
-                name: debuggable.name.clone(),
-                id: debuggable_index,
-                value_in_json: debuggable.last_value.clone().unwrap_or_else(|| "{}".to_string()),
-            }.to_json().unwrap();
-            server.read().send_message_to_client(client_index, notify_value_message);
+    /// Address the admitted client connects from, None if it's refused. Clients forwarded by the
+    /// TLS terminator are known by the peer it accepted, as the server only sees the terminator.
+    fn admit_client(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) -> Option<Option<SocketAddr>> {
+        let stream = Self::client_stream(server, client_index)?;
+        let peer = stream.peer_addr().ok();
+        let forwarded = peer.and_then(|peer| server.read().forwarded_peers.lock().unwrap().remove(&peer));
+        let (is_allowed, address) = match forwarded {
+            // Its own listener already filtered it
+            Some(None) => (true, peer),
+            Some(Some(forwarded_peer)) => (ip_filter::is_allowed(&server.read().allowed_ips, forwarded_peer.ip()), Some(forwarded_peer)),
+            None if server.read().admits_only_forwarded => {
+                log::warn!("Refusing connection from {peer:?}, which didn't come through TLS");
+                (false, peer)
+            }
+            None => (peer.map_or(server.read().allowed_ips.is_none(), |peer| ip_filter::is_allowed(&server.read().allowed_ips, peer.ip())), peer),
+        };
+        if !is_allowed {
+            let _ = stream.shutdown(Shutdown::Both);
+            server.read().stats.rejected_connections.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            return None;
         }
+        Some(address)
     }
 
-    fn process_message_of(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, message: String) {
-        let client_unit_message = ClientUnitMessage::from_json(&message);
-        if client_unit_message.is_none() {
-            return;
+    fn reject_message_of(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, rejection: InputRejection) {
+        server.read().stats.rejected_messages.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Self::send_server_message(server, &[client_id], &ServerMessage::Error { message: rejection.to_string(), panel: None });
+        let is_struck_out = {
+            let mut server = server.write();
+            let Some(max_strikes) = server.max_strikes else { return; };
+            let strikes = server.client_strikes.entry(client_id).or_insert(0);
+            *strikes += 1;
+            *strikes >= max_strikes
         };
-        let client_message = client_unit_message.unwrap();
-        match client_message {
-            ClientUnitMessage::UpdateValue { id, new_value } => {
-                match server.write().debuggables.get_mut(id) {
-                    None => { return; }
-                    Some(debuggable) => {
-                        debuggable.incoming_jsons.push((client_id, new_value));
-                    }
-                }
+        if !is_struck_out { return; }
+        server.read().stats.clients_struck_out.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let Some(stream) = Self::client_stream(server, client_id) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        Self::forget_client(server, client_id);
+    }
+
+    /// Resyncs every client once a client referenced unknown ids as many times as the threshold,
+    /// as its view of the debuggables can no longer match the server's.
+    fn count_unknown_id_reference(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize) {
+        let needs_resync = {
+            let mut server = server.write();
+            let Some(resync_threshold) = server.resync_threshold else { return; };
+            let references = server.unknown_id_references.entry(client_id).or_insert(0);
+            *references += 1;
+            *references >= resync_threshold
+        };
+        if !needs_resync { return; }
+        log::warn!("Client {client_id} keeps referencing unknown debuggables, resyncing every client");
+        server.write().unknown_id_references.clear();
+        Self::resync_clients_of(server);
+    }
+
+    fn resync_clients_of(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>) {
+        let clients = server.read().clients().iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+        Self::send_server_message(server, &*clients, &ServerMessage::RemoveAll);
+        let debuggable_ids = server.read().debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+        for debuggable_id in debuggable_ids {
+            Self::send_added_to(server, debuggable_id, AddedOrigin::Replay, &*clients);
+            Self::send_notify_to(server, debuggable_id, &*clients);
+            Self::send_metadata_to(server, debuggable_id, &*clients);
+        }
+    }
+
+    fn track_client_slot(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize, address: Option<SocketAddr>) {
+        let mut server = server.write();
+        let generation = server.next_client_generation;
+        server.next_client_generation += 1;
+        server.client_slots.insert(client_index, ClientSlot { generation, address });
+        server.events.emit(ServerEvent::ClientConnected { index: client_index, addr: address });
+    }
+
+    fn forget_client(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) {
+        let slot = {
+            let mut server = server.write();
+            server.deflate_clients.remove(&client_index);
+            server.text_patch_clients.remove(&client_index);
+            server.client_protocol_versions.remove(&client_index);
+            server.client_panels.remove(&client_index);
+            server.sessions.detach(client_index);
+            server.edit_transactions.forget_client(client_index);
+            server.client_strikes.remove(&client_index);
+            server.unknown_id_references.remove(&client_index);
+            server.forget_rejections_of(client_index);
+            let debuggable_ids = server.debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+            for debuggable_id in debuggable_ids {
+                let debuggable = server.debuggables.get_mut(debuggable_id).unwrap();
+                debuggable.full_value_fetched_by.remove(&client_index);
+                debuggable.value_requesters.remove(&client_index);
             }
-            ClientUnitMessage::Renotify => {
-                if server.read().clients().contains_index(client_id) {
-                    Self::init_client(server, client_id);
-                } else {
-                    server.read().clients()
-                        .iter_index()
-                        .map(|(index, _)| index)
-                        .collect::<Vec<_>>()
-                        .into_iter()
-                        .for_each(|client| Self::init_client(server, client_id));
-                }
+            if let Some(outgoing_queues) = server.outgoing_queues.as_ref() {
+                outgoing_queues.unregister_client(client_index);
             }
+            server.client_slots.remove(&client_index)
+        };
+        if slot.is_none() { return; }
+        server.read().events.emit(ServerEvent::ClientDisconnected { index: client_index });
+        let handler = server.write().on_client_disconnect.take();
+        if let Some(mut handler) = handler {
+            let _callback = CallbackScope::enter();
+            handler(client_index, slot.unwrap().address);
+            server.write().on_client_disconnect.get_or_insert(handler);
         }
     }
 
-    pub fn set_read_dir(&mut self, read_dir: Option<String>) {
-        self.write().read_from_dir = read_dir;
+    fn forget_disconnected_clients(&self) {
+        let disconnected_clients = {
+            let server = self.read();
+            let dropped_clients = server.outgoing_queues.as_ref().map(OutgoingQueues::take_dropped_clients).unwrap_or_default();
+            dropped_clients.iter().for_each(|client_index| server.events.emit(ServerEvent::ClientDropped { index: *client_index }));
+            server.client_slots.keys()
+                .filter(|client_index| dropped_clients.contains(client_index) || !server.clients().contains_index(**client_index))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        disconnected_clients.into_iter().for_each(|client_index| Self::forget_client(self, client_index));
     }
 
-    pub fn set_only_reads_from_dir(&mut self, only_reads_from_dir: bool) {
-        self.write().only_reads_from_dir = only_reads_from_dir;
+    /// Clears every client's debuggables with RemoveAll and sends them all again.
+    pub fn resync_clients(&self) {
+        Self::resync_clients_of(self);
     }
 
-    pub fn read_all_clients(&self) {
-        if self.read().only_reads_from_dir {
-            self.read_clients_from_read_dir();
-            return;
-        }
-        self.read_clients_no_context(true);
-        self.read_clients_from_read_dir();
+    /// Resyncs every client automatically once a single client referenced unknown ids this many
+    /// times, None disables it.
+    pub fn set_resync_threshold(&mut self, resync_threshold: Option<u32>) {
+        self.write().resync_threshold = resync_threshold;
     }
 
-    pub fn read_clients_from_read_dir(&self) -> usize {
-        let mut read_bytes = 0_usize;
-        if self.read().read_from_dir.is_none() { return read_bytes; }
-        let dir_read = fs::read_dir(self.read().read_from_dir.as_ref().unwrap());
-        if dir_read.is_err() { return read_bytes; }
-        let dir_read = dir_read.unwrap();
-        let mut transactions = dir_read.into_iter()
-            .filter(Result::is_ok)
-            .map(Result::unwrap)
-            .filter(|file| metadata(file.path()).map(|metada| metada.is_file()).unwrap_or(false))
-            .map(|file| {
-                let file_name = file.file_name().to_os_string().into_string();
-                (file, file_name)
-            })
-            .filter(|(_, file_name)| file_name.is_ok())
-            .map(|(file, filename)| (file, filename.unwrap()))
-            .map(|(file, filename)| {
-                let contains_names = filename.contains("client-") && filename.contains("-transaction-");
-                if !contains_names { return None; }
-                let delimited_name = filename.replace("client-", "").replace("-transaction-", "-");
-                let ids = delimited_name.split("-").map(usize::from_str).collect::<Vec<_>>();
-                let are_valid_ids = ids.len() == 2 && ids.iter().all(Result::is_ok);
-                if !are_valid_ids { return None; }
-                let mut ids = ids.into_iter().map(Result::unwrap).collect::<VecDeque<_>>();
-                Some((file, ids.pop_front().unwrap(), ids.pop_front().unwrap()))
-            })
-            .filter(Option::is_some)
-            .map(Option::unwrap)
-            .map(|(file, client_id, transaction)|
-                ((file, client_id, transaction)))
-            .map(|(file, client_id, transaction)| {
-                let contents = fs::read_to_string(file.path());
-                (file, client_id, transaction, contents)
-            })
-            .filter(|(_, _, _, contents)| contents.is_ok())
-            .map(|(file, client_id, transaction, contents)|
-                ((file, client_id, transaction, contents.unwrap()))
-            )
-            .filter(|(file, _, _, _)| fs::remove_file(file.path()).is_ok())
-            .map(|(_, client_id, transaction, contents)| {
-                (client_id, transaction, contents)
-            })
-            .collect::<Vec<_>>();
-        transactions.sort_by(|(this_client_id, this_transaction, _), (other_client_id, other_transaction, _)| {
-            if this_client_id != other_client_id {
-                Ordering::Equal
-            } else {
-                this_transaction.cmp(other_transaction)
+    /// Consecutive rejected updates of a debuggable from one client after which it's told what the
+    /// debuggable expects, and after which its updates of it are ignored. None disables either.
+    pub fn set_rejection_escalation(&mut self, explain_after: Option<u32>, ignore_after: Option<u32>) {
+        let mut server = self.write();
+        server.explain_after_rejections = explain_after;
+        server.ignore_after_rejections = ignore_after;
+    }
+
+    /// Updates a debuggable keeps waiting for it to sync, past which the oldest are dropped and
+    /// acknowledged as not accepted. None keeps every update.
+    pub fn set_max_pending_updates(&mut self, max_pending_updates: Option<usize>) {
+        self.write().max_pending_updates = max_pending_updates;
+    }
+
+    /// Sessions of clients that gave a session key in Hello the server remembers, past which those
+    /// used the longest ago are forgotten. Sessions of connected clients are never forgotten.
+    pub fn set_max_sessions(&mut self, max_sessions: usize) {
+        let mut server = self.write();
+        let evicted = server.sessions.set_max_sessions(max_sessions);
+        server.stats.evicted_sessions.fetch_add(evicted as u64, AtomicOrdering::Relaxed);
+    }
+
+    /// Bytes the blobs of a session may take together, blobs that don't fit are rejected.
+    pub fn set_max_session_blob_bytes(&mut self, max_session_blob_bytes: usize) {
+        self.write().sessions.set_max_blob_bytes(max_session_blob_bytes);
+    }
+
+    /// Sends clients the values of debuggables once they say hello instead of once accepted, so
+    /// those resuming a session are only sent the values they're subscribed to. Clients that never
+    /// say hello are sent values once they send Renotify.
+    pub fn set_replay_values_on_hello(&mut self, replays_values_on_hello: bool) {
+        self.write().replays_values_on_hello = replays_values_on_hello;
+    }
+
+    /// Sessions the server remembers, whether their clients are connected or not.
+    pub fn session_count(&self) -> usize {
+        self.read().sessions.len()
+    }
+
+    /// Minimum time between the intermediate values of animations requested by clients.
+    pub fn set_animation_step(&mut self, animation_step: Duration) {
+        self.write().animation_step = animation_step;
+    }
+
+    /// How long calls to RPC endpoints wait for the host to serve them before timing out.
+    pub fn set_rpc_timeout(&mut self, rpc_timeout: Duration) {
+        self.write().rpc_timeout = rpc_timeout;
+    }
+
+    /// Size above which clients are sent a NotifySummary of a value instead of the value itself,
+    /// they can still request it whole through RequestValue.
+    pub fn set_max_value_bytes(&mut self, max_value_bytes: Option<usize>) {
+        self.write().max_value_bytes = max_value_bytes;
+    }
+
+    /// How long updates sent as a group wait for all their debuggables to sync before each one is
+    /// released on its own.
+    pub fn set_update_group_timeout(&mut self, update_group_timeout: Duration) {
+        self.write().update_groups.set_timeout(update_group_timeout);
+    }
+
+    /// How long edit transactions may go without a value being staged in them before they're
+    /// dropped, their client is sent an Error when they are.
+    pub fn set_edit_transaction_timeout(&mut self, edit_transaction_timeout: Duration) {
+        self.write().edit_transactions.set_timeout(edit_transaction_timeout);
+    }
+
+    pub fn set_outgoing_transform(&mut self, outgoing_transform: Option<OutgoingTransform>) {
+        self.write().outgoing_transform = outgoing_transform;
+    }
+
+    pub fn set_incoming_transform(&mut self, incoming_transform: Option<IncomingTransform>) {
+        self.write().incoming_transform = incoming_transform;
+    }
+
+    pub fn set_input_limits(&mut self, input_limits: InputLimits) {
+        self.write().input_limits = input_limits;
+    }
+
+    pub fn set_max_strikes(&mut self, max_strikes: Option<u32>) {
+        self.write().max_strikes = max_strikes;
+    }
+
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        let mut server = self.write();
+        server.last_refresh = clock.now_instant();
+        server.last_derived_refresh = None;
+        let now = clock.now_instant();
+        let debuggable_ids = server.debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+        debuggable_ids.into_iter().for_each(|debuggable_id| {
+            if let Some(debuggable) = server.debuggables.get_mut(debuggable_id) {
+                debuggable.last_touched = now;
             }
         });
-        transactions.into_iter().for_each(|(client_id, _, contents)| {
-            read_bytes = read_bytes.checked_add(contents.len()).unwrap_or(usize::MAX);
-            let server = self.0.read();
-            let end_mark = server.message_endmark();
-            let contents = contents.replace(end_mark.escape(), end_mark.string());
-            drop(server);
-            Self::process_message_of(self, client_id, contents);
-        });
-        read_bytes
+        server.clock = clock;
     }
 
-    pub(crate) fn notify_new_value(&self, changed_id: usize, changed_value: Option<String>, who: Who) {
-        if self.read().debuggables.get(changed_id).unwrap().last_value.eq(&changed_value){
-            return;
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.read().clock.clone()
+    }
+
+    /// Debuggables created by the handler register once it returns, see the callbacks module.
+    pub fn set_on_client_disconnect(&mut self, on_client_disconnect: Option<ClientDisconnectHandler>) {
+        self.write().on_client_disconnect = on_client_disconnect;
+    }
+
+    pub fn disconnect_client(&self, client_index: usize) -> bool {
+        if !self.read().client_slots.contains_key(&client_index) { return false; }
+        if let Some(stream) = Self::client_stream(self, client_index) {
+            let _ = stream.shutdown(Shutdown::Both);
         }
-        self.write().debuggables.get_mut(changed_id).unwrap().last_value = changed_value;
-        let clients_to_notify: Vec<usize> = match who {
-            Who::Client(client_id) => vec![client_id],
-            Who::All => (0..self.clients_len()).into_iter().collect(),
-            Who::AllBut(except_client) => {
-                let mut clients_to_notify = (0..self.clients_len()).into_iter().collect::<Vec<_>>();
-                if except_client < clients_to_notify.len() {
-                    clients_to_notify.remove(except_client);
-                }
-                clients_to_notify
+        Self::forget_client(self, client_index);
+        true
+    }
+
+    pub fn client_handle(&self, client_index: usize) -> Option<ClientHandle> {
+        self.read().client_slots.get(&client_index)
+            .map(|slot| ClientHandle { index: client_index, generation: slot.generation })
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients_of(Who::All).len()
+    }
+
+    pub fn client_addr(&self, client_index: usize) -> Option<SocketAddr> {
+        self.read().client_slots.get(&client_index).and_then(|slot| slot.address)
+    }
+
+    fn apply_client_socket_options(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) {
+        let stream = Self::client_stream(server, client_index);
+        if stream.is_none() { return; }
+        let _ = server.read().client_socket_options.apply(&stream.unwrap());
+    }
+
+    fn register_outgoing_queue(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) {
+        if server.read().outgoing_queues.is_none() { return; }
+        let stream = Self::client_stream(server, client_index);
+        let server = server.read();
+        let outgoing_queues = server.outgoing_queues.as_ref().unwrap();
+        match stream {
+            None => outgoing_queues.unregister_client(client_index),
+            Some(stream) => outgoing_queues.register_client(client_index, stream),
+        }
+    }
+
+    pub(crate) fn send_to_clients(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, clients: &[usize], message: &str) {
+        let slow_clients = Self::send_or_find_slow_clients(&server.read(), clients, message);
+        slow_clients.into_iter().for_each(|client_index| Self::forget_client(server, client_index));
+    }
+
+    /// Sends the message, returning the clients disconnected for not taking it within the client
+    /// write timeout.
+    fn send_or_find_slow_clients(server: &InnerSimpleServer<DebuggableServerData, ()>, clients: &[usize], message: &str) -> Vec<usize> {
+        if server.is_shut_down.load(AtomicOrdering::Relaxed) { return Vec::new(); }
+        let transformed_message;
+        let message = match server.outgoing_transform.as_ref() {
+            None => message,
+            Some(outgoing_transform) => {
+                transformed_message = outgoing_transform(message);
+                &*transformed_message
             }
-            Who::WrongClients(wrong_clients) => {
-                wrong_clients.into_iter().collect()
+        };
+        let kept_clients;
+        let clients = if server.faults.injects_any() {
+            kept_clients = clients.iter().copied().filter(|_| !server.faults.drops_outgoing()).collect::<Vec<_>>();
+            server.stats.injected_drops.fetch_add((clients.len() - kept_clients.len()) as u64, AtomicOrdering::Relaxed);
+            &*kept_clients
+        } else {
+            clients
+        };
+        match server.outgoing_queues.as_ref() {
+            Some(outgoing_queues) => outgoing_queues.enqueue(clients, message),
+            None if server.client_socket_options.write_timeout.is_some() => return Self::write_or_disconnect(server, clients, message),
+            None => server.send_message_to_clients(clients, message),
+        }
+        Vec::new()
+    }
+
+    /// Writes the message to each client directly, so a client that couldn't take it within the
+    /// write timeout is disconnected instead of slowing down every later message.
+    fn write_or_disconnect(server: &InnerSimpleServer<DebuggableServerData, ()>, clients: &[usize], message: &str) -> Vec<usize> {
+        let end_mark = server.message_endmark();
+        let frame = framing::encode_frame(message, &Endmark::new_unchecked(end_mark.string(), end_mark.escape()));
+        clients.iter().copied().filter(|client_index| {
+            // Clients already disconnected stay in the list until their socket is next read
+            if !server.client_slots.contains_key(client_index) { return false; }
+            let stream = server.clients().get(*client_index).and_then(|client| client.stream().try_clone().ok());
+            let Some(mut stream) = stream else { return false; };
+            if stream.write_all(frame.as_bytes()).is_ok() { return false; }
+            log::warn!("Disconnecting client {client_index}, it didn't take a message within the write timeout");
+            let _ = stream.shutdown(Shutdown::Both);
+            server.stats.dropped_clients.fetch_add(1, AtomicOrdering::Relaxed);
+            server.events.emit(ServerEvent::ClientDropped { index: *client_index });
+            true
+        }).collect()
+    }
+
+    pub(crate) fn send_server_message(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, clients: &[usize], message: &ServerMessage) {
+        #[cfg(feature = "jsonrpc")]
+        if let ServerMessage::UpdateAck { request_id, accepted, .. } = message {
+            if clients.contains(&jsonrpc::JSONRPC_CLIENT_ID) {
+                server.write().jsonrpc_acks.push((*request_id, *accepted));
             }
+        }
+        let clients = clients.iter().copied().filter(|client| !RESERVED_CLIENT_IDS.contains(client)).collect::<Vec<_>>();
+        let min_protocol_version = message.min_protocol_version();
+        let clients = if min_protocol_version <= BASE_PROTOCOL_VERSION {
+            clients
+        } else {
+            let server = server.read();
+            clients.iter()
+                .filter(|client| server.protocol_version_of(**client) >= min_protocol_version)
+                .copied()
+                .collect::<Vec<_>>()
         };
-        let notify_value_message = &*ServerMessage::Notify {
-            id: changed_id,
-            name: self.read().debuggables.get(changed_id).unwrap().name.clone(),
-            value_in_json: self.read().debuggables.get(changed_id).unwrap().last_value.as_ref().unwrap_or(&"{}".to_string()).clone(),
-        }.to_json().unwrap();
-        self.send_message_to_clients(&*clients_to_notify, notify_value_message);
+        if clients.is_empty() { return; }
+        let message = message.to_json();
+        if message.is_none() { return; }
+        Self::send_to_clients(server, &*clients, &*message.unwrap());
     }
 
-    pub(crate) fn init_debuggable(&self, name: String, is_keep: bool) -> (usize, bool) {
-        if is_keep {
-            match self.read().kept_debuggable_values.get(&name) {
-                None => {}
-                Some(kept_index) => { return (*kept_index, true); }
+    fn send_notify_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, clients: &[usize]) {
+        // On demand values only go to the clients that requested them, see fulfill_requests
+        if server.read().debuggables.get(debuggable_id).is_some_and(|debuggable| debuggable.on_demand) { return; }
+        Self::send_current_value_to(server, debuggable_id, clients);
+    }
+
+    fn send_current_value_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, clients: &[usize]) {
+        let clients = server.read().subscribed_clients(debuggable_id, clients);
+        let clients = &*clients;
+        if clients.is_empty() { return; }
+        if server.read().visible_debuggable(debuggable_id).is_none() { return; }
+        // Placeholders have no value to notify until their owner registers them
+        if server.read().is_placeholder(debuggable_id) { return; }
+        let summary_message = server.read().summary_message_of(debuggable_id);
+        if let Some(summary_message) = summary_message {
+            Self::send_server_message(server, clients, &summary_message);
+            return;
+        }
+        let (change_info_clients, plain_clients): (Vec<usize>, Vec<usize>) = {
+            let server = server.read();
+            clients.iter().partition(|client| server.protocol_version_of(**client) >= CHANGE_INFO_PROTOCOL_VERSION)
+        };
+        Self::send_value_notify_to(server, debuggable_id, &change_info_clients, true);
+        Self::send_value_notify_to(server, debuggable_id, &plain_clients, false);
+    }
+
+    fn send_value_notify_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, clients: &[usize], with_change_info: bool) {
+        if clients.is_empty() { return; }
+        // The buffer is taken out while sending, as sending may forget slow clients, which needs the
+        // server's write lock
+        let notify_buffer = {
+            let server_data = server.read();
+            let mut notify_buffer = mem::take(&mut *server_data.notify_buffer.lock().unwrap());
+            if !server_data.write_notify_message_of(debuggable_id, with_change_info, &mut notify_buffer) { return; }
+            notify_buffer
+        };
+        let give_back = |notify_buffer: Vec<u8>| *server.read().notify_buffer.lock().unwrap() = notify_buffer;
+        let notify_value_message = std::str::from_utf8(&notify_buffer).unwrap();
+        #[cfg(feature = "compression")]
+        {
+            let (deflate_clients, plain_clients): (Vec<usize>, Vec<usize>) = clients.iter()
+                .partition(|client| server.read().deflate_clients.contains(*client));
+            if !deflate_clients.is_empty() {
+                let encoded_message = server.read().encoded_notify_message_of(debuggable_id);
+                if let Some(encoded_message) = encoded_message {
+                    Self::send_to_clients(server, &*deflate_clients, &*encoded_message);
+                    Self::send_to_clients(server, &*plain_clients, notify_value_message);
+                    give_back(notify_buffer);
+                    return;
+                }
             }
         }
-        let name_copy = if is_keep { Some(name.clone()) } else { None };
-        let res = (self.write().debuggables.push(DebuggableOnServer::new(name, None, Vec::new())), false);
-        if !is_keep { return res; }
-        self.write().kept_debuggable_values.insert(name_copy.unwrap(), res.0);
-        res
+        Self::send_to_clients(server, clients, notify_value_message);
+        give_back(notify_buffer);
     }
 
-    pub(crate) fn remove_debuggable(&self, debuggable_id: usize) {
-        let is_keep = self.read().debuggables.get(debuggable_id)
-            .map(|debuggable| self.read().kept_debuggable_values.contains_key(&debuggable.name))
-            .unwrap_or(false);
-        if is_keep { return; }
-        self.write().debuggables.remove(debuggable_id);
-        let message = &*ServerMessage::Remove { id: debuggable_id }.to_json().unwrap();
-        let clients_len = self.read().clients().len();
-        self.write().send_message_to_clients(&(0..clients_len).into_iter().collect::<Vec<_>>(), message);
+    /// Sends the clients applying text patches the lines that changed since the base value, unless
+    /// the patch isn't smaller than the whole value. Returns the clients still to be notified.
+    fn send_text_patch_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, base_value: &str, base_revision: u64, clients: &[usize]) -> Vec<usize> {
+        let (patch_clients, other_clients): (Vec<usize>, Vec<usize>) = {
+            let server = server.read();
+            clients.iter().partition(|client| server.text_patch_clients.contains(*client))
+        };
+        if patch_clients.is_empty() { return other_clients; }
+        let Some((value, revision)) = server.read().debuggables.get(debuggable_id)
+            .and_then(|debuggable| Some((debuggable.last_value.clone()?, debuggable.revision))) else { return clients.to_vec(); };
+        let (Some(base_text), Some(text)) = (String::from_json(base_value), String::from_json(&value)) else { return clients.to_vec(); };
+        let patch = ServerMessage::NotifyTextPatch { id: debuggable_id, base_revision, revision, hunks: diff_lines(&base_text, &text) };
+        let Some(patch) = patch.to_json().filter(|patch| patch.len() < value.len()) else { return clients.to_vec(); };
+        Self::send_to_clients(server, &*patch_clients, &*patch);
+        other_clients
     }
 
-    pub(crate) fn last_value_of(&self, debuggable_id: usize) -> Option<String> {
-        self.read().debuggables.get(debuggable_id).unwrap().last_value.clone()
+    /// Sends the whole value to the client however large it is, in as many ValueChunk messages as
+    /// needed, and lets the client edit the debuggable while it's summarized.
+    fn send_value_chunks_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, client_id: usize) {
+        let (outgoing_value, revision) = {
+            let mut server = server.write();
+            let Some(debuggable) = server.debuggables.get_mut(debuggable_id) else { return; };
+            debuggable.full_value_fetched_by.insert(client_id);
+            (debuggable.outgoing_value().unwrap_or_else(|| Arc::from("{}")), debuggable.revision)
+        };
+        let chunks = chunks_of(&outgoing_value, VALUE_CHUNK_BYTES);
+        let chunk_count = chunks.len();
+        for (chunk_index, json_chunk) in chunks.into_iter().enumerate() {
+            let chunk = ServerMessage::ValueChunk { id: debuggable_id, revision, chunk_index, chunk_count, json_chunk: json_chunk.to_string() };
+            Self::send_server_message(server, &[client_id], &chunk);
+        }
     }
 
-    pub(crate) fn last_value_of_equals(&self, debuggable_id: usize, current_value: &Option<String>) -> bool {
-        self.read().debuggables.get(debuggable_id).unwrap().last_value.eq(current_value)
+    fn refuse_edit_of(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, reason: String, panel: Option<String>) {
+        Self::send_server_message(server, &[client_id], &ServerMessage::Error { message: reason, panel });
     }
 
-    pub(crate) fn take_incoming_jsons_of(&self, debuggable_id: usize) -> Vec<(usize, String)> {
-        mem::take(&mut self.write().debuggables.get_mut(debuggable_id).unwrap().incoming_jsons)
+    fn framing_of(server: &InnerSimpleServer<DebuggableServerData, ()>) -> FramingInfo {
+        let end_mark = server.message_endmark();
+        FramingInfo { endmark: end_mark.string().to_string(), escape: end_mark.escape().to_string(), mode: Framing::Endmark }
     }
-}
 
-#[derive(Debug)]
-pub(crate) struct DebuggableOnServer {
-    name: String,
-    last_value: Option<String>,
-    incoming_jsons: Vec<(usize, String)>,
-}
+    pub(crate) fn client_stream(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) -> Option<TcpStream> {
+        server.read().clients().get(client_index)
+            .map(|client| client.stream().try_clone().ok())
+            .flatten()
+    }
 
-impl DebuggableOnServer {
-    pub fn new(name: String, last_value: Option<String>, incoming_jsons: Vec<(usize, String)>) -> Self {
-        Self { name, last_value, incoming_jsons }
+    /// Attaches the client to the session of the key, returning the panel it goes by, the one it
+    /// said hello with or else the one of its restored session, and the blobs to send back to it.
+    fn attach_session(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, session_key: Option<String>, panel: Option<String>) -> (Option<String>, Vec<(String, String)>) {
+        let mut server = server.write();
+        let attached = server.sessions.attach(client_id, session_key);
+        server.stats.evicted_sessions.fetch_add(attached.evicted as u64, AtomicOrdering::Relaxed);
+        let session = server.sessions.of_client_mut(client_id).unwrap();
+        if panel.is_some() {
+            session.panel = panel;
+        }
+        let restored_blobs = if attached.restored {
+            session.blobs.iter().map(|(key, data)| (key.clone(), data.clone())).collect()
+        } else {
+            Vec::new()
+        };
+        (session.panel.clone(), restored_blobs)
+    }
+
+    fn init_client(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize, sends_values: bool) {
+        let server_info = {
+            let server = server.read();
+            server.server_info_message(Self::framing_of(&server))
+        };
+        Self::send_server_message(server, &[client_index], &server_info);
+        Self::send_server_message(server, &[client_index], &ServerMessage::GiveClientId { client_id: client_index });
+        let debuggable_ids = server.read().debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+        for debuggable_index in debuggable_ids {
+            Self::send_added_to(server, debuggable_index, AddedOrigin::Replay, &[client_index]);
+            if sends_values {
+                Self::send_notify_to(server, debuggable_index, &[client_index]);
+            }
+            Self::send_metadata_to(server, debuggable_index, &[client_index]);
+        }
+        Self::send_composites_to(server, client_index);
+    }
+
+    fn send_composites_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_index: usize) {
+        let composite_messages = server.read().composites.messages();
+        for composite_message in composite_messages {
+            Self::send_server_message(server, &[client_index], &composite_message);
+        }
+    }
+
+    fn send_added_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, origin: AddedOrigin, clients: &[usize]) {
+        let (name, uid) = match server.read().debuggables.get(debuggable_id) {
+            Some(debuggable) if !debuggable.hidden => (debuggable.name.clone(), debuggable.registration),
+            _ => return,
+        };
+        Self::send_server_message(server, clients, &ServerMessage::Added { id: debuggable_id, name, origin, uid: Some(uid) });
+    }
+
+    /// Tells the author why its update was rejected, escalating once the client had as many
+    /// updates of the debuggable rejected in a row as the thresholds of set_rejection_escalation.
+    pub(crate) fn reject_update(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, author: &Author, reason: &str, value_type: &str) {
+        // Updates queued on behalf of the server itself can't be corrected by whoever sent them
+        if RESERVED_CLIENT_IDS.contains(&author.client) { return; }
+        let (name, rejections, explain_after, ignore_after) = {
+            let mut server = server.write();
+            let Some(name) = server.debuggables.get(debuggable_id).map(|debuggable| debuggable.name.clone()) else { return; };
+            let rejections = server.consecutive_rejections.entry((author.client, debuggable_id)).or_insert(0);
+            *rejections += 1;
+            let rejections = *rejections;
+            (name, rejections, server.explain_after_rejections, server.ignore_after_rejections)
+        };
+        server.read().events.emit(ServerEvent::UpdateRejected { id: debuggable_id, client: author.client });
+        Self::send_error_to(server, author, format!("Rejected update of {name}: {reason}"));
+        if explain_after == Some(rejections) {
+            let current_value = server.read().visible_debuggable(debuggable_id)
+                .and_then(|debuggable| debuggable.outgoing_value())
+                .map_or_else(|| "null".to_string(), |value| value.to_string());
+            Self::send_error_to(server, author, format!("{rejections} updates of {name} were rejected in a row, it expects a value of type {value_type} such as its current value {current_value}"));
+        }
+        if ignore_after == Some(rejections) {
+            log::warn!("Ignoring updates of {name} from client {} until it renotifies or says hello again", author.client);
+            Self::send_error_to(server, author, format!("Updates of {name} from this client are ignored until it sends Renotify or Hello"));
+        }
+    }
+
+    pub(crate) fn accept_update(&self, debuggable_id: usize, author: &Author) {
+        let mut server = self.write();
+        if server.consecutive_rejections.is_empty() { return; }
+        server.consecutive_rejections.remove(&(author.client, debuggable_id));
+    }
+
+    /// Clamps the update of a bounded debuggable, telling the sender the clamped value, or rejects it
+    /// if it isn't a number. Updates of debuggables without bounds are left as they are.
+    fn clamp_update(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, author: &Author, request_id: Option<u64>, new_value: String) -> Option<String> {
+        let Some((min, max)) = server.read().visible_debuggable(debuggable_id).and_then(|debuggable| debuggable.numeric_bounds) else {
+            return Some(new_value);
+        };
+        let Some(number) = number_of(&new_value) else {
+            Self::reject_update(server, debuggable_id, author, &format!("{new_value} is not a number"), "number");
+            Self::send_notify_to(server, debuggable_id, &[author.client]);
+            if let Some(request_id) = request_id {
+                Self::send_server_message(server, &[author.client], &ServerMessage::UpdateAck { request_id, accepted: false, panel: author.panel.clone() });
+            }
+            return None;
+        };
+        if (min..=max).contains(&number) { return Some(new_value); }
+        let clamped = format!("{}", number.clamp(min, max));
+        let clamped_notify = server.read().debuggables.get(debuggable_id).map(|debuggable| ServerMessage::Notify {
+            id: debuggable_id,
+            name: debuggable.name.clone(),
+            value_in_json: clamped.clone(),
+            revision: debuggable.revision,
+            changed_at_ms: None,
+            author: None,
+            uid: Some(debuggable.registration),
+            overridden: debuggable.overridden,
+        });
+        if let Some(clamped_notify) = clamped_notify {
+            Self::send_server_message(server, &[author.client], &clamped_notify);
+        }
+        Some(clamped)
+    }
+
+    pub(crate) fn answer_rpc_call(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, author: &Author, call_id: u64, result: Result<String, String>) {
+        let (response_json, error) = match result {
+            Ok(response_json) => (Some(response_json), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self::send_server_message(server, &[author.client], &ServerMessage::RpcResult { call_id, response_json, error });
+    }
+
+    fn send_error_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, author: &Author, message: String) {
+        Self::send_server_message(server, &[author.client], &ServerMessage::Error { message, panel: author.panel.clone() });
+    }
+
+    pub(crate) fn acknowledge_update(&self, author: &Author, request_id: u64, accepted: bool) {
+        Self::send_server_message(self, &[author.client], &ServerMessage::UpdateAck { request_id, accepted, panel: author.panel.clone() });
+    }
+
+    pub(crate) fn broadcast_added(&self, debuggable_id: usize, origin: AddedOrigin) {
+        if origin != AddedOrigin::Replay {
+            let server = self.read();
+            if let Some(debuggable) = server.debuggables.get(debuggable_id) {
+                server.events.emit(ServerEvent::DebuggableAdded { id: debuggable_id, name: debuggable.name.clone() });
+            }
+        }
+        let clients_to_notify = self.clients_of(Who::All);
+        Self::send_added_to(self, debuggable_id, origin, &*clients_to_notify);
+    }
+
+    fn send_metadata_to(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, debuggable_id: usize, clients: &[usize]) {
+        let metadata_message = server.read().metadata_message_of(debuggable_id);
+        if metadata_message.is_none() { return; }
+        Self::send_server_message(server, clients, &metadata_message.unwrap());
+    }
+
+    pub(crate) fn broadcast_metadata(&self, debuggable_id: usize) {
+        let clients_to_notify = self.clients_of(Who::All);
+        Self::send_metadata_to(self, debuggable_id, &*clients_to_notify);
+    }
+
+    /// Entry point of every message received from a client, through its socket or the read dir.
+    fn receive_message_of(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, message: String, origin: UpdateOrigin) {
+        if server.read().faults.duplicates_incoming() {
+            server.read().stats.injected_duplicates.fetch_add(1, AtomicOrdering::Relaxed);
+            Self::process_message_of(server, client_id, message.clone(), origin);
+        }
+        Self::process_message_of(server, client_id, message, origin);
+    }
+
+    /// Queues an update of the whole value, whether the client sent it as is or as a text patch.
+    fn receive_update(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, id: usize, new_value: String, request_id: Option<u64>, panel: Option<String>, origin: UpdateOrigin) {
+        let author = server.read().author_of(client_id, panel);
+        if server.read().ignores_updates_of(client_id, id) {
+            if let Some(request_id) = request_id {
+                Self::send_server_message(server, &[client_id], &ServerMessage::UpdateAck { request_id, accepted: false, panel: author.panel });
+            }
+            return;
+        }
+        let refusal = server.read().edit_refusal_of(client_id, id);
+        if let Some(reason) = refusal {
+            Self::refuse_edit_of(server, client_id, reason, author.panel.clone());
+            if let Some(request_id) = request_id {
+                Self::send_server_message(server, &[client_id], &ServerMessage::UpdateAck { request_id, accepted: false, panel: author.panel });
+            }
+            return;
+        }
+        let Some(new_value) = Self::clamp_update(server, id, &author, request_id, new_value) else { return; };
+        let queued = server.write().queue_update(id, author, request_id, new_value, origin);
+        match queued {
+            None => Self::count_unknown_id_reference(server, client_id),
+            Some(evicted) => evicted.iter()
+                .filter_map(|update| Some((&update.author, update.request_id?)))
+                .for_each(|(author, request_id)| Self::send_server_message(server, &[author.client], &ServerMessage::UpdateAck { request_id, accepted: false, panel: author.panel.clone() })),
+        }
+    }
+
+    /// Holds the updates until every debuggable they refer to syncs, so they're applied together.
+    /// Updates of debuggables ignoring the client are left out, and the group is dropped if any of
+    /// its updates can't be clamped.
+    fn hold_update_group(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, updates: Vec<(usize, String)>) {
+        let updates = updates.into_iter()
+            .filter(|(id, _)| !server.read().ignores_updates_of(client_id, *id))
+            .collect::<Vec<_>>();
+        if updates.is_empty() { return; }
+        let all_are_visible = updates.iter().all(|(id, _)| server.read().visible_debuggable(*id).is_some());
+        if !all_are_visible {
+            Self::send_server_message(server, &[client_id], &ServerMessage::Error { message: "Update group refers to unknown debuggables".to_string(), panel: None });
+            Self::count_unknown_id_reference(server, client_id);
+            return;
+        }
+        let refusal = updates.iter().find_map(|(id, _)| server.read().edit_refusal_of(client_id, *id));
+        if let Some(reason) = refusal {
+            Self::refuse_edit_of(server, client_id, reason, None);
+            return;
+        }
+        let author = server.read().author_of(client_id, None);
+        let updates = updates.into_iter()
+            .map(|(id, new_value)| Some((id, Self::clamp_update(server, id, &author, None, new_value)?)))
+            .collect::<Option<Vec<_>>>();
+        let Some(updates) = updates else { return; };
+        let mut server = server.write();
+        let now = server.clock.now_instant();
+        server.update_groups.hold(client_id, updates, now);
+    }
+
+    fn process_message_of(server: &UncheckedRwLock<InnerSimpleServer<DebuggableServerData, ()>>, client_id: usize, message: String, origin: UpdateOrigin) {
+        let transformed_message = server.read().incoming_transform.as_ref().map(|incoming_transform| incoming_transform(&message));
+        let message = match transformed_message {
+            None => message,
+            Some(Some(transformed_message)) => transformed_message,
+            Some(None) => {
+                server.read().stats.rejected_by_transform.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                return;
+            }
+        };
+        let input_limits = server.read().input_limits;
+        let client_message = match input_limits.parse::<ClientUnitMessage>(&message) {
+            Ok(client_message) => client_message,
+            Err(rejection) => {
+                drop(message);
+                Self::reject_message_of(server, client_id, rejection);
+                return;
+            }
+        };
+        // The owner dropped the debuggable while the message was in flight, queueing it would only
+        // have it synced by nobody
+        let removing_target = server.read().removing_target_of(&client_message);
+        if let Some(removing_id) = removing_target {
+            log::debug!("Dropping message of client {client_id} editing debuggable {removing_id}, which is being removed");
+            return;
+        }
+        match client_message {
+            ClientUnitMessage::UpdateValue { id, new_value, request_id, panel } => Self::receive_update(server, client_id, id, new_value, request_id, panel, origin),
+            ClientUnitMessage::UpdateTextPatch { id, base_revision, hunks } => {
+                let base = server.read().visible_debuggable(id).map(|debuggable| (debuggable.diffs_text(), debuggable.revision, debuggable.last_value.clone()));
+                let Some((diffs_text, revision, base_value)) = base else {
+                    Self::count_unknown_id_reference(server, client_id);
+                    return;
+                };
+                if !diffs_text {
+                    Self::refuse_edit_of(server, client_id, format!("Debuggable {id} doesn't take text patches"), None);
+                    return;
+                }
+                let patched_value = base_value.filter(|_| revision == base_revision)
+                    .and_then(|base_value| String::from_json(&base_value))
+                    .and_then(|base_text| apply_hunks(&base_text, &hunks))
+                    .and_then(|patched_text| patched_text.to_json());
+                match patched_value {
+                    // The client's base is behind, it needs the whole value to patch against
+                    None => Self::send_notify_to(server, id, &[client_id]),
+                    Some(new_value) => Self::receive_update(server, client_id, id, new_value, None, None, origin),
+                }
+            }
+            ClientUnitMessage::UpdateValueCas { id, expected_revision, new_value } => {
+                if server.read().ignores_updates_of(client_id, id) { return; }
+                let refusal = server.read().edit_refusal_of(client_id, id);
+                if let Some(reason) = refusal {
+                    Self::refuse_edit_of(server, client_id, reason, None);
+                    return;
+                }
+                let author = server.read().author_of(client_id, None);
+                let Some(new_value) = Self::clamp_update(server, id, &author, None, new_value) else { return; };
+                let received = server.read().clock.now_instant();
+                let max_pending_updates = server.read().max_pending_updates;
+                let reply = match server.write().debuggables.get_mut(id) {
+                    None => None,
+                    Some(debuggable) if debuggable.hidden => None,
+                    Some(debuggable) if debuggable.revision != expected_revision => Some(ServerMessage::Conflict {
+                        id,
+                        current_revision: debuggable.revision,
+                        current_value: debuggable.current_value_for_cas(),
+                    }),
+                    Some(debuggable) => {
+                        debuggable.revision += 1;
+                        debuggable.pending_cas = Some(new_value.clone());
+                        debuggable.animation = None;
+                        let update = PendingUpdate { author, request_id: None, json: new_value, received, origin };
+                        push_bounded(&mut debuggable.incoming_jsons, update, max_pending_updates);
+                        Some(ServerMessage::CasAccepted { id, revision: debuggable.revision })
+                    }
+                };
+                match reply {
+                    None => Self::count_unknown_id_reference(server, client_id),
+                    Some(reply) => Self::send_server_message(server, &[client_id], &reply),
+                }
+            }
+            ClientUnitMessage::UpdateGroup { updates } => {
+                let updates = updates.into_iter().map(|update| (update.id, update.new_value)).collect();
+                Self::hold_update_group(server, client_id, updates);
+            }
+            ClientUnitMessage::BeginEdit { txn_id } => {
+                let begun = {
+                    let mut server = server.write();
+                    let now = server.clock.now_instant();
+                    server.edit_transactions.begin(client_id, txn_id, now)
+                };
+                if let Err(reason) = begun {
+                    let author = server.read().author_of(client_id, None);
+                    Self::send_error_to(server, &author, reason);
+                }
+            }
+            ClientUnitMessage::StageValue { txn_id, id, new_value } => {
+                let staged = {
+                    let mut server = server.write();
+                    let now = server.clock.now_instant();
+                    server.edit_transactions.stage(client_id, txn_id, id, new_value, now)
+                };
+                if let Err(reason) = staged {
+                    let author = server.read().author_of(client_id, None);
+                    Self::send_error_to(server, &author, reason);
+                }
+            }
+            ClientUnitMessage::CommitEdit { txn_id } => {
+                let closed = server.write().edit_transactions.close(client_id, txn_id);
+                match closed {
+                    Ok(staged) => Self::hold_update_group(server, client_id, staged),
+                    Err(reason) => {
+                        let author = server.read().author_of(client_id, None);
+                        Self::send_error_to(server, &author, reason);
+                    }
+                }
+            }
+            ClientUnitMessage::AbortEdit { txn_id } => {
+                let closed = server.write().edit_transactions.close(client_id, txn_id);
+                if let Err(reason) = closed {
+                    let author = server.read().author_of(client_id, None);
+                    Self::send_error_to(server, &author, reason);
+                }
+            }
+            ClientUnitMessage::UpdateIndex { id, index, element_json } => {
+                let refusal = server.read().edit_refusal_of(client_id, id);
+                if let Some(reason) = refusal {
+                    Self::refuse_edit_of(server, client_id, reason, None);
+                    return;
+                }
+                let is_known = match server.write().debuggables.get_mut(id) {
+                    Some(debuggable) if !debuggable.hidden => {
+                        debuggable.animation = None;
+                        debuggable.incoming_index_updates.push((client_id, index, element_json));
+                        true
+                    }
+                    _ => false,
+                };
+                if !is_known {
+                    Self::count_unknown_id_reference(server, client_id);
+                }
+            }
+            ClientUnitMessage::AnimateValue { id, target_json, duration_ms } => {
+                let refusal = server.read().edit_refusal_of(client_id, id);
+                if let Some(reason) = refusal {
+                    Self::refuse_edit_of(server, client_id, reason, None);
+                    return;
+                }
+                let started = {
+                    let mut server = server.write();
+                    let now = server.clock.now_instant();
+                    match server.debuggables.get_mut(id) {
+                        Some(debuggable) if !debuggable.hidden => Some(debuggable.start_animation(target_json, Duration::from_millis(duration_ms), now)),
+                        _ => None,
+                    }
+                };
+                match started {
+                    None => Self::count_unknown_id_reference(server, client_id),
+                    Some(Err(reason)) => Self::send_server_message(server, &[client_id], &ServerMessage::Error { message: format!("Rejected animation of debuggable {id}: {reason}"), panel: None }),
+                    Some(Ok(())) => {}
+                }
+            }
+            ClientUnitMessage::GroupSnapshotRequest { prefix } => {
+                let (notifies, summarized_ids) = {
+                    let server = server.read();
+                    let group_ids = server.group_ids_of(&prefix);
+                    let notifies = group_ids.iter().filter_map(|id| server.notify_entry_of(*id)).collect();
+                    (notifies, group_ids.into_iter().filter(|id| server.is_summarized(*id)).collect::<Vec<_>>())
+                };
+                Self::send_server_message(server, &[client_id], &ServerMessage::NotifyMany { notifies });
+                summarized_ids.into_iter().for_each(|id| Self::send_notify_to(server, id, &[client_id]));
+            }
+            ClientUnitMessage::RequestValue { id, full } => {
+                let Some(on_demand) = server.read().visible_debuggable(id).map(|debuggable| debuggable.on_demand) else {
+                    Self::count_unknown_id_reference(server, client_id);
+                    return;
+                };
+                if on_demand {
+                    // Requests made before the owner computes the value are all answered by it
+                    if let Some(debuggable) = server.write().debuggables.get_mut(id) {
+                        debuggable.value_requesters.insert(client_id);
+                    }
+                } else if full {
+                    Self::send_value_chunks_to(server, id, client_id);
+                } else {
+                    Self::send_notify_to(server, id, &[client_id]);
+                }
+            }
+            ClientUnitMessage::GroupReset { prefix } => {
+                let (applied, denied) = server.write().reset_group(&prefix);
+                Self::send_server_message(server, &[client_id], &ServerMessage::GroupResult { prefix, applied, denied });
+            }
+            ClientUnitMessage::Renotify => {
+                server.write().forget_rejections_of(client_id);
+                if server.read().clients().contains_index(client_id) {
+                    Self::init_client(server, client_id, true);
+                } else {
+                    server.read().clients()
+                        .iter_index()
+                        .map(|(index, _)| index)
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .for_each(|client| Self::init_client(server, client_id, true));
+                }
+            }
+            ClientUnitMessage::Hello { protocol_version, supports_deflate, panel, supports_text_patches, session_key } => {
+                let protocol_version = protocol_version.clamp(BASE_PROTOCOL_VERSION, PROTOCOL_VERSION);
+                server.write().client_protocol_versions.insert(client_id, protocol_version);
+                server.write().forget_rejections_of(client_id);
+                let (panel, restored_blobs) = Self::attach_session(server, client_id, session_key, panel);
+                match panel {
+                    None => server.write().client_panels.remove(&client_id),
+                    Some(panel) => server.write().client_panels.insert(client_id, panel),
+                };
+                Self::send_server_message(server, &[client_id], &ServerMessage::Welcome { client_id, protocol_version });
+                let debuggable_ids = server.read().debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+                let sends_values = server.read().replays_values_on_hello;
+                // Added was dropped when the client was initialized before its version was known
+                debuggable_ids.into_iter().for_each(|debuggable_id| {
+                    Self::send_added_to(server, debuggable_id, AddedOrigin::Replay, &[client_id]);
+                    if sends_values {
+                        Self::send_notify_to(server, debuggable_id, &[client_id]);
+                    }
+                    Self::send_metadata_to(server, debuggable_id, &[client_id]);
+                });
+                Self::send_composites_to(server, client_id);
+                restored_blobs.into_iter().for_each(|(key, data)| {
+                    Self::send_server_message(server, &[client_id], &ServerMessage::Blob { key, data });
+                });
+                if supports_deflate {
+                    server.write().deflate_clients.insert(client_id);
+                } else {
+                    server.write().deflate_clients.remove(&client_id);
+                }
+                if supports_text_patches {
+                    server.write().text_patch_clients.insert(client_id);
+                } else {
+                    server.write().text_patch_clients.remove(&client_id);
+                }
+            }
+            ClientUnitMessage::RpcCall { id, call_id, request_json } => {
+                let author = server.read().author_of(client_id, None);
+                let received_at = server.read().clock.now_instant();
+                let is_queued = match server.write().debuggables.get_mut(id).filter(|debuggable| !debuggable.hidden) {
+                    Some(DebuggableOnServer { rpc_calls: Some(rpc_calls), .. }) => {
+                        rpc_calls.push(PendingRpcCall { author: author.clone(), call_id, request_json, received_at });
+                        true
+                    }
+                    _ => false,
+                };
+                if !is_queued {
+                    Self::answer_rpc_call(server, &author, call_id, Err(format!("{id} is not an RPC endpoint")));
+                }
+            }
+            ClientUnitMessage::Subscribe { names } => {
+                let newly_subscribed = {
+                    let mut server = server.write();
+                    let Some(session) = server.sessions.of_client_mut(client_id) else { return; };
+                    let subscriptions = names.map(|names| names.into_iter().collect::<BTreeSet<_>>());
+                    let previous = mem::replace(&mut session.subscriptions, subscriptions);
+                    let session = server.sessions.of_client(client_id).unwrap();
+                    server.debuggables.iter_index()
+                        .filter(|(_, debuggable)| previous.as_ref().is_some_and(|previous| !previous.contains(&debuggable.name)))
+                        .filter(|(_, debuggable)| session.subscriptions.as_ref().map_or(true, |subscriptions| subscriptions.contains(&debuggable.name)))
+                        .map(|(index, _)| index)
+                        .collect::<Vec<_>>()
+                };
+                newly_subscribed.into_iter().for_each(|debuggable_id| Self::send_notify_to(server, debuggable_id, &[client_id]));
+            }
+            ClientUnitMessage::StoreBlob { key, data } => {
+                let stored = server.write().sessions.store_blob(client_id, key, data);
+                if let Err(reason) = stored {
+                    server.read().stats.rejected_blobs.fetch_add(1, AtomicOrdering::Relaxed);
+                    let author = server.read().author_of(client_id, None);
+                    Self::send_error_to(server, &author, reason);
+                }
+            }
+            ClientUnitMessage::Custom { topic, payload } => {
+                let handler = server.write().custom_handlers.remove(&topic);
+                if handler.is_none() { return; }
+                let mut handler = handler.unwrap();
+                let _callback = CallbackScope::enter();
+                handler(client_id, &payload);
+                server.write().custom_handlers.entry(topic).or_insert(handler);
+            }
+        }
+    }
+
+    /// Handles custom messages of the topic from clients. Debuggables created by the handler
+    /// register once it returns, see the callbacks module.
+    pub fn on_custom(&mut self, topic: &str, handler: CustomMessageHandler) {
+        self.write().custom_handlers.insert(topic.to_string(), handler);
+    }
+
+    pub fn send_custom(&self, who: Who, topic: &str, payload: &str) {
+        let clients_to_notify = self.clients_of(who);
+        Self::send_server_message(self, &*clients_to_notify, &ServerMessage::Custom { topic: topic.to_string(), payload: payload.to_string() });
+    }
+
+    fn clients_of(&self, who: Who) -> Vec<usize> {
+        let server = self.read();
+        let is_live = |client_index: &usize| server.clients().contains_index(*client_index);
+        match who {
+            Who::Client(client_id) => Some(client_id).into_iter().filter(is_live).collect(),
+            Who::Handle(handle) => server.client_slots.get(&handle.index)
+                .filter(|slot| slot.generation == handle.generation)
+                .map(|_| handle.index)
+                .into_iter()
+                .filter(is_live)
+                .collect(),
+            Who::All => server.clients().iter_index().map(|(index, _)| index).collect(),
+            Who::AllBut(except_client) => server.clients().iter_index()
+                .map(|(index, _)| index)
+                .filter(|index| *index != except_client)
+                .collect(),
+            Who::WrongClients(wrong_clients) => {
+                wrong_clients.into_iter().filter(is_live).collect()
+            }
+        }
+    }
+
+    /// Visits every debuggable while holding the server's read lock. The visitor must not call back
+    /// into this server, as doing so while the lock is held can deadlock; use collect_infos instead
+    /// when the work done per debuggable is long or needs the server.
+    pub fn for_each_debuggable<Visitor: FnMut(DebuggableInfo<'_>)>(&self, mut visitor: Visitor) {
+        let server = self.read();
+        server.debuggables.iter_index().for_each(|(index, debuggable)| {
+            visitor(DebuggableInfo::new(index, debuggable, server.kept_debuggable_values.contains_key(&debuggable.name)))
+        });
+    }
+
+    pub fn collect_infos(&self) -> Vec<OwnedDebuggableInfo> {
+        let mut infos = Vec::new();
+        self.for_each_debuggable(|info| infos.push(info.to_owned_info()));
+        infos
+    }
+
+    pub fn debuggable_id_of(&self, name: &str) -> Option<usize> {
+        self.read().debuggables.iter_index()
+            .find(|(_, debuggable)| !debuggable.hidden && debuggable.name == name)
+            .map(|(index, _)| index)
+    }
+
+    pub fn visible_name_of(&self, debuggable_id: usize) -> Option<String> {
+        self.read().visible_debuggable(debuggable_id).map(|debuggable| debuggable.name.clone())
+    }
+
+    /// Value clients see for the visible debuggable with the given name.
+    pub fn value_of(&self, name: &str) -> Option<String> {
+        self.debuggable_id_of(name).and_then(|debuggable_id| self.visible_value_of(debuggable_id))
+    }
+
+    pub fn visible_value_of(&self, debuggable_id: usize) -> Option<String> {
+        self.read().visible_debuggable(debuggable_id).and_then(|debuggable| debuggable.outgoing_value()).map(|value| value.to_string())
+    }
+
+    /// Identifiers from serializable::capabilities describing what this server supports.
+    pub fn capabilities(&self) -> Vec<String> {
+        self.read().capabilities()
+    }
+
+    /// How messages are delimited on the server's own listener, as clients need to frame theirs.
+    pub fn framing_info(&self) -> FramingInfo {
+        Self::framing_of(&self.0.read())
+    }
+
+    pub fn visible_debuggables(&self) -> Vec<(usize, String, String)> {
+        self.read().debuggables.iter_index()
+            .filter(|(_, debuggable)| !debuggable.hidden)
+            .map(|(index, debuggable)| (index, debuggable.name.clone(), debuggable.outgoing_value().as_deref().unwrap_or("{}").to_string()))
+            .collect()
+    }
+
+    /// Handle reading the visible debuggables of the server for overlays rendered by the host.
+    pub fn overlay_feed(server: &Arc<RwLock<DebuggableServer>>) -> OverlayFeed {
+        OverlayFeed::new(server.clone())
+    }
+
+    pub(crate) fn fill_overlay_entries(&self, entries: &mut Vec<OverlayEntry>, highlight: Duration) {
+        let server = self.read();
+        let now = server.clock.now_instant();
+        let mut filled = 0;
+        for (id, debuggable) in server.debuggables.iter_index().filter(|(_, debuggable)| !debuggable.hidden) {
+            let changed_recently = debuggable.last_changed
+                .map(|last_changed| now.saturating_duration_since(last_changed) < highlight)
+                .unwrap_or(false);
+            match entries.get_mut(filled) {
+                None => entries.push(OverlayEntry { id, name: debuggable.name.clone(), value_json: debuggable.outgoing_value(), changed_recently }),
+                Some(entry) => {
+                    entry.id = id;
+                    entry.name.clone_from(&debuggable.name);
+                    entry.value_json = debuggable.outgoing_value();
+                    entry.changed_recently = changed_recently;
+                }
+            }
+            filled += 1;
+        }
+        entries.truncate(filled);
+    }
+
+    /// Values of the visible debuggables keyed by name, as compared by snapshot::diff.
+    ///
+    /// ```
+    /// use debug_monitor::debuggable::DebuggableBuilder;
+    /// use debug_monitor::scoped_server::ScopedServer;
+    ///
+    /// let server = ScopedServer::new();
+    /// let mut gravity = DebuggableBuilder::new("gravity", 9).scoped(&server).build();
+    /// let before = server.handle().read().unwrap().snapshot();
+    /// assert_eq!(before, r#"{"gravity":9}"#);
+    /// *gravity = 3;
+    /// assert_eq!(*gravity, 3);
+    /// let diff = server.handle().read().unwrap().diff_against_snapshot(&before).unwrap();
+    /// assert_eq!(diff.changed.len(), 1);
+    /// assert_eq!((diff.changed[0].before.as_str(), diff.changed[0].after.as_str()), ("9", "3"));
+    /// ```
+    pub fn snapshot(&self) -> String {
+        snapshot::of_entries(self.visible_debuggables().into_iter().map(|(_, name, value)| (name, value)))
+    }
+
+    /// Compares an earlier snapshot against the current values.
+    pub fn diff_against_snapshot(&self, snapshot: &str) -> Result<SnapshotDiff, SnapshotError> {
+        snapshot::diff(snapshot, &self.snapshot())
+    }
+
+    pub fn queue_update(&self, debuggable_id: usize, client_id: usize, new_value: String) -> bool {
+        self.write().queue_update(debuggable_id, Author::client(client_id), None, new_value, UpdateOrigin::Host).is_some()
+    }
+
+    #[cfg(feature = "jsonrpc")]
+    pub fn set_jsonrpc_listener(&mut self, address: Option<SocketAddr>) -> io::Result<Option<SocketAddr>> {
+        let bridge = address.map(jsonrpc::JsonRpcBridge::bind).transpose()?;
+        let local_addr = bridge.as_ref().map(jsonrpc::JsonRpcBridge::local_addr).transpose()?;
+        self.write().jsonrpc = bridge;
+        self.log_transport_configuration();
+        Ok(local_addr)
+    }
+
+    #[cfg(feature = "jsonrpc")]
+    pub fn jsonrpc_addr(&self) -> Option<SocketAddr> {
+        self.read().jsonrpc.as_ref().and_then(|bridge| bridge.local_addr().ok())
+    }
+
+    #[cfg(feature = "jsonrpc")]
+    fn poll_jsonrpc(&self) {
+        let bridge = self.write().jsonrpc.take();
+        if let Some(mut bridge) = bridge {
+            bridge.poll(self);
+            self.write().jsonrpc.get_or_insert(bridge);
+        }
+    }
+
+    /// Announces the server through a UDP beacon sent to the target at most once per
+    /// discovery::BEACON_INTERVAL while it syncs, None stops announcing it.
+    #[cfg(feature = "discovery")]
+    pub fn set_discovery_beacon(&mut self, target: Option<SocketAddr>) -> io::Result<()> {
+        self.write().beacon = target.map(Beacon::bind).transpose()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "discovery")]
+    pub(crate) fn set_announced_addr(&self, announced_addr: SocketAddr) {
+        if let Some(beacon) = self.write().beacon.as_mut() {
+            beacon.announced_addr = Some(announced_addr);
+        }
+    }
+
+    #[cfg(feature = "discovery")]
+    fn send_beacon_if_due(&self) {
+        let mut server = self.write();
+        let now = server.clock.now_instant();
+        let Some(beacon) = server.beacon.as_ref().filter(|beacon| beacon.is_due(now)) else { return; };
+        let Some(addr) = beacon.announced_addr.or(server.local_addr) else { return; };
+        let announced = DiscoveredServer {
+            name: discovery::process_name(),
+            addr,
+            debuggables: server.debuggables.iter_index().filter(|(_, debuggable)| !debuggable.hidden).count(),
+        };
+        if let Some(beacon) = server.beacon.as_mut() {
+            beacon.send(now, &announced);
+        }
+    }
+
+    /// Mirrors the last lines the process writes to its stdout into a debuggable named
+    /// stdio_capture::STDOUT_DEBUGGABLE_NAME, returning its id. Everything written still reaches
+    /// the original stdout. New lines are published while the server syncs.
+    #[cfg(all(unix, feature = "capture-stdio"))]
+    pub fn capture_stdout(&self, lines: usize) -> io::Result<usize> {
+        if self.read().stdout_capture.is_some() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Stdout is already being captured"));
+        }
+        let (debuggable_id, _) = self.init_debuggable(stdio_capture::STDOUT_DEBUGGABLE_NAME.to_string(), false);
+        let capture = match stdio_capture::StdoutCapture::start(self.id_cell_of(debuggable_id).unwrap(), lines) {
+            Ok(capture) => capture,
+            Err(error) => {
+                self.write().debuggables.remove(debuggable_id);
+                return Err(error);
+            }
+        };
+        self.write().stdout_capture = Some(capture);
+        self.broadcast_added(debuggable_id, AddedOrigin::HostCode);
+        self.notify_new_value(debuggable_id, Some("[]".to_string()), Who::All);
+        Ok(debuggable_id)
+    }
+
+    /// Points stdout back at where it pointed before capture_stdout and removes its debuggable.
+    #[cfg(all(unix, feature = "capture-stdio"))]
+    pub fn release_stdout(&self) -> io::Result<()> {
+        let Some(capture) = self.write().stdout_capture.take() else { return Ok(()); };
+        let debuggable_id = capture.debuggable_id();
+        let released = capture.release();
+        self.remove_and_broadcast(debuggable_id, RemoveReason::Dropped);
+        released
+    }
+
+    #[cfg(all(unix, feature = "capture-stdio"))]
+    fn publish_captured_stdout(&self) {
+        let captured = self.read().stdout_capture.as_ref().map(|capture| (capture.debuggable_id(), capture.take_changed_json()));
+        let Some((debuggable_id, lines_json)) = captured else { return; };
+        // Nobody owns the debuggable to take updates from clients
+        if self.pending_updates_of(debuggable_id) > 0 {
+            self.discard_pending_of(debuggable_id);
+        }
+        if let Some(lines_json) = lines_json {
+            self.notify_new_value(debuggable_id, Some(lines_json), Who::All);
+        }
+    }
+
+    pub fn read_dir(&self) -> Option<String> {
+        self.read().read_from_dir.clone()
+    }
+
+    pub fn only_reads_from_dir(&self) -> bool {
+        self.read().only_reads_from_dir
+    }
+
+    pub fn set_read_dir(&mut self, read_dir: Option<String>) -> io::Result<()> {
+        if let Some(read_dir) = read_dir.as_ref() {
+            if !metadata(read_dir)?.is_dir() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{read_dir} is not a directory")));
+            }
+        }
+        let mut server = self.write();
+        server.read_from_dir = read_dir;
+        server.recreates_read_dir = false;
+        server.read_dir_has_read = false;
+        server.read_dir_unavailable = false;
+        drop(server);
+        self.log_transport_configuration();
+        Ok(())
+    }
+
+    /// Like set_read_dir, creating the directory first, and again whenever it disappears while the
+    /// server runs.
+    pub fn set_read_dir_create<ReadDir: ToString>(&mut self, read_dir: ReadDir) -> io::Result<()> {
+        let read_dir = read_dir.to_string();
+        fs::create_dir_all(&read_dir)?;
+        self.set_read_dir(Some(read_dir))?;
+        self.write().recreates_read_dir = true;
+        Ok(())
+    }
+
+    /// Whether the read directory could be read the last time it was tried, true while no read
+    /// failed since it last succeeded, or when there's none.
+    pub fn read_dir_healthy(&self) -> bool {
+        !self.read().read_dir_unavailable
+    }
+
+    pub fn set_only_reads_from_dir(&mut self, only_reads_from_dir: bool) {
+        self.write().only_reads_from_dir = only_reads_from_dir;
+        self.log_transport_configuration();
+    }
+
+    fn log_transport_configuration(&self) {
+        let server = self.read();
+        let mut transports = Vec::new();
+        if !server.only_reads_from_dir {
+            transports.push(format!("tcp on {:?}", server.local_addr));
+            server.additional_local_addrs.iter().for_each(|address| transports.push(format!("tcp on {address}")));
+        }
+        #[cfg(feature = "windows-pipes")]
+        server.named_pipes.iter().for_each(|name| transports.push(format!("named pipe {name}")));
+        if let Some(read_dir) = server.read_from_dir.as_ref() {
+            transports.push(format!("read dir {read_dir}"));
+        }
+        #[cfg(feature = "jsonrpc")]
+        if let Some(jsonrpc) = server.jsonrpc.as_ref() {
+            transports.push(format!("json-rpc on {:?}", jsonrpc.local_addr().ok()));
+        }
+        log::info!("Debuggable server transports: {}", if transports.is_empty() { "none".to_string() } else { transports.join(", ") });
+    }
+
+    pub fn set_outgoing_queue(&mut self, max_depth: usize, policy: OverflowPolicy) {
+        let server = self.read();
+        let end_mark = server.message_endmark();
+        let endmark = Endmark::new_unchecked(end_mark.string(), end_mark.escape());
+        let stats = server.stats.clone();
+        drop(server);
+        self.write().outgoing_queues = Some(OutgoingQueues::start(max_depth, policy, endmark, stats));
+    }
+
+    /// Receives the lifecycle events of the server from now on. Each receiver has its own queue,
+    /// whose oldest events are dropped once it holds EVENT_QUEUE_CAPACITY of them.
+    pub fn events(&self) -> Receiver<ServerEvent> {
+        self.read().events.subscribe()
+    }
+
+    pub fn stats(&self) -> ServerStats {
+        self.read().stats.snapshot()
+    }
+
+    /// Sizes of what the server holds for its debuggables and clients, all taken under one read
+    /// lock. Meant to be compared over a long run, pending updates and client bookkeeping should go
+    /// back to where they were once clients disconnect and debuggables sync.
+    pub fn memory_report(&self) -> MemoryReport {
+        let server = self.read();
+        let mut report = MemoryReport::default();
+        for (id, debuggable) in server.debuggables.iter_index() {
+            report.debuggables += 1;
+            report.last_value_bytes += debuggable.last_value.as_ref().map_or(0, |last_value| last_value.len());
+            report.initial_value_bytes += debuggable.initial_value_json.as_ref().map_or(0, |initial_value| initial_value.len());
+            report.clients.full_value_fetchers += debuggable.full_value_fetched_by.len();
+            report.clients.value_requesters += debuggable.value_requesters.len();
+            let pending = PendingSize {
+                updates: debuggable.incoming_jsons.len() + debuggable.incoming_index_updates.len(),
+                bytes: debuggable.incoming_jsons.iter().map(|update| update.json.len()).sum::<usize>()
+                    + debuggable.incoming_index_updates.iter().map(|(_, _, element_json)| element_json.len()).sum::<usize>(),
+            };
+            if pending.updates > 0 {
+                report.pending.insert(id, pending);
+            }
+        }
+        let (held_updates, held_bytes) = server.update_groups.held_size();
+        report.held_group_updates = PendingSize { updates: held_updates, bytes: held_bytes };
+        let (staged_values, staged_bytes) = server.edit_transactions.staged_size();
+        report.staged_edit_values = PendingSize { updates: staged_values, bytes: staged_bytes };
+        report.outgoing_queue_bytes = server.outgoing_queues.as_ref().map_or(0, OutgoingQueues::queued_bytes);
+        report.clients = ClientBookkeeping {
+            connected: server.clients().iter_index().count(),
+            protocol_versions: server.client_protocol_versions.len(),
+            panels: server.client_panels.len(),
+            slots: server.client_slots.len(),
+            strikes: server.client_strikes.len(),
+            unknown_id_references: server.unknown_id_references.len(),
+            consecutive_rejections: server.consecutive_rejections.len(),
+            deflate_clients: server.deflate_clients.len(),
+            text_patch_clients: server.text_patch_clients.len(),
+            full_value_fetchers: report.clients.full_value_fetchers,
+            value_requesters: report.clients.value_requesters,
+        };
+        report
+    }
+
+    /// Injects the faults of the injector into the server's traffic, None stops injecting them.
+    #[cfg(feature = "fault-injection")]
+    pub fn set_fault_injector(&mut self, fault_injector: Option<FaultInjector>) {
+        self.write().faults = match fault_injector {
+            None => Box::new(NoFaults),
+            Some(fault_injector) => Box::new(fault_injector),
+        };
+    }
+
+    pub fn set_compression_threshold(&mut self, compression_threshold: Option<usize>) {
+        self.write().compression_threshold = compression_threshold;
+    }
+
+    pub fn set_allowed_ips(&mut self, allowed_ips: Option<Vec<IpRange>>) {
+        self.write().allowed_ips = allowed_ips;
+    }
+
+    pub fn set_client_socket_options(&mut self, client_socket_options: ClientSocketOptions) {
+        self.write().client_socket_options = client_socket_options;
+    }
+
+    /// Clone of the socket the client was accepted with, for inspecting the options applied to it.
+    pub fn client_socket(&self, client_index: usize) -> Option<TcpStream> {
+        Self::client_stream(self, client_index)
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.read().local_addr
+    }
+
+    /// Addresses of this server's own listener and of its additional listeners.
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        let server = self.read();
+        server.local_addr.into_iter().chain(server.additional_local_addrs.iter().copied()).collect()
+    }
+
+    /// Accepts clients on another listener too, which applies its own allow-list instead of this
+    /// server's. Its clients are forwarded to this server's own listener.
+    pub fn add_listener(&mut self, listener: AdditionalListener) -> io::Result<SocketAddr> {
+        let listener_addr = listener.local_addr()?;
+        let inner_address = self.local_addr()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::AddrNotAvailable, "The server's own listener has no address"))?;
+        let forwarded_peers = self.read().forwarded_peers.clone();
+        listeners::spawn_forwarder(listener, inner_address, forwarded_peers, MessageFraming::of_server(self));
+        self.write().additional_local_addrs.push(listener_addr);
+        self.log_transport_configuration();
+        Ok(listener_addr)
+    }
+
+    /// Terminates TLS for the clients accepted on the listener and forwards them to this server's
+    /// own listener, which from then on refuses connections that didn't come through it. Its
+    /// address is listed by local_addrs.
+    #[cfg(feature = "tls")]
+    pub(crate) fn terminate_tls(&mut self, public_listener: TcpListener, settings: TlsSettings) -> io::Result<()> {
+        let public_addr = public_listener.local_addr()?;
+        let inner_address = self.local_addr()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::AddrNotAvailable, "The server's own listener has no address"))?;
+        let forwarded_peers = self.read().forwarded_peers.clone();
+        self.write().admits_only_forwarded = true;
+        tls::spawn_tls_terminator(public_listener, settings, inner_address, forwarded_peers);
+        self.write().additional_local_addrs.push(public_addr);
+        self.log_transport_configuration();
+        Ok(())
+    }
+
+    /// Accepts clients on the named pipe too, forwarding them to this server's own listener so
+    /// they're given client indices like any other. Fails with ErrorKind::Unsupported outside of
+    /// Windows.
+    #[cfg(feature = "windows-pipes")]
+    pub fn add_named_pipe(&mut self, name: &str) -> io::Result<()> {
+        let inner_address = self.local_addr()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::AddrNotAvailable, "The server's own listener has no address"))?;
+        let forwarded_peers = self.read().forwarded_peers.clone();
+        named_pipe::spawn_pipe_forwarder(name, inner_address, forwarded_peers)?;
+        self.write().named_pipes.push(name.to_string());
+        self.log_transport_configuration();
+        Ok(())
+    }
+
+    /// Makes the debuggables registered on the other server move to this one, each of them when
+    /// it's next synced, as with Debuggable::migrate_to. Debuggables that aren't synced again stay
+    /// on the other server.
+    pub fn adopt_all_from(adopter: &Arc<RwLock<DebuggableServer>>, other: &DebuggableServer) {
+        other.write().adopted_by = Some(adopter.clone());
+    }
+
+    pub(crate) fn adopter(&self) -> Option<Arc<RwLock<DebuggableServer>>> {
+        self.read().adopted_by.clone()
+    }
+
+    pub fn shutdown(&self) {
+        if self.is_shut_down() { return; }
+        let clients = self.clients_of(Who::All);
+        let debuggable_ids = self.read().debuggables.iter_index()
+            .filter(|(_, debuggable)| !debuggable.hidden)
+            .map(|(index, debuggable)| (index, debuggable.registration))
+            .collect::<Vec<_>>();
+        debuggable_ids.into_iter().for_each(|(debuggable_id, uid)| {
+            Self::send_server_message(self, &*clients, &ServerMessage::Remove { id: debuggable_id, reason: RemoveReason::Shutdown, uid: Some(uid) });
+        });
+        // Goes the way the Removes went, so it's written after them and never alongside the writer
+        Self::send_server_message(self, &*clients, &ServerMessage::RemoveAll);
+        self.read().is_shut_down.store(true, AtomicOrdering::Relaxed);
+        let outgoing_queues = self.write().outgoing_queues.take();
+        if let Some(outgoing_queues) = outgoing_queues {
+            outgoing_queues.close(SHUTDOWN_FLUSH_TIMEOUT);
+        }
+        clients.into_iter()
+            .filter_map(|client_index| Self::client_stream(self, client_index))
+            .for_each(|stream| { let _ = stream.shutdown(Shutdown::Both); });
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.read().is_shut_down.load(AtomicOrdering::Relaxed)
+    }
+
+    pub(crate) fn shut_down_flag(&self) -> Arc<AtomicBool> {
+        self.read().is_shut_down.clone()
+    }
+
+    pub fn pause(&self) {
+        self.write().is_paused = true;
+    }
+
+    pub fn resume(&self) {
+        if !self.read().is_paused { return; }
+        self.write().is_paused = false;
+        let dirty_ids = mem::take(&mut self.write().dirty_while_paused);
+        let clients_to_notify = self.clients_of(Who::All);
+        for dirty_id in dirty_ids {
+            Self::send_notify_to(self, dirty_id, &*clients_to_notify);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.read().is_paused
+    }
+
+    /// Once the sync budget runs out the remaining stages are deferred, the next call starts from
+    /// the first one it skipped.
+    pub(crate) fn poll_clients(&self) {
+        if self.read().is_paused || self.read().polls_manually || self.is_shut_down() { return; }
+        let deadline = self.sync_deadline();
+        let first_stage = self.read().next_poll_stage;
+        let stage_count = PollStage::ALL.len();
+        for ran_stages in 0..stage_count {
+            let stage = (first_stage + ran_stages) % stage_count;
+            if ran_stages > 0 && self.is_past(deadline) {
+                let mut server = self.write();
+                server.next_poll_stage = stage;
+                server.stats.deferred_poll_stages.fetch_add((stage_count - ran_stages) as u64, AtomicOrdering::Relaxed);
+                return;
+            }
+            self.run_poll_stage(PollStage::ALL[stage], deadline);
+        }
+        self.write().next_poll_stage = 0;
+    }
+
+    pub(crate) fn set_polls_manually(&self, polls_manually: bool) {
+        self.write().polls_manually = polls_manually;
+    }
+
+    pub(crate) fn run_poll_stage(&self, stage: PollStage, deadline: Option<Instant>) {
+        match stage {
+            PollStage::Accept => {
+                if self.read().faults.skips_accept() {
+                    self.read().stats.injected_accept_skips.fetch_add(1, AtomicOrdering::Relaxed);
+                    return;
+                }
+                self.accept_incoming_not_blocking();
+            }
+            PollStage::ReadSockets => {
+                if !self.read().only_reads_from_dir {
+                    self.read_clients_no_context(true);
+                }
+                self.forget_disconnected_clients();
+            }
+            PollStage::ReadDir => { self.read_dir_transactions(deadline); }
+            PollStage::Housekeeping => {
+                #[cfg(feature = "jsonrpc")]
+                self.poll_jsonrpc();
+                #[cfg(feature = "discovery")]
+                self.send_beacon_if_due();
+                #[cfg(all(unix, feature = "capture-stdio"))]
+                self.publish_captured_stdout();
+                self.expire_stale();
+                self.expire_rpc_calls();
+                self.release_expired_update_groups();
+                self.expire_edit_transactions();
+                self.compact_if_due();
+                self.refresh_if_due();
+                self.refresh_derived_if_due();
+            }
+        }
+    }
+
+    /// Limits the time each sync may spend polling clients and the read directory.
+    pub fn set_sync_budget(&mut self, sync_budget: Option<Duration>) {
+        self.write().sync_budget = sync_budget;
+    }
+
+    fn sync_deadline(&self) -> Option<Instant> {
+        let server = self.read();
+        server.sync_budget.map(|sync_budget| server.clock.now_instant() + sync_budget)
+    }
+
+    fn is_past(&self, deadline: Option<Instant>) -> bool {
+        deadline.is_some_and(|deadline| self.read().clock.now_instant() >= deadline)
+    }
+
+    pub(crate) fn init_rpc_endpoint(&self, debuggable_id: usize) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.rpc_calls.get_or_insert_with(Vec::new);
+        }
+    }
+
+    pub(crate) fn take_rpc_calls(&self, debuggable_id: usize) -> Vec<PendingRpcCall> {
+        self.write().debuggables.get_mut(debuggable_id)
+            .and_then(|debuggable| debuggable.rpc_calls.as_mut())
+            .map(mem::take)
+            .unwrap_or_default()
+    }
+
+    pub fn pending_rpc_calls_of(&self, debuggable_id: usize) -> usize {
+        self.read().debuggables.get(debuggable_id).and_then(|debuggable| debuggable.rpc_calls.as_ref()).map_or(0, Vec::len)
+    }
+
+    fn expire_rpc_calls(&self) {
+        let expired_calls = {
+            let mut server = self.write();
+            let now = server.clock.now_instant();
+            let rpc_timeout = server.rpc_timeout;
+            let rpc_ids = server.debuggables.iter_index()
+                .filter(|(_, debuggable)| debuggable.rpc_calls.as_ref().is_some_and(|rpc_calls| !rpc_calls.is_empty()))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+            let mut expired_calls = Vec::new();
+            for rpc_id in rpc_ids {
+                let Some(rpc_calls) = server.debuggables.get_mut(rpc_id).and_then(|debuggable| debuggable.rpc_calls.as_mut()) else { continue; };
+                let (expired, waiting) = mem::take(rpc_calls).into_iter()
+                    .partition(|rpc_call| now.saturating_duration_since(rpc_call.received_at) >= rpc_timeout);
+                *rpc_calls = waiting;
+                expired_calls.extend::<Vec<PendingRpcCall>>(expired);
+            }
+            expired_calls
+        };
+        for expired_call in expired_calls {
+            Self::answer_rpc_call(self, &expired_call.author, expired_call.call_id, Err("The call timed out before the host served it".to_string()));
+        }
+    }
+
+    fn release_expired_update_groups(&self) {
+        let mut server = self.write();
+        if server.update_groups.is_empty() { return; }
+        let now = server.clock.now_instant();
+        let expired_groups = server.update_groups.take_expired(now);
+        server.release_update_groups(expired_groups);
+    }
+
+    fn expire_edit_transactions(&self) {
+        let expired = {
+            let mut server = self.write();
+            if server.edit_transactions.is_empty() { return; }
+            let now = server.clock.now_instant();
+            server.edit_transactions.take_expired(now)
+        };
+        for (client, txn_id) in expired {
+            let author = self.read().author_of(client, None);
+            Self::send_error_to(self, &author, format!("Edit transaction {txn_id} was dropped after going without activity"));
+        }
+    }
+
+    fn release_update_groups_synced_by(&self, debuggable_id: usize) {
+        let mut server = self.write();
+        if server.update_groups.is_empty() { return; }
+        let released_groups = server.update_groups.mark_synced(debuggable_id);
+        server.release_update_groups(released_groups);
+    }
+
+    pub fn set_refresh_interval(&mut self, refresh_interval: Option<Duration>) {
+        self.write().refresh_interval = refresh_interval;
+    }
+
+    pub fn refresh_if_due(&self) -> bool {
+        let is_due = match self.read().refresh_interval {
+            None => false,
+            Some(refresh_interval) => {
+                let server = self.read();
+                server.clock.now_instant().saturating_duration_since(server.last_refresh) >= refresh_interval
+            }
+        };
+        if is_due {
+            self.refresh_now();
+        }
+        is_due
+    }
+
+    /// Minimum time between refreshes of derived debuggables triggered by polling, explicit calls
+    /// to refresh_derived aren't limited.
+    pub fn set_derived_refresh_interval(&mut self, derived_refresh_interval: Duration) {
+        self.write().derived_refresh_interval = derived_refresh_interval;
+    }
+
+    /// Recomputes the derived debuggables of this server registered on the calling thread, those
+    /// registered on other threads are refreshed when they call it. Returns how many were computed.
+    pub fn refresh_derived(&self) -> usize {
+        let now = self.read().clock.now_instant();
+        self.write().last_derived_refresh = Some(now);
+        derived_debuggable::refresh_on_this_thread(self)
+    }
+
+    fn refresh_derived_if_due(&self) {
+        let is_due = {
+            let server = self.read();
+            server.last_derived_refresh
+                .map_or(true, |last_refresh| server.clock.now_instant().saturating_duration_since(last_refresh) >= server.derived_refresh_interval)
+        };
+        if is_due {
+            self.refresh_derived();
+        }
+    }
+
+    pub(crate) fn set_read_only(&self, debuggable_id: usize) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.read_only = true;
+        }
+    }
+
+    /// Clears the value of the debuggable, telling clients through NotifyUnset.
+    pub(crate) fn unset_value(&self, debuggable_id: usize) {
+        let was_set = {
+            let (now, changed_at) = (self.read().clock.now_instant(), self.read().clock.now_system());
+            let mut server = self.write();
+            match server.debuggables.get_mut(debuggable_id) {
+                Some(debuggable) if debuggable.last_value.is_some() => {
+                    debuggable.set_last_value(None, now, changed_at, None);
+                    true
+                }
+                _ => false,
+            }
+        };
+        if !was_set { return; }
+        let clients_to_notify = self.read().subscribed_clients(debuggable_id, &self.clients_of(Who::All));
+        Self::send_server_message(self, &*clients_to_notify, &ServerMessage::NotifyUnset { id: debuggable_id });
+    }
+
+    pub fn refresh_now(&self) {
+        let now = self.read().clock.now_instant();
+        self.write().last_refresh = now;
+        if self.read().is_paused { return; }
+        let clients_to_notify = self.clients_of(Who::All);
+        if clients_to_notify.is_empty() { return; }
+        let debuggable_ids = self.read().debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+        for debuggable_id in debuggable_ids {
+            Self::send_notify_to(self, debuggable_id, &*clients_to_notify);
+        }
+    }
+
+    pub fn read_all_clients(&self) {
+        let deadline = self.sync_deadline();
+        if !self.read().only_reads_from_dir {
+            self.read_clients_no_context(true);
+        }
+        self.read_dir_transactions(deadline);
+        self.refresh_derived_if_due();
+    }
+
+    pub fn read_clients_from_read_dir(&self) -> usize {
+        self.read_dir_transactions(self.sync_deadline())
+    }
+
+    /// Transactions left once the deadline passes stay in the directory for the next call, at least
+    /// one is always processed so the backlog drains.
+    fn read_dir_transactions(&self, deadline: Option<Instant>) -> usize {
+        let mut read_bytes = 0_usize;
+        if self.read().read_from_dir.is_none() { return read_bytes; }
+        if self.read().faults.defers_dir_read() {
+            self.read().stats.injected_dir_delays.fetch_add(1, AtomicOrdering::Relaxed);
+            return read_bytes;
+        }
+        let dir_read = match fs::read_dir(self.read().read_from_dir.as_ref().unwrap()) {
+            Ok(dir_read) => dir_read,
+            Err(error) => {
+                self.read_dir_failed(error);
+                return read_bytes;
+            }
+        };
+        self.read_dir_succeeded();
+        let mut transactions = dir_read.into_iter()
+            .filter(Result::is_ok)
+            .map(Result::unwrap)
+            .filter(|file| metadata(file.path()).map(|metada| metada.is_file()).unwrap_or(false))
+            .map(|file| {
+                let file_name = file.file_name().to_os_string().into_string();
+                (file, file_name)
+            })
+            .filter(|(_, file_name)| file_name.is_ok())
+            .map(|(file, filename)| (file, filename.unwrap()))
+            .filter_map(|(file, filename)| {
+                let (client_id, session, transaction) = parse_transaction_name(&filename)?;
+                Some((file, client_id, session, transaction))
+            })
+            .collect::<Vec<_>>();
+        // Sessions already seen go first, as a restarted client can only have begun after them,
+        // new ones are ordered by when their first file was written
+        let known_sessions = self.read().dir_sessions.keys().copied().collect::<HashSet<_>>();
+        let mut first_written = HashMap::<(usize, Option<u64>), SystemTime>::new();
+        for (file, client_id, session, _) in &transactions {
+            let written = file.metadata().and_then(|metadata| metadata.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            first_written.entry((*client_id, *session))
+                .and_modify(|first| *first = (*first).min(written))
+                .or_insert(written);
+        }
+        transactions.sort_by_key(|(_, client_id, session, transaction)| {
+            let is_new_session = session.is_some_and(|session| !known_sessions.contains(&(*client_id, session)));
+            (*client_id, is_new_session, first_written[&(*client_id, *session)], *session, *transaction)
+        });
+        let transaction_count = transactions.len();
+        for (processed, (file, client_id, session, transaction)) in transactions.into_iter().enumerate() {
+            if processed > 0 && self.is_past(deadline) {
+                self.read().stats.deferred_dir_transactions.fetch_add((transaction_count - processed) as u64, AtomicOrdering::Relaxed);
+                break;
+            }
+            if let Some(session) = session {
+                let last_transaction = self.read().dir_sessions.get(&(client_id, session)).copied();
+                if let Some(last_transaction) = last_transaction.filter(|last_transaction| transaction <= *last_transaction) {
+                    if transaction < last_transaction {
+                        log::warn!("Ignoring transaction {transaction} of session {session} of client {client_id}, which already reached transaction {last_transaction}");
+                    }
+                    let _ = fs::remove_file(file.path());
+                    continue;
+                }
+            }
+            let Ok(mut contents) = fs::read_to_string(file.path()) else { continue; };
+            if fs::remove_file(file.path()).is_err() { continue; }
+            if let Some(session) = session {
+                self.write().dir_sessions.insert((client_id, session), transaction);
+            }
+            read_bytes = read_bytes.checked_add(contents.len()).unwrap_or(usize::MAX);
+            let server = self.0.read();
+            let end_mark = server.message_endmark();
+            unescape_in_place(&mut contents, &Endmark::new_unchecked(end_mark.string(), end_mark.escape()));
+            drop(server);
+            Self::receive_message_of(self, client_id, contents, UpdateOrigin::ReadDir);
+        }
+        read_bytes
+    }
+
+    /// Reports the first failure of an outage and, if configured, tries recreating the directory at
+    /// most once per READ_DIR_RECREATE_INTERVAL.
+    fn read_dir_failed(&self, error: io::Error) {
+        let mut server = self.write();
+        let path = server.read_from_dir.clone().unwrap();
+        if server.read_dir_has_read && !server.read_dir_unavailable {
+            server.read_dir_unavailable = true;
+            log::error!("The read directory {path} can't be read anymore, dir clients won't be heard until it can: {error}");
+            server.events.emit(ServerEvent::ReadDirUnavailable { path: path.clone() });
+        }
+        if !server.recreates_read_dir { return; }
+        let now = server.clock.now_instant();
+        if server.last_read_dir_recreation.is_some_and(|last_recreation| now.saturating_duration_since(last_recreation) < READ_DIR_RECREATE_INTERVAL) { return; }
+        server.last_read_dir_recreation = Some(now);
+        drop(server);
+        if let Err(error) = fs::create_dir_all(&path) {
+            log::debug!("Could not recreate the read directory {path}: {error}");
+        }
+    }
+
+    fn read_dir_succeeded(&self) {
+        if self.read().read_dir_has_read && !self.read().read_dir_unavailable { return; }
+        let mut server = self.write();
+        server.read_dir_has_read = true;
+        if !server.read_dir_unavailable { return; }
+        server.read_dir_unavailable = false;
+        let path = server.read_from_dir.clone().unwrap();
+        log::info!("The read directory {path} can be read again");
+        server.events.emit(ServerEvent::ReadDirRecovered { path });
+    }
+
+    pub(crate) fn notify_new_value(&self, changed_id: usize, changed_value: Option<String>, who: Who) {
+        let last_value = match self.read().debuggables.get(changed_id) {
+            Some(debuggable) if !debuggable.removing => debuggable.last_value.clone(),
+            _ => {
+                log::debug!("Not notifying debuggable {changed_id}, it was removed");
+                return;
+            }
+        };
+        if last_value.as_deref() == changed_value.as_deref() {
+            // Clients whose update was rejected still need the value they overwrote in their view
+            if let Who::WrongClients(_) = who {
+                let clients_to_correct = self.clients_of(who);
+                Self::send_notify_to(self, changed_id, &*clients_to_correct);
+            }
+            return;
+        }
+        let (now, changed_at) = (self.read().clock.now_instant(), self.read().clock.now_system());
+        let is_unset = changed_value.is_none();
+        let origin = change_origin_of(&who);
+        let is_summarized = self.read().is_summarized(changed_id);
+        let mut server = self.write();
+        let Some(debuggable) = server.debuggables.get_mut(changed_id) else {
+            log::debug!("Not notifying debuggable {changed_id}, it was removed");
+            return;
+        };
+        if debuggable.initial_value_json.is_none() {
+            debuggable.initial_value_json = changed_value.as_deref().map(Arc::from);
+        }
+        // Accepted compare-and-swaps advance the revision early, clients can't tell the base apart
+        let text_base = debuggable.last_value.clone()
+            .filter(|_| debuggable.diffs_text() && !debuggable.hidden && debuggable.pending_cas.is_none() && !is_summarized)
+            .map(|base_value| (base_value, debuggable.revision));
+        debuggable.set_last_value(changed_value, now, changed_at, origin.client_index());
+        debuggable.overridden = matches!(origin, ChangeOrigin::Client { .. } | ChangeOrigin::Animation | ChangeOrigin::JsonRpc);
+        server.events.emit(ServerEvent::ValueChanged { id: changed_id, origin });
+        drop(server);
+        if self.read().is_paused {
+            self.write().dirty_while_paused.insert(changed_id);
+            return;
+        }
+        if self.read().batch_depth > 0 {
+            self.write().batched_notifies.push((changed_id, who));
+            return;
+        }
+        let clients_to_notify = self.read().subscribed_clients(changed_id, &self.clients_of(who));
+        if is_unset {
+            Self::send_server_message(self, &*clients_to_notify, &ServerMessage::NotifyUnset { id: changed_id });
+            return;
+        }
+        let clients_to_notify = match text_base.filter(|_| !self.read().is_summarized(changed_id)) {
+            None => clients_to_notify,
+            Some((base_value, base_revision)) => Self::send_text_patch_to(self, changed_id, &base_value, base_revision, &clients_to_notify),
+        };
+        Self::send_notify_to(self, changed_id, &*clients_to_notify);
+    }
+
+    pub(crate) fn init_debuggable(&self, name: String, is_keep: bool) -> (usize, bool) {
+        let declared_id = self.write().declarations.remove(&name).map(|(declared_id, _)| declared_id);
+        if let Some(declared_id) = declared_id.filter(|declared_id| self.read().debuggables.get(*declared_id).is_some()) {
+            if is_keep {
+                self.write().kept_debuggable_values.insert(name, declared_id);
+            }
+            // Claiming the placeholder is the host registering it, not a replay
+            return (declared_id, false);
+        }
+        if is_keep {
+            match self.read().kept_debuggable_values.get(&name) {
+                None => {}
+                Some(kept_index) => { return (*kept_index, true); }
+            }
+        }
+        let name_copy = if is_keep { Some(name.clone()) } else { None };
+        let mut debuggable = DebuggableOnServer::new(name, None, Vec::new(), self.read().clock.now_instant());
+        debuggable.registration = self.read().next_registration;
+        self.write().next_registration += 1;
+        let id_cell = debuggable.id_cell.clone();
+        let res = (self.write().debuggables.push(debuggable), false);
+        id_cell.store(res.0, AtomicOrdering::Relaxed);
+        if !is_keep { return res; }
+        self.write().kept_debuggable_values.insert(name_copy.unwrap(), res.0);
+        res
+    }
+
+    /// Announces a debuggable before its owner registers it, clients see it without a value until
+    /// then. The owner adopts its id and options when registering under the same name.
+    pub fn declare<Name: ToString>(&self, name: Name, options: DeclaredOptions) -> usize {
+        let name = name.to_string();
+        if let Some((declared_id, _)) = self.read().declarations.get(&name) {
+            return *declared_id;
+        }
+        let (id, existed) = self.init_debuggable(name.clone(), options.keep);
+        if existed { return id; }
+        self.init_hidden(id, options.hidden);
+        self.init_order(id, options.order);
+        self.write().declarations.insert(name, (id, options));
+        self.broadcast_added(id, AddedOrigin::HostCode);
+        self.broadcast_metadata(id);
+        id
+    }
+
+    /// Prefix and options every debuggable registered from now on starts from, its builder's own
+    /// options take precedence where it sets them.
+    pub fn set_default_options(&mut self, defaults: DebuggableDefaults) {
+        self.write().default_options = defaults;
+    }
+
+    /// Adds the prefix to the names of the debuggables built on this thread for this server while
+    /// the guard is alive, after the prefix of set_default_options and of any outer guard.
+    pub fn scoped_defaults(&self, prefix: &str) -> DefaultsGuard {
+        self.scoped_defaults_with(DebuggableDefaults::default().prefix(prefix))
+    }
+
+    /// Like scoped_defaults, also applying the options to those debuggables.
+    pub fn scoped_defaults_with(&self, defaults: DebuggableDefaults) -> DefaultsGuard {
+        DefaultsGuard::push(self as *const DebuggableServer as usize, defaults)
+    }
+
+    /// Defaults debuggables registered on this thread start from, guards alive on it included.
+    pub(crate) fn defaults_on_this_thread(&self) -> DebuggableDefaults {
+        defaults::scoped_on_this_thread(self as *const DebuggableServer as usize, &self.read().default_options)
+    }
+
+    pub(crate) fn declared_options_of(&self, name: &str) -> Option<DeclaredOptions> {
+        self.read().declarations.get(name).map(|(_, options)| options.clone())
+    }
+
+    /// Shared cell holding the current id of the debuggable, updated when the server compacts.
+    pub(crate) fn id_cell_of(&self, debuggable_id: usize) -> Option<Arc<AtomicUsize>> {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.id_cell.clone())
+    }
+
+    /// Rebuilds the storage of debuggables so their ids are contiguous again, as the slots of
+    /// removed debuggables are otherwise kept forever. Live debuggables follow their new ids and
+    /// every client is resynced, returns how many ids changed.
+    pub fn compact(&self) -> usize {
+        let remapped_ids = {
+            let mut server = self.write();
+            let old_ids = server.debuggables.iter_index().map(|(index, _)| index).collect::<Vec<_>>();
+            let mut compacted = FixedIndexVec::new();
+            let mut remapped_ids = HashMap::new();
+            for old_id in old_ids {
+                let debuggable = server.debuggables.remove(old_id).unwrap();
+                let new_id = compacted.push(debuggable);
+                compacted.get(new_id).unwrap().id_cell.store(new_id, AtomicOrdering::Relaxed);
+                if new_id != old_id {
+                    remapped_ids.insert(old_id, new_id);
+                }
+            }
+            server.debuggables = compacted;
+            server.removals_since_compaction = 0;
+            let remapped = |id: usize| remapped_ids.get(&id).copied().unwrap_or(id);
+            server.kept_debuggable_values.values_mut().for_each(|id| *id = remapped(*id));
+            server.declarations.values_mut().for_each(|(id, _)| *id = remapped(*id));
+            server.dirty_while_paused = mem::take(&mut server.dirty_while_paused).into_iter().map(remapped).collect();
+            server.batched_notifies.iter_mut().for_each(|(id, _)| *id = remapped(*id));
+            server.consecutive_rejections = mem::take(&mut server.consecutive_rejections).into_iter()
+                .map(|((client_id, debuggable_id), rejections)| ((client_id, remapped(debuggable_id)), rejections))
+                .collect();
+            server.update_groups.remap_ids(&remapped_ids);
+            server.edit_transactions.remap_ids(&remapped_ids);
+            server.composites.remap_ids(&remapped_ids);
+            #[cfg(feature = "jsonrpc")]
+            if let Some(jsonrpc) = server.jsonrpc.as_mut() {
+                jsonrpc.remap_ids(&remapped_ids);
+            }
+            remapped_ids
+        };
+        self.resync_clients();
+        remapped_ids.len()
+    }
+
+    /// Compacts automatically once this many debuggables were removed since the last compaction,
+    /// None disables it.
+    pub fn set_compaction_threshold(&mut self, compaction_threshold: Option<usize>) {
+        self.write().compaction_threshold = compaction_threshold;
+    }
+
+    fn compact_if_due(&self) {
+        let is_due = {
+            let server = self.read();
+            server.compaction_threshold.is_some_and(|threshold| server.removals_since_compaction >= threshold)
+        };
+        if is_due {
+            self.compact();
+        }
+    }
+
+    pub(crate) fn registration_of(&self, debuggable_id: usize) -> Option<u64> {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.registration)
+    }
+
+    /// Id of the debuggable that's never given to another one, unlike debuggable ids, which compact
+    /// hands out again once their debuggable is removed. Clients receive it in Added, Notify and
+    /// Remove.
+    pub fn uid_of(&self, debuggable_id: usize) -> Option<u64> {
+        self.registration_of(debuggable_id)
+    }
+
+    pub(crate) fn is_registration_alive(&self, debuggable_id: usize, registration: u64) -> bool {
+        self.registration_of(debuggable_id) == Some(registration)
+    }
+
+    pub(crate) fn remove_debuggable(&self, debuggable_id: usize, registration: u64) {
+        if !self.is_registration_alive(debuggable_id, registration) { return; }
+        let is_keep = self.read().debuggables.get(debuggable_id)
+            .map(|debuggable| self.read().kept_debuggable_values.contains_key(&debuggable.name))
+            .unwrap_or(false);
+        if is_keep { return; }
+        self.remove_and_broadcast(debuggable_id, RemoveReason::Dropped);
+    }
+
+    /// Removes a debuggable even if its value is kept, its owner registers it again under a new
+    /// id on its next sync.
+    pub fn kick_debuggable(&self, debuggable_id: usize) -> bool {
+        self.remove_forgetting_name(debuggable_id, RemoveReason::Kicked)
+    }
+
+    /// Removes a debuggable whose owner moved it to another server, even if its value is kept.
+    pub(crate) fn release_moved(&self, debuggable_id: usize, registration: u64) {
+        if !self.is_registration_alive(debuggable_id, registration) { return; }
+        self.remove_forgetting_name(debuggable_id, RemoveReason::Moved);
+    }
+
+    /// Removes a debuggable along with the value kept and the declaration under its name.
+    fn remove_forgetting_name(&self, debuggable_id: usize, reason: RemoveReason) -> bool {
+        let name = match self.read().debuggables.get(debuggable_id) {
+            None => return false,
+            Some(debuggable) => debuggable.name.clone(),
+        };
+        if self.read().kept_debuggable_values.get(&name) == Some(&debuggable_id) {
+            self.write().kept_debuggable_values.remove(&name);
+        }
+        if self.read().declarations.get(&name).map(|(declared_id, _)| *declared_id) == Some(debuggable_id) {
+            self.write().declarations.remove(&name);
+        }
+        self.remove_and_broadcast(debuggable_id, reason);
+        true
+    }
+
+    fn remove_and_broadcast(&self, debuggable_id: usize, reason: RemoveReason) {
+        let was_hidden = match self.write().debuggables.get_mut(debuggable_id) {
+            Some(debuggable) => {
+                debuggable.removing = true;
+                debuggable.hidden
+            }
+            None => {
+                log::debug!("Not removing debuggable {debuggable_id}, it was already removed");
+                return;
+            }
+        };
+        for orphaned_call in self.take_rpc_calls(debuggable_id) {
+            Self::answer_rpc_call(self, &orphaned_call.author, orphaned_call.call_id, Err("The RPC endpoint was removed".to_string()));
+        }
+        let removed = self.write().debuggables.remove(debuggable_id);
+        let uid = removed.map(|debuggable| {
+            crash_dump::forget(debuggable.dump_entry, &debuggable.name);
+            debuggable.registration
+        });
+        self.write().update_groups.forget_debuggable(debuggable_id);
+        self.write().edit_transactions.forget_debuggable(debuggable_id);
+        self.write().consecutive_rejections.retain(|(_, rejected_id), _| *rejected_id != debuggable_id);
+        self.write().removals_since_compaction += 1;
+        self.read().events.emit(ServerEvent::DebuggableRemoved { id: debuggable_id });
+        let dissolved_composites = self.write().composites.forget_debuggable(debuggable_id);
+        for name in dissolved_composites {
+            self.broadcast_server_message(&ServerMessage::CompositeDissolved { name });
+        }
+        // Clients were already sent a Remove when it was hidden
+        if was_hidden { return; }
+        let message = &*ServerMessage::Remove { id: debuggable_id, reason, uid }.to_json().unwrap();
+        let clients_len = self.read().clients().len();
+        Self::send_to_clients(self, &(0..clients_len).into_iter().collect::<Vec<_>>(), message);
+    }
+
+    /// Has clients show the debuggables as a single widget of the kind, member_ids holding one id
+    /// per slot of the kind in order. Replaces any composite declared under the same name, and
+    /// dissolves once any of the members is removed. Returns false if the number of members
+    /// doesn't match the kind or any of them isn't registered.
+    pub fn declare_composite(&self, name: &str, kind: CompositeKind, member_ids: Vec<usize>) -> bool {
+        if member_ids.len() != kind.slot_count() { return false; }
+        if member_ids.iter().any(|id| !self.read().debuggables.contains_index(*id)) { return false; }
+        self.write().composites.declare(name, kind, member_ids);
+        self.broadcast_composite(name);
+        true
+    }
+
+    /// Dissolves the composite, returns false if it wasn't announced to clients.
+    pub fn remove_composite(&self, name: &str) -> bool {
+        let was_announced = self.write().composites.remove(name);
+        if was_announced {
+            self.broadcast_server_message(&ServerMessage::CompositeDissolved { name: name.to_string() });
+        }
+        was_announced
+    }
+
+    /// Puts the debuggable in the slot of the composite, which is announced once all of its slots
+    /// are taken. See DebuggableBuilder::composite_member.
+    pub(crate) fn join_composite(&self, name: &str, kind: CompositeKind, slot: usize, debuggable_id: usize) {
+        let joined = self.write().composites.join(name, kind, slot, debuggable_id);
+        if !joined {
+            log::warn!("Debuggable {debuggable_id} can't take slot {slot} of composite {name} as a {kind:?}");
+            return;
+        }
+        self.broadcast_composite(name);
+    }
+
+    fn broadcast_composite(&self, name: &str) {
+        let composite_message = self.read().composites.message_of(name);
+        if let Some(composite_message) = composite_message {
+            self.broadcast_server_message(&composite_message);
+        }
+    }
+
+    fn broadcast_server_message(&self, message: &ServerMessage) {
+        let clients_to_notify = self.clients_of(Who::All);
+        Self::send_server_message(self, &*clients_to_notify, message);
+    }
+
+    pub fn set_hidden(&self, debuggable_id: usize, hidden: bool) {
+        let (was_hidden, uid) = match self.write().debuggables.get_mut(debuggable_id) {
+            None => return,
+            Some(debuggable) => (mem::replace(&mut debuggable.hidden, hidden), debuggable.registration),
+        };
+        if was_hidden == hidden { return; }
+        let clients_to_notify = self.clients_of(Who::All);
+        if hidden {
+            let message = &*ServerMessage::Remove { id: debuggable_id, reason: RemoveReason::Hidden, uid: Some(uid) }.to_json().unwrap();
+            Self::send_to_clients(self, &*clients_to_notify, message);
+        } else {
+            Self::send_added_to(self, debuggable_id, AddedOrigin::HostCode, &*clients_to_notify);
+            Self::send_notify_to(self, debuggable_id, &*clients_to_notify);
+            Self::send_metadata_to(self, debuggable_id, &*clients_to_notify);
+        }
+    }
+
+    pub(crate) fn init_hidden(&self, debuggable_id: usize, hidden: bool) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.hidden = hidden;
+        }
+    }
+
+    pub(crate) fn set_nullable(&self, debuggable_id: usize, nullable: bool) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.nullable = nullable;
+        }
+    }
+
+    pub(crate) fn set_interpolable(&self, debuggable_id: usize, interpolable: bool) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.interpolable = interpolable;
+        }
+    }
+
+    pub(crate) fn set_on_demand(&self, debuggable_id: usize, on_demand: bool) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.on_demand = on_demand;
+        }
+    }
+
+    /// Clients waiting for the value of an on demand debuggable.
+    pub fn value_requesters_of(&self, debuggable_id: usize) -> usize {
+        self.read().debuggables.get(debuggable_id).map_or(0, |debuggable| debuggable.value_requesters.len())
+    }
+
+    /// Sends the current value of an on demand debuggable to the clients that requested it since
+    /// it was last fulfilled, returning how many there were.
+    pub(crate) fn fulfill_requests(&self, debuggable_id: usize) -> usize {
+        let requesters = match self.write().debuggables.get_mut(debuggable_id) {
+            Some(debuggable) => mem::take(&mut debuggable.value_requesters),
+            None => return 0,
+        };
+        let requesters = self.clients_of(Who::All).iter().copied().filter(|client| requesters.contains(client)).collect::<Vec<_>>();
+        Self::send_current_value_to(self, debuggable_id, &requesters);
+        requesters.len()
+    }
+
+    pub(crate) fn set_text_diff(&self, debuggable_id: usize, text_diff: bool) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.text_diff = text_diff;
+        }
+    }
+
+    /// Clamps numbers clients send as updates of the debuggable into the inclusive range as soon as
+    /// they arrive, telling the sender the clamped value right away. Updates that aren't numbers
+    /// are rejected without waiting for the owner to sync.
+    pub fn set_numeric_bounds(&self, debuggable_id: usize, min: f64, max: f64) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.numeric_bounds = Some((min.min(max), max.max(min)));
+        }
+    }
+
+    pub fn clear_numeric_bounds(&self, debuggable_id: usize) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.numeric_bounds = None;
+        }
+    }
+
+    pub(crate) fn init_order(&self, debuggable_id: usize, order: i32) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.order = order;
+        }
+    }
+
+    pub fn set_order(&self, debuggable_id: usize, order: i32) {
+        let changed = match self.write().debuggables.get_mut(debuggable_id) {
+            Some(debuggable) if debuggable.order != order => {
+                debuggable.order = order;
+                true
+            }
+            _ => false,
+        };
+        if changed {
+            self.broadcast_metadata(debuggable_id);
+        }
+    }
+
+    pub(crate) fn record_remote_update(&self, debuggable_id: usize) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.change_generation += 1;
+        }
+    }
+
+    pub fn change_generation_of(&self, debuggable_id: usize) -> Option<u64> {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.change_generation)
+    }
+
+    pub fn revision_of(&self, debuggable_id: usize) -> Option<u64> {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.revision)
+    }
+
+    pub fn order_of(&self, debuggable_id: usize) -> Option<i32> {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.order)
+    }
+
+    pub fn is_hidden(&self, debuggable_id: usize) -> Option<bool> {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.hidden)
+    }
+
+    pub(crate) fn set_ttl(&self, debuggable_id: usize, ttl: Option<Duration>) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.ttl = ttl;
+        }
+    }
+
+    pub fn expire_stale(&self) -> usize {
+        let stale_ids = {
+            let server = self.read();
+            let now = server.clock.now_instant();
+            server.debuggables.iter_index()
+                .filter(|(_, debuggable)| !server.kept_debuggable_values.contains_key(&debuggable.name))
+                .filter(|(_, debuggable)| debuggable.ttl.map(|ttl| now.saturating_duration_since(debuggable.last_touched) > ttl).unwrap_or(false))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        };
+        stale_ids.iter().for_each(|stale_id| self.remove_and_broadcast(*stale_id, RemoveReason::Expired));
+        stale_ids.len()
+    }
+
+    pub(crate) fn set_redactor(&self, debuggable_id: usize, redactor: Option<Redactor>) {
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.redactor = redactor;
+        }
+    }
+
+    pub(crate) fn last_value_of(&self, debuggable_id: usize) -> Option<Arc<str>> {
+        self.read().debuggables.get(debuggable_id).and_then(|debuggable| debuggable.last_value.clone())
+    }
+
+    pub fn pending_updates_of(&self, debuggable_id: usize) -> usize {
+        self.read().debuggables.get(debuggable_id).map(|debuggable| debuggable.incoming_jsons.len()).unwrap_or(0)
+    }
+
+    /// Where the pending updates of the debuggable came from and how long they have been waiting,
+    /// oldest first.
+    pub fn pending_details(&self, debuggable_id: usize) -> Vec<PendingUpdateInfo> {
+        let server = self.read();
+        let now = server.clock.now_instant();
+        let Some(debuggable) = server.debuggables.get(debuggable_id) else { return Vec::new(); };
+        let mut details = debuggable.incoming_jsons.iter().map(|update| (update.received, update.info(now))).collect::<Vec<_>>();
+        details.sort_by_key(|(received, _)| *received);
+        details.into_iter().map(|(_, info)| info).collect()
+    }
+
+    pub fn discard_pending_of(&self, debuggable_id: usize) -> usize {
+        let discarded = match self.write().debuggables.get_mut(debuggable_id) {
+            None => return 0,
+            Some(debuggable) => mem::take(&mut debuggable.incoming_jsons),
+        };
+        let senders = discarded.iter().map(|update| update.author.client).collect::<HashSet<_>>();
+        let clients_to_notify = self.clients_of(Who::WrongClients(senders));
+        Self::send_notify_to(self, debuggable_id, &*clients_to_notify);
+        discarded.iter()
+            .filter_map(|update| Some((&update.author, update.request_id?)))
+            .for_each(|(author, request_id)| self.acknowledge_update(author, request_id, false));
+        discarded.len()
+    }
+
+    pub(crate) fn take_incoming_index_updates_of(&self, debuggable_id: usize) -> Vec<(usize, usize, String)> {
+        match self.write().debuggables.get_mut(debuggable_id) {
+            None => Vec::new(),
+            Some(debuggable) => mem::take(&mut debuggable.incoming_index_updates),
+        }
+    }
+
+    pub(crate) fn notify_index(&self, changed_id: usize, index: usize, element_json: String, full_value: Option<String>, who: Who) {
+        let (now, changed_at) = (self.read().clock.now_instant(), self.read().clock.now_system());
+        let author = change_origin_of(&who).client_index();
+        match self.write().debuggables.get_mut(changed_id) {
+            None => return,
+            Some(debuggable) => {
+                debuggable.set_last_value(full_value, now, changed_at, author);
+                debuggable.overridden = author.is_some();
+            }
+        }
+        let revision = self.read().debuggables.get(changed_id).map(|debuggable| debuggable.revision).unwrap_or_default();
+        if self.read().is_paused {
+            self.write().dirty_while_paused.insert(changed_id);
+            return;
+        }
+        let (index_clients, full_clients): (Vec<usize>, Vec<usize>) = {
+            let clients = self.clients_of(who);
+            let server = self.read();
+            server.subscribed_clients(changed_id, &clients).into_iter()
+                .partition(|client| server.protocol_version_of(*client) >= NOTIFY_INDEX_PROTOCOL_VERSION)
+        };
+        if self.read().debuggables.get(changed_id).map(|debuggable| debuggable.hidden || debuggable.redactor.is_some()).unwrap_or(true) {
+            Self::send_notify_to(self, changed_id, &*index_clients);
+        } else {
+            Self::send_server_message(self, &*index_clients, &ServerMessage::NotifyIndex { id: changed_id, index, element_json, revision });
+        }
+        Self::send_notify_to(self, changed_id, &*full_clients);
+    }
+
+    pub(crate) fn set_last_value(&self, debuggable_id: usize, last_value: Option<String>) {
+        let (now, changed_at) = (self.read().clock.now_instant(), self.read().clock.now_system());
+        if let Some(debuggable) = self.write().debuggables.get_mut(debuggable_id) {
+            debuggable.set_last_value(last_value, now, changed_at, None);
+        }
+    }
+
+    /// When the value of the debuggable last changed and the client whose update changed it, None
+    /// as author when the host did.
+    pub fn last_change_info(&self, debuggable_id: usize) -> Option<(SystemTime, Option<usize>)> {
+        let server = self.read();
+        let debuggable = server.debuggables.get(debuggable_id)?;
+        Some((debuggable.last_changed_at?, debuggable.last_author))
+    }
+
+    /// Whether the current value of the debuggable was set by a client rather than the host.
+    pub fn is_overridden(&self, debuggable_id: usize) -> bool {
+        self.read().debuggables.get(debuggable_id).is_some_and(|debuggable| debuggable.overridden)
+    }
+
+    /// Visible debuggables whose current value was set by a client rather than the host.
+    pub fn overridden_ids(&self) -> Vec<usize> {
+        self.read().debuggables.iter_index()
+            .filter(|(_, debuggable)| debuggable.overridden && !debuggable.hidden)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Queues the value the debuggable was registered with as an update, as group resets do, so
+    /// the override is cleared and clients notified once the debuggable syncs. Returns false if
+    /// the debuggable isn't overridden.
+    pub fn clear_override(&self, debuggable_id: usize) -> bool {
+        let mut server = self.write();
+        let Some(debuggable) = server.debuggables.get_mut(debuggable_id).filter(|debuggable| debuggable.overridden) else { return false; };
+        let Some(initial_value_json) = debuggable.initial_value_json.clone() else { return false; };
+        // Syncing the value it already holds wouldn't notify anyone, so the flag is cleared here
+        if debuggable.last_value.as_deref() == Some(&*initial_value_json) {
+            debuggable.overridden = false;
+            drop(server);
+            let clients = self.clients_of(Who::All);
+            Self::send_notify_to(self, debuggable_id, &*clients);
+            return true;
+        }
+        server.queue_update(debuggable_id, Author::client(GROUP_RESET_CLIENT_ID), None, initial_value_json.to_string(), UpdateOrigin::Host).is_some()
+    }
+
+    pub(crate) fn broadcast_notify_many(&self, debuggable_ids: &[usize]) {
+        if self.read().is_paused {
+            self.write().dirty_while_paused.extend(debuggable_ids.iter().copied());
+            return;
+        }
+        let clients = self.clients_of(Who::All);
+        self.notify_many_to(debuggable_ids, &clients);
+    }
+
+    /// Sends clients supporting it a single NotifyMany, and one Notify per debuggable to the rest.
+    /// Clients subscribed to some debuggables are sent those among them in messages of their own.
+    fn notify_many_to(&self, debuggable_ids: &[usize], clients: &[usize]) {
+        let (subscribing_clients, clients): (Vec<usize>, Vec<usize>) = {
+            let server = self.read();
+            clients.iter().partition(|client| server.sessions.of_client(**client).is_some_and(|session| session.subscriptions.is_some()))
+        };
+        for client in subscribing_clients {
+            let subscribed_ids = {
+                let server = self.read();
+                debuggable_ids.iter().copied().filter(|debuggable_id| !server.subscribed_clients(*debuggable_id, &[client]).is_empty()).collect::<Vec<_>>()
+            };
+            self.notify_many_to_all_of(&subscribed_ids, &[client]);
+        }
+        self.notify_many_to_all_of(debuggable_ids, &clients);
+    }
+
+    fn notify_many_to_all_of(&self, debuggable_ids: &[usize], clients: &[usize]) {
+        if clients.is_empty() || debuggable_ids.is_empty() { return; }
+        let (batch_clients, single_clients): (Vec<usize>, Vec<usize>) = {
+            let server = self.read();
+            clients.iter().partition(|client| server.protocol_version_of(**client) >= NOTIFY_MANY_PROTOCOL_VERSION)
+        };
+        let notifies = {
+            let server = self.read();
+            debuggable_ids.iter().filter_map(|debuggable_id| server.notify_entry_of(*debuggable_id)).collect::<Vec<_>>()
+        };
+        if !notifies.is_empty() {
+            Self::send_server_message(self, &*batch_clients, &ServerMessage::NotifyMany { notifies });
+        }
+        let summarized_ids = {
+            let server = self.read();
+            debuggable_ids.iter().copied().filter(|debuggable_id| server.is_summarized(*debuggable_id)).collect::<Vec<_>>()
+        };
+        summarized_ids.into_iter().for_each(|debuggable_id| Self::send_notify_to(self, debuggable_id, &*batch_clients));
+        debuggable_ids.iter().for_each(|debuggable_id| Self::send_notify_to(self, *debuggable_id, &*single_clients));
+    }
+
+    /// Holds back notifications of new values until the returned batch is dropped, which then
+    /// sends each client one NotifyMany with every debuggable that changed for it, instead of a
+    /// Notify per change. Batches may be nested, notifications are sent once the outermost ends.
+    pub fn begin_batch(server: &Arc<RwLock<DebuggableServer>>) -> NotifyBatch {
+        server.read().unwrap().write().batch_depth += 1;
+        NotifyBatch::new(server.clone())
+    }
+
+    pub(crate) fn end_batch(&self) {
+        let batch_depth = {
+            let mut server = self.write();
+            server.batch_depth = server.batch_depth.saturating_sub(1);
+            server.batch_depth
+        };
+        if batch_depth == 0 {
+            self.flush_batch();
+        }
+    }
+
+    /// Sends the notifications held back so far. Each client is sent every debuggable that changed
+    /// for it once, with its latest value, skipping those whose update came from that client.
+    pub(crate) fn flush_batch(&self) {
+        let batched_notifies = mem::take(&mut self.write().batched_notifies);
+        if batched_notifies.is_empty() || self.is_shut_down() { return; }
+        if self.read().is_paused {
+            self.write().dirty_while_paused.extend(batched_notifies.into_iter().map(|(debuggable_id, _)| debuggable_id));
+            return;
+        }
+        let mut ids_per_client: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (debuggable_id, who) in batched_notifies {
+            let clients = self.read().subscribed_clients(debuggable_id, &self.clients_of(who));
+            for client in clients {
+                let ids = ids_per_client.entry(client).or_default();
+                if !ids.contains(&debuggable_id) {
+                    ids.push(debuggable_id);
+                }
+            }
+        }
+        // Clients sent the same debuggables share their messages
+        let mut clients_per_ids: HashMap<Vec<usize>, Vec<usize>> = HashMap::new();
+        ids_per_client.into_iter().for_each(|(client, ids)| clients_per_ids.entry(ids).or_default().push(client));
+        for (debuggable_ids, clients) in clients_per_ids {
+            let (unset_ids, set_ids): (Vec<usize>, Vec<usize>) = {
+                let server = self.read();
+                debuggable_ids.into_iter()
+                    .partition(|debuggable_id| server.debuggables.get(*debuggable_id).is_some_and(|debuggable| debuggable.last_value.is_none()))
+            };
+            self.notify_many_to(&set_ids, &clients);
+            unset_ids.into_iter().for_each(|debuggable_id| {
+                Self::send_server_message(self, &*clients, &ServerMessage::NotifyUnset { id: debuggable_id });
+            });
+        }
+    }
+
+    pub(crate) fn renotify_clients(&self, debuggable_id: usize, clients: &[usize]) {
+        Self::send_notify_to(self, debuggable_id, clients);
+    }
+
+    /// Marks the debuggable as synced without comparing its value, returns whether clients sent
+    /// updates for it since the last sync or it's being animated.
+    pub(crate) fn touch_debuggable(&self, debuggable_id: usize) -> bool {
+        self.release_update_groups_synced_by(debuggable_id);
+        let mut server = self.write();
+        let now = server.clock.now_instant();
+        let Some(debuggable) = server.debuggables.get_mut(debuggable_id) else {
+            log::debug!("Not touching debuggable {debuggable_id}, it was removed");
+            return false;
+        };
+        debuggable.last_touched = now;
+        !debuggable.incoming_jsons.is_empty() || debuggable.animation.is_some()
+    }
+
+    /// Takes the updates sent by clients and compares the local value against the last one sent, in
+    /// a single pass over the server data. If no client sent anything, a changed local value is
+    /// broadcast right away; otherwise the caller decides between the candidates outside the lock.
+    /// A running animation adds its next step as an update, unless the value changed locally.
+    pub(crate) fn sync_debuggable(&self, debuggable_id: usize, current_json: &Option<String>) -> PendingSync {
+        self.release_update_groups_synced_by(debuggable_id);
+        let (incoming_jsons, has_changed) = {
+            let mut server = self.write();
+            let now = server.clock.now_instant();
+            let animation_step = server.animation_step;
+            let Some(debuggable) = server.debuggables.get_mut(debuggable_id) else {
+                log::debug!("Not syncing debuggable {debuggable_id}, it was removed");
+                return PendingSync { incoming_jsons: Vec::new(), has_changed: false };
+            };
+            debuggable.last_touched = now;
+            let has_changed = debuggable.last_value.as_deref() != current_json.as_deref();
+            if has_changed {
+                debuggable.animation = None;
+            }
+            let mut incoming_jsons = mem::take(&mut debuggable.incoming_jsons);
+            if incoming_jsons.is_empty() {
+                if let Some(step_json) = debuggable.step_animation(now, animation_step) {
+                    incoming_jsons.push(PendingUpdate { author: Author::client(ANIMATION_CLIENT_ID), request_id: None, json: step_json, received: now, origin: UpdateOrigin::Animation });
+                }
+            }
+            // The newest by when it was received is applied, whatever order the transports queued in
+            incoming_jsons.sort_by_key(|update| update.received);
+            if let Some(newest) = incoming_jsons.last().filter(|newest| newest.origin != UpdateOrigin::Animation) {
+                server.stats.record_update_latency(now.saturating_duration_since(newest.received));
+            }
+            (incoming_jsons, has_changed)
+        };
+        if incoming_jsons.is_empty() && has_changed {
+            self.notify_new_value(debuggable_id, current_json.clone(), Who::All);
+            return PendingSync { incoming_jsons, has_changed: false };
+        }
+        PendingSync { incoming_jsons, has_changed }
+    }
+}
+
+fn change_origin_of(who: &Who) -> ChangeOrigin {
+    match who {
+        Who::AllBut(ANIMATION_CLIENT_ID) => ChangeOrigin::Animation,
+        Who::AllBut(GROUP_RESET_CLIENT_ID) => ChangeOrigin::GroupReset,
+        #[cfg(feature = "jsonrpc")]
+        Who::AllBut(jsonrpc::JSONRPC_CLIENT_ID) => ChangeOrigin::JsonRpc,
+        Who::AllBut(client) if !RESERVED_CLIENT_IDS.contains(client) => ChangeOrigin::Client { index: *client },
+        _ => ChangeOrigin::Host,
+    }
+}
+
+/// Splits the text in pieces of at most max_bytes, the piece a character would be split in grows
+/// to hold it whole. Empty texts are a single empty piece.
+fn chunks_of(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = max_bytes.max(1).min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        let (chunk, remaining) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = remaining;
+    }
+    if chunks.is_empty() {
+        chunks.push(text);
+    }
+    chunks
+}
+
+/// Client, session and transaction of a file named client-N-session-S-transaction-M, or
+/// client-N-transaction-M as written by clients predating sessions.
+fn parse_transaction_name(file_name: &str) -> Option<(usize, Option<u64>, u64)> {
+    let (client_and_session, transaction) = file_name.strip_prefix("client-")?.split_once("-transaction-")?;
+    let (client_id, session) = match client_and_session.split_once("-session-") {
+        Some((client_id, session)) => (client_id, Some(session.parse().ok()?)),
+        None => (client_and_session, None),
+    };
+    Some((client_id.parse().ok()?, session, transaction.parse().ok()?))
+}
+
+pub(crate) struct PendingSync {
+    pub(crate) incoming_jsons: Vec<PendingUpdate>,
+    pub(crate) has_changed: bool,
+}
+
+#[derive(Clone)]
+pub struct Redactor(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl Redactor {
+    pub fn new<Redact: Fn(&str) -> String + Send + Sync + 'static>(redact: Redact) -> Self {
+        Self(Arc::new(redact))
+    }
+
+    pub fn redact(&self, json: &str) -> String {
+        (self.0)(json)
+    }
+}
+
+impl Debug for Redactor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Redactor")
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DebuggableOnServer {
+    name: String,
+    last_value: Option<Arc<str>>,
+    incoming_jsons: Vec<PendingUpdate>,
+    redactor: Option<Redactor>,
+    // Never reused, clients know it as the uid of the debuggable
+    registration: u64,
+    ttl: Option<Duration>,
+    last_touched: Instant,
+    hidden: bool,
+    incoming_index_updates: Vec<(usize, usize, String)>,
+    nullable: bool,
+    order: i32,
+    revision: u64,
+    pending_cas: Option<String>,
+    change_generation: u64,
+    id_cell: Arc<AtomicUsize>,
+    last_changed: Option<Instant>,
+    // Wall-clock time of the last change and the client that made it, None if the host did
+    last_changed_at: Option<SystemTime>,
+    last_author: Option<usize>,
+    animation: Option<Animation>,
+    interpolable: bool,
+    // Whether changes are sent as NotifyTextPatch to the clients applying them
+    text_diff: bool,
+    // Inclusive range numeric updates from clients are clamped into before being queued
+    numeric_bounds: Option<(f64, f64)>,
+    // Calls waiting for the host to serve them, None unless the debuggable is an RPC endpoint
+    rpc_calls: Option<Vec<PendingRpcCall>>,
+    // Clients that requested the whole value, the only ones editing it while it's summarized
+    full_value_fetched_by: HashSet<usize>,
+    // Whether updates from clients are refused, as its value is computed by the host
+    read_only: bool,
+    // First value the owner registered the debuggable with, restored by group resets
+    initial_value_json: Option<Arc<str>>,
+    // Whether the value is only sent to clients that requested it, once its owner computes it
+    on_demand: bool,
+    // Clients that requested the value of an on demand debuggable since it was last fulfilled
+    value_requesters: HashSet<usize>,
+    // Set once its removal starts, messages of clients editing it are dropped from then on
+    removing: bool,
+    // Whether the current value came from a client rather than the host
+    overridden: bool,
+    // What crash dumps keep its value under
+    dump_entry: u64,
+}
+
+impl DebuggableOnServer {
+    pub fn new(name: String, last_value: Option<String>, incoming_jsons: Vec<PendingUpdate>, last_touched: Instant) -> Self {
+        Self {
+            name,
+            last_value: last_value.map(Arc::from),
+            incoming_jsons,
+            redactor: None,
+            registration: 0,
+            ttl: None,
+            last_touched,
+            hidden: false,
+            incoming_index_updates: Vec::new(),
+            nullable: false,
+            order: 0,
+            revision: 0,
+            pending_cas: None,
+            change_generation: 0,
+            id_cell: Arc::new(AtomicUsize::new(0)),
+            last_changed: None,
+            last_changed_at: None,
+            last_author: None,
+            animation: None,
+            interpolable: false,
+            text_diff: false,
+            numeric_bounds: None,
+            rpc_calls: None,
+            full_value_fetched_by: HashSet::new(),
+            read_only: false,
+            initial_value_json: None,
+            on_demand: false,
+            value_requesters: HashSet::new(),
+            removing: false,
+            overridden: false,
+            dump_entry: crash_dump::new_entry(),
+        }
+    }
+
+    fn set_last_value(&mut self, last_value: Option<String>, now: Instant, changed_at: SystemTime, author: Option<usize>) {
+        // An accepted compare-and-swap already advanced the revision when it was queued
+        if self.pending_cas.take().is_none() {
+            self.revision += 1;
+        }
+        self.last_value = last_value.map(Arc::from);
+        self.last_changed = Some(now);
+        self.last_changed_at = Some(changed_at);
+        self.last_author = author;
+        if crash_dump::is_dumped(&self.name) {
+            crash_dump::record(self.dump_entry, &self.name, self.outgoing_value());
+        }
+    }
+
+    /// Redacted values are left out, as patches of the raw value would reveal it.
+    fn diffs_text(&self) -> bool {
+        self.text_diff && self.redactor.is_none() && !self.on_demand
+    }
+
+    /// Unix time in milliseconds of the last change, as sent in Notify.
+    fn changed_at_ms(&self) -> Option<u64> {
+        let since_epoch = self.last_changed_at?.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+        Some(since_epoch.as_millis() as u64)
+    }
+
+    fn start_animation(&mut self, target_json: String, duration: Duration, now: Instant) -> Result<(), String> {
+        self.animation = Some(Animation::new(self.last_value.as_deref(), target_json, duration, now, self.interpolable)?);
+        Ok(())
+    }
+
+    fn step_animation(&mut self, now: Instant, min_step: Duration) -> Option<String> {
+        let (step_json, has_ended) = self.animation.as_mut()?.step(now, min_step)?;
+        if has_ended {
+            self.animation = None;
+        }
+        Some(step_json)
+    }
+
+    fn current_value_for_cas(&self) -> String {
+        let current_value = self.pending_cas.clone()
+            .or_else(|| self.last_value.as_deref().map(str::to_string))
+            .unwrap_or_else(|| "{}".to_string());
+        match self.redactor.as_ref() {
+            None => current_value,
+            Some(redactor) => redactor.redact(&current_value),
+        }
+    }
+
+    fn outgoing_value(&self) -> Option<Arc<str>> {
+        let last_value = self.last_value.as_ref()?;
+        match self.redactor.as_ref() {
+            None => Some(last_value.clone()),
+            Some(redactor) => Some(Arc::from(redactor.redact(last_value))),
+        }
+    }
+}
+
+/// Who sent an update, the connection of the client and, for monitors multiplexing several panels
+/// over one connection, the panel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Author {
+    pub client: usize,
+    pub panel: Option<String>,
+}
+
+impl Author {
+    pub fn client(client: usize) -> Self {
+        Self { client, panel: None }
     }
 }
 
-pub(crate) enum Who {
+pub enum Who {
     Client(usize),
+    Handle(ClientHandle),
     All,
     AllBut(usize),
     WrongClients(HashSet<usize>),
@@ -0,0 +1,173 @@
+#[cfg(not(windows))]
+use std::io;
+#[cfg(not(windows))]
+use std::net::SocketAddr;
+
+#[cfg(not(windows))]
+use crate::server::listeners::ForwardedPeers;
+
+#[cfg(windows)]
+pub(crate) use self::windows::{connect_bridged, spawn_pipe_forwarder};
+
+/// Accepts clients on the named pipe and forwards each of them to the server listening on the
+/// inner address, the same way additional listeners do.
+#[cfg(not(windows))]
+pub(crate) fn spawn_pipe_forwarder(name: &str, _inner_address: SocketAddr, _forwarded_peers: ForwardedPeers) -> io::Result<()> {
+    Err(unsupported(name))
+}
+
+/// Opens the named pipe and bridges it to a loopback stream the client reads as usual.
+#[cfg(not(windows))]
+pub(crate) fn connect_bridged(name: &str) -> io::Result<std::net::TcpStream> {
+    Err(unsupported(name))
+}
+
+#[cfg(not(windows))]
+fn unsupported(name: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("Named pipe {name} is not available, named pipes are only supported on Windows"))
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::OsStr;
+    use std::fs::File;
+    use std::io;
+    use std::io::{ErrorKind, Read, Write};
+    use std::mem;
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
+    use std::ptr;
+    use std::thread;
+    use std::time::Duration;
+
+    use windows_sys::Win32::Foundation::{ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, GENERIC_READ, GENERIC_WRITE, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{CreateFileW, FILE_FLAG_FIRST_PIPE_INSTANCE, OPEN_EXISTING, PIPE_ACCESS_DUPLEX};
+    use windows_sys::Win32::System::Pipes::{ConnectNamedPipe, CreateNamedPipeW, PeekNamedPipe, WaitNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT};
+
+    use crate::server::listeners::{connect_inner, ForwardedPeers};
+
+    const PUMP_POLL_TIMEOUT: Duration = Duration::from_millis(1);
+    const PIPE_BUFFER_LEN: u32 = 64 * 1024;
+    const CONNECT_WAIT_MILLIS: u32 = 2000;
+
+    pub(crate) fn spawn_pipe_forwarder(name: &str, inner_address: SocketAddr, forwarded_peers: ForwardedPeers) -> io::Result<()> {
+        let wide_name = wide(name);
+        // The first instance is created here so a pipe name already taken fails the build
+        let mut instance = create_instance(&wide_name, true)?;
+        let name = name.to_string();
+        thread::spawn(move || loop {
+            if let Err(error) = wait_for_client(&instance) {
+                log::warn!("Named pipe {} stopped accepting clients: {}", name, error);
+                return;
+            }
+            let next_instance = match create_instance(&wide_name, false) {
+                Ok(next_instance) => next_instance,
+                Err(error) => {
+                    log::warn!("Named pipe {} stopped accepting clients: {}", name, error);
+                    return;
+                }
+            };
+            let client_pipe = mem::replace(&mut instance, next_instance);
+            let forwarded_peers = forwarded_peers.clone();
+            let name = name.clone();
+            thread::spawn(move || {
+                let forwarded = connect_inner(inner_address, &forwarded_peers, None).and_then(|inner_stream| pump(client_pipe, inner_stream));
+                if let Err(error) = forwarded {
+                    log::warn!("Client of named pipe {} dropped: {}", name, error);
+                }
+            });
+        });
+        Ok(())
+    }
+
+    pub(crate) fn connect_bridged(name: &str) -> io::Result<TcpStream> {
+        let pipe = open_client_end(&wide(name))?;
+        let bridge = TcpListener::bind("127.0.0.1:0")?;
+        let client_stream = TcpStream::connect(bridge.local_addr()?)?;
+        let (bridge_stream, _) = bridge.accept()?;
+        let name = name.to_string();
+        thread::spawn(move || {
+            if let Err(error) = pump(pipe, bridge_stream) {
+                log::warn!("Connection to named pipe {} dropped: {}", name, error);
+            }
+        });
+        Ok(client_stream)
+    }
+
+    fn wide(name: &str) -> Vec<u16> {
+        OsStr::new(name).encode_wide().chain(Some(0)).collect()
+    }
+
+    fn create_instance(wide_name: &[u16], is_first: bool) -> io::Result<File> {
+        let open_mode = PIPE_ACCESS_DUPLEX | if is_first { FILE_FLAG_FIRST_PIPE_INSTANCE } else { 0 };
+        let handle = unsafe {
+            CreateNamedPipeW(wide_name.as_ptr(), open_mode, PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                             PIPE_UNLIMITED_INSTANCES, PIPE_BUFFER_LEN, PIPE_BUFFER_LEN, 0, ptr::null())
+        };
+        file_of(handle)
+    }
+
+    fn wait_for_client(instance: &File) -> io::Result<()> {
+        if unsafe { ConnectNamedPipe(instance.as_raw_handle() as HANDLE, ptr::null_mut()) } != 0 { return Ok(()); }
+        let error = io::Error::last_os_error();
+        // The client connected between creating the instance and waiting for it
+        if error.raw_os_error() == Some(ERROR_PIPE_CONNECTED as i32) { return Ok(()); }
+        Err(error)
+    }
+
+    fn open_client_end(wide_name: &[u16]) -> io::Result<File> {
+        loop {
+            let handle = unsafe {
+                CreateFileW(wide_name.as_ptr(), GENERIC_READ | GENERIC_WRITE, 0, ptr::null(), OPEN_EXISTING, 0, 0)
+            };
+            match file_of(handle) {
+                Err(error) if error.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
+                    if unsafe { WaitNamedPipeW(wide_name.as_ptr(), CONNECT_WAIT_MILLIS) } == 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                opened => return opened,
+            }
+        }
+    }
+
+    fn file_of(handle: HANDLE) -> io::Result<File> {
+        if handle == INVALID_HANDLE_VALUE { return Err(io::Error::last_os_error()); }
+        Ok(unsafe { File::from_raw_handle(handle as RawHandle) })
+    }
+
+    fn bytes_available(pipe: &File) -> io::Result<usize> {
+        let mut available = 0_u32;
+        let peeked = unsafe {
+            PeekNamedPipe(pipe.as_raw_handle() as HANDLE, ptr::null_mut(), 0, ptr::null_mut(), &mut available, ptr::null_mut())
+        };
+        if peeked == 0 { return Err(io::Error::last_os_error()); }
+        Ok(available as usize)
+    }
+
+    /// Copies bytes both ways from a single thread, as reads and writes on a synchronous pipe
+    /// handle would wait on each other. The pipe is only read once it has bytes available.
+    fn pump(mut pipe: File, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(PUMP_POLL_TIMEOUT))?;
+        let mut buffer = [0_u8; 16 * 1024];
+        loop {
+            let available = match bytes_available(&pipe) {
+                Ok(available) => available,
+                Err(error) if error.kind() == ErrorKind::BrokenPipe => return Ok(()),
+                Err(error) => return Err(error),
+            };
+            if available > 0 {
+                let read = pipe.read(&mut buffer[..available.min(buffer.len())])?;
+                if read == 0 { return Ok(()); }
+                stream.write_all(&buffer[..read])?;
+            }
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => pipe.write_all(&buffer[..read])?,
+                Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
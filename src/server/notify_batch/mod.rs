@@ -0,0 +1,52 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+
+use crate::server::DebuggableServer;
+
+/// Notifications of a server held back since DebuggableServer::begin_batch, sent together when the
+/// batch is dropped. Debuggables synced meanwhile, on any thread, are part of it.
+///
+/// ```
+/// use debug_monitor::debuggable::DebuggableBuilder;
+/// use debug_monitor::scoped_server::ScopedServer;
+/// use debug_monitor::server::DebuggableServer;
+///
+/// let server = ScopedServer::new();
+/// let mut x = DebuggableBuilder::new("x", 0).scoped(&server).build();
+/// let mut y = DebuggableBuilder::new("y", 0).scoped(&server).build();
+/// let batch = DebuggableServer::begin_batch(&server.handle());
+/// *x = 1;
+/// *y = 2;
+/// assert_eq!((*x, *y), (1, 2));
+/// drop(batch);
+/// ```
+pub struct NotifyBatch {
+    server: Arc<RwLock<DebuggableServer>>,
+}
+
+impl NotifyBatch {
+    pub(crate) fn new(server: Arc<RwLock<DebuggableServer>>) -> Self {
+        Self { server }
+    }
+
+    /// Sends what was held back so far, the batch keeps holding back later notifications.
+    pub fn flush(&self) {
+        if let Ok(server) = self.server.read() {
+            server.flush_batch();
+        }
+    }
+}
+
+impl Drop for NotifyBatch {
+    fn drop(&mut self) {
+        if let Ok(server) = self.server.read() {
+            server.end_batch();
+        }
+    }
+}
+
+impl Debug for NotifyBatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotifyBatch").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{ErrorKind, Write};
+use std::mem;
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::protocol::framing::{self, Endmark};
+use crate::server::stats::StatsCounters;
+
+const WRITER_SOCKET_TIMEOUT: Duration = Duration::from_millis(1);
+const WRITER_IDLE_WAIT: Duration = Duration::from_millis(50);
+const WRITER_BUSY_WAIT: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    DropOldest,
+    DropClient,
+}
+
+#[derive(Debug)]
+struct ClientQueue {
+    stream: Arc<TcpStream>,
+    messages: VecDeque<Arc<[u8]>>,
+    written_of_front: usize,
+    // Frames at the front the writer is writing with the lock released, which overflow can't drop
+    in_flight: usize,
+}
+
+#[derive(Debug)]
+struct OutgoingShared {
+    queues: Mutex<HashMap<usize, ClientQueue>>,
+    has_messages: Condvar,
+    closed: AtomicBool,
+    dropped_clients: Mutex<Vec<usize>>,
+}
+
+impl OutgoingShared {
+    fn drop_client(&self, client_index: usize, queue: ClientQueue, reason: &str, stats: &StatsCounters) {
+        log::warn!("Dropping client {client_index}: {reason}");
+        let _ = queue.stream.shutdown(Shutdown::Both);
+        stats.dropped_clients.fetch_add(1, Ordering::Relaxed);
+        self.dropped_clients.lock().unwrap().push(client_index);
+    }
+}
+
+/// Frames of a client the writer took to write while the queues aren't locked.
+struct WriteBatch {
+    client_index: usize,
+    stream: Arc<TcpStream>,
+    frames: Vec<Arc<[u8]>>,
+    written_of_front: usize,
+}
+
+struct WriteProgress {
+    written_frames: usize,
+    written_of_front: usize,
+    failed: bool,
+}
+
+impl WriteBatch {
+    /// Writes as much as the socket takes without waiting longer than WRITER_SOCKET_TIMEOUT.
+    fn write(&self) -> WriteProgress {
+        let mut stream: &TcpStream = &self.stream;
+        let mut progress = WriteProgress { written_frames: 0, written_of_front: self.written_of_front, failed: false };
+        for frame in &self.frames {
+            while progress.written_of_front < frame.len() {
+                match stream.write(&frame[progress.written_of_front..]) {
+                    Ok(0) => {
+                        progress.failed = true;
+                        return progress;
+                    }
+                    Ok(written) => progress.written_of_front += written,
+                    Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                    Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return progress,
+                    Err(_) => {
+                        progress.failed = true;
+                        return progress;
+                    }
+                }
+            }
+            progress.written_frames += 1;
+            progress.written_of_front = 0;
+        }
+        progress
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct OutgoingQueues {
+    shared: Arc<OutgoingShared>,
+    max_depth: usize,
+    policy: OverflowPolicy,
+    endmark: Endmark,
+    stats: Arc<StatsCounters>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl OutgoingQueues {
+    pub(crate) fn start(max_depth: usize, policy: OverflowPolicy, endmark: Endmark, stats: Arc<StatsCounters>) -> Self {
+        let shared = Arc::new(OutgoingShared {
+            queues: Mutex::new(HashMap::new()),
+            has_messages: Condvar::new(),
+            closed: AtomicBool::new(false),
+            dropped_clients: Mutex::new(Vec::new()),
+        });
+        let writer_shared = shared.clone();
+        let writer_stats = stats.clone();
+        let writer = thread::spawn(move || Self::writer_loop(writer_shared, writer_stats));
+        Self { shared, max_depth: max_depth.max(1), policy, endmark, stats, writer: Some(writer) }
+    }
+
+    pub(crate) fn register_client(&self, client_index: usize, stream: TcpStream) {
+        let _ = stream.set_write_timeout(Some(WRITER_SOCKET_TIMEOUT));
+        self.shared.queues.lock().unwrap().insert(client_index, ClientQueue {
+            stream: Arc::new(stream),
+            messages: VecDeque::new(),
+            written_of_front: 0,
+            in_flight: 0,
+        });
+    }
+
+    pub(crate) fn unregister_client(&self, client_index: usize) {
+        self.shared.queues.lock().unwrap().remove(&client_index);
+    }
+
+    pub(crate) fn is_registered(&self, client_index: usize) -> bool {
+        self.shared.queues.lock().unwrap().contains_key(&client_index)
+    }
+
+    /// Clients dropped since the last call, for the server to report and forget.
+    pub(crate) fn take_dropped_clients(&self) -> Vec<usize> {
+        mem::take(&mut *self.shared.dropped_clients.lock().unwrap())
+    }
+
+    /// Bytes of the frames not yet written, frames shared by several clients count once per client.
+    pub(crate) fn queued_bytes(&self) -> usize {
+        self.shared.queues.lock().unwrap().values()
+            .map(|queue| queue.messages.iter().map(|frame| frame.len()).sum::<usize>() - queue.written_of_front)
+            .sum()
+    }
+
+    pub(crate) fn enqueue(&self, clients: &[usize], message: &str) {
+        // Every client queue shares the same frame
+        let frame: Arc<[u8]> = Arc::from(framing::encode_frame(message, &self.endmark).into_bytes());
+        let mut queues = self.shared.queues.lock().unwrap();
+        for client_index in clients {
+            let is_overflowing = match queues.get(client_index) {
+                None => continue,
+                Some(queue) => queue.messages.len() >= self.max_depth,
+            };
+            if is_overflowing && self.policy == OverflowPolicy::DropClient {
+                let queue = queues.remove(client_index).unwrap();
+                self.shared.drop_client(*client_index, queue, "its outgoing queue overflowed", &self.stats);
+                continue;
+            }
+            let queue = queues.get_mut(client_index).unwrap();
+            if is_overflowing {
+                // Frames being written or partially written can't be dropped without corrupting the stream
+                let first_droppable = queue.in_flight.max((queue.written_of_front > 0) as usize);
+                if queue.messages.remove(first_droppable).is_some() {
+                    self.stats.dropped_messages.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            queue.messages.push_back(frame.clone());
+        }
+        drop(queues);
+        self.shared.has_messages.notify_one();
+    }
+
+    /// Waits up to the timeout for the queued frames to be written, then stops the writer and waits
+    /// for it to exit, so nothing else is writing to the sockets once this returns.
+    pub(crate) fn close(mut self, timeout: Duration) {
+        let give_up_at = Instant::now() + timeout;
+        while Instant::now() < give_up_at && self.shared.queues.lock().unwrap().values().any(|queue| !queue.messages.is_empty()) {
+            thread::sleep(WRITER_BUSY_WAIT);
+        }
+        self.shared.closed.store(true, Ordering::Relaxed);
+        self.shared.has_messages.notify_one();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+
+    /// Sockets are only written with the queues unlocked, so enqueueing never waits on a client.
+    fn writer_loop(shared: Arc<OutgoingShared>, stats: Arc<StatsCounters>) {
+        while !shared.closed.load(Ordering::Relaxed) {
+            let batches = Self::take_batches(&shared);
+            let mut has_written = false;
+            for batch in batches {
+                let progress = batch.write();
+                has_written |= progress.written_frames > 0 || progress.written_of_front != batch.written_of_front;
+                Self::finish_batch(&shared, batch, progress, &stats);
+            }
+            let queues = shared.queues.lock().unwrap();
+            let has_pending = queues.values().any(|queue| !queue.messages.is_empty());
+            if has_pending && has_written { continue; }
+            let wait = if has_pending { WRITER_BUSY_WAIT } else { WRITER_IDLE_WAIT };
+            let _ = shared.has_messages.wait_timeout(queues, wait).unwrap();
+        }
+    }
+
+    fn take_batches(shared: &OutgoingShared) -> Vec<WriteBatch> {
+        let mut queues = shared.queues.lock().unwrap();
+        queues.iter_mut()
+            .filter(|(_, queue)| !queue.messages.is_empty())
+            .map(|(client_index, queue)| {
+                queue.in_flight = queue.messages.len();
+                WriteBatch {
+                    client_index: *client_index,
+                    stream: queue.stream.clone(),
+                    frames: queue.messages.iter().cloned().collect(),
+                    written_of_front: queue.written_of_front,
+                }
+            })
+            .collect()
+    }
+
+    fn finish_batch(shared: &OutgoingShared, batch: WriteBatch, progress: WriteProgress, stats: &StatsCounters) {
+        let mut queues = shared.queues.lock().unwrap();
+        // The client may have been dropped or its slot reused meanwhile
+        let Some(queue) = queues.get_mut(&batch.client_index) else { return; };
+        if !Arc::ptr_eq(&queue.stream, &batch.stream) { return; }
+        queue.messages.drain(..progress.written_frames);
+        queue.written_of_front = progress.written_of_front;
+        queue.in_flight = 0;
+        if progress.failed {
+            let queue = queues.remove(&batch.client_index).unwrap();
+            shared.drop_client(batch.client_index, queue, "writing to it failed", stats);
+        }
+    }
+}
+
+impl Drop for OutgoingQueues {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Relaxed);
+        self.shared.has_messages.notify_one();
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::server::DebuggableServer;
+
+pub const DEFAULT_HIGHLIGHT: Duration = Duration::from_secs(1);
+
+/// A visible debuggable as clients see it, for overlays rendered by the host itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlayEntry {
+    pub id: usize,
+    pub name: String,
+    pub value_json: Option<Arc<str>>,
+    /// Whether its value changed within the feed's highlight duration.
+    pub changed_recently: bool,
+}
+
+/// Reads the visible debuggables of a server without connecting to it as a client.
+#[derive(Debug, Clone)]
+pub struct OverlayFeed {
+    server: Arc<RwLock<DebuggableServer>>,
+    highlight: Duration,
+}
+
+impl OverlayFeed {
+    pub fn new(server: Arc<RwLock<DebuggableServer>>) -> Self {
+        Self { server, highlight: DEFAULT_HIGHLIGHT }
+    }
+
+    /// How long entries are reported as changed recently after their value changes.
+    pub fn highlight(mut self, highlight: Duration) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    pub fn entries(&self) -> Vec<OverlayEntry> {
+        let mut entries = Vec::new();
+        self.entries_into(&mut entries);
+        entries
+    }
+
+    /// Like entries, reusing the given entries and their names so rendering each frame doesn't
+    /// allocate once the overlay settles.
+    pub fn entries_into(&self, entries: &mut Vec<OverlayEntry>) {
+        self.server.read().unwrap().fill_overlay_entries(entries, self.highlight);
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use crate::server::Author;
+
+/// Pending updates a debuggable keeps unless changed through
+/// DebuggableServer::set_max_pending_updates, the oldest go first once reached.
+pub const DEFAULT_MAX_PENDING_UPDATES: usize = 64;
+
+/// Where a pending update came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpdateOrigin {
+    /// A client connected through a socket.
+    Socket,
+    /// A transaction file of the read directory.
+    ReadDir,
+    /// An update group released once every update of it could sync together.
+    Group,
+    /// A step of a running animation.
+    Animation,
+    /// The host, through DebuggableServer::queue_update.
+    Host,
+}
+
+/// An update received for a debuggable that it didn't sync yet.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingUpdate {
+    pub(crate) author: Author,
+    pub(crate) request_id: Option<u64>,
+    pub(crate) json: String,
+    pub(crate) received: Instant,
+    pub(crate) origin: UpdateOrigin,
+}
+
+impl PendingUpdate {
+    pub(crate) fn info(&self, now: Instant) -> PendingUpdateInfo {
+        PendingUpdateInfo {
+            client: self.author.client,
+            panel: self.author.panel.clone(),
+            origin: self.origin,
+            waiting: now.saturating_duration_since(self.received),
+            bytes: self.json.len(),
+        }
+    }
+}
+
+/// Diagnostics of a pending update, see DebuggableServer::pending_details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingUpdateInfo {
+    pub client: usize,
+    pub panel: Option<String>,
+    pub origin: UpdateOrigin,
+    /// Time since the server received it.
+    pub waiting: Duration,
+    pub bytes: usize,
+}
+
+/// Queues the update, evicting the oldest ones past the maximum, which are returned.
+pub(crate) fn push_bounded(pending: &mut Vec<PendingUpdate>, update: PendingUpdate, max_pending: Option<usize>) -> Vec<PendingUpdate> {
+    pending.push(update);
+    let Some(max_pending) = max_pending else { return Vec::new(); };
+    let mut evicted = Vec::new();
+    while pending.len() > max_pending.max(1) {
+        let oldest = pending.iter().enumerate().min_by_key(|(_, update)| update.received).map(|(index, _)| index).unwrap();
+        evicted.push(pending.remove(oldest));
+    }
+    evicted
+}
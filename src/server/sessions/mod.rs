@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Sessions the server remembers unless changed through DebuggableServer::set_max_sessions, those
+/// used the longest ago are forgotten first once reached.
+pub const DEFAULT_MAX_SESSIONS: usize = 64;
+/// Bytes the blobs of a session may take together, keys included, unless changed through
+/// DebuggableServer::set_max_session_blob_bytes.
+pub const DEFAULT_MAX_SESSION_BLOB_BYTES: usize = 64 * 1024;
+
+/// What a monitor set up over its connection, kept across reconnections when it gives a session key
+/// in Hello.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClientSession {
+    pub(crate) panel: Option<String>,
+    /// Names of the debuggables the client is sent values of, all of them when None.
+    pub(crate) subscriptions: Option<BTreeSet<String>>,
+    pub(crate) blobs: BTreeMap<String, String>,
+    last_used: u64,
+}
+
+impl ClientSession {
+    fn blob_bytes(&self) -> usize {
+        self.blobs.iter().map(|(key, data)| key.len() + data.len()).sum()
+    }
+}
+
+/// Whether attaching a client to a session restored it, and how many sessions were forgotten to
+/// make room for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Attached {
+    pub(crate) restored: bool,
+    pub(crate) evicted: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Sessions {
+    by_key: HashMap<String, ClientSession>,
+    // Key of the session each connected client said hello with
+    keys_of_clients: HashMap<usize, String>,
+    // Sessions of connected clients that gave no key, forgotten once they disconnect
+    anonymous: HashMap<usize, ClientSession>,
+    max_sessions: usize,
+    max_blob_bytes: usize,
+    next_use: u64,
+}
+
+impl Sessions {
+    pub(crate) fn new() -> Self {
+        Self {
+            by_key: HashMap::new(),
+            keys_of_clients: HashMap::new(),
+            anonymous: HashMap::new(),
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            max_blob_bytes: DEFAULT_MAX_SESSION_BLOB_BYTES,
+            next_use: 0,
+        }
+    }
+
+    /// Attaches the client to the session of the key, starting a new one when the key is unknown or
+    /// none is given. Sessions unused the longest are evicted past max_sessions, except those of
+    /// connected clients.
+    pub(crate) fn attach(&mut self, client: usize, key: Option<String>) -> Attached {
+        self.detach(client);
+        let Some(key) = key else {
+            self.anonymous.insert(client, ClientSession::default());
+            return Attached { restored: false, evicted: 0 };
+        };
+        let restored = self.by_key.contains_key(&key);
+        self.by_key.entry(key.clone()).or_default();
+        self.keys_of_clients.insert(client, key);
+        self.touch(client);
+        Attached { restored, evicted: self.evict_over_limit() }
+    }
+
+    pub(crate) fn detach(&mut self, client: usize) {
+        self.anonymous.remove(&client);
+        self.keys_of_clients.remove(&client);
+    }
+
+    pub(crate) fn of_client(&self, client: usize) -> Option<&ClientSession> {
+        match self.keys_of_clients.get(&client) {
+            None => self.anonymous.get(&client),
+            Some(key) => self.by_key.get(key),
+        }
+    }
+
+    /// Session of the client, marked as just used.
+    pub(crate) fn of_client_mut(&mut self, client: usize) -> Option<&mut ClientSession> {
+        self.touch(client);
+        match self.keys_of_clients.get(&client) {
+            None => self.anonymous.get_mut(&client),
+            Some(key) => self.by_key.get_mut(key),
+        }
+    }
+
+    pub(crate) fn is_subscribed(&self, client: usize, debuggable_name: &str) -> bool {
+        self.of_client(client)
+            .and_then(|session| session.subscriptions.as_ref())
+            .map_or(true, |subscriptions| subscriptions.contains(debuggable_name))
+    }
+
+    pub(crate) fn has_subscriptions(&self) -> bool {
+        self.keys_of_clients.keys().chain(self.anonymous.keys())
+            .any(|client| self.of_client(*client).is_some_and(|session| session.subscriptions.is_some()))
+    }
+
+    /// Stores the blob in the session of the client, failing if the session's blobs would take
+    /// more than max_blob_bytes with it.
+    pub(crate) fn store_blob(&mut self, client: usize, key: String, data: String) -> Result<(), String> {
+        let max_blob_bytes = self.max_blob_bytes;
+        let Some(session) = self.of_client_mut(client) else {
+            return Err(format!("Blob {key} can't be stored before saying hello"));
+        };
+        let replaced_bytes = session.blobs.get(&key).map_or(0, |replaced| key.len() + replaced.len());
+        let stored_bytes = session.blob_bytes() - replaced_bytes + key.len() + data.len();
+        if stored_bytes > max_blob_bytes {
+            return Err(format!("Blob {key} of {} bytes doesn't fit in the {max_blob_bytes} bytes of the session", data.len()));
+        }
+        session.blobs.insert(key, data);
+        Ok(())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    /// Returns how many sessions were evicted to fit within the new maximum.
+    pub(crate) fn set_max_sessions(&mut self, max_sessions: usize) -> usize {
+        self.max_sessions = max_sessions;
+        self.evict_over_limit()
+    }
+
+    pub(crate) fn set_max_blob_bytes(&mut self, max_blob_bytes: usize) {
+        self.max_blob_bytes = max_blob_bytes;
+    }
+
+    fn touch(&mut self, client: usize) {
+        let Some(session) = self.keys_of_clients.get(&client).and_then(|key| self.by_key.get_mut(key)) else { return; };
+        session.last_used = self.next_use;
+        self.next_use += 1;
+    }
+
+    fn evict_over_limit(&mut self) -> usize {
+        let mut evicted = 0;
+        while self.by_key.len() > self.max_sessions {
+            let in_use = self.keys_of_clients.values().collect::<BTreeSet<_>>();
+            let Some(least_recent) = self.by_key.iter()
+                .filter(|(key, _)| !in_use.contains(key))
+                .min_by_key(|(_, session)| session.last_used)
+                .map(|(key, _)| key.clone()) else { break; };
+            self.by_key.remove(&least_recent);
+            evicted += 1;
+        }
+        evicted
+    }
+}
@@ -0,0 +1,50 @@
+use std::io;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::ops::Range;
+use std::time::Duration;
+
+use socket2::{Domain, Socket, Type};
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientSocketOptions {
+    pub tcp_nodelay: Option<bool>,
+    pub read_timeout: Option<Duration>,
+    pub write_timeout: Option<Duration>,
+}
+
+impl ClientSocketOptions {
+    pub fn apply(&self, stream: &TcpStream) -> io::Result<()> {
+        if let Some(tcp_nodelay) = self.tcp_nodelay {
+            stream.set_nodelay(tcp_nodelay)?;
+        }
+        stream.set_read_timeout(self.read_timeout)?;
+        stream.set_write_timeout(self.write_timeout)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn bind_listener(address: SocketAddr, reuse_addr: bool) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(address), Type::STREAM, None)?;
+    socket.set_reuse_address(reuse_addr)?;
+    socket.bind(&address.into())?;
+    socket.listen(128)?;
+    Ok(socket.into())
+}
+
+/// Binds the address or, if its port is taken and among the fallback ports, the first free one
+/// of those following it.
+pub(crate) fn bind_listener_with_fallback(address: SocketAddr, reuse_addr: bool, fallback_ports: Range<u16>) -> io::Result<TcpListener> {
+    let error = match bind_listener(address, reuse_addr) {
+        Err(error) if error.kind() == ErrorKind::AddrInUse && fallback_ports.contains(&address.port()) => error,
+        bound => return bound,
+    };
+    for port in address.port() + 1..fallback_ports.end {
+        let fallback_address = SocketAddr::new(address.ip(), port);
+        if let Ok(tcp_listener) = bind_listener(fallback_address, reuse_addr) {
+            log::info!("Port {} is taken, debuggable server listening on {} instead", address.port(), fallback_address);
+            return Ok(tcp_listener);
+        }
+    }
+    Err(error)
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Weight of the previous average against a new latency, out of LATENCY_AVERAGE_WEIGHT + 1
+const LATENCY_AVERAGE_WEIGHT: u64 = 7;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerStats {
+    pub dropped_messages: u64,
+    pub dropped_clients: u64,
+    pub rejected_connections: u64,
+    pub rejected_messages: u64,
+    pub clients_struck_out: u64,
+    pub rejected_by_transform: u64,
+    /// Stages of client polling left for the next sync once a sync ran out of budget.
+    pub deferred_poll_stages: u64,
+    /// Read directory transactions left for the next sync once a sync ran out of budget.
+    pub deferred_dir_transactions: u64,
+    /// Outgoing messages dropped by the FaultInjector, counted once per client.
+    pub injected_drops: u64,
+    /// Incoming messages the FaultInjector had processed twice.
+    pub injected_duplicates: u64,
+    /// Accept cycles the FaultInjector skipped.
+    pub injected_accept_skips: u64,
+    /// Reads of the read directory the FaultInjector delayed.
+    pub injected_dir_delays: u64,
+    /// Updates from clients or the host that their debuggable took to sync.
+    pub synced_updates: u64,
+    /// Rolling average of how long updates waited from being received until their debuggable
+    /// synced them, weighting recent updates the most.
+    pub update_latency_average: Duration,
+    pub update_latency_max: Duration,
+    /// Sessions forgotten to stay within the server's max_sessions.
+    pub evicted_sessions: u64,
+    /// Blobs clients tried to store that didn't fit in their session.
+    pub rejected_blobs: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters {
+    pub(crate) dropped_messages: AtomicU64,
+    pub(crate) dropped_clients: AtomicU64,
+    pub(crate) rejected_connections: AtomicU64,
+    pub(crate) rejected_messages: AtomicU64,
+    pub(crate) clients_struck_out: AtomicU64,
+    pub(crate) rejected_by_transform: AtomicU64,
+    pub(crate) deferred_poll_stages: AtomicU64,
+    pub(crate) deferred_dir_transactions: AtomicU64,
+    pub(crate) injected_drops: AtomicU64,
+    pub(crate) injected_duplicates: AtomicU64,
+    pub(crate) injected_accept_skips: AtomicU64,
+    pub(crate) injected_dir_delays: AtomicU64,
+    pub(crate) synced_updates: AtomicU64,
+    pub(crate) update_latency_average_micros: AtomicU64,
+    pub(crate) update_latency_max_micros: AtomicU64,
+    pub(crate) evicted_sessions: AtomicU64,
+    pub(crate) rejected_blobs: AtomicU64,
+}
+
+impl StatsCounters {
+    pub(crate) fn snapshot(&self) -> ServerStats {
+        ServerStats {
+            dropped_messages: self.dropped_messages.load(Ordering::Relaxed),
+            dropped_clients: self.dropped_clients.load(Ordering::Relaxed),
+            rejected_connections: self.rejected_connections.load(Ordering::Relaxed),
+            rejected_messages: self.rejected_messages.load(Ordering::Relaxed),
+            clients_struck_out: self.clients_struck_out.load(Ordering::Relaxed),
+            rejected_by_transform: self.rejected_by_transform.load(Ordering::Relaxed),
+            deferred_poll_stages: self.deferred_poll_stages.load(Ordering::Relaxed),
+            deferred_dir_transactions: self.deferred_dir_transactions.load(Ordering::Relaxed),
+            injected_drops: self.injected_drops.load(Ordering::Relaxed),
+            injected_duplicates: self.injected_duplicates.load(Ordering::Relaxed),
+            injected_accept_skips: self.injected_accept_skips.load(Ordering::Relaxed),
+            injected_dir_delays: self.injected_dir_delays.load(Ordering::Relaxed),
+            synced_updates: self.synced_updates.load(Ordering::Relaxed),
+            update_latency_average: Duration::from_micros(self.update_latency_average_micros.load(Ordering::Relaxed)),
+            update_latency_max: Duration::from_micros(self.update_latency_max_micros.load(Ordering::Relaxed)),
+            evicted_sessions: self.evicted_sessions.load(Ordering::Relaxed),
+            rejected_blobs: self.rejected_blobs.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records how long a synced update waited since it was received.
+    pub(crate) fn record_update_latency(&self, latency: Duration) {
+        let latency = latency.as_micros().min(u64::MAX as u128) as u64;
+        let is_first = self.synced_updates.fetch_add(1, Ordering::Relaxed) == 0;
+        let _ = self.update_latency_average_micros.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+            if is_first { return Some(latency); }
+            Some(((average as u128 * LATENCY_AVERAGE_WEIGHT as u128 + latency as u128) / (LATENCY_AVERAGE_WEIGHT as u128 + 1)) as u64)
+        });
+        self.update_latency_max_micros.fetch_max(latency, Ordering::Relaxed);
+    }
+}
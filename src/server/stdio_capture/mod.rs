@@ -0,0 +1,142 @@
+//! Mirrors what the process writes to its stdout into a debuggable, by pointing file descriptor
+//! 1 at a pipe whose reader forwards every byte to the original stdout.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::os::fd::{FromRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+
+use crate::snapshot;
+
+pub const STDOUT_DEBUGGABLE_NAME: &str = "stdout";
+
+const STDOUT_FD: RawFd = 1;
+
+#[derive(Debug, Default)]
+struct CapturedLines {
+    lines: VecDeque<String>,
+    capacity: usize,
+    // Bytes read after the last newline, completed by the next read
+    partial_line: Vec<u8>,
+    has_changed: bool,
+}
+
+impl CapturedLines {
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while let Some(newline) = rest.iter().position(|byte| *byte == b'\n') {
+            self.partial_line.extend_from_slice(&rest[..newline]);
+            self.finish_line();
+            rest = &rest[newline + 1..];
+        }
+        self.partial_line.extend_from_slice(rest);
+    }
+
+    fn finish_line(&mut self) {
+        let line = String::from_utf8_lossy(&self.partial_line).trim_end_matches('\r').to_string();
+        self.partial_line.clear();
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.has_changed = true;
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct StdoutCapture {
+    // Follows the debuggable when compaction moves it
+    debuggable_id: Arc<AtomicUsize>,
+    original_stdout: RawFd,
+    lines: Arc<Mutex<CapturedLines>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl StdoutCapture {
+    pub(crate) fn start(debuggable_id: Arc<AtomicUsize>, capacity: usize) -> io::Result<Self> {
+        io::stdout().flush()?;
+        let original_stdout = check(unsafe { libc::dup(STDOUT_FD) })?;
+        let mut pipe_fds = [0; 2];
+        if let Err(error) = check(unsafe { libc::pipe(pipe_fds.as_mut_ptr()) }) {
+            unsafe { libc::close(original_stdout); }
+            return Err(error);
+        }
+        let [read_fd, write_fd] = pipe_fds;
+        let forward_fd = unsafe { libc::dup(original_stdout) };
+        let redirected = check(forward_fd).and_then(|_| check(unsafe { libc::dup2(write_fd, STDOUT_FD) }));
+        unsafe { libc::close(write_fd); }
+        if let Err(error) = redirected {
+            unsafe {
+                libc::close(read_fd);
+                libc::close(original_stdout);
+                if forward_fd >= 0 { libc::close(forward_fd); }
+            }
+            return Err(error);
+        }
+        let lines = Arc::new(Mutex::new(CapturedLines { capacity: capacity.max(1), ..Default::default() }));
+        let (pipe, forward) = unsafe { (File::from_raw_fd(read_fd), File::from_raw_fd(forward_fd)) };
+        let reader_lines = lines.clone();
+        let reader = thread::spawn(move || forward_and_capture(pipe, forward, reader_lines));
+        Ok(Self { debuggable_id, original_stdout, lines, reader: Some(reader) })
+    }
+
+    pub(crate) fn debuggable_id(&self) -> usize {
+        self.debuggable_id.load(Ordering::Relaxed)
+    }
+
+    /// Captured lines as a JSON array if any arrived since the last time.
+    pub(crate) fn take_changed_json(&self) -> Option<String> {
+        let mut lines = self.lines.lock().unwrap();
+        if !lines.has_changed { return None; }
+        lines.has_changed = false;
+        Some(format!("[{}]", lines.lines.iter().map(|line| snapshot::quoted(line)).collect::<Vec<_>>().join(",")))
+    }
+
+    /// Points stdout back at where it pointed before, which ends the pipe and so the reader.
+    pub(crate) fn release(mut self) -> io::Result<()> {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        let Some(reader) = self.reader.take() else { return Ok(()); };
+        let _ = io::stdout().flush();
+        let restored = check(unsafe { libc::dup2(self.original_stdout, STDOUT_FD) });
+        unsafe { libc::close(self.original_stdout); }
+        restored?;
+        let _ = reader.join();
+        Ok(())
+    }
+}
+
+impl Drop for StdoutCapture {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+fn forward_and_capture(mut pipe: File, mut forward: File, lines: Arc<Mutex<CapturedLines>>) {
+    let mut buffer = [0; 4096];
+    loop {
+        let read = match pipe.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        };
+        let _ = forward.write_all(&buffer[..read]);
+        lines.lock().unwrap().push_bytes(&buffer[..read]);
+    }
+    let mut lines = lines.lock().unwrap();
+    if !lines.partial_line.is_empty() {
+        lines.finish_line();
+    }
+}
+
+fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result < 0 { Err(io::Error::last_os_error()) } else { Ok(result) }
+}
@@ -0,0 +1,183 @@
+use std::io;
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::ops::DerefMut;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rustls::{Certificate, ClientConfig, ClientConnection, ConnectionCommon, PrivateKey, RootCertStore, ServerConfig, ServerConnection, ServerName};
+use rustls_pemfile::Item;
+
+use crate::server::listeners::{connect_inner, ForwardedPeers};
+
+const PUMP_BUFFER_LEN: usize = 16 * 1024;
+
+#[derive(Clone)]
+pub struct TlsSettings {
+    config: Arc<ServerConfig>,
+}
+
+impl TlsSettings {
+    pub fn from_pem(cert_chain_pem: &str, private_key_pem: &str) -> io::Result<TlsSettings> {
+        let cert_chain = rustls_pemfile::certs(&mut BufReader::new(cert_chain_pem.as_bytes()))?
+            .into_iter()
+            .map(Certificate)
+            .collect::<Vec<_>>();
+        let mut private_key = None;
+        let mut key_reader = BufReader::new(private_key_pem.as_bytes());
+        while let Some(item) = rustls_pemfile::read_one(&mut key_reader)? {
+            match item {
+                Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => {
+                    private_key = Some(PrivateKey(key));
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let private_key = private_key.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No private key found in PEM"))?;
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, private_key)
+            .map_err(|error| io::Error::new(ErrorKind::InvalidInput, error))?;
+        Ok(Self { config: Arc::new(config) })
+    }
+}
+
+/// Terminates TLS for every client accepted by the public listener and forwards its plaintext to
+/// the server listening on the inner address. The server is told the peer each forwarded
+/// connection belongs to, so its allow-list applies to the real peer rather than the terminator.
+pub(crate) fn spawn_tls_terminator(public_listener: TcpListener, settings: TlsSettings, inner_address: SocketAddr, forwarded_peers: ForwardedPeers) {
+    thread::spawn(move || {
+        for incoming in public_listener.incoming() {
+            let tls_stream = match incoming {
+                Ok(tls_stream) => tls_stream,
+                Err(_) => continue,
+            };
+            let config = settings.config.clone();
+            let forwarded_peers = forwarded_peers.clone();
+            thread::spawn(move || {
+                let peer = tls_stream.peer_addr().ok();
+                if let Err(error) = serve_tls_client(tls_stream, config, inner_address, &forwarded_peers) {
+                    log::warn!("TLS connection from {:?} dropped: {}", peer, error);
+                }
+            });
+        }
+    });
+}
+
+fn serve_tls_client(mut tls_stream: TcpStream, config: Arc<ServerConfig>, inner_address: SocketAddr, forwarded_peers: &ForwardedPeers) -> io::Result<()> {
+    let peer = tls_stream.peer_addr()?;
+    let mut connection = ServerConnection::new(config).map_err(|error| io::Error::new(ErrorKind::Other, error))?;
+    while connection.is_handshaking() {
+        connection.complete_io(&mut tls_stream)?;
+    }
+    let plain_stream = connect_inner(inner_address, forwarded_peers, Some(peer))?;
+    pump(connection, tls_stream, plain_stream)
+}
+
+/// Connects to a server using TLS whose certificate chains up to the given root, and bridges the
+/// session to a loopback stream the client reads as usual. The handshake completes before this
+/// returns, so a certificate that doesn't verify fails here.
+pub(crate) fn connect_bridged(address: SocketAddr, root_cert_pem: &str) -> io::Result<TcpStream> {
+    let mut roots = RootCertStore::empty();
+    for root_cert in rustls_pemfile::certs(&mut BufReader::new(root_cert_pem.as_bytes()))? {
+        roots.add(&Certificate(root_cert)).map_err(|error| io::Error::new(ErrorKind::InvalidInput, format!("{error:?}")))?;
+    }
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let mut connection = ClientConnection::new(Arc::new(config), ServerName::IpAddress(address.ip()))
+        .map_err(|error| io::Error::new(ErrorKind::Other, error))?;
+    let mut tls_stream = TcpStream::connect(address)?;
+    while connection.is_handshaking() {
+        connection.complete_io(&mut tls_stream)?;
+    }
+    let bridge = TcpListener::bind("127.0.0.1:0")?;
+    let client_stream = TcpStream::connect(bridge.local_addr()?)?;
+    let (bridge_stream, _) = bridge.accept()?;
+    thread::spawn(move || {
+        if let Err(error) = pump(connection, tls_stream, bridge_stream) {
+            log::warn!("TLS connection to {} dropped: {}", address, error);
+        }
+    });
+    Ok(client_stream)
+}
+
+/// Copies plaintext both ways through the TLS session until either side closes. Each direction
+/// waits on its own socket from its own thread, the session is only locked to encrypt or decrypt.
+fn pump<Connection, Data>(connection: Connection, tls_stream: TcpStream, plain_stream: TcpStream) -> io::Result<()>
+    where Connection: DerefMut<Target = ConnectionCommon<Data>> + Send + 'static {
+    let connection = Arc::new(Mutex::new(connection));
+    let outgoing = {
+        let (connection, tls_writer, plain_reader) = (connection.clone(), tls_stream.try_clone()?, plain_stream.try_clone()?);
+        thread::spawn(move || {
+            let encrypted = encrypt_outgoing(&connection, &tls_writer, plain_reader);
+            let _ = tls_writer.shutdown(Shutdown::Both);
+            encrypted
+        })
+    };
+    let decrypted = decrypt_incoming(&connection, tls_stream, &plain_stream);
+    let _ = plain_stream.shutdown(Shutdown::Both);
+    let encrypted = outgoing.join().unwrap_or(Ok(()));
+    decrypted.and(encrypted)
+}
+
+fn encrypt_outgoing<Connection, Data>(connection: &Mutex<Connection>, mut tls_writer: &TcpStream, mut plain_reader: TcpStream) -> io::Result<()>
+    where Connection: DerefMut<Target = ConnectionCommon<Data>> {
+    let mut buffer = [0_u8; PUMP_BUFFER_LEN];
+    loop {
+        let read = match plain_reader.read(&mut buffer) {
+            Ok(read) => read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            // The other direction closed the plaintext side
+            Err(_) => 0,
+        };
+        let mut connection = connection.lock().unwrap();
+        if read == 0 {
+            connection.send_close_notify();
+        } else {
+            connection.writer().write_all(&buffer[..read])?;
+        }
+        while connection.wants_write() {
+            connection.write_tls(&mut tls_writer)?;
+        }
+        if read == 0 { return Ok(()); }
+    }
+}
+
+fn decrypt_incoming<Connection, Data>(connection: &Mutex<Connection>, mut tls_reader: TcpStream, mut plain_writer: &TcpStream) -> io::Result<()>
+    where Connection: DerefMut<Target = ConnectionCommon<Data>> {
+    let mut buffer = [0_u8; PUMP_BUFFER_LEN];
+    let mut plaintext = Vec::new();
+    loop {
+        let read = match tls_reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        let mut received = &buffer[..read];
+        let mut is_closed = false;
+        {
+            let mut connection = connection.lock().unwrap();
+            while !received.is_empty() {
+                connection.read_tls(&mut received)?;
+                let state = connection.process_new_packets().map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+                let mut reader = connection.reader();
+                let plaintext_len = state.plaintext_bytes_to_read();
+                let start = plaintext.len();
+                plaintext.resize(start + plaintext_len, 0);
+                reader.read_exact(&mut plaintext[start..])?;
+                is_closed |= state.peer_has_closed();
+            }
+            while connection.wants_write() {
+                connection.write_tls(&mut tls_reader)?;
+            }
+        }
+        plain_writer.write_all(&plaintext)?;
+        plaintext.clear();
+        if is_closed { return Ok(()); }
+    }
+}
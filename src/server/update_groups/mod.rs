@@ -0,0 +1,83 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_UPDATE_GROUP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Updates a client sent as a group, held until every debuggable they refer to syncs so they're
+/// released together rather than trickling in on each debuggable's own schedule.
+#[derive(Debug)]
+pub(crate) struct UpdateGroups {
+    pending: Vec<PendingUpdateGroup>,
+    timeout: Duration,
+}
+
+#[derive(Debug)]
+struct PendingUpdateGroup {
+    client: usize,
+    updates: Vec<(usize, String)>,
+    waiting_for: HashSet<usize>,
+    received_at: Instant,
+}
+
+pub(crate) type ReleasedGroup = (usize, Vec<(usize, String)>);
+
+impl UpdateGroups {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self { pending: Vec::new(), timeout }
+    }
+
+    pub(crate) fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub(crate) fn hold(&mut self, client: usize, updates: Vec<(usize, String)>, now: Instant) {
+        let waiting_for = updates.iter().map(|(id, _)| *id).collect();
+        self.pending.push(PendingUpdateGroup { client, updates, waiting_for, received_at: now });
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of updates held and the bytes of their values.
+    pub(crate) fn held_size(&self) -> (usize, usize) {
+        self.pending.iter().flat_map(|group| &group.updates)
+            .fold((0, 0), |(updates, bytes), (_, new_value)| (updates + 1, bytes + new_value.len()))
+    }
+
+    /// Releases the groups for which the given debuggable was the last one left to sync.
+    pub(crate) fn mark_synced(&mut self, debuggable_id: usize) -> Vec<ReleasedGroup> {
+        self.pending.iter_mut().for_each(|group| { group.waiting_for.remove(&debuggable_id); });
+        self.release_where(|group| group.waiting_for.is_empty())
+    }
+
+    /// Releases the groups held for longer than the timeout, their updates are then applied
+    /// independently by each debuggable.
+    pub(crate) fn take_expired(&mut self, now: Instant) -> Vec<ReleasedGroup> {
+        let timeout = self.timeout;
+        self.release_where(|group| now.saturating_duration_since(group.received_at) >= timeout)
+    }
+
+    pub(crate) fn remap_ids(&mut self, remapped_ids: &HashMap<usize, usize>) {
+        let remapped = |id: usize| remapped_ids.get(&id).copied().unwrap_or(id);
+        self.pending.iter_mut().for_each(|group| {
+            group.updates.iter_mut().for_each(|(id, _)| *id = remapped(*id));
+            group.waiting_for = group.waiting_for.iter().map(|id| remapped(*id)).collect();
+        });
+    }
+
+    /// Drops the debuggable from the groups waiting for it, as it will never sync again.
+    pub(crate) fn forget_debuggable(&mut self, debuggable_id: usize) {
+        self.pending.iter_mut().for_each(|group| {
+            group.waiting_for.remove(&debuggable_id);
+            group.updates.retain(|(id, _)| *id != debuggable_id);
+        });
+        self.pending.retain(|group| !group.updates.is_empty());
+    }
+
+    fn release_where<Releases: Fn(&PendingUpdateGroup) -> bool>(&mut self, releases: Releases) -> Vec<ReleasedGroup> {
+        let (released, pending) = std::mem::take(&mut self.pending).into_iter().partition::<Vec<_>, _>(|group| releases(group));
+        self.pending = pending;
+        released.into_iter().map(|group| (group.client, group.updates)).collect()
+    }
+}
@@ -0,0 +1,249 @@
+//! Snapshots are JSON objects from the names of debuggables to their values, as produced by
+//! DebuggableServer::snapshot.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "use_nanoserde")]
+use nanoserde::{DeJson, SerJson};
+#[cfg(feature = "use_serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ChangedValue>,
+}
+
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedValue {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+    /// Changed fields when both values are objects, only filled in with the serde feature.
+    pub fields: Vec<FieldChange>,
+}
+
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_nanoserde", derive(SerJson, DeJson))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Names of the nested fields leading to this one, separated by dots.
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    NotAnObject,
+    Malformed { reason: String },
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::NotAnObject => write!(f, "A snapshot must be a JSON object"),
+            SnapshotError::Malformed { reason } => write!(f, "Malformed snapshot: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for SnapshotDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for name in &self.added {
+            writeln!(f, "+ {name}")?;
+        }
+        for name in &self.removed {
+            writeln!(f, "- {name}")?;
+        }
+        for changed in &self.changed {
+            writeln!(f, "~ {}: {} -> {}", changed.name, changed.before, changed.after)?;
+            for field in &changed.fields {
+                let before = field.before.as_deref().unwrap_or("(none)");
+                let after = field.after.as_deref().unwrap_or("(none)");
+                writeln!(f, "    ~ {}: {before} -> {after}", field.path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn diff(before: &str, after: &str) -> Result<SnapshotDiff, SnapshotError> {
+    let before = parse(before)?;
+    let after = parse(after)?;
+    let mut diff = SnapshotDiff::default();
+    for (name, before_value) in &before {
+        match after.get(name) {
+            None => diff.removed.push(name.clone()),
+            Some(after_value) if !is_same_value(before_value, after_value) => diff.changed.push(ChangedValue {
+                name: name.clone(),
+                before: before_value.clone(),
+                after: after_value.clone(),
+                fields: field_changes(before_value, after_value),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.added = after.keys().filter(|name| !before.contains_key(*name)).cloned().collect();
+    Ok(diff)
+}
+
+pub(crate) fn of_entries<Entries: IntoIterator<Item=(String, String)>>(entries: Entries) -> String {
+    let entries = entries.into_iter()
+        .map(|(name, value)| format!("{}:{value}", quoted(&name)))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", entries.join(","))
+}
+
+fn parse(snapshot: &str) -> Result<BTreeMap<String, String>, SnapshotError> {
+    let body = snapshot.trim().strip_prefix('{').and_then(|body| body.strip_suffix('}'))
+        .ok_or(SnapshotError::NotAnObject)?;
+    let mut entries = BTreeMap::new();
+    let mut rest = body.trim_start();
+    while !rest.is_empty() {
+        let (name, after_name) = parse_string(rest)?;
+        let after_colon = after_name.trim_start().strip_prefix(':')
+            .ok_or_else(|| SnapshotError::Malformed { reason: format!("Expected ':' after {name}") })?;
+        let value_end = value_end_of(after_colon);
+        let value = after_colon[..value_end].trim();
+        if value.is_empty() {
+            return Err(SnapshotError::Malformed { reason: format!("Missing value of {name}") });
+        }
+        entries.insert(name, value.to_string());
+        rest = after_colon[value_end..].strip_prefix(',').unwrap_or_default().trim_start();
+    }
+    Ok(entries)
+}
+
+/// Reads the JSON string the input starts with, returning it unescaped along with what follows it.
+fn parse_string(input: &str) -> Result<(String, &str), SnapshotError> {
+    let malformed = |reason: &str| SnapshotError::Malformed { reason: reason.to_string() };
+    let contents = input.strip_prefix('"').ok_or_else(|| malformed("Expected the name of a debuggable"))?;
+    let mut unescaped = String::new();
+    let mut characters = contents.char_indices();
+    while let Some((position, character)) = characters.next() {
+        match character {
+            '"' => return Ok((unescaped, &contents[position + 1..])),
+            '\\' => match characters.next().map(|(_, escaped)| escaped) {
+                Some('n') => unescaped.push('\n'),
+                Some('t') => unescaped.push('\t'),
+                Some('r') => unescaped.push('\r'),
+                Some('b') => unescaped.push('\u{8}'),
+                Some('f') => unescaped.push('\u{c}'),
+                Some('u') => {
+                    let hex = (0..4).filter_map(|_| characters.next().map(|(_, digit)| digit)).collect::<String>();
+                    let code = u32::from_str_radix(&hex, 16).map_err(|_| malformed("Invalid unicode escape"))?;
+                    unescaped.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                Some(escaped) => unescaped.push(escaped),
+                None => break,
+            },
+            _ => unescaped.push(character),
+        }
+    }
+    Err(malformed("Unterminated name"))
+}
+
+/// Position of the comma ending the JSON value the input starts with, or its length if it's the
+/// last one.
+fn value_end_of(input: &str) -> usize {
+    let (mut depth, mut in_string, mut is_escaped) = (0_usize, false, false);
+    for (position, character) in input.char_indices() {
+        if in_string {
+            match character {
+                _ if is_escaped => is_escaped = false,
+                '\\' => is_escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match character {
+            '"' => in_string = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => return position,
+            _ => {}
+        }
+    }
+    input.len()
+}
+
+pub(crate) fn quoted(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            character if character.is_control() => quoted.push_str(&format!("\\u{:04x}", character as u32)),
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(feature = "use_serde")]
+fn is_same_value(before: &str, after: &str) -> bool {
+    match (serde_json::from_str::<serde_json::Value>(before), serde_json::from_str::<serde_json::Value>(after)) {
+        (Ok(before), Ok(after)) => before == after,
+        _ => before == after,
+    }
+}
+
+#[cfg(not(feature = "use_serde"))]
+fn is_same_value(before: &str, after: &str) -> bool {
+    before == after
+}
+
+#[cfg(feature = "use_serde")]
+fn field_changes(before: &str, after: &str) -> Vec<FieldChange> {
+    use serde_json::Value;
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) = (serde_json::from_str(before), serde_json::from_str(after)) else {
+        return Vec::new();
+    };
+    let mut changes = Vec::new();
+    collect_field_changes("", &before, &after, &mut changes);
+    changes
+}
+
+#[cfg(feature = "use_serde")]
+fn collect_field_changes(prefix: &str, before: &serde_json::Map<String, serde_json::Value>, after: &serde_json::Map<String, serde_json::Value>, changes: &mut Vec<FieldChange>) {
+    use serde_json::Value;
+    let only_after = after.keys().filter(|field| !before.contains_key(*field));
+    for field in before.keys().chain(only_after) {
+        let path = if prefix.is_empty() { field.clone() } else { format!("{prefix}.{field}") };
+        match (before.get(field), after.get(field)) {
+            (Some(Value::Object(before)), Some(Value::Object(after))) => collect_field_changes(&path, before, after, changes),
+            (before, after) if before != after => changes.push(FieldChange {
+                path,
+                before: before.map(Value::to_string),
+                after: after.map(Value::to_string),
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(not(feature = "use_serde"))]
+fn field_changes(_before: &str, _after: &str) -> Vec<FieldChange> {
+    Vec::new()
+}
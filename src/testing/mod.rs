@@ -0,0 +1,141 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::client::{DebuggableClient, MessageFraming};
+use crate::clock::Clock;
+use crate::scoped_server::ScopedServer;
+use crate::serializable::ServerMessage;
+use crate::server::{DebuggableServer, PollStage};
+use crate::server::debuggable_server_builder::DebuggableServerBuilder;
+
+/// Clock that only moves when told to, so time-based behaviour can be exercised without sleeping.
+#[derive(Debug)]
+pub struct ManualClock {
+    elapsed: Mutex<Duration>,
+    start_instant: Instant,
+    start_system: SystemTime,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self { elapsed: Mutex::new(Duration::ZERO), start_instant: Instant::now(), start_system: SystemTime::now() }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+
+    fn now_system(&self) -> SystemTime {
+        self.start_system + self.elapsed()
+    }
+}
+
+/// How long StepServer and poll_client_until keep retrying before giving up.
+pub const STEP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Server that only polls its clients when told to, so tests can script the order in which clients
+/// connect, their messages are read and debuggables sync. Debuggables still sync on access.
+pub struct StepServer {
+    scoped_server: ScopedServer,
+}
+
+impl StepServer {
+    pub fn new() -> Self {
+        Self::from_scoped_server(ScopedServer::new())
+    }
+
+    pub fn from_builder(builder: DebuggableServerBuilder) -> Self {
+        Self::from_scoped_server(ScopedServer::from_builder(builder))
+    }
+
+    fn from_scoped_server(scoped_server: ScopedServer) -> Self {
+        scoped_server.handle().read().unwrap().set_polls_manually(true);
+        Self { scoped_server }
+    }
+
+    pub fn scoped_server(&self) -> &ScopedServer {
+        &self.scoped_server
+    }
+
+    pub fn handle(&self) -> Arc<RwLock<DebuggableServer>> {
+        self.scoped_server.handle()
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.scoped_server.addr()
+    }
+
+    pub fn framing(&self) -> MessageFraming {
+        MessageFraming::of_server(&self.handle().read().unwrap())
+    }
+
+    pub fn connect(&self) -> io::Result<DebuggableClient> {
+        DebuggableClient::connect(self.addr(), self.framing())
+    }
+
+    /// Accepts connections until the server has the given number of clients.
+    pub fn accept_until(&self, client_count: usize) -> bool {
+        self.step_until(PollStage::Accept, |server| server.client_count() >= client_count)
+    }
+
+    /// Reads the messages clients sent until the condition holds.
+    pub fn read_until<Done: Fn(&DebuggableServer) -> bool>(&self, done: Done) -> bool {
+        self.step_until(PollStage::ReadSockets, done)
+    }
+
+    /// Expires, releases and compacts whatever is due.
+    pub fn housekeeping(&self) {
+        self.handle().read().unwrap().run_poll_stage(PollStage::Housekeeping, None);
+    }
+
+    fn step_until<Done: Fn(&DebuggableServer) -> bool>(&self, stage: PollStage, done: Done) -> bool {
+        let give_up_at = Instant::now() + STEP_TIMEOUT;
+        let handle = self.handle();
+        loop {
+            let server = handle.read().unwrap();
+            server.run_poll_stage(stage, None);
+            if done(&server) { return true; }
+            drop(server);
+            if Instant::now() >= give_up_at { return false; }
+            thread::yield_now();
+        }
+    }
+}
+
+impl Default for StepServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Polls the client until the condition holds, returning every message received meanwhile or
+/// None if it didn't hold in time.
+pub fn poll_client_until<Done: FnMut(&DebuggableClient, &[ServerMessage]) -> bool>(client: &mut DebuggableClient, mut done: Done) -> Option<Vec<ServerMessage>> {
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    let mut received = Vec::new();
+    loop {
+        received.extend(client.poll().ok()?);
+        if done(client, &received) { return Some(received); }
+        if Instant::now() >= give_up_at { return None; }
+        thread::yield_now();
+    }
+}
@@ -0,0 +1,143 @@
+use std::io;
+use std::io::Stdout;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::Terminal;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+
+use crate::client::{DebuggableClient, MessageFraming};
+use crate::serializable::ServerMessage;
+use crate::tui::model::{Key, Mode, MonitorModel, Outcome};
+
+pub mod model;
+
+/// How often the server is polled for changes while no key is pressed.
+const TICK: Duration = Duration::from_millis(250);
+/// The Changed column counts whole seconds, so ticks without changes redraw once per second.
+const AGE_RESOLUTION: Duration = Duration::from_secs(1);
+
+/// Monitors the server at the address, which frames messages as servers do by default.
+pub fn run(address: SocketAddr) -> io::Result<()> {
+    run_with_framing(address, MessageFraming::default())
+}
+
+pub fn run_with_framing(address: SocketAddr, framing: MessageFraming) -> io::Result<()> {
+    let mut client = DebuggableClient::connect(address, framing)?;
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let result = event_loop(&mut terminal, &mut client);
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    result
+}
+
+fn event_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>, client: &mut DebuggableClient) -> io::Result<()> {
+    let mut model = MonitorModel::default();
+    let mut table_state = TableState::default();
+    let mut needs_redraw = true;
+    let mut last_draw = Instant::now();
+    let mut next_tick = Instant::now();
+    loop {
+        if needs_redraw {
+            table_state.select(Some(model.selected()));
+            terminal.draw(|frame| draw(frame, &model, &mut table_state))?;
+            needs_redraw = false;
+            last_draw = Instant::now();
+        }
+        if !crossterm::event::poll(next_tick.saturating_duration_since(Instant::now()))? {
+            next_tick = Instant::now() + TICK;
+            let messages = client.poll()?;
+            needs_redraw = !messages.is_empty() || (!model.visible_rows().is_empty() && last_draw.elapsed() >= AGE_RESOLUTION);
+            messages.into_iter().for_each(|message| apply_server_message(&mut model, client, message));
+            continue;
+        }
+        let key = match crossterm::event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key_of(key.code),
+            Event::Resize(_, _) => {
+                needs_redraw = true;
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(key) = key else { continue; };
+        needs_redraw = true;
+        match model.handle_key(key) {
+            Outcome::None => {}
+            Outcome::Send(message) => client.send(&message)?,
+            Outcome::Quit => return Ok(()),
+        }
+    }
+}
+
+fn apply_server_message(model: &mut MonitorModel, client: &DebuggableClient, message: ServerMessage) {
+    let now = Instant::now();
+    match message {
+        ServerMessage::Remove { id, .. } => model.remove(id),
+        ServerMessage::RemoveAll => model.clear(),
+        ServerMessage::Notify { id, .. } | ServerMessage::NotifyEncoded { id, .. } | ServerMessage::NotifyIndex { id, .. } => {
+            if let Some(debuggable) = client.debuggable(id) {
+                model.set_value(id, &debuggable.name, &debuggable.value_in_json, now);
+            }
+        }
+        ServerMessage::NotifySummary { id, name, byte_len, preview } => {
+            model.set_value(id, &name, &format!("{preview}... ({byte_len} bytes)"), now);
+        }
+        ServerMessage::NotifyUnset { id } => {
+            if let Some(debuggable) = client.debuggable(id) {
+                model.set_value(id, &debuggable.name, "(unset)", now);
+            }
+        }
+        ServerMessage::Metadata { id, order, .. } => model.set_order(id, order),
+        ServerMessage::NotifyMany { notifies } => notifies.into_iter().for_each(|notify| {
+            if let Some(debuggable) = client.debuggable(notify.id) {
+                model.set_value(notify.id, &debuggable.name, &debuggable.value_in_json, now);
+            }
+        }),
+        _ => {}
+    }
+}
+
+fn key_of(code: KeyCode) -> Option<Key> {
+    Some(match code {
+        KeyCode::Char(character) => Key::Char(character),
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        _ => return None,
+    })
+}
+
+fn draw(frame: &mut Frame, model: &MonitorModel, table_state: &mut TableState) {
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(frame.size());
+    let rows = model.visible_rows().into_iter().map(|row| Row::new(vec![
+        Cell::from(row.name.clone()),
+        Cell::from(row.value_in_json.clone()),
+        Cell::from(format!("{}s ago", row.last_changed.elapsed().as_secs())),
+    ])).collect::<Vec<_>>();
+    let table = Table::new(rows, [Constraint::Percentage(25), Constraint::Percentage(60), Constraint::Percentage(15)])
+        .header(Row::new(vec!["Name", "Value", "Changed"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().borders(Borders::ALL).title("Debuggables"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, areas[0], table_state);
+    let status = match model.mode() {
+        Mode::Browse if model.filter().is_empty() => "/ filter, Enter edit, r renotify, q quit".to_string(),
+        Mode::Browse => format!("Filter: {} (/ to change)", model.filter()),
+        Mode::Filter => format!("/{}", model.filter()),
+        Mode::Edit { buffer, .. } => format!("Edit: {buffer}"),
+    };
+    frame.render_widget(Paragraph::new(status).block(Block::default().borders(Borders::ALL)), areas[1]);
+}
@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use crate::serializable::ClientUnitMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Enter,
+    Escape,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Browse,
+    Filter,
+    Edit { id: usize, buffer: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorRow {
+    pub id: usize,
+    pub name: String,
+    pub value_in_json: String,
+    pub order: i32,
+    pub last_changed: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    None,
+    Send(ClientUnitMessage),
+    Quit,
+}
+
+#[derive(Debug)]
+pub struct MonitorModel {
+    rows: BTreeMap<usize, MonitorRow>,
+    filter: String,
+    mode: Mode,
+    selected: usize,
+}
+
+impl Default for MonitorModel {
+    fn default() -> Self {
+        Self { rows: BTreeMap::new(), filter: String::new(), mode: Mode::Browse, selected: 0 }
+    }
+}
+
+impl MonitorModel {
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn visible_rows(&self) -> Vec<&MonitorRow> {
+        let mut rows = self.rows.values().filter(|row| row.name.contains(&*self.filter)).collect::<Vec<_>>();
+        rows.sort_by(|this, other| this.order.cmp(&other.order).then_with(|| this.name.cmp(&other.name)));
+        rows
+    }
+
+    pub fn selected_row(&self) -> Option<&MonitorRow> {
+        self.visible_rows().get(self.selected).copied()
+    }
+
+    pub fn set_value(&mut self, id: usize, name: &str, value_in_json: &str, now: Instant) {
+        match self.rows.get_mut(&id) {
+            Some(row) if row.name == name && row.value_in_json == value_in_json => {}
+            Some(row) => {
+                row.name = name.to_string();
+                row.value_in_json = value_in_json.to_string();
+                row.last_changed = now;
+            }
+            None => {
+                self.rows.insert(id, MonitorRow { id, name: name.to_string(), value_in_json: value_in_json.to_string(), order: 0, last_changed: now });
+            }
+        }
+        self.clamp_selection();
+    }
+
+    pub fn set_order(&mut self, id: usize, order: i32) {
+        if let Some(row) = self.rows.get_mut(&id) {
+            row.order = order;
+        }
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.rows.remove(&id);
+        if matches!(self.mode, Mode::Edit { id: edited_id, .. } if edited_id == id) {
+            self.mode = Mode::Browse;
+        }
+        self.clamp_selection();
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        if matches!(self.mode, Mode::Edit { .. }) {
+            self.mode = Mode::Browse;
+        }
+        self.selected = 0;
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> Outcome {
+        match &mut self.mode {
+            Mode::Browse => match key {
+                Key::Char('q') | Key::Escape => return Outcome::Quit,
+                Key::Char('/') => self.mode = Mode::Filter,
+                Key::Char('r') => return Outcome::Send(ClientUnitMessage::Renotify),
+                Key::Up => self.selected = self.selected.saturating_sub(1),
+                Key::Down => self.selected = self.selected.saturating_add(1),
+                Key::Enter => {
+                    if let Some(row) = self.selected_row() {
+                        self.mode = Mode::Edit { id: row.id, buffer: row.value_in_json.clone() };
+                    }
+                }
+                _ => {}
+            },
+            Mode::Filter => match key {
+                Key::Char(character) => self.filter.push(character),
+                Key::Backspace => { self.filter.pop(); }
+                Key::Enter => self.mode = Mode::Browse,
+                Key::Escape => {
+                    self.filter.clear();
+                    self.mode = Mode::Browse;
+                }
+                _ => {}
+            },
+            Mode::Edit { id, buffer } => match key {
+                Key::Char(character) => buffer.push(character),
+                Key::Backspace => { buffer.pop(); }
+                Key::Escape => self.mode = Mode::Browse,
+                Key::Enter => {
+                    let message = ClientUnitMessage::UpdateValue { id: *id, new_value: buffer.clone(), request_id: None, panel: None };
+                    self.mode = Mode::Browse;
+                    return Outcome::Send(message);
+                }
+                _ => {}
+            },
+        }
+        self.clamp_selection();
+        Outcome::None
+    }
+
+    fn clamp_selection(&mut self) {
+        let visible = self.visible_rows().len();
+        self.selected = self.selected.min(visible.saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::serializable::ClientUnitMessage;
+    use crate::tui::model::{Key, Mode, MonitorModel, Outcome};
+
+    fn model_with_rows() -> MonitorModel {
+        let mut model = MonitorModel::default();
+        let now = Instant::now();
+        model.set_value(0, "speed", "1", now);
+        model.set_value(1, "gravity", "9.8", now);
+        model.set_value(2, "max_speed", "10", now);
+        model
+    }
+
+    fn type_text(model: &mut MonitorModel, text: &str) {
+        text.chars().for_each(|character| assert_eq!(model.handle_key(Key::Char(character)), Outcome::None));
+    }
+
+    fn visible_names(model: &MonitorModel) -> Vec<&str> {
+        model.visible_rows().into_iter().map(|row| &*row.name).collect()
+    }
+
+    #[test]
+    fn slash_filters_by_name_substring() {
+        let mut model = model_with_rows();
+        assert_eq!(model.handle_key(Key::Char('/')), Outcome::None);
+        assert_eq!(model.mode(), &Mode::Filter);
+        type_text(&mut model, "speedx");
+        assert!(visible_names(&model).is_empty());
+        model.handle_key(Key::Backspace);
+        assert_eq!(model.filter(), "speed");
+        assert_eq!(visible_names(&model), vec!["max_speed", "speed"]);
+
+        // Enter keeps the filter while browsing, so q quits rather than filtering
+        model.handle_key(Key::Enter);
+        assert_eq!(model.mode(), &Mode::Browse);
+        assert_eq!(visible_names(&model), vec!["max_speed", "speed"]);
+        assert_eq!(model.handle_key(Key::Char('q')), Outcome::Quit);
+    }
+
+    #[test]
+    fn escape_clears_the_filter() {
+        let mut model = model_with_rows();
+        model.handle_key(Key::Char('/'));
+        type_text(&mut model, "grav");
+        assert_eq!(visible_names(&model), vec!["gravity"]);
+        model.handle_key(Key::Escape);
+        assert_eq!(model.mode(), &Mode::Browse);
+        assert_eq!(model.filter(), "");
+        assert_eq!(visible_names(&model).len(), 3);
+    }
+
+    #[test]
+    fn enter_commits_the_edit_of_the_selected_row_as_an_update() {
+        let mut model = model_with_rows();
+        model.handle_key(Key::Down);
+        assert_eq!(model.selected_row().unwrap().name, "max_speed");
+        model.handle_key(Key::Enter);
+        assert_eq!(model.mode(), &Mode::Edit { id: 2, buffer: "10".to_string() });
+        model.handle_key(Key::Backspace);
+        type_text(&mut model, "5");
+        let update = ClientUnitMessage::UpdateValue { id: 2, new_value: "15".to_string(), request_id: None, panel: None };
+        assert_eq!(model.handle_key(Key::Enter), Outcome::Send(update));
+        assert_eq!(model.mode(), &Mode::Browse);
+    }
+
+    #[test]
+    fn escape_abandons_the_edit_without_sending_it() {
+        let mut model = model_with_rows();
+        model.handle_key(Key::Enter);
+        type_text(&mut model, "0");
+        assert_eq!(model.handle_key(Key::Escape), Outcome::None);
+        assert_eq!(model.mode(), &Mode::Browse);
+        assert_eq!(model.selected_row().unwrap().value_in_json, "9.8");
+    }
+
+    #[test]
+    fn removing_the_edited_row_stops_editing_it() {
+        let mut model = model_with_rows();
+        model.handle_key(Key::Enter);
+        model.remove(1);
+        assert_eq!(model.mode(), &Mode::Browse);
+        assert_eq!(model.handle_key(Key::Char('r')), Outcome::Send(ClientUnitMessage::Renotify));
+    }
+}
@@ -0,0 +1,66 @@
+//! Debuggables announced with Added and removed with a reason, and older clients that get neither.
+#![cfg(feature = "server")]
+
+mod common;
+
+use common::WireClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{AddedOrigin, JSONDeSerializable, RemoveReason, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn added_and_remove_round_trip_with_their_origin_and_reason() {
+    for origin in [AddedOrigin::HostCode, AddedOrigin::ClientCreated, AddedOrigin::Replay] {
+        let added = ServerMessage::Added { id: 2, name: "speed".to_string(), origin, uid: Some(9) };
+        assert_eq!(ServerMessage::from_json(&added.to_json().unwrap()), Some(added));
+    }
+    let reasons = [RemoveReason::Dropped, RemoveReason::Expired, RemoveReason::Shutdown, RemoveReason::Kicked, RemoveReason::Hidden, RemoveReason::Moved];
+    for reason in reasons {
+        let remove = ServerMessage::Remove { id: 2, reason, uid: None };
+        assert_eq!(ServerMessage::from_json(&remove.to_json().unwrap()), Some(remove));
+    }
+}
+
+#[test]
+fn connected_client_is_told_what_was_added_and_why_it_was_removed() {
+    let step = StepServer::new();
+    let existing = DebuggableBuilder::new("existing", 1).scoped(step.scoped_server()).build();
+    let existing_id = step.handle().read().unwrap().debuggable_id_of("existing").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(existing_id).is_some_and(|existing| existing.value_in_json == "1")).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Added { id, origin: AddedOrigin::Replay, .. } if *id == existing_id)), "{received:?}");
+
+    let added = DebuggableBuilder::new("added", 2).scoped(step.scoped_server()).build();
+    let added_id = step.handle().read().unwrap().debuggable_id_of("added").unwrap();
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(added_id).is_some_and(|added| added.value_in_json == "2")).unwrap();
+    let about_added = received.iter()
+        .filter(|message| matches!(message, ServerMessage::Added { id, .. } | ServerMessage::Notify { id, .. } if *id == added_id))
+        .collect::<Vec<_>>();
+    assert!(matches!(&about_added[..], [ServerMessage::Added { name, origin: AddedOrigin::HostCode, .. }, ServerMessage::Notify { .. }] if name == "added"), "{about_added:?}");
+
+    drop(added);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(added_id).is_none()).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Remove { id, reason: RemoveReason::Dropped, .. } if *id == added_id)));
+
+    assert!(step.handle().read().unwrap().kick_debuggable(existing_id));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(existing_id).is_none()).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Remove { id, reason: RemoveReason::Kicked, .. } if *id == existing_id)));
+    drop(existing);
+}
+
+#[test]
+fn clients_that_never_said_hello_get_no_added() {
+    let step = StepServer::new();
+    let _existing = DebuggableBuilder::new("existing", 1).scoped(step.scoped_server()).build();
+    let mut client = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    assert!(step.accept_until(1));
+
+    let added = DebuggableBuilder::new("added", 2).scoped(step.scoped_server()).build();
+    let added_id = step.handle().read().unwrap().debuggable_id_of("added").unwrap();
+    let received = client.receive_until(|received| received.iter().any(|message| matches!(message, ServerMessage::Notify { id, .. } if *id == added_id))).unwrap();
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Added { .. })), "{received:?}");
+    drop(added);
+    let received = client.receive_until(|received| received.iter().any(|message| matches!(message, ServerMessage::Remove { .. }))).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Remove { id, reason: RemoveReason::Dropped, .. } if *id == added_id)));
+}
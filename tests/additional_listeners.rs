@@ -0,0 +1,59 @@
+//! Servers accepting clients on more than one address, each listener with its own allow-list.
+#![cfg(feature = "server")]
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::ip_filter::IpRange;
+use debug_monitor::server::listeners::AdditionalListener;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn ephemeral_listener() -> (TcpListener, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    (listener, addr)
+}
+
+fn sees_value(client: &mut DebuggableClient, id: usize, json: &str) -> bool {
+    poll_client_until(client, |client, _| client.debuggable(id).is_some_and(|debuggable| debuggable.value_in_json == json)).is_some()
+}
+
+#[test]
+fn clients_of_both_listeners_share_one_index_space_and_broadcasts() {
+    let (additional, additional_addr) = ephemeral_listener();
+    let step = StepServer::from_builder(DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).additional_listener(additional));
+    assert_eq!(step.handle().read().unwrap().local_addrs(), vec![step.addr(), additional_addr]);
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+
+    let mut local = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let mut remote = DebuggableClient::connect(additional_addr, step.framing()).unwrap();
+    assert!(step.accept_until(2));
+    assert!(sees_value(&mut local, id, "1") && sees_value(&mut remote, id, "1"));
+    assert_ne!(local.client_id(), remote.client_id());
+
+    *speed = 2;
+    assert_eq!(*speed, 2);
+    assert!(sees_value(&mut local, id, "2"));
+    assert!(sees_value(&mut remote, id, "2"));
+}
+
+#[test]
+fn each_listener_filters_peers_by_its_own_allow_list() {
+    let (additional, additional_addr) = ephemeral_listener();
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .allow_ips(vec!["10.0.0.0/8".parse::<IpRange>().unwrap()])
+        .additional_listener(AdditionalListener::new(additional).loopback_only());
+    let step = StepServer::from_builder(builder);
+
+    let _remote = DebuggableClient::connect(additional_addr, step.framing()).unwrap();
+    assert!(step.accept_until(1));
+    assert_eq!(step.handle().read().unwrap().stats().rejected_connections, 0);
+
+    let _refused = TcpStream::connect(step.addr()).unwrap();
+    assert!(!step.accept_until(2));
+    assert_eq!(step.handle().read().unwrap().stats().rejected_connections, 1);
+}
@@ -0,0 +1,50 @@
+//! Debuggables outliving the server they're registered on, which keep their value locally.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::debuggable::debuggable_vec::DebuggableVec;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn debuggables_of_a_shut_down_server_deref_mutate_format_and_drop() {
+    let step = StepServer::new();
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let mut curve = DebuggableVec::from(DebuggableBuilder::new("curve", vec![0; 4]).scoped(step.scoped_server()).build());
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+    client.send_update(id, "9").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert!(speed.is_server_alive());
+
+    step.handle().read().unwrap().shutdown();
+    assert!(!speed.is_server_alive());
+    // The pending update is left on the server, which isn't polled anymore
+    assert_eq!(*speed, 1);
+    *speed = 2;
+    assert_eq!(*speed, 2);
+    assert_eq!(format!("{speed:?} {speed} {:?}", speed.debug_synced()), "2 2 2");
+    assert_eq!(curve.set(1, 5), Some(0));
+    assert_eq!(curve[1], 5);
+    assert_eq!(step.handle().read().unwrap().value_of("speed").as_deref(), Some("1"));
+
+    drop(speed);
+    drop(curve);
+    assert_eq!(step.handle().read().unwrap().memory_report().debuggables, 2);
+}
+
+#[test]
+fn debuggables_keep_syncing_with_the_servers_still_running() {
+    let (stopped, running) = (StepServer::new(), StepServer::new());
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(stopped.scoped_server()).also_on(running.handle()).build();
+    stopped.handle().read().unwrap().shutdown();
+    assert!(speed.is_server_alive());
+
+    *speed = 2;
+    assert_eq!(*speed, 2);
+    assert_eq!(running.handle().read().unwrap().value_of("speed").as_deref(), Some("2"));
+    assert_eq!(stopped.handle().read().unwrap().value_of("speed").as_deref(), Some("1"));
+    drop(speed);
+    assert_eq!(running.handle().read().unwrap().debuggable_id_of("speed"), None);
+}
@@ -0,0 +1,97 @@
+//! Numeric debuggables moved towards a target by the server, one step each time they sync.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+fn animated_server() -> (StepServer, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .clock(clock.clone())
+        .animation_step(Duration::from_millis(50));
+    (StepServer::from_builder(builder), clock)
+}
+
+/// Sends the animation followed by an update of the marker, which once pending tells the server
+/// read the animation too.
+fn animate(step: &StepServer, client: &mut DebuggableClient, id: usize, target_json: &str, marker_id: usize) {
+    client.send_animation(id, target_json, Duration::from_secs(1)).unwrap();
+    client.send_update(marker_id, "1").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(marker_id) == 1));
+}
+
+#[test]
+fn value_approaches_the_target_with_every_step_and_clients_see_it() {
+    let (step, clock) = animated_server();
+    let zoom = DebuggableBuilder::new("zoom", 0.0_f64).scoped(step.scoped_server()).build();
+    let marker = DebuggableBuilder::new("marker", 0).scoped(step.scoped_server()).build();
+    let [id, marker_id] = ["zoom", "marker"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    animate(&step, &mut client, id, "10.0", marker_id);
+    assert_eq!(*marker, 1);
+
+    clock.advance(Duration::from_millis(10));
+    let mut reached = vec![*zoom];
+    for _ in 0..10 {
+        clock.advance(Duration::from_millis(90));
+        reached.push(*zoom);
+        // Syncing again before the next step is due leaves the value where it was
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(*zoom, *reached.last().unwrap());
+    }
+    assert!(reached.windows(2).all(|pair| pair[0] < pair[1]), "{reached:?}");
+    assert_eq!(*reached.last().unwrap(), 10.0);
+
+    // The animation ended, so later syncs keep the target
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(*zoom, 10.0);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|zoom| zoom.value_in_json == "10.0")).unwrap();
+    let intermediate = received.iter().filter(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id)).count();
+    assert!(intermediate > 2, "{received:?}");
+}
+
+#[test]
+fn later_updates_cancel_the_animation() {
+    let (step, clock) = animated_server();
+    let zoom = DebuggableBuilder::new("zoom", 0.0_f64).scoped(step.scoped_server()).build();
+    let _marker = DebuggableBuilder::new("marker", 0).scoped(step.scoped_server()).build();
+    let [id, marker_id] = ["zoom", "marker"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    animate(&step, &mut client, id, "10.0", marker_id);
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(*zoom, 5.0);
+
+    client.send_update(id, "-1.0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*zoom, -1.0);
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(*zoom, -1.0);
+}
+
+#[test]
+fn non_numeric_debuggables_reject_the_animation() {
+    let (step, _clock) = animated_server();
+    let label = DebuggableBuilder::new("label", "calm".to_string()).scoped(step.scoped_server()).build();
+    let _marker = DebuggableBuilder::new("marker", 0).scoped(step.scoped_server()).build();
+    let [id, marker_id] = ["label", "marker"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    animate(&step, &mut client, id, "10", marker_id);
+
+    let received = poll_client_until(&mut client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Error { .. }))).unwrap();
+    let rejection = received.iter().find_map(|message| match message {
+        ServerMessage::Error { message, .. } => Some(message.clone()),
+        _ => None,
+    });
+    assert!(rejection.is_some_and(|rejection| rejection.starts_with(&format!("Rejected animation of debuggable {id}"))));
+    assert_eq!(*label, "calm");
+}
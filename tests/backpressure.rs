@@ -0,0 +1,85 @@
+//! Clients that stop reading only lose messages or get dropped, without slowing down the host.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::DebuggableServer;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::events::ServerEvent;
+use debug_monitor::server::outgoing::OverflowPolicy;
+
+use common::{serve_until, WireClient};
+
+// Enough rounds of large values to fill the socket buffers of a client that doesn't read
+const ROUNDS: u64 = 200;
+const ELEMENTS: usize = 20_000;
+
+fn server(policy: OverflowPolicy) -> (Arc<RwLock<DebuggableServer>>, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = DebuggableServerBuilder::new(listener)
+        .max_outgoing_queue(4)
+        .overflow_policy(policy)
+        .build();
+    (Arc::new(RwLock::new(server)), addr)
+}
+
+fn accept_clients(server: &RwLock<DebuggableServer>, client_count: usize) -> bool {
+    let server = server.read().unwrap();
+    serve_until(&server, || (0..client_count).all(|client_index| server.read().clients().contains_index(client_index)))
+}
+
+#[test]
+fn hosts_stay_fast_and_readers_keep_receiving_while_a_client_stalls() {
+    let (server, addr) = server(OverflowPolicy::DropOldest);
+    let mut values = DebuggableBuilder::new("values", vec![0_u64; ELEMENTS]).server(Some(server.clone())).build();
+    let _stalled_client = TcpStream::connect(addr).unwrap();
+    assert!(accept_clients(&server, 1));
+    let mut reader = WireClient::connect(&server.read().unwrap(), addr);
+    assert!(accept_clients(&server, 2));
+
+    let started = Instant::now();
+    for round in 1..=ROUNDS {
+        *values = vec![round; ELEMENTS];
+        assert_eq!(values[0], round);
+    }
+    assert!(started.elapsed() < Duration::from_secs(10), "{ROUNDS} writes took {:?}", started.elapsed());
+
+    let last_value = format!("[{ROUNDS},");
+    assert!(reader.receive_until(|received| received.iter().any(|message| {
+        matches!(message, ServerMessage::Notify { name, value_in_json, .. } if name == "values" && value_in_json.starts_with(&last_value))
+    })).is_some());
+    assert!(server.read().unwrap().stats().dropped_messages > 0);
+}
+
+#[test]
+fn stalled_clients_are_dropped_and_reported() {
+    let (server, addr) = server(OverflowPolicy::DropClient);
+    let events = server.read().unwrap().events();
+    let mut values = DebuggableBuilder::new("values", vec![0_u64; ELEMENTS]).server(Some(server.clone())).build();
+    let _stalled_client = TcpStream::connect(addr).unwrap();
+    assert!(accept_clients(&server, 1));
+
+    for round in 1..=ROUNDS {
+        *values = vec![round; ELEMENTS];
+        assert_eq!(values[0], round);
+        if server.read().unwrap().stats().dropped_clients > 0 { break; }
+    }
+    assert_eq!(server.read().unwrap().stats().dropped_clients, 1);
+    // Syncing the debuggable polls the server, which forgets the dropped client
+    let give_up_at = Instant::now() + Duration::from_secs(2);
+    while server.read().unwrap().client_count() > 0 && Instant::now() < give_up_at {
+        assert!(values[0] > 0);
+    }
+    assert_eq!(server.read().unwrap().client_count(), 0);
+    let dropped = events.iter()
+        .take_while(|event| *event != ServerEvent::ClientDisconnected { index: 0 })
+        .any(|event| event == ServerEvent::ClientDropped { index: 0 });
+    assert!(dropped);
+}
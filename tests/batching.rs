@@ -0,0 +1,121 @@
+//! Notifications held back by a NotifyBatch and sent together.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::DebuggableServer;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn debuggable_on(step: &StepServer, name: &str, value: i32) -> (Debuggable<i32>, usize) {
+    let debuggable = DebuggableBuilder::new(name, value).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of(name).unwrap();
+    (debuggable, id)
+}
+
+/// Connects a client and waits until the server read its Hello, which precedes the update sent
+/// for the given debuggable.
+fn connect_announced(step: &StepServer, client_count: usize, handshake_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(client_count));
+    client.send_update(handshake_id, "0").unwrap();
+    let pending_before = step.handle().read().unwrap().pending_updates_of(handshake_id);
+    assert!(step.read_until(|server| server.pending_updates_of(handshake_id) > pending_before));
+    client
+}
+
+fn batched_ids_of(received: &[ServerMessage]) -> Vec<Vec<usize>> {
+    received.iter().filter_map(|message| match message {
+        ServerMessage::NotifyMany { notifies } => Some(notifies.iter().map(|notify| notify.id).collect()),
+        _ => None,
+    }).collect()
+}
+
+fn has_notify_of(received: &[ServerMessage], id: usize) -> bool {
+    received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id))
+}
+
+#[test]
+fn changes_within_a_batch_arrive_as_one_notify_many() {
+    let step = StepServer::new();
+    let (_handshake, handshake_id) = debuggable_on(&step, "handshake", 0);
+    let (mut x, x_id) = debuggable_on(&step, "x", 0);
+    let (mut y, y_id) = debuggable_on(&step, "y", 0);
+    let mut client = connect_announced(&step, 1, handshake_id);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(y_id).is_some()).is_some());
+
+    let batch = DebuggableServer::begin_batch(&step.handle());
+    *x = 1;
+    *y = 2;
+    assert_eq!((*x, *y), (1, 2));
+    drop(batch);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(x_id).is_some_and(|x| x.value_in_json == "1")).unwrap();
+    assert_eq!(batched_ids_of(&received), vec![vec![x_id, y_id]]);
+    assert!(!has_notify_of(&received, x_id) && !has_notify_of(&received, y_id));
+    assert_eq!(client.debuggable(y_id).unwrap().value_in_json, "2");
+}
+
+#[test]
+fn author_of_an_update_is_still_left_out_within_a_batch() {
+    let step = StepServer::new();
+    let (_handshake, handshake_id) = debuggable_on(&step, "handshake", 0);
+    let (mut x, x_id) = debuggable_on(&step, "x", 0);
+    let (mut y, y_id) = debuggable_on(&step, "y", 0);
+    let mut author = connect_announced(&step, 1, handshake_id);
+    let mut observer = connect_announced(&step, 2, handshake_id);
+    assert!(poll_client_until(&mut author, |client, _| client.debuggable(y_id).is_some()).is_some());
+    assert!(poll_client_until(&mut observer, |client, _| client.debuggable(y_id).is_some()).is_some());
+
+    let batch = DebuggableServer::begin_batch(&step.handle());
+    author.send_update(x_id, "7").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(x_id) == 1));
+    assert_eq!(*x, 7);
+    *y = 2;
+    assert_eq!(*y, 2);
+    drop(batch);
+
+    let observed = poll_client_until(&mut observer, |client, _| client.debuggable(y_id).is_some_and(|y| y.value_in_json == "2")).unwrap();
+    assert_eq!(batched_ids_of(&observed), vec![vec![x_id, y_id]]);
+    let authored = poll_client_until(&mut author, |client, _| client.debuggable(y_id).is_some_and(|y| y.value_in_json == "2")).unwrap();
+    assert_eq!(batched_ids_of(&authored), vec![vec![y_id]]);
+}
+
+#[test]
+fn nested_batches_flush_once_the_outermost_ends() {
+    let step = StepServer::new();
+    let (_handshake, handshake_id) = debuggable_on(&step, "handshake", 0);
+    let (mut x, x_id) = debuggable_on(&step, "x", 0);
+    let mut client = connect_announced(&step, 1, handshake_id);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(x_id).is_some()).is_some());
+
+    let outer = DebuggableServer::begin_batch(&step.handle());
+    let inner = DebuggableServer::begin_batch(&step.handle());
+    *x = 1;
+    assert_eq!(*x, 1);
+    drop(inner);
+    *x = 2;
+    assert_eq!(*x, 2);
+    drop(outer);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(x_id).is_some_and(|x| x.value_in_json == "2")).unwrap();
+    assert_eq!(batched_ids_of(&received), vec![vec![x_id]]);
+}
+
+#[test]
+fn batched_changes_follow_their_debuggable_when_compacting() {
+    let step = StepServer::new();
+    let (_handshake, handshake_id) = debuggable_on(&step, "handshake", 0);
+    let (gap, _) = debuggable_on(&step, "gap", 0);
+    let (mut x, x_id) = debuggable_on(&step, "x", 0);
+    let mut client = connect_announced(&step, 1, handshake_id);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(x_id).is_some()).is_some());
+
+    let batch = DebuggableServer::begin_batch(&step.handle());
+    *x = 1;
+    assert_eq!(*x, 1);
+    drop(gap);
+    assert_eq!(step.handle().read().unwrap().compact(), 1);
+    let moved_id = step.handle().read().unwrap().debuggable_id_of("x").unwrap();
+    drop(batch);
+    let received = poll_client_until(&mut client, |_, received| !batched_ids_of(received).is_empty()).unwrap();
+    assert_eq!(batched_ids_of(&received), vec![vec![moved_id]]);
+}
@@ -0,0 +1,47 @@
+//! Guards keeping the value of a debuggable from being swapped while a reference to it is held.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn remote_update_is_applied_only_after_the_guard_drops() {
+    let step = StepServer::new();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+
+    let guard = counter.borrow();
+    client.send_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    // Accessing it again, as a log statement would, leaves the update queued
+    assert_eq!(*counter, 1);
+    counter.sync();
+    assert_eq!(*guard, 1);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 1);
+
+    drop(guard);
+    assert_eq!(*counter, 5);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 0);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "5")).is_some());
+}
+
+#[test]
+fn changes_made_through_a_mutable_guard_are_sent_on_the_next_sync() {
+    let step = StepServer::new();
+    let mut counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+
+    {
+        let mut guard = counter.borrow_mut();
+        *guard += 1;
+        *guard *= 10;
+    }
+    assert_eq!(step.handle().read().unwrap().value_of("counter").as_deref(), Some("1"));
+    assert_eq!(*counter, 20);
+    assert_eq!(step.handle().read().unwrap().value_of("counter").as_deref(), Some("20"));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "20")).is_some());
+}
@@ -0,0 +1,17 @@
+//! Types of a downstream crate deriving the traits of either backend, while debug_monitor has both
+//! of them enabled.
+
+use nanoserde::{DeJson, SerJson};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerdePosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, SerJson, DeJson)]
+pub struct NanoPosition {
+    pub x: i32,
+    pub y: i32,
+}
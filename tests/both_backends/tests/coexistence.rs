@@ -0,0 +1,44 @@
+//! Debuggables of serde and nanoserde types living side by side.
+
+use both_backends::{NanoPosition, SerdePosition};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::serializable::backend_wrappers::{NanoJson, SerdeJson};
+use debug_monitor::serializable::{JSONDeSerializable, ServerMessage};
+use nanoserde::{DeJson, SerJson};
+
+#[test]
+fn serde_types_keep_working_unwrapped() {
+    let server = ScopedServer::new();
+    let mut position = DebuggableBuilder::new("position", SerdePosition { x: 1, y: 2 }).scoped(&server).build();
+    assert_eq!(server.handle().read().unwrap().value_of("position").as_deref(), Some("{\"x\":1,\"y\":2}"));
+    position.x = 5;
+    assert_eq!(position.y, 2);
+    assert_eq!(server.handle().read().unwrap().value_of("position").as_deref(), Some("{\"x\":5,\"y\":2}"));
+    assert_eq!(SerdePosition::from_json("{\"x\":3,\"y\":4}"), Some(SerdePosition { x: 3, y: 4 }));
+}
+
+#[test]
+fn nanoserde_types_go_through_their_wrapper() {
+    let server = ScopedServer::new();
+    let mut position = DebuggableBuilder::new("position", NanoJson(NanoPosition { x: 1, y: 2 })).scoped(&server).build();
+    position.y = 7;
+    assert_eq!(position.x, 1);
+    let json = server.handle().read().unwrap().value_of("position").unwrap();
+    assert_eq!(NanoJson::<NanoPosition>::from_json(&json), Some(NanoJson(NanoPosition { x: 1, y: 7 })));
+    assert!(NanoJson::<NanoPosition>::from_json_detailed("{\"x\":1}").is_err());
+}
+
+#[test]
+fn serde_wrapper_matches_the_blanket_impl() {
+    let position = SerdePosition { x: -1, y: 9 };
+    assert_eq!(SerdeJson(position.clone()).to_json(), position.to_json());
+    assert_eq!(SerdeJson::<SerdePosition>::from_json("{\"x\":-1,\"y\":9}"), Some(SerdeJson(position)));
+}
+
+#[test]
+fn protocol_messages_round_trip_through_either_backend() {
+    let metadata = ServerMessage::Metadata { id: 4, nullable: true, order: -3, on_demand: false };
+    assert_eq!(ServerMessage::from_json(&metadata.to_json().unwrap()), Some(metadata.clone()));
+    assert_eq!(ServerMessage::deserialize_json(&metadata.serialize_json()).unwrap(), metadata);
+}
@@ -0,0 +1,69 @@
+//! Numeric bounds the server clamps updates into before their debuggable syncs.
+#![cfg(feature = "server")]
+
+use std::time::Instant;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer, STEP_TIMEOUT};
+
+#[test]
+fn out_of_range_update_is_clamped_before_the_host_syncs() {
+    let step = StepServer::new();
+    let volume = DebuggableBuilder::new("volume", 50).numeric_bounds(0.0, 100.0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("volume").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+
+    client.send_update(id, "250").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    let clamped_notify = |_: &_, received: &[ServerMessage]| received.iter()
+        .any(|message| matches!(message, ServerMessage::Notify { id: notified, value_in_json, .. } if *notified == id && value_in_json == "100"));
+    assert!(poll_client_until(&mut client, clamped_notify).is_some());
+    assert_eq!(*volume, 100);
+}
+
+#[test]
+fn non_numeric_update_of_a_bounded_debuggable_is_rejected_on_arrival() {
+    let step = StepServer::new();
+    let volume = DebuggableBuilder::new("volume", 50).numeric_bounds(0.0, 100.0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("volume").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+
+    client.send_update(id, "\"loud\"").unwrap();
+    // Nothing is queued, so the server is read until the rejection reaches the client
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    let mut received = Vec::new();
+    while !received.iter().any(|message| matches!(message, ServerMessage::Error { .. })) {
+        assert!(Instant::now() < give_up_at);
+        step.read_until(|_| true);
+        received.extend(client.poll().unwrap());
+    }
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 0);
+    assert_eq!(*volume, 50);
+}
+
+#[test]
+fn grouped_updates_are_clamped_too() {
+    let step = StepServer::new();
+    let volume = DebuggableBuilder::new("volume", 50).numeric_bounds(0.0, 100.0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("volume").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+
+    client.send_update_group(&[(id, "-20".to_string())]).unwrap();
+    // Groups aren't pending updates until released, so the server is read until the clamped value
+    // reaches the client
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    let mut received = Vec::new();
+    while !received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, value_in_json, .. } if *notified == id && value_in_json == "0")) {
+        assert!(Instant::now() < give_up_at);
+        step.read_until(|_| true);
+        received.extend(client.poll().unwrap());
+    }
+    assert_eq!(*volume, 0);
+}
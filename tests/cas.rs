@@ -0,0 +1,74 @@
+//! Compare-and-swap updates racing on one debuggable, of which only the first one applies.
+#![cfg(feature = "server")]
+
+use std::thread;
+
+use debug_monitor::client::{CasOutcome, DebuggableClient};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{ClientUnitMessage, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer, STEP_TIMEOUT};
+
+/// Polls until the server answered the CAS update of the debuggable.
+fn cas_answer(client: &mut DebuggableClient, id: usize) -> ServerMessage {
+    let is_answer = |message: &ServerMessage| matches!(message, ServerMessage::CasAccepted { id: answered, .. } | ServerMessage::Conflict { id: answered, .. } if *answered == id);
+    let received = poll_client_until(client, |_, received| received.iter().any(is_answer)).unwrap();
+    received.into_iter().find(is_answer).unwrap()
+}
+
+#[test]
+fn racing_cas_updates_yield_one_accepted_and_one_conflict() {
+    let step = StepServer::new();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let _handshake = DebuggableBuilder::new("handshake", 0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let handshake_id = step.handle().read().unwrap().debuggable_id_of("handshake").unwrap();
+    let mut clients = Vec::new();
+    for client_count in 1..=2 {
+        let mut client = step.connect().unwrap();
+        assert!(step.accept_until(client_count));
+        assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "1")).is_some());
+        clients.push(client);
+    }
+    let revision = clients[0].debuggable(id).unwrap().revision;
+    assert_eq!(clients[1].debuggable(id).unwrap().revision, revision);
+
+    for (client, new_value) in clients.iter_mut().zip(["10", "20"]) {
+        client.send(&ClientUnitMessage::UpdateValueCas { id, expected_revision: revision, new_value: new_value.to_string() }).unwrap();
+        // Read after the CAS update, so once it's pending the CAS update was handled
+        client.send_update(handshake_id, "0").unwrap();
+    }
+    assert!(step.read_until(|server| server.pending_updates_of(handshake_id) == 2));
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 1);
+    let answers = clients.iter_mut().map(|client| cas_answer(client, id)).collect::<Vec<_>>();
+
+    let accepted = answers.iter().filter(|answer| matches!(answer, ServerMessage::CasAccepted { revision: accepted, .. } if *accepted == revision + 1)).count();
+    assert_eq!(accepted, 1, "{answers:?}");
+    let (winner, current_value) = answers.iter().enumerate()
+        .find_map(|(loser, answer)| match answer {
+            ServerMessage::Conflict { current_revision, current_value, .. } => {
+                assert_eq!(*current_revision, revision + 1);
+                Some((1 - loser, current_value.clone()))
+            }
+            _ => None,
+        })
+        .unwrap();
+    let winner_value = ["10", "20"][winner];
+    assert_eq!(current_value, winner_value);
+    assert_eq!((*counter).to_string(), winner_value);
+}
+
+#[test]
+fn try_set_cas_applies_against_the_revision_the_client_last_saw() {
+    let step = StepServer::new();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "1")).is_some());
+    let revision = client.debuggable(id).unwrap().revision;
+
+    let setter = thread::spawn(move || client.try_set_cas(id, "30", STEP_TIMEOUT).unwrap());
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(setter.join().unwrap(), CasOutcome::Applied { revision: revision + 1 });
+    assert_eq!(*counter, 30);
+}
@@ -0,0 +1,165 @@
+//! Protocol messages as length-prefixed CBOR frames, alone and through a listener marked with Codec::Cbor.
+#![cfg(feature = "cbor")]
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::framing::{Framing, FramingInfo};
+use debug_monitor::serializable::text_patch::TextHunk;
+use debug_monitor::serializable::{AddedOrigin, ClientUnitMessage, CompositeKind, GroupedUpdate, NotifyEntry, RemoveReason, ServerMessage, PROTOCOL_VERSION};
+use debug_monitor::server::cbor::{encode_frame, read_frame};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::listeners::{AdditionalListener, Codec};
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+fn client_messages() -> Vec<ClientUnitMessage> {
+    let messages = vec![
+        ClientUnitMessage::UpdateValue { id: 0, new_value: "1".to_string(), request_id: Some(1), panel: Some("panel".to_string()) },
+        ClientUnitMessage::UpdateIndex { id: 0, index: 1, element_json: "1".to_string() },
+        ClientUnitMessage::Renotify,
+        ClientUnitMessage::Hello { protocol_version: PROTOCOL_VERSION, supports_deflate: true, panel: Some("panel".to_string()), supports_text_patches: true, session_key: Some("key".to_string()) },
+        ClientUnitMessage::Custom { topic: "topic".to_string(), payload: "payload".to_string() },
+        ClientUnitMessage::UpdateValueCas { id: 0, expected_revision: 1, new_value: "1".to_string() },
+        ClientUnitMessage::UpdateGroup { updates: vec![GroupedUpdate { id: 0, new_value: "1".to_string() }] },
+        ClientUnitMessage::AnimateValue { id: 0, target_json: "1".to_string(), duration_ms: 10 },
+        ClientUnitMessage::GroupSnapshotRequest { prefix: "a/".to_string() },
+        ClientUnitMessage::GroupReset { prefix: "a/".to_string() },
+        ClientUnitMessage::RpcCall { id: 0, call_id: 1, request_json: "1".to_string() },
+        ClientUnitMessage::RequestValue { id: 0, full: true },
+        ClientUnitMessage::UpdateTextPatch { id: 0, base_revision: 1, hunks: vec![TextHunk { start: 0, removed: 1, inserted: vec!["line".to_string()] }] },
+        ClientUnitMessage::Subscribe { names: Some(vec!["a".to_string()]) },
+        ClientUnitMessage::StoreBlob { key: "layout".to_string(), data: "{}".to_string() },
+        ClientUnitMessage::BeginEdit { txn_id: 1 },
+        ClientUnitMessage::StageValue { txn_id: 1, id: 0, new_value: "1".to_string() },
+        ClientUnitMessage::CommitEdit { txn_id: 1 },
+        ClientUnitMessage::AbortEdit { txn_id: 1 },
+    ];
+    // Fails to compile once a variant is added, so it gets a sample above
+    messages.iter().for_each(|message| match message {
+        ClientUnitMessage::UpdateValue { .. } | ClientUnitMessage::UpdateIndex { .. } | ClientUnitMessage::Renotify | ClientUnitMessage::Hello { .. }
+        | ClientUnitMessage::Custom { .. } | ClientUnitMessage::UpdateValueCas { .. } | ClientUnitMessage::UpdateGroup { .. }
+        | ClientUnitMessage::AnimateValue { .. } | ClientUnitMessage::GroupSnapshotRequest { .. } | ClientUnitMessage::GroupReset { .. }
+        | ClientUnitMessage::RpcCall { .. } | ClientUnitMessage::RequestValue { .. } | ClientUnitMessage::UpdateTextPatch { .. }
+        | ClientUnitMessage::Subscribe { .. } | ClientUnitMessage::StoreBlob { .. } | ClientUnitMessage::BeginEdit { .. }
+        | ClientUnitMessage::StageValue { .. } | ClientUnitMessage::CommitEdit { .. } | ClientUnitMessage::AbortEdit { .. } => {}
+    });
+    messages
+}
+
+fn server_messages() -> Vec<ServerMessage> {
+    let framing = FramingInfo { endmark: "<end>".to_string(), escape: "<escaped>".to_string(), mode: Framing::LengthPrefixed };
+    let messages = vec![
+        ServerMessage::GiveClientId { client_id: 0 },
+        ServerMessage::Notify { id: 0, name: "a".to_string(), value_in_json: "1".to_string(), revision: 1, changed_at_ms: Some(1), author: Some(1), uid: Some(1), overridden: true },
+        ServerMessage::NotifyEncoded { id: 0, name: "a".to_string(), encoding: "deflate".to_string(), value_in_json: "eJwzBAAAMgAy".to_string(), revision: 1 },
+        ServerMessage::Remove { id: 0, reason: RemoveReason::Kicked, uid: Some(1) },
+        ServerMessage::RemoveAll,
+        ServerMessage::Custom { topic: "topic".to_string(), payload: "payload".to_string() },
+        ServerMessage::Welcome { client_id: 0, protocol_version: PROTOCOL_VERSION },
+        ServerMessage::NotifyIndex { id: 0, index: 1, element_json: "1".to_string(), revision: 1 },
+        ServerMessage::Metadata { id: 0, nullable: true, order: -1, on_demand: true },
+        ServerMessage::NotifyMany { notifies: vec![NotifyEntry { id: 0, name: "a".to_string(), value_in_json: "1".to_string(), revision: 1, overridden: true }] },
+        ServerMessage::Conflict { id: 0, current_revision: 2, current_value: "2".to_string() },
+        ServerMessage::CasAccepted { id: 0, revision: 2 },
+        ServerMessage::Error { message: "rejected".to_string(), panel: Some("panel".to_string()) },
+        ServerMessage::Added { id: 0, name: "a".to_string(), origin: AddedOrigin::ClientCreated, uid: Some(1) },
+        ServerMessage::ServerInfo { crate_version: "0.1.0".to_string(), protocol_version: PROTOCOL_VERSION, capabilities: vec!["a".to_string()], debuggable_count: 1, framing: Some(framing) },
+        ServerMessage::UpdateAck { request_id: 1, accepted: true, panel: None },
+        ServerMessage::GroupResult { prefix: "a/".to_string(), applied: vec![0], denied: vec![1] },
+        ServerMessage::RpcResult { call_id: 1, response_json: Some("1".to_string()), error: None },
+        ServerMessage::NotifySummary { id: 0, name: "a".to_string(), byte_len: 100, preview: "[1,".to_string() },
+        ServerMessage::NotifyUnset { id: 0 },
+        ServerMessage::ValueChunk { id: 0, revision: 1, chunk_index: 0, chunk_count: 2, json_chunk: "[1,".to_string() },
+        ServerMessage::Composite { name: "a".to_string(), kind: CompositeKind::ColorRgba, members: vec![0, 1, 2, 3] },
+        ServerMessage::CompositeDissolved { name: "a".to_string() },
+        ServerMessage::NotifyTextPatch { id: 0, base_revision: 1, revision: 2, hunks: vec![TextHunk { start: 0, removed: 0, inserted: vec!["line".to_string()] }] },
+        ServerMessage::Blob { key: "layout".to_string(), data: "{}".to_string() },
+    ];
+    // Fails to compile once a variant is added, so it gets a sample above
+    messages.iter().for_each(|message| match message {
+        ServerMessage::GiveClientId { .. } | ServerMessage::Notify { .. } | ServerMessage::NotifyEncoded { .. } | ServerMessage::Remove { .. }
+        | ServerMessage::RemoveAll | ServerMessage::Custom { .. } | ServerMessage::Welcome { .. } | ServerMessage::NotifyIndex { .. }
+        | ServerMessage::Metadata { .. } | ServerMessage::NotifyMany { .. } | ServerMessage::Conflict { .. } | ServerMessage::CasAccepted { .. }
+        | ServerMessage::Error { .. } | ServerMessage::Added { .. } | ServerMessage::ServerInfo { .. } | ServerMessage::UpdateAck { .. }
+        | ServerMessage::GroupResult { .. } | ServerMessage::RpcResult { .. } | ServerMessage::NotifySummary { .. } | ServerMessage::NotifyUnset { .. }
+        | ServerMessage::ValueChunk { .. } | ServerMessage::Composite { .. } | ServerMessage::CompositeDissolved { .. }
+        | ServerMessage::NotifyTextPatch { .. } | ServerMessage::Blob { .. } => {}
+    });
+    messages
+}
+
+#[test]
+fn every_message_round_trips_through_one_stream_of_frames() {
+    let client_messages = client_messages();
+    let server_messages = server_messages();
+    let client_stream = client_messages.iter().flat_map(|message| encode_frame(message).unwrap()).collect::<Vec<_>>();
+    let server_stream = server_messages.iter().flat_map(|message| encode_frame(message).unwrap()).collect::<Vec<_>>();
+
+    let mut reader = &client_stream[..];
+    let decoded = std::iter::from_fn(|| read_frame::<ClientUnitMessage, _>(&mut reader).unwrap()).collect::<Vec<_>>();
+    assert_eq!(decoded, client_messages);
+    let mut reader = &server_stream[..];
+    let decoded = std::iter::from_fn(|| read_frame::<ServerMessage, _>(&mut reader).unwrap()).collect::<Vec<_>>();
+    assert_eq!(decoded, server_messages);
+}
+
+#[test]
+fn frames_carry_their_length_and_reject_what_isnt_the_expected_message() {
+    let frame = encode_frame(&ClientUnitMessage::Renotify).unwrap();
+    assert_eq!(u32::from_be_bytes(frame[..4].try_into().unwrap()) as usize, frame.len() - 4);
+
+    let mut stream = encode_frame(&ServerMessage::RemoveAll).unwrap();
+    stream.extend(&frame);
+    let mut reader = &stream[..];
+    assert!(read_frame::<ClientUnitMessage, _>(&mut reader).is_err());
+    assert_eq!(read_frame::<ClientUnitMessage, _>(&mut reader).unwrap(), Some(ClientUnitMessage::Renotify));
+    assert_eq!(read_frame::<ClientUnitMessage, _>(&mut reader).unwrap(), None);
+}
+
+#[test]
+fn cbor_listener_translates_a_whole_session() {
+    let cbor_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let cbor_addr = cbor_listener.local_addr().unwrap();
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .additional_listener(AdditionalListener::new(cbor_listener).codec(Codec::Cbor));
+    let step = StepServer::from_builder(builder);
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+
+    let mut stream = TcpStream::connect(cbor_addr).unwrap();
+    let mut reader = stream.try_clone().unwrap();
+    let (received_sender, received) = mpsc::channel();
+    thread::spawn(move || {
+        while let Ok(Some(message)) = read_frame::<ServerMessage, _>(&mut reader) {
+            if received_sender.send(message).is_err() { break; }
+        }
+    });
+    let hello = ClientUnitMessage::Hello { protocol_version: PROTOCOL_VERSION, supports_deflate: false, panel: None, supports_text_patches: false, session_key: None };
+    stream.write_all(&encode_frame(&hello).unwrap()).unwrap();
+    assert!(step.accept_until(1));
+
+    let receive_until = |is_wanted: &dyn Fn(&ServerMessage) -> bool| {
+        let give_up_at = Instant::now() + STEP_TIMEOUT;
+        loop {
+            assert!(Instant::now() < give_up_at, "The wanted message wasn't received in time");
+            step.housekeeping();
+            if let Ok(message) = received.try_recv() {
+                if is_wanted(&message) { return message; }
+            }
+            thread::yield_now();
+        }
+    };
+    receive_until(&|message| matches!(message, ServerMessage::GiveClientId { .. }));
+    receive_until(&|message| matches!(message, ServerMessage::Notify { id: notified, value_in_json, .. } if *notified == id && value_in_json == "1"));
+
+    let update = ClientUnitMessage::UpdateValue { id, new_value: "5".to_string(), request_id: Some(1), panel: None };
+    stream.write_all(&encode_frame(&update).unwrap()).unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 5);
+    let ack = receive_until(&|message| matches!(message, ServerMessage::UpdateAck { .. }));
+    assert_eq!(ack, ServerMessage::UpdateAck { request_id: 1, accepted: true, panel: None });
+}
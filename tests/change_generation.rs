@@ -0,0 +1,44 @@
+//! Counters of the remote updates debuggables accepted, for hosts polling whether anything changed.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::StepServer;
+
+fn update_and_read(step: &StepServer, client: &mut DebuggableClient, id: usize, jsons: &[&str]) {
+    jsons.iter().for_each(|json| client.send_update(id, json).unwrap());
+    assert!(step.read_until(|server| server.pending_updates_of(id) == jsons.len()));
+}
+
+#[test]
+fn only_accepted_remote_updates_count() {
+    let step = StepServer::new();
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let last_seen = speed.change_generation();
+    assert_eq!(last_seen, 0);
+
+    update_and_read(&step, &mut client, id, &["5"]);
+    assert_eq!(*speed, 5);
+    assert!(speed.changed_since(last_seen));
+    let last_seen = speed.change_generation();
+    assert_eq!((last_seen, step.handle().read().unwrap().change_generation_of(id)), (1, Some(1)));
+
+    // Rejected, and equal to the current value
+    update_and_read(&step, &mut client, id, &["\"fast\""]);
+    assert_eq!(*speed, 5);
+    update_and_read(&step, &mut client, id, &["5"]);
+    assert_eq!(*speed, 5);
+    assert!(!speed.changed_since(last_seen));
+
+    *speed = 9;
+    assert_eq!(*speed, 9);
+    assert!(!speed.changed_since(last_seen));
+
+    // Updates superseded by a later one in the same sync count once
+    update_and_read(&step, &mut client, id, &["6", "7"]);
+    assert_eq!(*speed, 7);
+    assert_eq!((speed.change_generation(), step.handle().read().unwrap().change_generation_of(id)), (2, Some(2)));
+}
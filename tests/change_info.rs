@@ -0,0 +1,88 @@
+//! When and by whom the value of each debuggable last changed.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::clock::Clock;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+fn server_with_clock() -> (StepServer, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone());
+    (StepServer::from_builder(builder), clock)
+}
+
+/// Connects a client and waits for the server to read its Hello, by waiting for an update it sends
+/// to the greeting debuggable right after.
+fn greeted_client(step: &StepServer, greeting_id: usize, client_count: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(client_count));
+    client.send_update(greeting_id, "0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(greeting_id) == client_count));
+    assert!(poll_client_until(&mut client, |client, _| client.client_id().is_some()).is_some());
+    client
+}
+
+fn millis_of(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+#[test]
+fn remote_changes_are_attributed_to_their_client() {
+    let (step, clock) = server_with_clock();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let lives = DebuggableBuilder::new("lives", 3).scoped(step.scoped_server()).build();
+    let (greeting_id, lives_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("lives").unwrap())
+    };
+    let mut author = greeted_client(&step, greeting_id, 1);
+    let mut observer = greeted_client(&step, greeting_id, 2);
+    let author_id = author.client_id().unwrap();
+
+    clock.advance(Duration::from_secs(12));
+    author.send_update(lives_id, "7").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(lives_id) == 1));
+    assert_eq!(*lives, 7);
+    let changed_at = clock.now_system();
+    assert_eq!(step.handle().read().unwrap().last_change_info(lives_id), Some((changed_at, Some(author_id))));
+
+    let attributed = poll_client_until(&mut observer, |client, _| client.debuggable(lives_id).is_some_and(|lives| lives.value_in_json == "7"));
+    assert!(attributed.is_some());
+    let seen_by_observer = observer.debuggable(lives_id).unwrap();
+    assert_eq!((seen_by_observer.changed_at_ms, seen_by_observer.last_author), (Some(millis_of(changed_at)), Some(author_id)));
+}
+
+#[test]
+fn local_changes_have_no_author() {
+    let (step, clock) = server_with_clock();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let mut lives = DebuggableBuilder::new("lives", 3).scoped(step.scoped_server()).build();
+    let (greeting_id, lives_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("lives").unwrap())
+    };
+    let mut author = greeted_client(&step, greeting_id, 1);
+    author.send_update(lives_id, "7").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(lives_id) == 1));
+    assert_eq!(*lives, 7);
+    assert!(step.handle().read().unwrap().last_change_info(lives_id).unwrap().1.is_some());
+
+    clock.advance(Duration::from_secs(3));
+    *lives = 9;
+    assert_eq!(*lives, 9);
+    let changed_at = clock.now_system();
+    assert_eq!(step.handle().read().unwrap().last_change_info(lives_id), Some((changed_at, None)));
+
+    let notified = poll_client_until(&mut author, |client, _| client.debuggable(lives_id).is_some_and(|lives| lives.value_in_json == "9"));
+    assert!(notified.is_some());
+    let seen_by_author = author.debuggable(lives_id).unwrap();
+    assert_eq!((seen_by_author.changed_at_ms, seen_by_author.last_author), (Some(millis_of(changed_at)), None));
+}
@@ -0,0 +1,40 @@
+//! The clock servers read time from, and the manual one tests move by hand.
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::clock::Clock;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::{ManualClock, StepServer};
+
+#[test]
+fn manual_clock_only_moves_when_advanced() {
+    let clock = ManualClock::new();
+    let (start_instant, start_system) = (clock.now_instant(), clock.now_system());
+    assert_eq!(clock.now_instant(), start_instant);
+    assert_eq!(clock.now_system(), start_system);
+
+    clock.advance(Duration::from_millis(1500));
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(clock.elapsed(), Duration::from_secs(2));
+    assert_eq!(clock.now_instant() - start_instant, Duration::from_secs(2));
+    assert_eq!(clock.now_system().duration_since(start_system).unwrap(), Duration::from_secs(2));
+}
+
+#[test]
+fn clock_swapped_at_runtime_drives_expiry_from_then_on() {
+    let step = StepServer::new();
+    let ghost = DebuggableBuilder::new("ghost", 1).scoped(step.scoped_server()).ttl(Duration::from_secs(60)).build();
+    let clock = Arc::new(ManualClock::new());
+    step.handle().write().unwrap().set_clock(clock.clone());
+    assert_eq!(step.handle().read().unwrap().clock().now_instant(), clock.now_instant());
+
+    // Swapping the clock counts every debuggable as touched at its current time
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(step.handle().read().unwrap().expire_stale(), 0);
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(step.handle().read().unwrap().expire_stale(), 1);
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("ghost"), None);
+    drop(ghost);
+}
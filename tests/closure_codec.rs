@@ -0,0 +1,75 @@
+//! Debuggables serialized by plain functions kept on each debuggable through with_codec.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::UpdateOutcome;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::closure_codec::ClosureCodec;
+use debug_monitor::serializable::JSONDeSerializable;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Temperature(f32);
+
+fn to_celsius(temperature: &Temperature) -> Option<String> {
+    Some(format!("\"{}C\"", temperature.0))
+}
+
+fn from_celsius(json: &str) -> Option<Temperature> {
+    json.trim().strip_prefix('"')?.strip_suffix("C\"")?.parse().ok().map(Temperature)
+}
+
+fn to_fahrenheit(temperature: &Temperature) -> Option<String> {
+    Some((temperature.0 * 9.0 / 5.0 + 32.0).to_string())
+}
+
+fn from_fahrenheit(json: &str) -> Option<Temperature> {
+    json.trim().parse::<f32>().ok().map(|fahrenheit| Temperature((fahrenheit - 32.0) * 5.0 / 9.0))
+}
+
+#[test]
+fn debuggables_of_the_same_type_keep_their_own_codecs() {
+    let step = StepServer::new();
+    let indoor = DebuggableBuilder::with_codec("indoor", Temperature(20.0), to_celsius, from_celsius).scoped(step.scoped_server()).build();
+    let outdoor = DebuggableBuilder::with_codec("outdoor", Temperature(100.0), to_fahrenheit, from_fahrenheit).scoped(step.scoped_server()).build();
+    let indoor_id = step.handle().read().unwrap().debuggable_id_of("indoor").unwrap();
+    let outdoor_id = step.handle().read().unwrap().debuggable_id_of("outdoor").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+
+    // Registering the second codec didn't replace the first one
+    assert!(poll_client_until(&mut client, |client, _| {
+        client.debuggable(indoor_id).is_some_and(|indoor| indoor.value_in_json == "\"20C\"")
+            && client.debuggable(outdoor_id).is_some_and(|outdoor| outdoor.value_in_json == "212")
+    }).is_some());
+    assert_eq!(indoor.0, Temperature(20.0));
+    assert_eq!(outdoor.0, Temperature(100.0));
+}
+
+#[test]
+fn remote_updates_go_through_the_codec_of_the_debuggable() {
+    let step = StepServer::new();
+    let indoor = DebuggableBuilder::with_codec("indoor", Temperature(20.0), to_celsius, from_celsius).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("indoor").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|indoor| !indoor.value_in_json.is_empty())).is_some());
+
+    let accepted = client.send_tracked_update(id, "\"22.5C\"").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(indoor.0, Temperature(22.5));
+    // A Fahrenheit value isn't understood by the Celsius codec of this debuggable
+    let rejected = client.send_tracked_update(id, "72.5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(indoor.0, Temperature(22.5));
+
+    assert!(poll_client_until(&mut client, |client, _| !client.is_update_pending(rejected)).is_some());
+    assert_eq!(client.take_update_outcomes(), vec![(accepted, UpdateOutcome::Accepted), (rejected, UpdateOutcome::Rejected)]);
+}
+
+#[test]
+fn closure_codec_has_no_json_outside_of_a_debuggable() {
+    let wrapped = ClosureCodec::new(Temperature(20.0));
+    assert_eq!(wrapped.to_json(), None);
+    assert_eq!(ClosureCodec::<Temperature>::from_json("\"20C\""), None);
+    assert_eq!(wrapped.into_inner(), Temperature(20.0));
+}
@@ -0,0 +1,107 @@
+//! Client speaking the wire protocol over a raw socket, and helpers polling a server by hand.
+#![allow(dead_code)]
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+use debug_monitor::server::DebuggableServer;
+use debug_monitor::simple_tcp::server::Server;
+
+/// How long the helpers keep retrying before giving up.
+pub const POLL_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct WireClient {
+    stream: TcpStream,
+    endmark: String,
+    escape: String,
+    buffer: Vec<u8>,
+}
+
+impl WireClient {
+    pub fn connect(server: &DebuggableServer, addr: SocketAddr) -> Self {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let inner_server = server.read();
+        let end_mark = inner_server.message_endmark();
+        let (endmark, escape) = (end_mark.string().to_string(), end_mark.escape().to_string());
+        drop(inner_server);
+        Self { stream, endmark, escape, buffer: Vec::new() }
+    }
+
+    pub fn send(&mut self, message: &ClientUnitMessage) {
+        self.send_raw(&message.to_json().unwrap());
+    }
+
+    /// Frames and sends the text as is, whether or not it's a message.
+    pub fn send_raw(&mut self, text: &str) {
+        let frame = format!("{}{}", text.replace(&*self.endmark, &*self.escape), self.endmark);
+        self.stream.write_all(frame.as_bytes()).unwrap();
+    }
+
+    /// Messages fully received so far, without waiting for more.
+    pub fn receive(&mut self) -> Vec<ServerMessage> {
+        let mut read_buffer = [0_u8; 4096];
+        loop {
+            match self.stream.read(&mut read_buffer) {
+                Ok(0) => break,
+                Ok(read) => self.buffer.extend_from_slice(&read_buffer[..read]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => panic!("Reading from the server failed: {error}"),
+            }
+        }
+        let mut received = Vec::new();
+        let endmark = self.endmark.as_bytes();
+        while let Some(end) = self.buffer.windows(endmark.len()).position(|window| window == endmark) {
+            let frame = self.buffer.drain(..end + endmark.len()).take(end).collect::<Vec<_>>();
+            let message = String::from_utf8(frame).unwrap().replace(&*self.escape, &*self.endmark);
+            received.extend(ServerMessage::from_json(&message));
+        }
+        received
+    }
+
+    /// Receives until the condition holds, returning every message received meanwhile or None if it
+    /// didn't hold in time.
+    pub fn receive_until<Done: FnMut(&[ServerMessage]) -> bool>(&mut self, mut done: Done) -> Option<Vec<ServerMessage>> {
+        let give_up_at = Instant::now() + POLL_TIMEOUT;
+        let mut received = Vec::new();
+        loop {
+            received.extend(self.receive());
+            if done(&received) { return Some(received); }
+            if Instant::now() >= give_up_at { return None; }
+            thread::yield_now();
+        }
+    }
+}
+
+/// Accepts and reads clients until the condition holds.
+pub fn serve_until<Done: FnMut() -> bool>(server: &DebuggableServer, mut done: Done) -> bool {
+    let give_up_at = Instant::now() + POLL_TIMEOUT;
+    loop {
+        server.accept_incoming_not_blocking();
+        server.read_all_clients();
+        if done() { return true; }
+        if Instant::now() >= give_up_at { return false; }
+        thread::yield_now();
+    }
+}
+
+/// Accepts and reads clients while the client receives, until the condition holds on what it got.
+pub fn poll_until<Done: FnMut(&[ServerMessage]) -> bool>(server: &DebuggableServer, client: &mut WireClient, mut done: Done) -> Option<Vec<ServerMessage>> {
+    let mut received = Vec::new();
+    let is_done = serve_until(server, || {
+        received.extend(client.receive());
+        done(&received)
+    });
+    if is_done { Some(received) } else { None }
+}
+
+pub fn client_id_in(received: &[ServerMessage]) -> Option<usize> {
+    received.iter().find_map(|message| match message {
+        ServerMessage::GiveClientId { client_id } => Some(*client_id),
+        _ => None,
+    })
+}
@@ -0,0 +1,69 @@
+//! Compacting the storage of a server after thousands of short-lived debuggables came and went.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const CREATED: usize = 10_000;
+const KEPT_EVERY: usize = 100;
+
+/// Creates CREATED debuggables and drops all but one out of every KEPT_EVERY of them.
+fn fragmented(step: &StepServer) -> Vec<Debuggable<usize>> {
+    let mut debuggables = (0..CREATED)
+        .map(|index| DebuggableBuilder::new(format!("request{index}"), index).scoped(step.scoped_server()).build())
+        .collect::<Vec<_>>();
+    let mut index = 0;
+    debuggables.retain(|_| {
+        index += 1;
+        (index - 1) % KEPT_EVERY == 0
+    });
+    debuggables
+}
+
+/// Slots iterating the debuggables goes through, as ids are the index of their slot.
+fn spanned_slots(step: &StepServer) -> usize {
+    step.handle().read().unwrap().collect_infos().iter().map(|info| info.id + 1).max().unwrap_or(0)
+}
+
+#[test]
+fn compacting_ten_thousand_churned_debuggables_keeps_the_live_ones_working() {
+    let step = StepServer::new();
+    let mut kept = fragmented(&step);
+    let live = CREATED / KEPT_EVERY;
+    let report_before = step.handle().read().unwrap().memory_report();
+    assert_eq!(report_before.debuggables, live);
+    assert!(spanned_slots(&step) > CREATED - KEPT_EVERY, "{}", spanned_slots(&step));
+
+    let remapped = step.handle().read().unwrap().compact();
+    assert_eq!(remapped, live - 1);
+    assert_eq!(spanned_slots(&step), live);
+    let report_after = step.handle().read().unwrap().memory_report();
+    assert_eq!((report_after.debuggables, report_after.last_value_bytes), (live, report_before.last_value_bytes));
+
+    // Handles follow their new ids, both when writing and when clients update them
+    let last = kept.last_mut().unwrap();
+    let last_id = step.handle().read().unwrap().debuggable_id_of(&format!("request{}", CREATED - KEPT_EVERY)).unwrap();
+    assert_eq!(last_id, live - 1);
+    **last = 1;
+    assert_eq!(**last, 1);
+    assert_eq!(step.handle().read().unwrap().value_of(&format!("request{}", CREATED - KEPT_EVERY)).as_deref(), Some("1"));
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(last_id).is_some_and(|debuggable| debuggable.value_in_json == "1")).is_some());
+    client.send_update(last_id, "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(last_id) == 1));
+    assert_eq!(*kept[live - 1], 2);
+    assert_eq!(*kept[0], 0);
+}
+
+#[test]
+fn compacts_by_itself_past_the_removal_threshold() {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).compact_after_removals(CREATED / 2);
+    let step = StepServer::from_builder(builder);
+    let _kept = fragmented(&step);
+    step.housekeeping();
+    assert_eq!(spanned_slots(&step), CREATED / KEPT_EVERY);
+}
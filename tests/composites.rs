@@ -0,0 +1,92 @@
+//! Debuggables grouped into composites that clients show as a single widget.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::{DebuggableClient, RemoteComposite};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{CompositeKind, JSONDeSerializable, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const KINDS: [CompositeKind; 4] = [CompositeKind::Vec2, CompositeKind::Vec3, CompositeKind::ColorRgba, CompositeKind::MinMaxRange];
+
+fn id_on(step: &StepServer, name: &str) -> usize {
+    step.handle().read().unwrap().debuggable_id_of(name).unwrap()
+}
+
+/// Connects a client and waits for the server to read its Hello, composites are only sent to
+/// clients known to understand them.
+fn greeted_client(step: &StepServer, debuggable_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(debuggable_id, "0.0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(debuggable_id) == 1));
+    client
+}
+
+#[test]
+fn composite_messages_round_trip() {
+    for kind in KINDS {
+        let composite = ServerMessage::Composite { name: "player_pos".to_string(), kind, members: (0..kind.slot_count()).collect() };
+        assert_eq!(ServerMessage::from_json(&composite.to_json().unwrap()), Some(composite));
+    }
+    let dissolved = ServerMessage::CompositeDissolved { name: "player_pos".to_string() };
+    assert_eq!(ServerMessage::from_json(&dissolved.to_json().unwrap()), Some(dissolved));
+}
+
+#[test]
+fn composite_is_announced_once_every_slot_is_taken() {
+    let step = StepServer::new();
+    let x = DebuggableBuilder::new("pos_x", 1.0).composite_member("player_pos", CompositeKind::Vec3, 0).scoped(step.scoped_server()).build();
+    let mut client = greeted_client(&step, id_on(&step, "pos_x"));
+    let y = DebuggableBuilder::new("pos_y", 2.0).composite_member("player_pos", CompositeKind::Vec3, 1).scoped(step.scoped_server()).build();
+    let z = DebuggableBuilder::new("pos_z", 3.0).composite_member("player_pos", CompositeKind::Vec3, 2).scoped(step.scoped_server()).build();
+    let members = vec![id_on(&step, "pos_x"), id_on(&step, "pos_y"), id_on(&step, "pos_z")];
+    let received = poll_client_until(&mut client, |client, _| client.composite("player_pos").is_some()).unwrap();
+    assert_eq!(client.composite("player_pos"), Some(&RemoteComposite { kind: CompositeKind::Vec3, members: members.clone() }));
+    assert_eq!(received.iter().filter(|message| matches!(message, ServerMessage::Composite { .. })).count(), 1);
+
+    let mut late_client = step.connect().unwrap();
+    assert!(step.accept_until(2));
+    late_client.send_update(members[1], "0.0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(members[1]) == 1));
+    assert!(poll_client_until(&mut late_client, |client, _| client.composite("player_pos").is_some_and(|composite| composite.members == members)).is_some());
+    drop((x, y, z));
+}
+
+#[test]
+fn dropping_a_member_dissolves_the_composite() {
+    let step = StepServer::new();
+    let build_member = |name: &str, slot: usize| DebuggableBuilder::new(name, 0.5).composite_member("bounds", CompositeKind::MinMaxRange, slot).scoped(step.scoped_server()).build();
+    let min = build_member("min", 0);
+    let max = build_member("max", 1);
+    let mut client = greeted_client(&step, id_on(&step, "min"));
+    assert!(poll_client_until(&mut client, |client, _| client.composite("bounds").is_some()).is_some());
+
+    drop(max);
+    let dissolved = |_: &DebuggableClient, received: &[ServerMessage]| received.iter()
+        .any(|message| matches!(message, ServerMessage::CompositeDissolved { name } if name == "bounds"));
+    assert!(poll_client_until(&mut client, dissolved).is_some());
+    assert_eq!(client.composite("bounds"), None);
+
+    let _max = build_member("max", 1);
+    let new_max_id = id_on(&step, "max");
+    assert!(poll_client_until(&mut client, |client, _| client.composite("bounds").is_some_and(|composite| composite.members[1] == new_max_id)).is_some());
+    drop(min);
+}
+
+#[test]
+fn declared_composites_need_one_registered_member_per_slot() {
+    let step = StepServer::new();
+    let channels = ["r", "g", "b", "a"].map(|name| DebuggableBuilder::new(name, 255).scoped(step.scoped_server()).build());
+    let ids = ["r", "g", "b", "a"].map(|name| id_on(&step, name)).to_vec();
+    let mut client = greeted_client(&step, ids[0]);
+    let handle = step.handle();
+    assert!(!handle.read().unwrap().declare_composite("tint", CompositeKind::ColorRgba, ids[..3].to_vec()));
+    assert!(!handle.read().unwrap().declare_composite("tint", CompositeKind::Vec2, vec![ids[0], 9_999]));
+    assert!(handle.read().unwrap().declare_composite("tint", CompositeKind::ColorRgba, ids.clone()));
+    assert!(poll_client_until(&mut client, |client, _| client.composite("tint").is_some_and(|composite| composite.members == ids)).is_some());
+
+    assert!(handle.read().unwrap().kick_debuggable(ids[2]));
+    assert!(poll_client_until(&mut client, |client, _| client.composite("tint").is_none()).is_some());
+    assert!(!handle.read().unwrap().remove_composite("tint"));
+    drop(channels);
+}
@@ -0,0 +1,68 @@
+//! Large values sent deflated to clients advertising it in their Hello, and plain to the others.
+#![cfg(feature = "compression")]
+
+mod common;
+
+use common::WireClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage, PROTOCOL_VERSION};
+use debug_monitor::server::compression::{decompress, DEFLATE_ENCODING};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Around 210KB of JSON, repetitive enough to deflate well.
+fn large_curve() -> Vec<u32> {
+    (0..55_000).map(|index| index % 1_000).collect()
+}
+
+fn say_hello(client: &mut WireClient, supports_deflate: bool, handshake_id: usize) {
+    client.send(&ClientUnitMessage::Hello { protocol_version: PROTOCOL_VERSION, supports_deflate, panel: None, supports_text_patches: false, session_key: None });
+    client.send(&ClientUnitMessage::UpdateValue { id: handshake_id, new_value: "0".to_string(), request_id: None, panel: None });
+}
+
+#[test]
+fn large_value_goes_deflated_only_to_clients_supporting_it() {
+    let step = StepServer::new();
+    let _handshake = DebuggableBuilder::new("handshake", 0).scoped(step.scoped_server()).build();
+    let mut curve = DebuggableBuilder::new("curve", vec![0_u32]).scoped(step.scoped_server()).build();
+    let handshake_id = step.handle().read().unwrap().debuggable_id_of("handshake").unwrap();
+    let id = step.handle().read().unwrap().debuggable_id_of("curve").unwrap();
+    let mut deflating = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    let mut plain = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    assert!(step.accept_until(2));
+    say_hello(&mut deflating, true, handshake_id);
+    say_hello(&mut plain, false, handshake_id);
+    assert!(step.read_until(|server| server.pending_updates_of(handshake_id) == 2));
+
+    *curve = large_curve();
+    let json = large_curve().to_json().unwrap();
+    assert!(json.len() > 200 * 1024, "{}", json.len());
+    let encoded = deflating.receive_until(|received| received.iter().any(|message| matches!(message, ServerMessage::NotifyEncoded { id: notified, .. } if *notified == id))).unwrap();
+    let encoded = encoded.into_iter().find(|message| matches!(message, ServerMessage::NotifyEncoded { .. })).unwrap();
+    let plain_notify = plain.receive_until(|received| received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, value_in_json, .. } if *notified == id && *value_in_json == json))).unwrap();
+    assert!(!plain_notify.iter().any(|message| matches!(message, ServerMessage::NotifyEncoded { .. })));
+    let plain_notify = plain_notify.into_iter().find(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id)).unwrap();
+
+    let ServerMessage::NotifyEncoded { encoding, value_in_json: compressed, .. } = &encoded else { unreachable!() };
+    assert_eq!(encoding, DEFLATE_ENCODING);
+    assert_eq!(decompress(encoding, compressed).as_ref(), Some(&json));
+    let (encoded_size, plain_size) = (encoded.to_json().unwrap().len(), plain_notify.to_json().unwrap().len());
+    assert!(encoded_size * 10 < plain_size, "Deflated to {encoded_size} bytes out of {plain_size}");
+}
+
+#[test]
+fn debuggable_client_decompresses_transparently() {
+    let step = StepServer::new();
+    let _handshake = DebuggableBuilder::new("handshake", 0).scoped(step.scoped_server()).build();
+    let mut curve = DebuggableBuilder::new("curve", vec![0_u32]).scoped(step.scoped_server()).build();
+    let handshake_id = step.handle().read().unwrap().debuggable_id_of("handshake").unwrap();
+    let id = step.handle().read().unwrap().debuggable_id_of("curve").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(handshake_id, "0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(handshake_id) == 1));
+
+    *curve = large_curve();
+    let json = large_curve().to_json().unwrap();
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|curve| curve.value_in_json == json)).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::NotifyEncoded { id: notified, .. } if *notified == id)));
+}
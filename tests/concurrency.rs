@@ -0,0 +1,124 @@
+//! Interleavings of clients and debuggables scripted step by step through testing::StepServer.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::{ClientEvent, DebuggableClient, UpdateOutcome};
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn value_seen_by(client: &DebuggableClient, id: usize) -> Option<&str> {
+    client.debuggable(id).map(|debuggable| debuggable.value_in_json.as_str())
+}
+
+fn connect_seeing(step: &StepServer, client_count: usize, id: usize, value_json: &str) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(client_count));
+    assert!(poll_client_until(&mut client, |client, _| value_seen_by(client, id) == Some(value_json)).is_some());
+    client
+}
+
+fn counter_on(step: &StepServer, keep: bool) -> (Debuggable<i32>, usize) {
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).set_is_keep(keep).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    (counter, id)
+}
+
+fn read_pending(step: &StepServer, id: usize, pending: usize) {
+    assert!(step.read_until(|server| server.pending_updates_of(id) == pending));
+}
+
+#[test]
+fn update_arriving_between_local_write_and_sync_wins() {
+    let step = StepServer::new();
+    let (mut counter, id) = counter_on(&step, false);
+    let mut client = connect_seeing(&step, 1, id, "1");
+    *counter = 5;
+    client.send_update(id, "7").unwrap();
+    read_pending(&step, id, 1);
+    assert_eq!(*counter, 7);
+    assert_eq!(step.handle().read().unwrap().value_of("counter").as_deref(), Some("7"));
+}
+
+#[test]
+fn client_connecting_between_local_write_and_sync_gets_the_new_value() {
+    let step = StepServer::new();
+    let (mut counter, id) = counter_on(&step, false);
+    let mut first_client = connect_seeing(&step, 1, id, "1");
+    *counter = 2;
+    let mut second_client = connect_seeing(&step, 2, id, "1");
+    assert_eq!(*counter, 2);
+    for client in [&mut first_client, &mut second_client] {
+        assert!(poll_client_until(client, |client, _| value_seen_by(client, id) == Some("2")).is_some());
+    }
+}
+
+#[test]
+fn dropping_a_debuggable_with_a_queued_update_removes_it() {
+    let step = StepServer::new();
+    let (counter, id) = counter_on(&step, false);
+    let mut client = connect_seeing(&step, 1, id, "1");
+    client.send_update(id, "7").unwrap();
+    read_pending(&step, id, 1);
+    drop(counter);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_none()).is_some());
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("counter"), None);
+}
+
+#[test]
+fn update_queued_for_a_dropped_kept_debuggable_reaches_its_successor() {
+    let step = StepServer::new();
+    let (counter, id) = counter_on(&step, true);
+    let mut client = connect_seeing(&step, 1, id, "1");
+    client.send_update(id, "7").unwrap();
+    read_pending(&step, id, 1);
+    drop(counter);
+    let (counter, successor_id) = counter_on(&step, true);
+    assert_eq!(successor_id, id);
+    assert_eq!(*counter, 7);
+}
+
+#[test]
+fn conflicting_optimistic_updates_converge_on_the_winner() {
+    let step = StepServer::new();
+    let (counter, id) = counter_on(&step, false);
+    let mut clients = [connect_seeing(&step, 1, id, "1"), connect_seeing(&step, 2, id, "1")];
+    let mut request_ids = Vec::new();
+    for (client, value) in clients.iter_mut().zip(["10", "20"]) {
+        client.set_optimistic(true);
+        request_ids.push(client.send_tracked_update(id, value).unwrap());
+        assert_eq!(value_seen_by(client, id), Some(value));
+    }
+    read_pending(&step, id, 2);
+    let winner = *counter;
+    assert!(winner == 10 || winner == 20);
+    let winner_json = winner.to_string();
+    for (client, request_id) in clients.iter_mut().zip(request_ids) {
+        let is_loser = value_seen_by(client, id) != Some(winner_json.as_str());
+        assert!(poll_client_until(client, |client, _| !client.is_update_pending(request_id)).is_some());
+        assert_eq!(value_seen_by(client, id), Some(winner_json.as_str()));
+        let outcomes = client.take_update_outcomes();
+        let expected_outcome = if is_loser { UpdateOutcome::Rejected } else { UpdateOutcome::Accepted };
+        assert_eq!(outcomes, vec![(request_id, expected_outcome)]);
+        assert_eq!(client.take_events().contains(&ClientEvent::Reverted { id }), is_loser);
+    }
+}
+
+#[test]
+fn rejected_update_is_corrected_only_for_its_sender() {
+    let step = StepServer::new();
+    let (counter, id) = counter_on(&step, false);
+    let mut sender = connect_seeing(&step, 1, id, "1");
+    let mut bystander = connect_seeing(&step, 2, id, "1");
+    sender.send_update(id, "\"not a number\"").unwrap();
+    read_pending(&step, id, 1);
+    assert_eq!(*counter, 1);
+    let corrected = |_: &DebuggableClient, received: &[ServerMessage]| {
+        received.iter().any(|message| matches!(message, ServerMessage::Error { .. }))
+            && received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id))
+    };
+    assert!(poll_client_until(&mut sender, corrected).is_some());
+    assert_eq!(value_seen_by(&sender, id), Some("1"));
+    let bystander_received = bystander.poll().unwrap();
+    assert!(!bystander_received.iter().any(|message| matches!(message, ServerMessage::Error { .. } | ServerMessage::Notify { .. })));
+    assert_eq!(value_seen_by(&bystander, id), Some("1"));
+}
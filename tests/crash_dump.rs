@@ -0,0 +1,105 @@
+//! Last values of debuggables appended to panic reports, read without taking any server's lock.
+#![cfg(feature = "server")]
+
+use std::sync::Mutex;
+use std::thread;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::server::crash_dump::{current_dump, install_panic_hook_with, MAX_DUMP_BYTES};
+use debug_monitor::DumpSelection;
+
+static REPORTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Installs the hook every test reports to, only the first call takes effect.
+fn install() {
+    install_panic_hook_with(DumpSelection::Group("crash/".to_string()), |dump| REPORTS.lock().unwrap().push(dump.to_string()));
+}
+
+fn reported(pair: &str) -> bool {
+    REPORTS.lock().unwrap().iter().any(|report| report.contains(pair))
+}
+
+#[test]
+fn panics_holding_the_server_lock_report_the_values() {
+    install();
+    let server = ScopedServer::new();
+    let mut speed = DebuggableBuilder::new("crash/speed", 1).scoped(&server).build();
+    let _unselected = DebuggableBuilder::new("unselected", 5).scoped(&server).build();
+    *speed = 42;
+    assert_eq!(*speed, 42);
+
+    let handle = server.handle();
+    let panicked = thread::spawn(move || {
+        let _locked = handle.write().unwrap();
+        panic!("panicking while holding the server");
+    }).join();
+    assert!(panicked.is_err());
+    assert!(reported("crash/speed=42"));
+    assert!(!reported("unselected="));
+}
+
+#[test]
+fn panics_while_another_thread_holds_the_server_report_the_values() {
+    install();
+    let server = ScopedServer::new();
+    let mut gravity = DebuggableBuilder::new("crash/gravity", 9.5).scoped(&server).build();
+    *gravity = -1.5;
+    assert_eq!(*gravity, -1.5);
+
+    let handle = server.handle();
+    let locked = handle.read().unwrap();
+    let panicked = thread::spawn(|| panic!("panicking while the server is held")).join();
+    drop(locked);
+    assert!(panicked.is_err());
+    assert!(reported("crash/gravity=-1.5"));
+}
+
+#[test]
+fn dumps_stay_within_their_size_and_forget_removed_debuggables() {
+    install();
+    let server = ScopedServer::new();
+    let large_values = (0..8)
+        .map(|index| DebuggableBuilder::new(format!("crash/large/{index}"), "x".repeat(MAX_DUMP_BYTES / 4)).scoped(&server).build())
+        .collect::<Vec<_>>();
+    let dump = current_dump().unwrap();
+    let (pairs, left_out) = dump.split_once(" ...and ").unwrap();
+    assert!(pairs.len() <= MAX_DUMP_BYTES, "{} bytes dumped", pairs.len());
+    assert!(left_out.ends_with(" more"));
+
+    drop(large_values);
+    let removed = DebuggableBuilder::new("crash/removed", 7).scoped(&server).build();
+    assert!(current_dump().unwrap().contains("crash/removed=7"));
+    drop(removed);
+    assert!(!current_dump().unwrap().contains("crash/removed="));
+    assert!(!current_dump().unwrap().contains("crash/large/"));
+}
+
+#[test]
+fn debuggables_sharing_a_name_across_servers_are_dumped_apart() {
+    install();
+    let first_server = ScopedServer::new();
+    let second_server = ScopedServer::new();
+    let first = DebuggableBuilder::new("crash/shared", 1).scoped(&first_server).build();
+    let second = DebuggableBuilder::new("crash/shared", 2).scoped(&second_server).build();
+    let dump = current_dump().unwrap();
+    assert!(dump.contains("crash/shared=1") && dump.contains("crash/shared=2"));
+
+    drop(first);
+    let dump = current_dump().unwrap();
+    assert!(!dump.contains("crash/shared=1"));
+    assert!(dump.contains("crash/shared=2"));
+    drop(second);
+    assert!(!current_dump().unwrap().contains("crash/shared="));
+}
+
+#[test]
+fn selections_match_names_and_groups() {
+    assert!(DumpSelection::All.selects("anything"));
+    let names = DumpSelection::Names(vec!["camera/near".to_string()]);
+    assert!(names.selects("camera/near"));
+    assert!(!names.selects("camera/far"));
+    let group = DumpSelection::Group("camera/".to_string());
+    assert!(group.selects("camera/far"));
+    assert!(!group.selects("player/speed"));
+}
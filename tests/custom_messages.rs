@@ -0,0 +1,47 @@
+//! App-specific messages exchanged with clients under a topic, next to the core protocol.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+use debug_monitor::server::{DebuggableServer, Who};
+
+use common::{client_id_in, poll_until, serve_until, WireClient};
+
+#[test]
+fn custom_messages_round_trip() {
+    let from_client = ClientUnitMessage::Custom { topic: "profiler".to_string(), payload: "{\"frame\":3}".to_string() }.to_json().unwrap();
+    let from_client = ClientUnitMessage::from_json(&from_client).unwrap();
+    assert!(matches!(from_client, ClientUnitMessage::Custom { topic, payload } if topic == "profiler" && payload == "{\"frame\":3}"));
+    let from_server = ServerMessage::Custom { topic: "profiler".to_string(), payload: "line\nbreak".to_string() }.to_json().unwrap();
+    let from_server = ServerMessage::from_json(&from_server).unwrap();
+    assert!(matches!(from_server, ServerMessage::Custom { topic, payload } if topic == "profiler" && payload == "line\nbreak"));
+}
+
+#[test]
+fn custom_messages_reach_the_handler_of_their_topic() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut server = DebuggableServer::new(listener);
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let handled = received.clone();
+    server.on_custom("profiler", Box::new(move |client_id, payload| {
+        handled.lock().unwrap().push((client_id, payload.to_string()));
+    }));
+    let mut client = WireClient::connect(&server, addr);
+    let client_id = client_id_in(&poll_until(&server, &mut client, |received| client_id_in(received).is_some()).unwrap()).unwrap();
+
+    // Topics without a handler are dropped without affecting the client
+    client.send(&ClientUnitMessage::Custom { topic: "unknown".to_string(), payload: "ignored".to_string() });
+    client.send(&ClientUnitMessage::Custom { topic: "profiler".to_string(), payload: "start".to_string() });
+    assert!(serve_until(&server, || !received.lock().unwrap().is_empty()));
+    assert_eq!(*received.lock().unwrap(), vec![(client_id, "start".to_string())]);
+
+    server.send_custom(Who::All, "profiler", "stop");
+    assert!(client.receive_until(|received| received.iter().any(|message| {
+        matches!(message, ServerMessage::Custom { topic, payload } if topic == "profiler" && payload == "stop")
+    })).is_some());
+}
@@ -0,0 +1,54 @@
+//! Debuggables of different types registered together by a DebuggableGroupBuilder.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::debuggable_group::{DebuggableGroupBuilder, GroupTakeError};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Connects a client and waits until the server read its Hello, which precedes the update sent
+/// for the given debuggable, so the server knows it takes NotifyMany.
+fn connect_announced(step: &StepServer, handshake_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(handshake_id, "0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(handshake_id) == 1));
+    client
+}
+
+#[test]
+fn group_of_mixed_types_reaches_a_connected_client_as_one_notify_many() {
+    let step = StepServer::new();
+    let _handshake = DebuggableBuilder::new("handshake", 0).scoped(step.scoped_server()).build();
+    let handshake_id = step.handle().read().unwrap().debuggable_id_of("handshake").unwrap();
+    let mut client = connect_announced(&step, handshake_id);
+
+    let mut group = DebuggableGroupBuilder::new(step.handle())
+        .add("speed", 1.5_f32)
+        .add("enabled", true)
+        .add_with(|entry| entry.name("label").initial("ready".to_string()).order(2))
+        .build();
+    let ids = ["speed", "enabled", "label"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let received = poll_client_until(&mut client, |client, _| {
+        ids.iter().all(|id| client.debuggable(*id).is_some_and(|debuggable| !debuggable.value_in_json.is_empty()))
+    }).unwrap();
+
+    let batches = received.iter()
+        .filter_map(|message| match message {
+            ServerMessage::NotifyMany { notifies } => Some(notifies.iter().map(|notify| notify.id).collect::<Vec<_>>()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(batches, vec![ids.to_vec()]);
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Notify { .. })), "{received:?}");
+    let values = ids.map(|id| client.debuggable(id).unwrap().value_in_json.clone());
+    assert_eq!(values, ["1.5".to_string(), "true".to_string(), "\"ready\"".to_string()]);
+
+    assert_eq!(*group.take::<f32>("speed").unwrap(), 1.5);
+    let mismatch = group.take::<i32>("enabled").map(|_| ()).unwrap_err();
+    assert_eq!(mismatch, GroupTakeError::TypeMismatch { name: "enabled".to_string(), expected: "i32", found: "bool" });
+    assert!(mismatch.to_string().contains("holds a value of type bool, but i32 was requested"));
+    assert!(*group.take::<bool>("enabled").unwrap());
+    assert_eq!(group.take::<String>("speed").map(|_| ()).unwrap_err(), GroupTakeError::NotFound { name: "speed".to_string() });
+}
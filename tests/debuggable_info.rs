@@ -0,0 +1,58 @@
+//! Walking every debuggable of a server through borrowed views and owned snapshots of them.
+#![cfg(feature = "server")]
+
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_info::OwnedDebuggableInfo;
+use debug_monitor::testing::StepServer;
+
+#[test]
+fn visits_and_collects_three_debuggables() {
+    let step = StepServer::new();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).order(2).build();
+    let limit = DebuggableBuilder::new("limit", Some(10)).scoped(step.scoped_server()).ttl(Duration::from_secs(30)).build();
+    let token = DebuggableBuilder::new("token", "hunter2".to_string()).scoped(step.scoped_server()).redact(|_| "\"***\"".to_string()).build();
+    // Reading them syncs their values to the server
+    assert_eq!((*speed, *limit, token.len()), (1, Some(10), 7));
+    let ids = ["speed", "limit", "token"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(ids[0], "4").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(ids[0]) == 1));
+
+    let mut visited = Vec::new();
+    step.handle().read().unwrap().for_each_debuggable(|info| {
+        visited.push((info.id(), info.name().to_string(), info.last_value_json().map(str::to_string), info.pending_updates()))
+    });
+    visited.sort();
+    let mut expected = vec![
+        (ids[0], "speed".to_string(), Some("1".to_string()), 1),
+        (ids[1], "limit".to_string(), Some("10".to_string()), 0),
+        (ids[2], "token".to_string(), Some("\"hunter2\"".to_string()), 0),
+    ];
+    expected.sort();
+    assert_eq!(visited, expected);
+
+    let infos = step.handle().read().unwrap().collect_infos();
+    let info_of = |id: usize| infos.iter().find(|info| info.id == id).unwrap().clone();
+    assert_eq!(infos.len(), 3);
+    assert_eq!(info_of(ids[0]), OwnedDebuggableInfo {
+        id: ids[0],
+        name: "speed".to_string(),
+        last_value_json: Some("1".to_string()),
+        pending_updates: 1,
+        is_hidden: false,
+        is_nullable: false,
+        is_kept: false,
+        is_redacted: false,
+        order: 2,
+        revision: info_of(ids[0]).revision,
+        change_generation: info_of(ids[0]).change_generation,
+        ttl: None,
+        is_read_only: false,
+    });
+    assert!(info_of(ids[1]).is_nullable && info_of(ids[1]).ttl == Some(Duration::from_secs(30)));
+    assert!(info_of(ids[2]).is_redacted && info_of(ids[2]).order == 0);
+}
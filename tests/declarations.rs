@@ -0,0 +1,46 @@
+//! Debuggables declared by the server before the host registers them.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::declarations::DeclaredOptions;
+use debug_monitor::server::events::ServerEvent;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn registering_a_declared_debuggable_adopts_its_id_as_host_code() {
+    let step = StepServer::new();
+    let declared_id = step.handle().read().unwrap().declare("speed", DeclaredOptions::default());
+    let events = step.handle().read().unwrap().events();
+
+    let speed = DebuggableBuilder::new("speed", 3).scoped(step.scoped_server()).build();
+    assert_eq!(*speed, 3);
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("speed"), Some(declared_id));
+    // Replays don't emit DebuggableAdded, only debuggables registered by the host do
+    let added = events.try_iter().any(|event| matches!(event, ServerEvent::DebuggableAdded { id, .. } if id == declared_id));
+    assert!(added);
+}
+
+#[test]
+fn clients_see_the_placeholder_until_the_host_fills_in_its_value() {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .declare("physics/gravity", DeclaredOptions::default().order(4));
+    let step = StepServer::from_builder(builder);
+    let declared_id = step.handle().read().unwrap().debuggable_id_of("physics/gravity").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(declared_id).is_some_and(|gravity| gravity.order == 4)).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Added { id, name, .. } if *id == declared_id && name == "physics/gravity")), "{received:?}");
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Notify { .. })), "{received:?}");
+    assert!(client.debuggable(declared_id).unwrap().value_in_json.is_empty());
+
+    let gravity = DebuggableBuilder::new("physics/gravity", -9.8).scoped(step.scoped_server()).build();
+    assert_eq!(*gravity, -9.8);
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("physics/gravity"), Some(declared_id));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(declared_id).is_some_and(|gravity| gravity.value_in_json == "-9.8")).is_some());
+    assert_eq!(client.debuggable(declared_id).unwrap().order, 4);
+    assert_eq!(client.debuggables().len(), 1);
+}
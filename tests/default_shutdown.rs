@@ -0,0 +1,37 @@
+//! Shutting the default server down, as done when the process exits.
+#![cfg(feature = "server")]
+
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+use std::time::Instant;
+
+use debug_monitor::debuggable::Debuggable;
+use debug_monitor::default_server::{default_server, set_default_server_initializer};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::STEP_TIMEOUT;
+
+// The default server is global, so this file holds a single test
+#[test]
+fn connected_client_receives_remove_all_before_eof() {
+    set_default_server_initializer(|| DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()));
+    let mut score = Debuggable::new("score", 1);
+    let mut client = TcpStream::connect(default_server().read().unwrap().local_addr().unwrap()).unwrap();
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    // Syncing a debuggable polls the default server, which accepts the client
+    while *score == 1 && default_server().read().unwrap().client_count() == 0 {
+        assert!(Instant::now() < give_up_at, "The client was never accepted");
+    }
+
+    debug_monitor::shutdown_default_server();
+    client.set_read_timeout(Some(STEP_TIMEOUT)).unwrap();
+    let mut received = String::new();
+    client.read_to_string(&mut received).unwrap();
+    assert!(received.contains("RemoveAll"), "received {received}");
+
+    assert!(default_server().read().unwrap().is_shut_down());
+    *score = 2;
+    assert_eq!(*score, 2);
+    drop(score);
+    let late = Debuggable::new("late", 3);
+    assert!(!late.is_server_alive());
+}
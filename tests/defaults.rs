@@ -0,0 +1,87 @@
+//! Name prefixes and options debuggables pick up from the server they're built on.
+#![cfg(feature = "server")]
+
+use std::thread;
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::server::debuggable_info::OwnedDebuggableInfo;
+use debug_monitor::server::defaults::DebuggableDefaults;
+
+fn info_of(server: &ScopedServer, name: &str) -> OwnedDebuggableInfo {
+    server.handle().read().unwrap().collect_infos().into_iter().find(|info| info.name == name).unwrap()
+}
+
+#[test]
+fn guards_prefix_names_while_alive() {
+    let server = ScopedServer::new();
+    let outside = DebuggableBuilder::new("speed", 1).scoped(&server).build();
+    let (inside, nested) = {
+        let _plugin = server.handle().read().unwrap().scoped_defaults("pluginA/");
+        let inside = DebuggableBuilder::new("speed", 2).scoped(&server).build();
+        let _nested = server.handle().read().unwrap().scoped_defaults("physics/");
+        (inside, DebuggableBuilder::new("gravity", 3).scoped(&server).build())
+    };
+    let after = DebuggableBuilder::new("after", 4).scoped(&server).build();
+
+    let handle = server.handle();
+    let server_data = handle.read().unwrap();
+    assert_eq!(server_data.value_of("speed").as_deref(), Some("1"));
+    assert_eq!(server_data.value_of("pluginA/speed").as_deref(), Some("2"));
+    assert_eq!(server_data.value_of("pluginA/physics/gravity").as_deref(), Some("3"));
+    assert_eq!(server_data.value_of("after").as_deref(), Some("4"));
+    assert!(server_data.debuggable_id_of("gravity").is_none());
+    drop(server_data);
+    assert_eq!((*outside, *inside, *nested, *after), (1, 2, 3, 4));
+}
+
+#[test]
+fn registering_again_keeps_the_prefix() {
+    let server = ScopedServer::new();
+    let mut speed = {
+        let _plugin = server.handle().read().unwrap().scoped_defaults("pluginA/");
+        DebuggableBuilder::new("speed", 1).scoped(&server).build()
+    };
+    let id = server.handle().read().unwrap().debuggable_id_of("pluginA/speed").unwrap();
+    assert!(server.handle().read().unwrap().kick_debuggable(id));
+    *speed = 5;
+    assert_eq!(*speed, 5);
+    assert_eq!(server.handle().read().unwrap().value_of("pluginA/speed").as_deref(), Some("5"));
+    assert!(server.handle().read().unwrap().debuggable_id_of("speed").is_none());
+}
+
+#[test]
+fn builder_options_override_the_defaults() {
+    let server = ScopedServer::new();
+    server.handle().write().unwrap().set_default_options(DebuggableDefaults::default().prefix("app/").order(5).read_only().ttl(Duration::from_secs(60)));
+    let _defaulted = DebuggableBuilder::new("defaulted", 0).scoped(&server).build();
+    let _ordered = DebuggableBuilder::new("ordered", 0).scoped(&server).order(2).ttl(Duration::from_secs(1)).build();
+    let _scoped = {
+        let _plugin = server.handle().read().unwrap().scoped_defaults_with(DebuggableDefaults::default().prefix("pluginA/").order(7));
+        DebuggableBuilder::new("scoped", 0).scoped(&server).build()
+    };
+
+    let defaulted = info_of(&server, "app/defaulted");
+    assert_eq!((defaulted.order, defaulted.is_read_only, defaulted.ttl), (5, true, Some(Duration::from_secs(60))));
+    let ordered = info_of(&server, "app/ordered");
+    assert_eq!((ordered.order, ordered.ttl), (2, Some(Duration::from_secs(1))));
+    let scoped = info_of(&server, "app/pluginA/scoped");
+    assert_eq!((scoped.order, scoped.is_read_only), (7, true));
+}
+
+#[test]
+fn guards_only_apply_to_their_server_and_thread() {
+    let server = ScopedServer::new();
+    let other_server = ScopedServer::new();
+    let _plugin = server.handle().read().unwrap().scoped_defaults("pluginA/");
+    let _other = DebuggableBuilder::new("other", 0).scoped(&other_server).build();
+    assert!(other_server.handle().read().unwrap().debuggable_id_of("other").is_some());
+
+    let handle = server.handle();
+    thread::spawn(move || {
+        let _threaded = DebuggableBuilder::new("threaded", 0).server(Some(handle)).keep().build();
+    }).join().unwrap();
+    assert!(server.handle().read().unwrap().value_of("threaded").is_some());
+    assert!(server.handle().read().unwrap().value_of("pluginA/threaded").is_none());
+}
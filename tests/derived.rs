@@ -0,0 +1,117 @@
+//! Read-only debuggables computed by the host from other debuggables.
+#![cfg(feature = "server")]
+
+use std::cell::RefCell;
+use std::net::TcpListener;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::debuggable::derived_debuggable::DerivedDebuggable;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::DEFAULT_DERIVED_REFRESH_INTERVAL;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+type Input = Rc<RefCell<Debuggable<i32>>>;
+
+fn server_with_clock() -> (StepServer, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone());
+    (StepServer::from_builder(builder), clock)
+}
+
+fn input_on(step: &StepServer, name: &str, value: i32) -> Input {
+    Rc::new(RefCell::new(DebuggableBuilder::new(name, value).scoped(step.scoped_server()).build()))
+}
+
+fn entities_per_chunk_on(step: &StepServer, entities: &Input, chunks: &Input) -> DerivedDebuggable<i32> {
+    let (entities, chunks) = (entities.clone(), chunks.clone());
+    DerivedDebuggable::scoped(step.scoped_server(), "entities_per_chunk", move || **entities.borrow() / **chunks.borrow())
+}
+
+fn value_of(step: &StepServer, name: &str) -> Option<String> {
+    step.handle().read().unwrap().value_of(name)
+}
+
+/// Runs housekeeping once the derived refresh interval passed.
+fn tick(step: &StepServer, clock: &ManualClock) {
+    clock.advance(DEFAULT_DERIVED_REFRESH_INTERVAL);
+    step.housekeeping();
+}
+
+#[test]
+fn derived_value_follows_its_inputs_on_the_next_tick() {
+    let (step, clock) = server_with_clock();
+    let (entities, chunks) = (input_on(&step, "entities", 120), input_on(&step, "chunks", 4));
+    let _entities_per_chunk = entities_per_chunk_on(&step, &entities, &chunks);
+    assert_eq!(value_of(&step, "entities_per_chunk").as_deref(), Some("30"));
+    tick(&step, &clock);
+    **entities.borrow_mut() = 200;
+    **chunks.borrow_mut() = 5;
+    assert_eq!(value_of(&step, "entities_per_chunk").as_deref(), Some("30"));
+    tick(&step, &clock);
+    assert_eq!(value_of(&step, "entities_per_chunk").as_deref(), Some("40"));
+}
+
+#[test]
+fn refreshes_from_polling_are_throttled() {
+    let (step, clock) = server_with_clock();
+    let (entities, chunks) = (input_on(&step, "entities", 120), input_on(&step, "chunks", 4));
+    let _entities_per_chunk = entities_per_chunk_on(&step, &entities, &chunks);
+    tick(&step, &clock);
+    **entities.borrow_mut() = 200;
+    step.housekeeping();
+    assert_eq!(value_of(&step, "entities_per_chunk").as_deref(), Some("30"));
+    assert_eq!(step.handle().read().unwrap().refresh_derived(), 1);
+    assert_eq!(value_of(&step, "entities_per_chunk").as_deref(), Some("50"));
+}
+
+#[test]
+fn panicking_computation_unsets_the_value() {
+    let (step, clock) = server_with_clock();
+    let (entities, chunks) = (input_on(&step, "entities", 120), input_on(&step, "chunks", 4));
+    let entities_per_chunk = entities_per_chunk_on(&step, &entities, &chunks);
+    let id = entities_per_chunk.id();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|derived| derived.value_in_json == "30")).is_some());
+    **chunks.borrow_mut() = 0;
+    tick(&step, &clock);
+    assert_eq!(value_of(&step, "entities_per_chunk"), None);
+    let unset = |_: &_, received: &[ServerMessage]| received.iter().any(|message| matches!(message, ServerMessage::NotifyUnset { id: unset_id } if *unset_id == id));
+    assert!(poll_client_until(&mut client, unset).is_some());
+    **chunks.borrow_mut() = 2;
+    tick(&step, &clock);
+    assert_eq!(value_of(&step, "entities_per_chunk").as_deref(), Some("60"));
+}
+
+#[test]
+fn updates_from_clients_are_refused() {
+    let (step, _clock) = server_with_clock();
+    let (entities, chunks) = (input_on(&step, "entities", 120), input_on(&step, "chunks", 4));
+    let entities_per_chunk = entities_per_chunk_on(&step, &entities, &chunks);
+    let id = entities_per_chunk.id();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+    client.send_update(id, "7").unwrap();
+    // Processed in order, so the refused update was read once the next one is queued
+    let chunks_id = step.handle().read().unwrap().debuggable_id_of("chunks").unwrap();
+    client.send_update(chunks_id, "6").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(chunks_id) == 1));
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 0);
+    let refused = |_: &_, received: &[ServerMessage]| received.iter().any(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("read-only")));
+    assert!(poll_client_until(&mut client, refused).is_some());
+}
+
+#[test]
+fn dropping_the_handle_unregisters_it() {
+    let (step, clock) = server_with_clock();
+    let (entities, chunks) = (input_on(&step, "entities", 120), input_on(&step, "chunks", 4));
+    let entities_per_chunk = entities_per_chunk_on(&step, &entities, &chunks);
+    drop(entities_per_chunk);
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("entities_per_chunk"), None);
+    tick(&step, &clock);
+    assert_eq!(step.handle().read().unwrap().refresh_derived(), 0);
+}
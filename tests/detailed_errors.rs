@@ -0,0 +1,42 @@
+//! Why a remote update couldn't be deserialized, as the backend explains it, sent to its author.
+#![cfg(all(feature = "server", feature = "use_serde"))]
+
+use serde::{Deserialize, Serialize};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{JSONDeSerializable, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Tuning {
+    speed: f32,
+    label: String,
+}
+
+#[test]
+fn missing_field_is_named_in_the_error() {
+    let error = Tuning::from_json_detailed("{\"label\":\"slow\"}").unwrap_err();
+    assert!(error.contains("missing field `speed`"), "{error}");
+    assert_eq!(Tuning::from_json("{\"label\":\"slow\"}"), None);
+    assert_eq!(Tuning::from_json_detailed("{\"speed\":1.5,\"label\":\"slow\"}"), Ok(Tuning { speed: 1.5, label: "slow".to_string() }));
+}
+
+#[test]
+fn author_of_a_rejected_update_is_told_the_missing_field() {
+    let step = StepServer::new();
+    let tuning = DebuggableBuilder::new("tuning", Tuning { speed: 1.0, label: "normal".to_string() }).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("tuning").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|tuning| !tuning.value_in_json.is_empty())).is_some());
+
+    client.send_update(id, "{\"label\":\"fast\"}").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(tuning.label, "normal");
+    let received = poll_client_until(&mut client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Error { .. }))).unwrap();
+    let error = received.iter().find_map(|message| match message {
+        ServerMessage::Error { message, .. } => Some(message),
+        _ => None,
+    }).unwrap();
+    assert!(error.starts_with("Rejected update of tuning") && error.contains("missing field `speed`"), "{error}");
+}
@@ -0,0 +1,74 @@
+//! DirClient writing transactions for servers that only read their read directory.
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::StepServer;
+
+const CLIENT_ID: usize = 7;
+
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("debug_monitor-dir_client-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn server_only_reading(dir: &PathBuf, builder: DebuggableServerBuilder) -> StepServer {
+    StepServer::from_builder(builder.read_dir(dir.to_string_lossy()).only_reads_from_dir())
+}
+
+fn read_dir(step: &StepServer) {
+    step.handle().read().unwrap().read_clients_from_read_dir();
+}
+
+#[test]
+fn updates_a_debuggable_through_a_server_only_reading_its_dir() {
+    let dir = temp_dir("default_endmark");
+    let step = server_only_reading(&dir, DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()));
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+
+    let mut client = DirClient::new(&dir, CLIENT_ID).unwrap();
+    client.send_update(id, "5").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 5);
+    client.send_update(id, "-2").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, -2);
+    assert_eq!(client.next_transaction(), 2);
+
+    // A restarted client carries on from the persisted counter
+    let mut restarted = DirClient::new(&dir, CLIENT_ID).unwrap();
+    assert_eq!(restarted.next_transaction(), 2);
+    restarted.send_update(id, "8").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 8);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn values_holding_the_endmark_of_the_server_are_escaped() {
+    let dir = temp_dir("custom_endmark");
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).message_endmark("END", "<escaped end>");
+    let step = server_only_reading(&dir, builder);
+    let label = DebuggableBuilder::new("label", "start".to_string()).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("label").unwrap();
+
+    let mut client = DirClient::for_server(&step.handle().read().unwrap(), CLIENT_ID).unwrap();
+    client.send_update(id, "\"THE END\"").unwrap();
+    let written = fs::read_dir(&dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.file_name().unwrap().to_string_lossy().starts_with("client-"))
+        .unwrap();
+    let written = fs::read_to_string(written).unwrap();
+    assert!(written.contains("THE <escaped end>") && !written.contains("END"), "{written}");
+    read_dir(&step);
+    assert_eq!(*label, "THE END");
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,110 @@
+//! Transactions of dir clients that restart, resend or write out of order.
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable};
+use debug_monitor::testing::StepServer;
+
+const CLIENT_ID: usize = 7;
+
+fn server_reading_dir(test_name: &str) -> (StepServer, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("debug_monitor-dir_sessions-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let step = StepServer::new();
+    step.handle().write().unwrap().set_read_dir_create(dir.to_string_lossy()).unwrap();
+    (step, dir)
+}
+
+fn counter_on(step: &StepServer) -> (Debuggable<i32>, usize) {
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    (counter, id)
+}
+
+fn read_dir(step: &StepServer) {
+    step.handle().read().unwrap().read_clients_from_read_dir();
+}
+
+/// Client that starts over from transaction 0, as one whose counter file was lost would.
+fn restarted_client(dir: &PathBuf) -> DirClient {
+    let _ = fs::remove_file(dir.join(format!(".dir-client-{CLIENT_ID}-counter")));
+    DirClient::new(dir, CLIENT_ID).unwrap()
+}
+
+fn write_transaction(dir: &PathBuf, file_name: String, id: usize, value_json: &str) {
+    let message = ClientUnitMessage::UpdateValue { id, new_value: value_json.to_string(), request_id: None, panel: None };
+    fs::write(dir.join(file_name), message.to_json().unwrap()).unwrap();
+}
+
+#[test]
+fn resent_transaction_is_applied_once() {
+    let (step, dir) = server_reading_dir("resent");
+    let (mut counter, id) = counter_on(&step);
+    let mut client = DirClient::new(&dir, CLIENT_ID).unwrap();
+    client.send_update(id, "2").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 2);
+    *counter = 5;
+    write_transaction(&dir, format!("client-{CLIENT_ID}-session-{}-transaction-0", client.session()), id, "2");
+    read_dir(&step);
+    assert_eq!(*counter, 5);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn earlier_transaction_of_a_session_is_ignored() {
+    let (step, dir) = server_reading_dir("regression");
+    let (counter, id) = counter_on(&step);
+    write_transaction(&dir, format!("client-{CLIENT_ID}-session-42-transaction-1"), id, "3");
+    read_dir(&step);
+    assert_eq!(*counter, 3);
+    write_transaction(&dir, format!("client-{CLIENT_ID}-session-42-transaction-0"), id, "2");
+    read_dir(&step);
+    assert_eq!(*counter, 3);
+    assert!(fs::read_dir(&dir).unwrap().all(|file| file.unwrap().file_name().to_string_lossy().starts_with('.')));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn client_restarting_twice_with_overlapping_transactions_is_applied_in_order() {
+    let (step, dir) = server_reading_dir("restarts");
+    let (counter, id) = counter_on(&step);
+    let mut first_run = DirClient::new(&dir, CLIENT_ID).unwrap();
+    first_run.send_update(id, "2").unwrap();
+    first_run.send_update(id, "3").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 3);
+
+    let mut second_run = restarted_client(&dir);
+    assert_ne!(second_run.session(), first_run.session());
+    second_run.send_update(id, "4").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 4);
+
+    let mut third_run = restarted_client(&dir);
+    assert_ne!(third_run.session(), second_run.session());
+    third_run.send_update(id, "5").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 5);
+
+    // A late copy of the first run's last transaction shares its number with the other runs' first
+    write_transaction(&dir, format!("client-{CLIENT_ID}-session-{}-transaction-1", first_run.session()), id, "3");
+    third_run.send_update(id, "6").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 6);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn transactions_named_without_a_session_are_still_read() {
+    let (step, dir) = server_reading_dir("legacy");
+    let (counter, id) = counter_on(&step);
+    write_transaction(&dir, format!("client-{CLIENT_ID}-transaction-0"), id, "8");
+    read_dir(&step);
+    assert_eq!(*counter, 8);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,64 @@
+//! Clients disconnecting and a new one reconnecting into the slot they left.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::memory_report::ClientBookkeeping;
+use debug_monitor::server::Who;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn reconnecting_into_the_same_slot_inherits_nothing_from_the_previous_client() {
+    let disconnected = Arc::new(Mutex::new(Vec::new()));
+    let recorded = disconnected.clone();
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .on_client_disconnect(move |index, address| recorded.lock().unwrap().push((index, address.is_some())));
+    let step = StepServer::from_builder(builder);
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+
+    // The first client leaves a Hello and a rejected update behind
+    let mut leaving = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut leaving, |client, _| client.client_id().is_some()).is_some());
+    let index = leaving.client_id().unwrap();
+    let old_handle = step.handle().read().unwrap().client_handle(index).unwrap();
+    leaving.send_update(id, "\"not a number\"").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*counter, 1);
+    assert_eq!(step.handle().read().unwrap().memory_report().clients.consecutive_rejections, 1);
+
+    drop(leaving);
+    assert!(step.read_until(|server| server.client_count() == 0));
+    assert_eq!(*disconnected.lock().unwrap(), vec![(index, true)]);
+    assert_eq!(step.handle().read().unwrap().memory_report().clients, ClientBookkeeping::default());
+
+    let mut arriving = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut arriving, |client, _| client.client_id().is_some()).is_some());
+    assert_eq!(arriving.client_id(), Some(index));
+    let new_handle = step.handle().read().unwrap().client_handle(index).unwrap();
+    assert_ne!(new_handle.generation, old_handle.generation);
+
+    // A late send meant for the previous client is dropped instead of reaching the new one
+    step.handle().read().unwrap().send_custom(Who::Handle(old_handle), "stale", "for the old client");
+    step.handle().read().unwrap().send_custom(Who::Handle(new_handle), "fresh", "for the new client");
+    let received = poll_client_until(&mut arriving, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Custom { .. }))).unwrap();
+    let topics = received.iter()
+        .filter_map(|message| match message {
+            ServerMessage::Custom { topic, .. } => Some(topic.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(topics, ["fresh"]);
+
+    // Its first update isn't held against it by the rejection of the previous client
+    arriving.send_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*counter, 5);
+    assert_eq!(step.handle().read().unwrap().memory_report().clients.consecutive_rejections, 0);
+}
@@ -0,0 +1,48 @@
+//! Servers falling back to free ports and announcing themselves to discovery::scan.
+#![cfg(feature = "discovery")]
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::thread;
+use std::time::Duration;
+
+use debug_monitor::discovery;
+use debug_monitor::server::debuggable_server_builder::{DebuggableServerBuilder, DEFAULT_FALLBACK_PORTS};
+use debug_monitor::testing::StepServer;
+
+#[test]
+fn scan_finds_servers_on_fallback_ports() {
+    let default_address = SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_FALLBACK_PORTS.start));
+    let servers = [
+        StepServer::from_builder(DebuggableServerBuilder::bind(default_address)),
+        StepServer::from_builder(DebuggableServerBuilder::bind(default_address)),
+    ];
+    let addrs = servers.iter().map(StepServer::addr).collect::<Vec<_>>();
+    assert_ne!(addrs[0], addrs[1]);
+    assert!(addrs.iter().all(|addr| DEFAULT_FALLBACK_PORTS.contains(&addr.port())));
+
+    let scan = thread::spawn(|| discovery::scan(Duration::from_millis(2500)));
+    while !scan.is_finished() {
+        servers.iter().for_each(StepServer::housekeeping);
+        thread::sleep(Duration::from_millis(50));
+    }
+    let found = scan.join().unwrap();
+    for addr in addrs {
+        let server = found.iter().find(|server| server.addr == addr);
+        assert!(server.is_some_and(|server| server.debuggables == 0 && !server.name.is_empty()), "{addr} not found in {found:?}");
+    }
+}
+
+#[test]
+fn disabled_beacons_are_not_found() {
+    let port = 5070;
+    let builder = DebuggableServerBuilder::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        .discovery_target(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+        .announces(false);
+    let server = StepServer::from_builder(builder);
+    let scan = thread::spawn(move || discovery::scan_port(port, Duration::from_millis(1500)).unwrap());
+    while !scan.is_finished() {
+        server.housekeeping();
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(scan.join().unwrap().is_empty());
+}
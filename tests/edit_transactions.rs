@@ -0,0 +1,163 @@
+//! Values clients stage in edit transactions, applied together on commit or discarded on abort.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Camera {
+    step: StepServer,
+    clock: Arc<ManualClock>,
+    near: Debuggable<i32>,
+    far: Debuggable<i32>,
+    _marker: Debuggable<i32>,
+    near_id: usize,
+    far_id: usize,
+    marker_id: usize,
+}
+
+impl Camera {
+    fn new() -> Self {
+        let clock = Arc::new(ManualClock::new());
+        let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+            .clock(clock.clone())
+            .edit_transaction_timeout(TIMEOUT);
+        let step = StepServer::from_builder(builder);
+        let near = DebuggableBuilder::new("near", 1).scoped(step.scoped_server()).build();
+        let far = DebuggableBuilder::new("far", 100).scoped(step.scoped_server()).build();
+        let marker = DebuggableBuilder::new("marker", 0).scoped(step.scoped_server()).build();
+        let id_of = |name| step.handle().read().unwrap().debuggable_id_of(name).unwrap();
+        let (near_id, far_id, marker_id) = (id_of("near"), id_of("far"), id_of("marker"));
+        Self { step, clock, near, far, _marker: marker, near_id, far_id, marker_id }
+    }
+
+    fn connect(&self, client_count: usize) -> DebuggableClient {
+        let client = self.step.connect().unwrap();
+        assert!(self.step.accept_until(client_count));
+        client
+    }
+
+    /// Waits for the server to read everything the client sent so far, by sending an update to the
+    /// marker right after and waiting for it to be pending.
+    fn read_all_of(&self, client: &mut DebuggableClient) {
+        let pending = self.step.handle().read().unwrap().pending_updates_of(self.marker_id);
+        client.send_update(self.marker_id, "1").unwrap();
+        assert!(self.step.read_until(|server| server.pending_updates_of(self.marker_id) > pending));
+    }
+
+    fn staged_values(&self) -> usize {
+        self.step.handle().read().unwrap().memory_report().staged_edit_values.updates
+    }
+
+    /// Syncs both debuggables twice, so a group released by the last of them reaches the first too.
+    fn settle(&self) -> (i32, i32) {
+        let _ = (*self.near, *self.far);
+        (*self.near, *self.far)
+    }
+}
+
+fn received_error(client: &mut DebuggableClient) -> bool {
+    poll_client_until(client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Error { .. }))).is_some()
+}
+
+#[test]
+fn committed_values_apply_together() {
+    let camera = Camera::new();
+    let mut editor = camera.connect(1);
+    let mut bystander = camera.connect(2);
+
+    let txn_id = editor.begin_edit().unwrap();
+    editor.stage_value(txn_id, camera.near_id, "5").unwrap();
+    editor.stage_value(txn_id, camera.far_id, "500").unwrap();
+    editor.stage_value(txn_id, camera.far_id, "400").unwrap();
+    camera.read_all_of(&mut editor);
+    assert_eq!(camera.staged_values(), 2);
+    assert_eq!(camera.step.handle().read().unwrap().pending_updates_of(camera.near_id), 0);
+    assert_eq!(camera.settle(), (1, 100));
+
+    editor.commit_edit(txn_id).unwrap();
+    camera.read_all_of(&mut editor);
+    assert_eq!(camera.staged_values(), 0);
+    assert_eq!(camera.settle(), (5, 400));
+    let (near_id, far_id) = (camera.near_id, camera.far_id);
+    assert!(poll_client_until(&mut bystander, |client, _| {
+        client.debuggable(near_id).is_some_and(|near| near.value_in_json == "5")
+            && client.debuggable(far_id).is_some_and(|far| far.value_in_json == "400")
+    }).is_some());
+}
+
+#[test]
+fn aborted_values_are_discarded() {
+    let camera = Camera::new();
+    let mut editor = camera.connect(1);
+
+    let txn_id = editor.begin_edit().unwrap();
+    editor.stage_value(txn_id, camera.near_id, "5").unwrap();
+    editor.abort_edit(txn_id).unwrap();
+    camera.read_all_of(&mut editor);
+    assert_eq!(camera.staged_values(), 0);
+
+    // The transaction is gone, so committing it fails
+    editor.commit_edit(txn_id).unwrap();
+    camera.read_all_of(&mut editor);
+    assert!(received_error(&mut editor));
+    assert_eq!(camera.settle(), (1, 100));
+}
+
+#[test]
+fn stale_transactions_are_dropped() {
+    let camera = Camera::new();
+    let mut editor = camera.connect(1);
+
+    let txn_id = editor.begin_edit().unwrap();
+    editor.stage_value(txn_id, camera.near_id, "5").unwrap();
+    camera.read_all_of(&mut editor);
+    camera.clock.advance(TIMEOUT / 2);
+    camera.step.housekeeping();
+    assert_eq!(camera.staged_values(), 1);
+
+    // Staging counts as activity
+    editor.stage_value(txn_id, camera.far_id, "500").unwrap();
+    camera.read_all_of(&mut editor);
+    camera.clock.advance(TIMEOUT / 2);
+    camera.step.housekeeping();
+    assert_eq!(camera.staged_values(), 2);
+
+    camera.clock.advance(TIMEOUT);
+    camera.step.housekeeping();
+    assert_eq!(camera.staged_values(), 0);
+    assert!(received_error(&mut editor));
+
+    editor.commit_edit(txn_id).unwrap();
+    camera.read_all_of(&mut editor);
+    assert_eq!(camera.settle(), (1, 100));
+}
+
+#[test]
+fn commits_land_whole_when_racing_direct_updates() {
+    let camera = Camera::new();
+    let mut editor = camera.connect(1);
+    let mut other = camera.connect(2);
+
+    let txn_id = editor.begin_edit().unwrap();
+    editor.stage_value(txn_id, camera.near_id, "5").unwrap();
+    editor.stage_value(txn_id, camera.far_id, "500").unwrap();
+    editor.commit_edit(txn_id).unwrap();
+    other.send_update(camera.near_id, "7").unwrap();
+    camera.read_all_of(&mut editor);
+    camera.read_all_of(&mut other);
+
+    // Whichever was read first, the direct update applies on its own while the commit waits for
+    // both debuggables to sync, then lands as a whole
+    assert_eq!(*camera.near, 7);
+    assert_eq!(*camera.far, 500);
+    assert_eq!(camera.settle(), (5, 500));
+}
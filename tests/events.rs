@@ -0,0 +1,70 @@
+//! Lifecycle events the host can subscribe to.
+#![cfg(feature = "server")]
+
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::events::{ChangeOrigin, ServerEvent, EVENT_QUEUE_CAPACITY};
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+fn next_event(events: &Receiver<ServerEvent>) -> ServerEvent {
+    events.recv_timeout(STEP_TIMEOUT).unwrap()
+}
+
+#[test]
+fn session_emits_events_in_order() {
+    let step = StepServer::new();
+    let events = step.handle().read().unwrap().events();
+    let mut counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    assert_eq!(next_event(&events), ServerEvent::DebuggableAdded { id, name: "counter".to_string() });
+    assert_eq!(next_event(&events), ServerEvent::ValueChanged { id, origin: ChangeOrigin::Host });
+
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let ServerEvent::ClientConnected { index, addr } = next_event(&events) else { panic!("expected a connection") };
+    assert!(addr.is_some());
+
+    client.send_update(id, "7").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*counter, 7);
+    assert_eq!(next_event(&events), ServerEvent::ValueChanged { id, origin: ChangeOrigin::Client { index } });
+
+    *counter = 9;
+    assert_eq!(*counter, 9);
+    assert_eq!(next_event(&events), ServerEvent::ValueChanged { id, origin: ChangeOrigin::Host });
+
+    drop(client);
+    assert!(step.read_until(|server| server.client_count() == 0));
+    assert_eq!(next_event(&events), ServerEvent::ClientDisconnected { index });
+
+    drop(counter);
+    assert_eq!(next_event(&events), ServerEvent::DebuggableRemoved { id });
+}
+
+#[test]
+fn slow_subscriber_keeps_only_the_newest_events() {
+    let step = StepServer::new();
+    let events = step.handle().read().unwrap().events();
+    let mut counter = DebuggableBuilder::new("counter", 0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let changes = EVENT_QUEUE_CAPACITY * 4;
+    for value in 1..=changes {
+        *counter = value as i32;
+        assert_eq!(*counter, value as i32);
+    }
+    drop(counter);
+    let received = events.iter().take_while(|event| *event != ServerEvent::DebuggableRemoved { id }).count() + 1;
+    // The forwarder may hold one event beyond the queue
+    assert!(received <= EVENT_QUEUE_CAPACITY + 1, "received {received} events");
+    assert!(events.recv_timeout(Duration::from_millis(50)).is_err());
+}
+
+#[test]
+fn dropped_receivers_are_pruned() {
+    let step = StepServer::new();
+    drop(step.handle().read().unwrap().events());
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    assert_eq!(*counter, 1);
+}
@@ -0,0 +1,81 @@
+//! Servers whose traffic is disturbed on purpose by a seeded FaultInjector.
+#![cfg(feature = "fault-injection")]
+
+use std::fs;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::fault_injection::FaultInjector;
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+const SEED: u64 = 0x5eed;
+
+fn server_with(injector: FaultInjector) -> StepServer {
+    StepServer::from_builder(DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).fault_injector(injector))
+}
+
+fn counter_on(step: &StepServer) -> (Debuggable<i32>, usize) {
+    let counter = DebuggableBuilder::new("counter", 0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    (counter, id)
+}
+
+/// Retries the attempt until it succeeds or STEP_TIMEOUT passes.
+fn eventually<Attempt: FnMut() -> bool>(mut attempt: Attempt) -> bool {
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    while Instant::now() < give_up_at {
+        if attempt() { return true; }
+        thread::sleep(Duration::from_millis(5));
+    }
+    false
+}
+
+#[test]
+fn values_converge_despite_drops_and_duplicates() {
+    let step = server_with(FaultInjector::new(SEED).drop_outgoing(40).duplicate_incoming(50).skip_accepts(50));
+    let (counter, id) = counter_on(&step);
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+
+    for value in 1..=10 {
+        client.send_update(id, value).unwrap();
+    }
+    assert!(eventually(|| {
+        step.read_until(|_| true);
+        *counter == 10
+    }));
+    assert_eq!(step.handle().read().unwrap().value_of("counter").as_deref(), Some("10"));
+
+    // Only the periodic refresh can make up for dropped notifies
+    assert!(eventually(|| {
+        step.handle().read().unwrap().refresh_now();
+        client.poll().unwrap();
+        client.debuggable(id).is_some_and(|counter| counter.value_in_json == "10")
+    }));
+    let stats = step.handle().read().unwrap().stats();
+    assert!(stats.injected_drops > 0);
+    assert!(stats.injected_duplicates > 0);
+}
+
+#[test]
+fn dir_transactions_wait_for_the_configured_polls() {
+    let dir = std::env::temp_dir().join(format!("debug_monitor-fault_injection-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let step = server_with(FaultInjector::new(SEED).delay_dir_transactions(2));
+    step.handle().write().unwrap().set_read_dir_create(dir.to_string_lossy()).unwrap();
+    let (counter, id) = counter_on(&step);
+    DirClient::new(&dir, 7).unwrap().send_update(id, "5").unwrap();
+
+    for _ in 0..2 {
+        step.handle().read().unwrap().read_clients_from_read_dir();
+        assert_eq!(*counter, 0);
+    }
+    step.handle().read().unwrap().read_clients_from_read_dir();
+    assert_eq!(*counter, 5);
+    assert_eq!(step.handle().read().unwrap().stats().injected_dir_delays, 2);
+    let _ = fs::remove_dir_all(&dir);
+}
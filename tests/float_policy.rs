@@ -0,0 +1,110 @@
+//! NaN and infinite floats going through the float policy of their debuggable.
+#![cfg(feature = "server")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use debug_monitor::debuggable::float_policy::FloatPolicy;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+
+fn value_on(server: &ScopedServer, name: &str) -> Option<String> {
+    server.handle().read().unwrap().value_of(name)
+}
+
+fn update_from_client(server: &ScopedServer, name: &str, json: &str) {
+    let handle = server.handle();
+    let server = handle.read().unwrap();
+    assert!(server.queue_update(server.debuggable_id_of(name).unwrap(), 0, json.to_string()));
+}
+
+#[test]
+fn reject_non_finite_reports_a_serialize_error() {
+    let server = ScopedServer::new();
+    let errors = Arc::new(AtomicUsize::new(0));
+    let reported = errors.clone();
+    let mut speed = DebuggableBuilder::new("speed", 1.5f64)
+        .scoped(&server)
+        .float_policy(FloatPolicy::RejectNonFinite)
+        .on_serialize_error(move |_| { reported.fetch_add(1, Ordering::Relaxed); })
+        .build();
+    assert_eq!(value_on(&server, "speed").as_deref(), Some("1.5"));
+
+    for (non_finite, reports) in [(f64::NAN, 1), (f64::INFINITY, 2), (f64::NEG_INFINITY, 3)] {
+        *speed = 2.0;
+        assert_eq!(*speed, 2.0);
+        *speed = non_finite;
+        let _ = *speed;
+        assert_eq!(errors.load(Ordering::Relaxed), reports);
+        assert_ne!(value_on(&server, "speed").as_deref(), Some("2.0"));
+    }
+
+    update_from_client(&server, "speed", "\"NaN\"");
+    *speed = 3.0;
+    assert_eq!(*speed, 3.0);
+    assert_eq!(value_on(&server, "speed").as_deref(), Some("3.0"));
+}
+
+#[test]
+fn string_encode_round_trips_every_value() {
+    let server = ScopedServer::new();
+    let mut speed = DebuggableBuilder::new("speed", 0.25f32).scoped(&server).float_policy(FloatPolicy::StringEncode).build();
+    assert_eq!(value_on(&server, "speed").as_deref(), Some("0.25"));
+
+    for (non_finite, json) in [(f32::INFINITY, "\"Infinity\""), (f32::NEG_INFINITY, "\"-Infinity\""), (f32::NAN, "\"NaN\"")] {
+        *speed = non_finite;
+        let _ = *speed;
+        assert_eq!(value_on(&server, "speed").as_deref(), Some(json));
+    }
+
+    for (json, expected) in [("\"Infinity\"", f32::INFINITY), ("\"-Infinity\"", f32::NEG_INFINITY), ("4.5", 4.5)] {
+        update_from_client(&server, "speed", json);
+        assert_eq!(*speed, expected);
+        assert_eq!(value_on(&server, "speed").as_deref(), Some(json));
+    }
+    update_from_client(&server, "speed", "\"NaN\"");
+    assert!(speed.is_nan());
+}
+
+#[test]
+fn clamp_to_zero_sends_and_takes_zero() {
+    let server = ScopedServer::new();
+    let mut speed = DebuggableBuilder::new("speed", 7.0f64).scoped(&server).float_policy(FloatPolicy::ClampToZero).build();
+    assert_eq!(value_on(&server, "speed").as_deref(), Some("7.0"));
+
+    for non_finite in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        *speed = 1.0;
+        let _ = *speed;
+        *speed = non_finite;
+        let _ = *speed;
+        assert_eq!(value_on(&server, "speed").as_deref(), Some("0.0"));
+    }
+
+    update_from_client(&server, "speed", "\"-Infinity\"");
+    assert_eq!(*speed, 0.0);
+    update_from_client(&server, "speed", "8.0");
+    assert_eq!(*speed, 8.0);
+}
+
+#[test]
+fn string_encode_applies_to_the_whole_json_of_other_values() {
+    let server = ScopedServer::new();
+    let to_json = |pair: &(f64, f64)| Some(format!("[{:?},{:?}]", pair.0, pair.1));
+    let from_json = |json: &str| {
+        let (first, second) = json.trim().strip_prefix('[')?.strip_suffix(']')?.split_once(',')?;
+        Some((first.trim().parse().ok()?, second.trim().parse().ok()?))
+    };
+    let mut pair = DebuggableBuilder::new_unserializable("pair", (1.0, 2.0))
+        .with_serializer(to_json, from_json)
+        .scoped(&server)
+        .float_policy(FloatPolicy::StringEncode)
+        .build();
+    assert_eq!(value_on(&server, "pair").as_deref(), Some("[1.0,2.0]"));
+
+    pair.0 = (f64::NAN, f64::NEG_INFINITY);
+    let _ = pair.0;
+    assert_eq!(value_on(&server, "pair").as_deref(), Some("[\"NaN\",\"-Infinity\"]"));
+
+    update_from_client(&server, "pair", "[\"Infinity\", 3.0]");
+    assert_eq!(pair.0, (f64::INFINITY, 3.0));
+}
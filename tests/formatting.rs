@@ -0,0 +1,51 @@
+//! Debuggables formatted and serialized from their cached value, without syncing them.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::StepServer;
+
+#[test]
+fn debug_and_display_leave_updates_pending_until_a_synced_format() {
+    let step = StepServer::new();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(id, "9").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+
+    assert_eq!(format!("{speed:?} {speed}"), "1 1");
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 1);
+
+    assert_eq!(format!("{:?}", speed.debug_synced()), "9");
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 0);
+    assert_eq!(format!("{speed:?} {speed}"), "9 9");
+}
+
+#[cfg(feature = "use_serde")]
+#[test]
+fn structs_holding_debuggables_serialize_their_cached_values() {
+    use debug_monitor::debuggable::Debuggable;
+
+    #[derive(serde::Serialize)]
+    struct Tuning {
+        speed: Debuggable<i32>,
+        label: Debuggable<String>,
+    }
+
+    let step = StepServer::new();
+    let tuning = Tuning {
+        speed: DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build(),
+        label: DebuggableBuilder::new("label", "calm".to_string()).scoped(step.scoped_server()).build(),
+    };
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(id, "9").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+
+    assert_eq!(serde_json::to_string(&tuning).unwrap(), r#"{"speed":1,"label":"calm"}"#);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 1);
+    assert_eq!(*tuning.speed, 9);
+    assert_eq!(serde_json::to_string(&tuning).unwrap(), r#"{"speed":9,"label":"calm"}"#);
+}
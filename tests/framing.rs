@@ -0,0 +1,48 @@
+//! Servers delimiting messages with an endmark other than simple_tcp's.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::serializable::framing::Framing;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const ENDMARK: &str = "<<end>>";
+const ESCAPE: &str = "<<escaped end>>";
+
+fn custom_endmark_builder() -> DebuggableServerBuilder {
+    DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).message_endmark(ENDMARK, ESCAPE)
+}
+
+#[test]
+fn custom_endmark_round_trips_the_default_endmark() {
+    let default_endmark = ScopedServer::new().handle().read().unwrap().framing_info().endmark;
+    let step = StepServer::from_builder(custom_endmark_builder());
+    let framing = step.handle().read().unwrap().framing_info();
+    assert_eq!((framing.endmark.as_str(), framing.escape.as_str(), framing.mode), (ENDMARK, ESCAPE, Framing::Endmark));
+
+    let sent = format!("before{default_endmark}{ENDMARK}after");
+    let mut text = DebuggableBuilder::new("text", sent.clone()).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("text").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+    assert_eq!(client.server_info().and_then(|info| info.framing.clone()), Some(framing));
+
+    let received_json = client.debuggable(id).unwrap().value_in_json.clone();
+    *text = "replaced".to_string();
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).unwrap().value_in_json != received_json).is_some());
+    client.send_update(id, &received_json).unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*text, sent);
+}
+
+#[test]
+fn endmarks_prefixing_their_escape_are_rejected() {
+    for (endmark, escape) in [("", "\\e"), ("\n", ""), ("\n", "\n\n"), ("ab", "a")] {
+        let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).message_endmark(endmark, escape);
+        assert_eq!(builder.try_build().unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
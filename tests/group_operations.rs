@@ -0,0 +1,49 @@
+//! Snapshots and resets clients request for every debuggable whose name starts with a prefix.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::defaults::DebuggableDefaults;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn reset_restores_initial_values_except_read_only_members() {
+    let step = StepServer::new();
+    let fov = DebuggableBuilder::new("camera/fov", 60).scoped(step.scoped_server()).build();
+    let zoom = DebuggableBuilder::new("camera/zoom", 1.5).scoped(step.scoped_server()).build();
+    let mut lens = {
+        let _read_only = step.handle().read().unwrap().scoped_defaults_with(DebuggableDefaults::default().read_only());
+        DebuggableBuilder::new("camera/lens", 35).scoped(step.scoped_server()).build()
+    };
+    let outside = DebuggableBuilder::new("cameraman", 1).scoped(step.scoped_server()).build();
+    let ids = ["camera/fov", "camera/zoom", "camera/lens", "cameraman"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| ids.iter().all(|id| client.debuggable(*id).is_some())).is_some());
+
+    client.send_update(ids[0], "90").unwrap();
+    client.send_update(ids[1], "3.0").unwrap();
+    client.send_update(ids[3], "2").unwrap();
+    assert!(step.read_until(|server| ids.iter().filter(|id| server.pending_updates_of(**id) == 1).count() == 3));
+    assert_eq!((*fov, *zoom, *outside), (90, 3.0, 2));
+    *lens = 50;
+    assert_eq!(*lens, 50);
+
+    client.request_group_snapshot("camera/").unwrap();
+    client.reset_group("camera/").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(ids[0]) == 1));
+    let received = poll_client_until(&mut client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::GroupResult { .. }))).unwrap();
+    let snapshot = received.iter()
+        .find_map(|message| match message {
+            ServerMessage::NotifyMany { notifies } => Some(notifies.iter().map(|notify| (notify.id, notify.value_in_json.as_str())).collect::<Vec<_>>()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(snapshot, [(ids[0], "90"), (ids[1], "3.0"), (ids[2], "50")]);
+    let result = received.iter().find(|message| matches!(message, ServerMessage::GroupResult { .. })).unwrap();
+    assert_eq!(result, &ServerMessage::GroupResult { prefix: "camera/".to_string(), applied: vec![ids[0], ids[1]], denied: vec![ids[2]] });
+
+    assert_eq!((*fov, *zoom, *lens, *outside), (60, 1.5, 50, 2));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(ids[0]).is_some_and(|fov| fov.value_in_json == "60")).is_some());
+    assert_eq!(client.debuggable(ids[1]).unwrap().value_in_json, "1.5");
+}
@@ -0,0 +1,62 @@
+//! Debuggables hidden from clients and shown again without being unregistered.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{RemoveReason, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Names the Added, Notify and Remove messages about the debuggable, in the order received.
+fn sequence_of(received: &[ServerMessage], id: usize) -> Vec<&'static str> {
+    received.iter()
+        .filter_map(|message| match message {
+            ServerMessage::Added { id: about, .. } if *about == id => Some("Added"),
+            ServerMessage::Notify { id: about, .. } if *about == id => Some("Notify"),
+            ServerMessage::Remove { id: about, reason: RemoveReason::Hidden, .. } if *about == id => Some("Remove(Hidden)"),
+            ServerMessage::Remove { id: about, .. } if *about == id => Some("Remove"),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Polls until the client got a Notify of the fence debuggable, returning everything received.
+fn received_until_fence(client: &mut DebuggableClient, fence_id: usize) -> Vec<ServerMessage> {
+    poll_client_until(client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Notify { id, .. } if *id == fence_id))).unwrap()
+}
+
+#[test]
+fn toggling_visibility_removes_and_announces_again_and_dropping_it_hidden_sends_nothing() {
+    let step = StepServer::new();
+    let mut fence = DebuggableBuilder::new("fence", 0).scoped(step.scoped_server()).build();
+    let fence_id = step.handle().read().unwrap().debuggable_id_of("fence").unwrap();
+    let mut knob = DebuggableBuilder::new("knob", 1).scoped(step.scoped_server()).build();
+    let knob_id = step.handle().read().unwrap().debuggable_id_of("knob").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(knob_id).is_some_and(|knob| knob.value_in_json == "1")).unwrap();
+    assert_eq!(sequence_of(&received, knob_id), ["Added", "Notify"]);
+
+    knob.set_hidden(true);
+    assert!(knob.is_hidden());
+    *fence = 1;
+    assert_eq!(sequence_of(&received_until_fence(&mut client, fence_id), knob_id), ["Remove(Hidden)"]);
+    assert!(client.debuggable(knob_id).is_none());
+
+    // An update for the hidden id is ignored rather than queued
+    client.send_update(knob_id, "9").unwrap();
+    client.send_update(fence_id, "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(fence_id) == 1));
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(knob_id), 0);
+    assert_eq!(*knob, 1);
+
+    knob.set_hidden(false);
+    *fence = 3;
+    assert_eq!(sequence_of(&received_until_fence(&mut client, fence_id), knob_id), ["Added", "Notify"]);
+    assert_eq!(client.debuggable(knob_id).unwrap().value_in_json, "1");
+
+    knob.set_hidden(true);
+    drop(knob);
+    *fence = 4;
+    assert_eq!(sequence_of(&received_until_fence(&mut client, fence_id), knob_id), ["Remove(Hidden)"]);
+    assert!(step.handle().read().unwrap().is_hidden(knob_id).is_none());
+}
@@ -0,0 +1,89 @@
+//! Elements of large vecs moving alone across the wire when they're the only thing that changed.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::debuggable_vec::DebuggableVec;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const LEN: usize = 10_000;
+
+fn large_curve(step: &StepServer) -> (DebuggableVec<i32>, usize) {
+    let curve = DebuggableVec::from(DebuggableBuilder::new("curve", vec![0; LEN]).scoped(step.scoped_server()).build());
+    let id = step.handle().read().unwrap().debuggable_id_of("curve").unwrap();
+    (curve, id)
+}
+
+fn connect(step: &StepServer, id: usize, client_count: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(client_count));
+    // Added comes before the value, whose full Notify is all the client is sent on joining
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|curve| !curve.value_in_json.is_empty())).is_some());
+    client
+}
+
+/// Messages about the debuggable received until one of them is a NotifyIndex.
+fn messages_until_notify_index(client: &mut DebuggableClient, id: usize) -> Vec<ServerMessage> {
+    let received = poll_client_until(client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::NotifyIndex { .. }))).unwrap();
+    received.into_iter()
+        .filter(|message| match message {
+            ServerMessage::Notify { id: notified, .. } | ServerMessage::NotifyIndex { id: notified, .. } => *notified == id,
+            ServerMessage::NotifyMany { .. } | ServerMessage::NotifyEncoded { .. } => true,
+            _ => false,
+        })
+        .collect()
+}
+
+fn curve_with(index: usize, element: i32) -> String {
+    let mut curve = vec![0; LEN];
+    curve[index] = element;
+    curve.to_json().unwrap()
+}
+
+#[test]
+fn host_setting_one_element_sends_only_that_element() {
+    let step = StepServer::new();
+    let (mut curve, id) = large_curve(&step);
+    let mut client = connect(&step, id, 1);
+
+    assert_eq!(curve.set(1234, 7), Some(0));
+    // Reading it back syncs it, which must not find anything else to send
+    assert_eq!(curve[1234], 7);
+    let received = messages_until_notify_index(&mut client, id);
+    assert!(matches!(&received[..], [ServerMessage::NotifyIndex { index: 1234, element_json, .. }] if element_json == "7"), "{received:?}");
+    assert_eq!(client.debuggable(id).unwrap().value_in_json, curve_with(1234, 7));
+}
+
+#[test]
+fn remote_index_update_reaches_other_clients_as_one_element() {
+    let step = StepServer::new();
+    let (curve, id) = large_curve(&step);
+    let mut editor = connect(&step, id, 1);
+    let mut watcher = connect(&step, id, 2);
+
+    editor.send(&ClientUnitMessage::UpdateIndex { id, index: 9_999, element_json: "-3".to_string() }).unwrap();
+    assert!(step.read_until(|server| server.memory_report().pending.contains_key(&id)));
+    assert_eq!(curve[9_999], -3);
+    let received = messages_until_notify_index(&mut watcher, id);
+    assert!(matches!(&received[..], [ServerMessage::NotifyIndex { index: 9_999, element_json, .. }] if element_json == "-3"), "{received:?}");
+    assert_eq!(watcher.debuggable(id).unwrap().value_in_json, curve_with(9_999, -3));
+
+    // The editor already has the element, so it's sent nothing back
+    let echoed = editor.poll().unwrap();
+    assert!(!echoed.iter().any(|message| matches!(message, ServerMessage::Notify { .. } | ServerMessage::NotifyIndex { .. })), "{echoed:?}");
+}
+
+#[test]
+fn out_of_bounds_index_gets_the_whole_value_back() {
+    let step = StepServer::new();
+    let (curve, id) = large_curve(&step);
+    let mut editor = connect(&step, id, 1);
+
+    editor.send(&ClientUnitMessage::UpdateIndex { id, index: LEN, element_json: "1".to_string() }).unwrap();
+    assert!(step.read_until(|server| server.memory_report().pending.contains_key(&id)));
+    assert_eq!(curve.len(), LEN);
+    let received = poll_client_until(&mut editor, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Notify { .. }))).unwrap();
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::NotifyIndex { .. })));
+    assert_eq!(editor.debuggable(id).unwrap().value_in_json, vec![0; LEN].to_json().unwrap());
+}
@@ -0,0 +1,104 @@
+//! CIDR ranges of allowed peers and the connections refused for falling outside of them.
+#![cfg(feature = "server")]
+
+use std::io::Read;
+use std::net::{IpAddr, TcpListener, TcpStream};
+
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::ip_filter::IpRange;
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+fn ip(address: &str) -> IpAddr {
+    address.parse().unwrap()
+}
+
+fn range(range: &str) -> IpRange {
+    range.parse().unwrap()
+}
+
+#[test]
+fn v4_ranges_match_on_their_prefix_only() {
+    let private = range("192.168.1.0/24");
+    assert!(private.contains(ip("192.168.1.0")));
+    assert!(private.contains(ip("192.168.1.255")));
+    assert!(!private.contains(ip("192.168.2.1")));
+    assert!(!private.contains(ip("10.0.0.1")));
+
+    // Bits past the prefix don't need to be zero in the network
+    assert!(range("10.20.30.40/8").contains(ip("10.255.0.1")));
+    assert!(range("172.16.0.0/12").contains(ip("172.31.255.255")));
+    assert!(!range("172.16.0.0/12").contains(ip("172.32.0.0")));
+}
+
+#[test]
+fn single_addresses_and_zero_prefixes_are_the_extremes() {
+    let single = range("203.0.113.7");
+    assert_eq!(single, IpRange::single(ip("203.0.113.7")));
+    assert!(single.contains(ip("203.0.113.7")));
+    assert!(!single.contains(ip("203.0.113.8")));
+
+    let everything_v4 = range("0.0.0.0/0");
+    assert!(everything_v4.contains(ip("8.8.8.8")));
+    assert!(everything_v4.contains(ip("255.255.255.255")));
+    assert!(!everything_v4.contains(ip("2001:db8::1")));
+    assert!(range("::/0").contains(ip("2001:db8::1")));
+}
+
+#[test]
+fn v6_ranges_match_and_v4_mapped_peers_match_v4_ranges() {
+    let documentation = range("2001:db8::/32");
+    assert!(documentation.contains(ip("2001:db8:ffff::1")));
+    assert!(!documentation.contains(ip("2001:db9::1")));
+    assert!(range("fe80::/64").contains(ip("fe80::1234:5678")));
+    assert!(!range("fe80::/64").contains(ip("fe80:0:0:1::1")));
+
+    assert!(range("127.0.0.0/8").contains(ip("::ffff:127.0.0.1")));
+    assert!(!range("127.0.0.0/8").contains(ip("::ffff:10.0.0.1")));
+    assert!(!range("::1/128").contains(ip("127.0.0.1")));
+}
+
+#[test]
+fn loopback_ranges_cover_both_families() {
+    let loopback = IpRange::loopback();
+    let is_allowed = |address: &str| loopback.iter().any(|range| range.contains(ip(address)));
+    assert!(is_allowed("127.0.0.1"));
+    assert!(is_allowed("127.1.2.3"));
+    assert!(is_allowed("::1"));
+    assert!(is_allowed("::ffff:127.0.0.1"));
+    assert!(!is_allowed("10.0.0.1"));
+    assert!(!is_allowed("::2"));
+}
+
+#[test]
+fn ranges_parse_display_and_reject_malformed_input() {
+    assert_eq!(range(" 10.0.0.0 / 8 ").to_string(), "10.0.0.0/8");
+    assert_eq!(range("::1").to_string(), "::1/128");
+    assert_eq!(range("192.168.0.0/16"), IpRange::new(ip("192.168.0.0"), 16).unwrap());
+    assert_eq!(IpRange::new(ip("10.0.0.0"), 33), None);
+    assert_eq!(IpRange::new(ip("::"), 129), None);
+
+    assert!("10.0.0.0/33".parse::<IpRange>().unwrap_err().contains("Prefix too long"));
+    assert!("10.0.0.0/eight".parse::<IpRange>().unwrap_err().contains("Invalid prefix"));
+    assert!("10.0.0/8".parse::<IpRange>().unwrap_err().contains("Invalid address"));
+    assert!("not an address".parse::<IpRange>().is_err());
+}
+
+#[test]
+fn loopback_only_server_still_accepts_local_clients() {
+    let step = StepServer::from_builder(DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).loopback_only());
+    let _client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert_eq!(step.handle().read().unwrap().stats().rejected_connections, 0);
+}
+
+#[test]
+fn peers_outside_the_allowed_ranges_are_closed_and_counted() {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).allow_ips(vec![range("10.0.0.0/8")]);
+    let step = StepServer::from_builder(builder);
+    let mut refused = TcpStream::connect(step.addr()).unwrap();
+    assert!(!step.accept_until(1));
+    assert_eq!(step.handle().read().unwrap().stats().rejected_connections, 1);
+    refused.set_read_timeout(Some(STEP_TIMEOUT)).unwrap();
+    let mut received = Vec::new();
+    assert_eq!(refused.read_to_end(&mut received).unwrap(), 0);
+}
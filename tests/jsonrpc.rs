@@ -0,0 +1,154 @@
+//! Newline-delimited JSON-RPC 2.0 spoken over a raw socket to the server's secondary listener.
+#![cfg(feature = "jsonrpc")]
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Instant;
+
+use serde_json::{json, Value};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::events::{ChangeOrigin, ServerEvent};
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+struct RpcConnection {
+    stream: TcpStream,
+    received: Vec<u8>,
+}
+
+impl RpcConnection {
+    fn connect(step: &StepServer) -> Self {
+        let addr = step.handle().read().unwrap().jsonrpc_addr().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        Self { stream, received: Vec::new() }
+    }
+
+    fn send(&mut self, line: &str) {
+        self.stream.write_all(format!("{line}\n").as_bytes()).unwrap();
+    }
+
+    fn request(&mut self, id: u64, method: &str, params: Value) {
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string());
+    }
+
+    /// Sets the value and lets the server poll until it queued it for the debuggable to sync.
+    fn set(&mut self, step: &StepServer, id: u64, name: &str, value: Value) {
+        self.request(id, "set", json!([name, value]));
+        let debuggable_id = step.handle().read().unwrap().debuggable_id_of(name).unwrap();
+        let give_up_at = Instant::now() + STEP_TIMEOUT;
+        while step.handle().read().unwrap().pending_updates_of(debuggable_id) == 0 {
+            assert!(Instant::now() < give_up_at, "The set wasn't queued in time");
+            step.housekeeping();
+            thread::yield_now();
+        }
+    }
+
+    /// Lets the server poll until it wrote a whole line, returning it parsed.
+    fn next_line(&mut self, step: &StepServer) -> Value {
+        let give_up_at = Instant::now() + STEP_TIMEOUT;
+        let mut buffer = [0_u8; 4096];
+        loop {
+            if let Some(line_end) = self.received.iter().position(|byte| *byte == b'\n') {
+                let line = self.received.drain(..=line_end).collect::<Vec<_>>();
+                return serde_json::from_slice(&line).unwrap();
+            }
+            assert!(Instant::now() < give_up_at, "No line received in time");
+            step.housekeeping();
+            match self.stream.read(&mut buffer) {
+                Ok(0) => panic!("The server closed the connection"),
+                Ok(read) => self.received.extend_from_slice(&buffer[..read]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => thread::yield_now(),
+                Err(error) => panic!("Reading from the server failed: {error}"),
+            }
+        }
+    }
+}
+
+fn jsonrpc_server() -> StepServer {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .jsonrpc("127.0.0.1:0".parse().unwrap())
+        .rejection_escalation(Some(2), Some(3));
+    StepServer::from_builder(builder)
+}
+
+#[test]
+fn list_get_set_and_subscribe_over_a_raw_socket() {
+    let step = jsonrpc_server();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut connection = RpcConnection::connect(&step);
+
+    connection.request(1, "list", Value::Null);
+    assert_eq!(connection.next_line(&step), json!({ "jsonrpc": "2.0", "id": 1, "result": [{ "id": id, "name": "speed", "value": 1 }] }));
+
+    connection.request(2, "get", json!(["speed"]));
+    assert_eq!(connection.next_line(&step), json!({ "jsonrpc": "2.0", "id": 2, "result": 1 }));
+
+    connection.request(3, "subscribe", json!({ "name": "speed" }));
+    assert_eq!(connection.next_line(&step), json!({ "jsonrpc": "2.0", "id": 3, "result": true }));
+    let notify = json!({ "jsonrpc": "2.0", "method": "notify", "params": { "id": id, "name": "speed", "value": 1 } });
+    assert_eq!(connection.next_line(&step), notify);
+
+    connection.set(&step, 4, "speed", json!(5));
+    assert_eq!(*speed, 5);
+    assert_eq!(connection.next_line(&step), json!({ "jsonrpc": "2.0", "id": 4, "result": true }));
+    let notify = json!({ "jsonrpc": "2.0", "method": "notify", "params": { "id": id, "name": "speed", "value": 5 } });
+    assert_eq!(connection.next_line(&step), notify);
+
+    // Polling again without changes notifies nothing, so the next line is the answer to the get
+    step.housekeeping();
+    connection.request(5, "get", json!(["speed"]));
+    assert_eq!(connection.next_line(&step), json!({ "jsonrpc": "2.0", "id": 5, "result": 5 }));
+}
+
+#[test]
+fn failures_are_answered_with_error_objects() {
+    let step = jsonrpc_server();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let mut connection = RpcConnection::connect(&step);
+
+    connection.send("{ not json");
+    let parse_error = connection.next_line(&step);
+    assert_eq!((&parse_error["id"], &parse_error["error"]["code"]), (&Value::Null, &json!(-32700)));
+
+    connection.request(1, "teleport", Value::Null);
+    assert_eq!(connection.next_line(&step)["error"]["code"], json!(-32601));
+
+    connection.request(2, "get", json!(["altitude"]));
+    let not_found = connection.next_line(&step);
+    assert_eq!((&not_found["id"], &not_found["error"]["code"]), (&json!(2), &json!(-32001)));
+
+    connection.request(3, "set", json!(["speed"]));
+    assert_eq!(connection.next_line(&step)["error"]["code"], json!(-32602));
+
+    connection.set(&step, 4, "speed", json!("fast"));
+    assert_eq!(*speed, 1);
+    let rejected = connection.next_line(&step);
+    assert_eq!((&rejected["id"], &rejected["error"]["code"]), (&json!(4), &json!(-32002)));
+    assert!(rejected.get("result").is_none());
+}
+
+#[test]
+fn rejected_sets_never_get_json_rpc_ignored_and_its_changes_are_attributed_to_it() {
+    let step = jsonrpc_server();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let events = step.handle().read().unwrap().events();
+    let mut connection = RpcConnection::connect(&step);
+
+    // More rejections in a row than the server ignores a client after
+    for request_id in 0..4 {
+        connection.set(&step, request_id, "speed", json!("fast"));
+        assert_eq!(*speed, 1);
+        assert_eq!(connection.next_line(&step)["error"]["code"], json!(-32002));
+    }
+    assert_eq!(step.handle().read().unwrap().stats().consecutive_rejections, 0);
+
+    connection.set(&step, 4, "speed", json!(5));
+    assert_eq!(*speed, 5);
+    assert_eq!(connection.next_line(&step), json!({ "jsonrpc": "2.0", "id": 4, "result": true }));
+    assert_eq!(events.recv_timeout(STEP_TIMEOUT).unwrap(), ServerEvent::ValueChanged { id, origin: ChangeOrigin::JsonRpc });
+}
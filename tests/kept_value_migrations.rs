@@ -0,0 +1,65 @@
+//! Kept values that no longer deserialize as the type of their debuggable, rewritten by its migration.
+#![cfg(all(feature = "server", feature = "use_serde"))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::StepServer;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Tuning {
+    speed: f32,
+}
+
+const FALLBACK: Tuning = Tuning { speed: 0.0 };
+
+fn keep_speed(step: &StepServer, speed: f32) {
+    let mut kept = DebuggableBuilder::new("speed", 0.0_f32).scoped(step.scoped_server()).keep().build();
+    *kept = speed;
+    assert_eq!(*kept, speed);
+    drop(kept);
+    assert_eq!(step.handle().read().unwrap().value_of("speed"), Some(speed.to_string()));
+}
+
+fn kept_value(step: &StepServer) -> Option<String> {
+    step.handle().read().unwrap().value_of("speed")
+}
+
+#[test]
+fn migrated_value_is_adopted_and_kept_in_place_of_the_old_one() {
+    let step = StepServer::new();
+    keep_speed(&step, 1.5);
+
+    let migrations = Arc::new(AtomicUsize::new(0));
+    let counted = migrations.clone();
+    let migrate = move |json: &str| {
+        counted.fetch_add(1, Ordering::Relaxed);
+        json.parse::<f32>().ok().map(|speed| format!("{{\"speed\":{speed}}}"))
+    };
+    let tuning = DebuggableBuilder::new("speed", FALLBACK).scoped(step.scoped_server()).keep().migrate(migrate.clone()).build();
+    assert_eq!(*tuning, Tuning { speed: 1.5 });
+    assert_eq!(kept_value(&step).as_deref(), Some("{\"speed\":1.5}"));
+    drop(tuning);
+
+    // The re-kept value deserializes by itself, so it isn't migrated again
+    let tuning = DebuggableBuilder::new("speed", FALLBACK).scoped(step.scoped_server()).keep().migrate(migrate).build();
+    assert_eq!(*tuning, Tuning { speed: 1.5 });
+    assert_eq!(migrations.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn unmigrated_or_still_invalid_values_fall_back_to_the_initial_value() {
+    let step = StepServer::new();
+    keep_speed(&step, 1.5);
+    let declined = DebuggableBuilder::new("speed", FALLBACK).scoped(step.scoped_server()).keep().migrate(|_| None).build();
+    assert_eq!(*declined, FALLBACK);
+    drop(declined);
+
+    keep_speed(&step, 2.5);
+    let invalid = DebuggableBuilder::new("speed", FALLBACK).scoped(step.scoped_server()).keep().migrate(|json| Some(format!("{{\"velocity\":{json}}}"))).build();
+    assert_eq!(*invalid, FALLBACK);
+    assert_eq!(kept_value(&step).as_deref(), Some("{\"speed\":0.0}"));
+}
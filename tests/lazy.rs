@@ -0,0 +1,28 @@
+//! Lazy debuggables built before the application configures the default server.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::default_server::{default_server, is_default_server_initialized, set_default_server_initializer};
+use debug_monitor::server::debuggable_server_builder::{DebuggableServerBuilder, DEFAULT_FALLBACK_PORTS};
+
+// The default server is global, so this file holds a single test
+#[test]
+fn lazy_debuggable_registers_on_the_server_configured_after_it() {
+    // As a library would, before main configures anything
+    let score = DebuggableBuilder::new("lazy_score", 3).lazy().build();
+    assert!(!is_default_server_initialized());
+
+    set_default_server_initializer(|| DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()));
+    assert!(!is_default_server_initialized());
+    assert_eq!(*score, 3);
+    assert!(is_default_server_initialized());
+
+    let server = default_server();
+    let server = server.read().unwrap();
+    assert!(!DEFAULT_FALLBACK_PORTS.contains(&server.local_addr().unwrap().port()));
+    let infos = server.collect_infos();
+    assert_eq!(infos.len(), 1);
+    assert_eq!((infos[0].name.as_str(), infos[0].last_value_json.as_deref()), ("lazy_score", Some("3")));
+}
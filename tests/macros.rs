@@ -0,0 +1,65 @@
+//! The debuggable! and debuggable_scope! macros, compiled with and without the strip feature.
+#![cfg(feature = "server")]
+
+use std::cell::Cell;
+
+use debug_monitor::{debuggable, debuggable_scope};
+
+/// Code written once against the macros, which has to compile whether instrumentation is stripped
+/// or not. Calls while_alive while its debuggables are still alive.
+fn instrumented_code<WhileAlive: FnOnce()>(while_alive: WhileAlive) -> (i32, String, bool) {
+    let mut speed = debuggable!("macro_speed", 1);
+    speed.set(3);
+    speed.update(|speed| *speed *= 2);
+    *speed += 1;
+    let mut label = debuggable!("macro_label", "ready".to_string());
+    label.push('!');
+    let scope_ran = Cell::new(false);
+    debuggable_scope! {
+        scope_ran.set(true);
+    }
+    while_alive();
+    (*speed, (*label).clone(), scope_ran.get())
+}
+
+#[cfg(not(any(feature = "strip", all(feature = "strip_in_release", not(debug_assertions)))))]
+mod instrumented {
+    use std::net::TcpListener;
+
+    use debug_monitor::debuggable::Debuggable;
+    use debug_monitor::default_server::{default_server, set_default_server_initializer};
+    use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+
+    use super::instrumented_code;
+
+    // The default server is global, so this module holds a single test
+    #[test]
+    fn macros_register_on_the_default_server_and_run_scopes() {
+        set_default_server_initializer(|| DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()));
+        let registered = instrumented_code(|| {
+            let server = default_server().read().unwrap();
+            assert!(server.debuggable_id_of("macro_speed").is_some() && server.debuggable_id_of("macro_label").is_some());
+        });
+        assert_eq!(registered, (7, "ready!".to_string(), true));
+        assert_eq!(default_server().read().unwrap().debuggable_id_of("macro_speed"), None);
+
+        let built: Debuggable<i32> = debug_monitor::debuggable!("macro_typed", 1);
+        assert_eq!(*built, 1);
+    }
+}
+
+#[cfg(any(feature = "strip", all(feature = "strip_in_release", not(debug_assertions))))]
+mod stripped {
+    use debug_monitor::debuggable::plain_debuggable::PlainDebuggable;
+    use debug_monitor::default_server::is_default_server_initialized;
+
+    use super::instrumented_code;
+
+    #[test]
+    fn macros_expand_to_plain_values_and_drop_scopes() {
+        assert_eq!(instrumented_code(|| {}), (7, "ready!".to_string(), false));
+        let built: PlainDebuggable<i32> = debug_monitor::debuggable!("macro_typed", 1);
+        assert_eq!(*built, 1);
+        assert!(!is_default_server_initialized());
+    }
+}
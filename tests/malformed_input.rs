@@ -0,0 +1,118 @@
+//! Random bytes, truncated JSON and pathological nesting thrown at the server's message parsing.
+#![cfg(feature = "server")]
+
+mod common;
+
+use common::WireClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::input_limits::{InputLimits, InputRejection};
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::StepServer;
+
+/// Xorshift generator, so every run throws the same inputs.
+struct Noise(u64);
+
+impl Noise {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, max_len: usize) -> Vec<u8> {
+        let len = self.next() as usize % max_len;
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+
+    /// Printable text biased towards JSON punctuation.
+    fn text(&mut self, max_len: usize) -> String {
+        const ALPHABET: &[u8] = b"{}[]\":,0123456789-.eEtrufalsn \\UpdateValueidnew_value";
+        let len = self.next() as usize % max_len;
+        (0..len).map(|_| ALPHABET[self.next() as usize % ALPHABET.len()] as char).collect()
+    }
+}
+
+fn update_json(id: usize) -> String {
+    ClientUnitMessage::UpdateValue { id, new_value: "5".to_string(), request_id: None, panel: None }.to_json().unwrap()
+}
+
+#[test]
+fn random_bytes_and_truncated_json_are_rejected_without_panicking() {
+    let limits = InputLimits::default();
+    let mut noise = Noise(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..5_000 {
+        let _ = limits.parse::<ClientUnitMessage>(&String::from_utf8_lossy(&noise.bytes(256)));
+        let _ = limits.parse::<ClientUnitMessage>(&noise.text(256));
+    }
+
+    let json = update_json(3);
+    assert!(limits.parse::<ClientUnitMessage>(&json).is_ok());
+    for len in 0..json.len() {
+        assert_eq!(limits.parse::<ClientUnitMessage>(&json[..len]), Err(InputRejection::Unparseable), "{}", &json[..len]);
+    }
+}
+
+#[test]
+fn nesting_and_length_are_checked_before_parsing() {
+    let limits = InputLimits { max_message_len: 1024, max_nesting_depth: 8 };
+    let deep = format!("{}{}", "[".repeat(9), "]".repeat(9));
+    assert_eq!(limits.check(&deep), Err(InputRejection::TooDeep { max_depth: 8 }));
+    assert!(limits.check(&format!("{}{}", "[".repeat(8), "]".repeat(8))).is_ok());
+    // Brackets inside strings don't nest
+    assert!(limits.check(&format!("\"{}\"", "[".repeat(100))).is_ok());
+    // Unbalanced closing brackets can't hide opening ones
+    assert_eq!(limits.check(&format!("{}{}", "]".repeat(100), "[".repeat(9))), Err(InputRejection::TooDeep { max_depth: 8 }));
+
+    let padded = format!("{}{}", " ".repeat(2048), update_json(3));
+    assert_eq!(limits.check(&padded), Err(InputRejection::TooLong { len: padded.len(), max_len: 1024 }));
+    // The default limits reject megabytes of nesting without recursing into them
+    let huge = "[".repeat(4 * 1024 * 1024);
+    assert!(matches!(InputLimits::default().parse::<ClientUnitMessage>(&huge), Err(InputRejection::TooDeep { .. })));
+}
+
+#[test]
+fn server_counts_garbage_and_keeps_serving_the_client() {
+    let step = StepServer::new();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    assert!(step.accept_until(1));
+
+    let mut noise = Noise(42);
+    let mut garbage = (0..50).map(|_| noise.text(200)).filter(|text| !text.is_empty()).collect::<Vec<_>>();
+    garbage.push(update_json(id)[..10].to_string());
+    garbage.push(format!("{}{}", "{\"a\":".repeat(100), "}".repeat(100)));
+    // A confused client echoing what servers send
+    garbage.push(format!("{{\"Notify\":{{\"id\":{id},\"name\":\"counter\",\"value_in_json\":\"5\"}}}}"));
+    garbage.iter().for_each(|text| client.send_raw(text));
+    client.send_raw(&update_json(id));
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+
+    assert_eq!(step.handle().read().unwrap().stats().rejected_messages, garbage.len() as u64);
+    assert_eq!(step.handle().read().unwrap().memory_report().pending.len(), 1);
+    let errors = client.receive_until(|received| received.iter().filter(|message| matches!(message, ServerMessage::Error { .. })).count() == garbage.len());
+    assert!(errors.is_some());
+    assert_eq!(*counter, 5);
+    assert_eq!(step.handle().read().unwrap().client_count(), 1);
+}
+
+#[test]
+fn repeat_offenders_are_disconnected_after_their_strikes() {
+    let builder = DebuggableServerBuilder::new(std::net::TcpListener::bind("127.0.0.1:0").unwrap()).disconnect_after_strikes(3);
+    let step = StepServer::from_builder(builder);
+    let mut offender = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    let mut bystander = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    assert!(step.accept_until(2));
+
+    offender.send_raw("{ not json");
+    offender.send_raw("[[[");
+    assert!(step.read_until(|server| server.stats().rejected_messages == 2));
+    assert_eq!(step.handle().read().unwrap().client_count(), 2);
+    offender.send_raw("}");
+    assert!(step.read_until(|server| server.stats().clients_struck_out == 1 && server.client_count() == 1));
+    bystander.send_raw("still garbage");
+    assert!(step.read_until(|server| server.stats().rejected_messages == 4));
+    assert_eq!(step.handle().read().unwrap().stats().clients_struck_out, 1);
+}
@@ -0,0 +1,81 @@
+//! Moving debuggables from one server to another while the host runs.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{RemoveReason, ServerMessage};
+use debug_monitor::server::DebuggableServer;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn id_on(step: &StepServer, name: &str) -> Option<usize> {
+    step.handle().read().unwrap().debuggable_id_of(name)
+}
+
+fn was_moved(id: usize) -> impl FnMut(&debug_monitor::client::DebuggableClient, &[ServerMessage]) -> bool {
+    move |_, received| received.iter().any(|message| matches!(message, ServerMessage::Remove { id: removed, reason: RemoveReason::Moved, .. } if *removed == id))
+}
+
+#[test]
+fn migrated_debuggable_is_removed_from_the_old_server_and_notified_on_the_new_one() {
+    let (old, new) = (StepServer::new(), StepServer::new());
+    let mut counter = DebuggableBuilder::new("counter", 3).scoped(old.scoped_server()).build();
+    let old_id = id_on(&old, "counter").unwrap();
+    let mut old_client = old.connect().unwrap();
+    assert!(old.accept_until(1));
+    assert!(poll_client_until(&mut old_client, |client, _| client.debuggable(old_id).is_some()).is_some());
+    let mut new_client = new.connect().unwrap();
+    assert!(new.accept_until(1));
+
+    counter.migrate_to(new.handle());
+    assert!(poll_client_until(&mut old_client, was_moved(old_id)).is_some());
+    assert_eq!(id_on(&old, "counter"), None);
+    let new_id = id_on(&new, "counter").unwrap();
+    assert!(poll_client_until(&mut new_client, |client, _| client.debuggable(new_id).is_some_and(|counter| counter.value_in_json == "3")).is_some());
+
+    *counter = 5;
+    assert_eq!(*counter, 5);
+    assert_eq!(new.handle().read().unwrap().value_of("counter").as_deref(), Some("5"));
+}
+
+#[test]
+fn updates_pending_on_the_old_server_are_dropped() {
+    let (old, new) = (StepServer::new(), StepServer::new());
+    let mut counter = DebuggableBuilder::new("counter", 3).scoped(old.scoped_server()).build();
+    let old_id = id_on(&old, "counter").unwrap();
+    let mut old_client = old.connect().unwrap();
+    assert!(old.accept_until(1));
+    old_client.send_update(old_id, "9").unwrap();
+    assert!(old.read_until(|server| server.pending_updates_of(old_id) == 1));
+    counter.migrate_to(new.handle());
+    assert_eq!(*counter, 3);
+    assert_eq!(new.handle().read().unwrap().value_of("counter").as_deref(), Some("3"));
+}
+
+#[test]
+fn kept_values_are_not_left_behind() {
+    let (old, new) = (StepServer::new(), StepServer::new());
+    let mut counter = DebuggableBuilder::new("counter", 3).keep().scoped(old.scoped_server()).build();
+    counter.migrate_to(new.handle());
+    assert_eq!(id_on(&old, "counter"), None);
+    assert_eq!(old.handle().read().unwrap().value_of("counter"), None);
+}
+
+#[test]
+fn adopted_server_hands_over_debuggables_as_they_sync() {
+    let (old, new) = (StepServer::new(), StepServer::new());
+    let speed = DebuggableBuilder::new("speed", 1.5_f32).scoped(old.scoped_server()).build();
+    let lives = DebuggableBuilder::new("lives", 3).scoped(old.scoped_server()).build();
+    let old_speed_id = id_on(&old, "speed").unwrap();
+    let mut old_client = old.connect().unwrap();
+    assert!(old.accept_until(1));
+    assert!(poll_client_until(&mut old_client, |client, _| client.debuggable(old_speed_id).is_some()).is_some());
+
+    DebuggableServer::adopt_all_from(&new.handle(), &old.handle().read().unwrap());
+    assert_eq!(*speed, 1.5);
+    assert!(poll_client_until(&mut old_client, was_moved(old_speed_id)).is_some());
+    assert!(id_on(&new, "speed").is_some());
+    // Not synced since, so still served by the old server
+    assert!(id_on(&old, "lives").is_some());
+    assert_eq!(*lives, 3);
+    assert_eq!(id_on(&old, "lives"), None);
+    assert_eq!(new.handle().read().unwrap().value_of("lives").as_deref(), Some("3"));
+}
@@ -0,0 +1,68 @@
+//! One debuggable served by two servers at once, editable and notified on both.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::{DebuggableClient, UpdateOutcome};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn connect(step: &StepServer, id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|debuggable| !debuggable.value_in_json.is_empty())).is_some());
+    client
+}
+
+fn sees(client: &mut DebuggableClient, id: usize, value_in_json: &str) -> bool {
+    poll_client_until(client, |client, _| client.debuggable(id).is_some_and(|debuggable| debuggable.value_in_json == value_in_json)).is_some()
+}
+
+#[test]
+fn both_servers_edit_and_get_notified() {
+    let (local, external) = (StepServer::new(), StepServer::new());
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(local.scoped_server()).also_on(external.handle()).build();
+    let local_id = local.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let external_id = external.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut local_client = connect(&local, local_id);
+    let mut external_client = connect(&external, external_id);
+
+    local_client.send_update(local_id, "2").unwrap();
+    assert!(local.read_until(|server| server.pending_updates_of(local_id) == 1));
+    assert_eq!(*speed, 2);
+    assert!(sees(&mut external_client, external_id, "2"));
+
+    external_client.send_update(external_id, "3").unwrap();
+    assert!(external.read_until(|server| server.pending_updates_of(external_id) == 1));
+    assert_eq!(*speed, 3);
+    assert!(sees(&mut local_client, local_id, "3"));
+
+    *speed = 4;
+    assert_eq!(*speed, 4);
+    assert!(sees(&mut local_client, local_id, "4"));
+    assert!(sees(&mut external_client, external_id, "4"));
+}
+
+#[test]
+fn first_server_wins_ties_and_dropping_unregisters_from_both() {
+    let (local, external) = (StepServer::new(), StepServer::new());
+    let speed = DebuggableBuilder::new("speed", 1).scoped(local.scoped_server()).also_on(external.handle()).build();
+    let local_id = local.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let external_id = external.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut local_client = connect(&local, local_id);
+    let mut external_client = connect(&external, external_id);
+
+    let local_update = local_client.send_tracked_update(local_id, "10").unwrap();
+    let external_update = external_client.send_tracked_update(external_id, "20").unwrap();
+    assert!(local.read_until(|server| server.pending_updates_of(local_id) == 1));
+    assert!(external.read_until(|server| server.pending_updates_of(external_id) == 1));
+    assert_eq!(*speed, 10);
+    assert!(poll_client_until(&mut local_client, |client, _| !client.is_update_pending(local_update)).is_some());
+    assert!(poll_client_until(&mut external_client, |client, _| !client.is_update_pending(external_update)).is_some());
+    assert_eq!(local_client.take_update_outcomes(), vec![(local_update, UpdateOutcome::Accepted)]);
+    assert_eq!(external_client.take_update_outcomes(), vec![(external_update, UpdateOutcome::Rejected)]);
+    assert!(sees(&mut external_client, external_id, "10"));
+
+    drop(speed);
+    assert_eq!(local.handle().read().unwrap().debuggable_id_of("speed"), None);
+    assert_eq!(external.handle().read().unwrap().debuggable_id_of("speed"), None);
+    assert!(poll_client_until(&mut external_client, |client, _| client.debuggable(external_id).is_none()).is_some());
+}
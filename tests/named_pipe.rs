@@ -0,0 +1,35 @@
+//! Clients connecting through a Windows named pipe instead of TCP.
+#![cfg(feature = "windows-pipes")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+
+#[cfg(windows)]
+#[test]
+fn value_round_trips_through_the_pipe() {
+    use debug_monitor::debuggable::DebuggableBuilder;
+    use debug_monitor::testing::{poll_client_until, StepServer};
+
+    let name = format!(r"\\.\pipe\debug_monitor_test_{}", std::process::id());
+    let step = StepServer::from_builder(DebuggableServerBuilder::named_pipe(&name));
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = DebuggableClient::connect_named_pipe(&name, step.framing()).unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "1")).is_some());
+    client.send_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*counter, 5);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn named_pipes_are_unsupported_outside_of_windows() {
+    use debug_monitor::client::MessageFraming;
+
+    let name = r"\\.\pipe\debug_monitor_test";
+    let built = DebuggableServerBuilder::named_pipe(name).try_build();
+    assert_eq!(built.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+    let connected = DebuggableClient::connect_named_pipe(name, MessageFraming::new("\n", "\\n"));
+    assert_eq!(connected.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+}
@@ -0,0 +1,127 @@
+//! Debuggables whose value is only computed and sent when clients ask for it.
+#![cfg(feature = "server")]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Connects the clients and waits for the server to read their Hello, by waiting for the update
+/// each sends to the greeting debuggable right after.
+fn greeted_clients(step: &StepServer, greeting_id: usize, count: usize) -> Vec<DebuggableClient> {
+    let mut clients = (0..count).map(|_| step.connect().unwrap()).collect::<Vec<_>>();
+    assert!(step.accept_until(count));
+    clients.iter_mut().for_each(|client| client.send_update(greeting_id, "0").unwrap());
+    assert!(step.read_until(|server| server.pending_updates_of(greeting_id) == count));
+    clients
+}
+
+fn notifies_of(received: &[ServerMessage], debuggable_id: usize) -> usize {
+    received.iter().filter(|message| matches!(message, ServerMessage::Notify { id, .. } if *id == debuggable_id)).count()
+}
+
+#[test]
+fn one_fulfillment_answers_every_requester() {
+    let step = StepServer::new();
+    let mut greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let mut statistics = DebuggableBuilder::new("statistics", 0).scoped(step.scoped_server()).on_demand().build();
+    let (greeting_id, statistics_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("statistics").unwrap())
+    };
+    let mut clients = greeted_clients(&step, greeting_id, 3);
+    for client in clients.iter_mut() {
+        let advertised = poll_client_until(client, |client, _| client.debuggable(statistics_id).is_some_and(|statistics| statistics.on_demand)).unwrap();
+        assert_eq!(notifies_of(&advertised, statistics_id), 0);
+        assert!(client.debuggable(statistics_id).unwrap().value_in_json.is_empty());
+    }
+
+    clients[0].request_value(statistics_id).unwrap();
+    assert!(step.read_until(|server| server.value_requesters_of(statistics_id) == 1));
+    assert_eq!(statistics.poll_requests(), 1);
+    // Requests arriving while the value is being computed are answered by the same value
+    clients[1].request_value(statistics_id).unwrap();
+    clients[1].request_value(statistics_id).unwrap();
+    assert!(step.read_until(|server| server.value_requesters_of(statistics_id) == 2));
+    *statistics = 42;
+    assert_eq!(statistics.poll_requests(), 0);
+
+    for client in clients[..2].iter_mut() {
+        let received = poll_client_until(client, |client, _| client.debuggable(statistics_id).is_some_and(|statistics| statistics.value_in_json == "42")).unwrap();
+        assert_eq!(notifies_of(&received, statistics_id), 1);
+    }
+    *greeting = 1;
+    assert_eq!(*greeting, 1);
+    let received = poll_client_until(&mut clients[2], |client, _| client.debuggable(greeting_id).is_some_and(|greeting| greeting.value_in_json == "1")).unwrap();
+    assert_eq!(notifies_of(&received, statistics_id), 0);
+    assert!(clients[2].debuggable(statistics_id).unwrap().value_in_json.is_empty());
+}
+
+#[test]
+fn values_change_without_reaching_clients_until_requested() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let mut statistics = DebuggableBuilder::new("statistics", 0).scoped(step.scoped_server()).on_demand().build();
+    let (greeting_id, statistics_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("statistics").unwrap())
+    };
+    let mut client = greeted_clients(&step, greeting_id, 1).remove(0);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(statistics_id).is_some_and(|statistics| statistics.on_demand)).is_some());
+
+    *statistics = 5;
+    assert_eq!(statistics.poll_requests(), 0);
+    client.request_value(statistics_id).unwrap();
+    assert!(step.read_until(|server| server.value_requesters_of(statistics_id) == 1));
+    assert_eq!(step.handle().read().unwrap().memory_report().clients.value_requesters, 1);
+    assert_eq!(statistics.poll_requests(), 1);
+    assert_eq!(statistics.poll_requests(), 0);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(statistics_id).is_some_and(|statistics| statistics.value_in_json == "5")).unwrap();
+    assert_eq!(notifies_of(&received, statistics_id), 1);
+    assert_eq!(step.handle().read().unwrap().memory_report().clients.value_requesters, 0);
+}
+
+#[test]
+fn providers_compute_the_value_once_per_fulfillment() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let computations = Rc::new(Cell::new(0));
+    let computed = computations.clone();
+    let statistics = DebuggableBuilder::new("statistics", 0)
+        .scoped(step.scoped_server())
+        .provide_on_demand(move || {
+            computed.set(computed.get() + 1);
+            computed.get() * 10
+        })
+        .build();
+    let (greeting_id, statistics_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("statistics").unwrap())
+    };
+    let mut clients = greeted_clients(&step, greeting_id, 3);
+    assert_eq!(*statistics, 0);
+    assert_eq!(computations.get(), 0);
+
+    clients[0].request_value(statistics_id).unwrap();
+    clients[1].request_value(statistics_id).unwrap();
+    assert!(step.read_until(|server| server.value_requesters_of(statistics_id) == 2));
+    assert_eq!(*statistics, 10);
+    assert_eq!(computations.get(), 1);
+    for client in clients[..2].iter_mut() {
+        assert!(poll_client_until(client, |client, _| client.debuggable(statistics_id).is_some_and(|statistics| statistics.value_in_json == "10")).is_some());
+    }
+
+    clients[2].request_value(statistics_id).unwrap();
+    assert!(step.read_until(|server| server.value_requesters_of(statistics_id) == 1));
+    assert_eq!(*statistics, 20);
+    assert_eq!(*statistics, 20);
+    assert_eq!(computations.get(), 2);
+    let received = poll_client_until(&mut clients[2], |client, _| client.debuggable(statistics_id).is_some_and(|statistics| statistics.value_in_json == "20")).unwrap();
+    assert_eq!(notifies_of(&received, statistics_id), 1);
+}
@@ -0,0 +1,100 @@
+//! Tracked updates applied to the client's mirror before the server answers, and rolled back if
+//! they lose.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::{ClientEvent, DebuggableClient, UpdateOutcome};
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn speed_with_client(step: &StepServer) -> (Debuggable<i32>, usize, DebuggableClient) {
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let client = connect(step, id, 1);
+    (speed, id, client)
+}
+
+fn connect(step: &StepServer, id: usize, client_count: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(client_count));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|speed| speed.value_in_json == "1")).is_some());
+    client
+}
+
+fn mirrored(client: &DebuggableClient, id: usize) -> String {
+    client.debuggable(id).unwrap().value_in_json.clone()
+}
+
+#[test]
+fn accepted_update_stays_and_rejected_one_rolls_back() {
+    let step = StepServer::new();
+    let (speed, id, mut client) = speed_with_client(&step);
+    client.set_optimistic(true);
+
+    let accepted = client.send_tracked_update(id, "5").unwrap();
+    assert_eq!(mirrored(&client, id), "5");
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 5);
+    assert!(poll_client_until(&mut client, |client, _| !client.is_update_pending(accepted)).is_some());
+    assert_eq!(client.take_update_outcomes(), vec![(accepted, UpdateOutcome::Accepted)]);
+    assert_eq!(mirrored(&client, id), "5");
+    assert!(client.take_events().is_empty());
+
+    let rejected = client.send_tracked_update(id, "\"fast\"").unwrap();
+    assert_eq!(mirrored(&client, id), "\"fast\"");
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 5);
+    assert!(poll_client_until(&mut client, |client, _| !client.is_update_pending(rejected)).is_some());
+    assert_eq!(client.take_update_outcomes(), vec![(rejected, UpdateOutcome::Rejected)]);
+    assert_eq!(mirrored(&client, id), "5");
+    assert_eq!(client.take_events(), vec![ClientEvent::Reverted { id }]);
+}
+
+#[test]
+fn concurrent_edit_of_another_client_wins_over_the_optimistic_one() {
+    let step = StepServer::new();
+    let (speed, id, mut optimist) = speed_with_client(&step);
+    let mut other = connect(&step, id, 2);
+    optimist.set_optimistic(true);
+
+    let overwritten = optimist.send_tracked_update(id, "5").unwrap();
+    assert_eq!(mirrored(&optimist, id), "5");
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    other.send_update(id, "9").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 2));
+    assert_eq!(*speed, 9);
+
+    // Whether the ack or the other client's value arrives first, the mirror ends up with the latter
+    assert!(poll_client_until(&mut optimist, |client, _| !client.is_update_pending(overwritten) && mirrored(client, id) == "9").is_some());
+    assert_eq!(optimist.take_update_outcomes(), vec![(overwritten, UpdateOutcome::Rejected)]);
+    assert_eq!(optimist.take_events(), vec![ClientEvent::Reverted { id }]);
+}
+
+#[test]
+fn stale_echo_does_not_undo_the_latest_update() {
+    let step = StepServer::new();
+    let (mut speed, id, mut client) = speed_with_client(&step);
+    client.set_optimistic(true);
+
+    let first = client.send_tracked_update(id, "5").unwrap();
+    let latest = client.send_tracked_update(id, "6").unwrap();
+    assert_eq!(mirrored(&client, id), "6");
+
+    // The host notifies the value of the older update before the server reads either of them
+    *speed = 5;
+    assert_eq!(*speed, 5);
+    assert!(poll_client_until(&mut client, |_, received| {
+        received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, value_in_json, .. } if *notified == id && value_in_json == "5"))
+    }).is_some());
+    assert_eq!(mirrored(&client, id), "6");
+
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 2));
+    assert_eq!(*speed, 6);
+    assert!(poll_client_until(&mut client, |client, _| !client.is_update_pending(first) && !client.is_update_pending(latest)).is_some());
+    let mut outcomes = client.take_update_outcomes();
+    outcomes.sort_by_key(|(request_id, _)| *request_id);
+    assert_eq!(outcomes, vec![(first, UpdateOutcome::Rejected), (latest, UpdateOutcome::Accepted)]);
+    // The superseded update hands its previous value to the latest one instead of reverting
+    assert_eq!(mirrored(&client, id), "6");
+    assert!(client.take_events().is_empty());
+}
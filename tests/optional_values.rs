@@ -0,0 +1,49 @@
+//! Debuggables of optional values going between Some and None, on either side of the wire.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::UpdateOutcome;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn some_none_some_round_trip_is_visible_to_a_client() {
+    let step = StepServer::new();
+    let mut target = DebuggableBuilder::new("target", Some(3)).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("target").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|target| target.value_in_json == "3" && target.nullable)).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Metadata { id: described, nullable: true, .. } if *described == id)));
+
+    assert_eq!(target.clear(), Some(3));
+    assert_eq!(*target, None);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|target| target.value_in_json == "null")).is_some());
+
+    *target = Some(7);
+    assert_eq!(*target, Some(7));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|target| target.value_in_json == "7")).is_some());
+    assert!(client.debuggable(id).unwrap().nullable);
+}
+
+#[test]
+fn client_clears_and_sets_again_without_being_corrected() {
+    let step = StepServer::new();
+    let target = DebuggableBuilder::new("target", Some(3)).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("target").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|target| target.value_in_json == "3")).is_some());
+
+    let cleared = client.send_tracked_update(id, "null").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*target, None);
+    let set_again = client.send_tracked_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*target, Some(5));
+
+    let received = poll_client_until(&mut client, |client, _| !client.is_update_pending(set_again)).unwrap();
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Error { .. })), "{received:?}");
+    assert_eq!(client.take_update_outcomes(), vec![(cleared, UpdateOutcome::Accepted), (set_again, UpdateOutcome::Accepted)]);
+    assert_eq!(client.debuggable(id).unwrap().value_in_json, "5");
+}
@@ -0,0 +1,45 @@
+//! Order metadata monitors sort debuggables by, set when built and adjusted at runtime.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{JSONDeSerializable, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn sorted_names(client: &DebuggableClient) -> Vec<String> {
+    client.sorted_debuggables().into_iter().map(|(_, debuggable)| debuggable.name.clone()).collect()
+}
+
+#[test]
+fn metadata_round_trips_with_its_order() {
+    for order in [i32::MIN, -3, 0, 7, i32::MAX] {
+        let metadata = ServerMessage::Metadata { id: 4, nullable: false, order, on_demand: false };
+        assert_eq!(ServerMessage::from_json(&metadata.to_json().unwrap()), Some(metadata));
+    }
+}
+
+#[test]
+fn clients_sort_by_order_then_name_and_follow_runtime_changes() {
+    let step = StepServer::new();
+    let _beta = DebuggableBuilder::new("beta", 0).scoped(step.scoped_server()).build();
+    let _alpha = DebuggableBuilder::new("alpha", 0).scoped(step.scoped_server()).build();
+    let _zeta = DebuggableBuilder::new("zeta", 0).scoped(step.scoped_server()).order(-1).build();
+    let beta_id = step.handle().read().unwrap().debuggable_id_of("beta").unwrap();
+    assert_eq!(step.handle().read().unwrap().order_of(beta_id), Some(0));
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.sorted_debuggables().len() == 3 && client.sorted_debuggables()[0].1.order == -1).is_some());
+    assert_eq!(sorted_names(&client), ["zeta", "alpha", "beta"]);
+
+    step.handle().read().unwrap().set_order(beta_id, -5);
+    assert_eq!(step.handle().read().unwrap().order_of(beta_id), Some(-5));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(beta_id).is_some_and(|beta| beta.order == -5)).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Metadata { id, order: -5, .. } if *id == beta_id)));
+    assert_eq!(sorted_names(&client), ["beta", "zeta", "alpha"]);
+
+    // Setting the same order again broadcasts nothing
+    step.handle().read().unwrap().set_order(beta_id, -5);
+    step.handle().read().unwrap().refresh_now();
+    let received = poll_client_until(&mut client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Notify { .. }))).unwrap();
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Metadata { .. })), "{received:?}");
+}
@@ -0,0 +1,49 @@
+//! Overlays rendered by the host, fed straight from the server with edited rows flashing.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::overlay::OverlayEntry;
+use debug_monitor::server::DebuggableServer;
+use debug_monitor::testing::{ManualClock, StepServer};
+
+fn rows(entries: &[OverlayEntry]) -> Vec<(&str, Option<&str>, bool)> {
+    entries.iter().map(|entry| (entry.name.as_str(), entry.value_json.as_deref(), entry.changed_recently)).collect()
+}
+
+#[test]
+fn changed_recently_decays_after_the_highlight_duration() {
+    let clock = Arc::new(ManualClock::new());
+    let step = StepServer::from_builder(DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone()));
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let _secret = DebuggableBuilder::new("secret", 0).scoped(step.scoped_server()).hidden(true).build();
+    let feed = DebuggableServer::overlay_feed(&step.handle()).highlight(Duration::from_millis(500));
+    assert_eq!(rows(&feed.entries()), [("speed", Some("1"), true)]);
+
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(rows(&feed.entries()), [("speed", Some("1"), false)]);
+
+    *speed = 2;
+    assert_eq!(*speed, 2);
+    clock.advance(Duration::from_millis(499));
+    assert_eq!(rows(&feed.clone().entries()), [("speed", Some("2"), true)]);
+    clock.advance(Duration::from_millis(1));
+    assert_eq!(rows(&feed.entries()), [("speed", Some("2"), false)]);
+}
+
+#[test]
+fn entries_into_reuses_and_trims_the_given_entries() {
+    let step = StepServer::new();
+    let _speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let feed = DebuggableServer::overlay_feed(&step.handle());
+    let stale = OverlayEntry { id: 9, name: "stale".to_string(), value_json: None, changed_recently: false };
+    let mut entries = vec![stale.clone(), stale.clone(), stale];
+
+    feed.entries_into(&mut entries);
+    let named = entries.iter().map(|entry| (entry.id, entry.name.as_str(), entry.value_json.as_deref())).collect::<Vec<_>>();
+    assert_eq!(named, [(step.handle().read().unwrap().debuggable_id_of("speed").unwrap(), "speed", Some("1"))]);
+}
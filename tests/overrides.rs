@@ -0,0 +1,98 @@
+//! Values set from a monitor rather than by host code, flagged until the host writes them again.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Connects a client and waits for the value it's sent on connecting, so later Notify messages are
+/// told apart from it.
+fn connect(step: &StepServer, client_count: usize, debuggable_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(client_count));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(debuggable_id).is_some_and(|debuggable| !debuggable.value_in_json.is_empty())).is_some());
+    client
+}
+
+/// Polls the client until it's sent the value of the debuggable, returning whether it came flagged
+/// as overridden.
+fn notified_override(client: &mut DebuggableClient, debuggable_id: usize, value_in_json: &str) -> bool {
+    let received = poll_client_until(client, |_, received| received.iter().any(|message| {
+        matches!(message, ServerMessage::Notify { id, value_in_json: notified, .. } if *id == debuggable_id && notified == value_in_json)
+    })).unwrap();
+    received.iter().rev().find_map(|message| match message {
+        ServerMessage::Notify { id, overridden, .. } if *id == debuggable_id => Some(*overridden),
+        _ => None,
+    }).unwrap()
+}
+
+#[test]
+fn remote_edits_are_flagged_until_the_host_writes() {
+    let step = StepServer::new();
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut editor = connect(&step, 1, id);
+    let mut bystander = connect(&step, 2, id);
+    assert!(!speed.is_overridden());
+
+    editor.send_update(id, "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 2);
+    assert!(speed.is_overridden());
+    assert_eq!(step.handle().read().unwrap().overridden_ids(), vec![id]);
+    assert!(notified_override(&mut bystander, id, "2"));
+    assert!(poll_client_until(&mut bystander, |client, _| client.debuggable(id).is_some_and(|speed| speed.overridden)).is_some());
+
+    // Replays keep the flag
+    bystander.send_renotify().unwrap();
+    assert!(poll_client_until(&mut bystander, |_, received| {
+        step.read_until(|_| true);
+        received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, overridden: true, .. } if *notified == id))
+    }).is_some());
+
+    *speed = 3;
+    assert_eq!(*speed, 3);
+    assert!(!speed.is_overridden());
+    assert!(step.handle().read().unwrap().overridden_ids().is_empty());
+    assert!(!notified_override(&mut bystander, id, "3"));
+}
+
+#[test]
+fn clearing_an_override_restores_the_initial_value() {
+    let step = StepServer::new();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut editor = connect(&step, 1, id);
+    assert!(!step.handle().read().unwrap().clear_override(id));
+
+    editor.send_update(id, "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 2);
+    assert!(speed.is_overridden());
+
+    assert!(step.handle().read().unwrap().clear_override(id));
+    assert_eq!(*speed, 1);
+    assert!(!speed.is_overridden());
+    assert!(!notified_override(&mut editor, id, "1"));
+}
+
+#[test]
+fn overrides_of_the_initial_value_clear_right_away() {
+    let step = StepServer::new();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut editor = connect(&step, 1, id);
+
+    editor.send_update(id, "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 2);
+    editor.send_update(id, "1").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 1);
+    assert!(speed.is_overridden());
+
+    assert!(step.handle().read().unwrap().clear_override(id));
+    assert!(!speed.is_overridden());
+    assert!(!notified_override(&mut editor, id, "1"));
+}
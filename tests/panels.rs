@@ -0,0 +1,56 @@
+//! Panels of one monitor multiplexed over a single connection, each attributed its own updates.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{ClientUnitMessage, ServerMessage, PROTOCOL_VERSION};
+use debug_monitor::server::pending_updates::UpdateOrigin;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+#[test]
+fn pending_updates_name_the_panel_that_sent_them() {
+    let step = StepServer::new();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.client_id().is_some()).is_some());
+    let client_index = client.client_id().unwrap();
+
+    client.send_panel_update("tuning", id, "2").unwrap();
+    client.send_panel_update("camera", id, "3").unwrap();
+    client.send_update(id, "4").unwrap();
+    client.set_panel(Some("qa".to_string()));
+    client.send_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 4));
+
+    let authors = step.handle().read().unwrap().pending_details(id).into_iter()
+        .map(|pending| (pending.client, pending.panel, pending.origin))
+        .collect::<Vec<_>>();
+    let panels = [Some("tuning"), Some("camera"), None, Some("qa")].map(|panel| (client_index, panel.map(str::to_string), UpdateOrigin::Socket));
+    assert_eq!(authors, panels);
+    assert_eq!(*speed, 5);
+}
+
+#[test]
+fn errors_and_acks_echo_the_panel_on_the_shared_socket() {
+    let step = StepServer::new();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    // The panel said hello with is the default of updates that don't name one
+    client.send(&ClientUnitMessage::Hello { protocol_version: PROTOCOL_VERSION, supports_deflate: false, panel: Some("hud".to_string()), supports_text_patches: false, session_key: None }).unwrap();
+    client.send_panel_update("camera", id, "\"fast\"").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(step.handle().read().unwrap().pending_details(id)[0].panel.as_deref(), Some("camera"));
+    assert_eq!(*speed, 1);
+    let tracked = client.send_tracked_update(id, "7").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(step.handle().read().unwrap().pending_details(id)[0].panel.as_deref(), Some("hud"));
+    assert_eq!(*speed, 7);
+
+    let received = poll_client_until(&mut client, |client, _| !client.is_update_pending(tracked)).unwrap();
+    let rejection = received.iter().find(|message| matches!(message, ServerMessage::Error { .. }));
+    assert!(matches!(rejection, Some(ServerMessage::Error { panel: Some(panel), .. }) if panel == "camera"), "{received:?}");
+    assert!(received.contains(&ServerMessage::UpdateAck { request_id: tracked, accepted: true, panel: Some("hud".to_string()) }), "{received:?}");
+}
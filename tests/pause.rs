@@ -0,0 +1,74 @@
+//! Pausing a server's networking and consolidating the changes made meanwhile on resume.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{ClientUnitMessage, ServerMessage};
+use debug_monitor::server::DebuggableServer;
+
+use common::{poll_until, serve_until, WireClient};
+
+fn server() -> (Arc<RwLock<DebuggableServer>>, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    (Arc::new(RwLock::new(DebuggableServer::new(listener))), addr)
+}
+
+fn notifies_in(received: &[ServerMessage]) -> Vec<(usize, String, String)> {
+    received.iter().filter_map(|message| match message {
+        ServerMessage::Notify { id, name, value_in_json } => Some((*id, name.clone(), value_in_json.clone())),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn resuming_sends_one_notify_per_changed_debuggable() {
+    let (server, addr) = server();
+    let names = ["a", "b", "c"];
+    let mut values = names.map(|name| DebuggableBuilder::new(name, 0).server(Some(server.clone())).build());
+    let mut client = WireClient::connect(&server.read().unwrap(), addr);
+    assert!(poll_until(&server.read().unwrap(), &mut client, |received| notifies_in(received).len() == names.len()).is_some());
+
+    server.read().unwrap().pause();
+    assert!(server.read().unwrap().is_paused());
+    for round in 1..=2 {
+        for value in values.iter_mut() {
+            **value = round;
+            assert_eq!(**value, round);
+        }
+    }
+    server.read().unwrap().resume();
+    assert!(!server.read().unwrap().is_paused());
+
+    let received = client.receive_until(|received| notifies_in(received).len() >= names.len()).unwrap();
+    let notifies = notifies_in(&received);
+    assert_eq!(notifies.len(), names.len());
+    assert!(notifies.iter().all(|(_, _, value_in_json)| value_in_json == "2"));
+    assert!(names.iter().all(|name| notifies.iter().any(|(_, notified_name, _)| notified_name == name)));
+}
+
+#[test]
+fn updates_received_before_pausing_stay_queued() {
+    let (server, addr) = server();
+    let speed = DebuggableBuilder::new("speed", 1).server(Some(server.clone())).build();
+    // Clients' messages are read in order, so the update is queued once the marker is handled
+    let marker_handled = Arc::new(AtomicBool::new(false));
+    let handled = marker_handled.clone();
+    server.write().unwrap().on_custom("marker", Box::new(move |_, _| handled.store(true, Ordering::Relaxed)));
+    let mut client = WireClient::connect(&server.read().unwrap(), addr);
+    let received = poll_until(&server.read().unwrap(), &mut client, |received| !notifies_in(received).is_empty()).unwrap();
+    let id = notifies_in(&received)[0].0;
+
+    client.send(&ClientUnitMessage::UpdateValue { id, new_value: "5".to_string() });
+    client.send(&ClientUnitMessage::Custom { topic: "marker".to_string(), payload: String::new() });
+    assert!(serve_until(&server.read().unwrap(), || marker_handled.load(Ordering::Relaxed)));
+
+    server.read().unwrap().pause();
+    server.read().unwrap().resume();
+    assert_eq!(*speed, 5);
+}
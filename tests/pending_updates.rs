@@ -0,0 +1,127 @@
+//! Where pending updates came from, how long they waited and how many a debuggable keeps.
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::pending_updates::UpdateOrigin;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+fn server_with_clock(test_name: &str) -> (StepServer, Arc<ManualClock>, PathBuf) {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone());
+    let step = StepServer::from_builder(builder);
+    let dir = std::env::temp_dir().join(format!("debug_monitor-pending_updates-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    step.handle().write().unwrap().set_read_dir_create(dir.to_string_lossy()).unwrap();
+    (step, clock, dir)
+}
+
+fn counter_on(step: &StepServer) -> (Debuggable<i32>, usize) {
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    (counter, id)
+}
+
+fn origins_of(step: &StepServer, id: usize) -> Vec<UpdateOrigin> {
+    step.handle().read().unwrap().pending_details(id).into_iter().map(|details| details.origin).collect()
+}
+
+#[test]
+fn updates_are_tagged_with_their_transport_and_wait() {
+    let (step, clock, dir) = server_with_clock("origins");
+    let (mut counter, id) = counter_on(&step);
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(id, "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    clock.advance(Duration::from_secs(1));
+    DirClient::new(&dir, 7).unwrap().send_update(id, "3").unwrap();
+    step.handle().read().unwrap().read_clients_from_read_dir();
+
+    let details = step.handle().read().unwrap().pending_details(id);
+    assert_eq!(details.iter().map(|details| (details.origin, details.waiting)).collect::<Vec<_>>(),
+               vec![(UpdateOrigin::Socket, Duration::from_secs(1)), (UpdateOrigin::ReadDir, Duration::ZERO)]);
+    assert_eq!(details[1].client, 7);
+    assert_eq!(details[1].bytes, 1);
+
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(*counter, 3);
+    let stats = step.handle().read().unwrap().stats();
+    assert_eq!((stats.synced_updates, stats.update_latency_average, stats.update_latency_max), (1, Duration::from_secs(2), Duration::from_secs(2)));
+
+    step.handle().read().unwrap().queue_update(id, 0, "4".to_string());
+    assert_eq!(origins_of(&step, id), vec![UpdateOrigin::Host]);
+    *counter = 9;
+    assert_eq!(*counter, 9);
+    let stats = step.handle().read().unwrap().stats();
+    assert_eq!((stats.synced_updates, stats.update_latency_average, stats.update_latency_max), (2, Duration::from_millis(1750), Duration::from_secs(2)));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_newest_received_update_is_applied() {
+    let (step, clock, dir) = server_with_clock("newest");
+    let (counter, id) = counter_on(&step);
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    DirClient::new(&dir, 7).unwrap().send_update(id, "5").unwrap();
+    step.handle().read().unwrap().read_clients_from_read_dir();
+    clock.advance(Duration::from_millis(10));
+    client.send_update(id, "6").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 2));
+    assert_eq!(origins_of(&step, id), vec![UpdateOrigin::ReadDir, UpdateOrigin::Socket]);
+    assert_eq!(*counter, 6);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_oldest_updates_go_past_the_maximum() {
+    let (step, clock, dir) = server_with_clock("bounded");
+    step.handle().write().unwrap().set_max_pending_updates(Some(2));
+    let (counter, id) = counter_on(&step);
+    for value in ["2", "3", "4"] {
+        assert!(step.handle().read().unwrap().queue_update(id, 0, value.to_string()));
+        clock.advance(Duration::from_millis(5));
+    }
+    let details = step.handle().read().unwrap().pending_details(id);
+    assert_eq!(details.iter().map(|details| details.waiting).collect::<Vec<_>>(), vec![Duration::from_millis(10), Duration::from_millis(5)]);
+    assert_eq!(step.handle().read().unwrap().memory_report().pending_updates(), 2);
+    assert_eq!(*counter, 4);
+    assert!(step.handle().read().unwrap().pending_details(id).is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn discarded_updates_are_counted_and_their_sender_corrected() {
+    let (step, _clock, dir) = server_with_clock("discard");
+    let (mut counter, id) = counter_on(&step);
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "1")).is_some());
+
+    for value in ["2", "3", "4"] {
+        client.send_update(id, value).unwrap();
+    }
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 3));
+    assert_eq!(counter.pending_updates(), 3);
+    assert_eq!(counter.discard_pending(), 3);
+    assert_eq!(counter.pending_updates(), 0);
+    assert_eq!(*counter, 1);
+
+    let received = poll_client_until(&mut client, |_, received| received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id))).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, value_in_json, .. } if *notified == id && value_in_json == "1")), "{received:?}");
+    assert_eq!(client.debuggable(id).unwrap().value_in_json, "1");
+    // Discarding again finds nothing, nor does asking about a removed id
+    assert_eq!(counter.discard_pending(), 0);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(usize::MAX - 10), 0);
+    assert_eq!(step.handle().read().unwrap().discard_pending_of(usize::MAX - 10), 0);
+    let _ = fs::remove_dir_all(&dir);
+}
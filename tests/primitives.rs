@@ -0,0 +1,56 @@
+//! JSON of the primitive types implemented by the crate itself when no serde backend is enabled.
+#![cfg(not(any(feature = "use_serde", feature = "use_nanoserde")))]
+
+use debug_monitor::serializable::primitives::{escape_json_string, unescape_json_string};
+use debug_monitor::serializable::JSONDeSerializable;
+
+#[test]
+fn integers_round_trip_and_reject_what_does_not_fit() {
+    assert_eq!((-42_i32).to_json().unwrap(), "-42");
+    assert_eq!(i32::from_json(" -42 "), Some(-42));
+    assert_eq!(u64::MAX.to_json().unwrap(), u64::MAX.to_string());
+    assert_eq!(u8::from_json("256"), None);
+    assert_eq!(u32::from_json("-1"), None);
+    assert_eq!(i64::from_json("1.5"), None);
+}
+
+#[test]
+fn floats_round_trip_and_have_no_json_when_not_finite() {
+    assert_eq!(1.5_f32.to_json().unwrap(), "1.5");
+    assert_eq!(f64::from_json("2.0"), Some(2.0));
+    assert_eq!(f64::from_json(&0.1_f64.to_json().unwrap()), Some(0.1));
+    assert_eq!(f32::NAN.to_json(), None);
+    assert_eq!(f64::INFINITY.to_json(), None);
+    assert_eq!(f64::from_json("inf"), None);
+    assert_eq!(f32::from_json("NaN"), None);
+}
+
+#[test]
+fn bools_only_take_their_literals() {
+    assert_eq!(true.to_json().unwrap(), "true");
+    assert_eq!(bool::from_json(" false "), Some(false));
+    assert_eq!(bool::from_json("1"), None);
+    assert_eq!(bool::from_json("True"), None);
+}
+
+#[test]
+fn strings_are_escaped_and_unescaped() {
+    let text = "quote \" backslash \\ newline \n tab \t bell \u{7}".to_string();
+    let json = text.to_json().unwrap();
+    assert_eq!(json, "\"quote \\\" backslash \\\\ newline \\n tab \\t bell \\u0007\"");
+    assert_eq!(String::from_json(&json), Some(text));
+    assert_eq!(escape_json_string("plain"), "\"plain\"");
+    assert_eq!(unescape_json_string("\"\\u00e9\\ud83d\\ude00\""), Some("é😀".to_string()));
+    assert_eq!(unescape_json_string("unquoted"), None);
+    assert_eq!(unescape_json_string("\"stray \" quote\""), None);
+    assert_eq!(unescape_json_string("\"bad \\x escape\""), None);
+}
+
+#[test]
+fn options_are_null_or_their_value() {
+    assert_eq!(None::<i32>.to_json().unwrap(), "null");
+    assert_eq!(Some(3_u8).to_json().unwrap(), "3");
+    assert_eq!(Option::<i32>::from_json(" null "), Some(None));
+    assert_eq!(Option::<i32>::from_json("7"), Some(Some(7)));
+    assert_eq!(Option::<i32>::from_json("seven"), None);
+}
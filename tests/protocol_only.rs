@@ -0,0 +1,72 @@
+//! What remains of the crate without its server feature: the serializable messages and their
+//! framing, usable from targets without sockets such as wasm.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use debug_monitor::protocol::{frame, unescape, unescape_in_place};
+
+/// Modules lib.rs declares without requiring the server feature.
+const PROTOCOL_MODULES: [&str; 5] = ["serializable", "protocol", "clock", "snapshot", "error"];
+/// Anything pulling in sockets or the server's own dependencies.
+const TRANSPORT_MARKERS: [&str; 7] = ["std::net", "simple_tcp", "socket2", "fixed_index_vec", "crate::server", "crate::client", "crate::debuggable"];
+
+fn source_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+}
+
+fn rust_files_in(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_files_in(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn protocol_modules_use_no_transport() {
+    let mut files = Vec::new();
+    PROTOCOL_MODULES.iter().for_each(|module| rust_files_in(&source_dir().join(module), &mut files));
+    assert!(files.len() >= PROTOCOL_MODULES.len());
+    for file in files {
+        let contents = fs::read_to_string(&file).unwrap();
+        for marker in TRANSPORT_MARKERS {
+            assert!(!contents.contains(marker), "{} uses {marker}, which isn't available without the server feature", file.display());
+        }
+    }
+}
+
+#[test]
+fn every_other_module_requires_the_server_feature() {
+    let lib = fs::read_to_string(source_dir().join("lib.rs")).unwrap();
+    let lines = lib.lines().collect::<Vec<_>>();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(module) = line.strip_prefix("pub mod ").or_else(|| line.strip_prefix("mod ")).map(|module| module.trim_end_matches(';')) else { continue; };
+        if PROTOCOL_MODULES.contains(&module) { continue; }
+        let gate = index.checked_sub(1).map(|previous| lines[previous]).unwrap_or_default();
+        assert!(gate.starts_with("#[cfg(feature = "), "module {module} is compiled without the server feature");
+    }
+}
+
+#[test]
+fn framed_messages_unescape_back() {
+    let (endmark, escape) = ("<<end>>", "<<escaped end>>");
+    let message = "before<<end>>after<<end>>";
+    let framed = frame(message, endmark, escape);
+    assert!(framed.ends_with(endmark));
+    let body = &framed[..framed.len() - endmark.len()];
+    assert!(!body.contains(endmark));
+    assert_eq!(unescape(body, endmark, escape), message);
+
+    let mut in_place = body.to_string();
+    unescape_in_place(&mut in_place, endmark, escape);
+    assert_eq!(in_place, message);
+
+    let mut shorter_escape = frame(message, endmark, "#");
+    shorter_escape.truncate(shorter_escape.len() - endmark.len());
+    unescape_in_place(&mut shorter_escape, endmark, "#");
+    assert_eq!(shorter_escape, message);
+}
@@ -0,0 +1,77 @@
+//! Reading back and reconfiguring the read directory of a running server.
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::testing::StepServer;
+
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("debug_monitor-read_dir_config-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn settings_round_trip_through_their_getters() {
+    let step = StepServer::new();
+    let dir = temp_dir("round_trip");
+    fs::create_dir_all(&dir).unwrap();
+    let handle = step.handle();
+    assert_eq!(handle.read().unwrap().read_dir(), None);
+    assert!(!handle.read().unwrap().only_reads_from_dir());
+
+    handle.write().unwrap().set_read_dir(Some(dir.to_string_lossy().to_string())).unwrap();
+    assert_eq!(handle.read().unwrap().read_dir(), Some(dir.to_string_lossy().to_string()));
+    handle.write().unwrap().set_only_reads_from_dir(true);
+    assert!(handle.read().unwrap().only_reads_from_dir());
+    handle.write().unwrap().set_only_reads_from_dir(false);
+    assert!(!handle.read().unwrap().only_reads_from_dir());
+    handle.write().unwrap().set_read_dir(None).unwrap();
+    assert_eq!(handle.read().unwrap().read_dir(), None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_directories_and_files_are_refused_unless_created() {
+    let step = StepServer::new();
+    let dir = temp_dir("validation");
+    let handle = step.handle();
+
+    let missing = handle.write().unwrap().set_read_dir(Some(dir.to_string_lossy().to_string())).unwrap_err();
+    assert_eq!(missing.kind(), ErrorKind::NotFound);
+    assert_eq!(handle.read().unwrap().read_dir(), None);
+
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("not_a_dir");
+    fs::write(&file, "").unwrap();
+    let not_a_dir = handle.write().unwrap().set_read_dir(Some(file.to_string_lossy().to_string())).unwrap_err();
+    assert_eq!(not_a_dir.kind(), ErrorKind::InvalidInput);
+    assert_eq!(handle.read().unwrap().read_dir(), None);
+
+    let nested = dir.join("nested").join("read_dir");
+    handle.write().unwrap().set_read_dir_create(nested.to_string_lossy()).unwrap();
+    assert!(nested.is_dir());
+    assert_eq!(handle.read().unwrap().read_dir(), Some(nested.to_string_lossy().to_string()));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn swapped_directory_is_the_only_one_read() {
+    let step = StepServer::new();
+    let (old_dir, new_dir) = (temp_dir("swap_old"), temp_dir("swap_new"));
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    step.handle().write().unwrap().set_read_dir_create(old_dir.to_string_lossy()).unwrap();
+    step.handle().write().unwrap().set_read_dir_create(new_dir.to_string_lossy()).unwrap();
+
+    DirClient::new(&old_dir, 1).unwrap().send_update(id, "5").unwrap();
+    DirClient::new(&new_dir, 2).unwrap().send_update(id, "7").unwrap();
+    assert!(step.handle().read().unwrap().read_clients_from_read_dir() > 0);
+    assert_eq!(*counter, 7);
+    let _ = fs::remove_dir_all(&old_dir);
+    let _ = fs::remove_dir_all(&new_dir);
+}
@@ -0,0 +1,107 @@
+//! Read directories deleted while the server runs, and the server noticing they're back.
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::events::ServerEvent;
+use debug_monitor::server::READ_DIR_RECREATE_INTERVAL;
+use debug_monitor::testing::{ManualClock, StepServer, STEP_TIMEOUT};
+
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("debug_monitor-read_dir_health-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn read_dir(step: &StepServer) {
+    step.handle().read().unwrap().read_clients_from_read_dir();
+}
+
+fn is_healthy(step: &StepServer) -> bool {
+    step.handle().read().unwrap().read_dir_healthy()
+}
+
+/// Next event about the read directory, skipping the rest.
+fn next_read_dir_event(events: &Receiver<ServerEvent>) -> ServerEvent {
+    loop {
+        match events.recv_timeout(STEP_TIMEOUT).unwrap() {
+            event @ (ServerEvent::ReadDirUnavailable { .. } | ServerEvent::ReadDirRecovered { .. }) => return event,
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn outage_is_reported_once_and_recovers() {
+    let dir = temp_dir("outage");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.to_string_lossy().to_string();
+    let step = StepServer::new();
+    step.handle().write().unwrap().set_read_dir(Some(path.clone())).unwrap();
+    let events = step.handle().read().unwrap().events();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    read_dir(&step);
+    assert!(is_healthy(&step));
+
+    fs::remove_dir_all(&dir).unwrap();
+    for _ in 0..3 {
+        read_dir(&step);
+        assert!(!is_healthy(&step));
+    }
+    assert!(!dir.exists());
+    assert_eq!(next_read_dir_event(&events), ServerEvent::ReadDirUnavailable { path: path.clone() });
+
+    fs::create_dir_all(&dir).unwrap();
+    read_dir(&step);
+    assert!(is_healthy(&step));
+    // Reported once, so recovering is the very next event about it
+    assert_eq!(next_read_dir_event(&events), ServerEvent::ReadDirRecovered { path });
+    DirClient::new(&dir, 7).unwrap().send_update(id, "4").unwrap();
+    read_dir(&step);
+    assert_eq!(*counter, 4);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn created_read_dirs_are_recreated_at_a_limited_rate() {
+    let dir = temp_dir("recreate");
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone());
+    let step = StepServer::from_builder(builder);
+    step.handle().write().unwrap().set_read_dir_create(dir.to_string_lossy()).unwrap();
+    let events = step.handle().read().unwrap().events();
+    read_dir(&step);
+
+    fs::remove_dir_all(&dir).unwrap();
+    read_dir(&step);
+    assert!(!is_healthy(&step));
+    assert!(dir.exists());
+    assert!(matches!(next_read_dir_event(&events), ServerEvent::ReadDirUnavailable { .. }));
+    read_dir(&step);
+    assert!(is_healthy(&step));
+    assert!(matches!(next_read_dir_event(&events), ServerEvent::ReadDirRecovered { .. }));
+
+    // A second outage within the interval waits for it to pass before recreating
+    fs::remove_dir_all(&dir).unwrap();
+    read_dir(&step);
+    read_dir(&step);
+    assert!(!dir.exists());
+    clock.advance(READ_DIR_RECREATE_INTERVAL - Duration::from_millis(1));
+    read_dir(&step);
+    assert!(!dir.exists());
+    clock.advance(Duration::from_millis(1));
+    read_dir(&step);
+    assert!(dir.exists());
+    read_dir(&step);
+    assert!(is_healthy(&step));
+    let _ = fs::remove_dir_all(&dir);
+}
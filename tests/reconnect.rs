@@ -0,0 +1,76 @@
+//! Clients reconnecting to a restarted server and reconciling what they knew with what it replays.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use common::serve_until;
+use debug_monitor::client::reconnect::{OfflinePolicy, ReconnectPolicy};
+use debug_monitor::client::{ClientEvent, DebuggableClient};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn server_on(address: SocketAddr) -> StepServer {
+    StepServer::from_builder(DebuggableServerBuilder::bind(address).reuse_addr(true))
+}
+
+/// Serves the client until the condition holds on the events it emitted so far.
+fn converge<Done: Fn(&[ClientEvent]) -> bool>(step: &StepServer, client: &mut DebuggableClient, events: &mut Vec<ClientEvent>, done: Done) -> bool {
+    serve_until(&step.handle().read().unwrap(), || {
+        client.poll().unwrap();
+        events.extend(client.take_events());
+        done(events)
+    })
+}
+
+#[test]
+fn client_reconverges_on_a_restarted_server_remapping_ids_by_name() {
+    let first = server_on("127.0.0.1:0".parse().unwrap());
+    let address = first.addr();
+    let alpha = DebuggableBuilder::new("alpha", 1).scoped(first.scoped_server()).build();
+    let beta = DebuggableBuilder::new("beta", 2).scoped(first.scoped_server()).build();
+    let gone = DebuggableBuilder::new("gone", 3).scoped(first.scoped_server()).build();
+    let old_ids = ["alpha", "beta", "gone"].map(|name| first.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = first.connect().unwrap();
+    client.set_reconnect_policy(Some(ReconnectPolicy { base_delay: Duration::from_millis(5), max_delay: Duration::from_millis(20), jitter: 0.0, max_attempts: None }));
+    client.set_offline_policy(OfflinePolicy::Queue { max_queued: 4 });
+    assert!(first.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| old_ids.iter().all(|id| client.debuggable(*id).is_some()) && client.client_id().is_some()).is_some());
+
+    // The connection drops without the server saying goodbye, as when it crashes
+    assert!(first.handle().read().unwrap().disconnect_client(client.client_id().unwrap()));
+    drop((alpha, beta, gone, first));
+    let mut events = Vec::new();
+    assert!(poll_client_until(&mut client, |client, _| {
+        events.extend(client.take_events());
+        events.contains(&ClientEvent::Disconnected)
+    }).is_some());
+    assert!(!client.is_connected());
+    client.send_update(old_ids[0], "9").unwrap();
+
+    let restarted = server_on(address);
+    assert_eq!(restarted.addr(), address);
+    // Registered in another order, so they get each other's ids
+    let _beta = DebuggableBuilder::new("beta", 2).scoped(restarted.scoped_server()).build();
+    let alpha = DebuggableBuilder::new("alpha", 1).scoped(restarted.scoped_server()).build();
+    let alpha_id = restarted.handle().read().unwrap().debuggable_id_of("alpha").unwrap();
+    let beta_id = restarted.handle().read().unwrap().debuggable_id_of("beta").unwrap();
+    assert_eq!((alpha_id, beta_id), (old_ids[1], old_ids[0]));
+    let remapped_alpha = ClientEvent::Remapped { name: "alpha".to_string(), old_id: old_ids[0], new_id: alpha_id };
+    assert!(converge(&restarted, &mut client, &mut events, |events| events.contains(&remapped_alpha)));
+    assert!(client.is_connected());
+
+    assert!(events.iter().any(|event| matches!(event, ClientEvent::Reconnecting { .. })));
+    assert!(events.contains(&ClientEvent::Connected));
+    assert!(events.contains(&ClientEvent::Removed { id: old_ids[2], name: "gone".to_string() }));
+    assert!(events.contains(&ClientEvent::Remapped { name: "beta".to_string(), old_id: old_ids[1], new_id: beta_id }));
+    assert_eq!(client.debuggable(beta_id).unwrap().name, "beta");
+    assert!(client.debuggable(old_ids[2]).is_none());
+
+    // The update queued while disconnected went to alpha under its new id
+    assert!(restarted.read_until(|server| server.pending_updates_of(alpha_id) == 1));
+    assert_eq!(*alpha, 9);
+}
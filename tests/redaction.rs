@@ -0,0 +1,66 @@
+//! Values redacted right before they leave the process, while the host keeps the real ones.
+#![cfg(feature = "server")]
+
+use std::io::{ErrorKind, Read};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Instant;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+const SECRET: &str = "hunter2-token";
+
+struct Account {
+    user: String,
+    token: String,
+}
+
+fn account_to_json(account: &Account) -> Option<String> {
+    Some(format!("{{\"user\":\"{}\",\"token\":\"{}\"}}", account.user, account.token))
+}
+
+fn mask_token(json: &str) -> String {
+    let token_start = json.find("\"token\":\"").unwrap() + "\"token\":\"".len();
+    let token_end = token_start + json[token_start..].find('"').unwrap();
+    format!("{}***{}", &json[..token_start], &json[token_end..])
+}
+
+/// Reads raw bytes from the socket into received until they contain the expected text.
+fn bytes_until(stream: &mut TcpStream, step: &StepServer, received: &mut Vec<u8>, expected: &str) {
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    let mut buffer = [0_u8; 4096];
+    while !String::from_utf8_lossy(received).contains(expected) {
+        assert!(Instant::now() < give_up_at, "{expected} never arrived, got {}", String::from_utf8_lossy(received));
+        step.housekeeping();
+        match stream.read(&mut buffer) {
+            Ok(0) => panic!("The server closed the connection"),
+            Ok(read) => received.extend_from_slice(&buffer[..read]),
+            Err(error) if error.kind() == ErrorKind::WouldBlock => thread::yield_now(),
+            Err(error) => panic!("Reading from the server failed: {error}"),
+        }
+    }
+}
+
+#[test]
+fn secret_field_never_reaches_the_wire() {
+    let step = StepServer::new();
+    let mut account = DebuggableBuilder::new_unserializable("account", Account { user: "ann".to_string(), token: SECRET.to_string() })
+        .with_serializer(account_to_json, |_| None)
+        .redact(mask_token)
+        .scoped(step.scoped_server())
+        .build();
+    let mut stream = TcpStream::connect(step.addr()).unwrap();
+    stream.set_nonblocking(true).unwrap();
+    assert!(step.accept_until(1));
+    let mut received = Vec::new();
+    bytes_until(&mut stream, &step, &mut received, "\\\"user\\\":\\\"ann\\\",\\\"token\\\":\\\"***\\\"");
+
+    // Rotating the token along with the user sends the new user with the token still masked
+    account.user = "bob".to_string();
+    account.token = format!("{SECRET}-rotated");
+    // Reading it back syncs it
+    assert_eq!(account.token, format!("{SECRET}-rotated"));
+    bytes_until(&mut stream, &step, &mut received, "\\\"user\\\":\\\"bob\\\",\\\"token\\\":\\\"***\\\"");
+    assert!(!String::from_utf8_lossy(&received).contains("hunter2"));
+}
@@ -0,0 +1,96 @@
+//! Debuggables created from callbacks the server runs while the thread holds its locks.
+#![cfg(feature = "server")]
+
+use std::cell::RefCell;
+use std::net::TcpListener;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::default_server::{default_server, set_default_server_initializer};
+use debug_monitor::serializable::ClientUnitMessage;
+use debug_monitor::server::callbacks::is_running_callback;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::StepServer;
+
+thread_local! {
+    static CREATED: RefCell<Vec<Debuggable<i32>>> = RefCell::new(Vec::new());
+}
+
+/// Runs the scenario on its own thread, failing instead of hanging if it deadlocks.
+fn within_timeout<Scenario: FnOnce() + Send + 'static>(scenario: Scenario) {
+    let (finished, finishing) = mpsc::channel();
+    let running = thread::spawn(move || {
+        scenario();
+        finished.send(()).unwrap();
+    });
+    match finishing.recv_timeout(Duration::from_secs(10)) {
+        Err(RecvTimeoutError::Timeout) => panic!("The scenario deadlocked"),
+        _ => running.join().unwrap(),
+    }
+}
+
+fn created_count() -> usize {
+    CREATED.with(|created| created.borrow().len())
+}
+
+#[test]
+fn debuggables_created_in_after_build_register_once_the_default_server_exists() {
+    within_timeout(|| {
+        set_default_server_initializer(|| DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+            .after_build(|_| {
+                assert!(is_running_callback());
+                let created = Debuggable::new("from_after_build", 1);
+                assert_eq!(*created, 1);
+                CREATED.with(|all_created| all_created.borrow_mut().push(created));
+            }));
+        let server = default_server();
+        assert!(!is_running_callback());
+        assert_eq!(created_count(), 1);
+        assert!(server.read().unwrap().debuggable_id_of("from_after_build").is_none());
+
+        CREATED.with(|created| assert_eq!(*created.borrow()[0], 1));
+        assert_eq!(server.read().unwrap().value_of("from_after_build").as_deref(), Some("1"));
+    });
+}
+
+#[test]
+fn debuggables_created_in_custom_handlers_register_after_the_read() {
+    within_timeout(|| {
+        let step = StepServer::new();
+        let handle = step.handle();
+        step.handle().write().unwrap().on_custom("spawn", Box::new(move |_, payload| {
+            let created = DebuggableBuilder::new(payload, 2).server(Some(handle.clone())).build();
+            assert_eq!(*created, 2);
+            CREATED.with(|all_created| all_created.borrow_mut().push(created));
+        }));
+        let mut client = step.connect().unwrap();
+        assert!(step.accept_until(1));
+        client.send(&ClientUnitMessage::Custom { topic: "spawn".to_string(), payload: "from_custom".to_string() }).unwrap();
+        assert!(step.read_until(|_| created_count() == 1));
+        assert!(step.handle().read().unwrap().debuggable_id_of("from_custom").is_none());
+
+        CREATED.with(|created| assert_eq!(*created.borrow()[0], 2));
+        assert_eq!(step.handle().read().unwrap().value_of("from_custom").as_deref(), Some("2"));
+    });
+}
+
+#[test]
+fn debuggables_created_in_disconnect_handlers_register_after_the_read() {
+    within_timeout(|| {
+        let step = StepServer::new();
+        let handle = step.handle();
+        step.handle().write().unwrap().set_on_client_disconnect(Some(Box::new(move |_, _| {
+            let created = DebuggableBuilder::new("from_disconnect", 3).server(Some(handle.clone())).build();
+            CREATED.with(|all_created| all_created.borrow_mut().push(created));
+        })));
+        let client = step.connect().unwrap();
+        assert!(step.accept_until(1));
+        drop(client);
+        assert!(step.read_until(|_| created_count() == 1));
+
+        CREATED.with(|created| assert_eq!(*created.borrow()[0], 3));
+        assert_eq!(step.handle().read().unwrap().value_of("from_disconnect").as_deref(), Some("3"));
+    });
+}
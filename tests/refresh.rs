@@ -0,0 +1,76 @@
+//! Values re-broadcast to clients that may have missed them, on demand or once the interval passed.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+fn refreshing_server() -> (StepServer, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .refresh_interval(Some(REFRESH_INTERVAL))
+        .clock(clock.clone());
+    (StepServer::from_builder(builder), clock)
+}
+
+fn connect(step: &StepServer, ids: &[usize]) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| ids.iter().all(|id| client.debuggable(*id).is_some_and(|debuggable| !debuggable.value_in_json.is_empty()))).is_some());
+    client
+}
+
+/// Polls until every debuggable was notified, returning how many Notifies each got meanwhile.
+fn notifies_until_all_refreshed(client: &mut DebuggableClient, ids: &[usize]) -> Vec<usize> {
+    let notified = |received: &[ServerMessage], id: usize| received.iter()
+        .filter(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id))
+        .count();
+    let received = poll_client_until(client, |_, received| ids.iter().all(|id| notified(received, *id) > 0)).unwrap();
+    ids.iter().map(|id| notified(&received, *id)).collect()
+}
+
+#[test]
+fn refresh_now_renotifies_every_value_without_counting_as_a_change() {
+    let step = StepServer::new();
+    let _speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let _label = DebuggableBuilder::new("label", "idle".to_string()).scoped(step.scoped_server()).build();
+    let ids = ["speed", "label"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = connect(&step, &ids);
+    let revisions = ids.map(|id| step.handle().read().unwrap().revision_of(id));
+    let events = step.handle().read().unwrap().events();
+
+    // Works without an interval being configured
+    step.handle().read().unwrap().refresh_now();
+    assert_eq!(notifies_until_all_refreshed(&mut client, &ids), [1, 1]);
+    assert_eq!(ids.map(|id| step.handle().read().unwrap().revision_of(id)), revisions);
+    assert!(events.recv_timeout(Duration::from_millis(50)).is_err());
+    assert_eq!(client.debuggable(ids[1]).unwrap().value_in_json, "\"idle\"");
+}
+
+#[test]
+fn refresh_if_due_only_renotifies_once_the_interval_passed() {
+    let (step, clock) = refreshing_server();
+    let _speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = connect(&step, &[id]);
+
+    clock.advance(REFRESH_INTERVAL / 2);
+    assert!(!step.handle().read().unwrap().refresh_if_due());
+    clock.advance(REFRESH_INTERVAL / 2);
+    assert!(step.handle().read().unwrap().refresh_if_due());
+    assert_eq!(notifies_until_all_refreshed(&mut client, &[id]), [1]);
+
+    // The interval starts over from the refresh
+    assert!(!step.handle().read().unwrap().refresh_if_due());
+    clock.advance(REFRESH_INTERVAL);
+    assert!(step.handle().read().unwrap().refresh_if_due());
+    assert_eq!(notifies_until_all_refreshed(&mut client, &[id]), [1]);
+}
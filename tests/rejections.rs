@@ -0,0 +1,102 @@
+//! Escalation of the answers to a client whose updates of a debuggable keep being rejected.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::client::{DebuggableClient, UpdateOutcome};
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const NOT_A_NUMBER: &str = "\"not a number\"";
+
+fn escalating_server() -> StepServer {
+    StepServer::from_builder(DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).rejection_escalation(Some(2), Some(3)))
+}
+
+fn connect(step: &StepServer, id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+    client
+}
+
+/// Sends the update, lets the counter sync and returns the errors the client got for it.
+fn errors_after_update(step: &StepServer, client: &mut DebuggableClient, counter: &Debuggable<i32>, id: usize, value_json: &str) -> Vec<String> {
+    let request_id = client.send_tracked_update(id, value_json).unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    let _ = **counter;
+    let received = poll_client_until(client, |client, _| !client.is_update_pending(request_id)).unwrap();
+    received.into_iter()
+        .filter_map(|message| match message {
+            ServerMessage::Error { message, .. } => Some(message),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn persistently_wrong_client_is_explained_to_then_ignored_until_it_renotifies() {
+    let step = escalating_server();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = connect(&step, id);
+
+    let errors = errors_after_update(&step, &mut client, &counter, id, NOT_A_NUMBER);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Rejected update of counter"));
+
+    let errors = errors_after_update(&step, &mut client, &counter, id, NOT_A_NUMBER);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[1].contains("expects a value of type i32 such as its current value 1"), "{}", errors[1]);
+
+    let errors = errors_after_update(&step, &mut client, &counter, id, NOT_A_NUMBER);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[1].contains("ignored until it sends Renotify or Hello"), "{}", errors[1]);
+
+    let request_id = client.send_tracked_update(id, "5").unwrap();
+    assert!(poll_client_until(&mut client, |client, _| !client.is_update_pending(request_id)).is_some());
+    assert_eq!(client.take_update_outcomes().last(), Some(&(request_id, UpdateOutcome::Rejected)));
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 0);
+    assert_eq!(*counter, 1);
+
+    client.send_renotify().unwrap();
+    client.send_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*counter, 5);
+}
+
+#[test]
+fn accepted_update_resets_the_escalation() {
+    let step = escalating_server();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = connect(&step, id);
+
+    assert_eq!(errors_after_update(&step, &mut client, &counter, id, NOT_A_NUMBER).len(), 1);
+    assert!(errors_after_update(&step, &mut client, &counter, id, "2").is_empty());
+    assert_eq!(*counter, 2);
+    assert_eq!(errors_after_update(&step, &mut client, &counter, id, NOT_A_NUMBER).len(), 1);
+}
+
+#[test]
+fn ignored_client_stays_ignored_once_the_debuggable_moves_on_compaction() {
+    let step = escalating_server();
+    let filler = DebuggableBuilder::new("filler", 0).scoped(step.scoped_server()).build();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = connect(&step, id);
+    for _ in 0..3 {
+        errors_after_update(&step, &mut client, &counter, id, NOT_A_NUMBER);
+    }
+
+    drop(filler);
+    assert_eq!(step.handle().read().unwrap().compact(), 1);
+    let moved_id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    assert_ne!(moved_id, id);
+    let request_id = client.send_tracked_update(moved_id, "5").unwrap();
+    assert!(poll_client_until(&mut client, |client, _| !client.is_update_pending(request_id)).is_some());
+    assert_eq!(client.take_update_outcomes().last(), Some(&(request_id, UpdateOutcome::Rejected)));
+    assert_eq!(*counter, 1);
+}
@@ -0,0 +1,102 @@
+//! Debuggables dropped while updates from clients referencing them are still in flight.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Connects a client and waits for the server to read its Hello, by waiting for an update it sends
+/// to the greeting debuggable right after.
+fn greeted_client(step: &StepServer, greeting_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(greeting_id, "0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(greeting_id) == 1));
+    client
+}
+
+/// Messages the client receives until the greeting reaches the given value, which the host sets
+/// after anything else it sends.
+fn received_until_greeting_is(client: &mut DebuggableClient, greeting_id: usize, greeting_json: &str) -> Vec<ServerMessage> {
+    poll_client_until(client, |client, _| client.debuggable(greeting_id).is_some_and(|greeting| greeting.value_in_json == greeting_json)).unwrap()
+}
+
+fn is_notify_of(message: &ServerMessage, debuggable_id: usize) -> bool {
+    match message {
+        ServerMessage::Notify { id, .. } | ServerMessage::NotifyUnset { id } | ServerMessage::NotifySummary { id, .. } => *id == debuggable_id,
+        ServerMessage::NotifyMany { notifies } => notifies.iter().any(|notify| notify.id == debuggable_id),
+        _ => false,
+    }
+}
+
+#[test]
+fn updates_read_after_the_drop_are_dropped_without_notifying() {
+    let step = StepServer::new();
+    let mut greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let doomed = DebuggableBuilder::new("doomed", 0).scoped(step.scoped_server()).build();
+    let (greeting_id, doomed_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("doomed").unwrap())
+    };
+    let mut client = greeted_client(&step, greeting_id);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(doomed_id).is_some_and(|doomed| doomed.value_in_json == "0")).is_some());
+
+    // Sent before the drop, read by the server after it
+    client.send_update(doomed_id, "7").unwrap();
+    drop(doomed);
+    assert!(step.read_until(|server| server.memory_report().clients.unknown_id_references == 1));
+    assert!(step.handle().read().unwrap().memory_report().pending.is_empty());
+
+    *greeting = 1;
+    assert_eq!(*greeting, 1);
+    let received = received_until_greeting_is(&mut client, greeting_id, "1");
+    let removed_at = received.iter().position(|message| matches!(message, ServerMessage::Remove { id, .. } if *id == doomed_id)).unwrap();
+    assert!(!received[removed_at..].iter().any(|message| is_notify_of(message, doomed_id)));
+    assert!(client.debuggable(doomed_id).is_none());
+}
+
+#[test]
+fn updates_queued_before_the_drop_go_with_it() {
+    let step = StepServer::new();
+    let mut greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let doomed = DebuggableBuilder::new("doomed", 0).scoped(step.scoped_server()).build();
+    let (greeting_id, doomed_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("doomed").unwrap())
+    };
+    let mut client = greeted_client(&step, greeting_id);
+
+    client.send_update(doomed_id, "7").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(doomed_id) == 1));
+    drop(doomed);
+    assert!(step.handle().read().unwrap().debuggable_id_of("doomed").is_none());
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(doomed_id), 0);
+
+    *greeting = 3;
+    assert_eq!(*greeting, 3);
+    let received = received_until_greeting_is(&mut client, greeting_id, "3");
+    assert!(!received.iter().any(|message| is_notify_of(message, doomed_id)));
+    assert!(step.handle().read().unwrap().memory_report().pending.is_empty());
+}
+
+#[test]
+fn kicked_debuggables_sync_without_panicking() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let mut kicked = DebuggableBuilder::new("kicked", 0).scoped(step.scoped_server()).keep().build();
+    let greeting_id = step.handle().read().unwrap().debuggable_id_of("greeting").unwrap();
+    let kicked_id = step.handle().read().unwrap().debuggable_id_of("kicked").unwrap();
+    let mut client = greeted_client(&step, greeting_id);
+
+    client.send_update(kicked_id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(kicked_id) == 1));
+    assert!(step.handle().read().unwrap().kick_debuggable(kicked_id));
+    *kicked = 6;
+    assert_eq!(*kicked, 6);
+    let new_id = step.handle().read().unwrap().debuggable_id_of("kicked").unwrap();
+    assert_ne!(new_id, kicked_id);
+    assert_eq!(step.handle().read().unwrap().value_of("kicked").as_deref(), Some("6"));
+}
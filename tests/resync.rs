@@ -0,0 +1,54 @@
+//! Clients whose view of the debuggables diverged being reset with RemoveAll and a full replay.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const BOGUS_ID: usize = 999;
+
+/// Names the RemoveAll, Added and Notify messages received, with the id they are about.
+fn sequence_of(received: &[ServerMessage]) -> Vec<(&'static str, Option<usize>)> {
+    received.iter()
+        .filter_map(|message| match message {
+            ServerMessage::RemoveAll => Some(("RemoveAll", None)),
+            ServerMessage::Added { id, .. } => Some(("Added", Some(*id))),
+            ServerMessage::Notify { id, .. } => Some(("Notify", Some(*id))),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn spamming_bogus_ids_resyncs_every_client_once() {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).resync_after_unknown_ids(3);
+    let step = StepServer::from_builder(builder);
+    let _speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let _label = DebuggableBuilder::new("label", "ready".to_string()).scoped(step.scoped_server()).build();
+    let ids = ["speed", "label"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| ids.iter().all(|id| client.debuggable(*id).is_some_and(|debuggable| !debuggable.value_in_json.is_empty()))).is_some());
+
+    // Five references, one short of resyncing twice
+    for _ in 0..5 {
+        client.send_update(BOGUS_ID, "0").unwrap();
+    }
+    client.send_update(ids[0], "2").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(ids[0]) == 1));
+
+    let mut received = poll_client_until(&mut client, |_, received| {
+        sequence_of(received).iter().filter(|(kind, _)| *kind == "Notify").count() == ids.len()
+    }).unwrap();
+    received.extend(client.poll().unwrap());
+    let expected = [("RemoveAll", None), ("Added", Some(ids[0])), ("Notify", Some(ids[0])), ("Added", Some(ids[1])), ("Notify", Some(ids[1]))];
+    assert_eq!(sequence_of(&received), expected);
+    assert!(ids.iter().all(|id| client.debuggable(*id).is_some()));
+
+    step.handle().read().unwrap().resync_clients();
+    let received = poll_client_until(&mut client, |_, received| sequence_of(received).len() == expected.len()).unwrap();
+    assert_eq!(sequence_of(&received), expected);
+}
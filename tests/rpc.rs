@@ -0,0 +1,75 @@
+//! Calls from clients to RPC endpoints served by the host.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::debuggable_rpc::DebuggableRpc;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+fn neighbours_of(entity: u32) -> Vec<u32> {
+    if entity == 0 { panic!("Entity 0 has no graph"); }
+    vec![entity - 1, entity + 1]
+}
+
+fn connect(step: &StepServer, id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+    client
+}
+
+fn call(step: &StepServer, client: &mut DebuggableClient, id: usize, request_json: &str) -> u64 {
+    let call_id = client.call_rpc(id, request_json).unwrap();
+    assert!(step.read_until(|server| server.pending_rpc_calls_of(id) == 1));
+    call_id
+}
+
+fn result_of(client: &mut DebuggableClient, call_id: u64) -> Result<String, String> {
+    assert!(poll_client_until(client, |client, _| client.has_rpc_result(call_id)).is_some());
+    client.take_rpc_result(call_id).unwrap()
+}
+
+#[test]
+fn served_call_answers_the_caller() {
+    let step = StepServer::new();
+    let mut rpc = DebuggableRpc::scoped(step.scoped_server(), "graph_around", neighbours_of);
+    let mut client = connect(&step, rpc.id());
+    let call_id = call(&step, &mut client, rpc.id(), "42");
+    assert_eq!(rpc.serve(), 1);
+    assert_eq!(result_of(&mut client, call_id), Ok("[41,43]".to_string()));
+}
+
+#[test]
+fn malformed_requests_and_panics_are_answered_with_errors() {
+    let step = StepServer::new();
+    let mut rpc = DebuggableRpc::scoped(step.scoped_server(), "graph_around", neighbours_of);
+    let mut client = connect(&step, rpc.id());
+
+    let call_id = call(&step, &mut client, rpc.id(), "\"forty two\"");
+    rpc.serve();
+    assert!(result_of(&mut client, call_id).unwrap_err().starts_with("Malformed request"));
+
+    let call_id = call(&step, &mut client, rpc.id(), "0");
+    rpc.serve();
+    assert!(result_of(&mut client, call_id).unwrap_err().contains("Entity 0 has no graph"));
+}
+
+#[test]
+fn unserved_call_times_out() {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .clock(clock.clone())
+        .rpc_timeout(Duration::from_secs(1));
+    let step = StepServer::from_builder(builder);
+    let mut rpc = DebuggableRpc::scoped(step.scoped_server(), "graph_around", neighbours_of);
+    let mut client = connect(&step, rpc.id());
+    let call_id = call(&step, &mut client, rpc.id(), "42");
+    clock.advance(Duration::from_secs(2));
+    step.housekeeping();
+    assert!(result_of(&mut client, call_id).unwrap_err().contains("timed out"));
+    assert_eq!(rpc.serve(), 0);
+}
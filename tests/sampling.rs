@@ -0,0 +1,73 @@
+//! Debuggables syncing only on some of their accesses.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{ManualClock, StepServer};
+
+#[test]
+fn remote_updates_are_applied_within_n_accesses() {
+    let server = ScopedServer::new();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(&server).sample_every(4).build();
+    let handle = server.handle();
+    let id = handle.read().unwrap().debuggable_id_of("counter").unwrap();
+    assert_eq!(*counter, 1);
+    assert!(handle.read().unwrap().queue_update(id, 0, "9".to_string()));
+    let seen = (0..4).map(|_| *counter).collect::<Vec<_>>();
+    assert_eq!(seen, vec![1, 1, 1, 9]);
+}
+
+#[test]
+fn local_changes_are_sent_on_sampled_accesses() {
+    let server = ScopedServer::new();
+    let mut counter = DebuggableBuilder::new("counter", 1).scoped(&server).sample_every(3).build();
+    let handle = server.handle();
+    *counter = 2;
+    *counter += 1;
+    *counter += 1;
+    assert_eq!(handle.read().unwrap().value_of("counter").as_deref(), Some("1"));
+    // The fourth access is sampled again, sending what the previous ones wrote
+    assert_eq!(*counter, 4);
+    assert_eq!(handle.read().unwrap().value_of("counter").as_deref(), Some("4"));
+}
+
+#[test]
+fn set_and_sync_bypass_sampling() {
+    let server = ScopedServer::new();
+    let mut counter = DebuggableBuilder::new("counter", 1).scoped(&server).sample_every(1000).build();
+    let handle = server.handle();
+    let id = handle.read().unwrap().debuggable_id_of("counter").unwrap();
+    assert_eq!(*counter, 1);
+    assert!(handle.read().unwrap().queue_update(id, 0, "7".to_string()));
+    counter.sync();
+    assert_eq!(*counter, 7);
+
+    counter.set(8);
+    counter.sync();
+    assert_eq!(handle.read().unwrap().value_of("counter").as_deref(), Some("8"));
+}
+
+#[test]
+fn intervals_follow_the_server_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone());
+    let step = StepServer::from_builder(builder);
+    let interval = Duration::from_millis(100);
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).sample_interval(interval).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    // Learning the clock on the first one, then sampling from the second
+    assert_eq!(*counter, 1);
+    assert_eq!(*counter, 1);
+
+    assert!(step.handle().read().unwrap().queue_update(id, 0, "9".to_string()));
+    assert_eq!(*counter, 1);
+    clock.advance(interval - Duration::from_millis(1));
+    assert_eq!(*counter, 1);
+    clock.advance(Duration::from_millis(1));
+    assert_eq!(*counter, 9);
+}
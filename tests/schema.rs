@@ -0,0 +1,136 @@
+//! The protocol schema against the messages the crate actually sends and handles.
+#![cfg(all(feature = "schema", feature = "use_serde"))]
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use debug_monitor::schema::protocol_schema;
+use debug_monitor::serializable::framing::{Framing, FramingInfo};
+use debug_monitor::serializable::text_patch::TextHunk;
+use debug_monitor::serializable::{AddedOrigin, ClientUnitMessage, CompositeKind, GroupedUpdate, JSONDeSerializable, NotifyEntry, RemoveReason, ServerMessage, PROTOCOL_VERSION};
+
+/// One of every message clients send, all of which process_message_of handles.
+fn client_messages() -> Vec<ClientUnitMessage> {
+    let messages = vec![
+        ClientUnitMessage::UpdateValue { id: 0, new_value: "1".to_string(), request_id: Some(1), panel: Some("panel".to_string()) },
+        ClientUnitMessage::UpdateIndex { id: 0, index: 1, element_json: "1".to_string() },
+        ClientUnitMessage::Renotify,
+        ClientUnitMessage::Hello { protocol_version: PROTOCOL_VERSION, supports_deflate: true, panel: Some("panel".to_string()), supports_text_patches: true, session_key: Some("key".to_string()) },
+        ClientUnitMessage::Custom { topic: "topic".to_string(), payload: "payload".to_string() },
+        ClientUnitMessage::UpdateValueCas { id: 0, expected_revision: 1, new_value: "1".to_string() },
+        ClientUnitMessage::UpdateGroup { updates: vec![GroupedUpdate { id: 0, new_value: "1".to_string() }] },
+        ClientUnitMessage::AnimateValue { id: 0, target_json: "1".to_string(), duration_ms: 10 },
+        ClientUnitMessage::GroupSnapshotRequest { prefix: "a/".to_string() },
+        ClientUnitMessage::GroupReset { prefix: "a/".to_string() },
+        ClientUnitMessage::RpcCall { id: 0, call_id: 1, request_json: "1".to_string() },
+        ClientUnitMessage::RequestValue { id: 0, full: true },
+        ClientUnitMessage::UpdateTextPatch { id: 0, base_revision: 1, hunks: vec![TextHunk { start: 0, removed: 1, inserted: vec!["line".to_string()] }] },
+        ClientUnitMessage::Subscribe { names: Some(vec!["a".to_string()]) },
+        ClientUnitMessage::StoreBlob { key: "layout".to_string(), data: "{}".to_string() },
+        ClientUnitMessage::BeginEdit { txn_id: 1 },
+        ClientUnitMessage::StageValue { txn_id: 1, id: 0, new_value: "1".to_string() },
+        ClientUnitMessage::CommitEdit { txn_id: 1 },
+        ClientUnitMessage::AbortEdit { txn_id: 1 },
+    ];
+    // Fails to compile once a variant is added, so it gets a sample above
+    messages.iter().for_each(|message| match message {
+        ClientUnitMessage::UpdateValue { .. } | ClientUnitMessage::UpdateIndex { .. } | ClientUnitMessage::Renotify | ClientUnitMessage::Hello { .. }
+        | ClientUnitMessage::Custom { .. } | ClientUnitMessage::UpdateValueCas { .. } | ClientUnitMessage::UpdateGroup { .. }
+        | ClientUnitMessage::AnimateValue { .. } | ClientUnitMessage::GroupSnapshotRequest { .. } | ClientUnitMessage::GroupReset { .. }
+        | ClientUnitMessage::RpcCall { .. } | ClientUnitMessage::RequestValue { .. } | ClientUnitMessage::UpdateTextPatch { .. }
+        | ClientUnitMessage::Subscribe { .. } | ClientUnitMessage::StoreBlob { .. } | ClientUnitMessage::BeginEdit { .. }
+        | ClientUnitMessage::StageValue { .. } | ClientUnitMessage::CommitEdit { .. } | ClientUnitMessage::AbortEdit { .. } => {}
+    });
+    messages
+}
+
+/// Messages init_client greets clients with, along with the ones announcing and removing values.
+fn greeting_messages() -> Vec<ServerMessage> {
+    let framing = FramingInfo { endmark: "<end>".to_string(), escape: "<escaped>".to_string(), mode: Framing::Endmark };
+    vec![
+        ServerMessage::ServerInfo { crate_version: "0.1.0".to_string(), protocol_version: PROTOCOL_VERSION, capabilities: vec!["a".to_string()], debuggable_count: 1, framing: Some(framing) },
+        ServerMessage::GiveClientId { client_id: 0 },
+        ServerMessage::Welcome { client_id: 0, protocol_version: PROTOCOL_VERSION },
+        ServerMessage::Added { id: 0, name: "a".to_string(), origin: AddedOrigin::Replay, uid: Some(1) },
+        ServerMessage::Metadata { id: 0, nullable: true, order: 1, on_demand: true },
+        ServerMessage::Notify { id: 0, name: "a".to_string(), value_in_json: "1".to_string(), revision: 1, changed_at_ms: Some(1), author: Some(1), uid: Some(1), overridden: true },
+        ServerMessage::NotifyMany { notifies: vec![NotifyEntry { id: 0, name: "a".to_string(), value_in_json: "1".to_string(), revision: 1, overridden: true }] },
+        ServerMessage::Composite { name: "a".to_string(), kind: CompositeKind::Vec2, members: vec![0, 1] },
+        ServerMessage::Blob { key: "layout".to_string(), data: "{}".to_string() },
+        ServerMessage::Remove { id: 0, reason: RemoveReason::Kicked, uid: Some(1) },
+        ServerMessage::RemoveAll,
+    ]
+}
+
+fn schema() -> Value {
+    serde_json::from_str(&protocol_schema()).unwrap()
+}
+
+fn type_named<'schema>(schema: &'schema Value, name: &str) -> &'schema Value {
+    schema["types"].as_array().unwrap().iter().find(|listed| listed["name"] == name).unwrap_or_else(|| panic!("{name} isn't listed"))
+}
+
+fn field_names(fields: &Value) -> BTreeSet<String> {
+    fields.as_array().unwrap().iter().map(|field| field["name"].as_str().unwrap().to_string()).collect()
+}
+
+/// Asserts the variant the JSON encodes is listed with exactly the fields it was sent with.
+fn assert_listed(schema: &Value, type_name: &str, message_json: &str) -> String {
+    let message: Value = serde_json::from_str(message_json).unwrap();
+    let (variant, sent_fields) = match &message {
+        Value::String(variant) => (variant.clone(), BTreeSet::new()),
+        Value::Object(tagged) => {
+            let (variant, fields) = tagged.iter().next().unwrap();
+            (variant.clone(), fields.as_object().unwrap().keys().cloned().collect())
+        }
+        _ => panic!("{message_json} isn't an enum"),
+    };
+    let listed = type_named(schema, type_name)["variants"].as_array().unwrap().iter()
+        .find(|listed| listed["name"] == variant.as_str())
+        .unwrap_or_else(|| panic!("{type_name}::{variant} isn't listed"));
+    assert_eq!(field_names(&listed["fields"]), sent_fields, "fields of {type_name}::{variant}");
+    variant
+}
+
+#[test]
+fn schema_holds_the_protocol_version() {
+    let schema = schema();
+    assert_eq!(schema["protocol_version"], PROTOCOL_VERSION);
+    assert_eq!(schema["server_messages"], "ServerMessage");
+    assert_eq!(schema["client_messages"], "ClientUnitMessage");
+}
+
+#[test]
+fn every_handled_client_message_is_listed() {
+    let schema = schema();
+    let sampled = client_messages().iter()
+        .map(|message| assert_listed(&schema, "ClientUnitMessage", &message.to_json().unwrap()))
+        .collect::<BTreeSet<_>>();
+    let listed = type_named(&schema, "ClientUnitMessage")["variants"].as_array().unwrap().iter()
+        .map(|variant| variant["name"].as_str().unwrap().to_string())
+        .collect::<BTreeSet<_>>();
+    assert_eq!(sampled, listed);
+}
+
+#[test]
+fn greeting_messages_are_listed() {
+    let schema = schema();
+    for message in greeting_messages() {
+        assert_listed(&schema, "ServerMessage", &message.to_json().unwrap());
+    }
+}
+
+#[test]
+fn fields_have_wire_types() {
+    let schema = schema();
+    let notify = type_named(&schema, "ServerMessage")["variants"].as_array().unwrap().iter().find(|variant| variant["name"] == "Notify").unwrap();
+    let type_of = |field: &str| notify["fields"].as_array().unwrap().iter().find(|listed| listed["name"] == field).unwrap()["type"].clone();
+    assert_eq!(type_of("id"), "integer");
+    assert_eq!(type_of("name"), "string");
+    assert_eq!(type_of("uid"), "optional<integer>");
+    let server_info = type_named(&schema, "ServerMessage")["variants"].as_array().unwrap().iter().find(|variant| variant["name"] == "ServerInfo").unwrap();
+    assert!(server_info["fields"].as_array().unwrap().iter().any(|field| field["name"] == "framing" && field["type"] == "optional<FramingInfo>"));
+    assert_eq!(type_named(&schema, "Framing")["kind"], "enum");
+    assert_eq!(field_names(&type_named(&schema, "TextHunk")["fields"]), ["inserted", "removed", "start"].map(str::to_string).into());
+}
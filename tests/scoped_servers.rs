@@ -0,0 +1,53 @@
+//! ScopedServers living side by side in one test binary and freeing their port when dropped.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::thread;
+use std::time::Instant;
+
+use debug_monitor::client::{DebuggableClient, MessageFraming};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::testing::STEP_TIMEOUT;
+
+/// Serves a debuggable under the same name as the other thread does, and checks its client only
+/// ever sees this server's value.
+fn serve_isolated(value: i32) {
+    let server = ScopedServer::new();
+    let shared_name = DebuggableBuilder::new("shared_name", value).scoped(&server).build();
+    let id = server.handle().read().unwrap().debuggable_id_of("shared_name").unwrap();
+    let mut client = DebuggableClient::connect(server.addr(), MessageFraming::of_server(&server.handle().read().unwrap())).unwrap();
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    // Syncing the debuggable polls the server, which accepts the client and notifies it
+    while client.debuggable(id).map_or(true, |debuggable| debuggable.value_in_json.is_empty()) {
+        assert!(Instant::now() < give_up_at, "The client of the server holding {value} never got it");
+        assert_eq!(*shared_name, value);
+        client.poll().unwrap();
+        thread::yield_now();
+    }
+    assert_eq!(client.debuggable(id).unwrap().value_in_json, value.to_string());
+}
+
+#[test]
+fn two_scoped_servers_run_concurrently_without_conflicts() {
+    let first = thread::spawn(|| serve_isolated(1));
+    let second = thread::spawn(|| serve_isolated(2));
+    first.join().unwrap();
+    second.join().unwrap();
+}
+
+#[test]
+fn dropping_a_scoped_server_frees_its_port_while_its_debuggables_live_on() {
+    let server = ScopedServer::new();
+    let addr = server.addr();
+    let handle = server.handle();
+    let mut outliving = DebuggableBuilder::new("outliving", 1).scoped(&server).build();
+    assert!(outliving.is_server_alive());
+
+    drop(server);
+    assert!(handle.read().unwrap().is_shut_down());
+    assert!(TcpListener::bind(addr).is_ok(), "{addr} is still bound");
+    assert!(!outliving.is_server_alive());
+    *outliving = 2;
+    assert_eq!(*outliving, 2);
+}
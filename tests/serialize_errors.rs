@@ -0,0 +1,61 @@
+//! Debuggables whose value can't always be serialized.
+#![cfg(feature = "server")]
+
+use std::sync::{Arc, Mutex};
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::error::DebugMonitorError;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+type Reported = Arc<Mutex<Vec<DebugMonitorError>>>;
+
+/// Serializes only non-negative values.
+fn balance_on(step: &StepServer, initial_value: i32, reported: &Reported) -> Debuggable<i32> {
+    let reported = reported.clone();
+    DebuggableBuilder::new("balance", initial_value)
+        .with_serializer(|balance: &i32| (*balance >= 0).then(|| balance.to_string()), |json| json.parse().ok())
+        .on_serialize_error(move |error| reported.lock().unwrap().push(error.clone()))
+        .scoped(step.scoped_server())
+        .build()
+}
+
+#[test]
+fn unserializable_initial_value_registers_unset() {
+    let step = StepServer::new();
+    let reported = Reported::default();
+    let mut balance = balance_on(&step, -5, &reported);
+    assert_eq!(*reported.lock().unwrap(), vec![DebugMonitorError::Serialize { name: "balance".to_string(), type_name: "i32" }]);
+    assert_eq!(step.handle().read().unwrap().value_of("balance"), None);
+    let id = step.handle().read().unwrap().debuggable_id_of("balance").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some()).is_some());
+    assert!(client.debuggable(id).unwrap().value_in_json.is_empty());
+
+    *balance = 10;
+    assert_eq!(*balance, 10);
+    let notified = |_: &_, received: &[ServerMessage]| received.iter().any(|message| matches!(message, ServerMessage::Notify { value_in_json, .. } if value_in_json == "10"));
+    assert!(poll_client_until(&mut client, notified).is_some());
+    assert_eq!(reported.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn value_becoming_unserializable_is_unset_and_reported_once() {
+    let step = StepServer::new();
+    let reported = Reported::default();
+    let mut balance = balance_on(&step, 3, &reported);
+    let id = step.handle().read().unwrap().debuggable_id_of("balance").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|balance| balance.value_in_json == "3")).is_some());
+
+    *balance = -1;
+    assert_eq!(*balance, -1);
+    *balance = -2;
+    assert_eq!(*balance, -2);
+    let unset = |_: &_, received: &[ServerMessage]| received.iter().any(|message| matches!(message, ServerMessage::NotifyUnset { id: unset_id } if *unset_id == id));
+    assert!(poll_client_until(&mut client, unset).is_some());
+    assert_eq!(step.handle().read().unwrap().value_of("balance"), None);
+    assert_eq!(reported.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,57 @@
+//! Serializers given to a single debuggable, used instead of the type's own in both directions.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn celsius_to_json(celsius: &i32) -> Option<String> {
+    Some(format!("\"{celsius}C\""))
+}
+
+fn celsius_from_json(json: &str) -> Option<i32> {
+    json.strip_prefix('"')?.strip_suffix("C\"")?.parse().ok()
+}
+
+fn temperature_on(step: &StepServer, initial_value: i32) -> Debuggable<i32> {
+    DebuggableBuilder::new("temperature", initial_value)
+        .with_serializer(celsius_to_json, celsius_from_json)
+        .scoped(step.scoped_server())
+        .keep()
+        .build()
+}
+
+#[test]
+fn override_serializes_outgoing_values_and_parses_incoming_ones() {
+    let step = StepServer::new();
+    let mut temperature = temperature_on(&step, 21);
+    let id = step.handle().read().unwrap().debuggable_id_of("temperature").unwrap();
+    assert_eq!(step.handle().read().unwrap().value_of("temperature").as_deref(), Some("\"21C\""));
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|temperature| temperature.value_in_json == "\"21C\"")).is_some());
+
+    *temperature = 25;
+    assert_eq!(*temperature, 25);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|temperature| temperature.value_in_json == "\"25C\"")).is_some());
+
+    client.send_update(id, "\"30C\"").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*temperature, 30);
+    // What the type itself would parse isn't accepted
+    client.send_update(id, "40").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*temperature, 30);
+}
+
+#[test]
+fn kept_value_is_restored_through_the_override() {
+    let step = StepServer::new();
+    let mut temperature = temperature_on(&step, 21);
+    *temperature = -4;
+    assert_eq!(*temperature, -4);
+    drop(temperature);
+    assert_eq!(step.handle().read().unwrap().value_of("temperature").as_deref(), Some("\"-4C\""));
+
+    let restored = temperature_on(&step, 21);
+    assert_eq!(*restored, -4);
+}
@@ -0,0 +1,73 @@
+//! The ServerInfo message clients get first, listing what the server was configured to support.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::net::TcpListener;
+
+use common::{client_id_in, WireClient};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{capabilities, ServerMessage, PROTOCOL_VERSION};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const ALWAYS_SUPPORTED: [&str; 13] = [
+    capabilities::NOTIFY_MANY, capabilities::CAS, capabilities::INDEX_UPDATES, capabilities::ADDED, capabilities::UPDATE_ACKS,
+    capabilities::UPDATE_GROUPS, capabilities::CUSTOM_MESSAGES, capabilities::ANIMATIONS, capabilities::GROUP_OPERATIONS,
+    capabilities::RPC, capabilities::COMPOSITES, capabilities::TEXT_PATCHES, capabilities::ON_DEMAND,
+];
+
+fn builder() -> DebuggableServerBuilder {
+    DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+}
+
+fn set_of(capabilities: &[String]) -> BTreeSet<&str> {
+    capabilities.iter().map(String::as_str).collect()
+}
+
+#[test]
+fn capabilities_match_the_builder_configuration() {
+    let plain = StepServer::from_builder(builder());
+    assert_eq!(set_of(&plain.handle().read().unwrap().capabilities()), BTreeSet::from(ALWAYS_SUPPORTED));
+
+    let dir = std::env::temp_dir().join(format!("debug_monitor-server_info-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let configured = StepServer::from_builder(builder().max_value_bytes(1024).read_dir(dir.to_string_lossy()).compression_threshold(4096));
+    let mut expected = BTreeSet::from(ALWAYS_SUPPORTED);
+    expected.extend([capabilities::VALUE_SUMMARIES, capabilities::READ_DIR]);
+    if cfg!(feature = "compression") {
+        expected.insert(capabilities::DEFLATE);
+    }
+    assert_eq!(set_of(&configured.handle().read().unwrap().capabilities()), expected);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn server_info_comes_before_the_client_id() {
+    let step = StepServer::new();
+    let _shown = DebuggableBuilder::new("shown", 1).scoped(step.scoped_server()).build();
+    let _hidden = DebuggableBuilder::new("hidden", 2).scoped(step.scoped_server()).hidden(true).build();
+    let mut client = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    assert!(step.accept_until(1));
+
+    let received = client.receive_until(|received| client_id_in(received).is_some()).unwrap();
+    let ServerMessage::ServerInfo { crate_version, protocol_version, capabilities, debuggable_count, .. } = &received[0] else {
+        panic!("The first message wasn't ServerInfo: {received:?}");
+    };
+    assert_eq!((crate_version.as_str(), *protocol_version, *debuggable_count), (env!("CARGO_PKG_VERSION"), PROTOCOL_VERSION, 1));
+    assert_eq!(set_of(capabilities), BTreeSet::from(ALWAYS_SUPPORTED));
+    assert!(matches!(received[1], ServerMessage::GiveClientId { .. }), "{received:?}");
+}
+
+#[test]
+fn debuggable_client_exposes_the_server_info() {
+    let step = StepServer::new();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.server_info().is_some()).is_some());
+    let server_info = client.server_info().unwrap();
+    assert!(server_info.supports(capabilities::NOTIFY_MANY) && !server_info.supports(capabilities::READ_DIR));
+    assert_eq!(server_info.protocol_version, PROTOCOL_VERSION);
+}
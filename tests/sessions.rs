@@ -0,0 +1,151 @@
+//! Monitors resuming their session under a key, getting back their panel, subscriptions and blobs.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn session_server(builder: DebuggableServerBuilder) -> (StepServer, Debuggable<u32>, Debuggable<u32>) {
+    let step = StepServer::from_builder(builder.replay_values_on_hello(true));
+    let fps = DebuggableBuilder::new("fps", 60).scoped(step.scoped_server()).build();
+    let gravity = DebuggableBuilder::new("gravity", 9).scoped(step.scoped_server()).build();
+    (step, fps, gravity)
+}
+
+fn builder() -> DebuggableServerBuilder {
+    DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+}
+
+fn id_of(step: &StepServer, name: &str) -> usize {
+    step.handle().read().unwrap().debuggable_id_of(name).unwrap()
+}
+
+/// Waits for the server to read everything the client sent so far, by sending an update to the
+/// debuggable right after and waiting for it to be pending.
+fn read_all_of(step: &StepServer, client: &mut DebuggableClient, debuggable_id: usize) {
+    let pending = step.handle().read().unwrap().pending_updates_of(debuggable_id);
+    client.send_update(debuggable_id, "1").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(debuggable_id) > pending));
+}
+
+fn welcome_count(received: &[ServerMessage]) -> usize {
+    received.iter().filter(|message| matches!(message, ServerMessage::Welcome { .. })).count()
+}
+
+/// Messages received after the server last welcomed the client.
+fn after_last_welcome(received: &[ServerMessage]) -> &[ServerMessage] {
+    let welcome = received.iter().rposition(|message| matches!(message, ServerMessage::Welcome { .. })).unwrap();
+    &received[welcome..]
+}
+
+fn notified_ids(messages: &[ServerMessage]) -> Vec<usize> {
+    messages.iter().filter_map(|message| match message {
+        ServerMessage::Notify { id, .. } => Some(*id),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn resumed_sessions_restore_panel_subscriptions_and_blobs() {
+    let (step, _fps, _gravity) = session_server(builder());
+    let (fps_id, gravity_id) = (id_of(&step, "fps"), id_of(&step, "gravity"));
+
+    let mut monitor = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    monitor.set_panel(Some("inspector".to_string()));
+    monitor.resume_session("monitor").unwrap();
+    monitor.subscribe(Some(vec!["fps".to_string()])).unwrap();
+    monitor.store_blob("layout", "{\"columns\":2}").unwrap();
+    read_all_of(&step, &mut monitor, fps_id);
+    assert_eq!(step.handle().read().unwrap().session_count(), 1);
+    drop(monitor);
+    assert!(step.read_until(|server| server.client_count() == 0));
+
+    let mut monitor = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    monitor.resume_session("monitor").unwrap();
+    read_all_of(&step, &mut monitor, fps_id);
+    let received = poll_client_until(&mut monitor, |client, _| client.blob("layout").is_some()).unwrap();
+    assert_eq!(monitor.blob("layout"), Some("{\"columns\":2}"));
+    assert_eq!(notified_ids(after_last_welcome(&received)), vec![fps_id]);
+
+    // Updates of the resumed session keep being attributed to its panel
+    monitor.send_update(gravity_id, "10").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(gravity_id) == 1));
+    let details = step.handle().read().unwrap().pending_details(gravity_id);
+    assert_eq!(details[0].panel.as_deref(), Some("inspector"));
+}
+
+#[test]
+fn unknown_keys_start_with_defaults() {
+    let (step, _fps, _gravity) = session_server(builder());
+    let gravity_id = id_of(&step, "gravity");
+
+    let mut monitor = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    monitor.resume_session("never seen").unwrap();
+    assert!(step.read_until(|server| server.session_count() == 1));
+    let received = poll_client_until(&mut monitor, |_, received| {
+        welcome_count(received) == 2 && notified_ids(after_last_welcome(received)).len() == 2
+    }).unwrap();
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Blob { .. })));
+
+    monitor.send_update(gravity_id, "10").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(gravity_id) == 1));
+    assert_eq!(step.handle().read().unwrap().pending_details(gravity_id)[0].panel, None);
+}
+
+#[test]
+fn blobs_over_the_session_limit_are_rejected() {
+    let (step, _fps, _gravity) = session_server(builder().max_session_blob_bytes(32));
+    let fps_id = id_of(&step, "fps");
+
+    let mut monitor = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    monitor.resume_session("monitor").unwrap();
+    monitor.store_blob("layout", "x".repeat(8)).unwrap();
+    monitor.store_blob("notes", "x".repeat(64)).unwrap();
+    read_all_of(&step, &mut monitor, fps_id);
+    assert_eq!(step.handle().read().unwrap().stats().rejected_blobs, 1);
+
+    // Replacing a blob only counts its new size
+    monitor.store_blob("layout", "x".repeat(20)).unwrap();
+    read_all_of(&step, &mut monitor, fps_id);
+    assert_eq!(step.handle().read().unwrap().stats().rejected_blobs, 1);
+}
+
+#[test]
+fn least_recently_used_sessions_are_evicted() {
+    let (step, _fps, _gravity) = session_server(builder().max_sessions(2));
+    let fps_id = id_of(&step, "fps");
+
+    let mut monitor = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    for key in ["first", "second", "third"] {
+        monitor.resume_session(key).unwrap();
+        monitor.store_blob("key", key).unwrap();
+    }
+    read_all_of(&step, &mut monitor, fps_id);
+    let server = step.handle();
+    assert_eq!(server.read().unwrap().session_count(), 2);
+    assert_eq!(server.read().unwrap().stats().evicted_sessions, 1);
+
+    // Sessions in use are kept, the rest are evicted down to the new limit
+    server.write().unwrap().set_max_sessions(1);
+    assert_eq!(server.read().unwrap().session_count(), 1);
+    assert_eq!(server.read().unwrap().stats().evicted_sessions, 2);
+
+    monitor.resume_session("first").unwrap();
+    assert!(step.read_until(|server| server.session_count() == 1 && server.stats().evicted_sessions == 3));
+    monitor.resume_session("third").unwrap();
+    assert!(step.read_until(|server| server.stats().evicted_sessions == 4));
+    // Welcomed once on connecting and once per resumed session, the evicted one without its blob
+    let received = poll_client_until(&mut monitor, |_, received| {
+        welcome_count(received) == 6 && notified_ids(after_last_welcome(received)).len() == 2
+    }).unwrap();
+    assert!(!after_last_welcome(&received).iter().any(|message| matches!(message, ServerMessage::Blob { .. })));
+}
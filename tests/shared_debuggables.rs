@@ -0,0 +1,53 @@
+//! One debuggable shared by handles on different threads, all under a single registration.
+#![cfg(feature = "server")]
+
+use std::sync::mpsc;
+use std::thread;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::testing::StepServer;
+
+fn registered(step: &StepServer) -> usize {
+    step.handle().read().unwrap().collect_infos().len()
+}
+
+#[test]
+fn handle_on_one_thread_sees_what_another_set_and_the_last_drop_unregisters() {
+    let step = StepServer::new();
+    let tuning = DebuggableBuilder::new("tuning", 1.0_f32).scoped(step.scoped_server()).build_shared();
+    let id = step.handle().read().unwrap().debuggable_id_of("tuning").unwrap();
+    let (setter_handle, observer_handle) = (tuning.clone(), tuning.clone());
+    assert_eq!((tuning.handles(), registered(&step)), (3, 1));
+
+    let (set_sender, set_receiver) = mpsc::channel();
+    let setter = thread::spawn(move || {
+        setter_handle.set(2.5);
+        setter_handle.sync();
+        set_sender.send(()).unwrap();
+    });
+    let observer = thread::spawn(move || {
+        set_receiver.recv().unwrap();
+        observer_handle.sync();
+        observer_handle.get_cloned()
+    });
+    setter.join().unwrap();
+    assert_eq!(observer.join().unwrap(), 2.5);
+    assert_eq!((tuning.handles(), registered(&step)), (1, 1));
+    assert_eq!(step.handle().read().unwrap().collect_infos()[0].last_value_json.as_deref(), Some("2.5"));
+
+    // A remote update is applied once by whichever handle syncs first
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(id, "4.0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    let remote_observer = tuning.clone();
+    assert_eq!(thread::spawn(move || { remote_observer.sync(); remote_observer.get_cloned() }).join().unwrap(), 4.0);
+    assert_eq!(tuning.get_cloned(), 4.0);
+    assert_eq!(registered(&step), 1);
+
+    let last = tuning.clone();
+    drop(tuning);
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("tuning"), Some(id));
+    drop(last);
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("tuning"), None);
+}
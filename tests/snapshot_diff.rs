@@ -0,0 +1,88 @@
+//! Differences between snapshots, crafted by hand or taken from a running server.
+
+use debug_monitor::snapshot::{diff, SnapshotError};
+
+const BEFORE: &str = r#"{"speed": 1, "label":"calm", "player":{"pos":{"x":1,"y":2},"hp":10}, "gone":true}"#;
+const AFTER: &str = r#"{"speed":1,"label":"angry","player":{"pos":{"x":1,"y":5},"hp":10,"mana":3},"new":[1]}"#;
+
+fn changed_values(diff: &debug_monitor::snapshot::SnapshotDiff) -> Vec<(&str, &str, &str)> {
+    changes.changed.iter().map(|changed| (changed.name.as_str(), changed.before.as_str(), changed.after.as_str())).collect()
+}
+
+#[test]
+fn lists_added_removed_and_changed_names() {
+    let changes = diff(BEFORE, AFTER).unwrap();
+    assert_eq!(changes.added, ["new"]);
+    assert_eq!(changes.removed, ["gone"]);
+    assert_eq!(changed_values(&changes), [
+        ("label", "\"calm\"", "\"angry\""),
+        ("player", r#"{"pos":{"x":1,"y":2},"hp":10}"#, r#"{"pos":{"x":1,"y":5},"hp":10,"mana":3}"#),
+    ]);
+
+    let shown = changes.to_string();
+    for line in ["+ new", "- gone", "~ label: \"calm\" -> \"angry\""] {
+        assert!(shown.lines().any(|shown_line| shown_line == line), "{line} missing from\n{shown}");
+    }
+    assert!(!changes.is_empty());
+    assert!(diff(BEFORE, BEFORE).unwrap().is_empty());
+}
+
+#[cfg(feature = "use_serde")]
+#[test]
+fn drills_down_into_nested_fields() {
+    use debug_monitor::snapshot::FieldChange;
+
+    let changes = diff(BEFORE, AFTER).unwrap();
+    let player = changes.changed.iter().find(|changed| changed.name == "player").unwrap();
+    assert_eq!(player.fields, [
+        FieldChange { path: "pos.y".to_string(), before: Some("2".to_string()), after: Some("5".to_string()) },
+        FieldChange { path: "mana".to_string(), before: None, after: Some("3".to_string()) },
+    ]);
+    let label = changes.changed.iter().find(|changed| changed.name == "label").unwrap();
+    assert!(label.fields.is_empty());
+    let shown = changes.to_string();
+    for line in ["    ~ pos.y: 2 -> 5", "    ~ mana: (none) -> 3"] {
+        assert!(shown.lines().any(|shown_line| shown_line == line), "{line} missing from\n{shown}");
+    }
+
+    // Equal values written differently aren't changes
+    assert!(diff(r#"{"origin": { "x": 0, "y": 0 }}"#, r#"{"origin":{"y":0,"x":0}}"#).unwrap().is_empty());
+}
+
+#[cfg(any(feature = "use_serde", feature = "use_nanoserde"))]
+#[test]
+fn round_trips_as_json() {
+    use debug_monitor::serializable::JSONDeSerializable;
+    use debug_monitor::snapshot::SnapshotDiff;
+
+    let changes = diff(BEFORE, AFTER).unwrap();
+    assert_eq!(SnapshotDiff::from_json(&changes.to_json().unwrap()), Some(changes));
+}
+
+#[test]
+fn rejects_what_isnt_a_snapshot() {
+    assert_eq!(diff("[1]", "{}"), Err(SnapshotError::NotAnObject));
+    assert_eq!(diff("{}", "\"speed\""), Err(SnapshotError::NotAnObject));
+    assert!(matches!(diff("{\"speed\"}", "{}"), Err(SnapshotError::Malformed { .. })));
+    assert!(matches!(diff("{}", "{\"speed\":}"), Err(SnapshotError::Malformed { .. })));
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn compares_a_snapshot_against_a_live_server() {
+    use debug_monitor::debuggable::DebuggableBuilder;
+    use debug_monitor::scoped_server::ScopedServer;
+
+    let server = ScopedServer::new();
+    let mut speed = DebuggableBuilder::new("speed", 1).scoped(&server).build();
+    let gone = DebuggableBuilder::new("gone", true).scoped(&server).build();
+    let before = server.handle().read().unwrap().snapshot();
+
+    *speed = 4;
+    assert_eq!(*speed, 4);
+    drop(gone);
+    let _new = DebuggableBuilder::new("new", "fresh".to_string()).scoped(&server).build();
+    let changes = server.handle().read().unwrap().diff_against_snapshot(&before).unwrap();
+    assert_eq!((changes.added.clone(), changes.removed.clone()), (vec!["new".to_string()], vec!["gone".to_string()]));
+    assert_eq!(changed_values(&changes), [("speed", "1", "4")]);
+}
@@ -0,0 +1,48 @@
+//! Memory the server holds while clients come, edit values and go, as seen through memory_report.
+#![cfg(feature = "server")]
+
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::testing::StepServer;
+
+const ROUNDS: usize = 3;
+const CLIENTS_PER_ROUND: usize = 4;
+const UPDATES_PER_CLIENT: usize = 25;
+
+#[test]
+fn pending_updates_and_client_bookkeeping_return_to_baseline() {
+    let step = StepServer::new();
+    let counters = (0..3).map(|index| DebuggableBuilder::new(format!("counter_{index}"), 0).scoped(step.scoped_server()).build()).collect::<Vec<Debuggable<i32>>>();
+    let ids = counters.iter().enumerate()
+        .map(|(index, _)| step.handle().read().unwrap().debuggable_id_of(&format!("counter_{index}")).unwrap())
+        .collect::<Vec<_>>();
+    let baseline = step.handle().read().unwrap().memory_report();
+    assert_eq!(baseline.debuggables, ids.len());
+    assert_eq!((baseline.pending_updates(), baseline.clients.connected, baseline.clients.entries()), (0, 0, 0));
+
+    for round in 0..ROUNDS {
+        let mut clients = (0..CLIENTS_PER_ROUND).map(|_| step.connect().unwrap()).collect::<Vec<_>>();
+        assert!(step.accept_until(CLIENTS_PER_ROUND));
+        for (client_index, client) in clients.iter_mut().enumerate() {
+            for update in 0..UPDATES_PER_CLIENT {
+                client.send_update(ids[update % ids.len()], round * 1_000 + client_index * 100 + update).unwrap();
+            }
+        }
+        let sent = CLIENTS_PER_ROUND * UPDATES_PER_CLIENT;
+        assert!(step.read_until(|server| server.memory_report().pending_updates() == sent));
+        let during = step.handle().read().unwrap().memory_report();
+        assert!(during.pending_bytes() > 0);
+        assert_eq!(during.clients.connected, CLIENTS_PER_ROUND);
+        assert!(during.clients.entries() > 0);
+
+        counters.iter().for_each(|counter| { let _ = **counter; });
+        assert!(step.handle().read().unwrap().memory_report().pending.is_empty());
+        drop(clients);
+        assert!(step.read_until(|server| server.memory_report().clients == baseline.clients));
+    }
+
+    let after = step.handle().read().unwrap().memory_report();
+    assert!(after.pending.is_empty());
+    assert_eq!(after.held_group_updates, baseline.held_group_updates);
+    assert_eq!(after.debuggables, baseline.debuggables);
+    assert_eq!(after.clients, baseline.clients);
+}
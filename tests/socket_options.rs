@@ -0,0 +1,58 @@
+//! Options applied to the sockets of accepted clients, and disconnecting clients too slow to read.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use debug_monitor::server::{DebuggableServer, Who};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+
+use common::serve_until;
+
+fn listener() -> (TcpListener, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    (listener, addr)
+}
+
+fn has_client(server: &DebuggableServer) -> bool {
+    server.read().clients().contains_index(0)
+}
+
+#[test]
+fn accepted_sockets_get_the_configured_options() {
+    let (listener, addr) = listener();
+    let server = DebuggableServerBuilder::new(listener)
+        .tcp_nodelay(true)
+        .client_read_timeout(Some(Duration::from_millis(250)))
+        .client_write_timeout(Some(Duration::from_millis(500)))
+        .build();
+    let _client = TcpStream::connect(addr).unwrap();
+    assert!(serve_until(&server, || has_client(&server)));
+
+    let socket = server.client_socket(0).unwrap();
+    assert!(socket.nodelay().unwrap());
+    assert_eq!(socket.read_timeout().unwrap(), Some(Duration::from_millis(250)));
+    assert_eq!(socket.write_timeout().unwrap(), Some(Duration::from_millis(500)));
+}
+
+#[test]
+fn clients_not_reading_are_disconnected_once_writes_time_out() {
+    let (listener, addr) = listener();
+    let server = DebuggableServerBuilder::new(listener)
+        .client_write_timeout(Some(Duration::from_millis(20)))
+        .build();
+    let _stalled_client = TcpStream::connect(addr).unwrap();
+    assert!(serve_until(&server, || has_client(&server)));
+
+    // Sends until the socket buffers fill up and a write times out
+    let payload = "x".repeat(1024 * 1024);
+    for _ in 0..512 {
+        server.send_custom(Who::All, "bulk", &payload);
+        server.read_all_clients();
+        if !has_client(&server) { break; }
+    }
+    assert!(!has_client(&server));
+}
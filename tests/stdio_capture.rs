@@ -0,0 +1,65 @@
+//! Mirroring the process' stdout into a debuggable while it keeps reaching the original stdout.
+#![cfg(all(unix, feature = "capture-stdio"))]
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::FromRawFd;
+use std::thread;
+use std::time::Instant;
+
+use debug_monitor::server::stdio_capture::STDOUT_DEBUGGABLE_NAME;
+use debug_monitor::testing::{StepServer, STEP_TIMEOUT};
+
+/// Points stdout at a pipe for the rest of the test, returning its reading end and the original
+/// stdout to restore.
+fn redirect_stdout_to_pipe() -> (File, i32) {
+    let mut pipe_fds = [0; 2];
+    unsafe {
+        assert_eq!(libc::pipe(pipe_fds.as_mut_ptr()), 0);
+        let original_stdout = libc::dup(1);
+        assert!(original_stdout >= 0);
+        assert!(libc::dup2(pipe_fds[1], 1) >= 0);
+        libc::close(pipe_fds[1]);
+        (File::from_raw_fd(pipe_fds[0]), original_stdout)
+    }
+}
+
+fn restore_stdout(original_stdout: i32) {
+    unsafe {
+        libc::dup2(original_stdout, 1);
+        libc::close(original_stdout);
+    }
+}
+
+#[test]
+fn captured_lines_reach_the_debuggable_and_the_original_stdout() {
+    let (mut real_stdout, original_stdout) = redirect_stdout_to_pipe();
+    let forwarded = thread::spawn(move || {
+        let mut forwarded = Vec::new();
+        real_stdout.read_to_end(&mut forwarded).unwrap();
+        forwarded
+    });
+    let step = StepServer::new();
+    step.handle().read().unwrap().capture_stdout(2).unwrap();
+
+    let written: &[&[u8]] = &[b"first\nsec", b"ond\nthird \xff\n", b"partial"];
+    for bytes in written {
+        std::io::stdout().write_all(bytes).unwrap();
+        std::io::stdout().flush().unwrap();
+    }
+    let expected_lines = Some("[\"second\",\"third \u{fffd}\"]".to_string());
+    let give_up_at = Instant::now() + STEP_TIMEOUT;
+    let mut lines = None;
+    while lines != expected_lines && Instant::now() < give_up_at {
+        step.housekeeping();
+        lines = step.handle().read().unwrap().value_of(STDOUT_DEBUGGABLE_NAME);
+        thread::yield_now();
+    }
+
+    step.handle().read().unwrap().release_stdout().unwrap();
+    let still_captured = step.handle().read().unwrap().debuggable_id_of(STDOUT_DEBUGGABLE_NAME).is_some();
+    restore_stdout(original_stdout);
+    assert_eq!(lines, expected_lines);
+    assert!(!still_captured);
+    assert_eq!(forwarded.join().unwrap(), written.concat());
+}
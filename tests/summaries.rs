@@ -0,0 +1,96 @@
+//! Values too large to broadcast, which clients see summarized and fetch whole on request.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+
+use debug_monitor::client::{DebuggableClient, UpdateOutcome};
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::server::SUMMARY_PREVIEW_CHARS;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+const MAX_VALUE_BYTES: usize = 1024;
+const WORLD_BYTES: usize = 5 * 1024 * 1024;
+
+fn summarizing_server() -> StepServer {
+    // Queued writes keep the server from blocking on a client that didn't read the chunks yet
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .max_value_bytes(MAX_VALUE_BYTES)
+        .max_outgoing_queue(1024);
+    StepServer::from_builder(builder)
+}
+
+fn world_on(step: &StepServer) -> (Debuggable<String>, usize) {
+    let world = DebuggableBuilder::new("world", "w".repeat(WORLD_BYTES)).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("world").unwrap();
+    (world, id)
+}
+
+fn connect_seeing_summary(step: &StepServer, id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|world| world.summary.is_some()));
+    assert!(!received.unwrap().iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, .. } if *notified == id)));
+    client
+}
+
+/// Messages received until the full value arrived.
+fn fetch_full_value(step: &StepServer, client: &mut DebuggableClient, id: usize) -> Vec<ServerMessage> {
+    client.request_full_value(id).unwrap();
+    // Read until the request is processed, the update sent after it is then queued
+    client.send_update(id, "\"fetched\"").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    poll_client_until(client, |client, _| client.debuggable(id).is_some_and(|world| world.summary.is_none())).unwrap()
+}
+
+#[test]
+fn large_value_is_broadcast_as_a_summary() {
+    let step = summarizing_server();
+    let (world, id) = world_on(&step);
+    let client = connect_seeing_summary(&step, id);
+    let remote_world = client.debuggable(id).unwrap();
+    let summary = remote_world.summary.as_ref().unwrap();
+    assert_eq!(summary.byte_len, WORLD_BYTES + 2);
+    assert_eq!(summary.preview.chars().count(), SUMMARY_PREVIEW_CHARS);
+    assert!(summary.preview.starts_with("\"www"));
+    assert!(remote_world.value_in_json.is_empty());
+    assert_eq!(world.len(), WORLD_BYTES);
+}
+
+#[test]
+fn full_value_arrives_in_chunks() {
+    let step = summarizing_server();
+    let (mut world, id) = world_on(&step);
+    let mut client = connect_seeing_summary(&step, id);
+    fetch_full_value(&step, &mut client, id);
+    let remote_world = client.debuggable(id).unwrap();
+    assert_eq!(remote_world.value_in_json.len(), WORLD_BYTES + 2);
+    assert_eq!(remote_world.value_in_json, format!("\"{}\"", "w".repeat(WORLD_BYTES)));
+    assert_eq!(*world, "fetched");
+}
+
+#[test]
+fn edits_are_refused_until_the_full_value_is_fetched() {
+    let step = summarizing_server();
+    let (mut world, id) = world_on(&step);
+    let mut client = connect_seeing_summary(&step, id);
+    let request_id = client.send_tracked_update(id, "\"edited blindly\"").unwrap();
+    let received = fetch_full_value(&step, &mut client, id);
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("summarized"))));
+    assert!(!client.is_update_pending(request_id));
+    assert_eq!(client.take_update_outcomes(), vec![(request_id, UpdateOutcome::Rejected)]);
+    assert_eq!(*world, "fetched");
+}
+
+#[test]
+fn small_values_are_still_notified_whole() {
+    let step = summarizing_server();
+    let counter = DebuggableBuilder::new("counter", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|counter| counter.value_in_json == "1")).is_some());
+    assert!(client.debuggable(id).unwrap().summary.is_none());
+    assert_eq!(*counter, 1);
+}
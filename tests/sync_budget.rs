@@ -0,0 +1,107 @@
+//! Syncs limited to a time budget, deferring the rest of their polling to the next ones.
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use debug_monitor::clock::Clock;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::dir_client::DirClient;
+use debug_monitor::scoped_server::ScopedServer;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{ManualClock, StepServer};
+
+const BACKLOG: usize = 500;
+const TICK: Duration = Duration::from_millis(1);
+const BUDGET: Duration = Duration::from_millis(10);
+
+/// Moves forward a tick every time it's read, as if every step of the server took that long.
+#[derive(Debug, Default)]
+struct TickingClock {
+    manual: ManualClock,
+}
+
+impl Clock for TickingClock {
+    fn now_instant(&self) -> Instant {
+        self.manual.advance(TICK);
+        self.manual.now_instant()
+    }
+
+    fn now_system(&self) -> SystemTime {
+        self.manual.now_system()
+    }
+}
+
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("debug_monitor-sync_budget-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn transactions_left(dir: &PathBuf) -> usize {
+    fs::read_dir(dir).unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("client-"))
+        .count()
+}
+
+#[test]
+fn read_dir_backlog_drains_a_budget_at_a_time() {
+    let dir = temp_dir("backlog");
+    let clock = Arc::new(TickingClock::default());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .clock(clock.clone())
+        .read_dir(dir.to_string_lossy())
+        .sync_budget(BUDGET);
+    let step = StepServer::from_builder(builder);
+    let counter = DebuggableBuilder::new("counter", 0).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("counter").unwrap();
+    let mut client = DirClient::new(&dir, 7).unwrap();
+    for value in 1..=BACKLOG {
+        client.send_update(id, value).unwrap();
+    }
+
+    let mut calls = 0;
+    while transactions_left(&dir) > 0 {
+        let left_before = transactions_left(&dir);
+        step.handle().read().unwrap().read_clients_from_read_dir();
+        let processed = left_before - transactions_left(&dir);
+        // Every transaction checks the clock, so no more fit than ticks in the budget
+        assert!((1..=BUDGET.as_millis() as usize).contains(&processed), "processed {processed}");
+        calls += 1;
+        assert!(calls <= BACKLOG);
+    }
+    assert!(calls > 1);
+    assert!(step.handle().read().unwrap().stats().deferred_dir_transactions > 0);
+    assert_eq!(*counter, BACKLOG as i32);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn stages_left_when_the_budget_runs_out_resume_on_the_next_sync() {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .clock(clock.clone())
+        .sync_budget(Duration::ZERO);
+    let server = ScopedServer::from_builder(builder);
+    let counter = DebuggableBuilder::new("counter", 0).scoped(&server).build();
+    let _client = TcpStream::connect(server.addr()).unwrap();
+    let polls = || {
+        let deferred_stages = server.handle().read().unwrap().stats().deferred_poll_stages;
+        // Only one of the four stages fits in the budget, the other three are deferred
+        assert_eq!(deferred_stages % 3, 0);
+        deferred_stages / 3
+    };
+
+    // Whichever stage the next poll starts from, accepting comes around within a round of polls
+    let polls_before = polls();
+    while server.handle().read().unwrap().client_count() == 0 {
+        assert_eq!(*counter, 0);
+        assert!(polls() - polls_before <= 4, "{} polls without accepting", polls() - polls_before);
+    }
+    assert!(polls() > polls_before);
+    assert_eq!(*counter, 0);
+}
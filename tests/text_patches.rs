@@ -0,0 +1,145 @@
+//! Text debuggables whose changes travel as the lines that changed.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::text_patch::{apply_hunks, diff_lines, TextHunk};
+use debug_monitor::serializable::{ClientUnitMessage, JSONDeSerializable, ServerMessage};
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn source_of(line_count: usize) -> String {
+    (0..line_count).map(|line| format!("float value_{line} = {line}.0;")).collect::<Vec<_>>().join("\n")
+}
+
+fn assert_round_trips(old: &str, new: &str) -> Vec<TextHunk> {
+    let hunks = diff_lines(old, new);
+    assert_eq!(apply_hunks(old, &hunks).as_deref(), Some(new), "{old:?} -> {new:?} through {hunks:?}");
+    hunks
+}
+
+/// Connects a client and waits for the server to read its Hello, by waiting for an update it sends
+/// to the greeting debuggable right after.
+fn greeted_client(step: &StepServer, greeting_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(greeting_id, "0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(greeting_id) == 1));
+    client
+}
+
+#[test]
+fn equal_texts_need_no_hunks() {
+    assert!(assert_round_trips("a\nb\nc", "a\nb\nc").is_empty());
+    assert!(assert_round_trips("", "").is_empty());
+}
+
+#[test]
+fn hunks_only_cover_the_changed_lines() {
+    let hunks = assert_round_trips("a\nb\nc\nd", "a\nB\nc\nd");
+    assert_eq!(hunks, vec![TextHunk { start: 1, removed: 1, inserted: vec!["B".to_string()] }]);
+
+    let hunks = assert_round_trips("a\nb\nc\nd\ne", "x\nb\nc\nd\ny");
+    assert_eq!(hunks.iter().map(|hunk| hunk.start).collect::<Vec<_>>(), vec![0, 4]);
+}
+
+#[test]
+fn insertions_and_removals_round_trip() {
+    assert_eq!(assert_round_trips("a\nc", "a\nb\nc"), vec![TextHunk { start: 1, removed: 0, inserted: vec!["b".to_string()] }]);
+    assert_eq!(assert_round_trips("a\nb\nc", "a\nc"), vec![TextHunk { start: 1, removed: 1, inserted: Vec::new() }]);
+    assert_round_trips("", "a\nb");
+    assert_round_trips("a\nb", "");
+    assert_round_trips("a\nb\n", "a\nb");
+    assert_round_trips("x\na\ny\nb\nz", "a\nq\nb\nr");
+    assert_round_trips(&source_of(300), &source_of(310).replace("value_7 ", "renamed_7 "));
+}
+
+#[test]
+fn hunks_not_fitting_the_base_are_rejected() {
+    let hunks = diff_lines("a\nb\nc\nd", "a\nb\nc\nD");
+    assert_eq!(apply_hunks("a\nb", &hunks), None);
+
+    let overlapping = [TextHunk { start: 1, removed: 2, inserted: Vec::new() }, TextHunk { start: 2, removed: 1, inserted: Vec::new() }];
+    assert_eq!(apply_hunks("a\nb\nc\nd", &overlapping), None);
+    assert_eq!(apply_hunks("a", &[TextHunk { start: usize::MAX, removed: 2, inserted: Vec::new() }]), None);
+}
+
+#[test]
+fn text_patch_messages_round_trip() {
+    let hunks = diff_lines("a\nb", "a\nc\nd");
+    let notify = ServerMessage::NotifyTextPatch { id: 3, base_revision: 4, revision: 5, hunks: hunks.clone() };
+    assert_eq!(ServerMessage::from_json(&notify.to_json().unwrap()), Some(notify));
+    let update = ClientUnitMessage::UpdateTextPatch { id: 3, base_revision: 5, hunks };
+    assert_eq!(ClientUnitMessage::from_json(&update.to_json().unwrap()), Some(update));
+}
+
+#[test]
+fn changed_lines_reach_clients_as_patches() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let mut shader = DebuggableBuilder::new("shader", source_of(200)).text_diff().scoped(step.scoped_server()).build();
+    let (greeting_id, shader_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("shader").unwrap())
+    };
+    let mut client = greeted_client(&step, greeting_id);
+    let initial_json = source_of(200).to_json().unwrap();
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(shader_id).is_some_and(|shader| shader.value_in_json == initial_json)).is_some());
+
+    *shader = shader.replace("value_120 = 120.0", "value_120 = sin(120.0)");
+    let edited_json = shader.to_json().unwrap();
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(shader_id).is_some_and(|shader| shader.value_in_json == edited_json)).unwrap();
+    let patch = received.iter().find(|message| matches!(message, ServerMessage::NotifyTextPatch { .. })).unwrap();
+    assert!(patch.to_json().unwrap().len() < edited_json.len());
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::Notify { id, .. } if *id == shader_id)));
+
+    // Patches larger than the new text are sent as a whole Notify
+    *shader = "void main() {}".to_string();
+    assert_eq!(*shader, "void main() {}");
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(shader_id).is_some_and(|shader| shader.value_in_json == "\"void main() {}\"")).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Notify { id, .. } if *id == shader_id)));
+}
+
+#[test]
+fn clients_update_text_through_patches() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let shader = DebuggableBuilder::new("shader", source_of(50)).text_diff().scoped(step.scoped_server()).build();
+    let (greeting_id, shader_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("shader").unwrap())
+    };
+    let mut client = greeted_client(&step, greeting_id);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(shader_id).is_some_and(|shader| !shader.value_in_json.is_empty())).is_some());
+
+    let edited = source_of(50).replace("value_3 = 3.0", "value_3 = 6.0");
+    assert!(client.send_text_update(shader_id, &edited).unwrap());
+    assert!(step.read_until(|server| server.pending_updates_of(shader_id) == 1));
+    assert_eq!(*shader, edited);
+}
+
+#[test]
+fn patches_against_a_stale_base_get_the_whole_value() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let shader = DebuggableBuilder::new("shader", source_of(50)).text_diff().scoped(step.scoped_server()).build();
+    let (greeting_id, shader_id) = {
+        let server = step.handle();
+        let server = server.read().unwrap();
+        (server.debuggable_id_of("greeting").unwrap(), server.debuggable_id_of("shader").unwrap())
+    };
+    let mut client = greeted_client(&step, greeting_id);
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(shader_id).is_some_and(|shader| !shader.value_in_json.is_empty())).is_some());
+    let revision = client.debuggable(shader_id).unwrap().revision;
+
+    let hunks = diff_lines(&source_of(50), "unrelated");
+    client.send(&ClientUnitMessage::UpdateTextPatch { id: shader_id, base_revision: revision + 1, hunks }).unwrap();
+    let resent = poll_client_until(&mut client, |_, received| {
+        step.read_until(|_| true);
+        received.iter().any(|message| matches!(message, ServerMessage::Notify { id, .. } if *id == shader_id))
+    });
+    assert!(resent.is_some());
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(shader_id), 0);
+    assert_eq!(*shader, source_of(50));
+}
@@ -0,0 +1,77 @@
+//! Clients connecting through TLS, terminated in front of the server's own listener.
+#![cfg(feature = "tls")]
+
+mod common;
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use debug_monitor::client::{DebuggableClient, MessageFraming};
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::DebuggableServer;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+
+use common::{serve_until, POLL_TIMEOUT};
+
+const ROOT_PEM: &str = include_str!("fixtures/tls/root.pem");
+const OTHER_ROOT_PEM: &str = include_str!("fixtures/tls/other_root.pem");
+// Signed by the root for localhost and 127.0.0.1
+const SERVER_CERT_PEM: &str = include_str!("fixtures/tls/server.pem");
+const SERVER_KEY_PEM: &str = include_str!("fixtures/tls/server.key");
+
+fn tls_server() -> (Arc<RwLock<DebuggableServer>>, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = DebuggableServerBuilder::new(listener).tls(SERVER_CERT_PEM, SERVER_KEY_PEM).build();
+    (Arc::new(RwLock::new(server)), addr)
+}
+
+fn connect(server: &RwLock<DebuggableServer>, addr: SocketAddr, root_pem: &str) -> std::io::Result<DebuggableClient> {
+    let framing = MessageFraming::of_server(&server.read().unwrap());
+    DebuggableClient::connect_tls(addr, framing, root_pem)
+}
+
+#[test]
+fn values_round_trip_over_tls() {
+    let (server, addr) = tls_server();
+    let speed = DebuggableBuilder::new("speed", 1).server(Some(server.clone())).build();
+    let id = server.read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = connect(&server, addr, ROOT_PEM).unwrap();
+    assert!(serve_until(&server.read().unwrap(), || {
+        client.poll().unwrap();
+        client.debuggable(id).is_some_and(|speed| speed.value_in_json == "1")
+    }));
+
+    client.send_update(id, "5").unwrap();
+    let give_up_at = Instant::now() + POLL_TIMEOUT;
+    while *speed != 5 {
+        assert!(Instant::now() < give_up_at);
+        server.read().unwrap().read_all_clients();
+    }
+    assert_eq!(server.read().unwrap().client_count(), 1);
+}
+
+#[test]
+fn certificates_from_another_root_are_refused_without_affecting_other_clients() {
+    let (server, addr) = tls_server();
+    let _speed = DebuggableBuilder::new("speed", 1).server(Some(server.clone())).build();
+    let id = server.read().unwrap().debuggable_id_of("speed").unwrap();
+    assert!(connect(&server, addr, OTHER_ROOT_PEM).is_err());
+
+    let mut client = connect(&server, addr, ROOT_PEM).unwrap();
+    assert!(serve_until(&server.read().unwrap(), || {
+        client.poll().unwrap();
+        client.debuggable(id).is_some()
+    }));
+    assert_eq!(server.read().unwrap().client_count(), 1);
+}
+
+#[test]
+fn plaintext_connections_skipping_the_terminator_are_refused() {
+    let (server, _) = tls_server();
+    let inner_addr = server.read().unwrap().local_addr().unwrap();
+    let _bypassing = TcpStream::connect(inner_addr).unwrap();
+    let server = server.read().unwrap();
+    assert!(serve_until(&server, || server.stats().rejected_connections == 1));
+}
@@ -0,0 +1,65 @@
+//! Messages rewritten by transforms on both ends of the wire, here a cipher reversing them.
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::{TcpListener, TcpStream};
+
+use common::WireClient;
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ClientUnitMessage;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn encrypt(message: &str) -> String {
+    message.chars().rev().collect()
+}
+
+/// Reversed JSON objects start with their closing brace, anything else wasn't encrypted.
+fn decrypt(message: &str) -> Option<String> {
+    message.starts_with('}').then(|| message.chars().rev().collect())
+}
+
+fn ciphered_server() -> StepServer {
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .outgoing_transform(Box::new(encrypt))
+        .incoming_transform(Box::new(decrypt));
+    StepServer::from_builder(builder)
+}
+
+fn ciphered_client(step: &StepServer) -> DebuggableClient {
+    let stream = TcpStream::connect(step.addr()).unwrap();
+    DebuggableClient::from_stream_with_transforms(stream, step.framing(), Some(Box::new(encrypt)), Some(Box::new(decrypt))).unwrap()
+}
+
+#[test]
+fn values_and_updates_round_trip_through_the_cipher() {
+    let step = ciphered_server();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = ciphered_client(&step);
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.server_info().is_some() && client.debuggable(id).is_some_and(|speed| speed.value_in_json == "1")).is_some());
+
+    client.send_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    assert_eq!(*speed, 5);
+    assert_eq!(step.handle().read().unwrap().stats().rejected_by_transform, 0);
+}
+
+#[test]
+fn plain_messages_are_dropped_and_counted() {
+    let step = ciphered_server();
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut plain = WireClient::connect(&step.handle().read().unwrap(), step.addr());
+    assert!(step.accept_until(1));
+
+    plain.send(&ClientUnitMessage::UpdateValue { id, new_value: "5".to_string(), request_id: None, panel: None });
+    assert!(step.read_until(|server| server.stats().rejected_by_transform == 1));
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(id), 0);
+    assert_eq!(*speed, 1);
+    // Everything the server sent is reversed, so none of it parses as a message
+    assert!(plain.receive().is_empty());
+}
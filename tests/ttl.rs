@@ -0,0 +1,63 @@
+//! Debuggables expiring once their owner stops syncing them, and registering again when it resumes.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::{RemoveReason, ServerMessage};
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+const TTL: Duration = Duration::from_millis(100);
+
+fn server_with_clock() -> (StepServer, Arc<ManualClock>) {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap()).clock(clock.clone());
+    (StepServer::from_builder(builder), clock)
+}
+
+#[test]
+fn untouched_debuggable_expires_while_one_kept_alive_does_not() {
+    let (step, clock) = server_with_clock();
+    let download = DebuggableBuilder::new("download", 10).scoped(step.scoped_server()).ttl(TTL).build();
+    let ghost = DebuggableBuilder::new("ghost", 20).scoped(step.scoped_server()).ttl(TTL).build();
+    let download_id = step.handle().read().unwrap().debuggable_id_of("download").unwrap();
+    let ghost_id = step.handle().read().unwrap().debuggable_id_of("ghost").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(ghost_id).is_some()).is_some());
+
+    for _ in 0..3 {
+        clock.advance(TTL / 2);
+        assert_eq!(*download, 10);
+        assert_eq!(step.handle().read().unwrap().expire_stale(), usize::from(clock.elapsed() > TTL));
+    }
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("download"), Some(download_id));
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("ghost"), None);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(ghost_id).is_none()).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Remove { id, reason: RemoveReason::Expired, .. } if *id == ghost_id)), "{received:?}");
+    drop(ghost);
+}
+
+#[test]
+fn expired_debuggable_registers_again_on_its_next_sync() {
+    let (step, clock) = server_with_clock();
+    let mut ghost = DebuggableBuilder::new("ghost", 20).scoped(step.scoped_server()).ttl(TTL).build();
+    let expired_id = step.handle().read().unwrap().debuggable_id_of("ghost").unwrap();
+
+    // Exactly the TTL isn't stale yet
+    clock.advance(TTL);
+    step.housekeeping();
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("ghost"), Some(expired_id));
+    clock.advance(Duration::from_millis(1));
+    step.housekeeping();
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("ghost"), None);
+
+    *ghost = 21;
+    assert_eq!(*ghost, 21);
+    assert!(step.handle().read().unwrap().debuggable_id_of("ghost").is_some());
+    assert_eq!(step.handle().read().unwrap().value_of("ghost").as_deref(), Some("21"));
+    assert!(ghost.is_registered());
+}
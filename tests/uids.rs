@@ -0,0 +1,60 @@
+//! Uids telling apart debuggables that took the id of a removed one.
+#![cfg(feature = "server")]
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+/// Connects a client and waits for the server to read its Hello, Added is only sent to clients
+/// known to understand it.
+fn greeted_client(step: &StepServer, greeting_id: usize) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    client.send_update(greeting_id, "0").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(greeting_id) == 1));
+    client
+}
+
+#[test]
+fn reused_ids_come_with_a_new_uid() {
+    let step = StepServer::new();
+    let _greeting = DebuggableBuilder::new("greeting", 0).scoped(step.scoped_server()).build();
+    let greeting_id = step.handle().read().unwrap().debuggable_id_of("greeting").unwrap();
+    let mut client = greeted_client(&step, greeting_id);
+
+    let first = DebuggableBuilder::new("first", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("first").unwrap();
+    let first_uid = first.uid().unwrap();
+    assert_eq!(step.handle().read().unwrap().uid_of(id), Some(first_uid));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|first| first.uid == Some(first_uid))).is_some());
+
+    drop(first);
+    let removed = poll_client_until(&mut client, |_, received| received.iter()
+        .any(|message| matches!(message, ServerMessage::Remove { id: removed, uid: Some(uid), .. } if *removed == id && *uid == first_uid)));
+    assert!(removed.is_some());
+    step.handle().read().unwrap().compact();
+
+    let second = DebuggableBuilder::new("second", 2).scoped(step.scoped_server()).build();
+    assert_eq!(step.handle().read().unwrap().debuggable_id_of("second"), Some(id));
+    let second_uid = second.uid().unwrap();
+    assert_ne!(second_uid, first_uid);
+    let received = poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|second| second.name == "second" && second.value_in_json == "2")).unwrap();
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Added { id: added, uid: Some(uid), .. } if *added == id && *uid == second_uid)));
+    assert!(received.iter().any(|message| matches!(message, ServerMessage::Notify { id: notified, uid: Some(uid), .. } if *notified == id && *uid == second_uid)));
+    assert_eq!(client.debuggable(id).unwrap().uid, Some(second_uid));
+}
+
+#[test]
+fn uids_survive_compaction() {
+    let step = StepServer::new();
+    let gap = DebuggableBuilder::new("gap", 0).scoped(step.scoped_server()).build();
+    let moved = DebuggableBuilder::new("moved", 0).scoped(step.scoped_server()).build();
+    let uid = moved.uid().unwrap();
+    drop(gap);
+    let handle = step.handle();
+    assert_eq!(handle.read().unwrap().compact(), 1);
+    let new_id = handle.read().unwrap().debuggable_id_of("moved").unwrap();
+    assert_eq!(handle.read().unwrap().uid_of(new_id), Some(uid));
+    assert_eq!(moved.uid(), Some(uid));
+}
@@ -0,0 +1,65 @@
+//! Tracked updates answered once their debuggable processes them, or given up on meanwhile.
+#![cfg(feature = "server")]
+
+use std::thread;
+use std::time::Duration;
+
+use debug_monitor::client::{DebuggableClient, UpdateOutcome};
+use debug_monitor::debuggable::{Debuggable, DebuggableBuilder};
+use debug_monitor::serializable::ServerMessage;
+use debug_monitor::testing::{poll_client_until, StepServer};
+
+fn speed_with_client(step: &StepServer) -> (Debuggable<i32>, usize, DebuggableClient) {
+    let speed = DebuggableBuilder::new("speed", 1).scoped(step.scoped_server()).build();
+    let id = step.handle().read().unwrap().debuggable_id_of("speed").unwrap();
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| client.debuggable(id).is_some_and(|speed| speed.value_in_json == "1")).is_some());
+    (speed, id, client)
+}
+
+/// Sends the updates, lets the debuggable process them together and returns their outcomes.
+fn outcomes_of(step: &StepServer, client: &mut DebuggableClient, speed: &Debuggable<i32>, id: usize, jsons: &[&str]) -> Vec<(u64, UpdateOutcome)> {
+    let request_ids = jsons.iter().map(|json| client.send_tracked_update(id, json).unwrap()).collect::<Vec<_>>();
+    assert!(client.has_pending_update(id));
+    assert!(step.read_until(|server| server.pending_updates_of(id) == jsons.len()));
+    let _ = **speed;
+    assert!(poll_client_until(client, |client, _| request_ids.iter().all(|request_id| !client.is_update_pending(*request_id))).is_some());
+    assert!(!client.has_pending_update(id));
+    client.take_update_outcomes()
+}
+
+#[test]
+fn accepted_rejected_and_superseded_updates_are_acknowledged() {
+    let step = StepServer::new();
+    let (speed, id, mut client) = speed_with_client(&step);
+
+    let accepted = outcomes_of(&step, &mut client, &speed, id, &["5"]);
+    assert_eq!(accepted, vec![(accepted[0].0, UpdateOutcome::Accepted)]);
+    assert_eq!(*speed, 5);
+
+    let rejected = outcomes_of(&step, &mut client, &speed, id, &["\"fast\""]);
+    assert_eq!(rejected, vec![(rejected[0].0, UpdateOutcome::Rejected)]);
+    assert_eq!(*speed, 5);
+
+    // Only the latest of the updates processed together is applied
+    let mut superseded = outcomes_of(&step, &mut client, &speed, id, &["6", "7"]);
+    superseded.sort_by_key(|(request_id, _)| *request_id);
+    assert_eq!(superseded.iter().map(|(_, outcome)| *outcome).collect::<Vec<_>>(), [UpdateOutcome::Rejected, UpdateOutcome::Accepted]);
+    assert_eq!(*speed, 7);
+}
+
+#[test]
+fn updates_of_a_debuggable_that_never_syncs_time_out() {
+    let step = StepServer::new();
+    let (_speed, id, mut client) = speed_with_client(&step);
+    client.set_update_timeout(Duration::from_millis(20));
+
+    let request_id = client.send_tracked_update(id, "5").unwrap();
+    assert!(step.read_until(|server| server.pending_updates_of(id) == 1));
+    thread::sleep(Duration::from_millis(30));
+    let received = client.poll().unwrap();
+    assert!(!received.iter().any(|message| matches!(message, ServerMessage::UpdateAck { .. })), "{received:?}");
+    assert_eq!(client.take_update_outcomes(), vec![(request_id, UpdateOutcome::TimedOut)]);
+    assert!(!client.is_update_pending(request_id));
+}
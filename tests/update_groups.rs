@@ -0,0 +1,70 @@
+//! Updates of coupled debuggables sent as one group, held until every one of them synced.
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+use debug_monitor::client::DebuggableClient;
+use debug_monitor::debuggable::DebuggableBuilder;
+use debug_monitor::server::debuggable_server_builder::DebuggableServerBuilder;
+use debug_monitor::testing::{poll_client_until, ManualClock, StepServer};
+
+fn connect(step: &StepServer, ids: &[usize]) -> DebuggableClient {
+    let mut client = step.connect().unwrap();
+    assert!(step.accept_until(1));
+    assert!(poll_client_until(&mut client, |client, _| ids.iter().all(|id| client.debuggable(*id).is_some())).is_some());
+    client
+}
+
+fn held_updates(step: &StepServer) -> usize {
+    step.handle().read().unwrap().memory_report().held_group_updates.updates
+}
+
+#[test]
+fn coupled_values_wait_for_each_other() {
+    let step = StepServer::new();
+    let count = DebuggableBuilder::new("count", 0).scoped(step.scoped_server()).build();
+    let items = DebuggableBuilder::new("items", Vec::<u32>::new()).scoped(step.scoped_server()).build();
+    let ids = ["count", "items"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = connect(&step, &ids);
+
+    client.send_update_group(&[(ids[0], "2".to_string()), (ids[1], "[7,8]".to_string())]).unwrap();
+    assert!(step.read_until(|server| server.memory_report().held_group_updates.updates == 2));
+    // Syncing count alone doesn't let it see its half of the group
+    assert_eq!(*count, 0);
+    assert_eq!(*count, 0);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(ids[0]), 0);
+
+    // Once items syncs too the whole group is released, each member applying its update
+    assert_eq!(*items, [7, 8]);
+    assert_eq!(held_updates(&step), 0);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(ids[0]), 1);
+    assert_eq!(*count, 2);
+}
+
+#[test]
+fn groups_fall_back_to_independent_updates_after_the_timeout() {
+    let clock = Arc::new(ManualClock::new());
+    let builder = DebuggableServerBuilder::new(TcpListener::bind("127.0.0.1:0").unwrap())
+        .clock(clock.clone())
+        .update_group_timeout(Duration::from_secs(1));
+    let step = StepServer::from_builder(builder);
+    let count = DebuggableBuilder::new("count", 0).scoped(step.scoped_server()).build();
+    let _items = DebuggableBuilder::new("items", Vec::<u32>::new()).scoped(step.scoped_server()).build();
+    let ids = ["count", "items"].map(|name| step.handle().read().unwrap().debuggable_id_of(name).unwrap());
+    let mut client = connect(&step, &ids);
+
+    client.send_update_group(&[(ids[0], "2".to_string()), (ids[1], "[7,8]".to_string())]).unwrap();
+    assert!(step.read_until(|server| server.memory_report().held_group_updates.updates == 2));
+    clock.advance(Duration::from_millis(999));
+    step.housekeeping();
+    assert_eq!(*count, 0);
+
+    // Items never synced, but count gets its update anyway
+    clock.advance(Duration::from_millis(1));
+    step.housekeeping();
+    assert_eq!(held_updates(&step), 0);
+    assert_eq!(*count, 2);
+    assert_eq!(step.handle().read().unwrap().pending_updates_of(ids[1]), 1);
+}
@@ -0,0 +1,108 @@
+//! The framing helpers foreign clients port, over generated payloads full of endmarks.
+
+use debug_monitor::protocol::framing::{decode_frames, encode_frame, escape, unescape, unescape_in_place, Endmark};
+
+const CASES: u64 = 500;
+
+/// Xorshift, so failing cases are reproducible from their seed.
+struct Generator(u64);
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+fn endmarks() -> Vec<Endmark> {
+    vec![
+        Endmark::new("<<end>>", "<<escaped end>>").unwrap(),
+        Endmark::new("\n", "\\n").unwrap(),
+        Endmark::new("§", "\\s").unwrap(),
+    ]
+}
+
+/// Pieces of the endmark and escape among other text, skipping payloads holding the escape itself,
+/// which the protocol can't tell apart from an escaped endmark.
+fn payload(generator: &mut Generator, endmark: &Endmark) -> String {
+    let (escape_start, escape_end) = endmark.escape().split_at(endmark.escape().len() / 2);
+    let fragments = [endmark.endmark(), escape_start, escape_end, "a", "é", "\\", " ", "{\"key\":[1,2]}", "<", ">"];
+    loop {
+        let payload = (0..generator.below(12)).map(|_| fragments[generator.below(fragments.len())]).collect::<String>();
+        if !payload.contains(endmark.escape()) {
+            return payload;
+        }
+    }
+}
+
+/// Feeds the stream in chunks of random length, as reads from a socket would return it.
+fn decode_in_chunks(generator: &mut Generator, stream: &str, endmark: &Endmark) -> Vec<String> {
+    let (mut buffer, mut decoded, mut start) = (String::new(), Vec::new(), 0);
+    while start < stream.len() {
+        let mut end = (start + 1 + generator.below(8)).min(stream.len());
+        while !stream.is_char_boundary(end) {
+            end += 1;
+        }
+        buffer.push_str(&stream[start..end]);
+        decoded.extend(decode_frames(&mut buffer, endmark));
+        start = end;
+    }
+    assert!(buffer.is_empty());
+    decoded
+}
+
+#[test]
+fn encoded_payloads_decode_back_in_order() {
+    for endmark in endmarks() {
+        for seed in 0..CASES {
+            let mut generator = Generator::new(seed);
+            let payloads = (0..generator.below(6)).map(|_| payload(&mut generator, &endmark)).collect::<Vec<_>>();
+            let frames = payloads.iter().map(|payload| encode_frame(payload, &endmark)).collect::<Vec<_>>();
+            for frame in &frames {
+                let body = frame.strip_suffix(endmark.endmark()).unwrap();
+                assert!(!body.contains(endmark.endmark()), "seed {seed}: {frame:?} holds an endmark before its end");
+            }
+            assert_eq!(decode_in_chunks(&mut generator, &frames.concat(), &endmark), payloads, "seed {seed}, endmark {endmark:?}");
+        }
+    }
+}
+
+#[test]
+fn unescaping_in_place_matches_unescaping() {
+    for endmark in endmarks() {
+        for seed in 0..CASES {
+            let mut generator = Generator::new(seed);
+            let payload = payload(&mut generator, &endmark);
+            let escaped = escape(&payload, &endmark).into_owned();
+            let mut in_place = escaped.clone();
+            unescape_in_place(&mut in_place, &endmark);
+            assert_eq!(in_place, unescape(&escaped, &endmark));
+            assert_eq!(in_place, payload);
+        }
+    }
+}
+
+#[test]
+fn partial_frames_stay_buffered() {
+    let endmark = Endmark::new("<<end>>", "<<escaped end>>").unwrap();
+    let mut buffer = "first<<end>>second<<en".to_string();
+    assert_eq!(decode_frames(&mut buffer, &endmark), vec!["first".to_string()]);
+    assert_eq!(buffer, "second<<en");
+    assert!(decode_frames(&mut buffer, &endmark).is_empty());
+    buffer.push_str("d>>");
+    assert_eq!(decode_frames(&mut buffer, &endmark), vec!["second".to_string()]);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn invalid_endmarks_are_rejected() {
+    assert!(Endmark::new("", "escape").is_err());
+    assert!(Endmark::new("end", "").is_err());
+    assert!(Endmark::new("<end>", "<end>escaped").is_err());
+}